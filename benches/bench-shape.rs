@@ -153,6 +153,7 @@ fn make_glyph(ch: char, glyph_index: u16) -> RawGlyph<()> {
         fake_italic: false,
         extra_data: (),
         variation: None,
+        feature_mask: None,
     }
 }
 
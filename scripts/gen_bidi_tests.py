@@ -0,0 +1,241 @@
+#!/usr/bin/env python3
+"""Generate the bidi test data in tests/bidi.
+
+Usage:
+
+    scripts/gen_bidi_tests.py <libfribidi> <libicuuc> <icu-version> <output-dir>
+
+The test cases are written to GeneratedBidiTest.txt and GeneratedBidiCharacterTest.txt, in the
+formats of BidiTest.txt and BidiCharacterTest.txt from https://www.unicode.org/Public/15.0.0/ucd/.
+They are not the Unicode conformance tests. The cases are every sequence of up to two bidi
+classes, random longer sequences, and random text that mixes strong types, numbers, brackets,
+explicit embeddings, and isolates. The expected results
+are those of GNU FriBidi. The random text is also resolved with ICU, and only the cases where
+the two implementations agree are kept, since each deviates from the algorithm in a few corner
+cases: FriBidi in some text with an unmatched isolate initiator, and ICU in text that it treats
+as entirely left-to-right, which it leaves at level 0 even when it contains Arabic numbers or
+isolates. For example, with FriBidi 1.0.8 and ICU 72:
+
+    scripts/gen_bidi_tests.py /usr/lib/x86_64-linux-gnu/libfribidi.so.0 \
+        /usr/lib/x86_64-linux-gnu/libicuuc.so.72 72 tests/bidi
+
+Paragraph separators only appear at the end of a test case, since FriBidi doesn't resolve text
+with more than one paragraph.
+"""
+
+import ctypes
+import itertools
+import os
+import random
+import sys
+
+# A character of each bidi class, the same as those used by tests/bidi.rs
+CLASS_CHARS = {
+    "L": 0x0061, "R": 0x05D0, "AL": 0x0627, "EN": 0x0030, "ES": 0x002B, "ET": 0x0024,
+    "AN": 0x0660, "CS": 0x002C, "NSM": 0x0300, "BN": 0x00AD, "B": 0x2029, "S": 0x0009,
+    "WS": 0x0020, "ON": 0x0021, "LRE": 0x202A, "LRO": 0x202D, "RLE": 0x202B, "RLO": 0x202E,
+    "PDF": 0x202C, "LRI": 0x2066, "RLI": 0x2067, "FSI": 0x2068, "PDI": 0x2069,
+}
+
+# Classes removed by rule X9, which have no level and are left out of the visual order
+REMOVED = {"LRE", "LRO", "RLE", "RLO", "PDF", "BN"}
+
+# Characters used in the BidiCharacterTest.txt cases
+TEXT_CHARS = [
+    # L, R, AL
+    0x0061, 0x0062, 0x05D0, 0x05D1, 0x0627, 0x0628,
+    # EN, ES, ET, AN, CS
+    0x0030, 0x0031, 0x06F1, 0x002B, 0x002D, 0x0024, 0x0025, 0x0660, 0x0661, 0x002C, 0x002E,
+    0x003A, 0x002F,
+    # NSM, BN, S, WS
+    0x0300, 0x05B0, 0x00AD, 0x0009, 0x0020, 0x2000,
+    # ON, including brackets and canonically equivalent brackets
+    0x0021, 0x0026, 0x00AB, 0x00BB, 0x0028, 0x0029, 0x005B, 0x005D, 0x007B, 0x007D, 0x2329,
+    0x232A, 0x3008, 0x3009,
+    # Explicit embeddings and isolates
+    0x202A, 0x202B, 0x202C, 0x202D, 0x202E, 0x2066, 0x2067, 0x2068, 0x2069,
+]
+
+# FriBidiParType values
+PAR_LTR = 0x110
+PAR_RTL = 0x111
+PAR_ON = 0x40
+
+UBIDI_DEFAULT_LTR = 0xFE
+
+
+class FriBidi:
+    def __init__(self, path):
+        lib = ctypes.CDLL(path)
+        self.get_bidi_types = lib.fribidi_get_bidi_types
+        self.get_bidi_types.restype = None
+        self.get_bracket_types = lib.fribidi_get_bracket_types
+        self.get_bracket_types.restype = None
+        self.get_par_embedding_levels = lib.fribidi_get_par_embedding_levels_ex
+        self.get_par_embedding_levels.restype = ctypes.c_int8
+        self.reorder_line = lib.fribidi_reorder_line
+        self.reorder_line.restype = ctypes.c_int8
+        types = self.types(list(CLASS_CHARS.values()))
+        self.classes = dict(zip(types, CLASS_CHARS.keys()))
+
+    def types(self, codes):
+        text = (ctypes.c_uint32 * len(codes))(*codes)
+        types = (ctypes.c_uint32 * len(codes))()
+        self.get_bidi_types(text, len(codes), types)
+        return list(types)
+
+    def resolve(self, codes, par_type):
+        """Returns the paragraph level, the level of each character, and the visual order."""
+        length = len(codes)
+        text = (ctypes.c_uint32 * length)(*codes)
+        types = (ctypes.c_uint32 * length)()
+        self.get_bidi_types(text, length, types)
+        brackets = (ctypes.c_uint32 * length)()
+        self.get_bracket_types(text, length, types, brackets)
+        base_dir = ctypes.c_uint32(par_type)
+        levels = (ctypes.c_int8 * length)()
+        result = self.get_par_embedding_levels(
+            types, brackets, length, ctypes.byref(base_dir), levels
+        )
+        assert result != 0
+        # Reordering the line also applies rule L1 to the levels
+        visual_map = (ctypes.c_int * length)(*range(length))
+        result = self.reorder_line(0, types, length, 0, base_dir, levels, None, visual_map)
+        assert result != 0
+        removed = [self.classes[types[i]] in REMOVED for i in range(length)]
+        result_levels = ["x" if removed[i] else str(levels[i]) for i in range(length)]
+        reorder = [str(i) for i in visual_map if not removed[i]]
+        return base_dir.value & 1, result_levels, reorder
+
+
+class Icu:
+    def __init__(self, path, version):
+        self.lib = ctypes.CDLL(path)
+        self.version = version
+        self.open = self.function("ubidi_open", ctypes.c_void_p)
+        self.close = self.function("ubidi_close", None, ctypes.c_void_p)
+        self.set_para = self.function(
+            "ubidi_setPara", None, ctypes.c_void_p, ctypes.c_void_p, ctypes.c_int32,
+            ctypes.c_uint8, ctypes.c_void_p, ctypes.POINTER(ctypes.c_int),
+        )
+        self.get_para_level = self.function("ubidi_getParaLevel", ctypes.c_uint8, ctypes.c_void_p)
+        self.get_levels = self.function(
+            "ubidi_getLevels", ctypes.POINTER(ctypes.c_uint8), ctypes.c_void_p,
+            ctypes.POINTER(ctypes.c_int),
+        )
+        self.get_visual_map = self.function(
+            "ubidi_getVisualMap", None, ctypes.c_void_p, ctypes.POINTER(ctypes.c_int32),
+            ctypes.POINTER(ctypes.c_int),
+        )
+
+    def function(self, name, restype, *argtypes):
+        function = getattr(self.lib, "%s_%s" % (name, self.version))
+        function.restype = restype
+        function.argtypes = argtypes
+        return function
+
+    def resolve(self, codes, level, removed):
+        """Returns the paragraph level, the level of each character, and the visual order."""
+        # The text is UTF-16, and all of the characters are in the BMP
+        text = (ctypes.c_uint16 * len(codes))(*codes)
+        bidi = self.open()
+        err = ctypes.c_int(0)
+        self.set_para(bidi, text, len(codes), level, None, ctypes.byref(err))
+        assert err.value <= 0, err.value
+        para_level = self.get_para_level(bidi)
+        levels = self.get_levels(bidi, ctypes.byref(err))
+        assert err.value <= 0, err.value
+        visual_map = (ctypes.c_int32 * len(codes))()
+        self.get_visual_map(bidi, visual_map, ctypes.byref(err))
+        assert err.value <= 0, err.value
+        result_levels = ["x" if removed[i] else str(levels[i]) for i in range(len(codes))]
+        reorder = [str(i) for i in visual_map if not removed[i]]
+        self.close(bidi)
+        return para_level, result_levels, reorder
+
+
+def class_sequences(rng):
+    classes = [c for c in CLASS_CHARS.keys() if c != "B"]
+    for length in range(1, 3):
+        for sequence in itertools.product(classes, repeat=length):
+            yield list(sequence)
+        for sequence in itertools.product(classes, repeat=length - 1):
+            yield list(sequence) + ["B"]
+    for _ in range(1500):
+        sequence = [rng.choice(classes) for _ in range(rng.randint(3, 8))]
+        if rng.random() < 0.1:
+            sequence.append("B")
+        yield sequence
+
+
+def write_bidi_test(fribidi, rng, out):
+    results = {}
+    for sequence in class_sequences(rng):
+        codes = [CLASS_CHARS[c] for c in sequence]
+        for bit, par_type in [(1, PAR_ON), (2, PAR_LTR), (4, PAR_RTL)]:
+            _, levels, reorder = fribidi.resolve(codes, par_type)
+            key = (" ".join(levels), " ".join(reorder))
+            cases = results.setdefault(key, {})
+            line = " ".join(sequence)
+            cases[line] = cases.get(line, 0) | bit
+
+    out.write(
+        "# Bidi test cases in the format of BidiTest.txt, generated by scripts/gen_bidi_tests.py\n"
+        "# from the results of GNU FriBidi. These are not the Unicode conformance tests.\n\n"
+    )
+    for (levels, reorder), cases in results.items():
+        out.write("@Levels: %s\n" % levels)
+        out.write("@Reorder: %s\n" % reorder)
+        for line, bitset in cases.items():
+            out.write("%s; %d\n" % (line, bitset))
+        out.write("\n")
+
+
+def texts(rng):
+    # Nesting beyond the maximum embedding depth and bracket pair stack size
+    yield [0x202B, 0x202A] * 64 + [0x0061, 0x05D0]
+    yield [0x2067, 0x2066] * 64 + [0x0061, 0x05D0] + [0x2069] * 130
+    yield [0x0028] * 70 + [0x05D0] + [0x0029] * 70
+    for _ in range(600):
+        yield [rng.choice(TEXT_CHARS) for _ in range(rng.randint(1, 16))]
+
+
+def write_bidi_character_test(fribidi, icu, rng, out):
+    out.write(
+        "# Bidi test cases in the format of BidiCharacterTest.txt, generated by\n"
+        "# scripts/gen_bidi_tests.py from the results of GNU FriBidi. These are not the Unicode\n"
+        "# conformance tests.\n\n"
+    )
+    for codes in texts(rng):
+        directions = [(0, PAR_LTR, 0), (1, PAR_RTL, 1), (2, PAR_ON, UBIDI_DEFAULT_LTR)]
+        for direction, par_type, icu_level in directions:
+            para_level, levels, reorder = fribidi.resolve(codes, par_type)
+            removed = [level == "x" for level in levels]
+            if icu.resolve(codes, icu_level, removed) != (para_level, levels, reorder):
+                continue
+            out.write(
+                "%s;%d;%d;%s;%s\n"
+                % (
+                    " ".join("%04X" % code for code in codes),
+                    direction,
+                    para_level,
+                    " ".join(levels),
+                    " ".join(reorder),
+                )
+            )
+
+
+def main():
+    if len(sys.argv) != 5:
+        sys.exit(__doc__)
+    fribidi = FriBidi(sys.argv[1])
+    icu = Icu(sys.argv[2], sys.argv[3])
+    output_dir = sys.argv[4]
+    with open(os.path.join(output_dir, "GeneratedBidiTest.txt"), "w") as out:
+        write_bidi_test(fribidi, random.Random(9), out)
+    with open(os.path.join(output_dir, "GeneratedBidiCharacterTest.txt"), "w") as out:
+        write_bidi_character_test(fribidi, icu, random.Random(9), out)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,663 @@
+#!/usr/bin/env python3
+"""Generate the Unicode data tables in src/unicode and src/scripts from the Unicode Character
+Database.
+
+Usage:
+
+    scripts/gen_unicode_data.py <table> <ucd-dir> > <output>
+
+where <table> is one of:
+
+    normalization  src/unicode/normalization_data.rs
+    bidi           src/unicode/bidi_data.rs
+    script         src/unicode/script_data.rs
+    use            src/scripts/universal_data.rs
+
+and <ucd-dir> is a directory containing the UCD files, such as the contents of
+https://www.unicode.org/Public/15.0.0/ucd/UCD.zip. The output is formatted with rustfmt, which
+must be on the PATH.
+"""
+
+import os
+import re
+import subprocess
+import sys
+
+HANGUL_SYLLABLES = range(0xAC00, 0xD7A4)
+
+# Long names of the Bidi_Class values, which may be used in @missing lines
+BIDI_CLASS_ALIASES = {
+    "Left_To_Right": "L",
+    "Right_To_Left": "R",
+    "Arabic_Letter": "AL",
+    "European_Number": "EN",
+    "European_Separator": "ES",
+    "European_Terminator": "ET",
+    "Arabic_Number": "AN",
+    "Common_Separator": "CS",
+    "Nonspacing_Mark": "NSM",
+    "Boundary_Neutral": "BN",
+    "Paragraph_Separator": "B",
+    "Segment_Separator": "S",
+    "White_Space": "WS",
+    "Other_Neutral": "ON",
+    "Left_To_Right_Embedding": "LRE",
+    "Left_To_Right_Override": "LRO",
+    "Right_To_Left_Embedding": "RLE",
+    "Right_To_Left_Override": "RLO",
+    "Pop_Directional_Format": "PDF",
+    "Left_To_Right_Isolate": "LRI",
+    "Right_To_Left_Isolate": "RLI",
+    "First_Strong_Isolate": "FSI",
+    "Pop_Directional_Isolate": "PDI",
+}
+
+
+def ucd_path(ucd_dir, name):
+    return os.path.join(ucd_dir, name)
+
+
+def unicode_version(ucd_dir, name):
+    """Returns the Unicode version from the first line of a UCD file, e.g. `# Scripts-15.0.0.txt`."""
+    with open(ucd_path(ucd_dir, name), encoding="utf-8") as f:
+        match = re.search(r"-(\d+\.\d+\.\d+)\.txt", f.readline())
+    if not match:
+        sys.exit("unable to determine the Unicode version from " + name)
+    return match.group(1)
+
+
+def parse_range(field):
+    if ".." in field:
+        start, end = field.split("..")
+        return int(start, 16), int(end, 16)
+    return int(field, 16), int(field, 16)
+
+
+def read_property_file(ucd_dir, name):
+    """Returns the @missing lines and the data lines of a UCD property file.
+
+    Each is a list of `(start, end, fields)`, where `fields` are the fields after the code points.
+    """
+    missing = []
+    lines = []
+    with open(ucd_path(ucd_dir, name), encoding="utf-8") as f:
+        for line in f:
+            match = re.match(r"#\s*@missing:\s*(.*)", line)
+            if match:
+                line = match.group(1)
+                target = missing
+            else:
+                target = lines
+            line = line.split("#", 1)[0].strip()
+            if not line:
+                continue
+            fields = [field.strip() for field in line.split(";")]
+            start, end = parse_range(fields[0])
+            target.append((start, end, fields[1:]))
+    return missing, lines
+
+
+def property_values(ucd_dir, name, aliases=None):
+    """Returns the value of a property for every code point, with @missing defaults applied."""
+    missing, lines = read_property_file(ucd_dir, name)
+    aliases = aliases or {}
+    values = [None] * 0x110000
+    # Later @missing lines take precedence over earlier ones
+    for start, end, fields in missing + lines:
+        value = aliases.get(fields[0], fields[0])
+        values[start : end + 1] = [value] * (end - start + 1)
+    return values
+
+
+def read_unicode_data(ucd_dir):
+    """Returns the fields of UnicodeData.txt for each code point, expanding First/Last ranges."""
+    data = {}
+    first = None
+    with open(ucd_path(ucd_dir, "UnicodeData.txt"), encoding="utf-8") as f:
+        for line in f:
+            fields = line.rstrip("\n").split(";")
+            cp = int(fields[0], 16)
+            if fields[1].endswith(", First>"):
+                first = cp
+            elif fields[1].endswith(", Last>"):
+                for range_cp in range(first, cp + 1):
+                    data[range_cp] = fields
+            else:
+                data[cp] = fields
+    return data
+
+
+def ranges(values, skip):
+    """Group runs of code points with the same value, omitting those with the value `skip`."""
+    result = []
+    for cp, value in enumerate(values):
+        if value == skip:
+            continue
+        if result and result[-1][1] == cp - 1 and result[-1][2] == value:
+            result[-1][1] = cp
+        else:
+            result.append([cp, cp, value])
+    return result
+
+
+def char(cp):
+    return "'\\u{%X}'" % cp
+
+
+def header(table, ucd_dir, version, description):
+    lines = [
+        "// DO NOT EDIT THIS FILE. IT WAS AUTOMATICALLY GENERATED BY:",
+        "//",
+        "//   scripts/gen_unicode_data.py %s %s" % (table, ucd_dir),
+        "//",
+        "// Unicode version: %s." % version,
+        "//",
+    ]
+    lines.extend("// " + line for line in description)
+    return "\n".join(lines) + "\n"
+
+
+def gen_normalization(ucd_dir):
+    version = unicode_version(ucd_dir, "DerivedNormalizationProps.txt")
+    exclusions = property_values(ucd_dir, "DerivedNormalizationProps.txt")
+    decompositions = []
+    for cp, fields in sorted(read_unicode_data(ucd_dir).items()):
+        mapping = fields[5]
+        if not mapping or mapping.startswith("<") or cp in HANGUL_SYLLABLES:
+            continue
+        decompositions.append((cp, [int(part, 16) for part in mapping.split()]))
+    compositions = sorted(
+        (tuple(parts), cp)
+        for cp, parts in decompositions
+        if len(parts) == 2 and exclusions[cp] != "Full_Composition_Exclusion"
+    )
+
+    out = [
+        header(
+            "normalization",
+            ucd_dir,
+            version,
+            [
+                "Canonical decomposition and composition mappings derived from the Unicode Character",
+                "Database. Precomposed Hangul syllables are excluded; they are decomposed and",
+                "composed algorithmically.",
+            ],
+        ),
+        "/// Single-level canonical decomposition mappings, sorted by character.",
+        "///",
+        "/// The second character is `None` for singleton decompositions.",
+        "pub(crate) const CANONICAL_DECOMPOSITION: &[(char, (char, Option<char>))] = &[",
+    ]
+    for cp, parts in decompositions:
+        second = "Some(%s)" % char(parts[1]) if len(parts) == 2 else "None"
+        out.append("    (%s, (%s, %s))," % (char(cp), char(parts[0]), second))
+    out += [
+        "];",
+        "",
+        "/// Primary composites, sorted by the pair of characters they are composed from.",
+        "///",
+        "/// Characters excluded from composition are not included.",
+        "pub(crate) const CANONICAL_COMPOSITION: &[((char, char), char)] = &[",
+    ]
+    for (first, second), cp in compositions:
+        out.append("    ((%s, %s), %s)," % (char(first), char(second), char(cp)))
+    out.append("];")
+    return out
+
+
+def gen_bidi(ucd_dir):
+    version = unicode_version(ucd_dir, "extracted/DerivedBidiClass.txt")
+    classes = property_values(ucd_dir, "extracted/DerivedBidiClass.txt", BIDI_CLASS_ALIASES)
+    _, mirroring = read_property_file(ucd_dir, "BidiMirroring.txt")
+    _, brackets = read_property_file(ucd_dir, "BidiBrackets.txt")
+
+    out = [
+        header(
+            "bidi",
+            ucd_dir,
+            version,
+            [
+                "Bidi_Class, Bidi_Mirroring_Glyph, Bidi_Paired_Bracket, and Bidi_Paired_Bracket_Type",
+                "data derived from the Unicode Character Database.",
+            ],
+        ),
+        "use crate::unicode::BidiClass::{self, *};",
+        "use crate::unicode::BracketType;",
+        "",
+        "/// Ranges of characters with the same `Bidi_Class`, sorted by their first character.",
+        "///",
+        "/// Characters that aren't in a range have the class `L`.",
+        "pub(crate) const BIDI_CLASS: &[(char, char, BidiClass)] = &[",
+    ]
+    for start, end, value in ranges(classes, "L"):
+        out.append("    (%s, %s, %s)," % (char(start), char(end), value))
+    out += [
+        "];",
+        "",
+        "/// Characters and their `Bidi_Mirroring_Glyph`, sorted by character.",
+        "pub(crate) const BIDI_MIRRORING_GLYPH: &[(char, char)] = &[",
+    ]
+    for cp, _, fields in sorted(mirroring):
+        out.append("    (%s, %s)," % (char(cp), char(int(fields[0], 16))))
+    out += [
+        "];",
+        "",
+        "/// Characters and their `Bidi_Paired_Bracket` and `Bidi_Paired_Bracket_Type`, sorted by",
+        "/// character.",
+        "pub(crate) const BIDI_PAIRED_BRACKET: &[(char, char, BracketType)] = &[",
+    ]
+    bracket_types = {"o": "BracketType::Open", "c": "BracketType::Close"}
+    for cp, _, fields in sorted(brackets):
+        pair = int(fields[0], 16)
+        out.append("    (%s, %s, %s)," % (char(cp), char(pair), bracket_types[fields[1]]))
+    out.append("];")
+    return out
+
+
+def script_names(ucd_dir):
+    """Returns the long name of each script, keyed by its short name."""
+    names = {}
+    with open(ucd_path(ucd_dir, "PropertyValueAliases.txt"), encoding="utf-8") as f:
+        for line in f:
+            fields = [field.strip() for field in line.split("#", 1)[0].split(";")]
+            if fields[0] == "sc":
+                names[fields[1]] = fields[2]
+    return names
+
+
+def variant(long_name):
+    return long_name.replace("_", "")
+
+
+def gen_script(ucd_dir):
+    version = unicode_version(ucd_dir, "Scripts.txt")
+    names = script_names(ucd_dir)
+    scripts = property_values(ucd_dir, "Scripts.txt")
+    extensions = property_values(ucd_dir, "ScriptExtensions.txt")
+    codes = {long_name: short_name for short_name, long_name in names.items()}
+
+    used = {value for value in scripts if value is not None}
+    used.update(
+        names[code] for value in extensions if value != "<script>" for code in value.split()
+    )
+    used.update(["Common", "Inherited", "Unknown"])
+    used = sorted(used, key=variant)
+
+    out = [
+        header(
+            "script",
+            ucd_dir,
+            version,
+            ["Script and Script_Extensions data derived from the Unicode Character Database."],
+        ),
+        "/// The Unicode [Script](https://www.unicode.org/reports/tr24/) property.",
+        "#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]",
+        "pub enum Script {",
+    ]
+    out += ["    %s," % variant(name) for name in used]
+    out += [
+        "}",
+        "",
+        "/// Every `Script`, in the order of their declaration.",
+        "pub(crate) const SCRIPTS: [Script; %d] = [" % len(used),
+    ]
+    out += ["    Script::%s," % variant(name) for name in used]
+    out += [
+        "];",
+        "",
+        "impl Script {",
+        "    /// The four letter [ISO 15924](https://www.unicode.org/iso15924/) code of the script.",
+        "    pub fn iso15924_code(self) -> &'static str {",
+        "        match self {",
+    ]
+    out += ['            Script::%s => "%s",' % (variant(name), codes[name]) for name in used]
+    out += [
+        "        }",
+        "    }",
+        "}",
+        "",
+        "/// Ranges of characters with the same `Script`, sorted by their first character.",
+        "///",
+        "/// Characters that aren't in a range have the script `Unknown`.",
+        "pub(crate) const SCRIPT: &[(char, char, Script)] = &[",
+    ]
+    for start, end, value in ranges(scripts, "Unknown"):
+        out.append("    (%s, %s, Script::%s)," % (char(start), char(end), variant(value)))
+    out += [
+        "];",
+        "",
+        "/// Ranges of characters whose `Script_Extensions` differ from their `Script`, sorted by their",
+        "/// first character.",
+        "pub(crate) const SCRIPT_EXTENSIONS: &[(char, char, &[Script])] = &[",
+    ]
+    for start, end, value in ranges(extensions, "<script>"):
+        values = ", ".join("Script::%s" % variant(names[code]) for code in value.split())
+        out.append("    (%s, %s, &[%s])," % (char(start), char(end), values))
+    out.append("];")
+    return out
+
+
+# The scripts shaped by the Universal Shaping Engine. Scripts with a shaper of their own, such as
+# Devanagari, Khmer, and Mongolian, aren't included.
+USE_SCRIPTS = [
+    "Ahom",
+    "Balinese",
+    "Batak",
+    "Bhaiksuki",
+    "Brahmi",
+    "Buginese",
+    "Buhid",
+    "Chakma",
+    "Cham",
+    "Dives_Akuru",
+    "Dogra",
+    "Grantha",
+    "Gunjala_Gondi",
+    "Hanunoo",
+    "Javanese",
+    "Kaithi",
+    "Kawi",
+    "Kayah_Li",
+    "Kharoshthi",
+    "Khojki",
+    "Khudawadi",
+    "Lepcha",
+    "Limbu",
+    "Mahajani",
+    "Makasar",
+    "Marchen",
+    "Masaram_Gondi",
+    "Meetei_Mayek",
+    "Modi",
+    "Multani",
+    "Nandinagari",
+    "Newa",
+    "Rejang",
+    "Saurashtra",
+    "Sharada",
+    "Siddham",
+    "Soyombo",
+    "Sundanese",
+    "Syloti_Nagri",
+    "Tagalog",
+    "Tagbanwa",
+    "Tai_Tham",
+    "Tai_Viet",
+    "Takri",
+    "Tirhuta",
+    "Zanabazar_Square",
+    # Scripts that join neighbouring clusters
+    "Adlam",
+    "Chorasmian",
+    "Hanifi_Rohingya",
+    "Mandaic",
+    "Manichaean",
+    "Nko",
+    "Old_Uyghur",
+    "Phags_Pa",
+    "Psalter_Pahlavi",
+    "Sogdian",
+    "Yezidi",
+]
+
+# Indic_Syllabic_Category overrides made by the USE specification, from Microsoft's
+# IndicSyllabicCategory-Additional.txt, for the characters of USE_SCRIPTS.
+USE_SYLLABIC_OVERRIDES = [
+    (0x07EB, 0x07F3, "Tone_Mark"),
+    (0x07FD, 0x07FD, "Tone_Mark"),
+    (0x0859, 0x085B, "Nukta"),
+    (0x193A, 0x193A, "Bindu"),
+    (0x1A7B, 0x1A7C, "Tone_Mark"),
+    (0x1A7F, 0x1A7F, "Tone_Mark"),
+    (0x1C36, 0x1C36, "Syllable_Modifier"),
+    (0xAA29, 0xAA29, "Bindu"),
+    (0xAAB1, 0xAAB1, "Vowel_Independent"),
+    (0xAABA, 0xAABA, "Vowel_Independent"),
+    (0xAABD, 0xAABD, "Vowel_Independent"),
+    (0x10A0D, 0x10A0D, "Bindu"),
+    (0x10A38, 0x10A3A, "Nukta"),
+    (0x10AE5, 0x10AE6, "Nukta"),
+    (0x10D24, 0x10D26, "Tone_Mark"),
+    (0x10D27, 0x10D27, "Gemination_Mark"),
+    (0x10D30, 0x10D39, "Number"),
+    (0x10EAB, 0x10EAC, "Vowel_Dependent"),
+    (0x10F46, 0x10F50, "Tone_Mark"),
+    (0x10F51, 0x10F54, "Number"),
+    (0x11134, 0x11134, "Gemination_Mark"),
+    (0x111DA, 0x111DA, "Consonant"),
+    (0x1E944, 0x1E94A, "Nukta"),
+    (0x1E94B, 0x1E94B, "Consonant"),
+    (0x1E950, 0x1E959, "Number"),
+]
+
+# Indic_Positional_Category overrides made by the USE specification, from Microsoft's
+# IndicPositionalCategory-Additional.txt, for the characters of USE_SCRIPTS. As in HarfBuzz, the
+# Vedic tone rings, which have no position in the UCD, are placed above, and the Ahom medial ra
+# before the base.
+USE_POSITIONAL_OVERRIDES = [
+    (0x07EB, 0x07F3, "Top"),
+    (0x07FD, 0x07FD, "Top"),
+    (0x0859, 0x085B, "Bottom"),
+    (0x1A18, 0x1A18, "Top"),
+    (0x1C29, 0x1C29, "Left"),
+    (0x1CF8, 0x1CF9, "Top"),
+    (0xA9BE, 0xA9BE, "Right"),
+    (0xA9BF, 0xA9BF, "Bottom"),
+    (0xAA35, 0xAA35, "Top"),
+    (0x10A0C, 0x10A0C, "Right"),
+    (0x10AE5, 0x10AE6, "Bottom"),
+    (0x10D24, 0x10D27, "Top"),
+    (0x10EAB, 0x10EAC, "Top"),
+    (0x10F46, 0x10F50, "Bottom"),
+    (0x11127, 0x11129, "Bottom"),
+    (0x1112D, 0x1112D, "Bottom"),
+    (0x11130, 0x11130, "Bottom"),
+    (0x1171E, 0x1171E, "Left"),
+    (0x11942, 0x11942, "Right"),
+    (0x1E944, 0x1E94A, "Top"),
+]
+
+# Characters outside USE_SCRIPTS that USE gives a category other than O
+USE_BASE_OTHER = [0x2015, 0x2022, 0x25FB, 0x25FC, 0x25FD, 0x25FE]
+USE_IGNORABLE = [0x034F] + list(range(0xFE00, 0xFE10)) + list(range(0xE0100, 0xE01F0))
+
+# The USE categories that are split by Indic_Positional_Category, and the suffix for each position
+USE_POSITIONS = {
+    "F": {"Top": "Abv", "Bottom": "Blw", "Right": "Pst"},
+    "M": {"Top": "Abv", "Bottom": "Blw", "Bottom_And_Left": "Blw", "Right": "Pst", "Left": "Pre"},
+    "CM": {"Top": "Abv", "Bottom": "Blw", "Overstruck": "Blw"},
+    "V": {
+        "Top": "Abv",
+        "Top_And_Bottom": "Abv",
+        "Top_And_Bottom_And_Right": "Abv",
+        "Top_And_Right": "Abv",
+        "Bottom": "Blw",
+        "Overstruck": "Blw",
+        "Bottom_And_Right": "Blw",
+        "Right": "Pst",
+        "Left": "Pre",
+        "Top_And_Left": "Pre",
+        "Top_And_Left_And_Right": "Pre",
+        "Left_And_Right": "Pre",
+    },
+    "VM": {"Top": "Abv", "Bottom": "Blw", "Overstruck": "Blw", "Right": "Pst", "Left": "Pre"},
+    "SM": {"Top": "Abv", "Bottom": "Blw"},
+    "FM": {"Top": "Abv", "Bottom": "Blw", "NA": "Pst"},
+}
+
+# The `UseCategory` variant of each USE category
+USE_VARIANTS = {
+    "O": "Other",
+    "B": "Base",
+    "N": "BaseNumber",
+    "GB": "BaseOther",
+    "CS": "ConsonantWithStacker",
+    "SUB": "ConsonantSubjoined",
+    "R": "Repha",
+    "H": "Halant",
+    "HN": "HalantNumber",
+    "HVM": "HalantOrVowelModifier",
+    "IS": "InvisibleStacker",
+    "Sk": "Sakot",
+    "FAbv": "ConsonantFinalAbove",
+    "FBlw": "ConsonantFinalBelow",
+    "FPst": "ConsonantFinalPost",
+    "MAbv": "ConsonantMedialAbove",
+    "MBlw": "ConsonantMedialBelow",
+    "MPst": "ConsonantMedialPost",
+    "MPre": "ConsonantMedialPre",
+    "CMAbv": "ConsonantModifierAbove",
+    "CMBlw": "ConsonantModifierBelow",
+    "VAbv": "VowelAbove",
+    "VBlw": "VowelBelow",
+    "VPst": "VowelPost",
+    "VPre": "VowelPre",
+    "VMAbv": "VowelModifierAbove",
+    "VMBlw": "VowelModifierBelow",
+    "VMPst": "VowelModifierPost",
+    "VMPre": "VowelModifierPre",
+    "SMAbv": "SymbolModifierAbove",
+    "SMBlw": "SymbolModifierBelow",
+    "FMAbv": "FinalModifierAbove",
+    "FMBlw": "FinalModifierBelow",
+    "FMPst": "FinalModifierPost",
+    "IGN": "Ignorable",
+}
+
+
+USE_BASES = ["Consonant", "Consonant_Head_Letter", "Number", "Tone_Letter", "Vowel_Independent"]
+USE_LETTER_BASES = [
+    "Avagraha",
+    "Bindu",
+    "Consonant_Final",
+    "Consonant_Medial",
+    "Consonant_Subjoined",
+    "Vowel",
+    "Vowel_Dependent",
+]
+
+
+def use_syllabic_category(cp, syllabic, gc, joining):
+    """Returns the USE category of a character, before it is split by position."""
+    if syllabic in ["Joiner", "Non_Joiner"] or cp in USE_IGNORABLE:
+        return "IGN"
+    if cp == 0x1A60:
+        return "Sk"
+    # Viramas that are also used as vowel modifiers
+    if cp in [0x11046, 0x1134D]:
+        return "HVM"
+    if 0x1B6B <= cp <= 0x1B73:
+        return "SM"
+    if syllabic == "Consonant_Placeholder" or cp in USE_BASE_OTHER:
+        return "GB"
+    if syllabic in USE_BASES or joining in ["C", "D", "L", "R"]:
+        return "B"
+    # Letters with the syllabic category of a mark are bases
+    if gc == "Lo" and syllabic in USE_LETTER_BASES:
+        return "B"
+    categories = {
+        "Virama": "H",
+        "Invisible_Stacker": "IS",
+        "Number_Joiner": "HN",
+        "Brahmi_Joining_Number": "N",
+        "Consonant_Preceding_Repha": "R",
+        "Consonant_Prefixed": "R",
+        "Consonant_With_Stacker": "CS",
+        "Consonant_Final": "F",
+        "Consonant_Succeeding_Repha": "F",
+        "Syllable_Modifier": "FM",
+        "Consonant_Final_Modifier": "FM",
+        "Consonant_Medial": "M",
+        "Consonant_Initial_Postfixed": "M",
+        "Nukta": "CM",
+        "Gemination_Mark": "CM",
+        "Consonant_Killer": "CM",
+        "Consonant_Subjoined": "SUB",
+        "Pure_Killer": "V",
+        "Vowel": "V",
+        "Vowel_Dependent": "V",
+        "Tone_Mark": "VM",
+        "Cantillation_Mark": "VM",
+        "Register_Shifter": "VM",
+        "Visarga": "VM",
+        "Bindu": "VM",
+    }
+    return categories.get(syllabic, "O")
+
+
+def gen_use(ucd_dir):
+    version = unicode_version(ucd_dir, "IndicSyllabicCategory.txt")
+    syllabic = property_values(ucd_dir, "IndicSyllabicCategory.txt")
+    positional = property_values(ucd_dir, "IndicPositionalCategory.txt")
+    joining = property_values(ucd_dir, "extracted/DerivedJoiningType.txt")
+    scripts = property_values(ucd_dir, "Scripts.txt")
+    unicode_data = read_unicode_data(ucd_dir)
+    for start, end, value in USE_SYLLABIC_OVERRIDES:
+        syllabic[start : end + 1] = [value] * (end - start + 1)
+    for start, end, value in USE_POSITIONAL_OVERRIDES:
+        positional[start : end + 1] = [value] * (end - start + 1)
+
+    categories = ["O"] * 0x110000
+    for cp, fields in unicode_data.items():
+        if scripts[cp] not in USE_SCRIPTS + ["Common", "Inherited"]:
+            continue
+        category = use_syllabic_category(cp, syllabic[cp], fields[2], joining[cp])
+        if category in USE_POSITIONS:
+            position = USE_POSITIONS[category].get(positional[cp])
+            if position is None:
+                sys.exit(
+                    "U+%04X has the USE category %s and no position for %s"
+                    % (cp, category, positional[cp])
+                )
+            category += position
+        categories[cp] = category
+
+    out = [
+        header(
+            "use",
+            ucd_dir,
+            version,
+            [
+                "Universal Shaping Engine categories derived from the Indic_Syllabic_Category,",
+                "Indic_Positional_Category, Joining_Type, and General_Category properties of the",
+                "characters of the scripts shaped by USE.",
+            ],
+        ),
+        "use crate::scripts::universal::UseCategory::{self, *};",
+        "",
+        "/// Ranges of characters with the same USE category, sorted by their first character.",
+        "///",
+        "/// Characters that aren't in a range have the category `Other`.",
+        "pub(crate) const USE_CATEGORY: &[(char, char, UseCategory)] = &[",
+    ]
+    for start, end, value in ranges(categories, "O"):
+        out.append("    (%s, %s, %s)," % (char(start), char(end), USE_VARIANTS[value]))
+    out.append("];")
+    return out
+
+
+GENERATORS = {
+    "normalization": gen_normalization,
+    "bidi": gen_bidi,
+    "script": gen_script,
+    "use": gen_use,
+}
+
+
+def main():
+    if len(sys.argv) != 3 or sys.argv[1] not in GENERATORS:
+        sys.exit(__doc__)
+    table, ucd_dir = sys.argv[1:]
+    source = "\n".join(GENERATORS[table](ucd_dir)) + "\n"
+    formatted = subprocess.run(
+        ["rustfmt", "--edition", "2018"],
+        input=source,
+        stdout=subprocess.PIPE,
+        check=True,
+        universal_newlines=True,
+    )
+    sys.stdout.write(formatted.stdout)
+
+
+if __name__ == "__main__":
+    main()
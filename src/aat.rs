@@ -0,0 +1,612 @@
+//! Apple Advanced Typography (AAT) shaping.
+//!
+//! Fonts made for macOS and iOS often have `morx`, `kerx`, and `trak` tables in place of `GSUB`
+//! and `GPOS`. [Font::shape](crate::font::Font::shape) applies `morx` when a font has no `GSUB`
+//! table, and `kerx` when it has no `GPOS` table. OpenType feature tags are mapped to AAT feature
+//! types and settings to select the `morx` subtables to apply.
+//!
+//! Only horizontal text is supported. Vertical, cross-stream, and variation subtables are
+//! skipped, as are `kerx` attachments using control points.
+//!
+//! <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6AATIntro.html>
+
+use std::convert::TryFrom;
+
+use crate::error::ParseError;
+use crate::gpos::{Info, Placement};
+use crate::gsub::{self, FeatureInfo, Features, GlyphData, GlyphOrigin, RawGlyph, RawGlyphFlags};
+use crate::layout::Anchor;
+use crate::tables::aat::{ExtendedStateTable, LookupTable, StateEntry, DELETED_GLYPH};
+use crate::tables::kerx::{
+    AnkrTable, AttachmentActionType, AttachmentKerning, ContextualKerning, KerxData, KerxTable,
+};
+use crate::tables::morx::{
+    Chain, ContextualSubtable, InsertionSubtable, LigatureSubtable, MorxTable, SubtableData,
+};
+use crate::tables::trak::TrakTable;
+use crate::tables::Fixed;
+use crate::tag;
+
+/// All AAT state tables use the same flag to stay on the current glyph.
+const DONT_ADVANCE: u16 = 0x4000;
+
+/// The maximum number of times a state table may stay on the same glyph.
+const MAX_DONT_ADVANCE: usize = 16;
+
+/// The maximum number of ligature components that are tracked.
+const MAX_LIGATURE_STACK: usize = 64;
+
+/// The maximum depth of the kerning stack of a contextual `kerx` subtable.
+const MAX_KERNING_STACK: usize = 8;
+
+/// OpenType features and the AAT feature type and settings that they map to.
+///
+/// The fields are the feature tag, the feature type, the setting to select when the feature is
+/// enabled, and the setting to select when it's not. Exclusive feature types have no setting to
+/// select when the feature isn't enabled.
+const FEATURE_MAPPINGS: &[(u32, u16, u16, Option<u16>)] = &[
+    (tag::AFRC, 11, 1, None),
+    (tag::C2SC, 38, 1, None),
+    (tag::CALT, 36, 0, Some(1)),
+    (tag!(b"case"), 33, 0, Some(1)),
+    (tag::CLIG, 1, 18, Some(19)),
+    (tag::DLIG, 1, 4, Some(5)),
+    (tag::FRAC, 11, 2, None),
+    (tag::HLIG, 1, 20, Some(21)),
+    (tag::LIGA, 1, 2, Some(3)),
+    (tag::LNUM, 21, 1, None),
+    (tag::ONUM, 21, 0, None),
+    (tag::ORDN, 10, 3, None),
+    (tag::PNUM, 6, 1, None),
+    (tag::RLIG, 1, 0, Some(1)),
+    (tag::SMCP, 37, 1, None),
+    (tag!(b"ss01"), 35, 2, Some(3)),
+    (tag!(b"ss02"), 35, 4, Some(5)),
+    (tag!(b"ss03"), 35, 6, Some(7)),
+    (tag!(b"ss04"), 35, 8, Some(9)),
+    (tag!(b"ss05"), 35, 10, Some(11)),
+    (tag!(b"ss06"), 35, 12, Some(13)),
+    (tag!(b"ss07"), 35, 14, Some(15)),
+    (tag!(b"ss08"), 35, 16, Some(17)),
+    (tag!(b"ss09"), 35, 18, Some(19)),
+    (tag!(b"ss10"), 35, 20, Some(21)),
+    (tag!(b"subs"), 10, 2, None),
+    (tag!(b"sups"), 10, 1, None),
+    (tag!(b"swsh"), 36, 2, Some(3)),
+    (tag::TNUM, 6, 0, None),
+    (tag::ZERO, 14, 4, Some(5)),
+];
+
+/// Apply the `morx` table to `glyphs`.
+///
+/// `features` select the subtables to apply, by way of the feature entries of each chain.
+/// Glyphs deleted by the subtables are removed and ligature components are merged into the
+/// ligature, as with `GSUB`.
+pub fn apply_morx<T: GlyphData>(
+    morx_table: &MorxTable<'_>,
+    features: &Features,
+    glyphs: &mut Vec<RawGlyph<T>>,
+) -> Result<(), ParseError> {
+    let feature_tags = match features {
+        Features::Custom(custom) => custom.iter().map(|info| info.feature_tag).collect(),
+        Features::Mask(mask) => mask
+            .iter()
+            .map(|FeatureInfo { feature_tag, .. }| feature_tag)
+            .collect::<Vec<_>>(),
+    };
+
+    for chain in &morx_table.chains {
+        let flags = chain_flags(chain, &feature_tags);
+        for subtable in &chain.subtables {
+            if subtable.sub_feature_flags & flags == 0 || !subtable.is_horizontal() {
+                continue;
+            }
+            if subtable.is_descending() {
+                glyphs.reverse();
+            }
+            let res = match &subtable.data {
+                SubtableData::Rearrangement(state_table) => rearrangement(state_table, glyphs),
+                SubtableData::Contextual(contextual) => contextual_substitution(contextual, glyphs),
+                SubtableData::Ligature(ligature) => ligature_substitution(ligature, glyphs),
+                SubtableData::Noncontextual(lookup_table) => {
+                    for glyph in glyphs.iter_mut() {
+                        substitute(glyph, lookup_table);
+                    }
+                    Ok(())
+                }
+                SubtableData::Insertion(insertion) => insertion_substitution(insertion, glyphs),
+            };
+            if subtable.is_descending() {
+                glyphs.reverse();
+            }
+            res?;
+        }
+    }
+
+    let mut i = 0;
+    while i < glyphs.len() {
+        if glyphs[i].glyph_index == DELETED_GLYPH {
+            gsub::delete_glyph(glyphs, i);
+        } else {
+            i += 1;
+        }
+    }
+    gsub::merge_unordered_clusters(glyphs);
+    Ok(())
+}
+
+/// Returns the subtable feature flags of `chain` with `feature_tags` enabled.
+fn chain_flags(chain: &Chain<'_>, feature_tags: &[u32]) -> u32 {
+    let mut flags = chain.default_flags;
+    for feature in chain.features.iter() {
+        let selected = FEATURE_MAPPINGS
+            .iter()
+            .filter(|&&(_, feature_type, _, _)| feature_type == feature.feature_type)
+            .any(|&(feature_tag, _, on, off)| {
+                let setting = if feature_tags.contains(&feature_tag) {
+                    Some(on)
+                } else {
+                    off
+                };
+                setting == Some(feature.feature_setting)
+            });
+        if selected {
+            flags = (flags & feature.disable_flags) | feature.enable_flags;
+        }
+    }
+    flags
+}
+
+trait StateMachineGlyphs {
+    fn len(&self) -> usize;
+
+    fn glyph_index(&self, i: usize) -> u16;
+}
+
+impl<T> StateMachineGlyphs for Vec<RawGlyph<T>> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn glyph_index(&self, i: usize) -> u16 {
+        self[i].glyph_index
+    }
+}
+
+impl StateMachineGlyphs for [Info] {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn glyph_index(&self, i: usize) -> u16 {
+        self[i].glyph.glyph_index
+    }
+}
+
+/// Run the state machine of `state_table` over `glyphs`, calling `transition` with each entry.
+///
+/// `transition` is passed the index of the current glyph, which it updates if it inserts glyphs
+/// before it. The index is equal to the number of glyphs at the end of the text.
+fn drive<G, F>(
+    state_table: &ExtendedStateTable<'_>,
+    glyphs: &mut G,
+    mut transition: F,
+) -> Result<(), ParseError>
+where
+    G: StateMachineGlyphs + ?Sized,
+    F: FnMut(&mut G, &StateEntry, &mut usize) -> Result<(), ParseError>,
+{
+    let mut state = ExtendedStateTable::START_OF_TEXT;
+    let mut dont_advance_count = 0;
+    let mut i = 0;
+    loop {
+        let end_of_text = i >= glyphs.len();
+        let class = if end_of_text {
+            ExtendedStateTable::END_OF_TEXT
+        } else {
+            state_table.glyph_class(glyphs.glyph_index(i))
+        };
+        let entry = state_table.entry(state, class)?;
+        transition(glyphs, &entry, &mut i)?;
+        state = entry.new_state;
+        if end_of_text {
+            return Ok(());
+        }
+        if entry.flags & DONT_ADVANCE == 0 || dont_advance_count == MAX_DONT_ADVANCE {
+            i += 1;
+            dont_advance_count = 0;
+        } else {
+            dont_advance_count += 1;
+        }
+    }
+}
+
+fn substitute<T>(glyph: &mut RawGlyph<T>, lookup_table: &LookupTable<'_>) {
+    if glyph.glyph_index == DELETED_GLYPH {
+        return;
+    }
+    if let Some(glyph_index) = lookup_table.value(glyph.glyph_index) {
+        glyph.glyph_index = glyph_index;
+        glyph.glyph_origin = GlyphOrigin::Direct;
+    }
+}
+
+fn rearrangement<T: GlyphData>(
+    state_table: &ExtendedStateTable<'_>,
+    glyphs: &mut Vec<RawGlyph<T>>,
+) -> Result<(), ParseError> {
+    const MARK_FIRST: u16 = 0x8000;
+    const MARK_LAST: u16 = 0x2000;
+    const VERB: u16 = 0x000F;
+
+    let mut start = 0;
+    let mut end = 0;
+    drive(state_table, glyphs, |glyphs, entry, &mut i| {
+        if entry.flags & MARK_FIRST != 0 {
+            start = i;
+        }
+        if entry.flags & MARK_LAST != 0 {
+            end = (i + 1).min(glyphs.len());
+        }
+        let verb = entry.flags & VERB;
+        if verb != 0 && start < end {
+            rearrange(&mut glyphs[start..end], verb);
+        }
+        Ok(())
+    })
+}
+
+/// Rearrange `glyphs` according to `verb`.
+///
+/// Each verb moves up to two glyphs from the start of the range (A and B) and up to two from the
+/// end (C and D), such as ABxCD => DCxAB for verb 15.
+fn rearrange<T: Clone>(glyphs: &mut [RawGlyph<T>], verb: u16) {
+    // The number of glyphs moved from the start in the high nibble and from the end in the low
+    // nibble, where 3 means two glyphs that are reversed
+    const MOVES: [u8; 16] = [
+        0x00, 0x10, 0x01, 0x11, 0x20, 0x30, 0x02, 0x03, 0x12, 0x13, 0x21, 0x31, 0x22, 0x32, 0x23,
+        0x33,
+    ];
+    let moves = MOVES[usize::from(verb & 0xF)];
+    let start_count = usize::from(moves >> 4).min(2);
+    let end_count = usize::from(moves & 0xF).min(2);
+    let len = glyphs.len();
+    if len < start_count + end_count {
+        return;
+    }
+    let mut first = glyphs[..start_count].to_vec();
+    let mut last = glyphs[len - end_count..].to_vec();
+    if moves >> 4 == 3 {
+        first.reverse();
+    }
+    if moves & 0xF == 3 {
+        last.reverse();
+    }
+    let middle = glyphs[start_count..len - end_count].to_vec();
+    for (glyph, moved) in glyphs
+        .iter_mut()
+        .zip(last.into_iter().chain(middle).chain(first))
+    {
+        *glyph = moved;
+    }
+}
+
+fn contextual_substitution<T: GlyphData>(
+    contextual: &ContextualSubtable<'_>,
+    glyphs: &mut Vec<RawGlyph<T>>,
+) -> Result<(), ParseError> {
+    let mut mark: Option<usize> = None;
+    drive(&contextual.state_table, glyphs, |glyphs, entry, &mut i| {
+        let [mark_index, current_index] = entry.data;
+        if mark_index != 0xFFFF {
+            if let Some(glyph) = mark.and_then(|mark| glyphs.get_mut(mark)) {
+                substitute(glyph, &contextual.substitution_table(mark_index)?);
+            }
+        }
+        if current_index != 0xFFFF {
+            // At the end of the text the current glyph is the last glyph
+            let current = i.min(glyphs.len().saturating_sub(1));
+            if let Some(glyph) = glyphs.get_mut(current) {
+                substitute(glyph, &contextual.substitution_table(current_index)?);
+            }
+        }
+        if entry.flags & ContextualSubtable::SET_MARK != 0 {
+            mark = Some(i);
+        }
+        Ok(())
+    })
+}
+
+fn ligature_substitution<T: GlyphData>(
+    ligature: &LigatureSubtable<'_>,
+    glyphs: &mut Vec<RawGlyph<T>>,
+) -> Result<(), ParseError> {
+    // The positions of the components of the ligature being formed
+    let mut components: Vec<usize> = Vec::new();
+    drive(&ligature.state_table, glyphs, |glyphs, entry, &mut i| {
+        if entry.flags & LigatureSubtable::SET_COMPONENT != 0 && i < glyphs.len() {
+            // Don't add the same glyph twice if the previous entry didn't advance
+            if components.last() == Some(&i) {
+                components.pop();
+            }
+            if components.len() == MAX_LIGATURE_STACK {
+                components.remove(0);
+            }
+            components.push(i);
+        }
+        if entry.flags & LigatureSubtable::PERFORM_ACTION == 0 {
+            return Ok(());
+        }
+
+        // Actions are performed on the components from last to first
+        let mut action_index = usize::from(entry.data[0]);
+        let mut cursor = components.len();
+        let mut ligature_index: u32 = 0;
+        loop {
+            if cursor == 0 {
+                components.clear();
+                break;
+            }
+            cursor -= 1;
+            let position = components[cursor];
+            let action = ligature.lig_action(action_index)?;
+            // The offset is a signed 30-bit value
+            let offset = ((action << 2) as i32) >> 2;
+            let component_index = i64::from(glyphs[position].glyph_index) + i64::from(offset);
+            let component = ligature.component(usize::try_from(component_index)?)?;
+            ligature_index = ligature_index.wrapping_add(u32::from(component));
+            if action & (LigatureSubtable::ACTION_STORE | LigatureSubtable::ACTION_LAST) != 0 {
+                let ligature_glyph = ligature.ligature(usize::try_from(ligature_index)?)?;
+                let end = components[components.len() - 1] + 1;
+                // Merge the components after this one into the ligature
+                let mut merged = Vec::new();
+                while components.len() - 1 > cursor {
+                    if let Some(component_position) = components.pop() {
+                        merged.push(component_position);
+                    }
+                }
+                for &component_position in merged.iter().rev() {
+                    let mut unicodes = std::mem::take(&mut glyphs[component_position].unicodes);
+                    glyphs[position].unicodes.append(&mut unicodes);
+                    glyphs[component_position].glyph_index = DELETED_GLYPH;
+                }
+                let cluster = glyphs[position..end]
+                    .iter()
+                    .map(|glyph| glyph.cluster)
+                    .min()
+                    .unwrap_or(glyphs[position].cluster);
+                for glyph in &mut glyphs[position..end] {
+                    glyph.cluster = cluster;
+                }
+                let glyph = &mut glyphs[position];
+                glyph.glyph_index = ligature_glyph;
+                glyph.glyph_origin = GlyphOrigin::Direct;
+                if !merged.is_empty() {
+                    glyph.flags.set(RawGlyphFlags::LIGATURE, true);
+                }
+            }
+            action_index += 1;
+            if action & LigatureSubtable::ACTION_LAST != 0 {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn insertion_substitution<T: GlyphData>(
+    insertion: &InsertionSubtable<'_>,
+    glyphs: &mut Vec<RawGlyph<T>>,
+) -> Result<(), ParseError> {
+    const CURRENT_INSERT_COUNT: u16 = 0x03E0;
+    const MARKED_INSERT_COUNT: u16 = 0x001F;
+
+    let mut mark = 0;
+    drive(&insertion.state_table, glyphs, |glyphs, entry, i| {
+        let [current_insert_index, marked_insert_index] = entry.data;
+        if marked_insert_index != 0xFFFF {
+            let count = usize::from(entry.flags & MARKED_INSERT_COUNT);
+            let inserted = insertion.insertion_glyphs(marked_insert_index, count)?;
+            let before = entry.flags & InsertionSubtable::MARKED_INSERT_BEFORE != 0;
+            let position = if before || mark >= glyphs.len() {
+                mark.min(glyphs.len())
+            } else {
+                mark + 1
+            };
+            insert_glyphs(glyphs, position, mark, &inserted);
+            if position <= *i {
+                *i += count;
+            }
+        }
+        if entry.flags & InsertionSubtable::SET_MARK != 0 {
+            mark = *i;
+        }
+        if current_insert_index != 0xFFFF {
+            let count = usize::from((entry.flags & CURRENT_INSERT_COUNT) >> 5);
+            let inserted = insertion.insertion_glyphs(current_insert_index, count)?;
+            let before = entry.flags & InsertionSubtable::CURRENT_INSERT_BEFORE != 0;
+            let position = if before || *i >= glyphs.len() {
+                *i
+            } else {
+                *i + 1
+            };
+            insert_glyphs(glyphs, position, *i, &inserted);
+            // Newly inserted glyphs are processed next if the entry doesn't advance
+            if entry.flags & DONT_ADVANCE == 0 {
+                *i += count;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Insert `inserted` glyphs at `position`, in the cluster of the glyph at `reference`.
+fn insert_glyphs<T: GlyphData>(
+    glyphs: &mut Vec<RawGlyph<T>>,
+    position: usize,
+    reference: usize,
+    inserted: &[u16],
+) {
+    let template = match glyphs.get(reference).or_else(|| glyphs.last()).cloned() {
+        Some(template) => template,
+        None => return,
+    };
+    let new_glyphs = inserted.iter().map(|&glyph_index| RawGlyph {
+        unicodes: Default::default(),
+        glyph_index,
+        liga_component_pos: 0,
+        glyph_origin: GlyphOrigin::Direct,
+        cluster: template.cluster,
+        flags: RawGlyphFlags::empty(),
+        variation: None,
+        feature_mask: template.feature_mask,
+        extra_data: template.extra_data.clone(),
+    });
+    glyphs.splice(position..position, new_glyphs);
+}
+
+/// Apply the `kerx` table to `infos`.
+///
+/// Kerning subtables are only applied if `kerning` is `true`. Marks are skipped by the pair
+/// kerning subtables. The `ankr` table is needed for attachment subtables that use anchor
+/// points.
+pub fn apply_kerx(
+    kerx_table: &KerxTable<'_>,
+    opt_ankr_table: Option<&AnkrTable<'_>>,
+    kerning: bool,
+    infos: &mut [Info],
+) -> Result<(), ParseError> {
+    for subtable in &kerx_table.subtables {
+        if !subtable.is_horizontal() || subtable.is_cross_stream() || subtable.is_variation() {
+            continue;
+        }
+        match &subtable.data {
+            KerxData::Format1(contextual) if kerning => {
+                let tuple_count = usize::try_from(subtable.tuple_count.max(1))?;
+                contextual_kerning(contextual, tuple_count, infos)?;
+            }
+            KerxData::Format4(attachment) => {
+                attachment_positioning(attachment, opt_ankr_table, infos)?
+            }
+            KerxData::Format1(_) => {}
+            data if kerning => {
+                let indices = (0..infos.len())
+                    .filter(|&i| !infos[i].is_mark)
+                    .collect::<Vec<_>>();
+                for pair in indices.windows(2) {
+                    let (left, right) = (pair[0], pair[1]);
+                    let value = data.pair_value(
+                        infos[left].glyph.glyph_index,
+                        infos[right].glyph.glyph_index,
+                    );
+                    if let Some(value) = value {
+                        infos[left].kerning = infos[left].kerning.saturating_add(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn contextual_kerning(
+    contextual: &ContextualKerning<'_>,
+    tuple_count: usize,
+    infos: &mut [Info],
+) -> Result<(), ParseError> {
+    let mut stack: Vec<usize> = Vec::with_capacity(MAX_KERNING_STACK);
+    drive(&contextual.state_table, infos, |infos, entry, &mut i| {
+        if entry.flags & ContextualKerning::RESET != 0 {
+            stack.clear();
+        }
+        if entry.flags & ContextualKerning::PUSH != 0 {
+            if stack.len() == MAX_KERNING_STACK {
+                stack.clear();
+            }
+            stack.push(i);
+        }
+        let value_index = entry.data[0];
+        if value_index == 0xFFFF {
+            return Ok(());
+        }
+        let mut index = usize::from(value_index);
+        while let Some(position) = stack.pop() {
+            let value = contextual.value(index)?;
+            index += tuple_count;
+            if position >= infos.len() {
+                continue;
+            }
+            // The value moves the popped glyph and those after it, so it's added to the advance
+            // of the glyph before
+            if let Some(previous) = position.checked_sub(1) {
+                infos[previous].kerning = infos[previous].kerning.saturating_add(value & !1);
+            }
+            if value & 1 != 0 {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn attachment_positioning(
+    attachment: &AttachmentKerning<'_>,
+    opt_ankr_table: Option<&AnkrTable<'_>>,
+    infos: &mut [Info],
+) -> Result<(), ParseError> {
+    let mut mark: Option<usize> = None;
+    drive(&attachment.state_table, infos, |infos, entry, &mut i| {
+        let action_index = entry.data[0];
+        if let Some(mark) = mark.filter(|_| action_index != 0xFFFF && i < infos.len()) {
+            let action = attachment.action(action_index)?;
+            let anchors = match attachment.action_type {
+                AttachmentActionType::ControlPoints => None,
+                AttachmentActionType::AnchorPoints => opt_ankr_table.and_then(|ankr_table| {
+                    let mark_glyph = infos[mark].glyph.glyph_index;
+                    let current_glyph = infos[i].glyph.glyph_index;
+                    Some((
+                        ankr_table.anchor(mark_glyph, action[0] as u16)?,
+                        ankr_table.anchor(current_glyph, action[1] as u16)?,
+                    ))
+                }),
+                AttachmentActionType::Coordinates => Some((
+                    Anchor {
+                        x: action[0],
+                        y: action[1],
+                    },
+                    Anchor {
+                        x: action[2],
+                        y: action[3],
+                    },
+                )),
+            };
+            if let Some((mark_anchor, current_anchor)) = anchors {
+                infos[i].placement = Placement::MarkAnchor(mark, mark_anchor, current_anchor);
+            }
+        }
+        if entry.flags & AttachmentKerning::MARK != 0 {
+            mark = Some(i);
+        }
+        Ok(())
+    })
+}
+
+/// Apply the normal track of the `trak` table to `infos`.
+///
+/// Tracking depends on the point size the text is set at, so it isn't applied by
+/// [Font::shape](crate::font::Font::shape). The tracking value is added to the advance of each
+/// glyph other than marks.
+pub fn apply_trak(trak_table: &TrakTable, point_size: f32, infos: &mut [Info]) {
+    let tracking = trak_table
+        .horizontal
+        .as_ref()
+        .and_then(|track_data| track_data.tracking(Fixed::from(0.), point_size));
+    let tracking = match tracking.map(i16::try_from) {
+        Some(Ok(tracking)) if tracking != 0 => tracking,
+        _ => return,
+    };
+    for info in infos.iter_mut().filter(|info| !info.is_mark) {
+        info.kerning = info.kerning.saturating_add(tracking);
+    }
+}
@@ -0,0 +1,1036 @@
+//! Bidirectional text support.
+//!
+//! Implements the [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/)
+//! (UAX #9). [paragraphs] splits text into paragraphs, and a [Paragraph] resolves the embedding
+//! level of each character of a paragraph and splits it into directional runs. [shape_paragraph]
+//! splits each run by script and shapes it with [Font::shape](crate::font::Font::shape),
+//! mirroring characters in right-to-left runs, and returns the runs in visual order.
+//!
+//! ```
+//! use allsorts::bidi::Paragraph;
+//! use allsorts::glyph_position::TextDirection;
+//!
+//! let text = "abc \u{05D0}\u{05D1} def";
+//! let paragraph = Paragraph::new(text, None);
+//! assert_eq!(paragraph.direction(), TextDirection::LeftToRight);
+//! let runs = paragraph
+//!     .visual_runs(0..text.len())
+//!     .into_iter()
+//!     .map(|run| (&text[run.range], run.level))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(runs, vec![("abc ", 0), ("\u{05D0}\u{05D1}", 1), (" def", 0)]);
+//! ```
+
+use std::ops::Range;
+
+use tinyvec::tiny_vec;
+
+use crate::error::ShapingError;
+use crate::font::{Font, MatchingPresentation};
+use crate::glyph_position::TextDirection;
+use crate::gpos::Info;
+use crate::gsub::{FeatureMask, Features, GlyphOrigin, RawGlyphFlags};
+use crate::itemize::{font_script_tag, opentype_script_tag, script_runs};
+use crate::tables::variable_fonts::fvar::Tuple;
+use crate::tables::FontTableProvider;
+use crate::unicode::BidiClass::{self, *};
+use crate::unicode::{bidi_class, bidi_mirroring_glyph, bidi_paired_bracket, BracketType, Script};
+
+/// The maximum explicit embedding level.
+const MAX_DEPTH: u8 = 125;
+
+/// The maximum number of nested brackets tracked when identifying bracket pairs.
+const MAX_BRACKET_DEPTH: usize = 63;
+
+/// A paragraph of text with resolved embedding levels.
+///
+/// Even levels are left-to-right and odd levels are right-to-left. Text containing several
+/// paragraphs should be split with [paragraphs] first, so that each paragraph has its own
+/// paragraph embedding level.
+#[derive(Debug, Clone)]
+pub struct Paragraph<'a> {
+    text: &'a str,
+    base_level: u8,
+    /// The byte offset of each character in `text`.
+    indices: Vec<usize>,
+    /// The `Bidi_Class` of each character.
+    classes: Vec<BidiClass>,
+    /// The resolved embedding level of each character, before rule L1 is applied.
+    levels: Vec<u8>,
+}
+
+/// A run of text with the same embedding level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidiRun {
+    /// The byte range of the run in the paragraph text.
+    pub range: Range<usize>,
+    /// The embedding level of the run.
+    pub level: u8,
+}
+
+/// A run of text that has been shaped.
+#[derive(Debug, Clone)]
+pub struct ShapedRun {
+    /// The byte range of the run in the paragraph text.
+    pub range: Range<usize>,
+    /// The embedding level of the run.
+    pub level: u8,
+    /// The script of the run.
+    pub script: Script,
+    /// The OpenType script tag the run was shaped with.
+    pub script_tag: u32,
+    /// The shaped glyphs of the run in logical order. The clusters of the glyphs are byte
+    /// offsets into the paragraph text.
+    pub infos: Vec<Info>,
+}
+
+impl BidiRun {
+    /// The direction of the run.
+    pub fn direction(&self) -> TextDirection {
+        level_direction(self.level)
+    }
+}
+
+impl ShapedRun {
+    /// The direction of the run. The glyphs of right-to-left runs are laid out from right to
+    /// left, starting with the first glyph in logical order.
+    pub fn direction(&self) -> TextDirection {
+        level_direction(self.level)
+    }
+}
+
+impl<'a> Paragraph<'a> {
+    /// Resolve the embedding levels of `text`, a single paragraph as returned by [paragraphs].
+    ///
+    /// If `direction` is `None` the paragraph direction is determined from the first strong
+    /// character in the text, defaulting to left-to-right.
+    ///
+    /// Rule P1 isn't applied. A paragraph separator within `text` terminates all explicit
+    /// embeddings and isolates, as in the Unicode conformance test data, but the whole text
+    /// shares one paragraph embedding level.
+    pub fn new(text: &'a str, direction: Option<TextDirection>) -> Paragraph<'a> {
+        let (indices, classes): (Vec<_>, Vec<_>) = text
+            .char_indices()
+            .map(|(i, ch)| (i, bidi_class(ch)))
+            .unzip();
+        let chars = text.chars().collect::<Vec<_>>();
+
+        // P2, P3
+        let base_level = match direction {
+            Some(TextDirection::LeftToRight) => 0,
+            Some(TextDirection::RightToLeft) => 1,
+            None => match first_strong(&classes, 0, false) {
+                Some(R) => 1,
+                _ => 0,
+            },
+        };
+
+        let matching_pdi = matching_pdis(&classes);
+        let mut types = classes.clone();
+        let mut levels = explicit_levels(base_level, &classes, &matching_pdi, &mut types);
+
+        // X9
+        let removed = |class: BidiClass| matches!(class, RLE | LRE | RLO | LRO | PDF | BN);
+        let kept = (0..classes.len())
+            .filter(|&i| !removed(classes[i]))
+            .collect::<Vec<_>>();
+
+        // The start and end of each sequence are determined from the explicit levels, before the
+        // levels of earlier sequences are resolved
+        let explicit = levels.clone();
+        for sequence in isolating_run_sequences(&kept, &explicit, &classes, &matching_pdi) {
+            let level = explicit[sequence[0]];
+            let first = sequence[0];
+            let last = sequence[sequence.len() - 1];
+
+            // X10
+            let prev_level = match kept.partition_point(|&i| i < first) {
+                0 => base_level,
+                k => explicit[kept[k - 1]],
+            };
+            let next_level = if matches!(classes[last], LRI | RLI | FSI) {
+                base_level
+            } else {
+                kept.get(kept.partition_point(|&i| i <= last))
+                    .map_or(base_level, |&i| explicit[i])
+            };
+            let sos = level_class(level.max(prev_level));
+            let eos = level_class(level.max(next_level));
+
+            resolve_weak_types(&sequence, sos, &mut types);
+            resolve_paired_brackets(&sequence, level, sos, &chars, &classes, &mut types);
+            resolve_neutral_types(&sequence, level, sos, eos, &mut types);
+
+            // I1, I2
+            for &i in &sequence {
+                levels[i] += match (levels[i] & 1, types[i]) {
+                    (0, R) => 1,
+                    (0, AN) | (0, EN) => 2,
+                    (1, L) | (1, EN) | (1, AN) => 1,
+                    _ => 0,
+                };
+            }
+        }
+
+        // Characters removed by X9 take the level of the preceding character so that they don't
+        // break up runs
+        let mut prev_level = base_level;
+        for i in 0..classes.len() {
+            if removed(classes[i]) {
+                levels[i] = prev_level;
+            }
+            prev_level = levels[i];
+        }
+
+        Paragraph {
+            text,
+            base_level,
+            indices,
+            classes,
+            levels,
+        }
+    }
+
+    /// The text of the paragraph.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The paragraph embedding level.
+    pub fn base_level(&self) -> u8 {
+        self.base_level
+    }
+
+    /// The paragraph direction.
+    pub fn direction(&self) -> TextDirection {
+        level_direction(self.base_level)
+    }
+
+    /// The runs of the paragraph in logical order, with the whole paragraph treated as a single
+    /// line.
+    pub fn runs(&self) -> Vec<BidiRun> {
+        self.line_runs(0..self.text.len())
+    }
+
+    /// The runs of a line of the paragraph in visual order, from left to right.
+    ///
+    /// `line` is the byte range of the line in the paragraph text. Whitespace at the end of the
+    /// line takes the paragraph embedding level.
+    pub fn visual_runs(&self, line: Range<usize>) -> Vec<BidiRun> {
+        let mut runs = self.line_runs(line);
+
+        // L2
+        let max_level = runs.iter().map(|run| run.level).max().unwrap_or(0);
+        let min_odd_level = runs
+            .iter()
+            .map(|run| run.level | 1)
+            .min()
+            .unwrap_or(MAX_DEPTH + 1);
+        for level in (min_odd_level..=max_level).rev() {
+            let mut i = 0;
+            while i < runs.len() {
+                if runs[i].level >= level {
+                    let start = i;
+                    while i < runs.len() && runs[i].level >= level {
+                        i += 1;
+                    }
+                    runs[start..i].reverse();
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        runs
+    }
+
+    /// Split a line into runs of the same level, in logical order, after applying rule L1.
+    fn line_runs(&self, line: Range<usize>) -> Vec<BidiRun> {
+        let start = self.indices.partition_point(|&i| i < line.start);
+        let end = self.indices.partition_point(|&i| i < line.end);
+        let mut levels = self.levels[start..end].to_vec();
+        let classes = &self.classes[start..end];
+
+        // L1
+        let mut reset_from = levels.len();
+        for i in (0..levels.len()).rev() {
+            match classes[i] {
+                S | B => {
+                    levels[i] = self.base_level;
+                    reset_from = i;
+                }
+                WS | LRI | RLI | FSI | PDI | RLE | LRE | RLO | LRO | PDF | BN
+                    if reset_from == i + 1 =>
+                {
+                    levels[i] = self.base_level;
+                    reset_from = i;
+                }
+                _ => reset_from = usize::MAX,
+            }
+        }
+
+        let mut runs: Vec<BidiRun> = Vec::new();
+        for (i, &level) in levels.iter().enumerate() {
+            let char_start = self.indices[start + i];
+            let char_end = self
+                .indices
+                .get(start + i + 1)
+                .copied()
+                .unwrap_or(self.text.len());
+            match runs.last_mut() {
+                Some(run) if run.level == level => run.range.end = char_end,
+                _ => runs.push(BidiRun {
+                    range: char_start..char_end,
+                    level,
+                }),
+            }
+        }
+        runs
+    }
+}
+
+/// Split `text` into paragraphs (P1), returning the byte range of each.
+///
+/// A paragraph separator is kept with the paragraph before it, and a carriage return followed by
+/// a line feed is treated as a single separator.
+pub fn paragraphs(text: &str) -> Vec<Range<usize>> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if bidi_class(ch) != B {
+            continue;
+        }
+        if ch == '\r' && chars.peek().map(|&(_, next)| next) == Some('\n') {
+            continue;
+        }
+        let end = index + ch.len_utf8();
+        paragraphs.push(start..end);
+        start = end;
+    }
+    if start < text.len() {
+        paragraphs.push(start..text.len());
+    }
+    paragraphs
+}
+
+/// Shape a paragraph of bidirectional text.
+///
+/// The paragraph is treated as a single line. Each directional run is split into runs of a single
+/// script with [script_runs], which are mapped with
+/// [Font::map_glyphs](crate::font::Font::map_glyphs) and shaped with
+/// [Font::shape](crate::font::Font::shape) using the script tag returned by [font_script_tag].
+/// The shaped runs are returned in visual order.
+///
+/// In right-to-left runs characters with a `Bidi_Mirroring_Glyph` are replaced by their mirror
+/// image when the font has a glyph for it. The `rtlm` feature is enabled for the other glyphs of
+/// the run if `features` is a `Features::Mask`.
+///
+/// Like `Font::shape` this continues in the face of errors, returning the first error
+/// encountered along with the runs shaped.
+pub fn shape_paragraph<T: FontTableProvider>(
+    font: &mut Font<T>,
+    paragraph: &Paragraph<'_>,
+    opt_lang_tag: Option<u32>,
+    features: &Features,
+    tuple: Option<Tuple<'_>>,
+    kerning: bool,
+) -> Result<Vec<ShapedRun>, (ShapingError, Vec<ShapedRun>)> {
+    let mut err: Option<ShapingError> = None;
+    let mut shaped_runs = Vec::new();
+    let script_runs = script_runs(paragraph.text);
+    for bidi_run in paragraph.visual_runs(0..paragraph.text.len()) {
+        let rtl = bidi_run.direction() == TextDirection::RightToLeft;
+        // The parts of the directional run in each script, in visual order
+        let mut runs = script_runs
+            .iter()
+            .filter_map(|script_run| {
+                let start = script_run.range.start.max(bidi_run.range.start);
+                let end = script_run.range.end.min(bidi_run.range.end);
+                (start < end).then_some((start..end, script_run.script))
+            })
+            .collect::<Vec<_>>();
+        if rtl {
+            runs.reverse();
+        }
+
+        for (range, script) in runs {
+            let script_tag = match font_script_tag(font, script) {
+                Ok(script_tag) => script_tag,
+                Err(error) => {
+                    err.get_or_insert(error.into());
+                    opentype_script_tag(script)
+                }
+            };
+            let mut glyphs = font.map_glyphs(
+                &paragraph.text[range.clone()],
+                script_tag,
+                MatchingPresentation::NotRequired,
+            );
+            for glyph in glyphs.iter_mut() {
+                glyph.cluster += range.start;
+                if let (true, GlyphOrigin::Char(ch)) = (rtl, glyph.glyph_origin) {
+                    if let Some(mirrored) = bidi_mirroring_glyph(ch) {
+                        let (glyph_index, _) = font.lookup_glyph_index(
+                            mirrored,
+                            MatchingPresentation::NotRequired,
+                            None,
+                        );
+                        if glyph_index != 0 {
+                            glyph.glyph_index = glyph_index;
+                            glyph.unicodes = tiny_vec![[char; 1] => mirrored];
+                            glyph.glyph_origin = GlyphOrigin::Char(mirrored);
+                            glyph.flags.insert(RawGlyphFlags::MIRRORED);
+                        }
+                    }
+                }
+            }
+
+            let run_features = match features {
+                Features::Mask(mask) if rtl => Features::Mask(*mask | FeatureMask::RTLM),
+                _ => features.clone(),
+            };
+            let infos = match font.shape(
+                glyphs,
+                script_tag,
+                opt_lang_tag,
+                &run_features,
+                tuple,
+                kerning,
+            ) {
+                Ok(infos) => infos,
+                Err((error, infos)) => {
+                    err.get_or_insert(error);
+                    infos
+                }
+            };
+            shaped_runs.push(ShapedRun {
+                range,
+                level: bidi_run.level,
+                script,
+                script_tag,
+                infos,
+            });
+        }
+    }
+
+    match err {
+        Some(err) => Err((err, shaped_runs)),
+        None => Ok(shaped_runs),
+    }
+}
+
+fn level_direction(level: u8) -> TextDirection {
+    if level & 1 == 0 {
+        TextDirection::LeftToRight
+    } else {
+        TextDirection::RightToLeft
+    }
+}
+
+fn level_class(level: u8) -> BidiClass {
+    if level & 1 == 0 {
+        L
+    } else {
+        R
+    }
+}
+
+/// Find the class, `L` or `R`, of the first strong character from `start`, skipping characters
+/// between an isolate initiator and its matching PDI (P2).
+///
+/// If `isolate` is true the search stops at the PDI that ends the isolate containing `start`.
+fn first_strong(classes: &[BidiClass], start: usize, isolate: bool) -> Option<BidiClass> {
+    let mut depth = 0;
+    for &class in &classes[start..] {
+        match class {
+            L if depth == 0 => return Some(L),
+            R | AL if depth == 0 => return Some(R),
+            LRI | RLI | FSI => depth += 1,
+            PDI if depth > 0 => depth -= 1,
+            PDI if isolate => return None,
+            B => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Find the matching PDI of each isolate initiator (BD9).
+fn matching_pdis(classes: &[BidiClass]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; classes.len()];
+    let mut stack = Vec::new();
+    for (i, &class) in classes.iter().enumerate() {
+        match class {
+            LRI | RLI | FSI => stack.push(i),
+            PDI => {
+                if let Some(initiator) = stack.pop() {
+                    matching[initiator] = Some(i);
+                }
+            }
+            B => stack.clear(),
+            _ => {}
+        }
+    }
+    matching
+}
+
+#[derive(Copy, Clone)]
+struct DirectionalStatus {
+    level: u8,
+    override_class: Option<BidiClass>,
+    isolate: bool,
+}
+
+/// Determine the explicit embedding levels (X1–X8), applying directional overrides to `types`.
+fn explicit_levels(
+    base_level: u8,
+    classes: &[BidiClass],
+    matching_pdi: &[Option<usize>],
+    types: &mut [BidiClass],
+) -> Vec<u8> {
+    let initial = DirectionalStatus {
+        level: base_level,
+        override_class: None,
+        isolate: false,
+    };
+    let mut stack = vec![initial];
+    let mut overflow_isolates = 0;
+    let mut overflow_embeddings = 0;
+    let mut valid_isolates = 0;
+    let mut levels = vec![base_level; classes.len()];
+
+    for (i, &class) in classes.iter().enumerate() {
+        let current = *stack.last().unwrap();
+        levels[i] = current.level;
+        match class {
+            RLE | LRE | RLO | LRO => {
+                let level = next_level(current.level, matches!(class, RLE | RLO));
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    stack.push(DirectionalStatus {
+                        level,
+                        override_class: match class {
+                            RLO => Some(R),
+                            LRO => Some(L),
+                            _ => None,
+                        },
+                        isolate: false,
+                    });
+                } else if overflow_isolates == 0 {
+                    overflow_embeddings += 1;
+                }
+            }
+            RLI | LRI | FSI => {
+                if let Some(override_class) = current.override_class {
+                    types[i] = override_class;
+                }
+                let rtl = match class {
+                    RLI => true,
+                    LRI => false,
+                    _ => {
+                        let end = matching_pdi[i].unwrap_or(classes.len());
+                        first_strong(&classes[..end], i + 1, true) == Some(R)
+                    }
+                };
+                let level = next_level(current.level, rtl);
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    valid_isolates += 1;
+                    stack.push(DirectionalStatus {
+                        level,
+                        override_class: None,
+                        isolate: true,
+                    });
+                } else {
+                    overflow_isolates += 1;
+                }
+            }
+            PDI => {
+                if overflow_isolates > 0 {
+                    overflow_isolates -= 1;
+                } else if valid_isolates > 0 {
+                    overflow_embeddings = 0;
+                    while stack.last().map_or(false, |status| !status.isolate) {
+                        stack.pop();
+                    }
+                    stack.pop();
+                    valid_isolates -= 1;
+                }
+                let current = *stack.last().unwrap();
+                levels[i] = current.level;
+                if let Some(override_class) = current.override_class {
+                    types[i] = override_class;
+                }
+            }
+            PDF => {
+                if overflow_isolates > 0 {
+                    // Ignored
+                } else if overflow_embeddings > 0 {
+                    overflow_embeddings -= 1;
+                } else if !current.isolate && stack.len() >= 2 {
+                    stack.pop();
+                }
+            }
+            B => {
+                // X8
+                levels[i] = base_level;
+                stack.truncate(1);
+                overflow_isolates = 0;
+                overflow_embeddings = 0;
+                valid_isolates = 0;
+            }
+            BN => {}
+            _ => {
+                if let Some(override_class) = current.override_class {
+                    types[i] = override_class;
+                }
+            }
+        }
+    }
+    levels
+}
+
+/// The least odd (`rtl`) or even level greater than `level`.
+fn next_level(level: u8, rtl: bool) -> u8 {
+    match (rtl, level & 1) {
+        (true, 0) | (false, 1) => level + 1,
+        _ => level + 2,
+    }
+}
+
+/// Split the characters that weren't removed by X9 into isolating run sequences (BD13).
+fn isolating_run_sequences(
+    kept: &[usize],
+    levels: &[u8],
+    classes: &[BidiClass],
+    matching_pdi: &[Option<usize>],
+) -> Vec<Vec<usize>> {
+    // Level runs (BD7)
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    for &i in kept {
+        match runs.last_mut() {
+            Some(run) if levels[run[0]] == levels[i] => run.push(i),
+            _ => runs.push(vec![i]),
+        }
+    }
+
+    let mut matched_pdi = vec![false; classes.len()];
+    for &pdi in matching_pdi.iter().flatten() {
+        matched_pdi[pdi] = true;
+    }
+
+    let mut sequences = Vec::new();
+    for run in &runs {
+        if matched_pdi[run[0]] {
+            // Part of the sequence of the matching isolate initiator
+            continue;
+        }
+        let mut sequence = run.clone();
+        // Only isolate initiators have a matching PDI
+        while let Some(pdi) = sequence.last().and_then(|&last| matching_pdi[last]) {
+            match runs.binary_search_by_key(&pdi, |run| run[0]) {
+                Ok(index) => sequence.extend_from_slice(&runs[index]),
+                Err(_) => break,
+            }
+        }
+        sequences.push(sequence);
+    }
+    sequences
+}
+
+/// Apply rules W1–W7 to an isolating run sequence.
+fn resolve_weak_types(sequence: &[usize], sos: BidiClass, types: &mut [BidiClass]) {
+    // W1
+    let mut prev = sos;
+    for &i in sequence {
+        if types[i] == NSM {
+            types[i] = match prev {
+                LRI | RLI | FSI | PDI => ON,
+                _ => prev,
+            };
+        }
+        prev = types[i];
+    }
+
+    // W2, W3
+    let mut last_strong = sos;
+    for &i in sequence {
+        match types[i] {
+            L | R | AL => last_strong = types[i],
+            EN if last_strong == AL => types[i] = AN,
+            _ => {}
+        }
+    }
+    for &i in sequence {
+        if types[i] == AL {
+            types[i] = R;
+        }
+    }
+
+    // W4
+    for k in 1..sequence.len().saturating_sub(1) {
+        let (prev, next) = (types[sequence[k - 1]], types[sequence[k + 1]]);
+        match types[sequence[k]] {
+            ES if prev == EN && next == EN => types[sequence[k]] = EN,
+            CS if prev == next && matches!(prev, EN | AN) => types[sequence[k]] = prev,
+            _ => {}
+        }
+    }
+
+    // W5
+    let mut k = 0;
+    while k < sequence.len() {
+        if types[sequence[k]] == ET {
+            let start = k;
+            while k < sequence.len() && types[sequence[k]] == ET {
+                k += 1;
+            }
+            let before = start > 0 && types[sequence[start - 1]] == EN;
+            let after = k < sequence.len() && types[sequence[k]] == EN;
+            if before || after {
+                for &i in &sequence[start..k] {
+                    types[i] = EN;
+                }
+            }
+        } else {
+            k += 1;
+        }
+    }
+
+    // W6
+    for &i in sequence {
+        if matches!(types[i], ES | ET | CS) {
+            types[i] = ON;
+        }
+    }
+
+    // W7
+    let mut last_strong = sos;
+    for &i in sequence {
+        match types[i] {
+            L | R => last_strong = types[i],
+            EN if last_strong == L => types[i] = L,
+            _ => {}
+        }
+    }
+}
+
+/// Apply rule N0 to the bracket pairs of an isolating run sequence.
+fn resolve_paired_brackets(
+    sequence: &[usize],
+    level: u8,
+    sos: BidiClass,
+    chars: &[char],
+    classes: &[BidiClass],
+    types: &mut [BidiClass],
+) {
+    // BD16
+    let mut pairs = Vec::new();
+    let mut stack: Vec<(char, usize)> = Vec::new();
+    for (k, &i) in sequence.iter().enumerate() {
+        if types[i] != ON {
+            continue;
+        }
+        match bidi_paired_bracket(chars[i]) {
+            Some((closing, BracketType::Open)) => {
+                if stack.len() == MAX_BRACKET_DEPTH {
+                    break;
+                }
+                stack.push((canonical_bracket(closing), k));
+            }
+            Some((_, BracketType::Close)) => {
+                let closing = canonical_bracket(chars[i]);
+                if let Some(depth) = stack.iter().rposition(|&(ch, _)| ch == closing) {
+                    pairs.push((stack[depth].1, k));
+                    stack.truncate(depth);
+                }
+            }
+            None => {}
+        }
+    }
+    pairs.sort_unstable();
+
+    let embedding = level_class(level);
+    let strong = |class: BidiClass| match class {
+        L => Some(L),
+        R | AN | EN => Some(R),
+        _ => None,
+    };
+    for (open, close) in pairs {
+        let mut found_embedding = false;
+        let mut found_opposite = false;
+        for &i in &sequence[open + 1..close] {
+            match strong(types[i]) {
+                Some(class) if class == embedding => found_embedding = true,
+                Some(_) => found_opposite = true,
+                None => {}
+            }
+        }
+
+        let class = if found_embedding {
+            embedding
+        } else if found_opposite {
+            let context = sequence[..open]
+                .iter()
+                .rev()
+                .find_map(|&i| strong(types[i]))
+                .unwrap_or(sos);
+            if context != embedding {
+                context
+            } else {
+                embedding
+            }
+        } else {
+            continue;
+        };
+
+        for k in [open, close] {
+            types[sequence[k]] = class;
+            // Nonspacing marks following a bracket take its type
+            for &i in &sequence[k + 1..] {
+                if classes[i] != NSM {
+                    break;
+                }
+                types[i] = class;
+            }
+        }
+    }
+}
+
+/// Map brackets to their canonical equivalents so that they match when paired.
+fn canonical_bracket(ch: char) -> char {
+    match ch {
+        '\u{2329}' => '\u{3008}',
+        '\u{232A}' => '\u{3009}',
+        _ => ch,
+    }
+}
+
+/// Apply rules N1 and N2 to an isolating run sequence.
+fn resolve_neutral_types(
+    sequence: &[usize],
+    level: u8,
+    sos: BidiClass,
+    eos: BidiClass,
+    types: &mut [BidiClass],
+) {
+    let is_neutral = |class: BidiClass| matches!(class, B | S | WS | ON | LRI | RLI | FSI | PDI);
+    let strong = |class: BidiClass| match class {
+        L => L,
+        _ => R,
+    };
+    let embedding = level_class(level);
+
+    let mut k = 0;
+    while k < sequence.len() {
+        if !is_neutral(types[sequence[k]]) {
+            k += 1;
+            continue;
+        }
+        let start = k;
+        while k < sequence.len() && is_neutral(types[sequence[k]]) {
+            k += 1;
+        }
+        let before = match start {
+            0 => sos,
+            _ => strong(types[sequence[start - 1]]),
+        };
+        let after = match sequence.get(k) {
+            Some(&i) => strong(types[i]),
+            None => eos,
+        };
+        let class = if before == after { before } else { embedding };
+        for &i in &sequence[start..k] {
+            types[i] = class;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(text: &str, direction: Option<TextDirection>) -> Vec<u8> {
+        let paragraph = Paragraph::new(text, direction);
+        let mut levels = vec![0; text.chars().count()];
+        for run in paragraph.runs() {
+            let start = text[..run.range.start].chars().count();
+            let len = text[run.range].chars().count();
+            levels[start..start + len].fill(run.level);
+        }
+        levels
+    }
+
+    fn visual(text: &str, direction: Option<TextDirection>) -> Vec<&str> {
+        Paragraph::new(text, direction)
+            .visual_runs(0..text.len())
+            .into_iter()
+            .map(|run| &text[run.range])
+            .collect()
+    }
+
+    const ALEF: char = '\u{05D0}';
+
+    #[test]
+    fn test_paragraph_direction() {
+        assert_eq!(
+            Paragraph::new("abc", None).direction(),
+            TextDirection::LeftToRight
+        );
+        assert_eq!(
+            Paragraph::new("\u{05D0}bc", None).direction(),
+            TextDirection::RightToLeft
+        );
+        assert_eq!(
+            Paragraph::new("123 \u{0627}", None).direction(),
+            TextDirection::RightToLeft
+        );
+        // Characters in isolates are skipped
+        assert_eq!(
+            Paragraph::new("\u{2067}abc\u{2069}\u{05D0}", None).direction(),
+            TextDirection::RightToLeft
+        );
+        assert_eq!(
+            Paragraph::new("", None).direction(),
+            TextDirection::LeftToRight
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        // European numbers in right-to-left text
+        assert_eq!(levels("\u{05D0} 123", None), vec![1, 1, 2, 2, 2]);
+        // European numbers after Arabic letters become Arabic numbers
+        assert_eq!(
+            levels("\u{0627}12", Some(TextDirection::LeftToRight)),
+            vec![1, 2, 2]
+        );
+        // Number separators and terminators
+        assert_eq!(
+            levels("\u{05D0} 1,000$", None),
+            vec![1, 1, 2, 2, 2, 2, 2, 2]
+        );
+        assert_eq!(levels("abc 12", None), vec![0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_neutrals() {
+        assert_eq!(
+            levels(
+                &format!("{} {}", ALEF, ALEF),
+                Some(TextDirection::LeftToRight)
+            ),
+            vec![1, 1, 1]
+        );
+        assert_eq!(
+            levels(&format!("a {}", ALEF), Some(TextDirection::LeftToRight)),
+            vec![0, 0, 1]
+        );
+        assert_eq!(
+            levels(&format!("a {}", ALEF), Some(TextDirection::RightToLeft)),
+            vec![2, 1, 1]
+        );
+        // Trailing whitespace takes the paragraph level
+        assert_eq!(
+            levels(&format!("a{} ", ALEF), Some(TextDirection::LeftToRight)),
+            vec![0, 1, 0]
+        );
+    }
+
+    #[test]
+    fn test_brackets() {
+        // The brackets take the embedding direction as they enclose an L
+        assert_eq!(levels("\u{05D0}(a)\u{05D1}", None), vec![1, 1, 2, 1, 1]);
+        // The brackets take the direction of the preceding context
+        assert_eq!(
+            levels("a(\u{05D0})b", Some(TextDirection::RightToLeft)),
+            vec![2, 1, 1, 1, 2]
+        );
+        assert_eq!(visual("\u{05D0}(\u{05D1}", None), vec!["\u{05D0}(\u{05D1}"]);
+    }
+
+    #[test]
+    fn test_explicit_embeddings() {
+        // RLE ... PDF
+        assert_eq!(levels("a\u{202B}b\u{202C}c", None), vec![0, 0, 2, 2, 0]);
+        // RLO overrides the direction of the characters. The PDF at the end of the line takes the
+        // paragraph level
+        assert_eq!(levels("\u{202E}ab\u{202C}", None), vec![0, 1, 1, 0]);
+        // RLI ... PDI
+        assert_eq!(levels("a\u{2067}b\u{2069}c", None), vec![0, 0, 2, 0, 0]);
+        // FSI ... PDI takes the direction of its first strong character
+        assert_eq!(
+            levels("a\u{2068}\u{05D0}\u{2069}b", None),
+            vec![0, 0, 1, 0, 0]
+        );
+        // An unmatched PDF is ignored
+        assert_eq!(levels("a\u{202C}b", None), vec![0, 0, 0]);
+        // The start of the sequence after the embedding is right-to-left from the explicit level
+        // of the first "1", not left-to-right from its resolved level
+        assert_eq!(
+            levels("\u{202B}1\u{202C}(1", Some(TextDirection::LeftToRight)),
+            vec![0, 2, 2, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let text = "\u{05D0}\u{2029}abc\r\ndef\n\n\u{05D1}";
+        let ranges = paragraphs(text);
+        let texts = ranges
+            .iter()
+            .map(|range| &text[range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec!["\u{05D0}\u{2029}", "abc\r\n", "def\n", "\n", "\u{05D1}"]
+        );
+        // Each paragraph has its own direction
+        let directions = ranges
+            .into_iter()
+            .map(|range| Paragraph::new(&text[range], None).direction())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            directions,
+            vec![
+                TextDirection::RightToLeft,
+                TextDirection::LeftToRight,
+                TextDirection::LeftToRight,
+                TextDirection::LeftToRight,
+                TextDirection::RightToLeft,
+            ]
+        );
+        // Without splitting the text shares the direction of the first paragraph
+        assert_eq!(levels("\u{05D0}\u{2029}abc", None), vec![1, 1, 2, 2, 2]);
+        assert!(paragraphs("").is_empty());
+    }
+
+    #[test]
+    fn test_visual_runs() {
+        assert_eq!(
+            visual("abc \u{05D0}\u{05D1} def", None),
+            vec!["abc ", "\u{05D0}\u{05D1}", " def"]
+        );
+        assert_eq!(
+            visual("\u{05D0} abc \u{05D1}", None),
+            vec![" \u{05D1}", "abc", "\u{05D0} "]
+        );
+        // Numbers keep their left-to-right order in right-to-left text
+        assert_eq!(
+            visual("\u{05D0} 12 \u{05D1}", None),
+            vec![" \u{05D1}", "12", "\u{05D0} "]
+        );
+    }
+
+    #[test]
+    fn test_line_runs() {
+        let text = "\u{05D0} abc \u{05D1} def";
+        let paragraph = Paragraph::new(text, None);
+        let line = 0..text.find('\u{05D1}').unwrap();
+        let runs = paragraph
+            .visual_runs(line)
+            .into_iter()
+            .map(|run| (&text[run.range], run.level))
+            .collect::<Vec<_>>();
+        // The trailing space of the line takes the paragraph level
+        assert_eq!(runs, vec![(" ", 1), ("abc", 2), ("\u{05D0} ", 1)]);
+    }
+}
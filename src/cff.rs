@@ -31,7 +31,7 @@ use crate::tables::variable_fonts::{ItemVariationStore, OwnedTuple};
 use crate::variations::VariationError;
 use cff2::BlendOperand;
 use charstring::{ArgumentsStack, GlyphId, TryNumFrom};
-pub use subset::SubsetCFF;
+pub use subset::{SubsetCFF, SubsetCFF2};
 
 /// Maximum number of operands in Top DICT, Font DICTs, Private DICTs and CharStrings.
 ///
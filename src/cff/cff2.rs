@@ -15,7 +15,7 @@ use super::{
     STANDARD_STRINGS,
 };
 use crate::binary::read::{ReadArrayCow, ReadBinary, ReadCtxt, ReadScope};
-use crate::binary::write::{WriteBinary, WriteBinaryDep, WriteBuffer, WriteContext};
+use crate::binary::write::{WriteBinary, WriteBinaryDep, WriteBuffer, WriteContext, WriteCounter};
 use crate::binary::{I16Be, U16Be, U32Be, U8};
 use crate::cff::charstring::{
     operator, ArgumentsStack, CharStringConversionError, CharStringVisitor,
@@ -286,6 +286,7 @@ impl<'a> CFF2<'a> {
                 &self.char_strings_index,
                 &self.global_subr_index,
                 glyph_id,
+                None,
             )?;
             used_global_subrs.extend(subrs.global_subr_used);
             if !subrs.local_subr_used.is_empty() {
@@ -1036,25 +1037,25 @@ impl<'a> WriteBinary for CFF2<'a> {
         // Write out Private DICTs and Local Subr INDEXes so the offsets can be updated
         let mut font_dicts = Vec::with_capacity(cff2.fonts.len());
         for font in &cff2.fonts {
-            // Write Local Subr INDEX
-            let local_subr_offset = if let Some(local_subr_index) = &font.local_subr_index {
-                let local_subr_offset = i32::try_from(ctxt.bytes_written())?;
-                MaybeOwnedIndex::write32(ctxt, local_subr_index)?;
-                Some(local_subr_offset)
-            } else {
-                None
-            };
-
-            // Write Private DICT
+            // Write Private DICT followed by the Local Subr INDEX, if present.
             // NOTE: The offset to local subrs INDEX is from the start of the Private DICT.
             let private_dict_offset = i32::try_from(ctxt.bytes_written())?;
+            let private_dict_len = PrivateDict::write_dep(
+                &mut WriteCounter::new(),
+                &font.private_dict,
+                DictDelta::new(),
+            )?;
             let mut private_dict_deltas = DictDelta::new();
-            if let Some(local_subr_offset) = local_subr_offset {
-                private_dict_deltas
-                    .push_offset(Operator::Subrs, local_subr_offset - private_dict_offset);
+            if font.local_subr_index.is_some() {
+                private_dict_deltas.push_offset(Operator::Subrs, i32::try_from(private_dict_len)?);
+            }
+            let written_len =
+                PrivateDict::write_dep(ctxt, &font.private_dict, private_dict_deltas)?;
+            assert_eq!(written_len, private_dict_len);
+            let private_dict_len = i32::try_from(private_dict_len)?;
+            if let Some(local_subr_index) = &font.local_subr_index {
+                MaybeOwnedIndex::write32(ctxt, local_subr_index)?;
             }
-            PrivateDict::write_dep(ctxt, &font.private_dict, private_dict_deltas)?;
-            let private_dict_len = i32::try_from(ctxt.bytes_written())? - private_dict_offset;
 
             // Build and write out new Font DICT
             let mut font_dict = FontDict::new();
@@ -1085,7 +1086,11 @@ impl<'a> WriteBinary for CFF2<'a> {
                 Operator::VStore,
                 vec![Operand::Offset(i32::try_from(ctxt.bytes_written())?)],
             );
-            ItemVariationStore::write(ctxt, variation_store)?;
+            // The VariationStore data is prefixed with its length
+            let mut variation_store_buffer = WriteBuffer::new();
+            ItemVariationStore::write(&mut variation_store_buffer, variation_store)?;
+            U16Be::write(ctxt, u16::try_from(variation_store_buffer.len())?)?;
+            ctxt.write_bytes(variation_store_buffer.bytes())?;
         }
 
         // Now that the offsets are known, write out the Top DICT
@@ -43,6 +43,8 @@ pub(crate) type GlyphId = u16;
 pub(crate) struct UsedSubrs {
     pub(crate) global_subr_used: FxHashSet<usize>,
     pub(crate) local_subr_used: FxHashSet<usize>,
+    /// The base and accent glyphs, if the CharString is an accented character made with `seac`.
+    pub(crate) seac_glyphs: Option<(GlyphId, GlyphId)>,
 }
 
 /// Context for traversing a CFF CharString.
@@ -56,7 +58,8 @@ pub struct CharStringVisitorContext<'a, 'data> {
     width_parsed: bool,
     stems_len: u32,
     has_endchar: bool,
-    has_seac: bool,
+    /// The base and accent glyphs of an accented character, once `seac` has been processed.
+    seac_glyphs: Option<(GlyphId, GlyphId)>,
     seen_blend: bool,
     vsindex: Option<u16>,
     scalars: Option<Vec<Option<f32>>>,
@@ -196,6 +199,7 @@ pub(crate) fn char_string_used_subrs<'a, 'data>(
     char_strings_index: &'a MaybeOwnedIndex<'data>,
     global_subr_index: &'a MaybeOwnedIndex<'data>,
     glyph_id: GlyphId,
+    variable: Option<VariableCharStringVisitorContext<'a, 'data>>,
 ) -> Result<UsedSubrs, CFFError> {
     let (local_subrs, max_len) = match font {
         CFFFont::CFF(font) => match &font.data {
@@ -210,14 +214,15 @@ pub(crate) fn char_string_used_subrs<'a, 'data>(
         char_strings_index,
         local_subrs,
         global_subr_index,
-        // This function should not be called on variable CFF2 fonts. If the CFF2 font is variable
-        // it should be instanced first.
-        None,
+        // Required for variable CFF2 fonts. The instance doesn't affect which subroutines are
+        // called, so the default instance can be used.
+        variable,
     );
 
     let mut used_subrs = UsedSubrs {
         global_subr_used: FxHashSet::default(),
         local_subr_used: FxHashSet::default(),
+        seac_glyphs: None,
     };
 
     let mut stack = ArgumentsStack {
@@ -232,6 +237,7 @@ pub(crate) fn char_string_used_subrs<'a, 'data>(
     if matches!(font, CFFFont::CFF(_)) && !ctx.has_endchar {
         return Err(CFFError::MissingEndChar);
     }
+    used_subrs.seac_glyphs = ctx.seac_glyphs;
 
     Ok(used_subrs)
 }
@@ -648,7 +654,7 @@ impl<'a, 'data> CharStringVisitorContext<'a, 'data> {
             width_parsed: false,
             stems_len: 0,
             has_endchar: false,
-            has_seac: false,
+            seac_glyphs: None,
             seen_blend: false,
             vsindex: None,
             scalars: None,
@@ -770,7 +776,7 @@ impl<'a, 'data> CharStringVisitorContext<'a, 'data> {
                         return Err(CFFError::NoLocalSubroutines.into());
                     }
 
-                    if self.has_endchar && !self.has_seac {
+                    if self.has_endchar && self.seac_glyphs.is_none() {
                         if s.bytes_available() {
                             return Err(CFFError::DataAfterEndChar.into());
                         }
@@ -824,7 +830,7 @@ impl<'a, 'data> CharStringVisitorContext<'a, 'data> {
                                     self.width_parsed = true;
                                 }
 
-                                self.has_seac = true;
+                                self.seac_glyphs = Some((base_char, accent_char));
 
                                 let base_char_string = self
                                     .char_strings_index
@@ -1002,7 +1008,7 @@ impl<'a, 'data> CharStringVisitorContext<'a, 'data> {
                     self.visit_impl(font, char_string, depth + 1, stack, visitor)?;
                     visitor.exit_subr()?;
 
-                    if self.has_endchar && !self.has_seac {
+                    if self.has_endchar && self.seac_glyphs.is_none() {
                         if s.bytes_available() {
                             return Err(CFFError::DataAfterEndChar.into());
                         }
@@ -3,6 +3,9 @@ use std::mem;
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use super::cff2::{self, CFF2};
+use super::charstring::VariableCharStringVisitorContext;
+use super::CFFError;
 use super::{
     owned, CFFFont, CFFVariant, CIDData, Charset, CustomCharset, DictDelta, FDSelect, Font,
     FontDict, MaybeOwnedIndex, Operand, Operator, ParseError, Range, ADOBE, CFF, IDENTITY,
@@ -11,6 +14,7 @@ use super::{
 use crate::binary::read::ReadArrayCow;
 use crate::binary::write::{WriteBinaryDep, WriteBuffer};
 use crate::subset::{SubsetError, SubsetGlyphs};
+use crate::tables::variable_fonts::OwnedTuple;
 
 /// A subset CFF font.
 pub struct SubsetCFF<'a> {
@@ -31,6 +35,11 @@ impl<'a> SubsetCFF<'a> {
             old_to_new_id,
         }
     }
+
+    /// The subset CFF table.
+    pub(crate) fn table(&self) -> &CFF<'a> {
+        &self.table
+    }
 }
 
 impl<'a> From<SubsetCFF<'a>> for CFF<'a> {
@@ -53,10 +62,45 @@ impl<'a> SubsetGlyphs for SubsetCFF<'a> {
     }
 }
 
+/// A subset CFF2 font.
+pub struct SubsetCFF2<'a> {
+    table: CFF2<'a>,
+    new_to_old_id: Vec<u16>,
+    old_to_new_id: FxHashMap<u16, u16>,
+}
+
+impl<'a> SubsetCFF2<'a> {
+    /// The subset CFF2 table.
+    pub(crate) fn table(&self) -> &CFF2<'a> {
+        &self.table
+    }
+}
+
+impl<'a> From<SubsetCFF2<'a>> for CFF2<'a> {
+    fn from(subset: SubsetCFF2<'a>) -> CFF2<'a> {
+        subset.table
+    }
+}
+
+impl<'a> SubsetGlyphs for SubsetCFF2<'a> {
+    fn len(&self) -> usize {
+        self.new_to_old_id.len()
+    }
+
+    fn old_id(&self, new_id: u16) -> u16 {
+        self.new_to_old_id[usize::from(new_id)]
+    }
+
+    fn new_id(&self, old_id: u16) -> u16 {
+        self.old_to_new_id.get(&old_id).copied().unwrap_or(0)
+    }
+}
+
 impl<'a> CFF<'a> {
     /// Create a subset of this CFF table.
     ///
-    /// - `glpyh_ids` contains the ids of the glyphs to retain.
+    /// - `glpyh_ids` contains the ids of the glyphs to retain. The base and accent glyphs of
+    ///   accented characters made with `seac` are retained as well, after them.
     ///
     /// When subsetting a Type 1 CFF font and retaining more than 255 glyphs the
     /// `convert_cff_to_cid_if_more_than_255_glyphs` argument controls whether the Type 1 font
@@ -87,7 +131,10 @@ impl<'a> CFF<'a> {
         let mut used_local_subrs = FxHashMap::default();
         let mut used_global_subrs = FxHashSet::default();
 
-        for &glyph_id in glyph_ids {
+        let mut glyph_ids = glyph_ids.to_vec();
+        let mut i = 0;
+        while i < glyph_ids.len() {
+            let glyph_id = glyph_ids[i];
             let char_string = font
                 .char_strings_index
                 .read_object(usize::from(glyph_id))
@@ -98,12 +145,23 @@ impl<'a> CFF<'a> {
                 &font.char_strings_index,
                 &cff.global_subr_index,
                 glyph_id,
+                None,
             )?;
             used_global_subrs.extend(subrs.global_subr_used);
             if !subrs.local_subr_used.is_empty() {
                 used_local_subrs.insert(glyph_id, subrs.local_subr_used);
             }
 
+            // The base and accent of an accented character are referred to by their charset
+            // entry, so they need to be in the subset font too
+            if let Some((base_glyph_id, accent_glyph_id)) = subrs.seac_glyphs {
+                for seac_glyph_id in [base_glyph_id, accent_glyph_id] {
+                    if !glyph_ids.contains(&seac_glyph_id) {
+                        glyph_ids.push(seac_glyph_id);
+                    }
+                }
+            }
+
             glyph_data.push(char_string.to_owned());
             // Cast should be safe as there must be less than u16::MAX glyphs in a font
             old_to_new_id.insert(glyph_id, new_to_old_id.len() as u16);
@@ -131,6 +189,7 @@ impl<'a> CFF<'a> {
                 }
                 CFFVariant::Type1(_type1) => {}
             }
+            i += 1;
         }
 
         cff.global_subr_index =
@@ -201,6 +260,121 @@ impl<'a> CFF<'a> {
     }
 }
 
+impl<'a> CFF2<'a> {
+    /// Create a subset of this CFF2 table, retaining its variations.
+    ///
+    /// `glpyh_ids` contains the ids of the glyphs to retain. It must begin with 0 (`.notdef`).
+    ///
+    /// Unlike [CFF2::subset_to_cff] the CharStrings are copied as is, so the subset font remains
+    /// variable. The variation store is retained in full, since CharStrings refer to its item
+    /// variation data by index.
+    pub fn subset(&'a self, glyph_ids: &[u16]) -> Result<SubsetCFF2<'a>, SubsetError> {
+        if glyph_ids.len() > usize::from(u16::MAX) {
+            return Err(SubsetError::TooManyGlyphs);
+        }
+        if glyph_ids.first().copied() != Some(0) {
+            // .notdef must be first
+            return Err(SubsetError::NotDef);
+        }
+
+        let default_instance = self.vstore.as_ref().map(|vstore| {
+            OwnedTuple::default_instance(*vstore.variation_region_list.variation_regions.args())
+        });
+        let variable = self
+            .vstore
+            .as_ref()
+            .zip(default_instance.as_ref())
+            .map(|(vstore, instance)| VariableCharStringVisitorContext { vstore, instance });
+
+        let mut fd_select = Vec::with_capacity(glyph_ids.len());
+        let mut new_to_old_id = Vec::with_capacity(glyph_ids.len());
+        let mut old_to_new_id =
+            FxHashMap::with_capacity_and_hasher(glyph_ids.len(), Default::default());
+        let mut glyph_data = Vec::with_capacity(glyph_ids.len());
+        let mut used_local_subrs = vec![FxHashMap::default(); self.fonts.len()];
+        let mut used_global_subrs = FxHashSet::default();
+
+        for &glyph_id in glyph_ids {
+            let font_index = match &self.fd_select {
+                Some(fd_select) => fd_select
+                    .font_dict_index(glyph_id)
+                    .ok_or(CFFError::InvalidFontIndex)?,
+                None => 0,
+            };
+            let font = self
+                .fonts
+                .get(usize::from(font_index))
+                .ok_or(CFFError::InvalidFontIndex)?;
+            let char_string = self
+                .char_strings_index
+                .read_object(usize::from(glyph_id))
+                .ok_or(ParseError::BadIndex)?;
+
+            let subrs = super::charstring::char_string_used_subrs(
+                CFFFont::CFF2(font),
+                &self.char_strings_index,
+                &self.global_subr_index,
+                glyph_id,
+                variable,
+            )?;
+            used_global_subrs.extend(subrs.global_subr_used);
+            if !subrs.local_subr_used.is_empty() {
+                used_local_subrs[usize::from(font_index)].insert(glyph_id, subrs.local_subr_used);
+            }
+
+            glyph_data.push(char_string.to_owned());
+            // Cast is safe as we checked that there are fewer than u16::MAX glyphs above
+            old_to_new_id.insert(glyph_id, new_to_old_id.len() as u16);
+            new_to_old_id.push(glyph_id);
+            fd_select.push(font_index);
+        }
+
+        // Each Font DICT has its own Local Subr INDEX, which is rebuilt in the same way as the
+        // one of a Type 1 font
+        let fonts = self
+            .fonts
+            .iter()
+            .zip(used_local_subrs)
+            .map(|(font, used_local_subrs)| {
+                let local_subr_index = rebuild_type_1_local_subr_index(
+                    font.local_subr_index.as_ref(),
+                    used_local_subrs,
+                )?;
+                let mut private_dict = font.private_dict.clone();
+                if local_subr_index.is_none() {
+                    private_dict.dict.retain(|(op, _)| *op != Operator::Subrs);
+                }
+                Ok(cff2::Font {
+                    font_dict: font.font_dict.clone(),
+                    private_dict,
+                    local_subr_index,
+                })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let table = CFF2 {
+            header: self.header,
+            top_dict: self.top_dict.clone(),
+            global_subr_index: rebuild_global_subr_index(
+                &self.global_subr_index,
+                used_global_subrs,
+            )?,
+            char_strings_index: MaybeOwnedIndex::Owned(owned::Index { data: glyph_data }),
+            vstore: self.vstore.clone(),
+            fd_select: self.fd_select.as_ref().map(|_| FDSelect::Format0 {
+                glyph_font_dict_indices: ReadArrayCow::Owned(fd_select),
+            }),
+            fonts,
+        };
+
+        Ok(SubsetCFF2 {
+            table,
+            new_to_old_id,
+            old_to_new_id,
+        })
+    }
+}
+
 pub(crate) fn rebuild_global_subr_index(
     src_global_subr_index: &MaybeOwnedIndex<'_>,
     used_global_subrs: FxHashSet<usize>,
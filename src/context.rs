@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 
 use crate::gdef;
-use crate::layout::{ClassDef, Coverage, GDEFTable};
+use crate::layout::{ClassDef, Coverage, GDEFTable, LookupCacheItem};
 
 #[derive(Copy, Clone)]
 pub struct LookupFlag(pub u16);
@@ -14,6 +14,8 @@ pub enum IgnoreMarks {
     NoIgnoreMarks,
     IgnoreAllMarks,
     IgnoreMarksExcept(u8),
+    /// Ignore marks that aren't in the `GDEF` mark glyph set with this index.
+    IgnoreMarksNotInSet(u16),
 }
 
 #[derive(Copy, Clone)]
@@ -87,6 +89,10 @@ impl LookupFlag {
         (self.0 & 0x0004) != 0
     }
 
+    pub fn get_use_mark_filtering_set(self) -> bool {
+        (self.0 & 0x0010) != 0
+    }
+
     pub fn get_ignore_marks(self) -> IgnoreMarks {
         if (self.0 & 0x8) != 0 {
             IgnoreMarks::IgnoreAllMarks
@@ -123,6 +129,18 @@ impl MatchType {
         }
     }
 
+    /// The match type of a lookup, including its mark filtering set.
+    pub fn from_lookup<T>(lookup: &LookupCacheItem<T>) -> MatchType {
+        let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+        match (match_type.ignore_marks, lookup.mark_filtering_set) {
+            (IgnoreMarks::IgnoreAllMarks, _) | (_, None) => match_type,
+            (_, Some(set_index)) => MatchType {
+                ignore_marks: IgnoreMarks::IgnoreMarksNotInSet(set_index),
+                ..match_type
+            },
+        }
+    }
+
     pub fn match_glyph<G: Glyph>(self, opt_gdef_table: Option<&GDEFTable>, glyph: &G) -> bool {
         if !self.ignore_bases
             && !self.ignore_ligatures
@@ -146,6 +164,14 @@ impl MatchType {
                     gdef::mark_attach_class(opt_gdef_table, glyph.get_glyph_index());
                 (glyph_class != 3) || (mark_attach_class == u16::from(keep_class))
             }
+            IgnoreMarks::IgnoreMarksNotInSet(set_index) => {
+                (glyph_class != 3)
+                    || gdef::is_in_mark_glyph_set(
+                        opt_gdef_table,
+                        set_index,
+                        glyph.get_glyph_index(),
+                    )
+            }
         }
     }
 
@@ -8,6 +8,8 @@ use std::fmt;
 pub enum ShapingError {
     Indic(IndicError),
     Parse(ParseError),
+    /// A glyph has a feature mask but the text was shaped with `Features::Custom`.
+    GlyphFeatureMask,
 }
 
 impl From<IndicError> for ShapingError {
@@ -33,6 +35,9 @@ impl fmt::Display for ShapingError {
         match self {
             ShapingError::Indic(err) => write!(f, "indic shaping: {}", err),
             ShapingError::Parse(err) => write!(f, "shaping parse: {}", err),
+            ShapingError::GlyphFeatureMask => {
+                write!(f, "glyph feature masks can't be used with custom features")
+            }
         }
     }
 }
@@ -156,6 +161,18 @@ impl From<WriteError> for ReadWriteError {
     }
 }
 
+impl From<ReadEof> for ReadWriteError {
+    fn from(error: ReadEof) -> Self {
+        ReadWriteError::Read(ParseError::from(error))
+    }
+}
+
+impl From<std::num::TryFromIntError> for ReadWriteError {
+    fn from(error: std::num::TryFromIntError) -> Self {
+        ReadWriteError::Write(WriteError::from(error))
+    }
+}
+
 impl fmt::Display for ReadWriteError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -5,6 +5,10 @@ use std::convert::{self, TryFrom};
 use std::rc::Rc;
 
 use bitflags::bitflags;
+#[cfg(feature = "outline")]
+use pathfinder_geometry::line_segment::LineSegment2F;
+#[cfg(feature = "outline")]
+use pathfinder_geometry::vector::Vector2F;
 use tinyvec::tiny_vec;
 
 use crate::big5::unicode_to_big5;
@@ -12,22 +16,40 @@ use crate::binary::read::ReadScope;
 use crate::bitmap::cbdt::{self, CBDTTable, CBLCTable};
 use crate::bitmap::sbix::Sbix as SbixTable;
 use crate::bitmap::{BitDepth, BitmapGlyph};
+#[cfg(feature = "outline")]
+use crate::cff::CFF;
 use crate::error::{ParseError, ShapingError};
 use crate::glyph_info::GlyphNames;
 use crate::gpos::Info;
-use crate::gsub::{Features, GlyphOrigin, RawGlyph, RawGlyphFlags};
-use crate::layout::{new_layout_cache, GDEFTable, LayoutCache, LayoutTable, GPOS, GSUB};
+use crate::gsub::{Features, GlyphAlternates, GlyphOrigin, RawGlyph, RawGlyphFlags};
+use crate::layout::{
+    new_layout_cache, CaretValue, Device, GDEFTable, LayoutCache, LayoutTable, GPOS, GSUB,
+};
 use crate::macroman::char_to_macroman;
-use crate::scripts::preprocess_text;
+#[cfg(feature = "outline")]
+use crate::outline::{OutlineBuilder, OutlineSink};
+use crate::scripts::{arabic, hangul, preprocess_text, thai_lao, ScriptType};
 use crate::tables::cmap::{Cmap, CmapSubtable, EncodingId, EncodingRecord, PlatformId};
+use crate::tables::glyf::{BoundingBox, GlyfTable, Glyph, PhantomPoints, Point};
+use crate::tables::kern::KernTable;
+use crate::tables::kerx::{AnkrTable, KerxTable};
+use crate::tables::loca::LocaTable;
+use crate::tables::morx::MorxTable;
 use crate::tables::os2::Os2;
 use crate::tables::svg::SvgTable;
-use crate::tables::variable_fonts::fvar::{FvarAxisCount, FvarTable, Tuple, VariationAxisRecord};
-use crate::tables::{FontTableProvider, HeadTable, HheaTable, MaxpTable};
-use crate::unicode::{self, VariationSelector};
+use crate::tables::trak::TrakTable;
+use crate::tables::variable_fonts::fvar::{
+    FvarAxisCount, FvarTable, OwnedTuple, Tuple, VariationAxisRecord,
+};
+use crate::tables::variable_fonts::gvar::GvarTable;
+use crate::tables::variable_fonts::hvar::HvarTable;
+use crate::tables::variable_fonts::vvar::VvarTable;
+use crate::tables::vorg::VorgTable;
+use crate::tables::{FontTableProvider, HeadTable, HheaTable, HmtxTable, MaxpTable, NameTable};
+use crate::unicode::{self, normalization, ClusterChar, VariationSelector};
 use crate::variations::{AxisNamesError, NamedAxis};
+use crate::{aat, gpos, gsub, DOTTED_CIRCLE};
 use crate::{glyph_info, tag, variations};
-use crate::{gpos, gsub, DOTTED_CIRCLE};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Encoding {
@@ -77,12 +99,21 @@ pub struct Font<T: FontTableProvider> {
     gdef_cache: LazyLoad<Rc<GDEFTable>>,
     gsub_cache: LazyLoad<LayoutCache<GSUB>>,
     gpos_cache: LazyLoad<LayoutCache<GPOS>>,
+    kern_table: LazyLoad<Rc<[u8]>>,
+    morx_table: LazyLoad<Rc<[u8]>>,
+    kerx_table: LazyLoad<Rc<[u8]>>,
+    ankr_table: LazyLoad<Rc<[u8]>>,
+    trak_table: LazyLoad<Rc<TrakTable>>,
+    vorg_table: LazyLoad<Rc<VorgTable>>,
+    hvar_table: LazyLoad<Rc<[u8]>>,
+    vvar_table: LazyLoad<Rc<[u8]>>,
     os2_us_first_char_index: LazyLoad<u16>,
     glyph_cache: GlyphCache,
     pub glyph_table_flags: GlyphTableFlags,
     embedded_image_filter: GlyphTableFlags,
     embedded_images: LazyLoad<Rc<Images>>,
     axis_count: u16,
+    fallback_mark_positioning: bool,
 }
 
 pub enum Images {
@@ -197,12 +228,21 @@ impl<T: FontTableProvider> Font<T> {
                     gdef_cache: LazyLoad::NotLoaded,
                     gsub_cache: LazyLoad::NotLoaded,
                     gpos_cache: LazyLoad::NotLoaded,
+                    kern_table: LazyLoad::NotLoaded,
+                    morx_table: LazyLoad::NotLoaded,
+                    kerx_table: LazyLoad::NotLoaded,
+                    ankr_table: LazyLoad::NotLoaded,
+                    trak_table: LazyLoad::NotLoaded,
+                    vorg_table: LazyLoad::NotLoaded,
+                    hvar_table: LazyLoad::NotLoaded,
+                    vvar_table: LazyLoad::NotLoaded,
                     os2_us_first_char_index: LazyLoad::NotLoaded,
                     glyph_cache: GlyphCache::new(),
                     glyph_table_flags,
                     embedded_image_filter,
                     embedded_images: LazyLoad::NotLoaded,
                     axis_count: fvar_axis_count,
+                    fallback_mark_positioning: false,
                 })
             }
             None => Err(ParseError::UnsuitableCmap),
@@ -225,6 +265,16 @@ impl<T: FontTableProvider> Font<T> {
         self.embedded_image_filter = flags;
     }
 
+    /// Set whether [Font::shape] positions marks from their glyph outlines in fonts without a
+    /// `GPOS` table.
+    ///
+    /// This is off by default, so marks in these fonts overprint their base. Some fonts, such as
+    /// terminal fonts, draw their combining marks in place over the base, and positioning them
+    /// from their outlines would move them. See [gpos::apply_fallback_mark_positioning].
+    pub fn set_fallback_mark_positioning(&mut self, enabled: bool) {
+        self.fallback_mark_positioning = enabled;
+    }
+
     /// Look up the glyph index for the supplied character in the font.
     pub fn lookup_glyph_index(
         &mut self,
@@ -245,6 +295,15 @@ impl<T: FontTableProvider> Font<T> {
     /// The method maps applies glyph substitution (`gsub`) and glyph positioning (`gpos`). Use
     /// `map_glyphs` to turn text into glyphs that can be accepted by this method.
     ///
+    /// Fonts that use Apple Advanced Typography instead of OpenType layout are shaped with their
+    /// `morx` table when there is no `gsub` table, and their `kerx` table when there is no `gpos`
+    /// table. The OpenType features are mapped to the equivalent AAT features. Tracking from the
+    /// `trak` table depends on the point size, so it isn't applied here, see
+    /// [aat::apply_trak](crate::aat::apply_trak).
+    ///
+    /// Arabic text in fonts with neither a `gsub` nor a `morx` table is shaped by mapping letters
+    /// to their Arabic Presentation Forms, which legacy Arabic fonts map in their `cmap` table.
+    ///
     /// **Arguments:**
     ///
     /// * `glyphs`: the glyphs to be shaped in logical order.
@@ -253,7 +312,9 @@ impl<T: FontTableProvider> Font<T> {
     /// * `features`: the [OpenType features](https://docs.microsoft.com/en-us/typography/opentype/spec/featuretags) to enable.
     /// * `kerning`: when applying `gpos` if this argument is `true` the `kern` OpenType feature
     ///   is enabled for non-complex scripts. If it is `false` then the `kern` feature is not
-    ///   enabled for non-complex scripts.
+    ///   enabled for non-complex scripts. When `true` and the font has no `gpos` table, or no
+    ///   `kern` feature for the script, kerning is applied from the `kerx` or legacy `kern` table
+    ///   instead.
     ///
     /// **Error Handling:**
     ///
@@ -338,10 +399,35 @@ impl<T: FontTableProvider> Font<T> {
                 &mut glyphs,
             );
             check_set_err(res, &mut err);
+        } else if let Some(morx_data) = check_set_err(self.morx_table(), &mut err) {
+            let res = ReadScope::new(&morx_data)
+                .read::<MorxTable<'_>>()
+                .and_then(|morx_table| aat::apply_morx(&morx_table, features, &mut glyphs));
+            check_set_err(res, &mut err);
+        } else if script_tag == tag::ARAB {
+            // Legacy Arabic fonts map the presentation forms instead
+            arabic::apply_presentation_forms(&mut glyphs, |ch| {
+                match self.lookup_glyph_index(ch, MatchingPresentation::NotRequired, None) {
+                    (0, _) => None,
+                    (glyph_index, _) => Some(glyph_index),
+                }
+            });
+        }
+
+        // Older Thai fonts without `gpos` use Private Use Area glyphs to position marks
+        if opt_gpos_cache.is_none() && script_tag == tag::THAI {
+            thai_lao::apply_pua_fallback(&mut glyphs, |ch| {
+                match self.lookup_glyph_index(ch, MatchingPresentation::NotRequired, None) {
+                    (0, _) => None,
+                    (glyph_index, _) => Some(glyph_index),
+                }
+            });
         }
 
         // Apply gpos if table is present
         let mut infos = Info::init_from_glyphs(opt_gdef_table, glyphs);
+        let mut legacy_kerning = kerning;
+        let fallback_marks;
         if let Some(gpos_cache) = opt_gpos_cache {
             let res = gpos::apply(
                 &gpos_cache,
@@ -354,8 +440,69 @@ impl<T: FontTableProvider> Font<T> {
                 &mut infos,
             );
             check_set_err(res, &mut err);
+            if kerning {
+                let res = gpos::has_kern_feature(&gpos_cache, script_tag, opt_lang_tag, tuple);
+                legacy_kerning = !check_set_err(res, &mut err);
+            }
+            fallback_marks = false;
         } else {
             gpos::apply_fallback(&mut infos);
+            // The Thai PUA glyphs are already positioned
+            fallback_marks = self.fallback_mark_positioning && script_tag != tag::THAI;
+            if let Some(kerx_data) = check_set_err(self.kerx_table(), &mut err) {
+                let opt_ankr_data = check_set_err(self.ankr_table(), &mut err);
+                let res =
+                    ReadScope::new(&kerx_data)
+                        .read::<KerxTable<'_>>()
+                        .and_then(|kerx_table| {
+                            let opt_ankr_table = opt_ankr_data
+                                .as_deref()
+                                .map(|ankr_data| ReadScope::new(ankr_data).read::<AnkrTable<'_>>())
+                                .transpose()?;
+                            aat::apply_kerx(
+                                &kerx_table,
+                                opt_ankr_table.as_ref(),
+                                kerning,
+                                &mut infos,
+                            )
+                        });
+                check_set_err(res, &mut err);
+                legacy_kerning = false;
+            }
+        }
+
+        // Position marks from their outlines if enabled and there's no `GPOS` table, as in HarfBuzz
+        if fallback_marks && infos.iter().any(|info| info.is_mark) {
+            let glyph_indices = infos.iter().map(|info| info.glyph.glyph_index);
+            let res = self.glyph_bounding_boxes(glyph_indices);
+            let opt_bounding_boxes = check_set_err(res, &mut err);
+            let opt_head = check_set_err(self.head_table(), &mut err);
+            if let (Some(bounding_boxes), Some(head)) = (opt_bounding_boxes, opt_head) {
+                let advances = infos
+                    .iter()
+                    .map(|info| self.horizontal_advance(info.glyph.glyph_index).unwrap_or(0))
+                    .collect::<Vec<_>>();
+                gpos::apply_fallback_mark_positioning(
+                    &mut infos,
+                    &bounding_boxes,
+                    &advances,
+                    head.units_per_em,
+                );
+            }
+        }
+
+        // Fall back to the `kern` table if there's no kerning in `gpos`
+        if legacy_kerning {
+            let res = self
+                .kern_table()
+                .and_then(|opt_kern_data| match opt_kern_data {
+                    Some(kern_data) => {
+                        let kern_table = ReadScope::new(&kern_data).read::<KernTable<'_>>()?;
+                        gpos::apply_kern_table(&kern_table, &mut infos)
+                    }
+                    None => Ok(()),
+                });
+            check_set_err(res, &mut err);
         }
 
         match err {
@@ -376,27 +523,81 @@ impl<T: FontTableProvider> Font<T> {
     /// `MatchingPresentation::Required` is passed then a character with emoji presentation,
     /// either by default or requested via variation selector will only map to a glyph if the font
     /// has mapping for the character, and it has the necessary tables for color emoji.
+    ///
+    /// Each glyph is assigned the byte offset of its character in `text` as its `cluster`.
+    /// Where text preprocessing reorders characters the affected clusters are merged so that
+    /// clusters never decrease from one glyph to the next.
+    ///
+    /// Hangul jamo are composed into precomposed syllables, or syllables decomposed into jamo,
+    /// depending on which of them the font has glyphs for. A composed syllable takes the cluster
+    /// of its first jamo.
+    ///
+    /// Text is mapped as it is given. Use `map_glyphs_normalized` to have it normalised according
+    /// to the glyphs in the font.
     pub fn map_glyphs(
         &mut self,
         text: &str,
         script_tag: u32,
         match_presentation: MatchingPresentation,
     ) -> Vec<RawGlyph<()>> {
-        let mut chars = text.chars().collect();
+        self.map_glyphs_impl(text, script_tag, match_presentation, false)
+    }
+
+    /// Map text to glyphs, normalising it according to the glyphs in the font.
+    ///
+    /// This is the same as `map_glyphs` except that text in scripts that don't have a dedicated
+    /// shaper, as well as Arabic and Syriac, is normalised first. Characters the font can't map
+    /// are decomposed, and base characters are composed with the marks that follow them where the
+    /// font has the composition, so each character ends up in NFC or NFD form as the font
+    /// requires. A composed character takes the cluster of its base.
+    pub fn map_glyphs_normalized(
+        &mut self,
+        text: &str,
+        script_tag: u32,
+        match_presentation: MatchingPresentation,
+    ) -> Vec<RawGlyph<()>> {
+        self.map_glyphs_impl(text, script_tag, match_presentation, true)
+    }
+
+    fn map_glyphs_impl(
+        &mut self,
+        text: &str,
+        script_tag: u32,
+        match_presentation: MatchingPresentation,
+        normalize: bool,
+    ) -> Vec<RawGlyph<()>> {
+        let mut chars = text
+            .char_indices()
+            .map(|(cluster, ch)| ClusterChar { ch, cluster })
+            .collect();
+        let script_type = ScriptType::from(script_tag);
+        let normalize = normalize
+            && matches!(
+                script_type,
+                ScriptType::Default | ScriptType::Arabic | ScriptType::Syriac
+            );
+        if normalize {
+            normalization::decompose_for_font(&mut chars, |ch| self.has_glyph(ch));
+        }
         preprocess_text(&mut chars, script_tag);
+        if normalize {
+            normalization::compose_for_font(&mut chars, |ch| self.has_glyph(ch));
+        } else if script_type == ScriptType::Hangul {
+            hangul::compose_jamo(&mut chars, |ch| self.has_glyph(ch));
+        }
 
         // We look ahead in the char stream for variation selectors. If one is found it is used for
         // mapping the current glyph. When a variation selector is reached in the stream it is
         // skipped as it was handled as part of the preceding character.
         let mut glyphs = Vec::with_capacity(chars.len());
         let mut chars_iter = chars.into_iter().peekable();
-        while let Some(ch) = chars_iter.next() {
+        while let Some(ClusterChar { ch, cluster }) = chars_iter.next() {
             match VariationSelector::try_from(ch) {
                 Ok(_) => {} // filter out variation selectors
                 Err(()) => {
                     let vs = chars_iter
                         .peek()
-                        .and_then(|&next| VariationSelector::try_from(next).ok());
+                        .and_then(|next| VariationSelector::try_from(next.ch).ok());
                     let (glyph_index, used_variation) =
                         self.lookup_glyph_index(ch, match_presentation, vs);
                     let glyph = RawGlyph {
@@ -404,18 +605,27 @@ impl<T: FontTableProvider> Font<T> {
                         glyph_index,
                         liga_component_pos: 0,
                         glyph_origin: GlyphOrigin::Char(ch),
+                        cluster,
                         flags: RawGlyphFlags::empty(),
                         extra_data: (),
                         variation: Some(used_variation),
+                        feature_mask: None,
                     };
                     glyphs.push(glyph);
                 }
             }
         }
+        gsub::merge_unordered_clusters(&mut glyphs);
         glyphs.shrink_to_fit();
         glyphs
     }
 
+    /// True if the font maps `ch` to a glyph.
+    fn has_glyph(&mut self, ch: char) -> bool {
+        let (glyph_index, _) = self.lookup_glyph_index(ch, MatchingPresentation::NotRequired, None);
+        glyph_index != 0
+    }
+
     /// True if the font has one or more variation axes.
     pub fn is_variable(&self) -> bool {
         self.axis_count > 0
@@ -562,6 +772,36 @@ impl<T: FontTableProvider> Font<T> {
         variations::axis_names(&self.font_table_provider)
     }
 
+    /// Returns the alternates of a glyph from the `aalt`, `salt`, `swsh`, `ssNN`, and `cvNN`
+    /// features of the script and language.
+    ///
+    /// The names of stylistic sets and character variants can be looked up from the
+    /// `feature_params` of each result with [Font::name].
+    pub fn glyph_alternates(
+        &mut self,
+        glyph_index: u16,
+        script_tag: u32,
+        opt_lang_tag: Option<u32>,
+    ) -> Result<Vec<GlyphAlternates>, ParseError> {
+        match self.gsub_cache()? {
+            Some(gsub_cache) => {
+                gsub::glyph_alternates(&gsub_cache, script_tag, opt_lang_tag, glyph_index)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the string for `name_id` from the `name` table, if there is one.
+    pub fn name(&self, name_id: u16) -> Result<Option<String>, ParseError> {
+        match self.font_table_provider.table_data(tag::NAME)? {
+            Some(name_data) => {
+                let name = ReadScope::new(&name_data).read::<NameTable<'_>>()?;
+                Ok(name.string_for_id(name_id))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Find an image matching the supplied criteria.
     ///
     /// * `glyph_index` is the glyph to lookup.
@@ -718,13 +958,7 @@ impl<T: FontTableProvider> Font<T> {
     }
 
     pub fn vertical_advance(&mut self, glyph: u16) -> Option<u16> {
-        let provider = &self.font_table_provider;
-        let vmtx = self
-            .vmtx_table
-            .get_or_load(|| {
-                read_and_box_optional_table(provider, tag::VMTX).map(|ok| ok.map(Rc::from))
-            })
-            .ok()?;
+        let vmtx = self.vmtx_table().ok()?;
         let vhea = self.vhea_table().ok()?;
 
         if let (Some(vhea), Some(vmtx_table)) = (vhea, vmtx) {
@@ -734,6 +968,400 @@ impl<T: FontTableProvider> Font<T> {
         }
     }
 
+    /// Returns the y coordinate of the vertical origin of the supplied glyph index.
+    ///
+    /// In vertical text the vertical origin of a glyph is placed on the pen position. Its x
+    /// coordinate is half the horizontal advance of the glyph. The y coordinate is read from the
+    /// `VORG` table, or else it is the top of the bounding box of the glyph plus its top side
+    /// bearing in `vmtx`. Without `vmtx` the glyph is centred between the ascender and descender
+    /// in `hhea`, and glyphs without an outline have their origin at the ascender.
+    ///
+    /// Use [Font::vertical_origins] for more than one glyph, as the outlines are parsed on each
+    /// call.
+    pub fn vertical_origin(&mut self, glyph: u16) -> i32 {
+        self.vertical_origins(&[glyph], None)
+            .ok()
+            .and_then(|mut origins| origins.pop())
+            .unwrap_or_else(|| i32::from(self.hhea_table.ascender))
+    }
+
+    /// Returns the y coordinates of the vertical origins of the supplied glyph indices,
+    /// optionally at the variation instance `tuple`.
+    ///
+    /// See [Font::vertical_origin] for how the origin is calculated. At a variation instance the
+    /// deltas in `VVAR` are applied to origins from `VORG`. Origins calculated from the bounding
+    /// box of a glyph use the default outline, without variations.
+    pub fn vertical_origins(
+        &mut self,
+        glyph_indices: &[u16],
+        tuple: Option<Tuple<'_>>,
+    ) -> Result<Vec<i32>, ParseError> {
+        if let Some(vorg) = self.vorg_table()? {
+            let origins = glyph_indices
+                .iter()
+                .map(|&glyph_index| i32::from(vorg.vert_origin_y(glyph_index)));
+            let (Some(tuple), Some(vvar_data)) = (tuple, self.vvar_table()?) else {
+                return Ok(origins.collect());
+            };
+            let vvar = ReadScope::new(&vvar_data).read::<VvarTable<'_>>()?;
+            let instance = OwnedTuple::from(tuple);
+            return origins
+                .zip(glyph_indices)
+                .map(|(origin, &glyph_index)| {
+                    let delta = vvar.vert_origin_delta(&instance, glyph_index)?;
+                    // NOTE(cast): float to int casts are saturating
+                    Ok(origin + delta.map_or(0, |delta| delta.round() as i32))
+                })
+                .collect();
+        }
+
+        let ascender = i32::from(self.hhea_table.ascender);
+        let descender = i32::from(self.hhea_table.descender);
+        let bounding_boxes = self
+            .glyph_bounding_boxes(glyph_indices.iter().copied())?
+            .unwrap_or_else(|| vec![None; glyph_indices.len()]);
+        let vmtx_data = self.vmtx_table()?;
+        let vhea = self.vhea_table()?;
+        let vmtx = match (&vmtx_data, &vhea) {
+            (Some(vmtx_data), Some(vhea)) => {
+                Some(ReadScope::new(vmtx_data).read_dep::<HmtxTable<'_>>((
+                    usize::from(self.maxp_table.num_glyphs),
+                    usize::from(vhea.num_h_metrics),
+                ))?)
+            }
+            _ => None,
+        };
+        Ok(glyph_indices
+            .iter()
+            .zip(bounding_boxes)
+            .map(|(&glyph_index, opt_bounding_box)| {
+                let Some(bounding_box) = opt_bounding_box else {
+                    return ascender;
+                };
+                let y_max = i32::from(bounding_box.y_max);
+                let top_side_bearing = vmtx
+                    .as_ref()
+                    .and_then(|vmtx| vmtx.metric(glyph_index).ok())
+                    .map(|metric| metric.lsb);
+                match top_side_bearing {
+                    Some(top_side_bearing) => y_max + i32::from(top_side_bearing),
+                    None => {
+                        let height = y_max - i32::from(bounding_box.y_min);
+                        y_max + (ascender - descender - height) / 2
+                    }
+                }
+            })
+            .collect())
+    }
+
+    fn vmtx_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.vmtx_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::VMTX).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    /// Returns the horizontal advance of the supplied glyph index at the variation instance
+    /// `tuple`.
+    ///
+    /// See [Font::variation_advances] for how the advance is calculated.
+    pub fn horizontal_advance_at(&mut self, glyph: u16, tuple: Tuple<'_>) -> Option<u16> {
+        self.variation_advances(&[glyph], tuple, false)
+            .ok()?
+            .pop()
+            .flatten()
+    }
+
+    /// Returns the vertical advance of the supplied glyph index at the variation instance
+    /// `tuple`.
+    ///
+    /// See [Font::variation_advances] for how the advance is calculated.
+    pub fn vertical_advance_at(&mut self, glyph: u16, tuple: Tuple<'_>) -> Option<u16> {
+        self.variation_advances(&[glyph], tuple, true)
+            .ok()?
+            .pop()
+            .flatten()
+    }
+
+    /// Returns the advances of the supplied glyph indices at the variation instance `tuple`.
+    ///
+    /// The advances are vertical if `vertical` is `true`, otherwise they're horizontal. Deltas
+    /// are applied to the advances in `hmtx` or `vmtx` from the `HVAR` or `VVAR` table. Fonts
+    /// with TrueType outlines may omit these tables, in which case the advances are calculated
+    /// from the phantom points of the glyphs after applying the deltas in `gvar`. Each advance is
+    /// `None` where [Font::horizontal_advance] or [Font::vertical_advance] would return `None`.
+    pub fn variation_advances(
+        &mut self,
+        glyph_indices: &[u16],
+        tuple: Tuple<'_>,
+        vertical: bool,
+    ) -> Result<Vec<Option<u16>>, ParseError> {
+        let advances = glyph_indices
+            .iter()
+            .map(|&glyph_index| {
+                if vertical {
+                    self.vertical_advance(glyph_index)
+                } else {
+                    self.horizontal_advance(glyph_index)
+                }
+            })
+            .collect::<Vec<_>>();
+        let instance = OwnedTuple::from(tuple);
+
+        let metrics_variations = if vertical {
+            self.vvar_table()?
+        } else {
+            self.hvar_table()?
+        };
+        if let Some(data) = metrics_variations {
+            let deltas = if vertical {
+                let vvar = ReadScope::new(&data).read::<VvarTable<'_>>()?;
+                glyph_indices
+                    .iter()
+                    .map(|&glyph_index| vvar.advance_delta(&instance, glyph_index))
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                let hvar = ReadScope::new(&data).read::<HvarTable<'_>>()?;
+                glyph_indices
+                    .iter()
+                    .map(|&glyph_index| hvar.advance_delta(&instance, glyph_index))
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+            return Ok(advances
+                .into_iter()
+                .zip(deltas)
+                .map(|(advance, delta)| {
+                    advance.map(|advance| {
+                        // NOTE(cast): float to int casts are saturating
+                        (f32::from(advance) + delta).round() as u16
+                    })
+                })
+                .collect());
+        }
+
+        match self.phantom_points(glyph_indices, &instance)? {
+            Some(phantom_points) => Ok(advances
+                .into_iter()
+                .zip(phantom_points)
+                .map(|(advance, [pp1, pp2, pp3, pp4])| {
+                    // pp2 is the advance width from pp1, and pp4 is the advance height below pp3
+                    advance.and_then(|_| {
+                        let advance = if vertical {
+                            i32::from(pp3.1) - i32::from(pp4.1)
+                        } else {
+                            i32::from(pp2.0) - i32::from(pp1.0)
+                        };
+                        u16::try_from(advance).ok()
+                    })
+                })
+                .collect()),
+            None => Ok(advances),
+        }
+    }
+
+    /// Returns the phantom points of the supplied glyph indices after applying the deltas in
+    /// `gvar`, or `None` if the font doesn't have `glyf` and `gvar` tables.
+    fn phantom_points(
+        &mut self,
+        glyph_indices: &[u16],
+        instance: &OwnedTuple,
+    ) -> Result<Option<Vec<PhantomPoints>>, ParseError> {
+        let vmtx_data = self.vmtx_table()?;
+        let vhea = self.vhea_table()?;
+        let os2 = self.os2_table()?;
+        let provider = &self.font_table_provider;
+        let (Some(head), Some(loca_data), Some(glyf_data), Some(gvar_data)) = (
+            self.head_table()?,
+            provider.table_data(tag::LOCA)?,
+            provider.table_data(tag::GLYF)?,
+            provider.table_data(tag::GVAR)?,
+        ) else {
+            return Ok(None);
+        };
+        let num_glyphs = usize::from(self.maxp_table.num_glyphs);
+        let loca = ReadScope::new(&loca_data)
+            .read_dep::<LocaTable<'_>>((num_glyphs, head.index_to_loc_format))?;
+        let mut glyf = ReadScope::new(&glyf_data).read_dep::<GlyfTable<'_>>(&loca)?;
+        let gvar = ReadScope::new(&gvar_data).read::<GvarTable<'_>>()?;
+        let hmtx = ReadScope::new(&self.hmtx_table)
+            .read_dep::<HmtxTable<'_>>((num_glyphs, usize::from(self.hhea_table.num_h_metrics)))?;
+        let vmtx = match (&vmtx_data, &vhea) {
+            (Some(vmtx_data), Some(vhea)) => Some(
+                ReadScope::new(vmtx_data)
+                    .read_dep::<HmtxTable<'_>>((num_glyphs, usize::from(vhea.num_h_metrics)))?,
+            ),
+            _ => None,
+        };
+        glyph_indices
+            .iter()
+            .map(|&glyph_index| {
+                let mut glyph = glyf.get_parsed_glyph(glyph_index)?.clone();
+                glyph.apply_variations(
+                    glyph_index,
+                    instance,
+                    &gvar,
+                    &hmtx,
+                    vmtx.as_ref(),
+                    os2.as_ref(),
+                    &self.hhea_table,
+                )?;
+                glyph.phantom_points().ok_or(ParseError::MissingValue)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    fn hvar_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.hvar_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::HVAR).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    fn vvar_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.vvar_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::VVAR).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    /// Returns the caret positions within a shaped ligature glyph.
+    ///
+    /// There is a caret between each pair of adjacent components of a ligature. Each position is
+    /// an x coordinate in font units, relative to the origin of the glyph. Positions are read from
+    /// the `GDEF` ligature caret list, with variation deltas applied for `tuple`. If the font has
+    /// no carets for the glyph, the advance of the glyph is divided evenly between the characters
+    /// that formed it.
+    ///
+    /// Returns an empty `Vec` for glyphs formed from a single character that have no carets.
+    pub fn ligature_carets(
+        &mut self,
+        info: &Info,
+        tuple: Option<Tuple<'_>>,
+    ) -> Result<Vec<i32>, ParseError> {
+        let glyph_index = info.glyph.glyph_index;
+        if let Some(gdef_table) = self.gdef_table()? {
+            let opt_caret_values = gdef_table
+                .opt_lig_caret_list
+                .as_ref()
+                .and_then(|lig_caret_list| lig_caret_list.caret_values(glyph_index));
+            if let Some(caret_values) = opt_caret_values {
+                if let Some(carets) =
+                    self.resolve_carets(&gdef_table, glyph_index, caret_values, tuple)?
+                {
+                    return Ok(carets);
+                }
+            }
+        }
+
+        let components = info.glyph.unicodes.len();
+        if components < 2 {
+            return Ok(Vec::new());
+        }
+        let advance = self.horizontal_advance(glyph_index).map_or(0, i32::from);
+        let components = components as i32;
+        Ok((1..components)
+            .map(|component| advance * component / components)
+            .collect())
+    }
+
+    /// Returns the x coordinates of `caret_values`, or `None` if a contour point can't be found.
+    fn resolve_carets(
+        &self,
+        gdef_table: &GDEFTable,
+        glyph_index: u16,
+        caret_values: &[CaretValue],
+        tuple: Option<Tuple<'_>>,
+    ) -> Result<Option<Vec<i32>>, ParseError> {
+        let mut carets = Vec::with_capacity(caret_values.len());
+        for caret_value in caret_values {
+            let caret = match caret_value {
+                CaretValue::Coordinate(x) => i32::from(*x),
+                CaretValue::DeviceCoordinate(x, opt_device) => {
+                    let delta = match (opt_device, tuple, &gdef_table.opt_item_variation_store) {
+                        (Some(Device::Variation(index)), Some(tuple), Some(store)) => {
+                            store.adjustment(*index, tuple).unwrap_or(0.0)
+                        }
+                        _ => 0.0,
+                    };
+                    i32::from(*x) + delta.round() as i32
+                }
+                CaretValue::ContourPoint(point_index) => {
+                    match self.contour_point(glyph_index, *point_index)? {
+                        Some(Point(x, _)) => i32::from(x),
+                        None => return Ok(None),
+                    }
+                }
+            };
+            carets.push(caret);
+        }
+        Ok(Some(carets))
+    }
+
+    /// Returns a point of the outline of a simple `glyf` glyph, without variations applied.
+    fn contour_point(
+        &self,
+        glyph_index: u16,
+        point_index: u16,
+    ) -> Result<Option<Point>, ParseError> {
+        let provider = &self.font_table_provider;
+        let (Some(head), Some(loca_data), Some(glyf_data)) = (
+            self.head_table()?,
+            provider.table_data(tag::LOCA)?,
+            provider.table_data(tag::GLYF)?,
+        ) else {
+            return Ok(None);
+        };
+        let loca = ReadScope::new(&loca_data).read_dep::<LocaTable<'_>>((
+            usize::from(self.maxp_table.num_glyphs),
+            head.index_to_loc_format,
+        ))?;
+        let mut glyf = ReadScope::new(&glyf_data).read_dep::<GlyfTable<'_>>(&loca)?;
+        match glyf.get_parsed_glyph(glyph_index)? {
+            Glyph::Simple(simple_glyph) => Ok(simple_glyph
+                .coordinates
+                .get(usize::from(point_index))
+                .map(|&(_, point)| point)),
+            Glyph::Empty(_) | Glyph::Composite(_) => Ok(None),
+        }
+    }
+
+    // Returns the bounding boxes of the glyphs from `glyf` or `CFF` outlines, or `None` if the
+    // font has no supported outlines
+    fn glyph_bounding_boxes(
+        &self,
+        glyph_indices: impl Iterator<Item = u16>,
+    ) -> Result<Option<Vec<Option<BoundingBox>>>, ParseError> {
+        let provider = &self.font_table_provider;
+        if let (Some(head), Some(loca_data), Some(glyf_data)) = (
+            self.head_table()?,
+            provider.table_data(tag::LOCA)?,
+            provider.table_data(tag::GLYF)?,
+        ) {
+            let loca = ReadScope::new(&loca_data).read_dep::<LocaTable<'_>>((
+                usize::from(self.maxp_table.num_glyphs),
+                head.index_to_loc_format,
+            ))?;
+            let mut glyf = ReadScope::new(&glyf_data).read_dep::<GlyfTable<'_>>(&loca)?;
+            let bounding_boxes = glyph_indices
+                .map(|glyph_index| Ok(glyf.get_parsed_glyph(glyph_index)?.bounding_box()))
+                .collect::<Result<_, ParseError>>()?;
+            return Ok(Some(bounding_boxes));
+        }
+        #[cfg(feature = "outline")]
+        if let Some(cff_data) = provider.table_data(tag::CFF)? {
+            let mut cff = ReadScope::new(&cff_data).read::<CFF<'_>>()?;
+            let bounding_boxes = glyph_indices
+                .map(|glyph_index| {
+                    let mut sink = BoundingBoxSink(None);
+                    cff.visit(glyph_index, &mut sink).ok().and(sink.0)
+                })
+                .collect();
+            return Ok(Some(bounding_boxes));
+        }
+        Ok(None)
+    }
+
     pub fn head_table(&self) -> Result<Option<HeadTable>, ParseError> {
         self.font_table_provider
             .table_data(tag::HEAD)?
@@ -795,6 +1423,70 @@ impl<T: FontTableProvider> Font<T> {
         })
     }
 
+    /// Returns the data of the `kern` table, if the font has one.
+    ///
+    /// The data is read with [KernTable].
+    pub fn kern_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.kern_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::KERN).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    /// Returns the data of the `morx` table, if the font has one.
+    ///
+    /// The data is read with [MorxTable].
+    pub fn morx_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.morx_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::MORX).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    /// Returns the data of the `kerx` table, if the font has one.
+    ///
+    /// The data is read with [KerxTable].
+    pub fn kerx_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.kerx_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::KERX).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    /// Returns the data of the `ankr` table, if the font has one.
+    ///
+    /// The data is read with [AnkrTable].
+    pub fn ankr_table(&mut self) -> Result<Option<Rc<[u8]>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.ankr_table.get_or_load(|| {
+            read_and_box_optional_table(provider, tag::ANKR).map(|ok| ok.map(Rc::from))
+        })
+    }
+
+    pub fn trak_table(&mut self) -> Result<Option<Rc<TrakTable>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.trak_table.get_or_load(|| {
+            if let Some(trak_data) = provider.table_data(tag::TRAK)? {
+                let trak = ReadScope::new(&trak_data).read::<TrakTable>()?;
+                Ok(Some(Rc::new(trak)))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    pub fn vorg_table(&mut self) -> Result<Option<Rc<VorgTable>>, ParseError> {
+        let provider = &self.font_table_provider;
+        self.vorg_table.get_or_load(|| {
+            if let Some(vorg_data) = provider.table_data(tag::VORG)? {
+                let vorg = ReadScope::new(&vorg_data).read::<VorgTable>()?;
+                Ok(Some(Rc::new(vorg)))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
     pub fn cmap_subtable_data(&self) -> &[u8] {
         &self.cmap_table[self.cmap_subtable_offset..]
     }
@@ -977,6 +1669,45 @@ pub fn find_good_cmap_subtable(cmap: &Cmap<'_>) -> Option<(Encoding, EncodingRec
 
 // Unwrap the supplied result returning `T` if `Ok` or `T::default` otherwise. If `Err` then set
 // `err` unless it's already set.
+// Computes the bounding box of the points of an outline, including control points
+#[cfg(feature = "outline")]
+struct BoundingBoxSink(Option<BoundingBox>);
+
+#[cfg(feature = "outline")]
+impl BoundingBoxSink {
+    fn add(&mut self, point: Vector2F) {
+        let point = Point(point.x() as i16, point.y() as i16);
+        match &mut self.0 {
+            Some(bounding_box) => bounding_box.add(point),
+            None => self.0 = Some(BoundingBox::from_points(std::iter::once(point))),
+        }
+    }
+}
+
+#[cfg(feature = "outline")]
+impl OutlineSink for BoundingBoxSink {
+    fn move_to(&mut self, to: Vector2F) {
+        self.add(to);
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.add(to);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.add(ctrl);
+        self.add(to);
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.add(ctrl.from());
+        self.add(ctrl.to());
+        self.add(to);
+    }
+
+    fn close(&mut self) {}
+}
+
 fn check_set_err<T, E>(res: Result<T, E>, err: &mut Option<ShapingError>) -> T
 where
     E: Into<ShapingError>,
@@ -1002,6 +1733,37 @@ mod tests {
     use crate::tests::read_fixture;
     use std::error::Error;
 
+    #[test]
+    fn test_vertical_origin() {
+        let vertical_origins = |path: &str, glyphs: &[u16]| {
+            let font_buffer = read_fixture(path);
+            let opentype_file = ReadScope::new(&font_buffer)
+                .read::<OpenTypeFont<'_>>()
+                .unwrap();
+            let provider = opentype_file.table_provider(0).unwrap();
+            let mut font = Font::new(provider).unwrap();
+            glyphs
+                .iter()
+                .map(|&glyph| font.vertical_origin(glyph))
+                .collect::<Vec<_>>()
+        };
+        // From the glyph bounding box and top side bearing in `vmtx`
+        assert_eq!(
+            vertical_origins("tests/fonts/vertical/VerticalTest.ttf", &[3, 4]),
+            vec![880, 880]
+        );
+        // From `VORG`
+        assert_eq!(
+            vertical_origins("tests/fonts/vertical/VerticalVorgTest.ttf", &[3, 4]),
+            vec![850, 880]
+        );
+        // Centred between the ascender and descender, or at the ascender for the empty space
+        assert_eq!(
+            vertical_origins("tests/fonts/fallback/FallbackMarkTest.ttf", &[2, 1]),
+            vec![750, 800]
+        );
+    }
+
     #[test]
     fn test_glyph_names() {
         let font_buffer = read_fixture("tests/fonts/opentype/TwitterColorEmoji-SVGinOT.ttf");
@@ -27,3 +27,19 @@ pub fn mark_attach_class(opt_gdef_table: Option<&GDEFTable>, glyph: u16) -> u16
         None => 0,
     }
 }
+
+/// Returns true if `glyph` is in the mark glyph set with index `set_index`.
+///
+/// Returns false if the `GDEF` table has no such mark glyph set.
+pub fn is_in_mark_glyph_set(
+    opt_gdef_table: Option<&GDEFTable>,
+    set_index: u16,
+    glyph: u16,
+) -> bool {
+    opt_gdef_table
+        .and_then(|gdef_table| gdef_table.opt_mark_glyph_sets.as_ref())
+        .and_then(|mark_glyph_sets| mark_glyph_sets.get(usize::from(set_index)))
+        .map_or(false, |coverage| {
+            coverage.glyph_coverage_value(glyph).is_some()
+        })
+}
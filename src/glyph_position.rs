@@ -2,19 +2,30 @@
 //!
 //! [GlyphLayout] is used to obtain the positions for a collection of shaped glyphs. The position
 //! for each glyph includes its horizontal and vertical advance as well as any `(x, y)` offset from
-//! the origin. Horizontal layout in left-to-right and right-to-left directions is supported. Basic
-//! (but incomplete) support for vertical text is present too.
+//! the origin. Horizontal layout in left-to-right and right-to-left directions is supported, as is
+//! vertical layout from top to bottom. [GlyphLayout::for_script] lays out scripts that are written
+//! top to bottom by default, such as Mongolian, vertically.
 //!
 //! The position of a series of glyphs is determined from an initial pen position, which is
 //! incremented by the advance of each glyph as they are processed. The position of a particular
 //! glyph is the current pen position plus `x_offset` and `y_offset`.
+//!
+//! In vertical text the pen position is incremented by the vertical advance of each glyph.
+//! Upright glyphs, such as CJK ideographs, advance by their height from `vmtx`, and are drawn with
+//! their vertical origin (see [Font::vertical_origin]), rather than their horizontal origin, at
+//! their position. [GlyphPosition::vert_origin_x] and [GlyphPosition::vert_origin_y] give the
+//! vertical origin of these glyphs. Other glyphs are rotated sideways by the renderer and advance
+//! by their horizontal advance. When shaping vertical text, enable the `vert` or `vrt2` features
+//! to select vertical alternates, and the `vkrn` or `vpal` features for vertical positioning.
 
 use std::convert::TryFrom;
 
 use crate::context::Glyph;
 use crate::error::ParseError;
 use crate::gpos::{Info, Placement};
+use crate::tables::variable_fonts::Tuple;
 use crate::tables::FontTableProvider;
+use crate::tag;
 use crate::unicode::codepoint::is_upright_char;
 use crate::Font;
 
@@ -27,6 +38,7 @@ where
     infos: &'i [Info],
     direction: TextDirection,
     vertical: bool,
+    tuple: Option<Tuple<'i>>,
 }
 
 /// The position and advance of a glyph.
@@ -40,6 +52,10 @@ pub struct GlyphPosition {
     pub x_offset: i32,
     /// Offset in the Y (vertical) direction of this glyph
     pub y_offset: i32,
+    /// X coordinate of the vertical origin of an upright glyph in vertical text, or 0
+    pub vert_origin_x: i32,
+    /// Y coordinate of the vertical origin of an upright glyph in vertical text, or 0
+    pub vert_origin_y: i32,
     cursive_attachment: Option<u16>,
 }
 
@@ -70,17 +86,64 @@ impl<'f, 'i, T: FontTableProvider> GlyphLayout<'f, 'i, T> {
             infos,
             direction,
             vertical,
+            tuple: None,
         }
     }
 
+    /// Construct a new `GlyphLayout` instance for glyphs shaped with `script_tag`, laid out in the
+    /// default orientation of the script.
+    ///
+    /// The glyphs are laid out top to bottom if [is_vertical_script] returns `true` for the
+    /// script, and horizontally otherwise. Use [GlyphLayout::new] to choose the orientation.
+    pub fn for_script(
+        font: &'f mut Font<T>,
+        infos: &'i [Info],
+        direction: TextDirection,
+        script_tag: u32,
+    ) -> Self {
+        GlyphLayout::new(font, infos, direction, is_vertical_script(script_tag))
+    }
+
+    /// Lay out the glyphs at the variation instance `tuple` of a variable font, which should be
+    /// the same as the one passed to [Font::shape]. Glyph advances and vertical origins are
+    /// adjusted for the instance.
+    pub fn with_tuple(mut self, tuple: Option<Tuple<'i>>) -> Self {
+        self.tuple = tuple;
+        self
+    }
+
     /// Retrieve the glyphs positions.
     pub fn glyph_positions(&mut self) -> Result<Vec<GlyphPosition>, ParseError> {
         let mut has_marks = false;
         let mut has_cursive_connection = false;
         let mut positions = vec![GlyphPosition::default(); self.infos.len()];
 
+        let glyph_indices = self
+            .infos
+            .iter()
+            .map(Info::get_glyph_index)
+            .collect::<Vec<_>>();
+        let font_hori_advances = self.font_advances(&glyph_indices, false)?;
+        let (font_vert_advances, font_vert_origins) = if self.vertical {
+            (
+                self.font_advances(&glyph_indices, true)?,
+                self.font.vertical_origins(&glyph_indices, self.tuple)?,
+            )
+        } else {
+            (
+                vec![None; glyph_indices.len()],
+                vec![0; glyph_indices.len()],
+            )
+        };
+
         for (i, info) in self.infos.iter().enumerate() {
-            let (hori_advance, vert_advance) = glyph_advance(self.font, info, self.vertical)?;
+            let (hori_advance, vert_advance) = glyph_advance(
+                self.font,
+                info,
+                self.vertical,
+                font_hori_advances[i],
+                font_vert_advances[i],
+            )?;
             match info.placement {
                 Placement::None => positions[i].update(hori_advance, vert_advance, 0, 0),
                 Placement::Distance(dx, dy) => {
@@ -125,6 +188,17 @@ impl<'f, 'i, T: FontTableProvider> GlyphLayout<'f, 'i, T> {
                     positions[i] = new_glyph;
                 }
             };
+
+            // Upright glyphs in vertical text are drawn from their vertical origin, which is
+            // centred horizontally. Marks are given the origin of their base below.
+            let is_mark = matches!(
+                info.placement,
+                Placement::MarkAnchor(_, _, _) | Placement::MarkOverprint(_)
+            );
+            if self.vertical && !is_mark && is_upright_glyph(info) {
+                positions[i].vert_origin_x = font_hori_advances[i].map_or(0, i32::from) / 2;
+                positions[i].vert_origin_y = font_vert_origins[i];
+            }
         }
 
         if has_cursive_connection {
@@ -142,6 +216,27 @@ impl<'f, 'i, T: FontTableProvider> GlyphLayout<'f, 'i, T> {
         Ok(positions)
     }
 
+    // Returns the advances of the glyphs in the font, adjusted for the variation instance
+    fn font_advances(
+        &mut self,
+        glyph_indices: &[u16],
+        vertical: bool,
+    ) -> Result<Vec<Option<u16>>, ParseError> {
+        match self.tuple {
+            Some(tuple) => self.font.variation_advances(glyph_indices, tuple, vertical),
+            None => Ok(glyph_indices
+                .iter()
+                .map(|&glyph_index| {
+                    if vertical {
+                        self.font.vertical_advance(glyph_index)
+                    } else {
+                        self.font.horizontal_advance(glyph_index)
+                    }
+                })
+                .collect()),
+        }
+    }
+
     fn adjust_cursive_connections(&self, positions: &mut [GlyphPosition]) {
         for (i, info) in self.infos.iter().enumerate() {
             match info.placement {
@@ -181,15 +276,21 @@ impl<'f, 'i, T: FontTableProvider> GlyphLayout<'f, 'i, T> {
                     };
 
                     // Line-layout direction
-                    // TODO: Handle vertical text
+                    //
+                    // Sideways glyphs in vertical text, such as Mongolian, are rotated so their
+                    // horizontal advance is used as the vertical advance.
+                    // TODO: Handle upright glyphs in vertical text
+                    let first_advance = if self.vertical {
+                        &mut positions[first_glyph_index].vert_advance
+                    } else {
+                        &mut positions[first_glyph_index].hori_advance
+                    };
                     match self.direction {
                         TextDirection::LeftToRight => {
-                            positions[first_glyph_index].hori_advance =
-                                i32::from(entry_glyph_anchor.x)
+                            *first_advance = i32::from(entry_glyph_anchor.x)
                         }
                         TextDirection::RightToLeft => {
-                            positions[first_glyph_index].hori_advance +=
-                                i32::from(entry_glyph_anchor.x)
+                            *first_advance += i32::from(entry_glyph_anchor.x)
                         }
                     }
 
@@ -257,18 +358,32 @@ impl<'f, 'i, T: FontTableProvider> GlyphLayout<'f, 'i, T> {
                             position.y_offset += vert_advance_offset;
                         }
                     }
+
+                    // Draw the mark from the same origin as its base
+                    position.vert_origin_x = base_pos.vert_origin_x;
+                    position.vert_origin_y = base_pos.vert_origin_y;
                 }
                 Placement::MarkOverprint(base_index) => {
                     let base_pos = positions[base_index];
                     let position = &mut positions[i];
                     position.x_offset = base_pos.x_offset;
                     position.y_offset = base_pos.y_offset;
+                    position.vert_origin_x = base_pos.vert_origin_x;
+                    position.vert_origin_y = base_pos.vert_origin_y;
                 }
             }
         }
     }
 }
 
+/// Returns `true` if text in the script is laid out top to bottom by default.
+///
+/// Mongolian glyphs are designed sideways so in vertical text they are rotated and advance by
+/// their horizontal advance.
+pub fn is_vertical_script(script_tag: u32) -> bool {
+    script_tag == tag::MONG
+}
+
 impl GlyphPosition {
     pub const fn new(hori_advance: i32, vert_advance: i32, x_offset: i32, y_offset: i32) -> Self {
         GlyphPosition {
@@ -276,6 +391,8 @@ impl GlyphPosition {
             vert_advance,
             x_offset,
             y_offset,
+            vert_origin_x: 0,
+            vert_origin_y: 0,
             cursive_attachment: None,
         }
     }
@@ -299,6 +416,8 @@ impl PartialEq for GlyphPosition {
             && self.vert_advance == other.vert_advance
             && self.x_offset == other.x_offset
             && self.y_offset == other.y_offset
+            && self.vert_origin_x == other.vert_origin_x
+            && self.vert_origin_y == other.vert_origin_y
     }
 }
 
@@ -326,19 +445,18 @@ fn sum_advance(positions: Option<&[GlyphPosition]>) -> (i32, i32) {
 }
 
 fn glyph_advance<T: FontTableProvider>(
-    font: &mut Font<T>,
+    font: &Font<T>,
     info: &Info,
     vertical: bool,
+    font_hori_advance: Option<u16>,
+    font_vert_advance: Option<u16>,
 ) -> Result<(i32, i32), ParseError> {
     let advance = if vertical && is_upright_glyph(info) {
-        font.vertical_advance(info.get_glyph_index())
-            .map(i32::from)
-            .unwrap_or_else(|| {
-                i32::from(font.hhea_table.ascender) - i32::from(font.hhea_table.descender)
-            })
-            + i32::from(info.kerning)
+        font_vert_advance.map(i32::from).unwrap_or_else(|| {
+            i32::from(font.hhea_table.ascender) - i32::from(font.hhea_table.descender)
+        }) + i32::from(info.vertical_kerning)
     } else {
-        font.horizontal_advance(info.get_glyph_index())
+        font_hori_advance
             .map(i32::from)
             .ok_or(ParseError::MissingValue)?
             + i32::from(info.kerning)
@@ -365,8 +483,9 @@ mod tests {
     use crate::font::MatchingPresentation;
     use crate::font_data::FontData;
     use crate::gsub::{FeatureMask, Features};
-    use crate::tag;
+    use crate::tables::Fixed;
     use crate::tests::read_fixture;
+    use crate::variations;
 
     fn get_positions(
         text: &str,
@@ -618,4 +737,186 @@ mod tests {
         assert_eq!(positions, expected);
         Ok(())
     }
+
+    #[test]
+    fn vertical_cjk() -> Result<(), Box<dyn Error>> {
+        let script = tag::HANI;
+        let lang = tag!(b"ZHS ");
+        // The ideographs and the vertical form of the comma are upright, with their vertical
+        // origins at y = 880. `vpal` moves the comma up by 250 and reduces its advance by 500,
+        // and `vkrn` reduces the space between the comma and the second ideograph by 200. The
+        // Latin letter is sideways.
+        let features = FeatureMask::default()
+            | FeatureMask::VRT2_OR_VERT
+            | FeatureMask::VKRN
+            | FeatureMask::VPAL;
+        let positions = get_positions_with_gpos_features(
+            "\u{4E00}\u{3001}\u{4E8C}A",
+            "vertical/VerticalTest.ttf",
+            script,
+            lang,
+            &Features::Mask(features),
+            TextDirection::LeftToRight,
+            true,
+        )?;
+        let upright = |vert_advance, y_offset| GlyphPosition {
+            vert_origin_x: 500,
+            vert_origin_y: 880,
+            ..GlyphPosition::new(0, vert_advance, 0, y_offset)
+        };
+        let expected = &[
+            upright(1000, 0),
+            upright(300, 250),
+            upright(1000, 0),
+            GlyphPosition::new(0, 600, 0, 0),
+        ];
+        assert_eq!(positions, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn vertical_features_in_horizontal_text() -> Result<(), Box<dyn Error>> {
+        let script = tag::HANI;
+        let lang = tag!(b"ZHS ");
+        // The vertical advance adjustments of `vpal` and `vkrn` don't change horizontal advances,
+        // although the `vpal` placement of the vertical comma still applies
+        let features = FeatureMask::default()
+            | FeatureMask::VRT2_OR_VERT
+            | FeatureMask::VKRN
+            | FeatureMask::VPAL;
+        let positions = get_positions_with_gpos_features(
+            "\u{4E00}\u{3001}\u{4E8C}",
+            "vertical/VerticalTest.ttf",
+            script,
+            lang,
+            &Features::Mask(features),
+            TextDirection::LeftToRight,
+            false,
+        )?;
+        let expected = &[
+            GlyphPosition::new(1000, 0, 0, 0),
+            GlyphPosition::new(1000, 0, 0, 250),
+            GlyphPosition::new(1000, 0, 0, 0),
+        ];
+        assert_eq!(positions, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn vertical_mongolian() -> Result<(), Box<dyn Error>> {
+        let script = tag::MONG;
+        let lang = tag!(b"MNG ");
+        assert!(is_vertical_script(script));
+        assert!(!is_vertical_script(tag::LATN));
+
+        let data = read_fixture("tests/fonts/mongolian/MongolianTest.ttf");
+        let scope = ReadScope::new(&data);
+        let font_file = scope.read::<FontData<'_>>()?;
+        let provider = font_file.table_provider(0)?;
+        let mut font = Font::new(provider)?;
+        let glyphs = font.map_glyphs(
+            "\u{182A}\u{1820}",
+            script,
+            MatchingPresentation::NotRequired,
+        );
+        let infos = font
+            .shape(
+                glyphs,
+                script,
+                Some(lang),
+                &Features::Mask(FeatureMask::default()),
+                None,
+                true,
+            )
+            .map_err(|(err, _info)| err)?;
+
+        // Mongolian is laid out top to bottom by default. The glyphs are rotated in vertical text
+        // so they advance by their horizontal advance
+        let positions =
+            GlyphLayout::for_script(&mut font, &infos, TextDirection::LeftToRight, script)
+                .glyph_positions()?;
+        let expected = &[
+            GlyphPosition {
+                vert_advance: 600,
+                ..Default::default()
+            },
+            GlyphPosition {
+                vert_advance: 600,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(positions, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn variable_advances_hvar() -> Result<(), Box<dyn Error>> {
+        // The advances are adjusted by the `HVAR` table
+        let [default, varied, instanced] = get_variable_positions(
+            "abc",
+            "variable/Inter[slnt,wght].abc.ttf",
+            &[Fixed::from(700), Fixed::from(0)],
+        )?;
+        assert_ne!(varied, default);
+        assert_eq!(varied, instanced);
+        Ok(())
+    }
+
+    #[test]
+    fn variable_advances_phantom_points() -> Result<(), Box<dyn Error>> {
+        // Without `HVAR` the advances come from the phantom points in `gvar`
+        let [default, varied, instanced] = get_variable_positions(
+            "abc",
+            "variable/Inter[slnt,wght].nohvar.abc.ttf",
+            &[Fixed::from(700), Fixed::from(-10)],
+        )?;
+        assert_ne!(varied, default);
+        assert_eq!(varied, instanced);
+        Ok(())
+    }
+
+    // Returns the positions of `text` at the default instance, at `user_tuple`, and in a static
+    // instance of the font created for `user_tuple`
+    fn get_variable_positions(
+        text: &str,
+        font: &str,
+        user_tuple: &[Fixed],
+    ) -> Result<[Vec<GlyphPosition>; 3], Box<dyn Error>> {
+        let path = Path::new("tests/fonts").join(font);
+        let data = read_fixture(&path);
+        let scope = ReadScope::new(&data);
+        let font_file = scope.read::<FontData<'_>>()?;
+        let provider = font_file.table_provider(0)?;
+        let (instance_data, tuple) = variations::instance(&provider, user_tuple)?;
+        let mut font = Font::new(provider)?;
+        let default = layout(&mut font, text, None)?;
+        let varied = layout(&mut font, text, Some(tuple.as_tuple()))?;
+
+        let scope = ReadScope::new(&instance_data);
+        let font_file = scope.read::<FontData<'_>>()?;
+        let mut instance = Font::new(font_file.table_provider(0)?)?;
+        let instanced = layout(&mut instance, text, None)?;
+        Ok([default, varied, instanced])
+    }
+
+    fn layout<T: FontTableProvider>(
+        font: &mut Font<T>,
+        text: &str,
+        tuple: Option<Tuple<'_>>,
+    ) -> Result<Vec<GlyphPosition>, Box<dyn Error>> {
+        let glyphs = font.map_glyphs(text, tag::LATN, MatchingPresentation::NotRequired);
+        let infos = font
+            .shape(
+                glyphs,
+                tag::LATN,
+                None,
+                &Features::Mask(FeatureMask::default()),
+                tuple,
+                false,
+            )
+            .map_err(|(err, _info)| err)?;
+        let mut layout =
+            GlyphLayout::new(font, &infos, TextDirection::LeftToRight, false).with_tuple(tuple);
+        layout.glyph_positions().map_err(|err| err.into())
+    }
 }
@@ -7,12 +7,13 @@
 //! — <https://docs.microsoft.com/en-us/typography/opentype/spec/gpos>
 use itertools::Itertools;
 use tinyvec::tiny_vec;
+use unicode_canonical_combining_class::get_canonical_combining_class;
 use unicode_general_category::GeneralCategory;
 
 use crate::context::{ContextLookupHelper, Glyph, LookupFlag, MatchType};
 use crate::error::ParseError;
 use crate::gdef::gdef_is_mark;
-use crate::gsub::{FeatureInfo, Features, RawGlyph};
+use crate::gsub::{self, FeatureInfo, Features, RawGlyph};
 use crate::layout::{
     chain_context_lookup_info, context_lookup_info, Adjust, Anchor, ChainContextLookup,
     ContextLookup, CursivePos, GDEFTable, LangSys, LayoutCache, LayoutTable, LookupList,
@@ -20,6 +21,8 @@ use crate::layout::{
 };
 use crate::scripts;
 use crate::scripts::ScriptType;
+use crate::tables::glyf::BoundingBox;
+use crate::tables::kern::KernTable;
 use crate::tables::variable_fonts::fvar::Tuple;
 use crate::tables::variable_fonts::owned;
 use crate::tag;
@@ -39,25 +42,7 @@ pub fn apply(
 ) -> Result<(), ParseError> {
     let gpos_table = &gpos_cache.layout_table;
     let script_type = ScriptType::from(script_tag);
-
-    let script = match script_type {
-        ScriptType::Indic => {
-            let indic2_tag = scripts::indic::indic2_tag(script_tag);
-            match gpos_table.find_script(indic2_tag)? {
-                Some(script) => script,
-                None => match gpos_table.find_script_or_default(script_tag)? {
-                    Some(script) => script,
-                    None => return Ok(()),
-                },
-            }
-        }
-        _ => match gpos_table.find_script_or_default(script_tag)? {
-            Some(script) => script,
-            None => return Ok(()),
-        },
-    };
-
-    let langsys = match script.find_langsys_or_default(opt_lang_tag)? {
+    let langsys = match find_langsys(gpos_table, script_tag, opt_lang_tag)? {
         Some(langsys) => langsys,
         None => return Ok(()),
     };
@@ -73,10 +58,21 @@ pub fn apply(
             tag::MKMK,
         ],
         ScriptType::Khmer => &[tag::ABVM, tag::BLWM, tag::DIST, tag::MARK, tag::MKMK],
+        ScriptType::Mongolian => &[tag::CURS, tag::KERN, tag::MARK, tag::MKMK],
         ScriptType::Syriac => &[tag::CURS, tag::KERN, tag::MARK, tag::MKMK],
         ScriptType::ThaiLao => &[tag::KERN, tag::MARK, tag::MKMK],
-        ScriptType::Default if kerning => &[tag::DIST, tag::KERN, tag::MARK, tag::MKMK],
-        ScriptType::Default => &[tag::DIST, tag::MARK, tag::MKMK],
+        ScriptType::Myanmar | ScriptType::Tibetan | ScriptType::Universal => &[
+            tag::ABVM,
+            tag::BLWM,
+            tag::DIST,
+            tag::KERN,
+            tag::MARK,
+            tag::MKMK,
+        ],
+        ScriptType::Default | ScriptType::Hangul if kerning => {
+            &[tag::DIST, tag::KERN, tag::MARK, tag::MKMK]
+        }
+        ScriptType::Default | ScriptType::Hangul => &[tag::DIST, tag::MARK, tag::MKMK],
     };
 
     apply_features(
@@ -91,6 +87,7 @@ pub fn apply(
         tuple,
         infos,
     )?;
+
     match features {
         Features::Custom(custom) => apply_features(
             gpos_cache,
@@ -101,18 +98,86 @@ pub fn apply(
             tuple,
             infos,
         ),
-        Features::Mask(mask) => apply_features(
-            gpos_cache,
-            gpos_table,
-            opt_gdef_table,
-            langsys,
-            mask.iter(),
-            tuple,
-            infos,
-        ),
+        Features::Mask(mask) => {
+            // Lookups are applied to each glyph according to its own feature mask, if it has one
+            let glyph_masks = gsub::with_glyph_masks(*mask, infos.iter().map(|info| &info.glyph));
+            apply_features_impl(
+                gpos_cache,
+                gpos_table,
+                opt_gdef_table,
+                langsys,
+                glyph_masks.iter(),
+                tuple,
+                infos,
+                |info, feature_tag| features.enabled_for(&info.glyph, feature_tag),
+            )
+        }
     }
 }
 
+/// Returns `true` if the `GPOS` table has a `kern` feature for the script and language.
+///
+/// When it doesn't, kerning can be applied from the legacy `kern` table with
+/// [apply_kern_table] instead.
+pub fn has_kern_feature(
+    gpos_cache: &LayoutCache<GPOS>,
+    script_tag: u32,
+    opt_lang_tag: Option<u32>,
+    tuple: Option<Tuple<'_>>,
+) -> Result<bool, ParseError> {
+    has_feature(gpos_cache, script_tag, opt_lang_tag, tuple, tag::KERN)
+}
+
+fn has_feature(
+    gpos_cache: &LayoutCache<GPOS>,
+    script_tag: u32,
+    opt_lang_tag: Option<u32>,
+    tuple: Option<Tuple<'_>>,
+    feature_tag: u32,
+) -> Result<bool, ParseError> {
+    let gpos_table = &gpos_cache.layout_table;
+    let langsys = match find_langsys(gpos_table, script_tag, opt_lang_tag)? {
+        Some(langsys) => langsys,
+        None => return Ok(false),
+    };
+    let feature_variations = gpos_table.feature_variations(tuple)?;
+    let feature_table =
+        gpos_table.find_langsys_feature(langsys, feature_tag, feature_variations.as_ref())?;
+    Ok(feature_table.is_some())
+}
+
+fn find_langsys(
+    gpos_table: &LayoutTable<GPOS>,
+    script_tag: u32,
+    opt_lang_tag: Option<u32>,
+) -> Result<Option<&LangSys>, ParseError> {
+    let script = match ScriptType::from(script_tag) {
+        ScriptType::Indic => {
+            let indic1_tag = scripts::indic::indic1_tag(script_tag);
+            let indic2_tag = scripts::indic::indic2_tag(indic1_tag);
+            match gpos_table.find_script(indic2_tag)? {
+                Some(script) => script,
+                None => match gpos_table.find_script_or_default(indic1_tag)? {
+                    Some(script) => script,
+                    None => return Ok(None),
+                },
+            }
+        }
+        ScriptType::Myanmar => {
+            let myanmar_tag = scripts::myanmar::myanmar_tag(gpos_table)?;
+            match gpos_table.find_script_or_default(myanmar_tag)? {
+                Some(script) => script,
+                None => return Ok(None),
+            }
+        }
+        _ => match gpos_table.find_script_or_default(script_tag)? {
+            Some(script) => script,
+            None => return Ok(None),
+        },
+    };
+    script.find_langsys_or_default(opt_lang_tag)
+}
+
 /// Apply glyph positioning using specified OpenType features.
 ///
 /// Generally prefer to use [apply], which will enable features based on script and language.
@@ -125,6 +190,31 @@ pub fn apply_features(
     features: impl Iterator<Item = FeatureInfo>,
     tuple: Option<Tuple<'_>>,
     infos: &mut [Info],
+) -> Result<(), ParseError> {
+    apply_features_impl(
+        gpos_cache,
+        gpos_table,
+        opt_gdef_table,
+        langsys,
+        features,
+        tuple,
+        infos,
+        |_, _| true,
+    )
+}
+
+// `pred` is called with each glyph and the tag of the feature of the lookup, and returns `true`
+// if the lookup should be applied to the glyph. The other arguments are those of `apply_features`.
+#[allow(clippy::too_many_arguments)]
+fn apply_features_impl(
+    gpos_cache: &LayoutCache<GPOS>,
+    gpos_table: &LayoutTable<GPOS>,
+    opt_gdef_table: Option<&GDEFTable>,
+    langsys: &LangSys,
+    features: impl Iterator<Item = FeatureInfo>,
+    tuple: Option<Tuple<'_>>,
+    infos: &mut [Info],
+    pred: impl Fn(&Info, u32) -> bool,
 ) -> Result<(), ParseError> {
     let mut lookup_indices = tiny_vec!([u16; 128]);
     let feature_variations = gpos_table.feature_variations(tuple)?;
@@ -148,6 +238,7 @@ pub fn apply_features(
                     lookup_index,
                     tuple,
                     infos,
+                    |info| pred(info, feature.feature_tag),
                 )?;
             }
         }
@@ -174,6 +265,203 @@ pub fn apply_fallback(infos: &mut [Info]) {
     }
 }
 
+/// Position marks from the bounding boxes of glyph outlines.
+///
+/// Use this when the font has no `gpos` table. Like the HarfBuzz fallback shaper, marks are
+/// placed above, below, or over the preceding base glyph according to their canonical combining
+/// class, and marks on the same side of a base are stacked so they don't overlap. Marks without
+/// a placement class, such as nuktas and viramas, and marks that already have an anchored
+/// placement are left unchanged.
+///
+/// `bounding_boxes` holds the bounding box of the glyph of each `Info`, or `None` if it has no
+/// outline, and `advances` holds the horizontal advance of each glyph. The advance of positioned
+/// marks is cancelled with `kerning`, as it would be for overprinted marks.
+pub fn apply_fallback_mark_positioning(
+    infos: &mut [Info],
+    bounding_boxes: &[Option<BoundingBox>],
+    advances: &[u16],
+    units_per_em: u16,
+) {
+    let gap = i32::from(units_per_em / 16);
+    let mut opt_base: Option<(usize, Extents)> = None;
+    for (i, info) in infos.iter_mut().enumerate() {
+        let bounding_box = bounding_boxes.get(i).copied().flatten();
+        if !info.is_mark {
+            let extents = bounding_box.map(Extents::from).unwrap_or_default();
+            opt_base = Some((i, extents));
+            continue;
+        }
+        let Some((base_index, base_extents)) = opt_base.as_mut() else {
+            continue;
+        };
+        if !matches!(
+            info.placement,
+            Placement::None | Placement::MarkOverprint(_)
+        ) {
+            continue;
+        }
+        let class = match info.glyph.unicodes.first() {
+            Some(&ch) => fallback_combining_class(ch),
+            None => continue,
+        };
+        let (Some(mark_bounding_box), true) = (bounding_box, is_placement_class(class)) else {
+            continue;
+        };
+        let (dx, dy) = position_mark(class, base_extents, Extents::from(mark_bounding_box), gap);
+        info.placement = Placement::MarkAnchor(
+            *base_index,
+            Anchor {
+                x: clamp_i16(dx),
+                y: clamp_i16(dy),
+            },
+            Anchor { x: 0, y: 0 },
+        );
+        let advance = advances.get(i).copied().unwrap_or(0);
+        info.kerning = clamp_i16(i32::from(info.kerning) - i32::from(advance));
+    }
+}
+
+// The extents of a base glyph and the marks stacked on it so far
+#[derive(Copy, Clone, Default)]
+struct Extents {
+    x_min: i32,
+    x_max: i32,
+    y_min: i32,
+    y_max: i32,
+}
+
+impl From<BoundingBox> for Extents {
+    fn from(bounding_box: BoundingBox) -> Self {
+        Extents {
+            x_min: i32::from(bounding_box.x_min),
+            x_max: i32::from(bounding_box.x_max),
+            y_min: i32::from(bounding_box.y_min),
+            y_max: i32::from(bounding_box.y_max),
+        }
+    }
+}
+
+// Canonical combining classes used for placement
+const OVERLAY: u8 = 1;
+const ATTACHED_BELOW_LEFT: u8 = 200;
+const ATTACHED_BELOW: u8 = 202;
+const ATTACHED_ABOVE: u8 = 214;
+const ATTACHED_ABOVE_RIGHT: u8 = 216;
+const BELOW_LEFT: u8 = 218;
+const BELOW: u8 = 220;
+const BELOW_RIGHT: u8 = 222;
+const ABOVE_LEFT: u8 = 228;
+const ABOVE: u8 = 230;
+const ABOVE_RIGHT: u8 = 232;
+const DOUBLE_BELOW: u8 = 233;
+const DOUBLE_ABOVE: u8 = 234;
+
+// Overlays and the classes at or above 200 say where a mark goes. Other classes, like nukta (7)
+// and virama (9), are about ordering, so those marks are left where the font put them.
+fn is_placement_class(class: u8) -> bool {
+    class == OVERLAY || class >= ATTACHED_BELOW_LEFT
+}
+
+// Returns the offset of the mark from the origin of the base, and extends the base extents to
+// include the mark
+fn position_mark(class: u8, base: &mut Extents, mark: Extents, gap: i32) -> (i32, i32) {
+    let dx = match class {
+        ATTACHED_BELOW_LEFT | BELOW_LEFT | ABOVE_LEFT => base.x_min - mark.x_min,
+        ATTACHED_ABOVE_RIGHT | BELOW_RIGHT | ABOVE_RIGHT => base.x_max - mark.x_max,
+        _ => {
+            let base_width = base.x_max - base.x_min;
+            let mark_width = mark.x_max - mark.x_min;
+            base.x_min + (base_width - mark_width) / 2 - mark.x_min
+        }
+    };
+    let dy = match class {
+        ATTACHED_BELOW_LEFT | ATTACHED_BELOW | BELOW_LEFT | BELOW | BELOW_RIGHT | DOUBLE_BELOW => {
+            let gap = if class <= ATTACHED_BELOW { 0 } else { gap };
+            // Never shift "below" marks up
+            let dy = (base.y_min - gap - mark.y_max).min(0);
+            base.y_min = mark.y_min + dy;
+            dy
+        }
+        ATTACHED_ABOVE | ATTACHED_ABOVE_RIGHT | ABOVE_LEFT | ABOVE | ABOVE_RIGHT | DOUBLE_ABOVE => {
+            let gap = if class <= ATTACHED_ABOVE_RIGHT {
+                0
+            } else {
+                gap
+            };
+            let mut dy = base.y_max + gap - mark.y_min;
+            // Don't shift "above" marks down too far
+            if dy < 0 {
+                dy -= dy / 2;
+            }
+            base.y_max = mark.y_max + dy;
+            dy
+        }
+        _ => 0,
+    };
+    (dx, dy)
+}
+
+// Returns the canonical combining class of `ch`, with the fixed position classes of Hebrew,
+// Arabic, Thai, Lao, and Tibetan mapped to the placement classes, as HarfBuzz does
+fn fallback_combining_class(ch: char) -> u8 {
+    let class = get_canonical_combining_class(ch) as u8;
+    if class >= 200 {
+        return class;
+    }
+    match (ch, class) {
+        // Thai and Lao marks with class 0
+        ('\u{0E31}' | '\u{0E34}'..='\u{0E37}' | '\u{0E47}' | '\u{0E4C}'..='\u{0E4E}', 0) => {
+            ABOVE_RIGHT
+        }
+        ('\u{0EB1}' | '\u{0EB4}'..='\u{0EB7}' | '\u{0EBB}' | '\u{0ECC}' | '\u{0ECD}', 0) => ABOVE,
+        ('\u{0EBC}', 0) => BELOW,
+        // Thai phinthu
+        ('\u{0E3A}', _) => BELOW_RIGHT,
+        // Hebrew
+        (_, 10..=18 | 20 | 22) => BELOW,
+        (_, 23) => ATTACHED_ABOVE,
+        (_, 24) => ABOVE_RIGHT,
+        (_, 19 | 25) => ABOVE_LEFT,
+        (_, 26) => ABOVE,
+        // Arabic and Syriac
+        (_, 27 | 28 | 30 | 31 | 33..=36) => ABOVE,
+        (_, 29 | 32) => BELOW,
+        // Thai
+        (_, 103) => BELOW_RIGHT,
+        (_, 107) => ABOVE_RIGHT,
+        // Lao
+        (_, 118) => BELOW,
+        (_, 122) => ABOVE,
+        // Tibetan
+        (_, 129 | 132) => BELOW,
+        (_, 130) => ABOVE,
+        _ => class,
+    }
+}
+
+fn clamp_i16(value: i32) -> i16 {
+    value.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16
+}
+
+/// Apply kerning from the legacy `kern` table.
+///
+/// Use this when the font has no `gpos` table, or when [has_kern_feature] is `false`. Marks are
+/// skipped, so the glyphs either side of them are kerned as a pair.
+pub fn apply_kern_table(kern_table: &KernTable<'_>, infos: &mut [Info]) -> Result<(), ParseError> {
+    let (indices, glyphs): (Vec<usize>, Vec<u16>) = infos
+        .iter()
+        .enumerate()
+        .filter(|(_, info)| !info.is_mark)
+        .map(|(i, info)| (i, info.glyph.glyph_index))
+        .unzip();
+    let adjustments = kern_table.horizontal_kerning(&glyphs)?;
+    for (i, adjustment) in indices.into_iter().zip(adjustments) {
+        let info = &mut infos[i];
+        info.kerning = info.kerning.saturating_add(adjustment as i16);
+    }
+    Ok(())
+}
+
 fn unicodes_are_marks(unicodes: &[char]) -> bool {
     unicodes
         .iter()
@@ -189,13 +477,20 @@ fn gpos_apply_lookup(
     lookup_index: usize,
     tuple: Option<Tuple<'_>>,
     infos: &mut [Info],
+    pred: impl Fn(&Info) -> bool,
 ) -> Result<(), ParseError> {
     if let Some(ref lookup_list) = gpos_table.opt_lookup_list {
         let lookup = lookup_list.lookup_cache_gpos(gpos_cache, lookup_index)?;
-        let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+        let match_type = MatchType::from_lookup(&lookup);
+        // Pairs are only positioned if the lookup applies to both glyphs, and marks if it applies
+        // to the mark
+        let pair_pred = |i1: usize, i2: usize, infos: &[Info]| pred(&infos[i1]) && pred(&infos[i2]);
         match lookup.lookup_subtables {
             PosLookup::SinglePos(ref subtables) => {
                 forall_glyphs_match(match_type, opt_gdef_table, infos, |i, infos| {
+                    if !pred(&infos[i]) {
+                        return Ok(());
+                    }
                     singlepos(subtables, tuple, opt_gdef_table, &mut infos[i])
                 })
             }
@@ -204,6 +499,9 @@ fn gpos_apply_lookup(
                 // not repositioned, ie. if the value_format is zero, but applying the lookup
                 // regardless does not break any test cases.
                 forall_glyph_pairs_match(match_type, opt_gdef_table, infos, |i1, i2, infos| {
+                    if !pair_pred(i1, i2, infos) {
+                        return Ok(());
+                    }
                     pairpos(subtables, tuple, opt_gdef_table, i1, i2, infos)
                 })
             }
@@ -211,25 +509,42 @@ fn gpos_apply_lookup(
                 MatchType::ignore_marks(),
                 opt_gdef_table,
                 infos,
-                |i1, i2, infos| cursivepos(subtables, i1, i2, lookup.lookup_flag, infos),
+                |i1, i2, infos| {
+                    if !pair_pred(i1, i2, infos) {
+                        return Ok(());
+                    }
+                    cursivepos(subtables, i1, i2, lookup.lookup_flag, infos)
+                },
             ),
             PosLookup::MarkBasePos(ref subtables) => {
-                forall_base_mark_glyph_pairs(infos, |i1, i2, infos| {
+                forall_base_mark_glyph_pairs(match_type, opt_gdef_table, infos, |i1, i2, infos| {
+                    if !pred(&infos[i2]) {
+                        return Ok(());
+                    }
                     markbasepos(subtables, i1, i2, infos)
                 })
             }
             PosLookup::MarkLigPos(ref subtables) => {
-                forall_base_mark_glyph_pairs(infos, |i1, i2, infos| {
+                forall_base_mark_glyph_pairs(match_type, opt_gdef_table, infos, |i1, i2, infos| {
+                    if !pred(&infos[i2]) {
+                        return Ok(());
+                    }
                     markligpos(subtables, i1, i2, infos)
                 })
             }
             PosLookup::MarkMarkPos(ref subtables) => {
-                forall_mark_mark_glyph_pairs(infos, |i1, i2, infos| {
+                forall_mark_mark_glyph_pairs(match_type, opt_gdef_table, infos, |i1, i2, infos| {
+                    if !pred(&infos[i2]) {
+                        return Ok(());
+                    }
                     markmarkpos(subtables, i1, i2, infos)
                 })
             }
             PosLookup::ContextPos(ref subtables) => {
                 forall_glyphs_match(match_type, opt_gdef_table, infos, |i, infos| {
+                    if !pred(&infos[i]) {
+                        return Ok(());
+                    }
                     contextpos(
                         gpos_cache,
                         lookup_list,
@@ -244,6 +559,9 @@ fn gpos_apply_lookup(
             }
             PosLookup::ChainContextPos(ref subtables) => {
                 forall_glyphs_match(match_type, opt_gdef_table, infos, |i, infos| {
+                    if !pred(&infos[i]) {
+                        return Ok(());
+                    }
                     chaincontextpos(
                         gpos_cache,
                         lookup_list,
@@ -450,10 +768,13 @@ pub struct Info {
     pub glyph: RawGlyph<()>,
     /// An offset from the horizontal glyph advance position for this glyph.
     pub kerning: i16,
+    /// An offset from the vertical glyph advance position for this glyph, from features such as
+    /// `vkrn` and `vpal`. It's used instead of `kerning` for upright glyphs in vertical text.
+    pub vertical_kerning: i16,
     /// When not `Placement::None` indicates that this glyph should be placed according to
     /// the variant.
     pub placement: Placement,
-    is_mark: bool,
+    pub(crate) is_mark: bool,
 }
 
 impl Glyph for Info {
@@ -473,6 +794,7 @@ impl Info {
             let info = Info {
                 glyph,
                 kerning: 0,
+                vertical_kerning: 0,
                 placement: Placement::None,
                 is_mark,
             };
@@ -486,30 +808,21 @@ impl Adjust {
     fn apply(&self, tuple: Option<Tuple<'_>>, opt_gdef_table: Option<&GDEFTable>, info: &mut Info) {
         let variation_store =
             opt_gdef_table.and_then(|gdef| gdef.opt_item_variation_store.as_ref());
-        if self.x_placement == 0 && self.y_placement == 0 {
-            if self.x_advance != 0 && self.y_advance == 0 {
-                info.kerning +=
-                    self.x_advance + self.x_advance_delta(tuple, variation_store).round() as i16;
-            } else if self.y_advance != 0 {
-                // error: y_advance non-zero
-            } else {
-                // both zero, but delta could still be present
-                let x_advance_delta = self.x_advance_delta(tuple, variation_store).round() as i16;
-                info.kerning += x_advance_delta;
-            }
-        } else if self.y_advance == 0 {
+        if self.x_placement != 0 || self.y_placement != 0 {
             let x_placement = i32::from(self.x_placement)
                 + self.x_placement_delta(tuple, variation_store).round() as i32;
             let y_placement = i32::from(self.y_placement)
                 + self.y_placement_delta(tuple, variation_store).round() as i32;
             info.placement.combine_distance(x_placement, y_placement);
-
-            let x_advance =
-                self.x_advance + self.x_advance_delta(tuple, variation_store).round() as i16;
-            info.kerning += x_advance;
-        } else {
-            // error: y_advance non-zero
         }
+
+        // Horizontal features such as `kern` adjust the x advance, and vertical features such as
+        // `vkrn` and `vpal` adjust the y advance. Which is used depends on how the glyph is laid
+        // out.
+        info.kerning +=
+            self.x_advance + self.x_advance_delta(tuple, variation_store).round() as i16;
+        info.vertical_kerning +=
+            self.y_advance + self.y_advance_delta(tuple, variation_store).round() as i16;
     }
 
     pub fn x_advance_delta(
@@ -587,7 +900,10 @@ fn forall_glyph_pairs_match(
     Ok(())
 }
 
+// Marks that don't match `match_type` are skipped
 fn forall_base_mark_glyph_pairs(
+    match_type: MatchType,
+    opt_gdef_table: Option<&GDEFTable>,
     infos: &mut [Info],
     f: impl Fn(usize, usize, &mut [Info]) -> Result<(), ParseError>,
 ) -> Result<(), ParseError> {
@@ -595,7 +911,9 @@ fn forall_base_mark_glyph_pairs(
     'outer: while i + 1 < infos.len() {
         if !infos[i].is_mark {
             for j in i + 1..infos.len() {
-                f(i, j, infos)?;
+                if match_type.match_glyph(opt_gdef_table, &infos[j]) {
+                    f(i, j, infos)?;
+                }
                 if !infos[j].is_mark {
                     i = j;
                     continue 'outer;
@@ -607,7 +925,10 @@ fn forall_base_mark_glyph_pairs(
     Ok(())
 }
 
+// Marks that don't match `match_type` are skipped
 fn forall_mark_mark_glyph_pairs(
+    match_type: MatchType,
+    opt_gdef_table: Option<&GDEFTable>,
     infos: &mut [Info],
     f: impl Fn(usize, usize, &mut [Info]) -> Result<(), ParseError>,
 ) -> Result<(), ParseError> {
@@ -615,7 +936,7 @@ fn forall_mark_mark_glyph_pairs(
     'outer: loop {
         let mut i = start;
         while i + 1 < infos.len() {
-            if infos[i].is_mark {
+            if infos[i].is_mark && match_type.match_glyph(opt_gdef_table, &infos[i]) {
                 // infos[i] is the base mark. Scan forward looking for attaching marks
                 for j in i + 1..infos.len() {
                     if !infos[j].is_mark {
@@ -624,6 +945,9 @@ fn forall_mark_mark_glyph_pairs(
                     }
 
                     // infos[j] is a candidate attaching mark
+                    if !match_type.match_glyph(opt_gdef_table, &infos[j]) {
+                        continue;
+                    }
                     if infos[i].glyph.liga_component_pos == infos[j].glyph.liga_component_pos {
                         f(i, j, infos)?;
                     } else if infos[i].glyph.ligature() || infos[j].glyph.ligature() {
@@ -848,7 +1172,7 @@ fn apply_pos(
     index: usize,
 ) -> Result<(), ParseError> {
     let lookup = lookup_list.lookup_cache_gpos(gpos_cache, lookup_index)?;
-    let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+    let match_type = MatchType::from_lookup(&lookup);
     let i1 = match match_type.find_nth(opt_gdef_table, infos, index, pos_index) {
         Some(index1) => index1,
         None => return Ok(()),
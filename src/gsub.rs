@@ -17,10 +17,11 @@ use tinyvec::{tiny_vec, TinyVec};
 use crate::context::{ContextLookupHelper, Glyph, GlyphTable, MatchType};
 use crate::error::{ParseError, ShapingError};
 use crate::layout::{
-    chain_context_lookup_info, context_lookup_info, AlternateSet, AlternateSubst,
-    ChainContextLookup, ContextLookup, FeatureTableSubstitution, GDEFTable, LangSys, LayoutCache,
-    LayoutTable, Ligature, LigatureSubst, LookupCacheItem, LookupList, MultipleSubst,
-    ReverseChainSingleSubst, SequenceTable, SingleSubst, SubstLookup, GSUB,
+    chain_context_lookup_info, context_lookup_info, is_character_variant, is_stylistic_set,
+    AlternateSet, AlternateSubst, ChainContextLookup, ContextLookup, FeatureParams,
+    FeatureTableSubstitution, GDEFTable, LangSys, LayoutCache, LayoutTable, Ligature,
+    LigatureSubst, LookupCacheItem, LookupList, MultipleSubst, ReverseChainSingleSubst,
+    SequenceTable, SingleSubst, SubstLookup, GSUB,
 };
 use crate::scripts::{self, ScriptType};
 use crate::tables::variable_fonts::Tuple;
@@ -59,6 +60,59 @@ impl Default for Features {
     }
 }
 
+impl Features {
+    /// Check that the feature masks of `glyphs` can be used when shaping with these features.
+    ///
+    /// A glyph feature mask replaces the feature mask passed to shaping, so it can't be used
+    /// with a custom feature list.
+    pub(crate) fn check_glyph_masks<'a, T: 'a>(
+        &self,
+        mut glyphs: impl Iterator<Item = &'a RawGlyph<T>>,
+    ) -> Result<(), ShapingError> {
+        match self {
+            Features::Custom(_) if glyphs.any(|glyph| glyph.feature_mask.is_some()) => {
+                Err(ShapingError::GlyphFeatureMask)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the feature `feature_tag` is enabled for `glyph`.
+    ///
+    /// The feature mask of the glyph is used in place of these features if it has one. Features
+    /// that don't have a `FeatureMask` flag are always enabled by a feature mask.
+    pub(crate) fn enabled_for<T>(&self, glyph: &RawGlyph<T>, feature_tag: u32) -> bool {
+        match (self, glyph.feature_mask) {
+            (Features::Mask(_), Some(glyph_mask)) => {
+                glyph_mask.contains(FeatureMask::from_tag(feature_tag))
+            }
+            _ => self.is_enabled(feature_tag),
+        }
+    }
+
+    /// Returns `true` if the feature `feature_tag` can be applied with these features.
+    ///
+    /// Features that don't have a `FeatureMask` flag are always enabled by a feature mask.
+    pub(crate) fn is_enabled(&self, feature_tag: u32) -> bool {
+        match self {
+            Features::Mask(mask) => mask.contains(FeatureMask::from_tag(feature_tag)),
+            Features::Custom(features_list) => features_list
+                .iter()
+                .any(|feature_info| feature_info.feature_tag == feature_tag),
+        }
+    }
+}
+
+/// Returns `feature_mask` with the flags of the feature masks of any of `glyphs` added.
+pub(crate) fn with_glyph_masks<'a, T: 'a>(
+    feature_mask: FeatureMask,
+    glyphs: impl Iterator<Item = &'a RawGlyph<T>>,
+) -> FeatureMask {
+    glyphs
+        .filter_map(|glyph| glyph.feature_mask)
+        .fold(feature_mask, |mask, glyph_mask| mask | glyph_mask)
+}
+
 type SubstContext<'a> = ContextLookupHelper<'a, GSUB>;
 
 impl Ligature {
@@ -76,7 +130,9 @@ impl Ligature {
             glyphs,
             i,
             &mut last_index,
-        )
+        ) && glyphs[i + 1..=last_index]
+            .iter()
+            .all(|glyph| glyph.feature_mask == glyphs[i].feature_mask)
     }
 
     pub fn apply<T: GlyphData>(
@@ -89,8 +145,10 @@ impl Ligature {
         let mut index = i + 1;
         let mut matched = 0;
         let mut skip = 0;
+        let mut cluster = glyphs[i].cluster;
         while matched < self.component_glyphs.len() {
             if index < glyphs.len() {
+                cluster = cluster.min(glyphs[index].cluster);
                 if match_type.match_glyph(opt_gdef_table, &glyphs[index]) {
                     matched += 1;
                     let mut matched_glyph = glyphs.remove(index);
@@ -107,6 +165,10 @@ impl Ligature {
                 panic!("ran out of glyphs");
             }
         }
+        // The ligature and any glyphs skipped over while matching it form a single cluster
+        for glyph in &mut glyphs[i..index] {
+            glyph.cluster = cluster;
+        }
         while index < glyphs.len()
             && MatchType::marks_only().match_glyph(opt_gdef_table, &glyphs[index])
         {
@@ -125,8 +187,29 @@ pub struct RawGlyph<T> {
     pub glyph_index: u16,
     pub liga_component_pos: u16,
     pub glyph_origin: GlyphOrigin,
+    /// The cluster this glyph belongs to.
+    ///
+    /// When glyphs are produced by `Font::map_glyphs` this is the byte offset of the start of
+    /// the cluster in the source text. Substitution and reordering merge clusters such that they
+    /// never decrease in logical order, so a cluster spans the source text from its value up to
+    /// the value of the next cluster.
+    pub cluster: usize,
     pub flags: RawGlyphFlags,
     pub variation: Option<VariationSelector>,
+    /// The feature mask to apply to this glyph in place of the one passed to shaping.
+    ///
+    /// This is used to enable or disable features for part of the text, such as a span with its
+    /// own `font-feature-settings` in CSS. `None` applies the features passed to shaping. Glyphs
+    /// formed by a substitution keep the mask of the first glyph they were formed from, and
+    /// ligatures are only formed from glyphs with the same mask.
+    ///
+    /// The shapers of complex scripts apply the features the script requires to every glyph, but
+    /// skip the features of `FeatureMask::default()` that aren't in the mask of a glyph. The
+    /// `GPOS` features enabled by default, such as `mark`, are always applied.
+    ///
+    /// Glyph feature masks can only be used when shaping with `Features::Mask`. Shaping with
+    /// `Features::Custom` returns `ShapingError::GlyphFeatureMask` if any glyph has a mask.
+    pub feature_mask: Option<FeatureMask>,
     pub extra_data: T,
 }
 
@@ -138,6 +221,7 @@ bitflags! {
         const LIGATURE        = 1 << 3;
         const FAKE_BOLD       = 1 << 4;
         const FAKE_ITALIC     = 1 << 5;
+        const MIRRORED        = 1 << 6;
     }
 }
 
@@ -221,7 +305,7 @@ pub fn gsub_lookup_would_apply<T: GlyphData>(
     glyphs: &[RawGlyph<T>],
     i: usize,
 ) -> Result<bool, ParseError> {
-    let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+    let match_type = MatchType::from_lookup(lookup);
     if i < glyphs.len() && match_type.match_glyph(opt_gdef_table, &glyphs[i]) {
         return match lookup.lookup_subtables {
             SubstLookup::SingleSubst(ref subtables) => {
@@ -295,9 +379,19 @@ pub fn gsub_apply_lookup<T: GlyphData>(
     mut length: usize,
     pred: impl Fn(&RawGlyph<T>) -> bool,
 ) -> Result<usize, ParseError> {
+    // The shapers of complex scripts apply the default features to every glyph, so check that
+    // they haven't been disabled by the feature mask of the glyph
+    let default_feature = FeatureMask::from_tag(feature_tag) & FeatureMask::default();
+    let pred = |glyph: &RawGlyph<T>| {
+        pred(glyph)
+            && glyph
+                .feature_mask
+                .unwrap_or(default_feature)
+                .contains(default_feature)
+    };
     if let Some(ref lookup_list) = gsub_table.opt_lookup_list {
         let lookup = lookup_list.lookup_cache_gsub(gsub_cache, lookup_index)?;
-        let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+        let match_type = MatchType::from_lookup(&lookup);
         match lookup.lookup_subtables {
             SubstLookup::SingleSubst(ref subtables) => {
                 for glyph in glyphs[start..(start + length)].iter_mut() {
@@ -474,16 +568,18 @@ fn multiplesubst<T: GlyphData>(
                         glyph_index: output_glyph_index,
                         liga_component_pos: 0, //glyphs[i].liga_component_pos,
                         glyph_origin: GlyphOrigin::Direct,
+                        cluster: glyphs[i].cluster,
                         flags,
                         extra_data: glyphs[i].extra_data.clone(),
                         variation: glyphs[i].variation,
+                        feature_mask: glyphs[i].feature_mask,
                     };
                     glyphs.insert(i + j, glyph);
                 }
                 Ok(Some(sequence_table.substitute_glyphs.len()))
             } else {
                 // the spec forbids this, but implementations all allow it
-                glyphs.remove(i);
+                delete_glyph(glyphs, i);
                 Ok(Some(0))
             }
         }
@@ -748,7 +844,7 @@ fn apply_subst<T: GlyphData>(
     index: usize,
 ) -> Result<Option<isize>, ParseError> {
     let lookup = lookup_list.lookup_cache_gsub(gsub_cache, lookup_index)?;
-    let match_type = MatchType::from_lookup_flag(lookup.lookup_flag);
+    let match_type = MatchType::from_lookup(&lookup);
     let i = match parent_match_type.find_nth(opt_gdef_table, glyphs, index, subst_index) {
         Some(index1) => index1,
         None => return Ok(None), // FIXME error?
@@ -992,15 +1088,15 @@ fn find_alternate(features_list: &[FeatureInfo], feature_tag: u32) -> Option<usi
 ///     // We look ahead in the char stream for variation selectors. If one is found it is used for
 ///     // mapping the current glyph. When a variation selector is reached in the stream it is
 ///     // skipped as it was handled as part of the preceding character.
-///     let mut chars_iter = text.chars().peekable();
+///     let mut chars_iter = text.char_indices().peekable();
 ///     let mut glyphs = Vec::new();
-///     while let Some(ch) = chars_iter.next() {
+///     while let Some((cluster, ch)) = chars_iter.next() {
 ///         match VariationSelector::try_from(ch) {
 ///             Ok(_) => {} // filter out variation selectors
 ///             Err(()) => {
 ///                 let vs = chars_iter
 ///                     .peek()
-///                     .and_then(|&next| VariationSelector::try_from(next).ok());
+///                     .and_then(|&(_, next)| VariationSelector::try_from(next).ok());
 ///                 let (glyph_index, used_variation) = font.lookup_glyph_index(
 ///                     ch,
 ///                     MatchingPresentation::NotRequired,
@@ -1011,9 +1107,11 @@ fn find_alternate(features_list: &[FeatureInfo], feature_tag: u32) -> Option<usi
 ///                     glyph_index: glyph_index,
 ///                     liga_component_pos: 0,
 ///                     glyph_origin: GlyphOrigin::Char(ch),
+///                     cluster,
 ///                     flags: RawGlyphFlags::empty(),
 ///                     extra_data: (),
 ///                     variation: Some(used_variation),
+///                     feature_mask: None,
 ///                 };
 ///                 glyphs.push(glyph);
 ///             }
@@ -1068,6 +1166,7 @@ pub fn apply(
     num_glyphs: u16,
     glyphs: &mut Vec<RawGlyph<()>>,
 ) -> Result<(), ShapingError> {
+    features.check_glyph_masks(glyphs.iter())?;
     match features {
         Features::Custom(features_list) => gsub_apply_custom(
             gsub_cache,
@@ -1168,10 +1267,96 @@ fn gsub_apply_custom(
             }
         }
     }
+    merge_unordered_clusters(glyphs);
     replace_missing_glyphs(glyphs, num_glyphs);
     Ok(())
 }
 
+/// The alternates of a glyph that are available from a feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphAlternates {
+    pub feature_tag: u32,
+    /// The alternate glyphs, in the order of the lookups of the feature.
+    ///
+    /// When the feature has a single alternate substitution lookup, the position of a glyph in
+    /// this list is the `FeatureInfo::alternate` index that selects it.
+    pub alternates: Vec<u16>,
+    /// The parameters of the feature, which give the names of stylistic sets and character
+    /// variants.
+    pub feature_params: Option<FeatureParams>,
+}
+
+/// Returns the alternates of `glyph_index` from the `aalt`, `salt`, `swsh`, `ssNN`, and `cvNN`
+/// features of the script and language.
+///
+/// Features are returned in the order of the language system, and only when they have at least
+/// one alternate for the glyph. Single and alternate substitution lookups are considered.
+pub fn glyph_alternates(
+    gsub_cache: &LayoutCache<GSUB>,
+    script_tag: u32,
+    opt_lang_tag: Option<u32>,
+    glyph_index: u16,
+) -> Result<Vec<GlyphAlternates>, ParseError> {
+    let gsub_table = &gsub_cache.layout_table;
+    let langsys = match gsub_table.find_script_or_default(script_tag)? {
+        Some(script) => match script.find_langsys_or_default(opt_lang_tag)? {
+            Some(langsys) => langsys,
+            None => return Ok(Vec::new()),
+        },
+        None => return Ok(Vec::new()),
+    };
+    let lookup_list = match gsub_table.opt_lookup_list {
+        Some(ref lookup_list) => lookup_list,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut glyph_alternates = Vec::new();
+    for feature_index in langsys.feature_indices_iter() {
+        let feature_record = gsub_table.feature_by_index(*feature_index)?;
+        if !is_alternates_feature(feature_record.feature_tag) {
+            continue;
+        }
+        let mut alternates = Vec::new();
+        for lookup_index in &feature_record.feature_table().lookup_indices {
+            let lookup = lookup_list.lookup_cache_gsub(gsub_cache, usize::from(*lookup_index))?;
+            let glyphs = match lookup.lookup_subtables {
+                SubstLookup::SingleSubst(ref subtables) => subtables
+                    .iter()
+                    .find_map(|subtable| subtable.apply_glyph(glyph_index).transpose())
+                    .transpose()?
+                    .into_iter()
+                    .collect(),
+                SubstLookup::AlternateSubst(ref subtables) => subtables
+                    .iter()
+                    .find_map(|subtable| subtable.apply_glyph(glyph_index).transpose())
+                    .transpose()?
+                    .map_or_else(Vec::new, |set| set.alternate_glyphs.clone()),
+                _ => Vec::new(),
+            };
+            for glyph in glyphs {
+                if !alternates.contains(&glyph) {
+                    alternates.push(glyph);
+                }
+            }
+        }
+        if !alternates.is_empty() {
+            glyph_alternates.push(GlyphAlternates {
+                feature_tag: feature_record.feature_tag,
+                alternates,
+                feature_params: feature_record.feature_params().cloned(),
+            });
+        }
+    }
+    Ok(glyph_alternates)
+}
+
+fn is_alternates_feature(feature_tag: u32) -> bool {
+    match feature_tag {
+        tag::AALT | tag::SALT | tag::SWSH => true,
+        _ => is_stylistic_set(feature_tag) || is_character_variant(feature_tag),
+    }
+}
+
 pub fn replace_missing_glyphs<T: GlyphData>(glyphs: &mut [RawGlyph<T>], num_glyphs: u16) {
     for glyph in glyphs.iter_mut() {
         if glyph.glyph_index >= num_glyphs {
@@ -1186,11 +1371,88 @@ pub fn replace_missing_glyphs<T: GlyphData>(glyphs: &mut [RawGlyph<T>], num_glyp
 }
 
 fn strip_joiners<T: GlyphData>(glyphs: &mut Vec<RawGlyph<T>>) {
-    glyphs.retain(|g| match g.glyph_origin {
-        GlyphOrigin::Char('\u{200C}') => false,
-        GlyphOrigin::Char('\u{200D}') => false,
-        _ => true,
-    })
+    let mut i = 0;
+    while i < glyphs.len() {
+        match glyphs[i].glyph_origin {
+            GlyphOrigin::Char('\u{200C}') | GlyphOrigin::Char('\u{200D}') => {
+                delete_glyph(glyphs, i)
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Remove the glyph at index `i`, merging its cluster into a neighbouring glyph if no other
+/// glyph remains in that cluster.
+pub(crate) fn delete_glyph<T>(glyphs: &mut Vec<RawGlyph<T>>, i: usize) {
+    let removed = glyphs.remove(i);
+    let cluster = removed.cluster;
+    let prev_cluster = i.checked_sub(1).map(|prev| glyphs[prev].cluster);
+    let next_cluster = glyphs.get(i).map(|next| next.cluster);
+    if prev_cluster == Some(cluster) || next_cluster == Some(cluster) {
+        // The cluster survives in a neighbouring glyph
+        return;
+    }
+    match (prev_cluster, next_cluster) {
+        (Some(prev_cluster), _) => {
+            // The text of the removed glyph is covered by the preceding cluster unless the
+            // clusters were out of order, in which case the preceding cluster is merged backwards
+            if cluster < prev_cluster {
+                for glyph in glyphs[..i].iter_mut().rev() {
+                    if glyph.cluster != prev_cluster {
+                        break;
+                    }
+                    glyph.cluster = cluster;
+                }
+            }
+        }
+        (None, Some(next_cluster)) => {
+            // Merge forwards into the following cluster
+            for glyph in glyphs[i..].iter_mut() {
+                if glyph.cluster != next_cluster {
+                    break;
+                }
+                glyph.cluster = cluster.min(next_cluster);
+            }
+        }
+        (None, None) => {}
+    }
+}
+
+/// Merge clusters where reordering has left them out of logical order.
+///
+/// After this has been called clusters never decrease from one glyph to the next. Each run of
+/// glyphs whose clusters overlap the clusters of other glyphs in the run is merged into a single
+/// cluster with the smallest value in the run. Glyphs that are already in order are left alone.
+pub(crate) fn merge_unordered_clusters<T>(glyphs: &mut [RawGlyph<T>]) {
+    if glyphs
+        .windows(2)
+        .all(|pair| pair[0].cluster <= pair[1].cluster)
+    {
+        return;
+    }
+
+    // `suffix_min[i]` is the smallest cluster in `glyphs[i..]`
+    let mut suffix_min = vec![0; glyphs.len()];
+    let mut min = usize::MAX;
+    for (i, glyph) in glyphs.iter().enumerate().rev() {
+        min = min.min(glyph.cluster);
+        suffix_min[i] = min;
+    }
+
+    // A run ends where every cluster before the end is no greater than every cluster after it
+    let mut start = 0;
+    let mut max = 0;
+    for i in 0..glyphs.len() {
+        max = max.max(glyphs[i].cluster);
+        if i + 1 == glyphs.len() || max <= suffix_min[i + 1] {
+            let cluster = suffix_min[start];
+            for glyph in &mut glyphs[start..=i] {
+                glyph.cluster = cluster;
+            }
+            start = i + 1;
+        }
+    }
 }
 
 bitflags! {
@@ -1243,6 +1505,12 @@ bitflags! {
         const VRT2_OR_VERT = 1 << 43;
         const ZERO = 1 << 44;
         const RVRN = 1 << 45;
+        const LJMO = 1 << 46;
+        const VJMO = 1 << 47;
+        const TJMO = 1 << 48;
+        const RTLM = 1 << 49;
+        const VKRN = 1 << 50;
+        const VPAL = 1 << 51;
     }
 }
 const FEATURE_MASKS: &[(FeatureMask, u32)] = &[
@@ -1269,6 +1537,7 @@ const FEATURE_MASKS: &[(FeatureMask, u32)] = &[
     (FeatureMask::INIT, tag::INIT),
     (FeatureMask::ISOL, tag::ISOL),
     (FeatureMask::LIGA, tag::LIGA),
+    (FeatureMask::LJMO, tag::LJMO),
     (FeatureMask::LNUM, tag::LNUM),
     (FeatureMask::LOCL, tag::LOCL),
     (FeatureMask::MEDI, tag::MEDI),
@@ -1286,10 +1555,15 @@ const FEATURE_MASKS: &[(FeatureMask, u32)] = &[
     (FeatureMask::RKRF, tag::RKRF),
     (FeatureMask::RLIG, tag::RLIG),
     (FeatureMask::RPHF, tag::RPHF),
+    (FeatureMask::RTLM, tag::RTLM),
     (FeatureMask::RVRN, tag::RVRN),
     (FeatureMask::SMCP, tag::SMCP),
+    (FeatureMask::TJMO, tag::TJMO),
     (FeatureMask::TNUM, tag::TNUM),
     (FeatureMask::VATU, tag::VATU),
+    (FeatureMask::VJMO, tag::VJMO),
+    (FeatureMask::VKRN, tag::VKRN),
+    (FeatureMask::VPAL, tag::VPAL),
     (FeatureMask::VRT2_OR_VERT, tag::VRT2),
     (FeatureMask::ZERO, tag::ZERO),
 ];
@@ -1320,6 +1594,7 @@ impl FeatureMask {
             tag::INIT => FeatureMask::INIT,
             tag::ISOL => FeatureMask::ISOL,
             tag::LIGA => FeatureMask::LIGA,
+            tag::LJMO => FeatureMask::LJMO,
             tag::LNUM => FeatureMask::LNUM,
             tag::LOCL => FeatureMask::LOCL,
             tag::MEDI => FeatureMask::MEDI,
@@ -1337,11 +1612,16 @@ impl FeatureMask {
             tag::RKRF => FeatureMask::RKRF,
             tag::RLIG => FeatureMask::RLIG,
             tag::RPHF => FeatureMask::RPHF,
+            tag::RTLM => FeatureMask::RTLM,
             tag::RVRN => FeatureMask::RVRN,
             tag::SMCP => FeatureMask::SMCP,
+            tag::TJMO => FeatureMask::TJMO,
             tag::TNUM => FeatureMask::TNUM,
             tag::VATU => FeatureMask::VATU,
             tag::VERT => FeatureMask::VRT2_OR_VERT,
+            tag::VJMO => FeatureMask::VJMO,
+            tag::VKRN => FeatureMask::VKRN,
+            tag::VPAL => FeatureMask::VPAL,
             tag::VRT2 => FeatureMask::VRT2_OR_VERT,
             tag::ZERO => FeatureMask::ZERO,
             _ => FeatureMask::empty(),
@@ -1473,7 +1753,37 @@ fn gsub_apply_default(
     }
     feature_mask.remove(FeatureMask::RVRN);
 
-    match ScriptType::from(script_tag) {
+    // Apply rtlm before the script shapers, which only apply the features they require. It is
+    // enabled for right-to-left runs, such as those produced by the `bidi` module, so that glyphs
+    // without a Unicode mirroring counterpart are mirrored. Glyphs that have already been
+    // replaced by their `Bidi_Mirroring_Glyph` are flagged as `MIRRORED` and left alone.
+    if feature_mask.contains(FeatureMask::RTLM) {
+        apply_rtlm(
+            gsub_cache,
+            opt_gdef_table,
+            script_tag,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?;
+        feature_mask.remove(FeatureMask::RTLM);
+    }
+
+    let script_type = ScriptType::from(script_tag);
+    if script_type == ScriptType::Hangul {
+        // The jamo features are applied before the features of the default shaper
+        scripts::hangul::gsub_apply_hangul(
+            gsub_cache,
+            gsub_table,
+            opt_gdef_table,
+            script_tag,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?;
+    }
+
+    match script_type {
         ScriptType::Arabic => scripts::arabic::gsub_apply_arabic(
             gsub_cache,
             gsub_table,
@@ -1503,6 +1813,24 @@ fn gsub_apply_default(
             feature_variations,
             glyphs,
         )?,
+        ScriptType::Mongolian => scripts::mongolian::gsub_apply_mongolian(
+            gsub_cache,
+            gsub_table,
+            opt_gdef_table,
+            script_tag,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?,
+        ScriptType::Myanmar => scripts::myanmar::gsub_apply_myanmar(
+            dotted_circle_index,
+            gsub_cache,
+            gsub_table,
+            opt_gdef_table,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?,
         ScriptType::Syriac => scripts::syriac::gsub_apply_syriac(
             gsub_cache,
             gsub_table,
@@ -1521,7 +1849,31 @@ fn gsub_apply_default(
             feature_variations,
             glyphs,
         )?,
-        ScriptType::Default => {
+        ScriptType::Tibetan => scripts::tibetan::gsub_apply_tibetan(
+            dotted_circle_index,
+            gsub_cache,
+            gsub_table,
+            opt_gdef_table,
+            script_tag,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?,
+        ScriptType::Universal => scripts::universal::gsub_apply_universal(
+            dotted_circle_index,
+            gsub_cache,
+            gsub_table,
+            opt_gdef_table,
+            script_tag,
+            opt_lang_tag,
+            feature_variations,
+            glyphs,
+        )?,
+        ScriptType::Default | ScriptType::Hangul => {
+            // Lookups are applied to each glyph according to its own feature mask, if it has one
+            let features = Features::Mask(feature_mask);
+            let pred = |glyph: &RawGlyph<()>, feature_tag| features.enabled_for(glyph, feature_tag);
+            feature_mask = with_glyph_masks(feature_mask, glyphs.iter());
             feature_mask &= get_supported_features(gsub_cache, script_tag, opt_lang_tag)?;
             if feature_mask.contains(FeatureMask::FRAC) {
                 let index_frac = get_lookups_cache_index(
@@ -1548,6 +1900,7 @@ fn gsub_apply_default(
                     lookups,
                     lookups_frac,
                     glyphs,
+                    pred,
                 )?;
             } else {
                 let index = get_lookups_cache_index(
@@ -1558,22 +1911,33 @@ fn gsub_apply_default(
                     feature_mask,
                 )?;
                 let lookups = &gsub_cache.cached_lookups.borrow()[index];
-                gsub_apply_lookups(gsub_cache, gsub_table, opt_gdef_table, lookups, glyphs)?;
+                gsub_apply_lookups(
+                    gsub_cache,
+                    gsub_table,
+                    opt_gdef_table,
+                    lookups,
+                    glyphs,
+                    pred,
+                )?;
             }
         }
     }
 
     strip_joiners(glyphs);
+    merge_unordered_clusters(glyphs);
     replace_missing_glyphs(glyphs, num_glyphs);
     Ok(())
 }
 
+// `pred` is called with each glyph and the tag of the feature of the lookup, and returns `true`
+// if the lookup should be applied to the glyph
 fn gsub_apply_lookups(
     gsub_cache: &LayoutCache<GSUB>,
     gsub_table: &LayoutTable<GSUB>,
     opt_gdef_table: Option<&GDEFTable>,
     lookups: &[(usize, u32)],
     glyphs: &mut Vec<RawGlyph<()>>,
+    pred: impl Fn(&RawGlyph<()>, u32) -> bool + Copy,
 ) -> Result<(), ShapingError> {
     gsub_apply_lookups_impl(
         gsub_cache,
@@ -1583,10 +1947,14 @@ fn gsub_apply_lookups(
         glyphs,
         0,
         glyphs.len(),
+        pred,
     )?;
     Ok(())
 }
 
+// Applies `lookups` to the `length` glyphs from `start` and returns their new length. The
+// arguments are those of `gsub_apply_lookups` plus the range of glyphs.
+#[allow(clippy::too_many_arguments)]
 fn gsub_apply_lookups_impl(
     gsub_cache: &LayoutCache<GSUB>,
     gsub_table: &LayoutTable<GSUB>,
@@ -1595,19 +1963,20 @@ fn gsub_apply_lookups_impl(
     glyphs: &mut Vec<RawGlyph<()>>,
     start: usize,
     mut length: usize,
+    pred: impl Fn(&RawGlyph<()>, u32) -> bool + Copy,
 ) -> Result<usize, ShapingError> {
-    for (lookup_index, feature_tag) in lookups {
+    for &(lookup_index, feature_tag) in lookups {
         length = gsub_apply_lookup(
             gsub_cache,
             gsub_table,
             opt_gdef_table,
-            *lookup_index,
-            *feature_tag,
+            lookup_index,
+            feature_tag,
             None,
             glyphs,
             start,
             length,
-            |_| true,
+            |glyph| pred(glyph, feature_tag),
         )?;
     }
     Ok(length)
@@ -1620,6 +1989,7 @@ fn gsub_apply_lookups_frac(
     lookups: &[(usize, u32)],
     lookups_frac: &[(usize, u32)],
     glyphs: &mut Vec<RawGlyph<()>>,
+    pred: impl Fn(&RawGlyph<()>, u32) -> bool + Copy,
 ) -> Result<(), ShapingError> {
     let mut i = 0;
     while i < glyphs.len() {
@@ -1633,6 +2003,7 @@ fn gsub_apply_lookups_frac(
                     glyphs,
                     i,
                     start_pos,
+                    pred,
                 )?;
             }
             i += gsub_apply_lookups_impl(
@@ -1643,6 +2014,7 @@ fn gsub_apply_lookups_frac(
                 glyphs,
                 i,
                 end_pos - start_pos + 1,
+                pred,
             )?;
         } else {
             gsub_apply_lookups_impl(
@@ -1653,6 +2025,7 @@ fn gsub_apply_lookups_frac(
                 glyphs,
                 i,
                 glyphs.len() - i,
+                pred,
             )?;
             break;
         }
@@ -1706,7 +2079,42 @@ fn apply_rvrn(
         FeatureMask::RVRN,
     )?;
     let lookups = &gsub_cache.cached_lookups.borrow()[index];
-    gsub_apply_lookups(gsub_cache, gsub_table, opt_gdef_table, lookups, glyphs)?;
+    gsub_apply_lookups(
+        gsub_cache,
+        gsub_table,
+        opt_gdef_table,
+        lookups,
+        glyphs,
+        |_, _| true,
+    )?;
+    Ok(())
+}
+
+fn apply_rtlm(
+    gsub_cache: &LayoutCache<GSUB>,
+    opt_gdef_table: Option<&GDEFTable>,
+    script_tag: u32,
+    opt_lang_tag: Option<u32>,
+    feature_variations: Option<&FeatureTableSubstitution<'_>>,
+    glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    let gsub_table = &gsub_cache.layout_table;
+    let index = get_lookups_cache_index(
+        gsub_cache,
+        script_tag,
+        opt_lang_tag,
+        feature_variations,
+        FeatureMask::RTLM,
+    )?;
+    let lookups = &gsub_cache.cached_lookups.borrow()[index];
+    gsub_apply_lookups(
+        gsub_cache,
+        gsub_table,
+        opt_gdef_table,
+        lookups,
+        glyphs,
+        |glyph, _| !glyph.flags.contains(RawGlyphFlags::MIRRORED),
+    )?;
     Ok(())
 }
 
@@ -1714,6 +2122,62 @@ fn apply_rvrn(
 mod tests {
     use super::*;
 
+    fn glyph_with_cluster(glyph_index: u16, cluster: usize) -> RawGlyph<()> {
+        RawGlyph {
+            unicodes: tiny_vec![],
+            glyph_index,
+            liga_component_pos: 0,
+            glyph_origin: GlyphOrigin::Direct,
+            cluster,
+            flags: RawGlyphFlags::empty(),
+            variation: None,
+            feature_mask: None,
+            extra_data: (),
+        }
+    }
+
+    fn clusters(glyphs: &[RawGlyph<()>]) -> Vec<usize> {
+        glyphs.iter().map(|glyph| glyph.cluster).collect()
+    }
+
+    #[test]
+    fn merge_unordered_clusters_in_order() {
+        let mut glyphs = [0, 1, 1, 4, 7]
+            .iter()
+            .map(|&cluster| glyph_with_cluster(1, cluster))
+            .collect::<Vec<_>>();
+        merge_unordered_clusters(&mut glyphs);
+        assert_eq!(clusters(&glyphs), &[0, 1, 1, 4, 7]);
+    }
+
+    #[test]
+    fn merge_unordered_clusters_reordered() {
+        let mut glyphs = [0, 6, 3, 9, 12, 10, 14]
+            .iter()
+            .map(|&cluster| glyph_with_cluster(1, cluster))
+            .collect::<Vec<_>>();
+        merge_unordered_clusters(&mut glyphs);
+        assert_eq!(clusters(&glyphs), &[0, 3, 3, 9, 10, 10, 14]);
+    }
+
+    #[test]
+    fn delete_glyph_merges_first_cluster_forwards() {
+        let mut glyphs = vec![glyph_with_cluster(1, 0), glyph_with_cluster(2, 3)];
+        delete_glyph(&mut glyphs, 0);
+        assert_eq!(clusters(&glyphs), &[0]);
+    }
+
+    #[test]
+    fn delete_glyph_keeps_shared_cluster() {
+        let mut glyphs = vec![
+            glyph_with_cluster(1, 0),
+            glyph_with_cluster(2, 3),
+            glyph_with_cluster(3, 3),
+        ];
+        delete_glyph(&mut glyphs, 1);
+        assert_eq!(clusters(&glyphs), &[0, 3]);
+    }
+
     #[test]
     fn feature_mask_iter() {
         let mask = FeatureMask::empty();
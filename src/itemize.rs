@@ -0,0 +1,351 @@
+//! Script itemisation.
+//!
+//! Splits text into runs of a single Unicode script so that mixed-script text can be shaped
+//! without the caller supplying a script tag for each part of it. [script_runs] resolves the
+//! script of each run from the Unicode `Script` and `Script_Extensions` properties, and
+//! [font_script_tag] maps it to the OpenType script tag best supported by a font. [shape_text]
+//! does both and shapes each run with [Font::shape](crate::font::Font::shape).
+//!
+//! ```
+//! use allsorts::itemize::script_runs;
+//! use allsorts::unicode::Script;
+//!
+//! let text = "abc (\u{0915}\u{093F}) def";
+//! let runs = script_runs(text)
+//!     .into_iter()
+//!     .map(|run| (&text[run.range], run.script))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     runs,
+//!     vec![
+//!         ("abc (", Script::Latin),
+//!         ("\u{0915}\u{093F}", Script::Devanagari),
+//!         (") def", Script::Latin),
+//!     ]
+//! );
+//! ```
+
+use std::ops::Range;
+
+use crate::error::{ParseError, ShapingError};
+use crate::font::{Font, MatchingPresentation};
+use crate::gpos::Info;
+use crate::gsub::Features;
+use crate::tables::variable_fonts::fvar::Tuple;
+use crate::tables::FontTableProvider;
+use crate::tag;
+use crate::unicode::{bidi_paired_bracket, script_extensions, BracketType, Script};
+
+/// The maximum number of open brackets that are tracked when pairing brackets.
+const MAX_BRACKET_DEPTH: usize = 63;
+
+/// A run of text in a single script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptRun {
+    /// The byte range of the run in the text.
+    pub range: Range<usize>,
+    /// The script of the run.
+    ///
+    /// This is `Script::Common` if the run contains only characters that are shared between
+    /// scripts, such as spaces, digits, and punctuation.
+    pub script: Script,
+}
+
+/// A run of text in a single script, shaped.
+#[derive(Debug)]
+pub struct ShapedScriptRun {
+    /// The byte range of the run in the text.
+    pub range: Range<usize>,
+    /// The script of the run.
+    pub script: Script,
+    /// The OpenType script tag the run was shaped with.
+    pub script_tag: u32,
+    /// The shaped glyphs of the run. Glyph clusters are byte offsets into the whole text.
+    pub infos: Vec<Info>,
+}
+
+/// Split `text` into runs of a single script.
+///
+/// Characters with the `Common` or `Inherited` script, such as spaces, punctuation, and
+/// combining marks, are added to the run before them, or the first run if they begin the text.
+/// Characters used with several scripts are added to the current run if it is in one of those
+/// scripts. A closing bracket is added to a run in the same script as its opening bracket.
+pub fn script_runs(text: &str) -> Vec<ScriptRun> {
+    // The start of each run, and its script once resolved
+    let mut runs: Vec<(usize, Option<Script>)> = Vec::new();
+    let mut candidates: Option<Vec<Script>> = None;
+    // The opening brackets that haven't been closed, and the run each is in
+    let mut brackets: Vec<(char, usize)> = Vec::new();
+
+    for (index, ch) in text.char_indices() {
+        if runs.is_empty() {
+            runs.push((index, None));
+        }
+
+        let bracket_script;
+        let mut scripts = script_extensions(ch);
+        match bidi_paired_bracket(ch) {
+            Some((_, BracketType::Open)) if brackets.len() < MAX_BRACKET_DEPTH => {
+                brackets.push((ch, runs.len() - 1));
+            }
+            Some((opening, BracketType::Close)) => {
+                if let Some(position) = brackets.iter().rposition(|&(open, _)| open == opening) {
+                    let (_, run_index) = brackets[position];
+                    brackets.truncate(position);
+                    // Brackets opened in an earlier run take the script of that run
+                    if run_index != runs.len() - 1 {
+                        if let Some(script) = runs[run_index].1 {
+                            bracket_script = script;
+                            scripts = std::slice::from_ref(&bracket_script);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if matches!(scripts, [Script::Common] | [Script::Inherited]) {
+            continue;
+        }
+
+        match candidates.as_mut() {
+            None => candidates = Some(scripts.to_vec()),
+            Some(current) => {
+                if current.iter().any(|script| scripts.contains(script)) {
+                    current.retain(|script| scripts.contains(script));
+                } else {
+                    if let Some(run) = runs.last_mut() {
+                        run.1 = current.first().copied();
+                    }
+                    runs.push((index, None));
+                    candidates = Some(scripts.to_vec());
+                }
+            }
+        }
+    }
+    if let (Some(run), Some(current)) = (runs.last_mut(), candidates) {
+        run.1 = current.first().copied();
+    }
+
+    let mut script_runs = Vec::with_capacity(runs.len());
+    for (i, &(start, script)) in runs.iter().enumerate() {
+        let end = runs.get(i + 1).map_or(text.len(), |&(next, _)| next);
+        script_runs.push(ScriptRun {
+            range: start..end,
+            script: script.unwrap_or(Script::Common),
+        });
+    }
+    script_runs
+}
+
+/// Returns the OpenType script tag for `script`.
+///
+/// For scripts with more than one OpenType tag this is the original tag, such as `deva` for
+/// Devanagari. Use [font_script_tag] to choose the tag supported by a font. `Common`,
+/// `Inherited`, and `Unknown` map to `DFLT`.
+///
+/// ```
+/// use allsorts::itemize::opentype_script_tag;
+/// use allsorts::tag;
+/// use allsorts::unicode::Script;
+///
+/// assert_eq!(opentype_script_tag(Script::Latin), tag::LATN);
+/// assert_eq!(opentype_script_tag(Script::Hiragana), tag::KANA);
+/// assert_eq!(opentype_script_tag(Script::Devanagari), tag::DEVA);
+/// ```
+pub fn opentype_script_tag(script: Script) -> u32 {
+    match script {
+        Script::Common | Script::Inherited | Script::Unknown => tag::DFLT,
+        Script::Hiragana | Script::Katakana => tag::KANA,
+        Script::Lao => tag::LAO,
+        Script::Nko => tag::NKO,
+        Script::Vai => tag!(b"vai "),
+        Script::Yi => tag!(b"yi  "),
+        _ => {
+            let code = script.iso15924_code().as_bytes();
+            u32::from_be_bytes([code[0].to_ascii_lowercase(), code[1], code[2], code[3]])
+        }
+    }
+}
+
+/// Returns the newer OpenType script tag for scripts that have two, such as `dev2` for
+/// Devanagari.
+fn opentype_script_tag_v2(script: Script) -> Option<u32> {
+    match script {
+        Script::Bengali => Some(tag::BNG2),
+        Script::Devanagari => Some(tag::DEV2),
+        Script::Gujarati => Some(tag::GJR2),
+        Script::Gurmukhi => Some(tag::GUR2),
+        Script::Kannada => Some(tag::KND2),
+        Script::Malayalam => Some(tag::MLM2),
+        Script::Myanmar => Some(tag::MYM2),
+        Script::Oriya => Some(tag::ORY2),
+        Script::Tamil => Some(tag::TML2),
+        Script::Telugu => Some(tag::TEL2),
+        _ => None,
+    }
+}
+
+/// Returns the OpenType script tag to shape `script` with in `font`.
+///
+/// For scripts with two OpenType tags the newer tag, such as `dev2`, is returned if the `GSUB`
+/// or `GPOS` script list of the font has it. Otherwise this is the same as
+/// [opentype_script_tag].
+pub fn font_script_tag<T: FontTableProvider>(
+    font: &mut Font<T>,
+    script: Script,
+) -> Result<u32, ParseError> {
+    if let Some(v2_tag) = opentype_script_tag_v2(script) {
+        if let Some(gsub_cache) = font.gsub_cache()? {
+            if gsub_cache.layout_table.find_script(v2_tag)?.is_some() {
+                return Ok(v2_tag);
+            }
+        }
+        if let Some(gpos_cache) = font.gpos_cache()? {
+            if gpos_cache.layout_table.find_script(v2_tag)?.is_some() {
+                return Ok(v2_tag);
+            }
+        }
+    }
+    Ok(opentype_script_tag(script))
+}
+
+/// Itemise `text` into script runs and shape each run.
+///
+/// The runs are returned in logical order. Each is shaped with the script tag returned by
+/// [font_script_tag]. Right-to-left text is not reordered; see the [bidi](crate::bidi) module
+/// for that.
+///
+/// If an error occurs shaping a run the remaining runs are still shaped, and the error is
+/// returned along with all the runs.
+pub fn shape_text<T: FontTableProvider>(
+    font: &mut Font<T>,
+    text: &str,
+    opt_lang_tag: Option<u32>,
+    features: &Features,
+    tuple: Option<Tuple<'_>>,
+    kerning: bool,
+) -> Result<Vec<ShapedScriptRun>, (ShapingError, Vec<ShapedScriptRun>)> {
+    let mut err: Option<ShapingError> = None;
+    let mut shaped_runs = Vec::new();
+    for run in script_runs(text) {
+        let script_tag = match font_script_tag(font, run.script) {
+            Ok(script_tag) => script_tag,
+            Err(error) => {
+                err.get_or_insert(error.into());
+                opentype_script_tag(run.script)
+            }
+        };
+        let mut glyphs = font.map_glyphs(
+            &text[run.range.clone()],
+            script_tag,
+            MatchingPresentation::NotRequired,
+        );
+        for glyph in glyphs.iter_mut() {
+            glyph.cluster += run.range.start;
+        }
+        let infos = match font.shape(glyphs, script_tag, opt_lang_tag, features, tuple, kerning) {
+            Ok(infos) => infos,
+            Err((error, infos)) => {
+                err.get_or_insert(error);
+                infos
+            }
+        };
+        shaped_runs.push(ShapedScriptRun {
+            range: run.range,
+            script: run.script,
+            script_tag,
+            infos,
+        });
+    }
+
+    match err {
+        Some(err) => Err((err, shaped_runs)),
+        None => Ok(shaped_runs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(text: &str) -> Vec<(&str, Script)> {
+        script_runs(text)
+            .into_iter()
+            .map(|run| (&text[run.range], run.script))
+            .collect()
+    }
+
+    #[test]
+    fn test_single_script() {
+        assert_eq!(runs("hello, world"), vec![("hello, world", Script::Latin)]);
+        assert_eq!(runs(""), vec![]);
+        assert_eq!(runs("12 + 3"), vec![("12 + 3", Script::Common)]);
+    }
+
+    #[test]
+    fn test_common_and_inherited() {
+        // Leading common characters join the first run and combining marks join their base
+        assert_eq!(
+            runs("1. \u{0627}\u{064E} b\u{0301}"),
+            vec![
+                ("1. \u{0627}\u{064E} ", Script::Arabic),
+                ("b\u{0301}", Script::Latin)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_extensions() {
+        // DEVANAGARI DANDA is used with Bengali
+        assert_eq!(
+            runs("\u{0995}\u{0964}"),
+            vec![("\u{0995}\u{0964}", Script::Bengali)]
+        );
+        assert_eq!(
+            runs("\u{0964}\u{0995}"),
+            vec![("\u{0964}\u{0995}", Script::Bengali)]
+        );
+        // Hiragana and Katakana are separate runs
+        assert_eq!(
+            runs("\u{3042}\u{30A2}"),
+            vec![
+                ("\u{3042}", Script::Hiragana),
+                ("\u{30A2}", Script::Katakana)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_brackets() {
+        assert_eq!(
+            runs("a (\u{05D0}) b"),
+            vec![
+                ("a (", Script::Latin),
+                ("\u{05D0}", Script::Hebrew),
+                (") b", Script::Latin)
+            ]
+        );
+        // Unmatched closing brackets are common
+        assert_eq!(
+            runs("a \u{05D0})"),
+            vec![("a ", Script::Latin), ("\u{05D0})", Script::Hebrew)]
+        );
+        assert_eq!(
+            runs("[a (\u{05D0}] b"),
+            vec![
+                ("[a (", Script::Latin),
+                ("\u{05D0}", Script::Hebrew),
+                ("] b", Script::Latin)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_opentype_script_tag() {
+        assert_eq!(opentype_script_tag(Script::Arabic), tag::ARAB);
+        assert_eq!(opentype_script_tag(Script::Common), tag::DFLT);
+        assert_eq!(opentype_script_tag(Script::Yi), tag!(b"yi  "));
+        assert_eq!(opentype_script_tag(Script::Myanmar), tag::MYMR);
+    }
+}
@@ -3,6 +3,7 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 use std::rc::Rc;
 
@@ -12,25 +13,65 @@ use crate::binary::read::{
     CheckIndex, ReadArray, ReadBinary, ReadBinaryDep, ReadCache, ReadCtxt, ReadFixedSizeDep,
     ReadFrom, ReadScope, ReadScopeOwned,
 };
-use crate::binary::{U16Be, U32Be};
+use crate::binary::{U16Be, U24Be, U32Be};
 use crate::context::{ContextLookupHelper, GlyphTable, LookupFlag, MatchContext};
 use crate::error::ParseError;
 use crate::tables::variable_fonts::{owned, ItemVariationStore, Tuple};
 use crate::tables::F2Dot14;
 use crate::{size, tag, SafeFrom};
 
+pub(crate) mod subset;
+
 pub enum GSUB {}
 pub enum GPOS {}
 
 pub struct GDEFTable {
     pub opt_glyph_classdef: Option<ClassDef>,
-    // pub opt_attach_list: Option<ReadScope<'a>>,
-    // pub opt_lig_caret_list: Option<ReadScope<'a>>,
+    pub opt_attach_list: Option<AttachList>,
+    pub opt_lig_caret_list: Option<LigCaretList>,
     pub opt_mark_attach_classdef: Option<ClassDef>,
-    // TODO read additional GDEF 1.2 fields
+    /// The mark glyph sets from `MarkGlyphSetsDef`, used by lookups with the
+    /// `UseMarkFilteringSet` flag.
+    pub opt_mark_glyph_sets: Option<Vec<Coverage>>,
     pub opt_item_variation_store: Option<owned::ItemVariationStore>,
 }
 
+/// The `AttachList` table from `GDEF`, which lists the attachment points of glyphs.
+pub struct AttachList {
+    coverage: Coverage,
+    attach_points: Vec<Vec<u16>>,
+}
+
+/// The `LigCaretList` table from `GDEF`, which lists the caret positions within ligatures.
+pub struct LigCaretList {
+    coverage: Coverage,
+    lig_glyphs: Vec<Vec<CaretValue>>,
+}
+
+/// The position of a caret between two components of a ligature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaretValue {
+    /// A coordinate in design units.
+    Coordinate(i16),
+    /// The index of a point of the ligature glyph's outline.
+    ContourPoint(u16),
+    /// A coordinate in design units, adjusted by a device or variation index table.
+    DeviceCoordinate(i16, Option<Device>),
+}
+
+/// A `Device` or `VariationIndex` table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Device {
+    /// Adjustments in pixels for sizes `start_size` to `end_size` ppem.
+    Hinting {
+        start_size: u16,
+        end_size: u16,
+        delta_values: Vec<i8>,
+    },
+    /// An index into the item variation store of `GDEF`.
+    Variation(VariationIndex),
+}
+
 // GSUB and GPOS tables have the same top-level structure
 pub struct LayoutTable<T> {
     pub opt_script_list: Option<ScriptList>,
@@ -59,7 +100,7 @@ pub struct LangSysRecord {
 }
 
 pub struct LangSys {
-    _required_feature_index: u16, // not used during shaping for now
+    required_feature_index: u16, // not used during shaping for now
     feature_indices: Vec<u16>,
 }
 
@@ -70,6 +111,7 @@ pub struct FeatureList {
 pub struct FeatureRecord {
     pub feature_tag: u32,
     feature_table: FeatureTable,
+    feature_params: Option<FeatureParams>,
 }
 
 #[derive(Clone)]
@@ -77,6 +119,65 @@ pub struct FeatureTable {
     pub lookup_indices: Vec<u16>,
 }
 
+/// Feature parameters, which describe a feature for display in a user interface.
+///
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2#feature-table>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureParams {
+    /// Parameters of the `size` feature.
+    Size(SizeParams),
+    /// Parameters of a stylistic set feature, `ss01` to `ss20`.
+    StylisticSet(StylisticSetParams),
+    /// Parameters of a character variant feature, `cv01` to `cv99`.
+    CharacterVariant(CharacterVariantParams),
+}
+
+/// Parameters of the `size` feature.
+///
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/features_pt#size>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SizeParams {
+    /// The design size in decipoints.
+    pub design_size: u16,
+    /// Identifies the fonts of a family that differ only in their design size range, or 0.
+    pub subfamily_id: u16,
+    /// The name ID of the subfamily name for menus, or 0.
+    pub subfamily_name_id: u16,
+    /// The start of the recommended size range in decipoints, exclusive.
+    pub range_start: u16,
+    /// The end of the recommended size range in decipoints, inclusive.
+    pub range_end: u16,
+}
+
+/// Parameters of a stylistic set feature.
+///
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/features_pt#ss01--ss20>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StylisticSetParams {
+    /// The name ID of the user interface name of the stylistic set.
+    pub ui_name_id: u16,
+}
+
+/// Parameters of a character variant feature.
+///
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/features_ae#cv01-cv99>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharacterVariantParams {
+    /// The name ID of the user interface label of the feature, or 0.
+    pub label_name_id: u16,
+    /// The name ID of the tooltip text of the feature, or 0.
+    pub tooltip_name_id: u16,
+    /// The name ID of sample text that shows the effect of the feature, or 0.
+    pub sample_text_name_id: u16,
+    /// The number of named parameters, which are the variants selected by `FeatureInfo::alternate`.
+    pub num_named_parameters: u16,
+    /// The name ID of the label of the first named parameter, or 0. The labels of the other
+    /// parameters follow consecutively.
+    pub first_param_ui_label_name_id: u16,
+    /// The Unicode scalar values of the characters the feature has variants for.
+    pub characters: Vec<u32>,
+}
+
 /// FeatureVariations table.
 ///
 /// A feature variations table describes variations on the effects of features based on various
@@ -176,6 +277,9 @@ pub struct Lookup<'a, T: LayoutTableType> {
     lookup_type: LookupType<T>,
     pub lookup_flag: u16,
     subtable_offsets: ReadArray<'a, U16Be>,
+    /// The index of the mark glyph set in `GDEF`, if the lookup has the `UseMarkFilteringSet`
+    /// flag.
+    pub mark_filtering_set: Option<u16>,
     phantom: PhantomData<T>,
 }
 
@@ -265,8 +369,8 @@ impl ReadBinary for GDEFTable {
         ctxt.check(major_version == 1)?;
         let minor_version = ctxt.read_u16be()?;
         let glyph_classdef_offset = usize::from(ctxt.read_u16be()?);
-        let _attach_list_offset = usize::from(ctxt.read_u16be()?);
-        let _lig_caret_list_offset = usize::from(ctxt.read_u16be()?);
+        let attach_list_offset = usize::from(ctxt.read_u16be()?);
+        let lig_caret_list_offset = usize::from(ctxt.read_u16be()?);
         // MarkAttachClassDef was added to GDEF in OpenType 1.2 but they did not change the GDEF
         // version. This means that it's not possible to know from the version alone whether the
         // field should be read. Some implementations use GSUB/GPOS to determine if it should be
@@ -283,9 +387,11 @@ impl ReadBinary for GDEFTable {
             _ => 8 * size::U16,
         };
 
-        if minor_version >= 2 {
-            let _mark_glyph_sets_def_offset = ctxt.read_u16be()?;
-        }
+        let mark_glyph_sets_def_offset = if minor_version >= 2 {
+            usize::from(ctxt.read_u16be()?)
+        } else {
+            0
+        };
         let item_var_store_offset = if minor_version >= 3 {
             usize::safe_from(ctxt.read_u32be()?)
         } else {
@@ -300,27 +406,18 @@ impl ReadBinary for GDEFTable {
             Some(table.offset(glyph_classdef_offset).read::<ClassDef>()?)
         };
 
-        /*
-                let opt_attach_list = if attach_list_offset >= table.data().len() {
-                    return Err(ParseError::BadOffset);
-                } else if attach_list_offset == 0 {
-                    None
-                } else if attach_list_offset < gdef_header_size {
-                    return Err(ParseError::BadOffset);
-                } else {
-                    Some(table.offset(attach_list_offset))
-                };
+        let opt_attach_list = if attach_list_offset < gdef_header_size {
+            None
+        } else {
+            Some(table.offset(attach_list_offset).read::<AttachList>()?)
+        };
+
+        let opt_lig_caret_list = if lig_caret_list_offset < gdef_header_size {
+            None
+        } else {
+            Some(table.offset(lig_caret_list_offset).read::<LigCaretList>()?)
+        };
 
-                let opt_lig_caret_list = if lig_caret_list_offset >= table.data().len() {
-                    return Err(ParseError::BadOffset);
-                } else if lig_caret_list_offset == 0 {
-                    None
-                } else if lig_caret_list_offset < gdef_header_size {
-                    return Err(ParseError::BadOffset);
-                } else {
-                    Some(table.offset(lig_caret_list_offset))
-                };
-        */
         let opt_mark_attach_classdef = if mark_attach_classdef_offset == 0 {
             None
         } else if mark_attach_classdef_offset < gdef_header_size {
@@ -333,6 +430,14 @@ impl ReadBinary for GDEFTable {
             )
         };
 
+        let opt_mark_glyph_sets = if mark_glyph_sets_def_offset < gdef_header_size {
+            None
+        } else {
+            Some(read_mark_glyph_sets(
+                table.offset(mark_glyph_sets_def_offset),
+            )?)
+        };
+
         let opt_item_variation_store = (item_var_store_offset > gdef_header_size)
             .then(|| {
                 table
@@ -344,14 +449,183 @@ impl ReadBinary for GDEFTable {
 
         Ok(GDEFTable {
             opt_glyph_classdef,
-            // opt_attach_list,
-            // opt_lig_caret_list,
+            opt_attach_list,
+            opt_lig_caret_list,
             opt_mark_attach_classdef,
+            opt_mark_glyph_sets,
             opt_item_variation_store,
         })
     }
 }
 
+impl ReadBinary for AttachList {
+    type HostType<'a> = Self;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self, ParseError> {
+        let scope = ctxt.scope();
+        let coverage_offset = usize::from(ctxt.read_u16be()?);
+        let glyph_count = usize::from(ctxt.read_u16be()?);
+        let attach_point_offsets = ctxt.read_array::<U16Be>(glyph_count)?;
+        let coverage = scope.offset(coverage_offset).read::<Coverage>()?;
+        let attach_points = attach_point_offsets
+            .iter()
+            .map(|offset| {
+                let mut ctxt = scope.offset(usize::from(offset)).ctxt();
+                let point_count = usize::from(ctxt.read_u16be()?);
+                Ok(ctxt.read_array::<U16Be>(point_count)?.to_vec())
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(AttachList {
+            coverage,
+            attach_points,
+        })
+    }
+}
+
+impl AttachList {
+    /// Returns the contour point indices of the attachment points of `glyph`.
+    pub fn attach_points(&self, glyph: u16) -> Option<&[u16]> {
+        let index = self.coverage.glyph_coverage_value(glyph)?;
+        self.attach_points
+            .get(usize::from(index))
+            .map(|points| points.as_slice())
+    }
+}
+
+impl ReadBinary for LigCaretList {
+    type HostType<'a> = Self;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self, ParseError> {
+        let scope = ctxt.scope();
+        let coverage_offset = usize::from(ctxt.read_u16be()?);
+        let lig_glyph_count = usize::from(ctxt.read_u16be()?);
+        let lig_glyph_offsets = ctxt.read_array::<U16Be>(lig_glyph_count)?;
+        let coverage = scope.offset(coverage_offset).read::<Coverage>()?;
+        let lig_glyphs = lig_glyph_offsets
+            .iter()
+            .map(|offset| {
+                let lig_glyph = scope.offset(usize::from(offset));
+                let mut ctxt = lig_glyph.ctxt();
+                let caret_count = usize::from(ctxt.read_u16be()?);
+                ctxt.read_array::<U16Be>(caret_count)?
+                    .iter()
+                    .map(|offset| lig_glyph.offset(usize::from(offset)).read::<CaretValue>())
+                    .collect()
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(LigCaretList {
+            coverage,
+            lig_glyphs,
+        })
+    }
+}
+
+impl LigCaretList {
+    /// Returns the caret values of the ligature glyph `glyph`, in increasing coordinate order.
+    pub fn caret_values(&self, glyph: u16) -> Option<&[CaretValue]> {
+        let index = self.coverage.glyph_coverage_value(glyph)?;
+        self.lig_glyphs
+            .get(usize::from(index))
+            .map(|carets| carets.as_slice())
+    }
+}
+
+impl ReadBinary for CaretValue {
+    type HostType<'a> = Self;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self, ParseError> {
+        let scope = ctxt.scope();
+        match ctxt.read_u16be()? {
+            1 => Ok(CaretValue::Coordinate(ctxt.read_i16be()?)),
+            2 => Ok(CaretValue::ContourPoint(ctxt.read_u16be()?)),
+            3 => {
+                let coordinate = ctxt.read_i16be()?;
+                let device_offset = usize::from(ctxt.read_u16be()?);
+                let opt_device = if device_offset == 0 {
+                    None
+                } else {
+                    scope.offset(device_offset).read::<Option<Device>>()?
+                };
+                Ok(CaretValue::DeviceCoordinate(coordinate, opt_device))
+            }
+            _ => Err(ParseError::BadVersion),
+        }
+    }
+}
+
+impl ReadBinary for Option<Device> {
+    type HostType<'a> = Self;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self, ParseError> {
+        let start_size = ctxt.read_u16be()?;
+        let end_size = ctxt.read_u16be()?;
+        let delta_format = ctxt.read_u16be()?;
+        let bits_per_value = match delta_format {
+            1 => 2,
+            2 => 4,
+            3 => 8,
+            0x8000 => {
+                return Ok(Some(Device::Variation(VariationIndex {
+                    outer_index: start_size,
+                    inner_index: end_size,
+                })))
+            }
+            // Unknown formats are ignored, as they are by `VariationIndex`
+            _ => return Ok(None),
+        };
+        if end_size < start_size {
+            return Err(ParseError::BadValue);
+        }
+        let value_count = usize::from(end_size - start_size) + 1;
+        let values_per_word = 16 / bits_per_value;
+        let word_count = (value_count + values_per_word - 1) / values_per_word;
+        let words = ctxt.read_array::<U16Be>(word_count)?;
+        let delta_values = (0..value_count)
+            .map(|i| {
+                let word = words.get_item(i / values_per_word);
+                let shift = 16 - bits_per_value * (1 + i % values_per_word);
+                // Shift the value to the top of an i16 then back down to sign extend it
+                let value = ((word >> shift) << (16 - bits_per_value)) as i16;
+                (value >> (16 - bits_per_value)) as i8
+            })
+            .collect();
+        Ok(Some(Device::Hinting {
+            start_size,
+            end_size,
+            delta_values,
+        }))
+    }
+}
+
+impl Device {
+    /// Returns the adjustment in pixels for the size `ppem`, if this is a hinting device table.
+    pub fn delta(&self, ppem: u16) -> i16 {
+        match self {
+            Device::Hinting {
+                start_size,
+                delta_values,
+                ..
+            } => ppem
+                .checked_sub(*start_size)
+                .and_then(|index| delta_values.get(usize::from(index)))
+                .map_or(0, |&delta| i16::from(delta)),
+            Device::Variation(_) => 0,
+        }
+    }
+}
+
+fn read_mark_glyph_sets(scope: ReadScope<'_>) -> Result<Vec<Coverage>, ParseError> {
+    let mut ctxt = scope.ctxt();
+    let format = ctxt.read_u16be()?;
+    ctxt.check(format == 1)?;
+    let mark_glyph_set_count = usize::from(ctxt.read_u16be()?);
+    let coverage_offsets = ctxt.read_array::<U32Be>(mark_glyph_set_count)?;
+    coverage_offsets
+        .iter()
+        .map(|offset| scope.offset(usize::safe_from(offset)).read::<Coverage>())
+        .collect()
+}
+
 impl<T> ReadBinary for LayoutTable<T> {
     type HostType<'a> = Self;
 
@@ -497,6 +771,11 @@ impl FeatureRecord {
     pub fn feature_table(&self) -> &FeatureTable {
         &self.feature_table
     }
+
+    /// The parameters of the feature, if it has any.
+    pub fn feature_params(&self) -> Option<&FeatureParams> {
+        self.feature_params.as_ref()
+    }
 }
 
 impl ReadBinaryDep for FeatureRecord {
@@ -506,12 +785,17 @@ impl ReadBinaryDep for FeatureRecord {
     fn read_dep<'a>(ctxt: &mut ReadCtxt<'a>, scope: Self::Args<'a>) -> Result<Self, ParseError> {
         let feature_tag = ctxt.read_u32be()?;
         let feature_offset = ctxt.read_u16be()?;
-        let feature_table = scope
-            .offset(usize::from(feature_offset))
-            .read::<FeatureTable>()?;
+        let feature_scope = scope.offset(usize::from(feature_offset));
+        let feature_table = feature_scope.read::<FeatureTable>()?;
+        // The parameters are only used for display, so a malformed parameters table shouldn't
+        // prevent the feature from being applied
+        let feature_params = FeatureParams::read_for_feature(feature_scope, feature_tag)
+            .ok()
+            .flatten();
         Ok(FeatureRecord {
             feature_tag,
             feature_table,
+            feature_params,
         })
     }
 }
@@ -533,6 +817,86 @@ impl ReadBinary for FeatureTable {
     }
 }
 
+impl FeatureParams {
+    /// Read the parameters of the feature table at `feature_scope`, if the feature has them.
+    fn read_for_feature(
+        feature_scope: ReadScope<'_>,
+        feature_tag: u32,
+    ) -> Result<Option<FeatureParams>, ParseError> {
+        let feature_params_offset = feature_scope.read::<U16Be>()?;
+        if feature_params_offset == 0 {
+            return Ok(None);
+        }
+        let mut ctxt = feature_scope
+            .offset(usize::from(feature_params_offset))
+            .ctxt();
+        let feature_params = match feature_tag {
+            tag::SIZE => FeatureParams::Size(SizeParams {
+                design_size: ctxt.read_u16be()?,
+                subfamily_id: ctxt.read_u16be()?,
+                subfamily_name_id: ctxt.read_u16be()?,
+                range_start: ctxt.read_u16be()?,
+                range_end: ctxt.read_u16be()?,
+            }),
+            _ if is_stylistic_set(feature_tag) => {
+                let _version = ctxt.read_u16be()?;
+                FeatureParams::StylisticSet(StylisticSetParams {
+                    ui_name_id: ctxt.read_u16be()?,
+                })
+            }
+            _ if is_character_variant(feature_tag) => {
+                let _format = ctxt.read_u16be()?;
+                let label_name_id = ctxt.read_u16be()?;
+                let tooltip_name_id = ctxt.read_u16be()?;
+                let sample_text_name_id = ctxt.read_u16be()?;
+                let num_named_parameters = ctxt.read_u16be()?;
+                let first_param_ui_label_name_id = ctxt.read_u16be()?;
+                let char_count = usize::from(ctxt.read_u16be()?);
+                let characters = ctxt.read_array::<U24Be>(char_count)?.to_vec();
+                FeatureParams::CharacterVariant(CharacterVariantParams {
+                    label_name_id,
+                    tooltip_name_id,
+                    sample_text_name_id,
+                    num_named_parameters,
+                    first_param_ui_label_name_id,
+                    characters,
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(feature_params))
+    }
+
+    /// The name ID of the user interface name of the feature, if it has one.
+    ///
+    /// This is the subfamily name of the `size` feature, the name of a stylistic set, or the
+    /// label of a character variant.
+    pub fn ui_name_id(&self) -> Option<u16> {
+        let name_id = match self {
+            FeatureParams::Size(params) => params.subfamily_name_id,
+            FeatureParams::StylisticSet(params) => params.ui_name_id,
+            FeatureParams::CharacterVariant(params) => params.label_name_id,
+        };
+        (name_id != 0).then_some(name_id)
+    }
+}
+
+/// Returns `true` if `feature_tag` is a stylistic set feature, `ss01` to `ss20`.
+pub(crate) fn is_stylistic_set(feature_tag: u32) -> bool {
+    matches!(
+        feature_tag.to_be_bytes(),
+        [b's', b's', b'0'..=b'9', b'0'..=b'9']
+    )
+}
+
+/// Returns `true` if `feature_tag` is a character variant feature, `cv01` to `cv99`.
+pub(crate) fn is_character_variant(feature_tag: u32) -> bool {
+    matches!(
+        feature_tag.to_be_bytes(),
+        [b'c', b'v', b'0'..=b'9', b'0'..=b'9']
+    )
+}
+
 impl ReadBinary for FeatureVariations<'_> {
     type HostType<'a> = FeatureVariations<'a>;
 
@@ -816,11 +1180,11 @@ impl ReadBinary for LangSys {
 
     fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self, ParseError> {
         let _reserved_lookup_order = ctxt.read_u16be()?;
-        let _required_feature_index = ctxt.read_u16be()?;
+        let required_feature_index = ctxt.read_u16be()?;
         let feature_index_count = usize::from(ctxt.read_u16be()?);
         let feature_indices = ctxt.read_array::<U16Be>(feature_index_count)?.to_vec();
         Ok(LangSys {
-            _required_feature_index,
+            required_feature_index,
             feature_indices,
         })
     }
@@ -836,6 +1200,14 @@ impl LangSys {
     pub fn feature_indices_iter(&self) -> impl Iterator<Item = &u16> {
         self.feature_indices.iter()
     }
+
+    /// Returns the index of the feature that is required for this language system, if any.
+    pub fn required_feature_index(&self) -> Option<u16> {
+        match self.required_feature_index {
+            0xFFFF => None,
+            feature_index => Some(feature_index),
+        }
+    }
 }
 
 impl<T> LayoutTable<T> {
@@ -1046,6 +1418,7 @@ impl LookupList<GSUB> {
         };
         Ok(LookupCacheItem {
             lookup_flag,
+            mark_filtering_set: lookup.mark_filtering_set,
             lookup_subtables,
         })
     }
@@ -1104,6 +1477,7 @@ impl LookupList<GPOS> {
         };
         Ok(LookupCacheItem {
             lookup_flag,
+            mark_filtering_set: lookup.mark_filtering_set,
             lookup_subtables,
         })
     }
@@ -1189,11 +1563,17 @@ impl<'b, T: LayoutTableType> ReadBinary for Lookup<'b, T> {
         let lookup_flag = ctxt.read_u16be()?;
         let subtable_count = usize::from(ctxt.read_u16be()?);
         let subtable_offsets = ctxt.read_array::<U16Be>(subtable_count)?;
+        let mark_filtering_set = if LookupFlag(lookup_flag).get_use_mark_filtering_set() {
+            Some(ctxt.read_u16be()?)
+        } else {
+            None
+        };
         Ok(Lookup {
             scope,
             lookup_type,
             lookup_flag,
             subtable_offsets,
+            mark_filtering_set,
             phantom: PhantomData,
         })
     }
@@ -3326,6 +3706,23 @@ impl Coverage {
         }
     }
 
+    /// Returns the covered glyphs, in coverage index order.
+    pub(crate) fn glyphs(&self) -> Vec<u16> {
+        match self {
+            Coverage::Format1 { glyph_array } => glyph_array.clone(),
+            Coverage::Format2 {
+                coverage_range_array,
+            } => {
+                let mut ranges = coverage_range_array.iter().collect::<Vec<_>>();
+                ranges.sort_by_key(|range| range.start_coverage_index);
+                ranges
+                    .into_iter()
+                    .flat_map(|range| range.start_glyph..=range.end_glyph)
+                    .collect()
+            }
+        }
+    }
+
     /// Convenience method to count the total number of glyphs covered
     pub fn glyph_count(&self) -> usize {
         match self {
@@ -3403,6 +3800,32 @@ impl ReadBinary for ClassDef {
 }
 
 impl ClassDef {
+    /// Returns the glyphs that are assigned a class other than zero, and their classes.
+    pub(crate) fn glyph_classes(&self) -> Vec<(u16, u16)> {
+        match self {
+            ClassDef::Format1 {
+                start_glyph,
+                class_value_array,
+            } => class_value_array
+                .iter()
+                .enumerate()
+                .filter(|&(_, &class_value)| class_value != 0)
+                .filter_map(|(index, &class_value)| {
+                    let glyph = u16::try_from(usize::from(*start_glyph) + index).ok()?;
+                    Some((glyph, class_value))
+                })
+                .collect(),
+            ClassDef::Format2 { class_range_array } => class_range_array
+                .iter()
+                .filter(|range| range.class_value != 0)
+                .flat_map(|range| {
+                    (range.start_glyph..=range.end_glyph)
+                        .map(move |glyph| (glyph, range.class_value))
+                })
+                .collect(),
+        }
+    }
+
     pub fn glyph_class_value(&self, glyph: u16) -> u16 {
         match *self {
             ClassDef::Format1 {
@@ -3438,6 +3861,7 @@ pub type LookupCache<T> = Vec<Option<Rc<LookupCacheItem<T>>>>;
 
 pub struct LookupCacheItem<T> {
     pub lookup_flag: LookupFlag,
+    pub mark_filtering_set: Option<u16>,
     pub lookup_subtables: T,
 }
 
@@ -0,0 +1,2443 @@
+//! Subsetting of the `GSUB`, `GPOS`, and `GDEF` tables.
+//!
+//! The tables are rewritten from their binary form, so data that isn't read for shaping, such as
+//! device tables, is preserved. Glyph ids are remapped with [SubsetGlyphs], and coverage and
+//! class definition tables are reduced to the retained glyphs. Lookup subtables, lookups, and
+//! features that no longer apply to any glyph are removed.
+//!
+//! The same rewriting is used by partial instancing, with every glyph retained, to renormalize
+//! the variation data of the tables to the new limits of the variation axes.
+
+use std::convert::TryFrom;
+
+use log::warn;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{
+    is_character_variant, is_stylistic_set, new_layout_cache, ChainContextLookup, ClassDef,
+    ContextLookup, Coverage, LayoutCache, LayoutTable, LookupList, ReverseChainSingleSubst,
+    SubstLookup, GSUB,
+};
+use crate::binary::read::{ReadCtxt, ReadScope};
+use crate::binary::write::{Placeholder, WriteBinary, WriteBuffer, WriteContext};
+use crate::binary::{U16Be, U32Be};
+use crate::error::{ParseError, ReadWriteError, WriteError};
+use crate::gsub::{FeatureMask, Features};
+use crate::subset::SubsetGlyphs;
+use crate::tables::variable_fonts::{owned, ItemVariationStore, NormalizedAxisLimit};
+use crate::tables::{F2Dot14, FontTableProvider};
+use crate::tag;
+
+/// Returns `glyph_ids` followed by the glyphs that the `GSUB` table can substitute them with.
+///
+/// Only the lookups of the features enabled by `features` and the required features are
+/// considered, in any script and language. Contextual lookups are assumed to match, so the closure
+/// can contain glyphs that are never reached when shaping.
+pub(crate) fn gsub_closure(
+    provider: &impl FontTableProvider,
+    glyph_ids: &[u16],
+    features: &Features,
+) -> Result<Vec<u16>, ParseError> {
+    let mut closure = glyph_ids.to_vec();
+    let gsub_data = match provider.table_data(tag::GSUB)? {
+        Some(gsub_data) => gsub_data,
+        None => return Ok(closure),
+    };
+    let gsub = ReadScope::new(&gsub_data).read::<LayoutTable<GSUB>>()?;
+    let cache = new_layout_cache(gsub);
+    let lookup_list = match cache.layout_table.opt_lookup_list {
+        Some(ref lookup_list) => lookup_list,
+        None => return Ok(closure),
+    };
+    let lookup_indices = feature_lookups(&cache, lookup_list, features)?;
+
+    let mut seen = closure.iter().copied().collect::<FxHashSet<_>>();
+    let mut start = 0;
+    // Each pass substitutes the glyphs that were added by the previous one
+    while start < closure.len() {
+        let glyphs = closure.clone();
+        let mut added = Vec::new();
+        let mut add = |glyph: u16| {
+            if seen.insert(glyph) {
+                added.push(glyph);
+            }
+        };
+        for &lookup_index in &lookup_indices {
+            let lookup = lookup_list.lookup_cache_gsub(&cache, lookup_index)?;
+            match lookup.lookup_subtables {
+                SubstLookup::SingleSubst(ref subtables) => {
+                    for subtable in subtables {
+                        for &glyph in &glyphs[start..] {
+                            if let Some(output) = subtable.apply_glyph(glyph)? {
+                                add(output);
+                            }
+                        }
+                    }
+                }
+                SubstLookup::MultipleSubst(ref subtables) => {
+                    for subtable in subtables {
+                        for &glyph in &glyphs[start..] {
+                            if let Some(sequence) = subtable.apply_glyph(glyph)? {
+                                sequence
+                                    .substitute_glyphs
+                                    .iter()
+                                    .copied()
+                                    .for_each(&mut add);
+                            }
+                        }
+                    }
+                }
+                SubstLookup::AlternateSubst(ref subtables) => {
+                    for subtable in subtables {
+                        for &glyph in &glyphs[start..] {
+                            if let Some(alternate_set) = subtable.apply_glyph(glyph)? {
+                                alternate_set
+                                    .alternate_glyphs
+                                    .iter()
+                                    .copied()
+                                    .for_each(&mut add);
+                            }
+                        }
+                    }
+                }
+                SubstLookup::LigatureSubst(ref subtables) => {
+                    // Ligatures are checked against all glyphs, since their other components may
+                    // have been added after the first one
+                    for subtable in subtables {
+                        for &glyph in &glyphs {
+                            let Some(ligature_set) = subtable.apply_glyph(glyph)? else {
+                                continue;
+                            };
+                            for ligature in &ligature_set.ligatures {
+                                let has_components = ligature
+                                    .component_glyphs
+                                    .iter()
+                                    .all(|component| glyphs.contains(component));
+                                if has_components {
+                                    add(ligature.ligature_glyph);
+                                }
+                            }
+                        }
+                    }
+                }
+                SubstLookup::ReverseChainSingleSubst(ref subtables) => {
+                    for subtable in subtables {
+                        let ReverseChainSingleSubst::Format1 {
+                            coverage,
+                            substitute_glyphs,
+                            ..
+                        } = subtable;
+                        for &glyph in &glyphs[start..] {
+                            if let Some(index) = coverage.glyph_coverage_value(glyph) {
+                                if let Some(&output) = substitute_glyphs.get(usize::from(index)) {
+                                    add(output);
+                                }
+                            }
+                        }
+                    }
+                }
+                // The lookups of contextual substitutions are in `lookup_indices` too
+                SubstLookup::ContextSubst(_) | SubstLookup::ChainContextSubst(_) => {}
+            }
+        }
+        start = closure.len();
+        closure.extend(added);
+    }
+    Ok(closure)
+}
+
+/// Returns the indices of the lookups of the features that are enabled by `features` or required,
+/// in any script and language, followed by the lookups that their contextual lookups apply.
+fn feature_lookups(
+    cache: &LayoutCache<GSUB>,
+    lookup_list: &LookupList<GSUB>,
+    features: &Features,
+) -> Result<Vec<usize>, ParseError> {
+    let gsub = &cache.layout_table;
+    let script_list = match gsub.opt_script_list {
+        Some(ref script_list) => script_list,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut feature_indices = Vec::new();
+    for script_record in script_list.script_records() {
+        let script = script_record.script_table();
+        let langsys_tables = script.default_langsys_record().into_iter().chain(
+            script
+                .langsys_records()
+                .iter()
+                .map(|langsys_record| langsys_record.langsys_table()),
+        );
+        for langsys in langsys_tables {
+            feature_indices.extend(langsys.required_feature_index());
+            for &feature_index in langsys.feature_indices_iter() {
+                let feature_tag = gsub.feature_by_index(feature_index)?.feature_tag;
+                let enabled = match features {
+                    // Shaping with a mask never applies the features that don't have a flag
+                    Features::Mask(mask) => {
+                        let feature_mask = FeatureMask::from_tag(feature_tag);
+                        !feature_mask.is_empty() && mask.contains(feature_mask)
+                    }
+                    Features::Custom(_) => features.is_enabled(feature_tag),
+                };
+                if enabled {
+                    feature_indices.push(feature_index);
+                }
+            }
+        }
+    }
+    feature_indices.sort_unstable();
+    feature_indices.dedup();
+
+    let mut lookup_indices = Vec::new();
+    let mut seen = FxHashSet::default();
+    let mut add = |lookup_index: u16| {
+        let lookup_index = usize::from(lookup_index);
+        if lookup_index < lookup_list.lookup_offsets.len() && seen.insert(lookup_index) {
+            lookup_indices.push(lookup_index);
+        }
+    };
+    for feature_index in feature_indices {
+        let feature_table = gsub.feature_by_index(feature_index)?.feature_table();
+        feature_table
+            .lookup_indices
+            .iter()
+            .copied()
+            .for_each(&mut add);
+        // The feature can be substituted by any of the feature variations
+        if let Some(ref feature_variations) = gsub.opt_feature_variations {
+            let scope = feature_variations.record_scope.scope();
+            for record in &feature_variations.records {
+                let substitution = record.feature_table_substitution(scope)?;
+                if let Some(feature_table) = substitution.substitute(feature_index) {
+                    feature_table
+                        .lookup_indices
+                        .iter()
+                        .copied()
+                        .for_each(&mut add);
+                }
+            }
+        }
+    }
+
+    // Each contextual lookup can add the lookups that it applies to the end
+    let mut index = 0;
+    while index < lookup_indices.len() {
+        let lookup = lookup_list.lookup_cache_gsub(cache, lookup_indices[index])?;
+        let lookup_records = match lookup.lookup_subtables {
+            SubstLookup::ContextSubst(ref subtables) => subtables
+                .iter()
+                .flat_map(context_lookup_records)
+                .collect::<Vec<_>>(),
+            SubstLookup::ChainContextSubst(ref subtables) => subtables
+                .iter()
+                .flat_map(chain_context_lookup_records)
+                .collect::<Vec<_>>(),
+            _ => Vec::new(),
+        };
+        for (_sequence_index, lookup_index) in lookup_records {
+            let lookup_index = usize::from(lookup_index);
+            if lookup_index < lookup_list.lookup_offsets.len() && seen.insert(lookup_index) {
+                lookup_indices.push(lookup_index);
+            }
+        }
+        index += 1;
+    }
+    Ok(lookup_indices)
+}
+
+/// Returns the sequence lookup records of all of the rules of a contextual lookup.
+fn context_lookup_records(subtable: &ContextLookup<GSUB>) -> Vec<(u16, u16)> {
+    match subtable {
+        ContextLookup::Format1 { subrulesets, .. } => subrulesets
+            .iter()
+            .flatten()
+            .flat_map(|subruleset| &subruleset.subrules)
+            .flat_map(|subrule| subrule.lookup_records.iter().copied())
+            .collect(),
+        ContextLookup::Format2 { subclasssets, .. } => subclasssets
+            .iter()
+            .flatten()
+            .flat_map(|subclassset| &subclassset.subclassrules)
+            .flat_map(|subclassrule| subclassrule.lookup_records.iter().copied())
+            .collect(),
+        ContextLookup::Format3 { lookup_records, .. } => lookup_records.clone(),
+    }
+}
+
+/// Returns the sequence lookup records of all of the rules of a chained contextual lookup.
+fn chain_context_lookup_records(subtable: &ChainContextLookup<GSUB>) -> Vec<(u16, u16)> {
+    match subtable {
+        ChainContextLookup::Format1 {
+            chainsubrulesets, ..
+        } => chainsubrulesets
+            .iter()
+            .flatten()
+            .flat_map(|chainsubruleset| &chainsubruleset.chainsubrules)
+            .flat_map(|chainsubrule| chainsubrule.lookup_records.iter().copied())
+            .collect(),
+        ChainContextLookup::Format2 {
+            chainsubclasssets, ..
+        } => chainsubclasssets
+            .iter()
+            .flatten()
+            .flat_map(|chainsubclassset| &chainsubclassset.chainsubclassrules)
+            .flat_map(|chainsubclassrule| chainsubclassrule.lookup_records.iter().copied())
+            .collect(),
+        ChainContextLookup::Format3 { lookup_records, .. } => lookup_records.clone(),
+    }
+}
+
+/// Subset the `GDEF`, `GSUB`, and `GPOS` tables of the font to the glyphs in `glyphs`.
+///
+/// Returns the tag and data of each table. These tables aren't needed to render the font, so a
+/// table that can't be subset is left out and a warning is logged, rather than failing.
+pub(crate) fn subset_layout_tables(
+    provider: &impl FontTableProvider,
+    glyphs: &impl SubsetGlyphs,
+) -> Result<Vec<(u32, WriteBuffer)>, ParseError> {
+    let mut tables = Vec::new();
+    for table_tag in [tag::GDEF, tag::GSUB, tag::GPOS] {
+        let data = match provider.table_data(table_tag)? {
+            Some(data) => data,
+            None => continue,
+        };
+        let mut subsetter = Subsetter {
+            glyphs,
+            lookup_map: None,
+            axis_limits: None,
+        };
+        let table_type = match table_tag {
+            tag::GSUB => TableType::Gsub,
+            tag::GPOS => TableType::Gpos,
+            _ => TableType::Gdef,
+        };
+        let result = match table_type {
+            TableType::Gdef => subsetter.subset_gdef(ReadScope::new(&data)).map(Some),
+            _ => subsetter.subset_layout_table(ReadScope::new(&data), table_type),
+        };
+        match result {
+            Ok(Some(table)) => tables.push((table_tag, table)),
+            Ok(None) => {}
+            Err(err) => warn!(
+                "unable to subset {} table: {:?}",
+                tag::DisplayTag(table_tag),
+                err
+            ),
+        }
+    }
+    Ok(tables)
+}
+
+/// Rewrite the `GDEF`, `GSUB`, and `GPOS` tables of a font with `num_glyphs` glyphs for its
+/// variation axes being limited to `limits`, one for each axis of the font.
+///
+/// The regions of the item variation store in `GDEF`, and the conditions of the feature
+/// variations in `GSUB` and `GPOS`, are renormalized to the new ranges of the axes, and pinned
+/// axes are removed from them. Feature variations that can no longer match are removed. Returns
+/// the tag and data of each table that has variation data. Unlike subsetting, a table that can't
+/// be rewritten is an error, since the font would otherwise be left with stale variation data.
+pub(crate) fn limit_layout_tables(
+    provider: &impl FontTableProvider,
+    num_glyphs: u16,
+    limits: &[NormalizedAxisLimit],
+) -> Result<Vec<(u32, WriteBuffer)>, ReadWriteError> {
+    let glyphs = AllGlyphs(num_glyphs);
+    let mut tables = Vec::new();
+    for table_tag in [tag::GDEF, tag::GSUB, tag::GPOS] {
+        let data = match provider.table_data(table_tag)? {
+            Some(data) => data,
+            None => continue,
+        };
+        let scope = ReadScope::new(&data);
+        let table_type = match table_tag {
+            tag::GSUB => TableType::Gsub,
+            tag::GPOS => TableType::Gpos,
+            _ => TableType::Gdef,
+        };
+        if !has_variation_data(scope, table_type)? {
+            continue;
+        }
+        let mut subsetter = Subsetter {
+            glyphs: &glyphs,
+            lookup_map: None,
+            axis_limits: Some(limits),
+        };
+        let table = match table_type {
+            TableType::Gdef => Some(subsetter.subset_gdef(scope)?),
+            _ => subsetter.subset_layout_table(scope, table_type)?,
+        };
+        if let Some(table) = table {
+            tables.push((table_tag, table));
+        }
+    }
+    Ok(tables)
+}
+
+/// Returns `true` if `GDEF` has an item variation store, or `GSUB` or `GPOS` has feature
+/// variations.
+fn has_variation_data(scope: ReadScope<'_>, table_type: TableType) -> Result<bool, ParseError> {
+    let mut ctxt = scope.ctxt();
+    let major_version = ctxt.read_u16be()?;
+    ctxt.check_version(major_version == 1)?;
+    let minor_version = ctxt.read_u16be()?;
+    match table_type {
+        TableType::Gdef if minor_version >= 3 => {
+            // glyphClassDef, attachList, ligCaretList, markAttachClassDef, markGlyphSetsDef
+            ctxt.read_array::<U16Be>(5)?;
+            Ok(ctxt.read_u32be()? != 0)
+        }
+        TableType::Gsub | TableType::Gpos if minor_version >= 1 => {
+            // scriptList, featureList, lookupList
+            ctxt.read_array::<U16Be>(3)?;
+            Ok(ctxt.read_u32be()? != 0)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Every glyph of a font, with unchanged ids.
+struct AllGlyphs(u16);
+
+impl SubsetGlyphs for AllGlyphs {
+    fn len(&self) -> usize {
+        usize::from(self.0)
+    }
+
+    fn old_id(&self, new_id: u16) -> u16 {
+        new_id
+    }
+
+    fn new_id(&self, old_id: u16) -> u16 {
+        old_id
+    }
+}
+
+/// A condition of a feature variation after the axes of the font have been limited.
+#[derive(Debug, PartialEq)]
+enum LimitedCondition {
+    /// The condition holds everywhere in the new variation space.
+    Always,
+    /// The condition holds nowhere in the new variation space.
+    Never,
+    /// The condition holds for values of the axis at the index between the minimum and maximum.
+    Range(u16, F2Dot14, F2Dot14),
+}
+
+/// Limit a condition that holds for values of the axis at `axis_index` from `min` to `max`.
+///
+/// The range is renormalized in the same way as the regions of variation data: values between
+/// zero and the new maximum are scaled to between zero and one, and the same for the minimum.
+fn limit_condition(
+    axis_index: u16,
+    min: F2Dot14,
+    max: F2Dot14,
+    limits: &[NormalizedAxisLimit],
+) -> Result<LimitedCondition, ParseError> {
+    let limit = limits
+        .get(usize::from(axis_index))
+        .ok_or(ParseError::BadIndex)?;
+    match *limit {
+        NormalizedAxisLimit::Pin(value) => {
+            if min <= value && value <= max {
+                Ok(LimitedCondition::Always)
+            } else {
+                Ok(LimitedCondition::Never)
+            }
+        }
+        NormalizedAxisLimit::Range(lower, upper) => {
+            if max < lower || min > upper || min > max {
+                return Ok(LimitedCondition::Never);
+            }
+            let renormalize = |value: F2Dot14| {
+                let value = f32::from(value.clamp(lower, upper));
+                if value > 0. {
+                    F2Dot14::from(value / f32::from(upper))
+                } else if value < 0. {
+                    F2Dot14::from(value / -f32::from(lower))
+                } else {
+                    F2Dot14::from(0)
+                }
+            };
+            let new_index = limits[..usize::from(axis_index)]
+                .iter()
+                .filter(|limit| matches!(limit, NormalizedAxisLimit::Range(..)))
+                .count();
+            Ok(LimitedCondition::Range(
+                u16::try_from(new_index)?,
+                renormalize(min),
+                renormalize(max),
+            ))
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum TableType {
+    Gdef,
+    Gsub,
+    Gpos,
+}
+
+impl TableType {
+    fn extension_lookup_type(self) -> u16 {
+        match self {
+            TableType::Gpos => 9,
+            _ => 7,
+        }
+    }
+}
+
+struct Subsetter<'a> {
+    glyphs: &'a dyn SubsetGlyphs,
+    /// The new index of each lookup, or `None` if it was removed.
+    ///
+    /// This is `None` until the lookups that are retained are known.
+    lookup_map: Option<Vec<Option<u16>>>,
+    /// The limits of the variation axes when creating a partial instance, which the variation
+    /// data is renormalized to.
+    axis_limits: Option<&'a [NormalizedAxisLimit]>,
+}
+
+/// A lookup that has been subset.
+struct SubsetLookup {
+    lookup_type: u16,
+    lookup_flag: u16,
+    subtables: Vec<WriteBuffer>,
+    mark_filtering_set: Option<u16>,
+}
+
+/// A feature table, with lookup indices that have been remapped.
+struct SubsetFeature {
+    feature_tag: u32,
+    params: Option<WriteBuffer>,
+    lookup_indices: Vec<u16>,
+}
+
+/// A record of the `FeatureVariations` table, with lookup indices that have been remapped.
+struct SubsetFeatureVariation {
+    condition_set: Option<WriteBuffer>,
+    /// The substitute lookups of features, by the index of the feature in the font.
+    substitutions: Option<Vec<(u16, Vec<u16>)>>,
+}
+
+/// The tables that a table refers to by offset, which are written after it.
+#[derive(Default)]
+struct Children {
+    links: Vec<(Link, WriteBuffer)>,
+}
+
+enum Link {
+    Offset16(Placeholder<U16Be, u16>),
+    Offset32(Placeholder<U32Be, u32>),
+}
+
+impl Children {
+    /// Write an `Offset16` to `table`, or a null offset if it's `None`.
+    fn offset16(
+        &mut self,
+        ctxt: &mut WriteBuffer,
+        table: Option<WriteBuffer>,
+    ) -> Result<(), WriteError> {
+        match table {
+            Some(table) => {
+                let placeholder = ctxt.placeholder::<U16Be, _>()?;
+                self.links.push((Link::Offset16(placeholder), table));
+            }
+            None => U16Be::write(ctxt, 0u16)?,
+        }
+        Ok(())
+    }
+
+    /// Write an `Offset32` to `table`, or a null offset if it's `None`.
+    fn offset32(
+        &mut self,
+        ctxt: &mut WriteBuffer,
+        table: Option<WriteBuffer>,
+    ) -> Result<(), WriteError> {
+        match table {
+            Some(table) => {
+                let placeholder = ctxt.placeholder::<U32Be, _>()?;
+                self.links.push((Link::Offset32(placeholder), table));
+            }
+            None => U32Be::write(ctxt, 0u32)?,
+        }
+        Ok(())
+    }
+
+    /// Write the tables after their parent, which starts at `start`.
+    ///
+    /// Identical tables, such as coverage tables that were shared in the font, are only written
+    /// once.
+    fn write(self, ctxt: &mut WriteBuffer, start: usize) -> Result<(), WriteError> {
+        let mut written = FxHashMap::<Vec<u8>, usize>::default();
+        for (link, table) in self.links {
+            let position = match written.get(table.bytes()) {
+                Some(&position) => position,
+                None => {
+                    let position = ctxt.bytes_written();
+                    ctxt.write_bytes(table.bytes())?;
+                    written.insert(table.into_inner(), position);
+                    position
+                }
+            };
+            let offset = position - start;
+            match link {
+                Link::Offset16(placeholder) => {
+                    ctxt.write_placeholder(placeholder, u16::try_from(offset)?)?
+                }
+                Link::Offset32(placeholder) => {
+                    ctxt.write_placeholder(placeholder, u32::try_from(offset)?)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Subsetter<'_> {
+    /// Returns the new id of `glyph`, or `None` if it isn't in the subset.
+    fn glyph(&self, glyph: u16) -> Option<u16> {
+        match self.glyphs.new_id(glyph) {
+            0 if glyph != 0 => None,
+            new_id => Some(new_id),
+        }
+    }
+
+    /// Returns the new index of the lookup at `lookup_index`, or `None` if it was removed.
+    fn lookup(&self, lookup_index: u16) -> Option<u16> {
+        match &self.lookup_map {
+            Some(lookup_map) => lookup_map.get(usize::from(lookup_index)).copied().flatten(),
+            None => Some(lookup_index),
+        }
+    }
+
+    /// Returns the retained glyphs of a coverage table with their coverage index in the font,
+    /// sorted by their new id.
+    fn subset_coverage(&self, coverage: &Coverage) -> Vec<(u16, usize)> {
+        let mut glyphs = coverage
+            .glyphs()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, glyph)| Some((self.glyph(glyph)?, index)))
+            .collect::<Vec<_>>();
+        glyphs.sort_unstable();
+        glyphs.dedup_by_key(|&mut (glyph, _)| glyph);
+        glyphs
+    }
+
+    fn subset_classdef(&self, classdef: &ClassDef) -> Result<WriteBuffer, WriteError> {
+        let mut classes = classdef
+            .glyph_classes()
+            .into_iter()
+            .filter_map(|(glyph, class_value)| Some((self.glyph(glyph)?, class_value)))
+            .collect::<Vec<_>>();
+        classes.sort_unstable();
+        classes.dedup_by_key(|&mut (glyph, _)| glyph);
+
+        // Ranges of consecutive glyphs in the same class
+        let mut ranges: Vec<(u16, u16, u16)> = Vec::new();
+        for (glyph, class_value) in classes {
+            match ranges.last_mut() {
+                Some((_, end, range_class))
+                    if u32::from(*end) + 1 == u32::from(glyph) && *range_class == class_value =>
+                {
+                    *end = glyph
+                }
+                _ => ranges.push((glyph, glyph, class_value)),
+            }
+        }
+        let mut ctxt = WriteBuffer::new();
+        U16Be::write(&mut ctxt, 2u16)?;
+        U16Be::write(&mut ctxt, u16::try_from(ranges.len())?)?;
+        for (start, end, class_value) in ranges {
+            ctxt.write_iter::<U16Be, _>(IntoIterator::into_iter([start, end, class_value]))?;
+        }
+        Ok(ctxt)
+    }
+
+    fn classdef_at(
+        &self,
+        scope: ReadScope<'_>,
+        offset: u16,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        if offset == 0 {
+            return Ok(None);
+        }
+        let classdef = scope.offset(usize::from(offset)).read::<ClassDef>()?;
+        Ok(Some(self.subset_classdef(&classdef)?))
+    }
+
+    /// Returns the subset coverage table at `offset` from `scope`, or `None` if none of its glyphs
+    /// are retained.
+    fn coverage_at(
+        &self,
+        scope: ReadScope<'_>,
+        offset: u16,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let coverage = read_coverage(scope, offset)?;
+        let glyphs = self.subset_coverage(&coverage);
+        if glyphs.is_empty() {
+            Ok(None)
+        } else {
+            let glyphs = glyphs
+                .into_iter()
+                .map(|(glyph, _)| glyph)
+                .collect::<Vec<_>>();
+            Ok(Some(coverage_table(&glyphs)?))
+        }
+    }
+
+    /// Read sequence lookup records and remap their lookup indices, removing the records of
+    /// lookups that were removed.
+    fn lookup_records(
+        &self,
+        ctxt: &mut ReadCtxt<'_>,
+        count: usize,
+    ) -> Result<Vec<(u16, u16)>, ReadWriteError> {
+        let mut records = Vec::with_capacity(count);
+        for _ in 0..count {
+            let sequence_index = ctxt.read_u16be()?;
+            let lookup_index = ctxt.read_u16be()?;
+            if let Some(lookup_index) = self.lookup(lookup_index) {
+                records.push((sequence_index, lookup_index));
+            }
+        }
+        Ok(records)
+    }
+
+    fn subset_layout_table(
+        &mut self,
+        scope: ReadScope<'_>,
+        table_type: TableType,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let major_version = ctxt.read_u16be()?;
+        ctxt.check_version(major_version == 1)?;
+        let minor_version = ctxt.read_u16be()?;
+        let script_list_offset = ctxt.read_u16be()?;
+        let feature_list_offset = ctxt.read_u16be()?;
+        let lookup_list_offset = ctxt.read_u16be()?;
+        let feature_variations_offset = if minor_version >= 1 {
+            ctxt.read_u32be()?
+        } else {
+            0
+        };
+        if lookup_list_offset == 0 {
+            return Ok(None);
+        }
+
+        // Lookups are subset twice: the first pass finds the lookups that are retained, and the
+        // second remaps the lookup indices of contextual lookups
+        let lookup_list = scope.offset(usize::from(lookup_list_offset));
+        let lookups = self.subset_lookups(lookup_list, table_type)?;
+        let mut lookup_map = Vec::with_capacity(lookups.len());
+        let mut num_lookups = 0;
+        for lookup in &lookups {
+            if lookup.is_some() {
+                lookup_map.push(Some(num_lookups));
+                num_lookups += 1;
+            } else {
+                lookup_map.push(None);
+            }
+        }
+        if num_lookups == 0 {
+            return Ok(None);
+        }
+        self.lookup_map = Some(lookup_map);
+        let lookups = self
+            .subset_lookups(lookup_list, table_type)?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let features = if feature_list_offset != 0 {
+            self.subset_features(scope.offset(usize::from(feature_list_offset)))?
+        } else {
+            Vec::new()
+        };
+        let feature_variations = if feature_variations_offset != 0 {
+            let feature_variations_scope =
+                scope.offset(usize::try_from(feature_variations_offset)?);
+            self.subset_feature_variations(feature_variations_scope)?
+        } else {
+            Vec::new()
+        };
+
+        // Features are kept if they have lookups, lookups in a feature variation, or parameters
+        let mut feature_map = vec![None; features.len()];
+        let mut num_features = 0;
+        for (feature_index, feature) in features.iter().enumerate() {
+            let has_variations = feature_variations.iter().any(|variation| {
+                variation
+                    .substitutions
+                    .iter()
+                    .flatten()
+                    .any(|(index, lookups)| {
+                        usize::from(*index) == feature_index && !lookups.is_empty()
+                    })
+            });
+            if !feature.lookup_indices.is_empty() || feature.params.is_some() || has_variations {
+                feature_map[feature_index] = Some(num_features);
+                num_features += 1;
+            }
+        }
+        let features = features
+            .into_iter()
+            .zip(&feature_map)
+            .filter_map(|(feature, new_index)| new_index.map(|_| feature))
+            .collect::<Vec<_>>();
+
+        let script_list = if script_list_offset != 0 {
+            Some(subset_script_list(
+                scope.offset(usize::from(script_list_offset)),
+                &feature_map,
+            )?)
+        } else {
+            None
+        };
+
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        U16Be::write(
+            &mut table,
+            if feature_variations.is_empty() {
+                0u16
+            } else {
+                1
+            },
+        )?;
+        children.offset16(&mut table, script_list)?;
+        children.offset16(&mut table, Some(write_feature_list(&features)?))?;
+        children.offset16(&mut table, Some(write_lookup_list(&lookups, table_type)?))?;
+        if !feature_variations.is_empty() {
+            let feature_variations = write_feature_variations(feature_variations, &feature_map)?;
+            children.offset32(&mut table, Some(feature_variations))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset the lookups of a lookup list, returning `None` for lookups that no longer have
+    /// any subtables.
+    fn subset_lookups(
+        &self,
+        lookup_list: ReadScope<'_>,
+        table_type: TableType,
+    ) -> Result<Vec<Option<SubsetLookup>>, ReadWriteError> {
+        let mut ctxt = lookup_list.ctxt();
+        let lookup_count = usize::from(ctxt.read_u16be()?);
+        let lookup_offsets = ctxt.read_array::<U16Be>(lookup_count)?;
+        let mut lookups = Vec::with_capacity(lookup_count);
+        for lookup_offset in lookup_offsets.iter() {
+            let lookup = lookup_list.offset(usize::from(lookup_offset));
+            let mut ctxt = lookup.ctxt();
+            let mut lookup_type = ctxt.read_u16be()?;
+            let lookup_flag = ctxt.read_u16be()?;
+            let subtable_count = usize::from(ctxt.read_u16be()?);
+            let subtable_offsets = ctxt.read_array::<U16Be>(subtable_count)?;
+            // The UseMarkFilteringSet flag
+            let mark_filtering_set = if lookup_flag & 0x0010 != 0 {
+                Some(ctxt.read_u16be()?)
+            } else {
+                None
+            };
+
+            let mut subtables = Vec::new();
+            for subtable_offset in subtable_offsets.iter() {
+                let mut subtable = lookup.offset(usize::from(subtable_offset));
+                if lookup_type == table_type.extension_lookup_type() {
+                    let mut ctxt = subtable.ctxt();
+                    let format = ctxt.read_u16be()?;
+                    ctxt.check_version(format == 1)?;
+                    let extension_lookup_type = ctxt.read_u16be()?;
+                    let extension_offset = ctxt.read_u32be()?;
+                    subtable = subtable.offset(usize::try_from(extension_offset)?);
+                    // The subtables of an extension lookup must all have the same type
+                    lookup_type = extension_lookup_type;
+                }
+                if let Some(subtable) = self.subset_subtable(table_type, lookup_type, subtable)? {
+                    subtables.push(subtable);
+                }
+            }
+            if subtables.is_empty() {
+                lookups.push(None);
+            } else {
+                lookups.push(Some(SubsetLookup {
+                    lookup_type,
+                    lookup_flag,
+                    subtables,
+                    mark_filtering_set,
+                }));
+            }
+        }
+        Ok(lookups)
+    }
+
+    fn subset_subtable(
+        &self,
+        table_type: TableType,
+        lookup_type: u16,
+        subtable: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        match (table_type, lookup_type) {
+            (TableType::Gsub, 1) => self.subset_single_subst(subtable),
+            (TableType::Gsub, 2) => self.subset_sequence_subst(subtable, false),
+            (TableType::Gsub, 3) => self.subset_sequence_subst(subtable, true),
+            (TableType::Gsub, 4) => self.subset_ligature_subst(subtable),
+            (TableType::Gsub, 5) | (TableType::Gpos, 7) => self.subset_context(subtable),
+            (TableType::Gsub, 6) | (TableType::Gpos, 8) => self.subset_chain_context(subtable),
+            (TableType::Gsub, 8) => self.subset_reverse_chain_single_subst(subtable),
+            (TableType::Gpos, 1) => self.subset_single_pos(subtable),
+            (TableType::Gpos, 2) => self.subset_pair_pos(subtable),
+            (TableType::Gpos, 3) => self.subset_cursive_pos(subtable),
+            (TableType::Gpos, 4) | (TableType::Gpos, 6) => self.subset_mark_base_pos(subtable),
+            (TableType::Gpos, 5) => self.subset_mark_lig_pos(subtable),
+            _ => Err(ParseError::BadVersion.into()),
+        }
+    }
+
+    fn subset_single_subst(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let substitutes = match format {
+            1 => {
+                let delta_glyph_index = ctxt.read_i16be()?;
+                coverage
+                    .glyphs()
+                    .into_iter()
+                    .map(|glyph| glyph.wrapping_add(delta_glyph_index as u16))
+                    .collect::<Vec<_>>()
+            }
+            2 => {
+                let glyph_count = usize::from(ctxt.read_u16be()?);
+                ctxt.read_array::<U16Be>(glyph_count)?.to_vec()
+            }
+            _ => return Err(ParseError::BadVersion.into()),
+        };
+
+        let mut mapping = Vec::new();
+        for (glyph, index) in self.subset_coverage(&coverage) {
+            let substitute = substitutes.get(index).copied();
+            if let Some(substitute) = substitute.and_then(|substitute| self.glyph(substitute)) {
+                mapping.push((glyph, substitute));
+            }
+        }
+        if mapping.is_empty() {
+            return Ok(None);
+        }
+
+        let glyphs = mapping.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        // Format 1 is used when every glyph is substituted with the same delta
+        let delta = |&(glyph, substitute): &(u16, u16)| substitute.wrapping_sub(glyph);
+        let first_delta = delta(&mapping[0]);
+        if mapping.iter().all(|pair| delta(pair) == first_delta) {
+            U16Be::write(&mut table, 1u16)?;
+            children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+            U16Be::write(&mut table, first_delta)?;
+        } else {
+            U16Be::write(&mut table, 2u16)?;
+            children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+            U16Be::write(&mut table, u16::try_from(mapping.len())?)?;
+            table.write_iter::<U16Be, _>(mapping.iter().map(|&(_, substitute)| substitute))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset a multiple substitution, or an alternate substitution if `alternates` is `true`.
+    ///
+    /// A multiple substitution is removed when one of its glyphs isn't retained, while the
+    /// alternates that aren't retained are removed from an alternate set.
+    fn subset_sequence_subst(
+        &self,
+        scope: ReadScope<'_>,
+        alternates: bool,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let sequence_count = usize::from(ctxt.read_u16be()?);
+        let sequence_offsets = ctxt.read_array::<U16Be>(sequence_count)?;
+
+        let mut sequences = Vec::new();
+        for (glyph, index) in self.subset_coverage(&coverage) {
+            let sequence_offset = sequence_offsets.read_item(index)?;
+            let mut ctxt = scope.offset(usize::from(sequence_offset)).ctxt();
+            let glyph_count = usize::from(ctxt.read_u16be()?);
+            let glyphs = ctxt.read_array::<U16Be>(glyph_count)?;
+            let new_glyphs = glyphs
+                .iter()
+                .filter_map(|glyph| self.glyph(glyph))
+                .collect::<Vec<_>>();
+            let keep = if alternates {
+                !new_glyphs.is_empty()
+            } else {
+                new_glyphs.len() == glyphs.len()
+            };
+            if keep {
+                sequences.push((glyph, new_glyphs));
+            }
+        }
+        if sequences.is_empty() {
+            return Ok(None);
+        }
+
+        let glyphs = sequences
+            .iter()
+            .map(|&(glyph, _)| glyph)
+            .collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(sequences.len())?)?;
+        for (_, glyphs) in sequences {
+            let mut sequence = WriteBuffer::new();
+            U16Be::write(&mut sequence, u16::try_from(glyphs.len())?)?;
+            sequence.write_iter::<U16Be, _>(glyphs.into_iter())?;
+            children.offset16(&mut table, Some(sequence))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    fn subset_ligature_subst(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let ligature_set_count = usize::from(ctxt.read_u16be()?);
+        let ligature_set_offsets = ctxt.read_array::<U16Be>(ligature_set_count)?;
+
+        let mut ligature_sets = Vec::new();
+        for (glyph, index) in self.subset_coverage(&coverage) {
+            let ligature_set = scope.offset(usize::from(ligature_set_offsets.read_item(index)?));
+            let mut ctxt = ligature_set.ctxt();
+            let ligature_count = usize::from(ctxt.read_u16be()?);
+            let ligature_offsets = ctxt.read_array::<U16Be>(ligature_count)?;
+            let mut ligatures = Vec::new();
+            for ligature_offset in ligature_offsets.iter() {
+                let mut ctxt = ligature_set.offset(usize::from(ligature_offset)).ctxt();
+                let ligature_glyph = ctxt.read_u16be()?;
+                let component_count = usize::from(ctxt.read_u16be()?);
+                let components = ctxt.read_array::<U16Be>(component_count.saturating_sub(1))?;
+                let new_components = components
+                    .iter()
+                    .map(|component| self.glyph(component))
+                    .collect::<Option<Vec<_>>>();
+                if let (Some(ligature_glyph), Some(components)) =
+                    (self.glyph(ligature_glyph), new_components)
+                {
+                    ligatures.push((ligature_glyph, components));
+                }
+            }
+            if !ligatures.is_empty() {
+                ligature_sets.push((glyph, ligatures));
+            }
+        }
+        if ligature_sets.is_empty() {
+            return Ok(None);
+        }
+
+        let glyphs = ligature_sets
+            .iter()
+            .map(|&(glyph, _)| glyph)
+            .collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(ligature_sets.len())?)?;
+        for (_, ligatures) in ligature_sets {
+            let mut ligature_set = WriteBuffer::new();
+            let mut ligature_set_children = Children::default();
+            U16Be::write(&mut ligature_set, u16::try_from(ligatures.len())?)?;
+            for (ligature_glyph, components) in ligatures {
+                let mut ligature = WriteBuffer::new();
+                U16Be::write(&mut ligature, ligature_glyph)?;
+                U16Be::write(&mut ligature, u16::try_from(components.len() + 1)?)?;
+                ligature.write_iter::<U16Be, _>(components.into_iter())?;
+                ligature_set_children.offset16(&mut ligature_set, Some(ligature))?;
+            }
+            ligature_set_children.write(&mut ligature_set, 0)?;
+            children.offset16(&mut table, Some(ligature_set))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset a sequence context subtable, which is `GSUB` lookup type 5 or `GPOS` lookup type 7.
+    fn subset_context(&self, scope: ReadScope<'_>) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        match ctxt.read_u16be()? {
+            1 => {
+                let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+                let rule_set_count = usize::from(ctxt.read_u16be()?);
+                let rule_set_offsets = ctxt.read_array::<U16Be>(rule_set_count)?;
+                let mut rule_sets = Vec::new();
+                for (glyph, index) in self.subset_coverage(&coverage) {
+                    let rule_set_offset = rule_set_offsets.read_item(index)?;
+                    let rule_set = self.subset_rule_set(scope, rule_set_offset, |ctxt, rule| {
+                        let glyph_count = usize::from(ctxt.read_u16be()?);
+                        let lookup_count = usize::from(ctxt.read_u16be()?);
+                        let input = self.subset_sequence(ctxt, glyph_count.saturating_sub(1))?;
+                        let records = self.lookup_records(ctxt, lookup_count)?;
+                        let Some(input) = input else {
+                            return Ok(false);
+                        };
+                        U16Be::write(rule, u16::try_from(input.len() + 1)?)?;
+                        U16Be::write(rule, u16::try_from(records.len())?)?;
+                        rule.write_iter::<U16Be, _>(input.into_iter())?;
+                        write_lookup_records(rule, &records)?;
+                        Ok(true)
+                    })?;
+                    if let Some(rule_set) = rule_set {
+                        rule_sets.push((glyph, rule_set));
+                    }
+                }
+                if rule_sets.is_empty() {
+                    return Ok(None);
+                }
+                let glyphs = rule_sets
+                    .iter()
+                    .map(|&(glyph, _)| glyph)
+                    .collect::<Vec<_>>();
+                U16Be::write(&mut table, 1u16)?;
+                children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+                U16Be::write(&mut table, u16::try_from(rule_sets.len())?)?;
+                for (_, rule_set) in rule_sets {
+                    children.offset16(&mut table, Some(rule_set))?;
+                }
+            }
+            2 => {
+                let Some(coverage) = self.coverage_at(scope, ctxt.read_u16be()?)? else {
+                    return Ok(None);
+                };
+                let classdef = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let class_set_count = usize::from(ctxt.read_u16be()?);
+                let class_set_offsets = ctxt.read_array::<U16Be>(class_set_count)?;
+                U16Be::write(&mut table, 2u16)?;
+                children.offset16(&mut table, Some(coverage))?;
+                children.offset16(&mut table, classdef)?;
+                U16Be::write(&mut table, u16::try_from(class_set_count)?)?;
+                for class_set_offset in class_set_offsets.iter() {
+                    let class_set =
+                        self.subset_rule_set(scope, class_set_offset, |ctxt, rule| {
+                            let glyph_count = usize::from(ctxt.read_u16be()?);
+                            let lookup_count = usize::from(ctxt.read_u16be()?);
+                            let input = ctxt.read_array::<U16Be>(glyph_count.saturating_sub(1))?;
+                            let records = self.lookup_records(ctxt, lookup_count)?;
+                            U16Be::write(rule, u16::try_from(glyph_count)?)?;
+                            U16Be::write(rule, u16::try_from(records.len())?)?;
+                            rule.write_iter::<U16Be, _>(input.iter())?;
+                            write_lookup_records(rule, &records)?;
+                            Ok(true)
+                        })?;
+                    children.offset16(&mut table, class_set)?;
+                }
+            }
+            3 => {
+                let glyph_count = usize::from(ctxt.read_u16be()?);
+                let lookup_count = usize::from(ctxt.read_u16be()?);
+                let coverage_offsets = ctxt.read_array::<U16Be>(glyph_count)?;
+                let records = self.lookup_records(&mut ctxt, lookup_count)?;
+                let Some(coverages) = self.coverages(scope, coverage_offsets.iter())? else {
+                    return Ok(None);
+                };
+                U16Be::write(&mut table, 3u16)?;
+                U16Be::write(&mut table, u16::try_from(glyph_count)?)?;
+                U16Be::write(&mut table, u16::try_from(records.len())?)?;
+                for coverage in coverages {
+                    children.offset16(&mut table, Some(coverage))?;
+                }
+                write_lookup_records(&mut table, &records)?;
+            }
+            _ => return Err(ParseError::BadVersion.into()),
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset a chained sequence context subtable, which is `GSUB` lookup type 6 or `GPOS`
+    /// lookup type 8.
+    fn subset_chain_context(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        match ctxt.read_u16be()? {
+            1 => {
+                let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+                let rule_set_count = usize::from(ctxt.read_u16be()?);
+                let rule_set_offsets = ctxt.read_array::<U16Be>(rule_set_count)?;
+                let mut rule_sets = Vec::new();
+                for (glyph, index) in self.subset_coverage(&coverage) {
+                    let rule_set_offset = rule_set_offsets.read_item(index)?;
+                    let rule_set = self.subset_rule_set(scope, rule_set_offset, |ctxt, rule| {
+                        let backtrack_count = usize::from(ctxt.read_u16be()?);
+                        let backtrack = self.subset_sequence(ctxt, backtrack_count)?;
+                        let input_count = usize::from(ctxt.read_u16be()?);
+                        let input = self.subset_sequence(ctxt, input_count.saturating_sub(1))?;
+                        let lookahead_count = usize::from(ctxt.read_u16be()?);
+                        let lookahead = self.subset_sequence(ctxt, lookahead_count)?;
+                        let lookup_count = usize::from(ctxt.read_u16be()?);
+                        let records = self.lookup_records(ctxt, lookup_count)?;
+                        let (Some(backtrack), Some(input), Some(lookahead)) =
+                            (backtrack, input, lookahead)
+                        else {
+                            return Ok(false);
+                        };
+                        U16Be::write(rule, u16::try_from(backtrack.len())?)?;
+                        rule.write_iter::<U16Be, _>(backtrack.into_iter())?;
+                        U16Be::write(rule, u16::try_from(input.len() + 1)?)?;
+                        rule.write_iter::<U16Be, _>(input.into_iter())?;
+                        U16Be::write(rule, u16::try_from(lookahead.len())?)?;
+                        rule.write_iter::<U16Be, _>(lookahead.into_iter())?;
+                        U16Be::write(rule, u16::try_from(records.len())?)?;
+                        write_lookup_records(rule, &records)?;
+                        Ok(true)
+                    })?;
+                    if let Some(rule_set) = rule_set {
+                        rule_sets.push((glyph, rule_set));
+                    }
+                }
+                if rule_sets.is_empty() {
+                    return Ok(None);
+                }
+                let glyphs = rule_sets
+                    .iter()
+                    .map(|&(glyph, _)| glyph)
+                    .collect::<Vec<_>>();
+                U16Be::write(&mut table, 1u16)?;
+                children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+                U16Be::write(&mut table, u16::try_from(rule_sets.len())?)?;
+                for (_, rule_set) in rule_sets {
+                    children.offset16(&mut table, Some(rule_set))?;
+                }
+            }
+            2 => {
+                let Some(coverage) = self.coverage_at(scope, ctxt.read_u16be()?)? else {
+                    return Ok(None);
+                };
+                let backtrack_classdef = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let input_classdef = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let lookahead_classdef = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let class_set_count = usize::from(ctxt.read_u16be()?);
+                let class_set_offsets = ctxt.read_array::<U16Be>(class_set_count)?;
+                U16Be::write(&mut table, 2u16)?;
+                children.offset16(&mut table, Some(coverage))?;
+                children.offset16(&mut table, backtrack_classdef)?;
+                children.offset16(&mut table, input_classdef)?;
+                children.offset16(&mut table, lookahead_classdef)?;
+                U16Be::write(&mut table, u16::try_from(class_set_count)?)?;
+                for class_set_offset in class_set_offsets.iter() {
+                    let class_set =
+                        self.subset_rule_set(scope, class_set_offset, |ctxt, rule| {
+                            for sequence in 0..3 {
+                                let count = usize::from(ctxt.read_u16be()?);
+                                // The count of the input sequence includes the first glyph
+                                let classes = if sequence == 1 {
+                                    ctxt.read_array::<U16Be>(count.saturating_sub(1))?
+                                } else {
+                                    ctxt.read_array::<U16Be>(count)?
+                                };
+                                U16Be::write(rule, u16::try_from(count)?)?;
+                                rule.write_iter::<U16Be, _>(classes.iter())?;
+                            }
+                            let lookup_count = usize::from(ctxt.read_u16be()?);
+                            let records = self.lookup_records(ctxt, lookup_count)?;
+                            U16Be::write(rule, u16::try_from(records.len())?)?;
+                            write_lookup_records(rule, &records)?;
+                            Ok(true)
+                        })?;
+                    children.offset16(&mut table, class_set)?;
+                }
+            }
+            3 => {
+                let backtrack_count = usize::from(ctxt.read_u16be()?);
+                let backtrack_offsets = ctxt.read_array::<U16Be>(backtrack_count)?;
+                let input_count = usize::from(ctxt.read_u16be()?);
+                let input_offsets = ctxt.read_array::<U16Be>(input_count)?;
+                let lookahead_count = usize::from(ctxt.read_u16be()?);
+                let lookahead_offsets = ctxt.read_array::<U16Be>(lookahead_count)?;
+                let lookup_count = usize::from(ctxt.read_u16be()?);
+                let records = self.lookup_records(&mut ctxt, lookup_count)?;
+                let (Some(backtrack), Some(input), Some(lookahead)) = (
+                    self.coverages(scope, backtrack_offsets.iter())?,
+                    self.coverages(scope, input_offsets.iter())?,
+                    self.coverages(scope, lookahead_offsets.iter())?,
+                ) else {
+                    return Ok(None);
+                };
+                U16Be::write(&mut table, 3u16)?;
+                for coverages in [backtrack, input, lookahead] {
+                    U16Be::write(&mut table, u16::try_from(coverages.len())?)?;
+                    for coverage in coverages {
+                        children.offset16(&mut table, Some(coverage))?;
+                    }
+                }
+                U16Be::write(&mut table, u16::try_from(records.len())?)?;
+                write_lookup_records(&mut table, &records)?;
+            }
+            _ => return Err(ParseError::BadVersion.into()),
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset the rule set at `offset`, using `subset_rule` to read each rule and write it to
+    /// the buffer it's given. `subset_rule` returns `false` if the rule should be removed.
+    ///
+    /// Returns `None` if the offset is null or no rules remain.
+    fn subset_rule_set(
+        &self,
+        scope: ReadScope<'_>,
+        offset: u16,
+        subset_rule: impl Fn(&mut ReadCtxt<'_>, &mut WriteBuffer) -> Result<bool, ReadWriteError>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        if offset == 0 {
+            return Ok(None);
+        }
+        let rule_set = scope.offset(usize::from(offset));
+        let mut ctxt = rule_set.ctxt();
+        let rule_count = usize::from(ctxt.read_u16be()?);
+        let rule_offsets = ctxt.read_array::<U16Be>(rule_count)?;
+        let mut rules = Vec::new();
+        for rule_offset in rule_offsets.iter() {
+            let mut rule = WriteBuffer::new();
+            let mut ctxt = rule_set.offset(usize::from(rule_offset)).ctxt();
+            if subset_rule(&mut ctxt, &mut rule)? {
+                rules.push(rule);
+            }
+        }
+        if rules.is_empty() {
+            return Ok(None);
+        }
+
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, u16::try_from(rules.len())?)?;
+        for rule in rules {
+            children.offset16(&mut table, Some(rule))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Read a sequence of glyphs and remap them, or return `None` if any of them aren't
+    /// retained.
+    fn subset_sequence(
+        &self,
+        ctxt: &mut ReadCtxt<'_>,
+        count: usize,
+    ) -> Result<Option<Vec<u16>>, ReadWriteError> {
+        let glyphs = ctxt.read_array::<U16Be>(count)?;
+        Ok(glyphs.iter().map(|glyph| self.glyph(glyph)).collect())
+    }
+
+    /// Subset the coverage tables at `offsets`, or return `None` if any of them become empty.
+    fn coverages(
+        &self,
+        scope: ReadScope<'_>,
+        offsets: impl Iterator<Item = u16>,
+    ) -> Result<Option<Vec<WriteBuffer>>, ReadWriteError> {
+        let mut coverages = Vec::new();
+        for offset in offsets {
+            match self.coverage_at(scope, offset)? {
+                Some(coverage) => coverages.push(coverage),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(coverages))
+    }
+
+    fn subset_reverse_chain_single_subst(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let backtrack_count = usize::from(ctxt.read_u16be()?);
+        let backtrack_offsets = ctxt.read_array::<U16Be>(backtrack_count)?;
+        let lookahead_count = usize::from(ctxt.read_u16be()?);
+        let lookahead_offsets = ctxt.read_array::<U16Be>(lookahead_count)?;
+        let glyph_count = usize::from(ctxt.read_u16be()?);
+        let substitutes = ctxt.read_array::<U16Be>(glyph_count)?;
+
+        let mut mapping = Vec::new();
+        for (glyph, index) in self.subset_coverage(&coverage) {
+            if let Some(substitute) = self.glyph(substitutes.read_item(index)?) {
+                mapping.push((glyph, substitute));
+            }
+        }
+        let (Some(backtrack), Some(lookahead)) = (
+            self.coverages(scope, backtrack_offsets.iter())?,
+            self.coverages(scope, lookahead_offsets.iter())?,
+        ) else {
+            return Ok(None);
+        };
+        if mapping.is_empty() {
+            return Ok(None);
+        }
+
+        let glyphs = mapping.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+        for coverages in [backtrack, lookahead] {
+            U16Be::write(&mut table, u16::try_from(coverages.len())?)?;
+            for coverage in coverages {
+                children.offset16(&mut table, Some(coverage))?;
+            }
+        }
+        U16Be::write(&mut table, u16::try_from(mapping.len())?)?;
+        table.write_iter::<U16Be, _>(mapping.iter().map(|&(_, substitute)| substitute))?;
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    fn subset_single_pos(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let value_format = ctxt.read_u16be()?;
+        let glyphs = self.subset_coverage(&coverage);
+        if glyphs.is_empty() {
+            return Ok(None);
+        }
+
+        let new_glyphs = glyphs.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, format)?;
+        children.offset16(&mut table, Some(coverage_table(&new_glyphs)?))?;
+        U16Be::write(&mut table, value_format & 0xFF)?;
+        match format {
+            1 => copy_value_record(&mut ctxt, scope, value_format, &mut table, &mut children)?,
+            2 => {
+                let value_count = usize::from(ctxt.read_u16be()?);
+                let value_records =
+                    ctxt.read_slice(value_count * value_record_size(value_format))?;
+                U16Be::write(&mut table, u16::try_from(glyphs.len())?)?;
+                for (_, index) in glyphs {
+                    let mut ctxt = ReadScope::new(value_records)
+                        .offset(index * value_record_size(value_format))
+                        .ctxt();
+                    copy_value_record(&mut ctxt, scope, value_format, &mut table, &mut children)?;
+                }
+            }
+            _ => return Err(ParseError::BadVersion.into()),
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    fn subset_pair_pos(&self, scope: ReadScope<'_>) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        let coverage_offset = ctxt.read_u16be()?;
+        let value_format1 = ctxt.read_u16be()?;
+        let value_format2 = ctxt.read_u16be()?;
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        match format {
+            1 => {
+                let coverage = read_coverage(scope, coverage_offset)?;
+                let pair_set_count = usize::from(ctxt.read_u16be()?);
+                let pair_set_offsets = ctxt.read_array::<U16Be>(pair_set_count)?;
+                let record_size =
+                    2 + value_record_size(value_format1) + value_record_size(value_format2);
+                let mut pair_sets = Vec::new();
+                for (glyph, index) in self.subset_coverage(&coverage) {
+                    let pair_set = scope.offset(usize::from(pair_set_offsets.read_item(index)?));
+                    let mut ctxt = pair_set.ctxt();
+                    let pair_value_count = usize::from(ctxt.read_u16be()?);
+                    let records = ReadScope::new(ctxt.read_slice(pair_value_count * record_size)?);
+                    let mut second_glyphs = Vec::new();
+                    for record_index in 0..pair_value_count {
+                        let mut ctxt = records.offset(record_index * record_size).ctxt();
+                        if let Some(second_glyph) = self.glyph(ctxt.read_u16be()?) {
+                            second_glyphs.push((second_glyph, record_index));
+                        }
+                    }
+                    if second_glyphs.is_empty() {
+                        continue;
+                    }
+                    // Pair value records are ordered by the second glyph
+                    second_glyphs.sort_unstable();
+                    let mut new_pair_set = WriteBuffer::new();
+                    let mut pair_set_children = Children::default();
+                    U16Be::write(&mut new_pair_set, u16::try_from(second_glyphs.len())?)?;
+                    for (second_glyph, record_index) in second_glyphs {
+                        let mut ctxt = records.offset(record_index * record_size + 2).ctxt();
+                        U16Be::write(&mut new_pair_set, second_glyph)?;
+                        for value_format in [value_format1, value_format2] {
+                            copy_value_record(
+                                &mut ctxt,
+                                pair_set,
+                                value_format,
+                                &mut new_pair_set,
+                                &mut pair_set_children,
+                            )?;
+                        }
+                    }
+                    pair_set_children.write(&mut new_pair_set, 0)?;
+                    pair_sets.push((glyph, new_pair_set));
+                }
+                if pair_sets.is_empty() {
+                    return Ok(None);
+                }
+                let glyphs = pair_sets
+                    .iter()
+                    .map(|&(glyph, _)| glyph)
+                    .collect::<Vec<_>>();
+                U16Be::write(&mut table, 1u16)?;
+                children.offset16(&mut table, Some(coverage_table(&glyphs)?))?;
+                U16Be::write(&mut table, value_format1 & 0xFF)?;
+                U16Be::write(&mut table, value_format2 & 0xFF)?;
+                U16Be::write(&mut table, u16::try_from(pair_sets.len())?)?;
+                for (_, pair_set) in pair_sets {
+                    children.offset16(&mut table, Some(pair_set))?;
+                }
+            }
+            2 => {
+                // Classes aren't renumbered, so the class records are copied as they are
+                let Some(coverage) = self.coverage_at(scope, coverage_offset)? else {
+                    return Ok(None);
+                };
+                let classdef1 = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let classdef2 = self.classdef_at(scope, ctxt.read_u16be()?)?;
+                let class1_count = ctxt.read_u16be()?;
+                let class2_count = ctxt.read_u16be()?;
+                U16Be::write(&mut table, 2u16)?;
+                children.offset16(&mut table, Some(coverage))?;
+                U16Be::write(&mut table, value_format1 & 0xFF)?;
+                U16Be::write(&mut table, value_format2 & 0xFF)?;
+                children.offset16(&mut table, classdef1)?;
+                children.offset16(&mut table, classdef2)?;
+                U16Be::write(&mut table, class1_count)?;
+                U16Be::write(&mut table, class2_count)?;
+                for _ in 0..usize::from(class1_count) * usize::from(class2_count) {
+                    for value_format in [value_format1, value_format2] {
+                        copy_value_record(
+                            &mut ctxt,
+                            scope,
+                            value_format,
+                            &mut table,
+                            &mut children,
+                        )?;
+                    }
+                }
+            }
+            _ => return Err(ParseError::BadVersion.into()),
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    fn subset_cursive_pos(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let entry_exit_count = usize::from(ctxt.read_u16be()?);
+        let entry_exit_records = ctxt.read_array::<(U16Be, U16Be)>(entry_exit_count)?;
+        let glyphs = self.subset_coverage(&coverage);
+        if glyphs.is_empty() {
+            return Ok(None);
+        }
+
+        let new_glyphs = glyphs.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&new_glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(glyphs.len())?)?;
+        for (_, index) in glyphs {
+            let (entry_offset, exit_offset) = entry_exit_records.read_item(index)?;
+            children.offset16(&mut table, anchor_at(scope, entry_offset)?)?;
+            children.offset16(&mut table, anchor_at(scope, exit_offset)?)?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset a mark-to-base or mark-to-mark attachment subtable, which have the same layout.
+    fn subset_mark_base_pos(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let mark_coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let base_coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let mark_class_count = usize::from(ctxt.read_u16be()?);
+        let mark_array = scope.offset(usize::from(ctxt.read_u16be()?));
+        let base_array = scope.offset(usize::from(ctxt.read_u16be()?));
+
+        let marks = self.subset_coverage(&mark_coverage);
+        let bases = self.subset_coverage(&base_coverage);
+        if marks.is_empty() || bases.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ctxt = base_array.ctxt();
+        let base_count = usize::from(ctxt.read_u16be()?);
+        let base_anchor_offsets = ctxt.read_array::<U16Be>(base_count * mark_class_count)?;
+        let mut new_base_array = WriteBuffer::new();
+        let mut base_array_children = Children::default();
+        U16Be::write(&mut new_base_array, u16::try_from(bases.len())?)?;
+        for &(_, index) in &bases {
+            for class in 0..mark_class_count {
+                let offset = base_anchor_offsets.read_item(index * mark_class_count + class)?;
+                base_array_children
+                    .offset16(&mut new_base_array, anchor_at(base_array, offset)?)?;
+            }
+        }
+        base_array_children.write(&mut new_base_array, 0)?;
+
+        let mark_glyphs = marks.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let base_glyphs = bases.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&mark_glyphs)?))?;
+        children.offset16(&mut table, Some(coverage_table(&base_glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(mark_class_count)?)?;
+        children.offset16(&mut table, Some(subset_mark_array(mark_array, &marks)?))?;
+        children.offset16(&mut table, Some(new_base_array))?;
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    fn subset_mark_lig_pos(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let mark_coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let ligature_coverage = read_coverage(scope, ctxt.read_u16be()?)?;
+        let mark_class_count = usize::from(ctxt.read_u16be()?);
+        let mark_array = scope.offset(usize::from(ctxt.read_u16be()?));
+        let ligature_array = scope.offset(usize::from(ctxt.read_u16be()?));
+
+        let marks = self.subset_coverage(&mark_coverage);
+        let ligatures = self.subset_coverage(&ligature_coverage);
+        if marks.is_empty() || ligatures.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ctxt = ligature_array.ctxt();
+        let ligature_count = usize::from(ctxt.read_u16be()?);
+        let ligature_attach_offsets = ctxt.read_array::<U16Be>(ligature_count)?;
+        let mut new_ligature_array = WriteBuffer::new();
+        let mut ligature_array_children = Children::default();
+        U16Be::write(&mut new_ligature_array, u16::try_from(ligatures.len())?)?;
+        for &(_, index) in &ligatures {
+            let ligature_attach =
+                ligature_array.offset(usize::from(ligature_attach_offsets.read_item(index)?));
+            let mut ctxt = ligature_attach.ctxt();
+            let component_count = usize::from(ctxt.read_u16be()?);
+            let anchor_offsets = ctxt.read_array::<U16Be>(component_count * mark_class_count)?;
+            let mut new_ligature_attach = WriteBuffer::new();
+            let mut ligature_attach_children = Children::default();
+            U16Be::write(&mut new_ligature_attach, u16::try_from(component_count)?)?;
+            for offset in anchor_offsets.iter() {
+                ligature_attach_children.offset16(
+                    &mut new_ligature_attach,
+                    anchor_at(ligature_attach, offset)?,
+                )?;
+            }
+            ligature_attach_children.write(&mut new_ligature_attach, 0)?;
+            ligature_array_children.offset16(&mut new_ligature_array, Some(new_ligature_attach))?;
+        }
+        ligature_array_children.write(&mut new_ligature_array, 0)?;
+
+        let mark_glyphs = marks.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let ligature_glyphs = ligatures
+            .iter()
+            .map(|&(glyph, _)| glyph)
+            .collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        children.offset16(&mut table, Some(coverage_table(&mark_glyphs)?))?;
+        children.offset16(&mut table, Some(coverage_table(&ligature_glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(mark_class_count)?)?;
+        children.offset16(&mut table, Some(subset_mark_array(mark_array, &marks)?))?;
+        children.offset16(&mut table, Some(new_ligature_array))?;
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Read the features of a feature list and remap their lookup indices.
+    fn subset_features(
+        &self,
+        feature_list: ReadScope<'_>,
+    ) -> Result<Vec<SubsetFeature>, ReadWriteError> {
+        let mut ctxt = feature_list.ctxt();
+        let feature_count = usize::from(ctxt.read_u16be()?);
+        let mut features = Vec::with_capacity(feature_count);
+        for _ in 0..feature_count {
+            let feature_tag = ctxt.read_u32be()?;
+            let feature = feature_list.offset(usize::from(ctxt.read_u16be()?));
+            let mut feature_ctxt = feature.ctxt();
+            let params_offset = feature_ctxt.read_u16be()?;
+            let lookup_indices = self.feature_lookup_indices(&mut feature_ctxt)?;
+            let params = copy_feature_params(feature, feature_tag, params_offset)?;
+            features.push(SubsetFeature {
+                feature_tag,
+                params,
+                lookup_indices,
+            });
+        }
+        Ok(features)
+    }
+
+    fn feature_lookup_indices(&self, ctxt: &mut ReadCtxt<'_>) -> Result<Vec<u16>, ReadWriteError> {
+        let lookup_index_count = usize::from(ctxt.read_u16be()?);
+        let lookup_indices = ctxt.read_array::<U16Be>(lookup_index_count)?;
+        Ok(lookup_indices
+            .iter()
+            .filter_map(|lookup_index| self.lookup(lookup_index))
+            .collect())
+    }
+
+    /// Read the records of a `FeatureVariations` table and remap the lookup indices of their
+    /// substitute feature tables.
+    ///
+    /// Records with a condition of an unknown format are removed, since they never match.
+    fn subset_feature_variations(
+        &self,
+        scope: ReadScope<'_>,
+    ) -> Result<Vec<SubsetFeatureVariation>, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let major_version = ctxt.read_u16be()?;
+        ctxt.check_version(major_version == 1)?;
+        let _minor_version = ctxt.read_u16be()?;
+        let record_count = usize::try_from(ctxt.read_u32be()?)?;
+        let records = ctxt.read_array::<(U32Be, U32Be)>(record_count)?;
+
+        let mut variations = Vec::with_capacity(record_count);
+        'records: for (condition_set_offset, substitution_offset) in records.iter() {
+            let condition_set = if condition_set_offset != 0 {
+                let condition_set_scope = scope.offset(usize::try_from(condition_set_offset)?);
+                let mut ctxt = condition_set_scope.ctxt();
+                let condition_count = usize::from(ctxt.read_u16be()?);
+                let condition_offsets = ctxt.read_array::<U32Be>(condition_count)?;
+                let mut conditions = Vec::with_capacity(condition_count);
+                for condition_offset in condition_offsets.iter() {
+                    let condition = condition_set_scope.offset(usize::try_from(condition_offset)?);
+                    let mut ctxt = condition.ctxt();
+                    if ctxt.read_u16be()? != 1 {
+                        continue 'records;
+                    }
+                    let mut new_condition = WriteBuffer::new();
+                    match self.axis_limits {
+                        Some(limits) => {
+                            let axis_index = ctxt.read_u16be()?;
+                            let min = ctxt.read::<F2Dot14>()?;
+                            let max = ctxt.read::<F2Dot14>()?;
+                            match limit_condition(axis_index, min, max, limits)? {
+                                LimitedCondition::Always => continue,
+                                LimitedCondition::Never => continue 'records,
+                                LimitedCondition::Range(axis_index, min, max) => {
+                                    U16Be::write(&mut new_condition, 1u16)?;
+                                    U16Be::write(&mut new_condition, axis_index)?;
+                                    F2Dot14::write(&mut new_condition, min)?;
+                                    F2Dot14::write(&mut new_condition, max)?;
+                                }
+                            }
+                        }
+                        None => new_condition.write_bytes(condition.offset_length(0, 8)?.data())?,
+                    }
+                    conditions.push(new_condition);
+                }
+                let mut condition_set = WriteBuffer::new();
+                let mut children = Children::default();
+                U16Be::write(&mut condition_set, u16::try_from(conditions.len())?)?;
+                for condition in conditions {
+                    children.offset32(&mut condition_set, Some(condition))?;
+                }
+                children.write(&mut condition_set, 0)?;
+                Some(condition_set)
+            } else {
+                None
+            };
+
+            let substitutions = if substitution_offset != 0 {
+                let substitution_scope = scope.offset(usize::try_from(substitution_offset)?);
+                let mut ctxt = substitution_scope.ctxt();
+                let major_version = ctxt.read_u16be()?;
+                ctxt.check_version(major_version == 1)?;
+                let _minor_version = ctxt.read_u16be()?;
+                let substitution_count = usize::from(ctxt.read_u16be()?);
+                let mut substitutions = Vec::with_capacity(substitution_count);
+                for _ in 0..substitution_count {
+                    let feature_index = ctxt.read_u16be()?;
+                    let feature_offset = usize::try_from(ctxt.read_u32be()?)?;
+                    let mut feature_ctxt = substitution_scope.offset(feature_offset).ctxt();
+                    let _params_offset = feature_ctxt.read_u16be()?;
+                    let lookup_indices = self.feature_lookup_indices(&mut feature_ctxt)?;
+                    substitutions.push((feature_index, lookup_indices));
+                }
+                Some(substitutions)
+            } else {
+                None
+            };
+
+            variations.push(SubsetFeatureVariation {
+                condition_set,
+                substitutions,
+            });
+        }
+        Ok(variations)
+    }
+
+    fn subset_gdef(&self, scope: ReadScope<'_>) -> Result<WriteBuffer, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let major_version = ctxt.read_u16be()?;
+        ctxt.check_version(major_version == 1)?;
+        let minor_version = ctxt.read_u16be()?;
+        let glyph_classdef_offset = ctxt.read_u16be()?;
+        let attach_list_offset = ctxt.read_u16be()?;
+        let lig_caret_list_offset = ctxt.read_u16be()?;
+        let mark_attach_classdef_offset = ctxt.read_u16be()?;
+        let mark_glyph_sets_offset = if minor_version >= 2 {
+            ctxt.read_u16be()?
+        } else {
+            0
+        };
+        let item_variation_store_offset = if minor_version >= 3 {
+            ctxt.read_u32be()?
+        } else {
+            0
+        };
+
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, major_version)?;
+        U16Be::write(&mut table, minor_version)?;
+        children.offset16(&mut table, self.classdef_at(scope, glyph_classdef_offset)?)?;
+        let attach_list = self.subset_glyph_records(scope, attach_list_offset, |record| {
+            let mut ctxt = record.ctxt();
+            let point_count = usize::from(ctxt.read_u16be()?);
+            let mut attach_point = WriteBuffer::new();
+            attach_point.write_bytes(record.offset_length(0, 2 + 2 * point_count)?.data())?;
+            Ok(attach_point)
+        })?;
+        children.offset16(&mut table, attach_list)?;
+        let lig_caret_list = self.subset_glyph_records(scope, lig_caret_list_offset, |record| {
+            let mut ctxt = record.ctxt();
+            let caret_count = usize::from(ctxt.read_u16be()?);
+            let caret_value_offsets = ctxt.read_array::<U16Be>(caret_count)?;
+            let mut lig_glyph = WriteBuffer::new();
+            let mut lig_glyph_children = Children::default();
+            U16Be::write(&mut lig_glyph, u16::try_from(caret_count)?)?;
+            for caret_value_offset in caret_value_offsets.iter() {
+                let caret_value = copy_caret_value(record.offset(usize::from(caret_value_offset)))?;
+                lig_glyph_children.offset16(&mut lig_glyph, Some(caret_value))?;
+            }
+            lig_glyph_children.write(&mut lig_glyph, 0)?;
+            Ok(lig_glyph)
+        })?;
+        children.offset16(&mut table, lig_caret_list)?;
+        children.offset16(
+            &mut table,
+            self.classdef_at(scope, mark_attach_classdef_offset)?,
+        )?;
+        if minor_version >= 2 {
+            let mark_glyph_sets = if mark_glyph_sets_offset != 0 {
+                let mark_glyph_sets = scope.offset(usize::from(mark_glyph_sets_offset));
+                Some(self.subset_mark_glyph_sets(mark_glyph_sets)?)
+            } else {
+                None
+            };
+            children.offset16(&mut table, mark_glyph_sets)?;
+        }
+        if minor_version >= 3 {
+            let item_variation_store = if item_variation_store_offset != 0 {
+                let item_variation_store =
+                    scope.offset(usize::try_from(item_variation_store_offset)?);
+                match self.axis_limits {
+                    Some(limits) => {
+                        let store = item_variation_store
+                            .read::<ItemVariationStore<'_>>()?
+                            .limit_axes(limits)?;
+                        let mut new_store = WriteBuffer::new();
+                        owned::ItemVariationStore::write(&mut new_store, &store)?;
+                        Some(new_store)
+                    }
+                    None => Some(copy_item_variation_store(item_variation_store)?),
+                }
+            } else {
+                None
+            };
+            children.offset32(&mut table, item_variation_store)?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(table)
+    }
+
+    /// Subset a table with a coverage table followed by an array of offsets to a table for each
+    /// covered glyph, such as the attachment list of `GDEF`.
+    fn subset_glyph_records(
+        &self,
+        scope: ReadScope<'_>,
+        offset: u16,
+        copy_record: impl Fn(ReadScope<'_>) -> Result<WriteBuffer, ReadWriteError>,
+    ) -> Result<Option<WriteBuffer>, ReadWriteError> {
+        if offset == 0 {
+            return Ok(None);
+        }
+        let list = scope.offset(usize::from(offset));
+        let mut ctxt = list.ctxt();
+        let coverage = read_coverage(list, ctxt.read_u16be()?)?;
+        let record_count = usize::from(ctxt.read_u16be()?);
+        let record_offsets = ctxt.read_array::<U16Be>(record_count)?;
+        let glyphs = self.subset_coverage(&coverage);
+
+        let new_glyphs = glyphs.iter().map(|&(glyph, _)| glyph).collect::<Vec<_>>();
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        children.offset16(&mut table, Some(coverage_table(&new_glyphs)?))?;
+        U16Be::write(&mut table, u16::try_from(glyphs.len())?)?;
+        for (_, index) in glyphs {
+            let record = list.offset(usize::from(record_offsets.read_item(index)?));
+            children.offset16(&mut table, Some(copy_record(record)?))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(Some(table))
+    }
+
+    /// Subset the mark glyph sets of `GDEF`. Sets that become empty are kept, since lookups refer
+    /// to the sets by index.
+    fn subset_mark_glyph_sets(&self, scope: ReadScope<'_>) -> Result<WriteBuffer, ReadWriteError> {
+        let mut ctxt = scope.ctxt();
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 1)?;
+        let mark_glyph_set_count = usize::from(ctxt.read_u16be()?);
+        let coverage_offsets = ctxt.read_array::<U32Be>(mark_glyph_set_count)?;
+
+        let mut table = WriteBuffer::new();
+        let mut children = Children::default();
+        U16Be::write(&mut table, 1u16)?;
+        U16Be::write(&mut table, u16::try_from(mark_glyph_set_count)?)?;
+        for coverage_offset in coverage_offsets.iter() {
+            let coverage = scope
+                .offset(usize::try_from(coverage_offset)?)
+                .read::<Coverage>()?;
+            let glyphs = self
+                .subset_coverage(&coverage)
+                .into_iter()
+                .map(|(glyph, _)| glyph)
+                .collect::<Vec<_>>();
+            children.offset32(&mut table, Some(coverage_table(&glyphs)?))?;
+        }
+        children.write(&mut table, 0)?;
+        Ok(table)
+    }
+}
+
+fn read_coverage(scope: ReadScope<'_>, offset: u16) -> Result<Coverage, ParseError> {
+    scope.offset(usize::from(offset)).read::<Coverage>()
+}
+
+/// Write a coverage table for `glyphs`, which must be sorted and unique.
+fn coverage_table(glyphs: &[u16]) -> Result<WriteBuffer, WriteError> {
+    // Ranges of consecutive glyphs, with the coverage index of their first glyph
+    let mut ranges: Vec<(u16, u16, u16)> = Vec::new();
+    for (index, &glyph) in glyphs.iter().enumerate() {
+        match ranges.last_mut() {
+            Some((_, end, _)) if u32::from(*end) + 1 == u32::from(glyph) => *end = glyph,
+            _ => ranges.push((glyph, glyph, u16::try_from(index)?)),
+        }
+    }
+
+    let mut ctxt = WriteBuffer::new();
+    if ranges.len() * 3 < glyphs.len() {
+        U16Be::write(&mut ctxt, 2u16)?;
+        U16Be::write(&mut ctxt, u16::try_from(ranges.len())?)?;
+        for (start, end, start_coverage_index) in ranges {
+            ctxt.write_iter::<U16Be, _>(IntoIterator::into_iter([
+                start,
+                end,
+                start_coverage_index,
+            ]))?;
+        }
+    } else {
+        U16Be::write(&mut ctxt, 1u16)?;
+        U16Be::write(&mut ctxt, u16::try_from(glyphs.len())?)?;
+        ctxt.write_iter::<U16Be, _>(glyphs.iter().copied())?;
+    }
+    Ok(ctxt)
+}
+
+fn write_lookup_records(ctxt: &mut WriteBuffer, records: &[(u16, u16)]) -> Result<(), WriteError> {
+    for &(sequence_index, lookup_index) in records {
+        U16Be::write(ctxt, sequence_index)?;
+        U16Be::write(ctxt, lookup_index)?;
+    }
+    Ok(())
+}
+
+fn value_record_size(value_format: u16) -> usize {
+    (value_format & 0xFF).count_ones() as usize * 2
+}
+
+/// Copy a value record, and the device tables it refers to, which are at offsets from `parent`.
+fn copy_value_record(
+    ctxt: &mut ReadCtxt<'_>,
+    parent: ReadScope<'_>,
+    value_format: u16,
+    table: &mut WriteBuffer,
+    children: &mut Children,
+) -> Result<(), ReadWriteError> {
+    for bit in 0..8 {
+        if value_format & (1 << bit) == 0 {
+            continue;
+        }
+        let value = ctxt.read_u16be()?;
+        // The first four fields are values and the rest are offsets to device tables
+        if bit < 4 {
+            U16Be::write(table, value)?;
+        } else {
+            children.offset16(table, device_at(parent, value)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the device or variation index table at `offset` from `scope`.
+fn device_at(scope: ReadScope<'_>, offset: u16) -> Result<Option<WriteBuffer>, ReadWriteError> {
+    if offset == 0 {
+        return Ok(None);
+    }
+    let device = scope.offset(usize::from(offset));
+    let mut ctxt = device.ctxt();
+    let start_size = ctxt.read_u16be()?;
+    let end_size = ctxt.read_u16be()?;
+    let delta_format = ctxt.read_u16be()?;
+    let length = match delta_format {
+        // Deltas of 2, 4, or 8 bits, packed into 16-bit words
+        1..=3 if start_size <= end_size => {
+            let delta_count = usize::from(end_size - start_size) + 1;
+            let bits = delta_count << delta_format;
+            6 + 2 * ((bits + 15) / 16)
+        }
+        _ => 6,
+    };
+    let mut table = WriteBuffer::new();
+    table.write_bytes(device.offset_length(0, length)?.data())?;
+    Ok(Some(table))
+}
+
+/// Copy the anchor table at `offset` from `scope`.
+fn anchor_at(scope: ReadScope<'_>, offset: u16) -> Result<Option<WriteBuffer>, ReadWriteError> {
+    if offset == 0 {
+        return Ok(None);
+    }
+    let anchor = scope.offset(usize::from(offset));
+    let mut ctxt = anchor.ctxt();
+    let mut table = WriteBuffer::new();
+    match ctxt.read_u16be()? {
+        1 => table.write_bytes(anchor.offset_length(0, 6)?.data())?,
+        // Format 2 has an anchor point
+        2 => table.write_bytes(anchor.offset_length(0, 8)?.data())?,
+        3 => {
+            let _x_coordinate = ctxt.read_i16be()?;
+            let _y_coordinate = ctxt.read_i16be()?;
+            let x_device_offset = ctxt.read_u16be()?;
+            let y_device_offset = ctxt.read_u16be()?;
+            let mut children = Children::default();
+            table.write_bytes(anchor.offset_length(0, 6)?.data())?;
+            children.offset16(&mut table, device_at(anchor, x_device_offset)?)?;
+            children.offset16(&mut table, device_at(anchor, y_device_offset)?)?;
+            children.write(&mut table, 0)?;
+        }
+        _ => return Err(ParseError::BadVersion.into()),
+    }
+    Ok(Some(table))
+}
+
+/// Subset a mark array to `marks`, the retained marks and their coverage index in the font.
+fn subset_mark_array(
+    mark_array: ReadScope<'_>,
+    marks: &[(u16, usize)],
+) -> Result<WriteBuffer, ReadWriteError> {
+    let mut ctxt = mark_array.ctxt();
+    let mark_count = usize::from(ctxt.read_u16be()?);
+    let mark_records = ctxt.read_array::<(U16Be, U16Be)>(mark_count)?;
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    U16Be::write(&mut table, u16::try_from(marks.len())?)?;
+    for &(_, index) in marks {
+        let (mark_class, anchor_offset) = mark_records.read_item(index)?;
+        U16Be::write(&mut table, mark_class)?;
+        children.offset16(&mut table, anchor_at(mark_array, anchor_offset)?)?;
+    }
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+/// Copy the parameters of a feature, if they're of a known type.
+fn copy_feature_params(
+    feature: ReadScope<'_>,
+    feature_tag: u32,
+    params_offset: u16,
+) -> Result<Option<WriteBuffer>, ReadWriteError> {
+    if params_offset == 0 {
+        return Ok(None);
+    }
+    let params = feature.offset(usize::from(params_offset));
+    let length = if feature_tag == tag::SIZE {
+        10
+    } else if is_stylistic_set(feature_tag) {
+        4
+    } else if is_character_variant(feature_tag) {
+        let mut ctxt = params.offset(12).ctxt();
+        let char_count = usize::from(ctxt.read_u16be()?);
+        14 + 3 * char_count
+    } else {
+        return Ok(None);
+    };
+    let mut table = WriteBuffer::new();
+    table.write_bytes(params.offset_length(0, length)?.data())?;
+    Ok(Some(table))
+}
+
+fn copy_caret_value(caret_value: ReadScope<'_>) -> Result<WriteBuffer, ReadWriteError> {
+    let mut ctxt = caret_value.ctxt();
+    let mut table = WriteBuffer::new();
+    match ctxt.read_u16be()? {
+        1 | 2 => table.write_bytes(caret_value.offset_length(0, 4)?.data())?,
+        3 => {
+            let _coordinate = ctxt.read_i16be()?;
+            let device_offset = ctxt.read_u16be()?;
+            let mut children = Children::default();
+            table.write_bytes(caret_value.offset_length(0, 4)?.data())?;
+            children.offset16(&mut table, device_at(caret_value, device_offset)?)?;
+            children.write(&mut table, 0)?;
+        }
+        _ => return Err(ParseError::BadVersion.into()),
+    }
+    Ok(table)
+}
+
+/// Copy an item variation store, which doesn't refer to glyphs.
+fn copy_item_variation_store(scope: ReadScope<'_>) -> Result<WriteBuffer, ReadWriteError> {
+    let mut ctxt = scope.ctxt();
+    let format = ctxt.read_u16be()?;
+    ctxt.check_version(format == 1)?;
+    let region_list_offset = usize::try_from(ctxt.read_u32be()?)?;
+    let data_count = usize::from(ctxt.read_u16be()?);
+    let data_offsets = ctxt.read_array::<U32Be>(data_count)?;
+
+    let region_list = scope.offset(region_list_offset);
+    let mut region_ctxt = region_list.ctxt();
+    let axis_count = usize::from(region_ctxt.read_u16be()?);
+    let region_count = usize::from(region_ctxt.read_u16be()?);
+    let mut new_region_list = WriteBuffer::new();
+    new_region_list.write_bytes(
+        region_list
+            .offset_length(0, 4 + region_count * axis_count * 6)?
+            .data(),
+    )?;
+
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    U16Be::write(&mut table, 1u16)?;
+    children.offset32(&mut table, Some(new_region_list))?;
+    U16Be::write(&mut table, u16::try_from(data_count)?)?;
+    for data_offset in data_offsets.iter() {
+        if data_offset == 0 {
+            children.offset32(&mut table, None)?;
+            continue;
+        }
+        let data = scope.offset(usize::try_from(data_offset)?);
+        let mut ctxt = data.ctxt();
+        let item_count = usize::from(ctxt.read_u16be()?);
+        let word_delta_count = ctxt.read_u16be()?;
+        let region_index_count = usize::from(ctxt.read_u16be()?);
+        // The high bit selects 32-bit and 16-bit deltas, rather than 16-bit and 8-bit deltas
+        let word_count = usize::from(word_delta_count & 0x7FFF);
+        let row_length = if word_delta_count & 0x8000 != 0 {
+            word_count * 4 + region_index_count.saturating_sub(word_count) * 2
+        } else {
+            word_count * 2 + region_index_count.saturating_sub(word_count)
+        };
+        let length = 6 + region_index_count * 2 + item_count * row_length;
+        let mut new_data = WriteBuffer::new();
+        new_data.write_bytes(data.offset_length(0, length)?.data())?;
+        children.offset32(&mut table, Some(new_data))?;
+    }
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+fn subset_script_list(
+    scope: ReadScope<'_>,
+    feature_map: &[Option<u16>],
+) -> Result<WriteBuffer, ReadWriteError> {
+    let mut ctxt = scope.ctxt();
+    let script_count = usize::from(ctxt.read_u16be()?);
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    U16Be::write(&mut table, u16::try_from(script_count)?)?;
+    for _ in 0..script_count {
+        let script_tag = ctxt.read_u32be()?;
+        let script = scope.offset(usize::from(ctxt.read_u16be()?));
+        let mut script_ctxt = script.ctxt();
+        let default_langsys_offset = script_ctxt.read_u16be()?;
+        let langsys_count = usize::from(script_ctxt.read_u16be()?);
+
+        let mut new_script = WriteBuffer::new();
+        let mut script_children = Children::default();
+        let default_langsys = if default_langsys_offset != 0 {
+            let langsys = script.offset(usize::from(default_langsys_offset));
+            Some(subset_langsys(langsys, feature_map)?)
+        } else {
+            None
+        };
+        script_children.offset16(&mut new_script, default_langsys)?;
+        U16Be::write(&mut new_script, u16::try_from(langsys_count)?)?;
+        for _ in 0..langsys_count {
+            let langsys_tag = script_ctxt.read_u32be()?;
+            let langsys = script.offset(usize::from(script_ctxt.read_u16be()?));
+            U32Be::write(&mut new_script, langsys_tag)?;
+            script_children
+                .offset16(&mut new_script, Some(subset_langsys(langsys, feature_map)?))?;
+        }
+        script_children.write(&mut new_script, 0)?;
+
+        U32Be::write(&mut table, script_tag)?;
+        children.offset16(&mut table, Some(new_script))?;
+    }
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+fn subset_langsys(
+    scope: ReadScope<'_>,
+    feature_map: &[Option<u16>],
+) -> Result<WriteBuffer, ReadWriteError> {
+    let new_feature_index = |feature_index: u16| {
+        feature_map
+            .get(usize::from(feature_index))
+            .copied()
+            .flatten()
+    };
+    let mut ctxt = scope.ctxt();
+    let _lookup_order_offset = ctxt.read_u16be()?;
+    let required_feature_index = ctxt.read_u16be()?;
+    let feature_index_count = usize::from(ctxt.read_u16be()?);
+    let feature_indices = ctxt
+        .read_array::<U16Be>(feature_index_count)?
+        .iter()
+        .filter_map(new_feature_index)
+        .collect::<Vec<_>>();
+
+    let mut table = WriteBuffer::new();
+    U16Be::write(&mut table, 0u16)?;
+    U16Be::write(
+        &mut table,
+        new_feature_index(required_feature_index).unwrap_or(0xFFFF),
+    )?;
+    U16Be::write(&mut table, u16::try_from(feature_indices.len())?)?;
+    table.write_iter::<U16Be, _>(feature_indices.into_iter())?;
+    Ok(table)
+}
+
+fn write_feature_table(
+    params: Option<WriteBuffer>,
+    lookup_indices: &[u16],
+) -> Result<WriteBuffer, WriteError> {
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    children.offset16(&mut table, params)?;
+    U16Be::write(&mut table, u16::try_from(lookup_indices.len())?)?;
+    table.write_iter::<U16Be, _>(lookup_indices.iter().copied())?;
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+fn write_feature_list(features: &[SubsetFeature]) -> Result<WriteBuffer, WriteError> {
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    U16Be::write(&mut table, u16::try_from(features.len())?)?;
+    for feature in features {
+        let params = feature.params.as_ref().map(|params| {
+            let mut copy = WriteBuffer::new();
+            copy.write_bytes(params.bytes()).map(|()| copy)
+        });
+        U32Be::write(&mut table, feature.feature_tag)?;
+        children.offset16(
+            &mut table,
+            Some(write_feature_table(
+                params.transpose()?,
+                &feature.lookup_indices,
+            )?),
+        )?;
+    }
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+fn write_feature_variations(
+    variations: Vec<SubsetFeatureVariation>,
+    feature_map: &[Option<u16>],
+) -> Result<WriteBuffer, WriteError> {
+    let mut table = WriteBuffer::new();
+    let mut children = Children::default();
+    U16Be::write(&mut table, 1u16)?;
+    U16Be::write(&mut table, 0u16)?;
+    U32Be::write(&mut table, u32::try_from(variations.len())?)?;
+    for variation in variations {
+        children.offset32(&mut table, variation.condition_set)?;
+        let substitution = match variation.substitutions {
+            Some(substitutions) => {
+                let substitutions = substitutions
+                    .into_iter()
+                    .filter_map(|(feature_index, lookup_indices)| {
+                        let feature_index = feature_map.get(usize::from(feature_index))?;
+                        Some(((*feature_index)?, lookup_indices))
+                    })
+                    .collect::<Vec<_>>();
+                let mut substitution = WriteBuffer::new();
+                let mut substitution_children = Children::default();
+                U16Be::write(&mut substitution, 1u16)?;
+                U16Be::write(&mut substitution, 0u16)?;
+                U16Be::write(&mut substitution, u16::try_from(substitutions.len())?)?;
+                for (feature_index, lookup_indices) in substitutions {
+                    U16Be::write(&mut substitution, feature_index)?;
+                    substitution_children.offset32(
+                        &mut substitution,
+                        Some(write_feature_table(None, &lookup_indices)?),
+                    )?;
+                }
+                substitution_children.write(&mut substitution, 0)?;
+                Some(substitution)
+            }
+            None => None,
+        };
+        children.offset32(&mut table, substitution)?;
+    }
+    children.write(&mut table, 0)?;
+    Ok(table)
+}
+
+/// Write a lookup list. If the offsets to the subtables of the lookups don't fit in 16 bits,
+/// the subtables are written as extension subtables instead.
+fn write_lookup_list(
+    lookups: &[SubsetLookup],
+    table_type: TableType,
+) -> Result<WriteBuffer, WriteError> {
+    write_lookup_list_impl(lookups, None)
+        .or_else(|_| write_lookup_list_impl(lookups, Some(table_type.extension_lookup_type())))
+}
+
+fn write_lookup_list_impl(
+    lookups: &[SubsetLookup],
+    extension_lookup_type: Option<u16>,
+) -> Result<WriteBuffer, WriteError> {
+    let mut table = WriteBuffer::new();
+    U16Be::write(&mut table, u16::try_from(lookups.len())?)?;
+    let lookup_offsets = table.placeholder_array::<U16Be, u16>(lookups.len())?;
+    // The subtables of extension lookups are written after all of the lookups
+    let mut extensions = Vec::new();
+    for (lookup, lookup_offset) in lookups.iter().zip(lookup_offsets) {
+        let lookup_start = table.bytes_written();
+        table.write_placeholder(lookup_offset, u16::try_from(lookup_start)?)?;
+        U16Be::write(
+            &mut table,
+            extension_lookup_type.unwrap_or(lookup.lookup_type),
+        )?;
+        U16Be::write(&mut table, lookup.lookup_flag)?;
+        U16Be::write(&mut table, u16::try_from(lookup.subtables.len())?)?;
+        let subtable_offsets = table.placeholder_array::<U16Be, u16>(lookup.subtables.len())?;
+        if let Some(mark_filtering_set) = lookup.mark_filtering_set {
+            U16Be::write(&mut table, mark_filtering_set)?;
+        }
+        for (subtable, subtable_offset) in lookup.subtables.iter().zip(subtable_offsets) {
+            let subtable_start = table.bytes_written();
+            table.write_placeholder(
+                subtable_offset,
+                u16::try_from(subtable_start - lookup_start)?,
+            )?;
+            if extension_lookup_type.is_some() {
+                U16Be::write(&mut table, 1u16)?;
+                U16Be::write(&mut table, lookup.lookup_type)?;
+                let extension_offset = table.placeholder::<U32Be, u32>()?;
+                extensions.push((subtable_start, extension_offset, subtable));
+            } else {
+                table.write_bytes(subtable.bytes())?;
+            }
+        }
+    }
+    for (extension_start, extension_offset, subtable) in extensions {
+        let offset = table.bytes_written() - extension_start;
+        table.write_placeholder(extension_offset, u32::try_from(offset)?)?;
+        table.write_bytes(subtable.bytes())?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_condition() {
+        let f2dot14 = F2Dot14::from;
+        let limits = [
+            NormalizedAxisLimit::Pin(f2dot14(0.5)),
+            NormalizedAxisLimit::Range(f2dot14(-0.5), f2dot14(0.25)),
+        ];
+        assert_eq!(
+            limit_condition(0, f2dot14(0.25), f2dot14(1.), &limits).unwrap(),
+            LimitedCondition::Always
+        );
+        assert_eq!(
+            limit_condition(0, f2dot14(-1.), f2dot14(0.), &limits).unwrap(),
+            LimitedCondition::Never
+        );
+        // The range is clamped to the limits and renormalized, on the second axis of the font
+        assert_eq!(
+            limit_condition(1, f2dot14(-0.25), f2dot14(0.5), &limits).unwrap(),
+            LimitedCondition::Range(0, f2dot14(-0.5), f2dot14(1.))
+        );
+        assert_eq!(
+            limit_condition(1, f2dot14(0.5), f2dot14(1.), &limits).unwrap(),
+            LimitedCondition::Never
+        );
+        assert!(limit_condition(2, f2dot14(0.), f2dot14(1.), &limits).is_err());
+    }
+}
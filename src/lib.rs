@@ -42,12 +42,6 @@
 //!
 //! ## Unimplemented Features / Known Issues
 //!
-//! We don't currently support:
-//!
-//! * Shaping Mongolian, and Tibetan.
-//! * Apple's [morx table](https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6morx.html).
-//! * Unicode normalisation.
-//!
 //! Known limitations:
 //!
 //! * The crate is not extensively documented yet ([#5](https://github.com/yeslogic/allsorts/issues/5)).
@@ -122,6 +116,8 @@
 //!
 //! See [LICENSE](https://github.com/yeslogic/allsorts/blob/master/LICENSE) for details.
 
+pub mod aat;
+pub mod bidi;
 pub mod big5;
 pub mod binary;
 pub mod bitmap;
@@ -139,6 +135,7 @@ pub mod glyph_info;
 pub mod glyph_position;
 pub mod gpos;
 pub mod gsub;
+pub mod itemize;
 pub mod layout;
 pub mod macroman;
 #[cfg(feature = "outline")]
@@ -6,12 +6,13 @@
 use unicode_joining_type::{get_joining_type, JoiningType};
 
 use crate::error::{ParseError, ShapingError};
-use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph};
+use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph, RawGlyphFlags};
 use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
 use crate::tag;
 use crate::unicode::mcc::{
     modified_combining_class, sort_by_modified_combining_class, ModifiedCombiningClass,
 };
+use crate::unicode::TextChar;
 
 #[derive(Clone)]
 struct ArabicData {
@@ -70,8 +71,10 @@ impl From<&RawGlyph<()>> for ArabicGlyph {
             glyph_index: raw_glyph.glyph_index,
             liga_component_pos: raw_glyph.liga_component_pos,
             glyph_origin: raw_glyph.glyph_origin,
+            cluster: raw_glyph.cluster,
             flags: raw_glyph.flags,
             variation: raw_glyph.variation,
+            feature_mask: raw_glyph.feature_mask,
             extra_data: ArabicData {
                 joining_type,
                 // For convenience, we loosely follow the spec (`2. Computing letter joining
@@ -89,8 +92,10 @@ impl From<&ArabicGlyph> for RawGlyph<()> {
             glyph_index: arabic_glyph.glyph_index,
             liga_component_pos: arabic_glyph.liga_component_pos,
             glyph_origin: arabic_glyph.glyph_origin,
+            cluster: arabic_glyph.cluster,
             flags: arabic_glyph.flags,
             variation: arabic_glyph.variation,
+            feature_mask: arabic_glyph.feature_mask,
             extra_data: (),
         }
     }
@@ -132,30 +137,7 @@ pub fn gsub_apply_arabic(
 
     // 2. Computing letter joining states
 
-    {
-        let mut previous_i = arabic_glyphs
-            .iter()
-            .position(|g| !g.is_transparent())
-            .unwrap_or(0);
-
-        for i in (previous_i + 1)..arabic_glyphs.len() {
-            if arabic_glyphs[i].is_transparent() {
-                continue;
-            }
-
-            if arabic_glyphs[previous_i].is_left_joining() && arabic_glyphs[i].is_right_joining() {
-                arabic_glyphs[i].set_feature_tag(tag::FINA);
-
-                match arabic_glyphs[previous_i].feature_tag() {
-                    tag::ISOL => arabic_glyphs[previous_i].set_feature_tag(tag::INIT),
-                    tag::FINA => arabic_glyphs[previous_i].set_feature_tag(tag::MEDI),
-                    _ => {}
-                }
-            }
-
-            previous_i = i;
-        }
-    }
+    compute_joining_states(arabic_glyphs);
 
     // 3. Applying the stch feature
     //
@@ -218,6 +200,221 @@ pub fn gsub_apply_arabic(
     Ok(())
 }
 
+fn compute_joining_states(arabic_glyphs: &mut [ArabicGlyph]) {
+    let mut previous_i = arabic_glyphs
+        .iter()
+        .position(|g| !g.is_transparent())
+        .unwrap_or(0);
+
+    for i in (previous_i + 1)..arabic_glyphs.len() {
+        if arabic_glyphs[i].is_transparent() {
+            continue;
+        }
+
+        if arabic_glyphs[previous_i].is_left_joining() && arabic_glyphs[i].is_right_joining() {
+            arabic_glyphs[i].set_feature_tag(tag::FINA);
+
+            match arabic_glyphs[previous_i].feature_tag() {
+                tag::ISOL => arabic_glyphs[previous_i].set_feature_tag(tag::INIT),
+                tag::FINA => arabic_glyphs[previous_i].set_feature_tag(tag::MEDI),
+                _ => {}
+            }
+        }
+
+        previous_i = i;
+    }
+}
+
+/// Shape Arabic text using the Arabic Presentation Forms-A and -B characters.
+///
+/// This is a fallback for fonts without a `GSUB` table, such as legacy Arabic fonts, that map
+/// the presentation forms in their `cmap` table instead. Each letter is mapped to the isolated,
+/// final, initial, or medial form given by its joining state, and LAM followed by ALEF is
+/// ligated. `lookup_glyph` returns the glyph of a character, or `None` if the font doesn't map
+/// it, in which case the glyph is left unchanged.
+pub fn apply_presentation_forms(
+    raw_glyphs: &mut Vec<RawGlyph<()>>,
+    mut lookup_glyph: impl FnMut(char) -> Option<u16>,
+) {
+    let mut arabic_glyphs = raw_glyphs.iter().map(ArabicGlyph::from).collect::<Vec<_>>();
+
+    compute_joining_states(&mut arabic_glyphs);
+
+    // Ligate LAM with a following ALEF. The ligature takes the form of the ALEF, which is final,
+    // so it's isolated when the LAM is initial, and final when the LAM is medial.
+    let mut i = 0;
+    while i < arabic_glyphs.len() {
+        if let Some((j, ligature_glyph)) = lam_alef_ligature(&arabic_glyphs, i, &mut lookup_glyph) {
+            let cluster = arabic_glyphs[i..=j]
+                .iter()
+                .map(|g| g.cluster)
+                .min()
+                .unwrap_or(arabic_glyphs[i].cluster);
+            let mut alef = arabic_glyphs.remove(j);
+            for g in &mut arabic_glyphs[i..j] {
+                g.cluster = cluster;
+            }
+            let lam = &mut arabic_glyphs[i];
+            lam.unicodes.append(&mut alef.unicodes);
+            lam.glyph_index = ligature_glyph;
+            lam.glyph_origin = GlyphOrigin::Direct;
+            lam.flags.set(RawGlyphFlags::LIGATURE, true);
+        }
+        i += 1;
+    }
+
+    for g in arabic_glyphs.iter_mut() {
+        let form = match g.glyph_origin {
+            GlyphOrigin::Char(ch) => presentation_form(ch, g.feature_tag()),
+            GlyphOrigin::Direct => None,
+        };
+        if let Some(glyph_index) = form.and_then(&mut lookup_glyph) {
+            g.glyph_index = glyph_index;
+            g.glyph_origin = GlyphOrigin::Direct;
+        }
+    }
+
+    *raw_glyphs = arabic_glyphs.iter().map(RawGlyph::from).collect();
+}
+
+/// Returns the index of the ALEF that ligates with the LAM at `i`, and the ligature glyph.
+fn lam_alef_ligature(
+    arabic_glyphs: &[ArabicGlyph],
+    i: usize,
+    lookup_glyph: &mut impl FnMut(char) -> Option<u16>,
+) -> Option<(usize, u16)> {
+    let lam = &arabic_glyphs[i];
+    if lam.glyph_origin != GlyphOrigin::Char('\u{0644}') {
+        return None;
+    }
+    let form = match lam.feature_tag() {
+        tag::INIT => 0,
+        tag::MEDI => 1,
+        _ => return None,
+    };
+    let j = i
+        + 1
+        + arabic_glyphs[i + 1..]
+            .iter()
+            .position(|g| !g.is_transparent())?;
+    let alef = match arabic_glyphs[j].glyph_origin {
+        GlyphOrigin::Char(ch) => ch,
+        GlyphOrigin::Direct => return None,
+    };
+    let (_, ligatures) = LAM_ALEF_LIGATURES.iter().find(|&&(ch, _)| ch == alef)?;
+    let ligature_glyph = lookup_glyph(ligatures[form])?;
+    Some((j, ligature_glyph))
+}
+
+/// Returns the presentation form of `ch` for the joining form `feature_tag`.
+fn presentation_form(ch: char, feature_tag: u32) -> Option<char> {
+    let form = match feature_tag {
+        tag::ISOL => 0,
+        tag::FINA => 1,
+        tag::INIT => 2,
+        tag::MEDI => 3,
+        _ => return None,
+    };
+    let index = PRESENTATION_FORMS
+        .binary_search_by_key(&u32::from(ch), |&(base, _)| u32::from(base))
+        .ok()?;
+    let (_, forms) = PRESENTATION_FORMS[index];
+    match forms[form] {
+        0 => None,
+        form => char::from_u32(u32::from(form)),
+    }
+}
+
+/// The isolated and final forms of the LAM-ALEF ligatures.
+const LAM_ALEF_LIGATURES: &[(char, [char; 2])] = &[
+    ('\u{0622}', ['\u{FEF5}', '\u{FEF6}']), // LAM WITH ALEF WITH MADDA ABOVE
+    ('\u{0623}', ['\u{FEF7}', '\u{FEF8}']), // LAM WITH ALEF WITH HAMZA ABOVE
+    ('\u{0625}', ['\u{FEF9}', '\u{FEFA}']), // LAM WITH ALEF WITH HAMZA BELOW
+    ('\u{0627}', ['\u{FEFB}', '\u{FEFC}']), // LAM WITH ALEF
+];
+
+/// The isolated, final, initial, and medial presentation forms of Arabic letters, from the
+/// Unicode Character Database. 0 means there is no such form.
+#[rustfmt::skip]
+const PRESENTATION_FORMS: &[(u16, [u16; 4])] = &[
+    (0x0621, [0xFE80, 0x0000, 0x0000, 0x0000]), // ARABIC LETTER HAMZA
+    (0x0622, [0xFE81, 0xFE82, 0x0000, 0x0000]), // ARABIC LETTER ALEF WITH MADDA ABOVE
+    (0x0623, [0xFE83, 0xFE84, 0x0000, 0x0000]), // ARABIC LETTER ALEF WITH HAMZA ABOVE
+    (0x0624, [0xFE85, 0xFE86, 0x0000, 0x0000]), // ARABIC LETTER WAW WITH HAMZA ABOVE
+    (0x0625, [0xFE87, 0xFE88, 0x0000, 0x0000]), // ARABIC LETTER ALEF WITH HAMZA BELOW
+    (0x0626, [0xFE89, 0xFE8A, 0xFE8B, 0xFE8C]), // ARABIC LETTER YEH WITH HAMZA ABOVE
+    (0x0627, [0xFE8D, 0xFE8E, 0x0000, 0x0000]), // ARABIC LETTER ALEF
+    (0x0628, [0xFE8F, 0xFE90, 0xFE91, 0xFE92]), // ARABIC LETTER BEH
+    (0x0629, [0xFE93, 0xFE94, 0x0000, 0x0000]), // ARABIC LETTER TEH MARBUTA
+    (0x062A, [0xFE95, 0xFE96, 0xFE97, 0xFE98]), // ARABIC LETTER TEH
+    (0x062B, [0xFE99, 0xFE9A, 0xFE9B, 0xFE9C]), // ARABIC LETTER THEH
+    (0x062C, [0xFE9D, 0xFE9E, 0xFE9F, 0xFEA0]), // ARABIC LETTER JEEM
+    (0x062D, [0xFEA1, 0xFEA2, 0xFEA3, 0xFEA4]), // ARABIC LETTER HAH
+    (0x062E, [0xFEA5, 0xFEA6, 0xFEA7, 0xFEA8]), // ARABIC LETTER KHAH
+    (0x062F, [0xFEA9, 0xFEAA, 0x0000, 0x0000]), // ARABIC LETTER DAL
+    (0x0630, [0xFEAB, 0xFEAC, 0x0000, 0x0000]), // ARABIC LETTER THAL
+    (0x0631, [0xFEAD, 0xFEAE, 0x0000, 0x0000]), // ARABIC LETTER REH
+    (0x0632, [0xFEAF, 0xFEB0, 0x0000, 0x0000]), // ARABIC LETTER ZAIN
+    (0x0633, [0xFEB1, 0xFEB2, 0xFEB3, 0xFEB4]), // ARABIC LETTER SEEN
+    (0x0634, [0xFEB5, 0xFEB6, 0xFEB7, 0xFEB8]), // ARABIC LETTER SHEEN
+    (0x0635, [0xFEB9, 0xFEBA, 0xFEBB, 0xFEBC]), // ARABIC LETTER SAD
+    (0x0636, [0xFEBD, 0xFEBE, 0xFEBF, 0xFEC0]), // ARABIC LETTER DAD
+    (0x0637, [0xFEC1, 0xFEC2, 0xFEC3, 0xFEC4]), // ARABIC LETTER TAH
+    (0x0638, [0xFEC5, 0xFEC6, 0xFEC7, 0xFEC8]), // ARABIC LETTER ZAH
+    (0x0639, [0xFEC9, 0xFECA, 0xFECB, 0xFECC]), // ARABIC LETTER AIN
+    (0x063A, [0xFECD, 0xFECE, 0xFECF, 0xFED0]), // ARABIC LETTER GHAIN
+    (0x0641, [0xFED1, 0xFED2, 0xFED3, 0xFED4]), // ARABIC LETTER FEH
+    (0x0642, [0xFED5, 0xFED6, 0xFED7, 0xFED8]), // ARABIC LETTER QAF
+    (0x0643, [0xFED9, 0xFEDA, 0xFEDB, 0xFEDC]), // ARABIC LETTER KAF
+    (0x0644, [0xFEDD, 0xFEDE, 0xFEDF, 0xFEE0]), // ARABIC LETTER LAM
+    (0x0645, [0xFEE1, 0xFEE2, 0xFEE3, 0xFEE4]), // ARABIC LETTER MEEM
+    (0x0646, [0xFEE5, 0xFEE6, 0xFEE7, 0xFEE8]), // ARABIC LETTER NOON
+    (0x0647, [0xFEE9, 0xFEEA, 0xFEEB, 0xFEEC]), // ARABIC LETTER HEH
+    (0x0648, [0xFEED, 0xFEEE, 0x0000, 0x0000]), // ARABIC LETTER WAW
+    (0x0649, [0xFEEF, 0xFEF0, 0xFBE8, 0xFBE9]), // ARABIC LETTER ALEF MAKSURA
+    (0x064A, [0xFEF1, 0xFEF2, 0xFEF3, 0xFEF4]), // ARABIC LETTER YEH
+    (0x0671, [0xFB50, 0xFB51, 0x0000, 0x0000]), // ARABIC LETTER ALEF WASLA
+    (0x0677, [0xFBDD, 0x0000, 0x0000, 0x0000]), // ARABIC LETTER U WITH HAMZA ABOVE
+    (0x0679, [0xFB66, 0xFB67, 0xFB68, 0xFB69]), // ARABIC LETTER TTEH
+    (0x067A, [0xFB5E, 0xFB5F, 0xFB60, 0xFB61]), // ARABIC LETTER TTEHEH
+    (0x067B, [0xFB52, 0xFB53, 0xFB54, 0xFB55]), // ARABIC LETTER BEEH
+    (0x067E, [0xFB56, 0xFB57, 0xFB58, 0xFB59]), // ARABIC LETTER PEH
+    (0x067F, [0xFB62, 0xFB63, 0xFB64, 0xFB65]), // ARABIC LETTER TEHEH
+    (0x0680, [0xFB5A, 0xFB5B, 0xFB5C, 0xFB5D]), // ARABIC LETTER BEHEH
+    (0x0683, [0xFB76, 0xFB77, 0xFB78, 0xFB79]), // ARABIC LETTER NYEH
+    (0x0684, [0xFB72, 0xFB73, 0xFB74, 0xFB75]), // ARABIC LETTER DYEH
+    (0x0686, [0xFB7A, 0xFB7B, 0xFB7C, 0xFB7D]), // ARABIC LETTER TCHEH
+    (0x0687, [0xFB7E, 0xFB7F, 0xFB80, 0xFB81]), // ARABIC LETTER TCHEHEH
+    (0x0688, [0xFB88, 0xFB89, 0x0000, 0x0000]), // ARABIC LETTER DDAL
+    (0x068C, [0xFB84, 0xFB85, 0x0000, 0x0000]), // ARABIC LETTER DAHAL
+    (0x068D, [0xFB82, 0xFB83, 0x0000, 0x0000]), // ARABIC LETTER DDAHAL
+    (0x068E, [0xFB86, 0xFB87, 0x0000, 0x0000]), // ARABIC LETTER DUL
+    (0x0691, [0xFB8C, 0xFB8D, 0x0000, 0x0000]), // ARABIC LETTER RREH
+    (0x0698, [0xFB8A, 0xFB8B, 0x0000, 0x0000]), // ARABIC LETTER JEH
+    (0x06A4, [0xFB6A, 0xFB6B, 0xFB6C, 0xFB6D]), // ARABIC LETTER VEH
+    (0x06A6, [0xFB6E, 0xFB6F, 0xFB70, 0xFB71]), // ARABIC LETTER PEHEH
+    (0x06A9, [0xFB8E, 0xFB8F, 0xFB90, 0xFB91]), // ARABIC LETTER KEHEH
+    (0x06AD, [0xFBD3, 0xFBD4, 0xFBD5, 0xFBD6]), // ARABIC LETTER NG
+    (0x06AF, [0xFB92, 0xFB93, 0xFB94, 0xFB95]), // ARABIC LETTER GAF
+    (0x06B1, [0xFB9A, 0xFB9B, 0xFB9C, 0xFB9D]), // ARABIC LETTER NGOEH
+    (0x06B3, [0xFB96, 0xFB97, 0xFB98, 0xFB99]), // ARABIC LETTER GUEH
+    (0x06BA, [0xFB9E, 0xFB9F, 0x0000, 0x0000]), // ARABIC LETTER NOON GHUNNA
+    (0x06BB, [0xFBA0, 0xFBA1, 0xFBA2, 0xFBA3]), // ARABIC LETTER RNOON
+    (0x06BE, [0xFBAA, 0xFBAB, 0xFBAC, 0xFBAD]), // ARABIC LETTER HEH DOACHASHMEE
+    (0x06C0, [0xFBA4, 0xFBA5, 0x0000, 0x0000]), // ARABIC LETTER HEH WITH YEH ABOVE
+    (0x06C1, [0xFBA6, 0xFBA7, 0xFBA8, 0xFBA9]), // ARABIC LETTER HEH GOAL
+    (0x06C5, [0xFBE0, 0xFBE1, 0x0000, 0x0000]), // ARABIC LETTER KIRGHIZ OE
+    (0x06C6, [0xFBD9, 0xFBDA, 0x0000, 0x0000]), // ARABIC LETTER OE
+    (0x06C7, [0xFBD7, 0xFBD8, 0x0000, 0x0000]), // ARABIC LETTER U
+    (0x06C8, [0xFBDB, 0xFBDC, 0x0000, 0x0000]), // ARABIC LETTER YU
+    (0x06C9, [0xFBE2, 0xFBE3, 0x0000, 0x0000]), // ARABIC LETTER KIRGHIZ YU
+    (0x06CB, [0xFBDE, 0xFBDF, 0x0000, 0x0000]), // ARABIC LETTER VE
+    (0x06CC, [0xFBFC, 0xFBFD, 0xFBFE, 0xFBFF]), // ARABIC LETTER FARSI YEH
+    (0x06D0, [0xFBE4, 0xFBE5, 0xFBE6, 0xFBE7]), // ARABIC LETTER E
+    (0x06D2, [0xFBAE, 0xFBAF, 0x0000, 0x0000]), // ARABIC LETTER YEH BARREE
+    (0x06D3, [0xFBB0, 0xFBB1, 0x0000, 0x0000]), // ARABIC LETTER YEH BARREE WITH HAMZA ABOVE
+];
+
 fn apply_lookups(
     feature_mask: FeatureMask,
     gsub_cache: &LayoutCache<GSUB>,
@@ -257,11 +454,11 @@ fn apply_lookups(
 }
 
 /// Reorder Arabic marks per AMTRA. See: https://www.unicode.org/reports/tr53/.
-pub(super) fn reorder_marks(cs: &mut [char]) {
+pub(super) fn reorder_marks<T: TextChar>(cs: &mut [T]) {
     sort_by_modified_combining_class(cs);
 
     for css in
-        cs.split_mut(|&c| modified_combining_class(c) == ModifiedCombiningClass::NotReordered)
+        cs.split_mut(|c| modified_combining_class(c.char()) == ModifiedCombiningClass::NotReordered)
     {
         reorder_marks_shadda(css);
         reorder_marks_other_combining(css, ModifiedCombiningClass::Above);
@@ -269,13 +466,13 @@ pub(super) fn reorder_marks(cs: &mut [char]) {
     }
 }
 
-fn reorder_marks_shadda(cs: &mut [char]) {
+fn reorder_marks_shadda<T: TextChar>(cs: &mut [T]) {
     use std::cmp::Ordering;
 
     // 2a. Move any Shadda characters to the beginning of S, where S is a max
     // length substring of non-starter characters.
-    fn comparator(c1: &char, _c2: &char) -> Ordering {
-        if modified_combining_class(*c1) == ModifiedCombiningClass::CCC33 {
+    fn comparator<T: TextChar>(c1: &T, _c2: &T) -> Ordering {
+        if modified_combining_class(c1.char()) == ModifiedCombiningClass::CCC33 {
             Ordering::Less
         } else {
             Ordering::Equal
@@ -284,20 +481,22 @@ fn reorder_marks_shadda(cs: &mut [char]) {
     cs.sort_by(comparator)
 }
 
-fn reorder_marks_other_combining(cs: &mut [char], mcc: ModifiedCombiningClass) {
+fn reorder_marks_other_combining<T: TextChar>(cs: &mut [T], mcc: ModifiedCombiningClass) {
     debug_assert!(mcc == ModifiedCombiningClass::Below || mcc == ModifiedCombiningClass::Above);
 
     // Get the start index of a possible sequence of characters with canonical
     // combining class equal to `mcc`. (Assumes that `glyphs` is normalised to
     // NFD.)
-    let first = cs.iter().position(|&c| modified_combining_class(c) == mcc);
+    let first = cs
+        .iter()
+        .position(|c| modified_combining_class(c.char()) == mcc);
 
     if let Some(first) = first {
         // 2b/2c. If the sequence of characters _begins_ with any MCM characters,
         // move the sequence of such characters to the beginning of S.
         let count = cs[first..]
             .iter()
-            .take_while(|&&c| is_modifier_combining_mark(c))
+            .take_while(|c| is_modifier_combining_mark(c.char()))
             .count();
         cs[..(first + count)].rotate_right(count);
     }
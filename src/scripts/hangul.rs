@@ -0,0 +1,301 @@
+//! Implementation of font shaping for Hangul script
+//!
+//! Sequences of conjoining jamo are composed into precomposed syllables when the font has a
+//! glyph for the syllable. Sequences that can't be composed, such as Old Hangul, are shaped with
+//! the `ljmo`, `vjmo`, and `tjmo` features applied to the leading, vowel, and trailing jamo.
+//!
+//! <https://github.com/n8willis/opentype-shaping-documents/blob/master/opentype-shaping-hangul.md>
+
+use crate::error::ShapingError;
+use crate::gsub::{self, FeatureMask, GlyphData, RawGlyph};
+use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::unicode::normalization::{
+    from_u32, L_BASE, L_COUNT, N_COUNT, S_BASE, S_COUNT, T_BASE, T_COUNT, V_BASE, V_COUNT,
+};
+use crate::unicode::TextChar;
+
+fn is_l(c: char) -> bool {
+    matches!(c, '\u{1100}'..='\u{115F}' | '\u{A960}'..='\u{A97C}')
+}
+
+fn is_v(c: char) -> bool {
+    matches!(c, '\u{1160}'..='\u{11A7}' | '\u{D7B0}'..='\u{D7C6}')
+}
+
+fn is_t(c: char) -> bool {
+    matches!(c, '\u{11A8}'..='\u{11FF}' | '\u{D7CB}'..='\u{D7FB}')
+}
+
+// The jamo that can be part of a precomposed syllable
+fn is_combining_l(c: char) -> bool {
+    (L_BASE..L_BASE + L_COUNT).contains(&(c as u32))
+}
+
+fn is_combining_v(c: char) -> bool {
+    (V_BASE..V_BASE + V_COUNT).contains(&(c as u32))
+}
+
+fn is_combining_t(c: char) -> bool {
+    (T_BASE + 1..T_BASE + T_COUNT).contains(&(c as u32))
+}
+
+fn is_combined_s(c: char) -> bool {
+    (S_BASE..S_BASE + S_COUNT).contains(&(c as u32))
+}
+
+/// Compose conjoining jamo into precomposed syllables, and decompose precomposed syllables into
+/// jamo, according to the glyphs available in the font.
+///
+/// `has_glyph` returns `true` if the font has a glyph for a character. This follows the Hangul
+/// preprocessing performed by HarfBuzz:
+///
+/// * <L, V> and <L, V, T> sequences are composed if the font has the syllable.
+/// * <LV, T> sequences are composed if the font has the <LVT> syllable.
+/// * Syllables that the font doesn't have, and <LV> syllables followed by a trailing jamo that
+///   can't be composed with them, are decomposed if the font has the jamo.
+pub fn compose_jamo<T: TextChar>(cs: &mut Vec<T>, mut has_glyph: impl FnMut(char) -> bool) {
+    let mut i = 0;
+    while i < cs.len() {
+        let c = cs[i].char();
+        let next = cs.get(i + 1).map(TextChar::char);
+        let next2 = cs.get(i + 2).map(TextChar::char);
+
+        match next {
+            Some(v) if is_l(c) && is_v(v) => {
+                let (len, t_index) = match next2 {
+                    Some(t) if is_combining_t(t) => (3, Some(t as u32 - T_BASE)),
+                    Some(t) if is_t(t) => (3, None),
+                    _ => (2, Some(0)),
+                };
+                match t_index {
+                    Some(t_index) if is_combining_l(c) && is_combining_v(v) => {
+                        let s = from_u32(
+                            S_BASE
+                                + (c as u32 - L_BASE) * N_COUNT
+                                + (v as u32 - V_BASE) * T_COUNT
+                                + t_index,
+                        );
+                        if has_glyph(s) {
+                            cs[i] = cs[i].with_char(s);
+                            cs.drain(i + 1..i + len);
+                            i += 1;
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+                i += len;
+            }
+            _ if is_combined_s(c) => {
+                let s_index = c as u32 - S_BASE;
+                let t_index = s_index % T_COUNT;
+                let followed_by_t = t_index == 0 && next.map_or(false, is_t);
+
+                // <LV, T>
+                if let Some(t) = next.filter(|&t| t_index == 0 && is_combining_t(t)) {
+                    let s = from_u32(c as u32 + (t as u32 - T_BASE));
+                    if has_glyph(s) {
+                        cs[i] = cs[i].with_char(s);
+                        cs.remove(i + 1);
+                        i += 1;
+                        continue;
+                    }
+                }
+
+                if !has_glyph(c) || followed_by_t {
+                    let l = from_u32(L_BASE + s_index / N_COUNT);
+                    let v = from_u32(V_BASE + (s_index % N_COUNT) / T_COUNT);
+                    let t = from_u32(T_BASE + t_index);
+                    if has_glyph(l) && has_glyph(v) && (t_index == 0 || has_glyph(t)) {
+                        let mut jamo = vec![cs[i].with_char(l), cs[i].with_char(v)];
+                        if t_index != 0 {
+                            jamo.push(cs[i].with_char(t));
+                        }
+                        let len = jamo.len();
+                        cs.splice(i..=i, jamo);
+                        i += len;
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct HangulData {
+    feature: FeatureMask,
+}
+
+impl GlyphData for HangulData {
+    fn merge(d1: HangulData, _d2: HangulData) -> HangulData {
+        d1
+    }
+}
+
+type RawGlyphHangul = RawGlyph<HangulData>;
+
+/// Apply the `ljmo`, `vjmo`, and `tjmo` features to the jamo of syllables that weren't composed.
+///
+/// The remaining features are applied by the default shaper.
+pub fn gsub_apply_hangul(
+    gsub_cache: &LayoutCache<GSUB>,
+    gsub_table: &LayoutTable<GSUB>,
+    gdef_table: Option<&GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&FeatureTableSubstitution<'_>>,
+    glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    let mut hangul_glyphs = glyphs.iter().map(to_raw_glyph_hangul).collect::<Vec<_>>();
+    if !assign_jamo_features(&mut hangul_glyphs) {
+        return Ok(());
+    }
+
+    let features = FeatureMask::LJMO | FeatureMask::VJMO | FeatureMask::TJMO;
+    let index = gsub::get_lookups_cache_index(
+        gsub_cache,
+        script_tag,
+        lang_tag,
+        feature_variations,
+        features,
+    )?;
+    let lookups = &gsub_cache.cached_lookups.borrow()[index];
+
+    for &(lookup_index, feature_tag) in lookups {
+        let feature = FeatureMask::from_tag(feature_tag);
+        let length = hangul_glyphs.len();
+        gsub::gsub_apply_lookup(
+            gsub_cache,
+            gsub_table,
+            gdef_table,
+            lookup_index,
+            feature_tag,
+            None,
+            &mut hangul_glyphs,
+            0,
+            length,
+            |g| g.extra_data.feature == feature,
+        )?;
+    }
+
+    *glyphs = hangul_glyphs
+        .into_iter()
+        .map(from_raw_glyph_hangul)
+        .collect();
+
+    Ok(())
+}
+
+/// Find the <L, V> and <L, V, T> jamo sequences, set the feature to apply to each jamo, and merge
+/// the clusters of each sequence. Returns `false` if there are no such sequences.
+fn assign_jamo_features(glyphs: &mut [RawGlyphHangul]) -> bool {
+    let is = |glyphs: &[RawGlyphHangul], i: usize, f: fn(char) -> bool| {
+        glyphs.get(i).and_then(|g| g.char()).map_or(false, f)
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < glyphs.len() {
+        if is(glyphs, i, is_l) && is(glyphs, i + 1, is_v) {
+            let len = if is(glyphs, i + 2, is_t) { 3 } else { 2 };
+            let features = [FeatureMask::LJMO, FeatureMask::VJMO, FeatureMask::TJMO];
+            let cluster = glyphs[i].cluster;
+            for (glyph, feature) in glyphs[i..i + len].iter_mut().zip(features) {
+                glyph.extra_data.feature = feature;
+                glyph.cluster = cluster;
+            }
+            found = true;
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+impl RawGlyphHangul {
+    fn char(&self) -> Option<char> {
+        match self.glyph_origin {
+            gsub::GlyphOrigin::Char(c) => Some(c),
+            gsub::GlyphOrigin::Direct => None,
+        }
+    }
+}
+
+fn to_raw_glyph_hangul(g: &RawGlyph<()>) -> RawGlyphHangul {
+    RawGlyphHangul {
+        unicodes: g.unicodes.clone(),
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: HangulData {
+            feature: FeatureMask::empty(),
+        },
+    }
+}
+
+fn from_raw_glyph_hangul(g: RawGlyphHangul) -> RawGlyph<()> {
+    RawGlyph {
+        unicodes: g.unicodes,
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compose `s` for a font that has glyphs for the characters in `font`
+    fn compose(s: &str, font: &str) -> String {
+        let mut cs = s.chars().collect::<Vec<_>>();
+        compose_jamo(&mut cs, |ch| font.contains(ch));
+        cs.into_iter().collect()
+    }
+
+    #[test]
+    fn test_compose() {
+        let font = "\u{1100}\u{1161}\u{11A8}\u{AC00}\u{AC01}";
+        assert_eq!(compose("\u{1100}\u{1161}", font), "\u{AC00}");
+        assert_eq!(compose("\u{1100}\u{1161}\u{11A8}", font), "\u{AC01}");
+        assert_eq!(compose("\u{AC00}\u{11A8}", font), "\u{AC01}");
+        // Old Hangul jamo have no precomposed syllables
+        assert_eq!(compose("\u{1100}\u{119E}", font), "\u{1100}\u{119E}");
+        assert_eq!(compose("\u{1161}\u{11A8}", font), "\u{1161}\u{11A8}");
+    }
+
+    #[test]
+    fn test_decompose() {
+        let font = "\u{1100}\u{1161}\u{11A8}\u{11C3}\u{AC00}";
+        assert_eq!(
+            compose("\u{1100}\u{1161}\u{11A8}", font),
+            "\u{1100}\u{1161}\u{11A8}"
+        );
+        assert_eq!(compose("\u{AC01}", font), "\u{1100}\u{1161}\u{11A8}");
+        assert_eq!(
+            compose("\u{AC00}\u{11A8}", font),
+            "\u{1100}\u{1161}\u{11A8}"
+        );
+        // U+11C3 can't be part of a precomposed syllable
+        assert_eq!(
+            compose("\u{AC00}\u{11C3}", font),
+            "\u{1100}\u{1161}\u{11C3}"
+        );
+        assert_eq!(compose("\u{AC00}", font), "\u{AC00}");
+        // Syllables are left alone if the font doesn't have the jamo either
+        assert_eq!(compose("\u{B098}", font), "\u{B098}");
+    }
+}
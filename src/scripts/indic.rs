@@ -9,6 +9,7 @@ use crate::layout::{FeatureTableSubstitution, GDEFTable, LangSys, LayoutCache, L
 use crate::scripts::syllable::*;
 use crate::tinyvec::tiny_vec;
 use crate::unicode::mcc::sort_by_modified_combining_class;
+use crate::unicode::TextChar;
 use crate::{tag, DOTTED_CIRCLE};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -692,7 +693,7 @@ fn match_syllable<T: SyllableChar>(cs: &[T]) -> Option<(usize, Syllable)> {
 
 /// Preprocess Indic character sequences. This function should be called
 /// prior to mapping Indic characters to their corresponding glyphs.
-pub(super) fn preprocess_indic(cs: &mut Vec<char>, script_tag: u32) {
+pub(super) fn preprocess_indic<T: TextChar>(cs: &mut Vec<T>, script_tag: u32) {
     let script = script(script_tag);
 
     constrain_vowel(cs);
@@ -721,17 +722,17 @@ enum InsertConstraint {
 /// a constraining character in between these combinations.
 ///
 /// E.g. Bengali Letter A + Bengali Sign Aa looks like Bengali Letter Aa.
-fn constrain_vowel(cs: &mut Vec<char>) {
+fn constrain_vowel<T: TextChar>(cs: &mut Vec<T>) {
     let mut i = 0;
     while i + 1 < cs.len() {
-        i += match vowel_constraint(cs[i], cs[i + 1]) {
+        i += match vowel_constraint(cs[i].char(), cs[i + 1].char()) {
             InsertConstraint::Between => {
-                cs.insert(i + 1, DOTTED_CIRCLE);
+                cs.insert(i + 1, cs[i + 1].with_char(DOTTED_CIRCLE));
                 3
             }
             InsertConstraint::MaybeAfter(c3) => {
-                if i + 2 < cs.len() && cs[i + 2] == c3 {
-                    cs.insert(i + 2, DOTTED_CIRCLE);
+                if i + 2 < cs.len() && cs[i + 2].char() == c3 {
+                    cs.insert(i + 2, cs[i + 2].with_char(DOTTED_CIRCLE));
                     4
                 } else {
                     2
@@ -857,18 +858,20 @@ enum MatraSplit {
 ///
 /// E.g. Bengali "Ka, Sign O" decomposes into "Ka, Sign E, Sign Aa", then
 /// gets reordered to "Sign E, Ka, Sign Aa".
-fn decompose_matra(cs: &mut Vec<char>) {
+fn decompose_matra<T: TextChar>(cs: &mut Vec<T>) {
     let mut i = 0;
     while i < cs.len() {
-        i += match split_matra(cs[i]) {
+        i += match split_matra(cs[i].char()) {
             MatraSplit::None => 1,
             MatraSplit::Two(c1, c2) => {
-                cs[i] = c1;
+                let c2 = cs[i].with_char(c2);
+                cs[i] = cs[i].with_char(c1);
                 cs.insert(i + 1, c2);
                 2
             }
             MatraSplit::Three(c1, c2, c3) => {
-                cs[i] = c1;
+                let (c2, c3) = (cs[i].with_char(c2), cs[i].with_char(c3));
+                cs[i] = cs[i].with_char(c1);
                 cs.insert(i + 1, c2);
                 cs.insert(i + 2, c3);
                 3
@@ -919,11 +922,11 @@ fn split_matra(ch: char) -> MatraSplit {
 /// HarfBuzz does this; we follow.
 ///
 /// https://github.com/n8willis/opentype-shaping-documents/issues/74
-fn recompose_bengali_ya_nukta(cs: &mut Vec<char>) {
+fn recompose_bengali_ya_nukta<T: TextChar>(cs: &mut Vec<T>) {
     let mut i = 0;
     while i + 1 < cs.len() {
-        if cs[i] == '\u{09AF}' && cs[i + 1] == '\u{09BC}' {
-            cs[i] = '\u{09DF}';
+        if cs[i].char() == '\u{09AF}' && cs[i + 1].char() == '\u{09BC}' {
+            cs[i] = cs[i].with_char('\u{09DF}');
             cs.remove(i + 1);
         }
         i += 1;
@@ -936,8 +939,13 @@ fn recompose_bengali_ya_nukta(cs: &mut Vec<char>) {
 ///
 /// https://github.com/n8willis/opentype-shaping-documents/issues/61
 /// https://github.com/harfbuzz/harfbuzz/issues/435
-fn reorder_kannada_ra_halant_zwj(cs: &mut [char]) {
-    if cs.starts_with(&['\u{0CB0}', '\u{0CCD}', '\u{200D}']) {
+fn reorder_kannada_ra_halant_zwj<T: TextChar>(cs: &mut [T]) {
+    let starts_with_ra_halant_zwj = cs
+        .iter()
+        .map(TextChar::char)
+        .take(3)
+        .eq(['\u{0CB0}', '\u{0CCD}', '\u{200D}']);
+    if starts_with_ra_halant_zwj {
         cs.swap(1, 2);
     }
 }
@@ -1098,7 +1106,7 @@ pub fn gsub_apply_indic<'a>(
     gsub_cache: &'a LayoutCache<GSUB>,
     gsub_table: &'a LayoutTable<GSUB>,
     gdef_table: Option<&'a GDEFTable>,
-    indic1_tag: u32,
+    script_tag: u32,
     lang_tag: Option<u32>,
     feature_variations: Option<&'a FeatureTableSubstitution<'a>>,
     glyphs: &mut Vec<RawGlyph<()>>,
@@ -1107,8 +1115,9 @@ pub fn gsub_apply_indic<'a>(
         return Err(IndicError::EmptyBuffer.into());
     }
 
-    // Currently, the script tag that gets passed from Mercury is the Indic1 tag.
-    // Map this to the Indic2 tag, as we want to check if a font supports it
+    // The script tag may be the Indic1 or Indic2 tag. Check if the font supports the Indic2
+    // tag, falling back to the Indic1 tag.
+    let indic1_tag = indic1_tag(script_tag);
     let indic2_tag = indic2_tag(indic1_tag);
 
     // Priority: Indic2 > Indic1 > Default
@@ -1237,8 +1246,11 @@ fn insert_dotted_circle(
         glyph_index: dotted_circle_index,
         liga_component_pos: 0,
         glyph_origin: GlyphOrigin::Char(DOTTED_CIRCLE),
+        // The dotted circle belongs to the cluster of the syllable it completes
+        cluster: glyphs.first().map_or(0, |glyph| glyph.cluster),
         flags: RawGlyphFlags::empty(),
         variation: None,
+        feature_mask: glyphs.first().and_then(|glyph| glyph.feature_mask),
         extra_data: IndicData {
             pos: None,
             mask: FeatureMask::empty(),
@@ -1257,9 +1269,9 @@ fn insert_dotted_circle(
     Ok(())
 }
 
-/// Maps an Indic1 script tag to its corresponding `Script` variant.
-fn script(indic1_tag: u32) -> Script {
-    match indic1_tag {
+/// Maps an Indic1 or Indic2 script tag to its corresponding `Script` variant.
+fn script(script_tag: u32) -> Script {
+    match indic1_tag(script_tag) {
         tag::DEVA => Script::Devanagari,
         tag::BENG => Script::Bengali,
         tag::GURU => Script::Gurmukhi,
@@ -1274,9 +1286,26 @@ fn script(indic1_tag: u32) -> Script {
     }
 }
 
-/// Maps an Indic1 script tag to its corresponding Indic2 script tag.
-pub fn indic2_tag(indic1_tag: u32) -> u32 {
-    match indic1_tag {
+/// Maps an Indic2 script tag to its corresponding Indic1 script tag. Other tags are returned
+/// unchanged.
+pub fn indic1_tag(script_tag: u32) -> u32 {
+    match script_tag {
+        tag::DEV2 => tag::DEVA,
+        tag::BNG2 => tag::BENG,
+        tag::GUR2 => tag::GURU,
+        tag::GJR2 => tag::GUJR,
+        tag::ORY2 => tag::ORYA,
+        tag::TML2 => tag::TAML,
+        tag::TEL2 => tag::TELU,
+        tag::KND2 => tag::KNDA,
+        tag::MLM2 => tag::MLYM,
+        _ => script_tag,
+    }
+}
+
+/// Maps an Indic1 or Indic2 script tag to its corresponding Indic2 script tag.
+pub fn indic2_tag(script_tag: u32) -> u32 {
+    match indic1_tag(script_tag) {
         tag::DEVA => tag::DEV2,
         tag::BENG => tag::BNG2,
         tag::GURU => tag::GUR2,
@@ -2257,8 +2286,10 @@ fn to_raw_glyph_indic(glyph: &RawGlyph<()>) -> RawGlyphIndic {
         glyph_index: glyph.glyph_index,
         liga_component_pos: glyph.liga_component_pos,
         glyph_origin: glyph.glyph_origin,
+        cluster: glyph.cluster,
         flags: glyph.flags,
         variation: glyph.variation,
+        feature_mask: glyph.feature_mask,
         extra_data: IndicData {
             pos: None,
             mask: FeatureMask::empty(),
@@ -2272,8 +2303,10 @@ fn from_raw_glyph_indic(glyph: RawGlyphIndic) -> RawGlyph<()> {
         glyph_index: glyph.glyph_index,
         liga_component_pos: glyph.liga_component_pos,
         glyph_origin: glyph.glyph_origin,
+        cluster: glyph.cluster,
         flags: glyph.flags,
         variation: glyph.variation,
+        feature_mask: glyph.feature_mask,
         extra_data: (),
     }
 }
@@ -6,6 +6,7 @@ use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTabl
 use crate::scripts::syllable::*;
 use crate::tag;
 use crate::unicode::mcc::sort_by_modified_combining_class;
+use crate::unicode::TextChar;
 use crate::DOTTED_CIRCLE;
 
 fn shaping_class(c: char) -> Option<ShapingClass> {
@@ -270,17 +271,17 @@ impl BasicFeature {
     }
 }
 
-pub(super) fn preprocess_khmer(cs: &mut Vec<char>) {
+pub(super) fn preprocess_khmer<T: TextChar>(cs: &mut Vec<T>) {
     decompose_matra(cs);
     sort_by_modified_combining_class(cs);
 }
 
-fn decompose_matra(cs: &mut Vec<char>) {
+fn decompose_matra<T: TextChar>(cs: &mut Vec<T>) {
     let mut i = 0;
     while i < cs.len() {
-        match cs[i] {
+        match cs[i].char() {
             '\u{17BE}' | '\u{17BF}' | '\u{17C0}' | '\u{17C4}' | '\u{17C5}' => {
-                cs.insert(i, '\u{17C1}');
+                cs.insert(i, cs[i].with_char('\u{17C1}'));
                 i += 2;
             }
             _ => i += 1,
@@ -402,8 +403,11 @@ fn insert_dotted_circle(dotted_circle_index: u16, glyphs: &mut Vec<RawGlyphKhmer
         glyph_index: dotted_circle_index,
         liga_component_pos: 0,
         glyph_origin: GlyphOrigin::Char(DOTTED_CIRCLE),
+        // The dotted circle belongs to the cluster of the syllable it completes
+        cluster: glyphs.first().map_or(0, |glyph| glyph.cluster),
         flags: RawGlyphFlags::empty(),
         variation: None,
+        feature_mask: glyphs.first().and_then(|glyph| glyph.feature_mask),
         extra_data: KhmerData {
             mask: FeatureMask::empty(),
         },
@@ -599,8 +603,10 @@ fn to_raw_glyph_khmer(g: &RawGlyph<()>) -> RawGlyphKhmer {
         glyph_index: g.glyph_index,
         liga_component_pos: g.liga_component_pos,
         glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
         flags: g.flags,
         variation: g.variation,
+        feature_mask: g.feature_mask,
         extra_data: KhmerData {
             mask: FeatureMask::empty(),
         },
@@ -613,8 +619,10 @@ fn from_raw_glyph_khmer(g: RawGlyphKhmer) -> RawGlyph<()> {
         glyph_index: g.glyph_index,
         liga_component_pos: g.liga_component_pos,
         glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
         flags: g.flags,
         variation: g.variation,
+        feature_mask: g.feature_mask,
         extra_data: (),
     }
 }
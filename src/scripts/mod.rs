@@ -1,23 +1,35 @@
 pub mod arabic;
+pub mod hangul;
 pub mod indic;
 pub mod khmer;
+pub mod mongolian;
+pub mod myanmar;
 mod syllable;
 pub mod syriac;
 pub mod thai_lao;
+pub mod tibetan;
+pub mod universal;
+mod universal_data;
 
 use crate::gsub::{GlyphOrigin, RawGlyph};
 use crate::scripts::syllable::SyllableChar;
 use crate::tag;
 use crate::unicode::mcc::sort_by_modified_combining_class;
+use crate::unicode::TextChar;
 
 #[derive(std::cmp::PartialEq)]
 pub enum ScriptType {
     Arabic,
     Default,
+    Hangul,
     Indic,
     Khmer,
+    Mongolian,
+    Myanmar,
     Syriac,
     ThaiLao,
+    Tibetan,
+    Universal,
 }
 
 impl From<u32> for ScriptType {
@@ -27,6 +39,7 @@ impl From<u32> for ScriptType {
             tag::LATN => ScriptType::Default,
             tag::CYRL => ScriptType::Default,
             tag::GREK => ScriptType::Default,
+            tag::HANG => ScriptType::Hangul,
             tag::DEVA => ScriptType::Indic,
             tag::BENG => ScriptType::Indic,
             tag::GURU => ScriptType::Indic,
@@ -37,10 +50,80 @@ impl From<u32> for ScriptType {
             tag::KNDA => ScriptType::Indic,
             tag::MLYM => ScriptType::Indic,
             tag::SINH => ScriptType::Indic,
+            tag::DEV2 => ScriptType::Indic,
+            tag::BNG2 => ScriptType::Indic,
+            tag::GUR2 => ScriptType::Indic,
+            tag::GJR2 => ScriptType::Indic,
+            tag::ORY2 => ScriptType::Indic,
+            tag::TML2 => ScriptType::Indic,
+            tag::TEL2 => ScriptType::Indic,
+            tag::KND2 => ScriptType::Indic,
+            tag::MLM2 => ScriptType::Indic,
             tag::KHMR => ScriptType::Khmer,
+            tag::MONG => ScriptType::Mongolian,
+            tag::MYM2 => ScriptType::Myanmar,
+            tag::MYMR => ScriptType::Myanmar,
             tag::SYRC => ScriptType::Syriac,
             tag::THAI => ScriptType::ThaiLao,
             tag::LAO => ScriptType::ThaiLao,
+            tag::TIBT => ScriptType::Tibetan,
+            tag::ADLM => ScriptType::Universal,
+            tag::AHOM => ScriptType::Universal,
+            tag::BALI => ScriptType::Universal,
+            tag::BATK => ScriptType::Universal,
+            tag::BHKS => ScriptType::Universal,
+            tag::BRAH => ScriptType::Universal,
+            tag::BUGI => ScriptType::Universal,
+            tag::BUHD => ScriptType::Universal,
+            tag::CAKM => ScriptType::Universal,
+            tag::CHAM => ScriptType::Universal,
+            tag::CHRS => ScriptType::Universal,
+            tag::DIAK => ScriptType::Universal,
+            tag::DOGR => ScriptType::Universal,
+            tag::GONG => ScriptType::Universal,
+            tag::GONM => ScriptType::Universal,
+            tag::GRAN => ScriptType::Universal,
+            tag::HANO => ScriptType::Universal,
+            tag::JAVA => ScriptType::Universal,
+            tag::KALI => ScriptType::Universal,
+            tag::KAWI => ScriptType::Universal,
+            tag::KHAR => ScriptType::Universal,
+            tag::KHOJ => ScriptType::Universal,
+            tag::KTHI => ScriptType::Universal,
+            tag::LANA => ScriptType::Universal,
+            tag::LEPC => ScriptType::Universal,
+            tag::LIMB => ScriptType::Universal,
+            tag::MAHJ => ScriptType::Universal,
+            tag::MAKA => ScriptType::Universal,
+            tag::MAND => ScriptType::Universal,
+            tag::MANI => ScriptType::Universal,
+            tag::MARC => ScriptType::Universal,
+            tag::MODI => ScriptType::Universal,
+            tag::MTEI => ScriptType::Universal,
+            tag::MULT => ScriptType::Universal,
+            tag::NAND => ScriptType::Universal,
+            tag::NEWA => ScriptType::Universal,
+            tag::NKO => ScriptType::Universal,
+            tag::OUGR => ScriptType::Universal,
+            tag::PHAG => ScriptType::Universal,
+            tag::PHLP => ScriptType::Universal,
+            tag::RJNG => ScriptType::Universal,
+            tag::ROHG => ScriptType::Universal,
+            tag::SAUR => ScriptType::Universal,
+            tag::SHRD => ScriptType::Universal,
+            tag::SIDD => ScriptType::Universal,
+            tag::SIND => ScriptType::Universal,
+            tag::SOGD => ScriptType::Universal,
+            tag::SOYO => ScriptType::Universal,
+            tag::SUND => ScriptType::Universal,
+            tag::SYLO => ScriptType::Universal,
+            tag::TAGB => ScriptType::Universal,
+            tag::TAKR => ScriptType::Universal,
+            tag::TAVT => ScriptType::Universal,
+            tag::TGLG => ScriptType::Universal,
+            tag::TIRH => ScriptType::Universal,
+            tag::YEZI => ScriptType::Universal,
+            tag::ZANB => ScriptType::Universal,
             _ => ScriptType::Default,
         }
     }
@@ -55,13 +138,23 @@ impl SyllableChar for RawGlyph<()> {
     }
 }
 
-pub fn preprocess_text(cs: &mut Vec<char>, script_tag: u32) {
+/// Prepare text for glyph mapping.
+///
+/// Performs script specific reordering and decomposition of characters. `cs` may be plain
+/// `char`s or characters carrying their cluster, such as
+/// [ClusterChar](crate::unicode::ClusterChar).
+pub fn preprocess_text<T: TextChar>(cs: &mut Vec<T>, script_tag: u32) {
     match ScriptType::from(script_tag) {
         ScriptType::Arabic => arabic::reorder_marks(cs),
         ScriptType::Default => sort_by_modified_combining_class(cs),
+        ScriptType::Hangul => sort_by_modified_combining_class(cs),
         ScriptType::Indic => indic::preprocess_indic(cs, script_tag),
         ScriptType::Khmer => khmer::preprocess_khmer(cs),
+        ScriptType::Mongolian => sort_by_modified_combining_class(cs),
+        ScriptType::Myanmar => sort_by_modified_combining_class(cs),
         ScriptType::Syriac => sort_by_modified_combining_class(cs),
         ScriptType::ThaiLao => thai_lao::reorder_marks(cs),
+        ScriptType::Tibetan => tibetan::preprocess_tibetan(cs),
+        ScriptType::Universal => sort_by_modified_combining_class(cs),
     }
 }
@@ -0,0 +1,281 @@
+//! Implementation of font shaping for Mongolian script
+//!
+//! Mongolian is a joining script so code herein follows the general specification for Arabic at:
+//! <https://github.com/n8willis/opentype-shaping-documents/blob/master/opentype-shaping-arabic-general.md>
+//!
+//! In addition the Mongolian free variation selectors (FVS1–FVS4) and the Mongolian vowel
+//! separator (MVS) are handled as described in chapter 13.5 of The Unicode Standard.
+
+use unicode_joining_type::{get_joining_type, JoiningType};
+
+use crate::error::{ParseError, ShapingError};
+use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph};
+use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::tag;
+
+/// U+180E MONGOLIAN VOWEL SEPARATOR
+const MVS: char = '\u{180E}';
+
+#[derive(Clone)]
+struct MongolianData {
+    joining_type: JoiningType,
+    feature_tag: u32,
+}
+
+impl GlyphData for MongolianData {
+    fn merge(data1: MongolianData, _data2: MongolianData) -> MongolianData {
+        data1
+    }
+}
+
+// Mongolian glyphs are represented as `RawGlyph` structs with `MongolianData` for its
+// `extra_data`.
+type MongolianGlyph = RawGlyph<MongolianData>;
+
+impl MongolianGlyph {
+    fn is_transparent(&self) -> bool {
+        self.extra_data.joining_type == JoiningType::Transparent || self.multi_subst_dup()
+    }
+
+    fn is_left_joining(&self) -> bool {
+        self.extra_data.joining_type == JoiningType::LeftJoining
+            || self.extra_data.joining_type == JoiningType::DualJoining
+            || self.extra_data.joining_type == JoiningType::JoinCausing
+    }
+
+    fn is_right_joining(&self) -> bool {
+        self.extra_data.joining_type == JoiningType::RightJoining
+            || self.extra_data.joining_type == JoiningType::DualJoining
+            || self.extra_data.joining_type == JoiningType::JoinCausing
+    }
+
+    fn is_free_variation_selector(&self) -> bool {
+        match self.glyph_origin {
+            GlyphOrigin::Char(ch) => is_free_variation_selector(ch),
+            GlyphOrigin::Direct => false,
+        }
+    }
+
+    fn is_vowel_separator(&self) -> bool {
+        self.glyph_origin == GlyphOrigin::Char(MVS)
+    }
+
+    fn feature_tag(&self) -> u32 {
+        self.extra_data.feature_tag
+    }
+
+    fn set_feature_tag(&mut self, feature_tag: u32) {
+        self.extra_data.feature_tag = feature_tag
+    }
+}
+
+impl From<&RawGlyph<()>> for MongolianGlyph {
+    fn from(raw_glyph: &RawGlyph<()>) -> MongolianGlyph {
+        // Since there's no `Char` to work out the `MongolianGlyph`s joining type when the
+        // glyph's `glyph_origin` is `GlyphOrigin::Direct`, we fallback to
+        // `JoiningType::NonJoining` as the safest approach
+        let joining_type = match raw_glyph.glyph_origin {
+            GlyphOrigin::Char(c) => get_joining_type(c),
+            GlyphOrigin::Direct => JoiningType::NonJoining,
+        };
+
+        MongolianGlyph {
+            unicodes: raw_glyph.unicodes.clone(),
+            glyph_index: raw_glyph.glyph_index,
+            liga_component_pos: raw_glyph.liga_component_pos,
+            glyph_origin: raw_glyph.glyph_origin,
+            cluster: raw_glyph.cluster,
+            flags: raw_glyph.flags,
+            variation: raw_glyph.variation,
+            feature_mask: raw_glyph.feature_mask,
+            extra_data: MongolianData {
+                joining_type,
+                feature_tag: tag::ISOL,
+            },
+        }
+    }
+}
+
+impl From<&MongolianGlyph> for RawGlyph<()> {
+    fn from(mongolian_glyph: &MongolianGlyph) -> RawGlyph<()> {
+        RawGlyph {
+            unicodes: mongolian_glyph.unicodes.clone(),
+            glyph_index: mongolian_glyph.glyph_index,
+            liga_component_pos: mongolian_glyph.liga_component_pos,
+            glyph_origin: mongolian_glyph.glyph_origin,
+            cluster: mongolian_glyph.cluster,
+            flags: mongolian_glyph.flags,
+            variation: mongolian_glyph.variation,
+            feature_mask: mongolian_glyph.feature_mask,
+            extra_data: (),
+        }
+    }
+}
+
+pub fn gsub_apply_mongolian<'a>(
+    gsub_cache: &'a LayoutCache<GSUB>,
+    gsub_table: &'a LayoutTable<GSUB>,
+    gdef_table: Option<&'a GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'a FeatureTableSubstitution<'a>>,
+    raw_glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    match gsub_table.find_script(script_tag)? {
+        Some(s) => {
+            if s.find_langsys_or_default(lang_tag)?.is_none() {
+                return Ok(());
+            }
+        }
+        None => return Ok(()),
+    }
+
+    let shaping_data = MongolianShapingData {
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        feature_variations,
+    };
+    let mongolian_glyphs = &mut raw_glyphs.iter().map(MongolianGlyph::from).collect();
+
+    // 1. Compound character composition and decomposition
+
+    shaping_data.apply_lookups(FeatureMask::CCMP, mongolian_glyphs, |_, _| true)?;
+
+    // 2. Computing letter joining states
+
+    compute_joining_states(mongolian_glyphs);
+
+    // 3. Applying the language-form substitution features from GSUB
+
+    const LANGUAGE_FEATURES: &[(FeatureMask, bool)] = &[
+        (FeatureMask::LOCL, true),
+        (FeatureMask::ISOL, false),
+        (FeatureMask::FINA, false),
+        (FeatureMask::MEDI, false),
+        (FeatureMask::INIT, false),
+        (FeatureMask::RLIG, true),
+        (FeatureMask::RCLT, true),
+        (FeatureMask::CALT, true),
+    ];
+
+    for &(feature_mask, is_global) in LANGUAGE_FEATURES {
+        shaping_data.apply_lookups(feature_mask, mongolian_glyphs, |g, feature_tag| {
+            is_global || g.feature_tag() == feature_tag
+        })?;
+    }
+
+    // 4. Applying the typographic-form substitution features from GSUB
+
+    shaping_data.apply_lookups(FeatureMask::LIGA, mongolian_glyphs, |_, _| true)?;
+
+    // 5. Free variation selectors that were not consumed by a lookup are format characters
+    // with no appearance of their own, so they are removed like joiners are
+
+    let mut i = 0;
+    while i < mongolian_glyphs.len() {
+        if mongolian_glyphs[i].is_free_variation_selector() {
+            gsub::delete_glyph(mongolian_glyphs, i);
+        } else {
+            i += 1;
+        }
+    }
+
+    *raw_glyphs = mongolian_glyphs.iter().map(RawGlyph::from).collect();
+
+    Ok(())
+}
+
+fn compute_joining_states(mongolian_glyphs: &mut [MongolianGlyph]) {
+    let mut previous_i = mongolian_glyphs
+        .iter()
+        .position(|g| !g.is_transparent())
+        .unwrap_or(0);
+
+    for i in (previous_i + 1)..mongolian_glyphs.len() {
+        if mongolian_glyphs[i].is_transparent() {
+            continue;
+        }
+
+        if mongolian_glyphs[previous_i].is_left_joining() && mongolian_glyphs[i].is_right_joining()
+        {
+            mongolian_glyphs[i].set_feature_tag(tag::FINA);
+
+            match mongolian_glyphs[previous_i].feature_tag() {
+                tag::ISOL => mongolian_glyphs[previous_i].set_feature_tag(tag::INIT),
+                tag::FINA => mongolian_glyphs[previous_i].set_feature_tag(tag::MEDI),
+                _ => {}
+            }
+        } else if mongolian_glyphs[previous_i].is_vowel_separator()
+            && mongolian_glyphs[i].is_right_joining()
+        {
+            // The MVS does not join, but the letter separated from the stem by it takes its
+            // final form. The letter before the MVS already ended its own word.
+            mongolian_glyphs[i].set_feature_tag(tag::FINA);
+        }
+
+        previous_i = i;
+    }
+
+    // Free variation selectors take the form of the letter they follow so that the lookups of
+    // that form can match sequences containing them
+    let mut feature_tag = tag::ISOL;
+    for glyph in mongolian_glyphs.iter_mut() {
+        if glyph.is_free_variation_selector() {
+            glyph.set_feature_tag(feature_tag);
+        } else {
+            feature_tag = glyph.feature_tag();
+        }
+    }
+}
+
+struct MongolianShapingData<'tables> {
+    gsub_cache: &'tables LayoutCache<GSUB>,
+    gsub_table: &'tables LayoutTable<GSUB>,
+    gdef_table: Option<&'tables GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'tables FeatureTableSubstitution<'tables>>,
+}
+
+impl MongolianShapingData<'_> {
+    fn apply_lookups(
+        &self,
+        feature_mask: FeatureMask,
+        mongolian_glyphs: &mut Vec<MongolianGlyph>,
+        pred: impl Fn(&MongolianGlyph, u32) -> bool + Copy,
+    ) -> Result<(), ParseError> {
+        let index = gsub::get_lookups_cache_index(
+            self.gsub_cache,
+            self.script_tag,
+            self.lang_tag,
+            self.feature_variations,
+            feature_mask,
+        )?;
+        let lookups = &self.gsub_cache.cached_lookups.borrow()[index];
+
+        for &(lookup_index, feature_tag) in lookups {
+            gsub::gsub_apply_lookup(
+                self.gsub_cache,
+                self.gsub_table,
+                self.gdef_table,
+                lookup_index,
+                feature_tag,
+                None,
+                mongolian_glyphs,
+                0,
+                mongolian_glyphs.len(),
+                |g| pred(g, feature_tag),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// U+180B–U+180D and U+180F MONGOLIAN FREE VARIATION SELECTOR ONE to FOUR
+fn is_free_variation_selector(ch: char) -> bool {
+    matches!(ch, '\u{180B}'..='\u{180D}' | '\u{180F}')
+}
@@ -0,0 +1,756 @@
+//! Implementation of font shaping for Myanmar script
+//!
+//! Myanmar syllables are reordered before the basic shaping features are applied: medial ra and
+//! pre-base vowels move before the base consonant, and kinzi moves after it. Both the `mym2` and
+//! the older `mymr` script tags are supported; fonts are shaped the same way with either.
+//!
+//! <https://learn.microsoft.com/en-us/typography/script-development/myanmar>
+
+use tinyvec::tiny_vec;
+
+use crate::error::{ParseError, ShapingError};
+use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph, RawGlyphFlags};
+use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::scripts::syllable::*;
+use crate::tag;
+use crate::DOTTED_CIRCLE;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ShapingClass {
+    Consonant,
+    Ra,
+    VowelIndependent,
+    Number,
+    Placeholder,
+    Asat,
+    Virama,
+    DotBelow,
+    Anusvara,
+    MedialYa,
+    MedialRa,
+    MedialWa,
+    MedialHa,
+    MedialLa,
+    VowelPre,
+    VowelAbove,
+    VowelBelow,
+    VowelPost,
+    PwoTone,
+    SyllableModifier,
+    Punctuation,
+    VariationSelector,
+    Joiner,
+    NonJoiner,
+}
+
+fn shaping_class(c: char) -> Option<ShapingClass> {
+    myanmar_character(c)
+}
+
+fn is(class: ShapingClass) -> impl Fn(char) -> bool {
+    move |c| shaping_class(c) == Some(class)
+}
+
+fn consonant(c: char) -> bool {
+    matches!(
+        shaping_class(c),
+        Some(ShapingClass::Consonant | ShapingClass::Ra)
+    )
+}
+
+fn syllable_base(c: char) -> bool {
+    matches!(
+        shaping_class(c),
+        Some(
+            ShapingClass::Consonant
+                | ShapingClass::Ra
+                | ShapingClass::VowelIndependent
+                | ShapingClass::Number
+                | ShapingClass::Placeholder
+        )
+    )
+}
+
+fn joiner(c: char) -> bool {
+    matches!(
+        shaping_class(c),
+        Some(ShapingClass::Joiner | ShapingClass::NonJoiner)
+    )
+}
+
+fn match_kinzi<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(is(ShapingClass::Ra)),
+        match_seq(
+            match_one(is(ShapingClass::Asat)),
+            match_one(is(ShapingClass::Virama)),
+        ),
+    )(cs)
+}
+
+fn match_variation_selector<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_optional(match_one(is(ShapingClass::VariationSelector)))(cs)
+}
+
+fn match_dot_below<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_optional(match_seq(
+        match_one(is(ShapingClass::DotBelow)),
+        match_optional(match_one(is(ShapingClass::Asat))),
+    ))(cs)
+}
+
+// MW MH? ML? | MH ML? | ML
+fn match_medial_wa_ha_la<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    let ml = || match_optional(match_one(is(ShapingClass::MedialLa)));
+    match_either(
+        match_seq(
+            match_one(is(ShapingClass::MedialWa)),
+            match_seq(match_optional(match_one(is(ShapingClass::MedialHa))), ml()),
+        ),
+        match_either(
+            match_seq(match_one(is(ShapingClass::MedialHa)), ml()),
+            match_one(is(ShapingClass::MedialLa)),
+        ),
+    )(cs)
+}
+
+// MY? As? MR? ((MW MH? ML? | MH ML? | ML) As?)?
+fn match_medial_group<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_optional(match_one(is(ShapingClass::MedialYa))),
+        match_seq(
+            match_optional(match_one(is(ShapingClass::Asat))),
+            match_seq(
+                match_optional(match_one(is(ShapingClass::MedialRa))),
+                match_optional(match_seq(
+                    match_medial_wa_ha_la,
+                    match_optional(match_one(is(ShapingClass::Asat))),
+                )),
+            ),
+        ),
+    )(cs)
+}
+
+// (VPre VS?)* VAbv* VBlw* A* (DB As?)?
+fn match_main_vowel_group<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_repeat_upto(
+        MAX_REPEAT,
+        match_seq(
+            match_one(is(ShapingClass::VowelPre)),
+            match_variation_selector,
+        ),
+        match_any(
+            match_one(is(ShapingClass::VowelAbove)),
+            match_any(
+                match_one(is(ShapingClass::VowelBelow)),
+                match_any(match_one(is(ShapingClass::Anusvara)), match_dot_below),
+            ),
+        ),
+    )(cs)
+}
+
+// VPst MH? ML? As* VAbv* A* (DB As?)?
+fn match_post_vowel_group<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(is(ShapingClass::VowelPost)),
+        match_seq(
+            match_optional(match_one(is(ShapingClass::MedialHa))),
+            match_seq(
+                match_optional(match_one(is(ShapingClass::MedialLa))),
+                match_any(
+                    match_one(is(ShapingClass::Asat)),
+                    match_any(
+                        match_one(is(ShapingClass::VowelAbove)),
+                        match_any(match_one(is(ShapingClass::Anusvara)), match_dot_below),
+                    ),
+                ),
+            ),
+        ),
+    )(cs)
+}
+
+// PT A* DB? As?
+fn match_pwo_tone_group<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(is(ShapingClass::PwoTone)),
+        match_any(
+            match_one(is(ShapingClass::Anusvara)),
+            match_seq(
+                match_optional(match_one(is(ShapingClass::DotBelow))),
+                match_optional(match_one(is(ShapingClass::Asat))),
+            ),
+        ),
+    )(cs)
+}
+
+// As* medial_group main_vowel_group post_vowel_group* pwo_tone_group* SM* j?
+fn match_complex_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_any(
+        match_one(is(ShapingClass::Asat)),
+        match_seq(
+            match_medial_group,
+            match_seq(
+                match_main_vowel_group,
+                match_repeat_upto(
+                    MAX_REPEAT,
+                    match_post_vowel_group,
+                    match_repeat_upto(
+                        MAX_REPEAT,
+                        match_pwo_tone_group,
+                        match_any(
+                            match_one(is(ShapingClass::SyllableModifier)),
+                            match_optional(match_one(joiner)),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    )(cs)
+}
+
+// (H (c|IV) VS?)* (H | complex_tail)
+fn match_syllable_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_repeat_upto(
+        MAX_REPEAT,
+        match_seq(
+            match_one(is(ShapingClass::Virama)),
+            match_seq(
+                match_either(
+                    match_one(consonant),
+                    match_one(is(ShapingClass::VowelIndependent)),
+                ),
+                match_variation_selector,
+            ),
+        ),
+        match_either(match_one(is(ShapingClass::Virama)), match_complex_tail),
+    )(cs)
+}
+
+// k? (c|IV|D|GB) VS? syllable_tail
+fn match_consonant_syllable<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_optional_seq(
+        match_kinzi,
+        match_seq(
+            match_one(syllable_base),
+            match_seq(match_variation_selector, match_syllable_tail),
+        ),
+    )(cs)
+}
+
+// P SM
+fn match_punctuation_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(is(ShapingClass::Punctuation)),
+        match_one(is(ShapingClass::SyllableModifier)),
+    )(cs)
+}
+
+// k? VS? syllable_tail
+fn match_broken_syllable<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_nonempty(match_optional_seq(
+        match_kinzi,
+        match_seq(match_variation_selector, match_syllable_tail),
+    ))(cs)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Syllable {
+    Consonant,
+    Punctuation,
+    Broken,
+}
+
+fn match_syllable<T: SyllableChar>(cs: &[T]) -> Option<(usize, Syllable)> {
+    let candidates = [
+        (match_consonant_syllable(cs), Syllable::Consonant),
+        (match_punctuation_cluster(cs), Syllable::Punctuation),
+        (match_broken_syllable(cs), Syllable::Broken),
+    ];
+
+    // Prefer the longest match, then the earliest candidate
+    let mut best: Option<(usize, Syllable)> = None;
+    for (len, syllable_type) in candidates {
+        match (len, best) {
+            (Some(len), Some((best_len, _))) if len <= best_len => {}
+            (Some(len), _) if len > 0 => best = Some((len, syllable_type)),
+            _ => {}
+        }
+    }
+    best
+}
+
+/// The position of a glyph within a syllable after reordering. Glyphs are sorted by position,
+/// keeping the original order of glyphs that have the same position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Position {
+    PreMatra,
+    PreConsonant,
+    BaseConsonant,
+    AfterMain,
+    BeforeSub,
+    BelowConsonant,
+    AfterSub,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct MyanmarData {
+    class: Option<ShapingClass>,
+    position: Position,
+}
+
+impl GlyphData for MyanmarData {
+    fn merge(d1: MyanmarData, _d2: MyanmarData) -> MyanmarData {
+        d1
+    }
+}
+
+type RawGlyphMyanmar = RawGlyph<MyanmarData>;
+
+impl RawGlyphMyanmar {
+    fn class(&self) -> Option<ShapingClass> {
+        self.extra_data.class
+    }
+
+    fn is_base_candidate(&self) -> bool {
+        matches!(
+            self.class(),
+            Some(
+                ShapingClass::Consonant
+                    | ShapingClass::Ra
+                    | ShapingClass::VowelIndependent
+                    | ShapingClass::Placeholder
+            )
+        )
+    }
+
+    fn set_position(&mut self, position: Position) {
+        self.extra_data.position = position;
+    }
+}
+
+/// Returns the script tag that the GSUB and GPOS tables of a font use for Myanmar text.
+///
+/// The `mym2` tag is preferred when the font has it, otherwise `mymr` is used.
+pub fn myanmar_tag<T>(layout_table: &LayoutTable<T>) -> Result<u32, ParseError> {
+    match layout_table.find_script(tag::MYM2)? {
+        Some(_) => Ok(tag::MYM2),
+        None => Ok(tag::MYMR),
+    }
+}
+
+pub fn gsub_apply_myanmar<'a>(
+    dotted_circle_index: u16,
+    gsub_cache: &'a LayoutCache<GSUB>,
+    gsub_table: &'a LayoutTable<GSUB>,
+    gdef_table: Option<&'a GDEFTable>,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'a FeatureTableSubstitution<'a>>,
+    glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    let shaping_data = MyanmarShapingData {
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag: myanmar_tag(gsub_table)?,
+        lang_tag,
+        feature_variations,
+    };
+    let mut syllables = to_myanmar_syllables(dotted_circle_index, glyphs);
+
+    for (syllable, syllable_type) in syllables.iter_mut() {
+        shape_syllable(&shaping_data, syllable, *syllable_type)?;
+    }
+
+    *glyphs = syllables
+        .into_iter()
+        .flat_map(|(syllable, _)| syllable)
+        .map(from_raw_glyph_myanmar)
+        .collect();
+
+    Ok(())
+}
+
+fn to_myanmar_syllables(
+    dotted_circle_index: u16,
+    mut glyphs: &[RawGlyph<()>],
+) -> Vec<(Vec<RawGlyphMyanmar>, Option<Syllable>)> {
+    let mut syllables: Vec<(Vec<RawGlyphMyanmar>, Option<Syllable>)> = Vec::new();
+
+    while !glyphs.is_empty() {
+        match match_syllable(glyphs) {
+            Some((len, syllable_type)) => {
+                let mut syllable = Vec::with_capacity(len + 1);
+                syllable.extend(glyphs[..len].iter().map(to_raw_glyph_myanmar));
+                // Add a dotted circle to broken syllables so they can be shaped like consonant
+                // syllables
+                let syllable_type = match syllable_type {
+                    Syllable::Broken
+                        if insert_dotted_circle(dotted_circle_index, &mut syllable) =>
+                    {
+                        Syllable::Consonant
+                    }
+                    _ => syllable_type,
+                };
+                syllables.push((syllable, Some(syllable_type)));
+                glyphs = &glyphs[len..];
+            }
+            None => {
+                let glyph = to_raw_glyph_myanmar(&glyphs[0]);
+                match syllables.last_mut() {
+                    // Group consecutive glyphs that are not part of any syllable
+                    Some((other, None)) => other.push(glyph),
+                    _ => syllables.push((vec![glyph], None)),
+                }
+                glyphs = &glyphs[1..];
+            }
+        }
+    }
+
+    syllables
+}
+
+/// Inserts a dotted circle to act as the base of a broken syllable. The dotted circle goes after
+/// any kinzi. Returns `false` if the font has no dotted circle.
+fn insert_dotted_circle(dotted_circle_index: u16, glyphs: &mut Vec<RawGlyphMyanmar>) -> bool {
+    if dotted_circle_index == 0 {
+        return false;
+    }
+    let dotted_circle = RawGlyphMyanmar {
+        unicodes: tiny_vec![[char; 1] => DOTTED_CIRCLE],
+        glyph_index: dotted_circle_index,
+        liga_component_pos: 0,
+        glyph_origin: GlyphOrigin::Char(DOTTED_CIRCLE),
+        // The dotted circle belongs to the cluster of the syllable it completes
+        cluster: glyphs.first().map_or(0, |glyph| glyph.cluster),
+        flags: RawGlyphFlags::empty(),
+        variation: None,
+        feature_mask: glyphs.first().and_then(|glyph| glyph.feature_mask),
+        extra_data: MyanmarData {
+            class: shaping_class(DOTTED_CIRCLE),
+            position: Position::BaseConsonant,
+        },
+    };
+    let index = if starts_with_kinzi(glyphs) { 3 } else { 0 };
+    glyphs.insert(index, dotted_circle);
+    true
+}
+
+fn shape_syllable(
+    shaping_data: &MyanmarShapingData<'_>,
+    syllable: &mut Vec<RawGlyphMyanmar>,
+    syllable_type: Option<Syllable>,
+) -> Result<(), ParseError> {
+    let apply = |features: FeatureMask, glyphs: &mut Vec<RawGlyphMyanmar>| {
+        shaping_data.apply_features(features, glyphs)
+    };
+
+    apply(FeatureMask::LOCL | FeatureMask::CCMP, syllable)?;
+
+    if syllable_type == Some(Syllable::Consonant) {
+        reorder_syllable(syllable);
+    }
+
+    // The basic shaping features are applied one at a time
+    for features in [
+        FeatureMask::RPHF,
+        FeatureMask::PREF,
+        FeatureMask::BLWF,
+        FeatureMask::PSTF,
+    ] {
+        apply(features, syllable)?;
+    }
+
+    let features = FeatureMask::ABVS
+        | FeatureMask::BLWS
+        | FeatureMask::CALT
+        | FeatureMask::CLIG
+        | FeatureMask::LIGA
+        | FeatureMask::PRES
+        | FeatureMask::PSTS
+        | FeatureMask::RLIG;
+    apply(features, syllable)?;
+
+    Ok(())
+}
+
+/// Reorders a consonant syllable in the same way as HarfBuzz.
+///
+/// A kinzi (Ra, Asat, Virama) at the start of the syllable moves after the base consonant,
+/// while medial ra and pre-base vowels move before it. Below-base vowels and anusvara are kept
+/// in their visual order.
+fn reorder_syllable(glyphs: &mut [RawGlyphMyanmar]) {
+    let limit = if starts_with_kinzi(glyphs) { 3 } else { 0 };
+    let base = glyphs[limit..]
+        .iter()
+        .position(|g| g.is_base_candidate())
+        .map_or(limit, |i| i + limit);
+
+    let mut i = 0;
+    while i < limit {
+        glyphs[i].set_position(Position::AfterMain);
+        i += 1;
+    }
+    while i < base {
+        glyphs[i].set_position(Position::PreConsonant);
+        i += 1;
+    }
+    if i < glyphs.len() {
+        glyphs[i].set_position(Position::BaseConsonant);
+        i += 1;
+    }
+
+    let mut position = Position::AfterMain;
+    for i in i..glyphs.len() {
+        let new_position = match glyphs[i].class() {
+            Some(ShapingClass::MedialRa) => Position::PreConsonant,
+            Some(ShapingClass::VowelPre) => Position::PreMatra,
+            Some(ShapingClass::VariationSelector) => glyphs[i - 1].extra_data.position,
+            Some(ShapingClass::VowelBelow) if position == Position::AfterMain => {
+                position = Position::BelowConsonant;
+                position
+            }
+            Some(ShapingClass::Anusvara) if position == Position::BelowConsonant => {
+                Position::BeforeSub
+            }
+            Some(ShapingClass::VowelBelow) if position == Position::BelowConsonant => position,
+            _ if position == Position::BelowConsonant => {
+                position = Position::AfterSub;
+                position
+            }
+            _ => position,
+        };
+        glyphs[i].set_position(new_position);
+    }
+
+    glyphs.sort_by_key(|g| g.extra_data.position);
+}
+
+fn starts_with_kinzi(glyphs: &[RawGlyphMyanmar]) -> bool {
+    glyphs.len() >= 3
+        && glyphs[0].class() == Some(ShapingClass::Ra)
+        && glyphs[1].class() == Some(ShapingClass::Asat)
+        && glyphs[2].class() == Some(ShapingClass::Virama)
+}
+
+struct MyanmarShapingData<'tables> {
+    gsub_cache: &'tables LayoutCache<GSUB>,
+    gsub_table: &'tables LayoutTable<GSUB>,
+    gdef_table: Option<&'tables GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'tables FeatureTableSubstitution<'tables>>,
+}
+
+impl MyanmarShapingData<'_> {
+    fn apply_features(
+        &self,
+        features: FeatureMask,
+        glyphs: &mut Vec<RawGlyphMyanmar>,
+    ) -> Result<(), ParseError> {
+        let index = gsub::get_lookups_cache_index(
+            self.gsub_cache,
+            self.script_tag,
+            self.lang_tag,
+            self.feature_variations,
+            features,
+        )?;
+        let lookups = &self.gsub_cache.cached_lookups.borrow()[index];
+
+        // Features within a stage are applied in lookup order
+        for &(lookup_index, feature_tag) in lookups {
+            gsub::gsub_apply_lookup(
+                self.gsub_cache,
+                self.gsub_table,
+                self.gdef_table,
+                lookup_index,
+                feature_tag,
+                None,
+                glyphs,
+                0,
+                glyphs.len(),
+                |_| true,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn to_raw_glyph_myanmar(g: &RawGlyph<()>) -> RawGlyphMyanmar {
+    RawGlyphMyanmar {
+        unicodes: g.unicodes.clone(),
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: MyanmarData {
+            class: shaping_class(g.char()),
+            position: Position::BaseConsonant,
+        },
+    }
+}
+
+fn from_raw_glyph_myanmar(g: RawGlyphMyanmar) -> RawGlyph<()> {
+    RawGlyph {
+        unicodes: g.unicodes,
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: (),
+    }
+}
+
+fn myanmar_character(c: char) -> Option<ShapingClass> {
+    use ShapingClass::*;
+
+    match c as u32 {
+        // Myanmar
+        0x1004 => Some(Ra), // Letter Nga
+        0x1000..=0x101A => Some(Consonant),
+        0x101B => Some(Ra), // Letter Ra
+        0x101C..=0x1020 => Some(Consonant),
+        0x1021..=0x102A => Some(VowelIndependent),
+        0x102B..=0x102C => Some(VowelPost),
+        0x102D..=0x102E => Some(VowelAbove),
+        0x102F..=0x1030 => Some(VowelBelow),
+        0x1031 => Some(VowelPre),
+        0x1032 => Some(Anusvara), // Sign Ai
+        0x1033..=0x1035 => Some(VowelAbove),
+        0x1036 => Some(Anusvara),
+        0x1037 => Some(DotBelow),
+        0x1038 => Some(SyllableModifier), // Sign Visarga
+        0x1039 => Some(Virama),
+        0x103A => Some(Asat),
+        0x103B => Some(MedialYa),
+        0x103C => Some(MedialRa),
+        0x103D => Some(MedialWa),
+        0x103E => Some(MedialHa),
+        0x103F => Some(Consonant), // Letter Great Sa
+        0x1040..=0x1049 => Some(Number),
+        0x104A..=0x104B => Some(Punctuation), // Little Section, Section
+        0x104E => Some(Consonant),            // Symbol Aforementioned
+        0x1050..=0x1051 => Some(Consonant),
+        0x1052..=0x1055 => Some(VowelIndependent),
+        0x1056..=0x1057 => Some(VowelPost),
+        0x1058..=0x1059 => Some(VowelBelow),
+        0x105A => Some(Ra), // Letter Mon Nga
+        0x105B..=0x105D => Some(Consonant),
+        0x105E..=0x105F => Some(MedialYa), // Consonant Sign Mon Medial Na, Mon Medial Ma
+        0x1060 => Some(MedialLa),
+        0x1061 => Some(Consonant),
+        0x1062 => Some(VowelPost),
+        0x1063..=0x1064 => Some(PwoTone),
+        0x1065..=0x1066 => Some(Consonant),
+        0x1067..=0x1068 => Some(VowelPost),
+        0x1069..=0x106D => Some(PwoTone),
+        0x106E..=0x1070 => Some(Consonant),
+        0x1071..=0x1074 => Some(VowelAbove),
+        0x1075..=0x1081 => Some(Consonant),
+        0x1082 => Some(MedialWa), // Consonant Sign Shan Medial Wa
+        0x1083 => Some(VowelPost),
+        0x1084 => Some(VowelPre),
+        0x1085..=0x1086 => Some(VowelAbove),
+        0x1087..=0x108D => Some(SyllableModifier), // Shan and Red Karen tones
+        0x108E => Some(Consonant),
+        0x108F => Some(SyllableModifier),
+        0x1090..=0x1099 => Some(Number),
+        0x109A..=0x109C => Some(SyllableModifier),
+        0x109D => Some(VowelAbove),
+
+        // Myanmar Extended-B
+        0xA9E0..=0xA9E4 => Some(Consonant),
+        0xA9E5 => Some(VowelAbove), // Sign Shan Saw
+        0xA9E7..=0xA9EF => Some(Consonant),
+        0xA9F0..=0xA9F9 => Some(Number),
+        0xA9FA..=0xA9FE => Some(Consonant),
+
+        // Myanmar Extended-A
+        0xAA60..=0xAA6F => Some(Consonant),
+        0xAA71..=0xAA76 => Some(Consonant),
+        0xAA7A => Some(Consonant),
+        0xAA7B => Some(PwoTone),                   // Sign Pao Karen Tone
+        0xAA7C..=0xAA7D => Some(SyllableModifier), // Tai Laing tones
+        0xAA7E..=0xAA7F => Some(Consonant),
+
+        // Miscellaneous character table.
+        0x002D => Some(Placeholder),          // Hyphen-minus
+        0x00A0 => Some(Placeholder),          // No-break space
+        0x00D7 => Some(Placeholder),          // Multiplication sign
+        0x200C => Some(NonJoiner),            // Zero-width non-joiner
+        0x200D => Some(Joiner),               // Zero-width joiner
+        0x2012..=0x2015 => Some(Placeholder), // Dashes
+        0x2022 => Some(Placeholder),          // Bullet
+        0x25CC => Some(Placeholder),          // Dotted circle
+        0x25FB..=0x25FE => Some(Placeholder), // Squares
+        0xFE00..=0xFE0F => Some(VariationSelector),
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syllables(s: &str) -> Vec<(usize, Option<Syllable>)> {
+        let mut cs = &s.chars().collect::<Vec<_>>()[..];
+        let mut syllables = Vec::new();
+        while !cs.is_empty() {
+            let (len, syllable_type) = match match_syllable(cs) {
+                Some((len, syllable_type)) => (len, Some(syllable_type)),
+                None => (1, None),
+            };
+            syllables.push((len, syllable_type));
+            cs = &cs[len..];
+        }
+        syllables
+    }
+
+    #[test]
+    fn test_consonant_syllables() {
+        // KA, MEDIAL RA, VOWEL SIGN E, VOWEL SIGN AA, ASAT
+        assert_eq!(
+            syllables("\u{1000}\u{103C}\u{1031}\u{102C}\u{103A}"),
+            vec![(5, Some(Syllable::Consonant))]
+        );
+        // NGA, ASAT, VIRAMA, GA (kinzi)
+        assert_eq!(
+            syllables("\u{1004}\u{103A}\u{1039}\u{1002}"),
+            vec![(4, Some(Syllable::Consonant))]
+        );
+        // KA, VIRAMA, KHA, VOWEL SIGN U followed by GA
+        assert_eq!(
+            syllables("\u{1000}\u{1039}\u{1001}\u{102F}\u{1002}"),
+            vec![
+                (4, Some(Syllable::Consonant)),
+                (1, Some(Syllable::Consonant))
+            ]
+        );
+        // DIGIT ONE
+        assert_eq!(syllables("\u{1041}"), vec![(1, Some(Syllable::Consonant))]);
+    }
+
+    #[test]
+    fn test_other_syllables() {
+        // SECTION, VISARGA
+        assert_eq!(
+            syllables("\u{104B}\u{1038}"),
+            vec![(2, Some(Syllable::Punctuation))]
+        );
+        // VOWEL SIGN E on its own
+        assert_eq!(syllables("\u{1031}"), vec![(1, Some(Syllable::Broken))]);
+        // KA, VOWEL SIGN AA, VOWEL SIGN U
+        assert_eq!(
+            syllables("\u{1000}\u{102C}\u{102F}"),
+            vec![(2, Some(Syllable::Consonant)), (1, Some(Syllable::Broken))]
+        );
+        assert_eq!(syllables("a\u{104B}"), vec![(1, None), (1, None)]);
+    }
+}
@@ -2,6 +2,15 @@ pub trait SyllableChar {
     fn char(&self) -> char;
 }
 
+impl SyllableChar for char {
+    fn char(&self) -> char {
+        *self
+    }
+}
+
+// The longest run of a repeated item that is matched within a syllable.
+pub const MAX_REPEAT: usize = 8;
+
 pub fn match_unit<T: SyllableChar>() -> impl Fn(&[T]) -> Option<usize> {
     |_cs: &[T]| Some(0)
 }
@@ -71,6 +80,13 @@ pub fn match_repeat_upto<T: SyllableChar>(
     }
 }
 
+pub fn match_any<T: SyllableChar>(
+    f: impl Fn(&[T]) -> Option<usize>,
+    g: impl Fn(&[T]) -> Option<usize>,
+) -> impl Fn(&[T]) -> Option<usize> {
+    match_repeat_upto(MAX_REPEAT, f, g)
+}
+
 pub fn match_seq<T: SyllableChar>(
     f1: impl Fn(&[T]) -> Option<usize>,
     f2: impl Fn(&[T]) -> Option<usize>,
@@ -86,8 +86,10 @@ impl From<&RawGlyph<()>> for SyriacGlyph {
             glyph_index: raw_glyph.glyph_index,
             liga_component_pos: raw_glyph.liga_component_pos,
             glyph_origin: raw_glyph.glyph_origin,
+            cluster: raw_glyph.cluster,
             flags: raw_glyph.flags,
             variation: raw_glyph.variation,
+            feature_mask: raw_glyph.feature_mask,
             extra_data: SyriacData {
                 joining_group,
                 joining_type,
@@ -106,8 +108,10 @@ impl From<&SyriacGlyph> for RawGlyph<()> {
             glyph_index: syriac_glyph.glyph_index,
             liga_component_pos: syriac_glyph.liga_component_pos,
             glyph_origin: syriac_glyph.glyph_origin,
+            cluster: syriac_glyph.cluster,
             flags: syriac_glyph.flags,
             variation: syriac_glyph.variation,
+            feature_mask: syriac_glyph.feature_mask,
             extra_data: (),
         }
     }
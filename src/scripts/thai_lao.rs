@@ -2,11 +2,12 @@
 //! <https://github.com/n8willis/opentype-shaping-documents/blob/master/opentype-shaping-thai-lao.md>.
 
 use crate::error::ShapingError;
-use crate::gsub::{self, FeatureMask, RawGlyph};
+use crate::gsub::{self, FeatureMask, GlyphOrigin, RawGlyph};
 use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
 use crate::unicode::mcc::sort_by_modified_combining_class;
+use crate::unicode::TextChar;
 
-pub(super) fn reorder_marks(cs: &mut Vec<char>) {
+pub(super) fn reorder_marks<T: TextChar>(cs: &mut Vec<T>) {
     // U+0E4D THAI NIKHAHIT and U+0ECD LAO NIGGAHITA marks that originate from an AM vowel
     // must be reordered before any tone markers. NIKHAHIT and NIGGAHITA marks that do not
     // originate from an AM vowel should not be reordered.
@@ -14,12 +15,13 @@ pub(super) fn reorder_marks(cs: &mut Vec<char>) {
     // Reordering may not just be limited to tone markers, but all abovebase marks:
     // https://github.com/n8willis/opentype-shaping-documents/issues/125.
     for i in 0..cs.len() {
-        if let Some((c1, c2)) = split_am_vowel(cs[i]) {
-            cs[i] = c1;
+        if let Some((c1, c2)) = split_am_vowel(cs[i].char()) {
+            let c2 = cs[i].with_char(c2);
+            cs[i] = cs[i].with_char(c1);
             cs.insert(i + 1, c2);
 
             let mut j = i;
-            while j > 0 && is_abovebase_mark(cs[j - 1]) {
+            while j > 0 && is_abovebase_mark(cs[j - 1].char()) {
                 j -= 1;
             }
             cs[j..=i].rotate_right(1);
@@ -93,6 +95,228 @@ pub fn gsub_apply_thai_lao(
     Ok(())
 }
 
+/// Shift Thai marks using glyphs from the Private Use Area.
+///
+/// This is a fallback for older Thai fonts without `GPOS` mark positioning. Tone marks and
+/// above-base vowels are shifted down or left so they don't collide with each other or with
+/// ascenders, below-base vowels are shifted down under descenders, and YO YING and THO THAN lose
+/// their descender when they have a below-base vowel. The shifted glyphs are found by mapping the
+/// Windows Private Use Area characters, and then the Mac ones, with `lookup_glyph`, which returns
+/// `None` if the font doesn't map a character. Glyphs are left unchanged if neither is mapped.
+///
+/// This follows HarfBuzz's Thai PUA fallback shaping.
+pub fn apply_pua_fallback(
+    glyphs: &mut [RawGlyph<()>],
+    mut lookup_glyph: impl FnMut(char) -> Option<u16>,
+) {
+    let mut above_state = ABOVE_START_STATE[ConsonantType::NotConsonant as usize];
+    let mut below_state = BELOW_START_STATE[ConsonantType::NotConsonant as usize];
+    let mut base = 0;
+
+    for i in 0..glyphs.len() {
+        let ch = match glyphs[i].glyph_origin {
+            GlyphOrigin::Char(ch) => Some(ch),
+            GlyphOrigin::Direct => None,
+        };
+        let mark_type = match ch.and_then(mark_type) {
+            Some(mark_type) => mark_type,
+            None => {
+                let consonant_type = ch.map_or(ConsonantType::NotConsonant, consonant_type);
+                above_state = ABOVE_START_STATE[consonant_type as usize];
+                below_state = BELOW_START_STATE[consonant_type as usize];
+                base = i;
+                continue;
+            }
+        };
+
+        let (above_action, next_above_state) = ABOVE_STATE_MACHINE[above_state][mark_type as usize];
+        let (below_action, next_below_state) = BELOW_STATE_MACHINE[below_state][mark_type as usize];
+        above_state = next_above_state;
+        below_state = next_below_state;
+
+        // At least one of the actions is `Nop`
+        let action = match above_action {
+            PuaAction::Nop => below_action,
+            _ => above_action,
+        };
+        let target = match action {
+            PuaAction::Nop => continue,
+            PuaAction::RemoveDescender => base,
+            _ => i,
+        };
+        let glyph = &mut glyphs[target];
+        let pua_glyph = match glyph.glyph_origin {
+            GlyphOrigin::Char(ch) => pua_glyph(ch, action, &mut lookup_glyph),
+            GlyphOrigin::Direct => None,
+        };
+        if let Some(glyph_index) = pua_glyph {
+            glyph.glyph_index = glyph_index;
+            glyph.glyph_origin = GlyphOrigin::Direct;
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum ConsonantType {
+    /// No ascender or descender.
+    Normal,
+    /// Ascender.
+    Ascender,
+    /// Removable descender.
+    RemovableDescender,
+    /// Strict descender.
+    Descender,
+    NotConsonant,
+}
+
+#[derive(Copy, Clone)]
+enum MarkType {
+    AboveVowel,
+    BelowVowel,
+    Tone,
+}
+
+#[derive(Copy, Clone)]
+enum PuaAction {
+    Nop,
+    /// Shift down.
+    ShiftDown,
+    /// Shift left.
+    ShiftLeft,
+    /// Shift down and left.
+    ShiftDownLeft,
+    /// Remove the descender of the base.
+    RemoveDescender,
+}
+
+fn consonant_type(ch: char) -> ConsonantType {
+    match ch {
+        '\u{0E1B}' | '\u{0E1D}' | '\u{0E1F}' => ConsonantType::Ascender,
+        '\u{0E0D}' | '\u{0E10}' => ConsonantType::RemovableDescender,
+        '\u{0E0E}' | '\u{0E0F}' => ConsonantType::Descender,
+        '\u{0E01}'..='\u{0E2E}' => ConsonantType::Normal,
+        _ => ConsonantType::NotConsonant,
+    }
+}
+
+fn mark_type(ch: char) -> Option<MarkType> {
+    match ch {
+        '\u{0E31}' | '\u{0E34}'..='\u{0E37}' | '\u{0E47}' | '\u{0E4D}'..='\u{0E4E}' => {
+            Some(MarkType::AboveVowel)
+        }
+        '\u{0E38}'..='\u{0E3A}' => Some(MarkType::BelowVowel),
+        '\u{0E48}'..='\u{0E4C}' => Some(MarkType::Tone),
+        _ => None,
+    }
+}
+
+// The states of the marks above the base, from no marks (0) to full (3)
+const ABOVE_START_STATE: [usize; 5] = [0, 1, 0, 0, 3];
+
+// Indexed by state and mark type
+const ABOVE_STATE_MACHINE: [[(PuaAction, usize); 3]; 4] = [
+    [
+        (PuaAction::Nop, 3),
+        (PuaAction::Nop, 0),
+        (PuaAction::ShiftDown, 3),
+    ],
+    [
+        (PuaAction::ShiftLeft, 2),
+        (PuaAction::Nop, 1),
+        (PuaAction::ShiftDownLeft, 2),
+    ],
+    [
+        (PuaAction::Nop, 3),
+        (PuaAction::Nop, 2),
+        (PuaAction::ShiftLeft, 3),
+    ],
+    [
+        (PuaAction::Nop, 3),
+        (PuaAction::Nop, 3),
+        (PuaAction::Nop, 3),
+    ],
+];
+
+// The states below the base: no descender (0), removable descender (1), and strict descender (2)
+const BELOW_START_STATE: [usize; 5] = [0, 0, 1, 2, 2];
+
+// Indexed by state and mark type
+const BELOW_STATE_MACHINE: [[(PuaAction, usize); 3]; 3] = [
+    [
+        (PuaAction::Nop, 0),
+        (PuaAction::Nop, 2),
+        (PuaAction::Nop, 0),
+    ],
+    [
+        (PuaAction::Nop, 1),
+        (PuaAction::RemoveDescender, 2),
+        (PuaAction::Nop, 1),
+    ],
+    [
+        (PuaAction::Nop, 2),
+        (PuaAction::ShiftDown, 2),
+        (PuaAction::Nop, 2),
+    ],
+];
+
+/// Returns the glyph of the Windows or Mac PUA character for `ch` with `action` applied.
+fn pua_glyph(
+    ch: char,
+    action: PuaAction,
+    lookup_glyph: &mut impl FnMut(char) -> Option<u16>,
+) -> Option<u16> {
+    let mappings = match action {
+        PuaAction::Nop => return None,
+        PuaAction::ShiftDown => SHIFT_DOWN_MAPPINGS,
+        PuaAction::ShiftLeft => SHIFT_LEFT_MAPPINGS,
+        PuaAction::ShiftDownLeft => SHIFT_DOWN_LEFT_MAPPINGS,
+        PuaAction::RemoveDescender => REMOVE_DESCENDER_MAPPINGS,
+    };
+    let &(_, windows_pua, mac_pua) = mappings.iter().find(|&&(base, _, _)| base == ch)?;
+    lookup_glyph(windows_pua).or_else(|| lookup_glyph(mac_pua))
+}
+
+// Mappings of Thai characters to their Windows and Mac PUA characters
+
+const SHIFT_DOWN_MAPPINGS: &[(char, char, char)] = &[
+    ('\u{0E48}', '\u{F70A}', '\u{F88B}'), // MAI EK
+    ('\u{0E49}', '\u{F70B}', '\u{F88E}'), // MAI THO
+    ('\u{0E4A}', '\u{F70C}', '\u{F891}'), // MAI TRI
+    ('\u{0E4B}', '\u{F70D}', '\u{F894}'), // MAI CHATTAWA
+    ('\u{0E4C}', '\u{F70E}', '\u{F897}'), // THANTHAKHAT
+    ('\u{0E38}', '\u{F718}', '\u{F89B}'), // SARA U
+    ('\u{0E39}', '\u{F719}', '\u{F89C}'), // SARA UU
+    ('\u{0E3A}', '\u{F71A}', '\u{F89D}'), // PHINTHU
+];
+
+const SHIFT_DOWN_LEFT_MAPPINGS: &[(char, char, char)] = &[
+    ('\u{0E48}', '\u{F705}', '\u{F88C}'), // MAI EK
+    ('\u{0E49}', '\u{F706}', '\u{F88F}'), // MAI THO
+    ('\u{0E4A}', '\u{F707}', '\u{F892}'), // MAI TRI
+    ('\u{0E4B}', '\u{F708}', '\u{F895}'), // MAI CHATTAWA
+    ('\u{0E4C}', '\u{F709}', '\u{F898}'), // THANTHAKHAT
+];
+
+const SHIFT_LEFT_MAPPINGS: &[(char, char, char)] = &[
+    ('\u{0E48}', '\u{F713}', '\u{F88A}'), // MAI EK
+    ('\u{0E49}', '\u{F714}', '\u{F88D}'), // MAI THO
+    ('\u{0E4A}', '\u{F715}', '\u{F890}'), // MAI TRI
+    ('\u{0E4B}', '\u{F716}', '\u{F893}'), // MAI CHATTAWA
+    ('\u{0E4C}', '\u{F717}', '\u{F896}'), // THANTHAKHAT
+    ('\u{0E31}', '\u{F710}', '\u{F884}'), // MAI HAN-AKAT
+    ('\u{0E34}', '\u{F701}', '\u{F885}'), // SARA I
+    ('\u{0E35}', '\u{F702}', '\u{F886}'), // SARA II
+    ('\u{0E36}', '\u{F703}', '\u{F887}'), // SARA UE
+    ('\u{0E37}', '\u{F704}', '\u{F888}'), // SARA UEE
+    ('\u{0E47}', '\u{F712}', '\u{F889}'), // MAITAIKHU
+    ('\u{0E4D}', '\u{F711}', '\u{F899}'), // NIKHAHIT
+];
+
+const REMOVE_DESCENDER_MAPPINGS: &[(char, char, char)] = &[
+    ('\u{0E0D}', '\u{F70F}', '\u{F89A}'), // YO YING
+    ('\u{0E10}', '\u{F700}', '\u{F89E}'), // THO THAN
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
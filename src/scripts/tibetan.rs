@@ -0,0 +1,419 @@
+//! Implementation of font shaping for Tibetan script
+//!
+//! Tibetan stacks are encoded with subjoined consonants so, unlike Indic scripts, no glyphs
+//! are reordered. Syllables are identified so that broken clusters can be completed with a
+//! dotted circle, then features are applied in the same order as HarfBuzz.
+
+use tinyvec::tiny_vec;
+
+use crate::error::{ParseError, ShapingError};
+use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph, RawGlyphFlags};
+use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::scripts::syllable::*;
+use crate::unicode::mcc::sort_by_modified_combining_class;
+use crate::unicode::TextChar;
+use crate::DOTTED_CIRCLE;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ShapingClass {
+    Consonant,
+    SubjoinedConsonant,
+    VowelDependent,
+    Nukta,
+    Virama,
+    SyllableModifier,
+    Number,
+    DigitMark,
+    Placeholder,
+    DottedCircle,
+    Joiner,
+    NonJoiner,
+}
+
+fn shaping_class(c: char) -> Option<ShapingClass> {
+    tibetan_character(c)
+}
+
+fn consonant(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::Consonant)
+}
+
+fn subjoined_consonant(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::SubjoinedConsonant)
+}
+
+fn vowel_dependent(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::VowelDependent)
+}
+
+fn nukta(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::Nukta)
+}
+
+// Marks that follow the vowels. The nukta is included because canonical ordering moves it after
+// the halanta.
+fn syllable_modifier(c: char) -> bool {
+    matches!(
+        shaping_class(c),
+        Some(ShapingClass::SyllableModifier | ShapingClass::Virama | ShapingClass::Nukta)
+    )
+}
+
+fn number(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::Number)
+}
+
+fn digit_mark(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::DigitMark)
+}
+
+fn placeholder(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::Placeholder)
+}
+
+fn dotted_circle(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::DottedCircle)
+}
+
+fn zwj(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::Joiner)
+}
+
+fn zwnj(c: char) -> bool {
+    shaping_class(c) == Some(ShapingClass::NonJoiner)
+}
+
+fn match_base<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_either(
+        match_one(consonant),
+        match_either(match_one(placeholder), match_one(dotted_circle)),
+    )(cs)
+}
+
+fn match_z<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_either(match_one(zwj), match_one(zwnj))(cs)
+}
+
+fn match_subjoined<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(subjoined_consonant),
+        match_optional(match_one(nukta)),
+    )(cs)
+}
+
+fn match_syllable_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_repeat_upto(
+        4,
+        match_one(vowel_dependent),
+        match_repeat_upto(4, match_one(syllable_modifier), match_unit()),
+    )(cs)
+}
+
+fn match_partial_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_optional(match_one(nukta)),
+        match_repeat_upto(
+            4,
+            match_subjoined,
+            match_optional_seq(match_z, match_syllable_tail),
+        ),
+    )(cs)
+}
+
+fn match_valid_syllable<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(match_base, match_partial_cluster)(cs)
+}
+
+fn match_number_syllable<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_one(number),
+        match_repeat_upto(2, match_one(digit_mark), match_unit()),
+    )(cs)
+}
+
+fn match_broken_syllable<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_either(
+        match_nonempty(match_partial_cluster),
+        match_repeat_upto(2, match_one(digit_mark), match_unit()),
+    )(cs)
+}
+
+fn match_syllable<T: SyllableChar>(cs: &[T]) -> Option<(usize, Syllable)> {
+    match match_either(match_valid_syllable, match_number_syllable)(cs) {
+        Some(len) => Some((len, Syllable::Valid)),
+        None => match match_broken_syllable(cs) {
+            Some(len) if len > 0 => Some((len, Syllable::Broken)),
+            _ => None,
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Syllable {
+    Valid,
+    Broken,
+}
+
+pub(super) fn preprocess_tibetan<T: TextChar>(cs: &mut Vec<T>) {
+    decompose_vowel(cs);
+    sort_by_modified_combining_class(cs);
+}
+
+/// Replace the discouraged precomposed vowel signs with their canonical decompositions so that
+/// each part can be positioned on its own.
+fn decompose_vowel<T: TextChar>(cs: &mut Vec<T>) {
+    let mut i = 0;
+    while i < cs.len() {
+        let (c1, c2) = match cs[i].char() {
+            '\u{0F73}' => ('\u{0F71}', '\u{0F72}'),
+            '\u{0F75}' => ('\u{0F71}', '\u{0F74}'),
+            '\u{0F76}' => ('\u{0FB2}', '\u{0F80}'),
+            '\u{0F78}' => ('\u{0FB3}', '\u{0F80}'),
+            '\u{0F81}' => ('\u{0F71}', '\u{0F80}'),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        cs.insert(i + 1, cs[i].with_char(c2));
+        cs[i] = cs[i].with_char(c1);
+        i += 2;
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TibetanData;
+
+impl GlyphData for TibetanData {
+    fn merge(d1: TibetanData, _d2: TibetanData) -> TibetanData {
+        d1
+    }
+}
+
+type RawGlyphTibetan = RawGlyph<TibetanData>;
+
+// The arguments match those of the other complex script shapers called from `gsub`
+#[allow(clippy::too_many_arguments)]
+pub fn gsub_apply_tibetan<'a>(
+    dotted_circle_index: u16,
+    gsub_cache: &'a LayoutCache<GSUB>,
+    gsub_table: &'a LayoutTable<GSUB>,
+    gdef_table: Option<&'a GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'a FeatureTableSubstitution<'a>>,
+    glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    let shaping_data = TibetanShapingData {
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        feature_variations,
+    };
+    let mut syllables = to_tibetan_syllables(dotted_circle_index, glyphs);
+
+    for syllable in syllables.iter_mut() {
+        for &features in FEATURE_STAGES {
+            shaping_data.apply_features(features, syllable)?;
+        }
+    }
+
+    *glyphs = syllables
+        .into_iter()
+        .flatten()
+        .map(from_raw_glyph_tibetan)
+        .collect();
+
+    Ok(())
+}
+
+/// The GSUB features applied to each syllable. The features within a stage are applied in lookup
+/// order, as HarfBuzz does. The `abvm` and `blwm` features are applied to the positioned
+/// glyphs in GPOS.
+const FEATURE_STAGES: &[FeatureMask] = &[
+    FeatureMask::CCMP.union(FeatureMask::LOCL),
+    FeatureMask::ABVS.union(FeatureMask::BLWS),
+    FeatureMask::CALT
+        .union(FeatureMask::CLIG)
+        .union(FeatureMask::LIGA)
+        .union(FeatureMask::RLIG),
+];
+
+fn to_tibetan_syllables(
+    dotted_circle_index: u16,
+    mut glyphs: &[RawGlyph<()>],
+) -> Vec<Vec<RawGlyphTibetan>> {
+    let mut syllables: Vec<(Vec<RawGlyphTibetan>, Option<Syllable>)> = Vec::new();
+
+    while !glyphs.is_empty() {
+        match match_syllable(glyphs) {
+            Some((len, syllable_type)) => {
+                let mut syllable = Vec::with_capacity(len + 1);
+                syllable.extend(glyphs[..len].iter().map(to_raw_glyph_tibetan));
+                // Add a dotted circle to broken syllables so they can be treated like valid
+                // syllables
+                if let Syllable::Broken = syllable_type {
+                    insert_dotted_circle(dotted_circle_index, &mut syllable);
+                }
+                syllables.push((syllable, Some(syllable_type)));
+                glyphs = &glyphs[len..];
+            }
+            None => {
+                let glyph = to_raw_glyph_tibetan(&glyphs[0]);
+                match syllables.last_mut() {
+                    // Group consecutive glyphs that are not part of any syllable
+                    Some((other, None)) => other.push(glyph),
+                    _ => syllables.push((vec![glyph], None)),
+                }
+                glyphs = &glyphs[1..];
+            }
+        }
+    }
+
+    syllables
+        .into_iter()
+        .map(|(syllable, _)| syllable)
+        .collect()
+}
+
+fn insert_dotted_circle(dotted_circle_index: u16, glyphs: &mut Vec<RawGlyphTibetan>) {
+    if dotted_circle_index == 0 {
+        return;
+    }
+    let dotted_circle = RawGlyphTibetan {
+        unicodes: tiny_vec![[char; 1] => DOTTED_CIRCLE],
+        glyph_index: dotted_circle_index,
+        liga_component_pos: 0,
+        glyph_origin: GlyphOrigin::Char(DOTTED_CIRCLE),
+        // The dotted circle belongs to the cluster of the syllable it completes
+        cluster: glyphs.first().map_or(0, |glyph| glyph.cluster),
+        flags: RawGlyphFlags::empty(),
+        variation: None,
+        feature_mask: glyphs.first().and_then(|glyph| glyph.feature_mask),
+        extra_data: TibetanData,
+    };
+    glyphs.insert(0, dotted_circle);
+}
+
+struct TibetanShapingData<'tables> {
+    gsub_cache: &'tables LayoutCache<GSUB>,
+    gsub_table: &'tables LayoutTable<GSUB>,
+    gdef_table: Option<&'tables GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'tables FeatureTableSubstitution<'tables>>,
+}
+
+impl TibetanShapingData<'_> {
+    fn apply_features(
+        &self,
+        features: FeatureMask,
+        glyphs: &mut Vec<RawGlyphTibetan>,
+    ) -> Result<(), ParseError> {
+        let index = gsub::get_lookups_cache_index(
+            self.gsub_cache,
+            self.script_tag,
+            self.lang_tag,
+            self.feature_variations,
+            features,
+        )?;
+        let lookups = &self.gsub_cache.cached_lookups.borrow()[index];
+
+        for &(lookup_index, feature_tag) in lookups {
+            gsub::gsub_apply_lookup(
+                self.gsub_cache,
+                self.gsub_table,
+                self.gdef_table,
+                lookup_index,
+                feature_tag,
+                None,
+                glyphs,
+                0,
+                glyphs.len(),
+                |_| true,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn to_raw_glyph_tibetan(g: &RawGlyph<()>) -> RawGlyphTibetan {
+    RawGlyphTibetan {
+        unicodes: g.unicodes.clone(),
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: TibetanData,
+    }
+}
+
+fn from_raw_glyph_tibetan(g: RawGlyphTibetan) -> RawGlyph<()> {
+    RawGlyph {
+        unicodes: g.unicodes,
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: (),
+    }
+}
+
+fn tibetan_character(c: char) -> Option<ShapingClass> {
+    use ShapingClass::*;
+
+    match c as u32 {
+        0x0F18..=0x0F19 => Some(DigitMark), // Astrological signs used with digits
+        0x0F20..=0x0F33 => Some(Number),    // Digits and half digits
+        0x0F35 => Some(SyllableModifier),   // Mark Ngas Bzung Nyi Zla
+        0x0F37 => Some(SyllableModifier),   // Mark Ngas Bzung Sgor Rtags
+        0x0F39 => Some(Nukta),              // Mark Tsa -Phru
+        0x0F3E..=0x0F3F => Some(SyllableModifier), // Sign Yar Tshes, Sign Mar Tshes
+        0x0F40..=0x0F47 => Some(Consonant),
+        0x0F49..=0x0F6C => Some(Consonant),
+        0x0F71..=0x0F7D => Some(VowelDependent),
+        0x0F7E..=0x0F7F => Some(SyllableModifier), // Sign Rjes Su Nga Ro, Sign Rnam Bcad
+        0x0F80..=0x0F81 => Some(VowelDependent),
+        0x0F82..=0x0F83 => Some(SyllableModifier), // Sign Nyi Zla Naa Da, Sign Sna Ldan
+        0x0F84 => Some(Virama),                    // Mark Halanta
+        0x0F86..=0x0F87 => Some(SyllableModifier), // Sign Lci Rtags, Sign Yang Rtags
+        0x0F88..=0x0F8C => Some(Consonant),        // Head letters such as Sign Lce Tsa Can
+        0x0F8D..=0x0F97 => Some(SubjoinedConsonant),
+        0x0F99..=0x0FBC => Some(SubjoinedConsonant),
+        0x0FC6 => Some(SyllableModifier), // Symbol Padma Gdan
+
+        // Miscellaneous character table.
+        0x00A0 => Some(Placeholder),          // No-break space
+        0x200C => Some(NonJoiner),            // Zero-width non-joiner
+        0x200D => Some(Joiner),               // Zero-width joiner
+        0x2010..=0x2014 => Some(Placeholder), // Hyphens and dashes
+        0x25CC => Some(DottedCircle),         // Dotted circle
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompose_vowel() {
+        let mut cs = vec!['\u{0F40}', '\u{0F73}', '\u{0F42}', '\u{0F81}'];
+        decompose_vowel(&mut cs);
+
+        assert_eq!(
+            vec!['\u{0F40}', '\u{0F71}', '\u{0F72}', '\u{0F42}', '\u{0F71}', '\u{0F80}'],
+            cs
+        );
+    }
+}
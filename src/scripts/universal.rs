@@ -0,0 +1,859 @@
+//! Implementation of font shaping for scripts handled by the Universal Shaping Engine
+//!
+//! The Universal Shaping Engine (USE) is a generic shaper for complex scripts that don't have a
+//! dedicated shaping engine. Each character has a USE category derived from its
+//! `Indic_Syllabic_Category` and `Indic_Positional_Category` properties. Text is split into
+//! clusters using those categories, then features are applied to each cluster in a fixed
+//! sequence of stages, with repha and pre-base glyphs reordered between them.
+//!
+//! <https://learn.microsoft.com/en-us/typography/script-development/use>
+
+use std::cmp;
+
+use tinyvec::tiny_vec;
+
+use crate::error::{ParseError, ShapingError};
+use crate::gsub::{self, FeatureMask, GlyphData, GlyphOrigin, RawGlyph, RawGlyphFlags};
+use crate::layout::{FeatureTableSubstitution, GDEFTable, LayoutCache, LayoutTable, GSUB};
+use crate::scripts::syllable::*;
+use crate::scripts::universal_data::USE_CATEGORY;
+use crate::unicode::lookup_range;
+use crate::{tag, DOTTED_CIRCLE};
+
+/// The USE category of a character. The abbreviation used by the USE specification is given for
+/// each category.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum UseCategory {
+    /// O
+    Other,
+    /// B
+    Base,
+    /// N
+    BaseNumber,
+    /// GB
+    BaseOther,
+    /// CS
+    ConsonantWithStacker,
+    /// SUB
+    ConsonantSubjoined,
+    /// R
+    Repha,
+    /// H
+    Halant,
+    /// HN
+    HalantNumber,
+    /// HVM
+    HalantOrVowelModifier,
+    /// IS
+    InvisibleStacker,
+    /// Sk
+    Sakot,
+    /// FAbv
+    ConsonantFinalAbove,
+    /// FBlw
+    ConsonantFinalBelow,
+    /// FPst
+    ConsonantFinalPost,
+    /// MAbv
+    ConsonantMedialAbove,
+    /// MBlw
+    ConsonantMedialBelow,
+    /// MPst
+    ConsonantMedialPost,
+    /// MPre
+    ConsonantMedialPre,
+    /// CMAbv
+    ConsonantModifierAbove,
+    /// CMBlw
+    ConsonantModifierBelow,
+    /// VAbv
+    VowelAbove,
+    /// VBlw
+    VowelBelow,
+    /// VPst
+    VowelPost,
+    /// VPre
+    VowelPre,
+    /// VMAbv
+    VowelModifierAbove,
+    /// VMBlw
+    VowelModifierBelow,
+    /// VMPst
+    VowelModifierPost,
+    /// VMPre
+    VowelModifierPre,
+    /// SMAbv
+    SymbolModifierAbove,
+    /// SMBlw
+    SymbolModifierBelow,
+    /// FMAbv
+    FinalModifierAbove,
+    /// FMBlw
+    FinalModifierBelow,
+    /// FMPst
+    FinalModifierPost,
+    /// CGJ, ZWJ, ZWNJ, and variation selectors. These are skipped when finding clusters.
+    Ignorable,
+}
+
+impl UseCategory {
+    fn is_halant(self) -> bool {
+        matches!(
+            self,
+            UseCategory::Halant
+                | UseCategory::HalantOrVowelModifier
+                | UseCategory::InvisibleStacker
+        )
+    }
+
+    fn is_post_base(self) -> bool {
+        use UseCategory::*;
+
+        matches!(
+            self,
+            ConsonantFinalAbove
+                | ConsonantFinalBelow
+                | ConsonantFinalPost
+                | ConsonantMedialAbove
+                | ConsonantMedialBelow
+                | ConsonantMedialPost
+                | ConsonantMedialPre
+                | VowelAbove
+                | VowelBelow
+                | VowelPost
+                | VowelPre
+                | VowelModifierAbove
+                | VowelModifierBelow
+                | VowelModifierPost
+                | VowelModifierPre
+        )
+    }
+}
+
+fn is(category: UseCategory) -> impl Fn(char) -> bool {
+    move |c| use_category(c) == category
+}
+
+fn halant_or_sakot(c: char) -> bool {
+    use_category(c).is_halant() || use_category(c) == UseCategory::Sakot
+}
+
+fn match_consonant_modifiers<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_any(match_one(is(ConsonantModifierAbove)), match_unit()),
+        match_seq(
+            match_any(match_one(is(ConsonantModifierBelow)), match_unit()),
+            match_any(
+                match_seq(
+                    match_either(
+                        match_seq(match_one(halant_or_sakot), match_one(is(Base))),
+                        match_one(is(ConsonantSubjoined)),
+                    ),
+                    match_seq(
+                        match_optional(match_one(is(ConsonantModifierAbove))),
+                        match_any(match_one(is(ConsonantModifierBelow)), match_unit()),
+                    ),
+                ),
+                match_unit(),
+            ),
+        ),
+    )(cs)
+}
+
+fn match_medial_consonants<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_optional(match_one(is(ConsonantMedialPre))),
+        match_seq(
+            match_optional(match_one(is(ConsonantMedialAbove))),
+            match_seq(
+                match_optional(match_one(is(ConsonantMedialBelow))),
+                match_optional(match_one(is(ConsonantMedialPost))),
+            ),
+        ),
+    )(cs)
+}
+
+fn match_dependent_vowels<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_either(
+        match_seq(
+            match_any(match_one(is(VowelPre)), match_unit()),
+            match_seq(
+                match_any(match_one(is(VowelAbove)), match_unit()),
+                match_seq(
+                    match_any(match_one(is(VowelBelow)), match_unit()),
+                    match_any(match_one(is(VowelPost)), match_unit()),
+                ),
+            ),
+        ),
+        match_one(is(Halant)),
+    )(cs)
+}
+
+fn match_vowel_modifiers<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_optional(match_one(is(HalantOrVowelModifier))),
+        match_seq(
+            match_any(match_one(is(VowelModifierPre)), match_unit()),
+            match_seq(
+                match_any(match_one(is(VowelModifierAbove)), match_unit()),
+                match_seq(
+                    match_any(match_one(is(VowelModifierBelow)), match_unit()),
+                    match_any(match_one(is(VowelModifierPost)), match_unit()),
+                ),
+            ),
+        ),
+    )(cs)
+}
+
+fn match_final_consonants<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_any(match_one(is(ConsonantFinalAbove)), match_unit()),
+        match_seq(
+            match_any(match_one(is(ConsonantFinalBelow)), match_unit()),
+            match_any(match_one(is(ConsonantFinalPost)), match_unit()),
+        ),
+    )(cs)
+}
+
+fn match_final_modifiers<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_either(
+        match_seq(
+            match_any(match_one(is(FinalModifierAbove)), match_unit()),
+            match_any(match_one(is(FinalModifierBelow)), match_unit()),
+        ),
+        match_optional(match_one(is(FinalModifierPost))),
+    )(cs)
+}
+
+fn match_syllable_start<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_optional(match_either(
+            match_one(is(Repha)),
+            match_one(is(ConsonantWithStacker)),
+        )),
+        match_either(match_one(is(Base)), match_one(is(BaseOther))),
+    )(cs)
+}
+
+fn match_syllable_middle<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_consonant_modifiers,
+        match_seq(
+            match_medial_consonants,
+            match_seq(
+                match_dependent_vowels,
+                match_seq(
+                    match_vowel_modifiers,
+                    match_any(
+                        match_seq(match_one(is(Sakot)), match_one(is(Base))),
+                        match_unit(),
+                    ),
+                ),
+            ),
+        ),
+    )(cs)
+}
+
+fn match_syllable_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_syllable_middle,
+        match_seq(match_final_consonants, match_final_modifiers),
+    )(cs)
+}
+
+fn match_virama_terminated_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_consonant_modifiers,
+        match_one(is(UseCategory::InvisibleStacker)),
+    )(cs)
+}
+
+fn match_sakot_terminated_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(match_syllable_middle, match_one(is(UseCategory::Sakot)))(cs)
+}
+
+fn match_number_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_any(
+            match_seq(match_one(is(HalantNumber)), match_one(is(BaseNumber))),
+            match_unit(),
+        ),
+        match_optional(match_one(is(HalantNumber))),
+    )(cs)
+}
+
+fn match_symbol_tail<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_any(match_one(is(SymbolModifierAbove)), match_unit()),
+        match_any(match_one(is(SymbolModifierBelow)), match_unit()),
+    )(cs)
+}
+
+fn match_standard_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(
+        match_syllable_start,
+        match_either(
+            match_syllable_tail,
+            match_either(match_virama_terminated_tail, match_sakot_terminated_tail),
+        ),
+    )(cs)
+}
+
+fn match_number_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_seq(match_one(is(UseCategory::BaseNumber)), match_number_tail)(cs)
+}
+
+fn match_symbol_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    use UseCategory::*;
+
+    match_seq(
+        match_either(match_one(is(Other)), match_one(is(BaseOther))),
+        match_symbol_tail,
+    )(cs)
+}
+
+fn match_broken_cluster<T: SyllableChar>(cs: &[T]) -> Option<usize> {
+    match_nonempty(match_seq(
+        match_optional(match_one(is(UseCategory::Repha))),
+        match_either(
+            match_either(match_syllable_tail, match_number_tail),
+            match_either(
+                match_symbol_tail,
+                match_either(match_virama_terminated_tail, match_sakot_terminated_tail),
+            ),
+        ),
+    ))(cs)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Syllable {
+    Standard,
+    Number,
+    Symbol,
+    Broken,
+    NonCluster,
+}
+
+fn match_syllable<T: SyllableChar>(cs: &[T]) -> Option<(usize, Syllable)> {
+    let candidates = [
+        (match_standard_cluster(cs), Syllable::Standard),
+        (match_number_cluster(cs), Syllable::Number),
+        (match_symbol_cluster(cs), Syllable::Symbol),
+        (match_broken_cluster(cs), Syllable::Broken),
+    ];
+
+    // Prefer the longest match, then the earliest candidate
+    let mut best: Option<(usize, Syllable)> = None;
+    for (len, syllable_type) in candidates {
+        match (len, best) {
+            (Some(len), Some((best_len, _))) if len <= best_len => {}
+            (Some(len), _) if len > 0 => best = Some((len, syllable_type)),
+            _ => {}
+        }
+    }
+    best
+}
+
+/// Splits `cs` into clusters, returning the length and type of each.
+///
+/// Ignorable characters are skipped when matching and belong to the cluster before them.
+fn find_syllables(cs: &[char]) -> Vec<(usize, Syllable)> {
+    let (positions, chars): (Vec<usize>, Vec<char>) = cs
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| use_category(c) != UseCategory::Ignorable)
+        .unzip();
+
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (len, syllable_type) = match_syllable(&chars[i..]).unwrap_or((1, Syllable::NonCluster));
+        starts.push((positions[i], syllable_type));
+        i += len;
+    }
+
+    let mut syllables = Vec::with_capacity(starts.len());
+    for (index, &(start, syllable_type)) in starts.iter().enumerate() {
+        // Leading ignorable characters belong to the first cluster
+        let start = if index == 0 { 0 } else { start };
+        let end = starts.get(index + 1).map_or(cs.len(), |&(end, _)| end);
+        syllables.push((end - start, syllable_type));
+    }
+    if syllables.is_empty() && !cs.is_empty() {
+        syllables.push((cs.len(), Syllable::NonCluster));
+    }
+    syllables
+}
+
+#[derive(Copy, Clone, Debug)]
+struct UseData {
+    category: UseCategory,
+}
+
+impl GlyphData for UseData {
+    fn merge(d1: UseData, _d2: UseData) -> UseData {
+        d1
+    }
+}
+
+type RawGlyphUse = RawGlyph<UseData>;
+
+impl RawGlyphUse {
+    fn category(&self) -> UseCategory {
+        self.extra_data.category
+    }
+
+    fn is_halant(&self) -> bool {
+        self.category().is_halant() && !self.ligature()
+    }
+}
+
+/// The joining form of a cluster, used by scripts that join neighbouring clusters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum JoiningForm {
+    Isol,
+    Init,
+    Medi,
+    Fina,
+}
+
+impl JoiningForm {
+    fn mask(self) -> FeatureMask {
+        match self {
+            JoiningForm::Isol => FeatureMask::ISOL,
+            JoiningForm::Init => FeatureMask::INIT,
+            JoiningForm::Medi => FeatureMask::MEDI,
+            JoiningForm::Fina => FeatureMask::FINA,
+        }
+    }
+}
+
+// The arguments match those of the other complex script shapers called from `gsub`
+#[allow(clippy::too_many_arguments)]
+pub fn gsub_apply_universal<'a>(
+    dotted_circle_index: u16,
+    gsub_cache: &'a LayoutCache<GSUB>,
+    gsub_table: &'a LayoutTable<GSUB>,
+    gdef_table: Option<&'a GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'a FeatureTableSubstitution<'a>>,
+    glyphs: &mut Vec<RawGlyph<()>>,
+) -> Result<(), ShapingError> {
+    let shaping_data = UseShapingData {
+        gsub_cache,
+        gsub_table,
+        gdef_table,
+        script_tag,
+        lang_tag,
+        feature_variations,
+    };
+    let mut syllables = to_use_syllables(dotted_circle_index, glyphs);
+    let forms = if is_joining_script(script_tag) {
+        joining_forms(&syllables)
+    } else {
+        vec![None; syllables.len()]
+    };
+
+    for ((syllable, _), form) in syllables.iter_mut().zip(forms) {
+        shape_syllable(&shaping_data, syllable, form)?;
+    }
+
+    *glyphs = syllables
+        .into_iter()
+        .flat_map(|(syllable, _)| syllable)
+        .map(from_raw_glyph_use)
+        .collect();
+
+    Ok(())
+}
+
+fn to_use_syllables(
+    dotted_circle_index: u16,
+    glyphs: &[RawGlyph<()>],
+) -> Vec<(Vec<RawGlyphUse>, Syllable)> {
+    let chars = glyphs.iter().map(|g| g.char()).collect::<Vec<_>>();
+    let mut syllables = Vec::new();
+    let mut start = 0;
+
+    for (len, syllable_type) in find_syllables(&chars) {
+        let mut syllable = Vec::with_capacity(len + 1);
+        syllable.extend(glyphs[start..start + len].iter().map(to_raw_glyph_use));
+        // Add a dotted circle to broken clusters so they can be shaped like standard clusters
+        if syllable_type == Syllable::Broken {
+            insert_dotted_circle(dotted_circle_index, &mut syllable);
+        }
+        syllables.push((syllable, syllable_type));
+        start += len;
+    }
+
+    syllables
+}
+
+fn insert_dotted_circle(dotted_circle_index: u16, glyphs: &mut Vec<RawGlyphUse>) {
+    if dotted_circle_index == 0 {
+        return;
+    }
+    let dotted_circle = RawGlyphUse {
+        unicodes: tiny_vec![[char; 1] => DOTTED_CIRCLE],
+        glyph_index: dotted_circle_index,
+        liga_component_pos: 0,
+        glyph_origin: GlyphOrigin::Char(DOTTED_CIRCLE),
+        // The dotted circle belongs to the cluster of the syllable it completes
+        cluster: glyphs.first().map_or(0, |glyph| glyph.cluster),
+        flags: RawGlyphFlags::empty(),
+        variation: None,
+        feature_mask: glyphs.first().and_then(|glyph| glyph.feature_mask),
+        extra_data: UseData {
+            category: UseCategory::BaseOther,
+        },
+    };
+    // The dotted circle takes the place of the base, after any repha
+    let index = match glyphs.first() {
+        Some(glyph) if glyph.category() == UseCategory::Repha => 1,
+        _ => 0,
+    };
+    glyphs.insert(index, dotted_circle);
+}
+
+/// Returns whether the clusters of a script join their neighbours, taking `isol`, `init`, `medi`,
+/// and `fina` forms.
+fn is_joining_script(script_tag: u32) -> bool {
+    matches!(
+        script_tag,
+        tag::ADLM
+            | tag::CHRS
+            | tag::MAND
+            | tag::MANI
+            | tag::NKO
+            | tag::OUGR
+            | tag::PHAG
+            | tag::PHLP
+            | tag::ROHG
+            | tag::SOGD
+            | tag::YEZI
+    )
+}
+
+/// Determine the joining form of each cluster. Clusters join the cluster before them unless
+/// either is a symbol or non-cluster.
+fn joining_forms(syllables: &[(Vec<RawGlyphUse>, Syllable)]) -> Vec<Option<JoiningForm>> {
+    let mut forms: Vec<Option<JoiningForm>> = Vec::with_capacity(syllables.len());
+
+    for (_, syllable_type) in syllables {
+        match syllable_type {
+            Syllable::Symbol | Syllable::NonCluster => forms.push(None),
+            _ => match forms.last_mut() {
+                Some(Some(last_form @ (JoiningForm::Isol | JoiningForm::Fina))) => {
+                    *last_form = match last_form {
+                        JoiningForm::Isol => JoiningForm::Init,
+                        _ => JoiningForm::Medi,
+                    };
+                    forms.push(Some(JoiningForm::Fina));
+                }
+                _ => forms.push(Some(JoiningForm::Isol)),
+            },
+        }
+    }
+
+    forms
+}
+
+fn shape_syllable(
+    shaping_data: &UseShapingData<'_>,
+    syllable: &mut Vec<RawGlyphUse>,
+    form: Option<JoiningForm>,
+) -> Result<(), ParseError> {
+    let apply = |features: FeatureMask, glyphs: &mut Vec<RawGlyphUse>, length: usize| {
+        shaping_data.apply_features(features, glyphs, length)
+    };
+
+    if syllable.is_empty() {
+        return Ok(());
+    }
+
+    // Default glyph pre-processing group
+    let features = FeatureMask::LOCL | FeatureMask::CCMP | FeatureMask::NUKT | FeatureMask::AKHN;
+    apply(features, syllable, syllable.len())?;
+
+    // Reordering group. A glyph substituted by `rphf` becomes a repha, and one substituted by
+    // `pref` becomes a pre-base vowel, so that they are moved by `reorder_syllable`.
+    let limit = match syllable[0].category() {
+        UseCategory::Repha => 1,
+        _ => cmp::min(3, syllable.len()),
+    };
+    let original = glyph_indices(syllable);
+    let limit = apply(FeatureMask::RPHF, syllable, limit)?;
+    if let Some(i) = first_substituted(&original, &syllable[..limit]) {
+        syllable[i].extra_data.category = UseCategory::Repha;
+    }
+
+    let original = glyph_indices(syllable);
+    apply(FeatureMask::PREF, syllable, syllable.len())?;
+    if let Some(i) = first_substituted(&original, syllable) {
+        syllable[i].extra_data.category = UseCategory::VowelPre;
+    }
+
+    // Orthographic unit shaping group
+    let features = FeatureMask::RKRF
+        | FeatureMask::ABVF
+        | FeatureMask::BLWF
+        | FeatureMask::HALF
+        | FeatureMask::PSTF
+        | FeatureMask::VATU
+        | FeatureMask::CJCT;
+    apply(features, syllable, syllable.len())?;
+
+    reorder_syllable(syllable);
+
+    // Topographical features
+    if let Some(form) = form {
+        apply(form.mask(), syllable, syllable.len())?;
+    }
+
+    // Standard typographic presentation
+    let features = FeatureMask::ABVS
+        | FeatureMask::BLWS
+        | FeatureMask::HALN
+        | FeatureMask::PRES
+        | FeatureMask::PSTS
+        | FeatureMask::CALT
+        | FeatureMask::CLIG
+        | FeatureMask::LIGA
+        | FeatureMask::RCLT
+        | FeatureMask::RLIG;
+    apply(features, syllable, syllable.len())?;
+
+    Ok(())
+}
+
+fn glyph_indices(glyphs: &[RawGlyphUse]) -> Vec<u16> {
+    glyphs.iter().map(|g| g.glyph_index).collect()
+}
+
+fn first_substituted(original: &[u16], glyphs: &[RawGlyphUse]) -> Option<usize> {
+    glyphs
+        .iter()
+        .zip(original)
+        .position(|(g, &glyph_index)| g.glyph_index != glyph_index)
+}
+
+fn reorder_syllable(glyphs: &mut [RawGlyphUse]) {
+    // Move a repha towards the end of the cluster, but before the first post-base glyph
+    if glyphs.len() > 1 && glyphs[0].category() == UseCategory::Repha {
+        let end = glyphs.len();
+        for i in 1..end {
+            let is_post_base = glyphs[i].category().is_post_base() || glyphs[i].is_halant();
+            if is_post_base || i == end - 1 {
+                let to = if is_post_base { i - 1 } else { i };
+                glyphs[..=to].rotate_left(1);
+                break;
+            }
+        }
+    }
+
+    // Move pre-base vowels and vowel modifiers to the start of the cluster, or after the last
+    // halant before them. Only the first glyph of a multiple substitution is moved.
+    let mut j = 0;
+    for i in 0..glyphs.len() {
+        let category = glyphs[i].category();
+        if glyphs[i].is_halant() {
+            j = i + 1;
+        } else if matches!(
+            category,
+            UseCategory::VowelPre | UseCategory::VowelModifierPre
+        ) && !glyphs[i].multi_subst_dup()
+            && j < i
+        {
+            glyphs[j..=i].rotate_right(1);
+        }
+    }
+}
+
+struct UseShapingData<'tables> {
+    gsub_cache: &'tables LayoutCache<GSUB>,
+    gsub_table: &'tables LayoutTable<GSUB>,
+    gdef_table: Option<&'tables GDEFTable>,
+    script_tag: u32,
+    lang_tag: Option<u32>,
+    feature_variations: Option<&'tables FeatureTableSubstitution<'tables>>,
+}
+
+impl UseShapingData<'_> {
+    fn apply_features(
+        &self,
+        features: FeatureMask,
+        glyphs: &mut Vec<RawGlyphUse>,
+        mut length: usize,
+    ) -> Result<usize, ParseError> {
+        let index = gsub::get_lookups_cache_index(
+            self.gsub_cache,
+            self.script_tag,
+            self.lang_tag,
+            self.feature_variations,
+            features,
+        )?;
+        let lookups = &self.gsub_cache.cached_lookups.borrow()[index];
+
+        // Features within a stage are applied in lookup order
+        for &(lookup_index, feature_tag) in lookups {
+            length = gsub::gsub_apply_lookup(
+                self.gsub_cache,
+                self.gsub_table,
+                self.gdef_table,
+                lookup_index,
+                feature_tag,
+                None,
+                glyphs,
+                0,
+                length,
+                |_| true,
+            )?;
+        }
+
+        Ok(length)
+    }
+}
+
+fn to_raw_glyph_use(g: &RawGlyph<()>) -> RawGlyphUse {
+    RawGlyphUse {
+        unicodes: g.unicodes.clone(),
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: UseData {
+            category: use_category(g.char()),
+        },
+    }
+}
+
+fn from_raw_glyph_use(g: RawGlyphUse) -> RawGlyph<()> {
+    RawGlyph {
+        unicodes: g.unicodes,
+        glyph_index: g.glyph_index,
+        liga_component_pos: g.liga_component_pos,
+        glyph_origin: g.glyph_origin,
+        cluster: g.cluster,
+        flags: g.flags,
+        variation: g.variation,
+        feature_mask: g.feature_mask,
+        extra_data: (),
+    }
+}
+
+fn use_category(c: char) -> UseCategory {
+    lookup_range(USE_CATEGORY, c).unwrap_or(UseCategory::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syllables(s: &str) -> Vec<(usize, Syllable)> {
+        find_syllables(&s.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_standard_clusters() {
+        // Tirhuta KA, VIRAMA, KHA, I, ANUSVARA
+        assert_eq!(
+            syllables("\u{1148F}\u{114C2}\u{11490}\u{114B1}\u{114C0}"),
+            vec![(5, Syllable::Standard)]
+        );
+        // Tirhuta KA, VIRAMA followed by KA
+        assert_eq!(
+            syllables("\u{1148F}\u{114C2}\u{1148F}"),
+            vec![(3, Syllable::Standard)]
+        );
+        // Tai Tham KA, SAKOT, KA, E
+        assert_eq!(
+            syllables("\u{1A20}\u{1A60}\u{1A20}\u{1A6E}"),
+            vec![(4, Syllable::Standard)]
+        );
+        // Tirhuta KA, AA followed by KA
+        assert_eq!(
+            syllables("\u{1148F}\u{114B0}\u{1148F}"),
+            vec![(2, Syllable::Standard), (1, Syllable::Standard)]
+        );
+    }
+
+    #[test]
+    fn test_derived_categories() {
+        // Newa KA, VIRAMA, KA, AA
+        assert_eq!(
+            syllables("\u{1140E}\u{11442}\u{1140E}\u{11435}"),
+            vec![(4, Syllable::Standard)]
+        );
+        // Sharada KA, I
+        assert_eq!(use_category('\u{111B4}'), UseCategory::VowelPre);
+        assert_eq!(
+            syllables("\u{11191}\u{111B4}"),
+            vec![(2, Syllable::Standard)]
+        );
+        // Adlam letters are bases because they join, and the ALIF LENGTHENER is overridden to be
+        // a consonant modifier
+        assert_eq!(
+            syllables("\u{1E900}\u{1E944}\u{1E900}"),
+            vec![(2, Syllable::Standard), (1, Syllable::Standard)]
+        );
+    }
+
+    #[test]
+    fn test_joining_scripts() {
+        assert!(is_joining_script(tag::ADLM));
+        assert!(is_joining_script(tag::NKO));
+        assert!(!is_joining_script(tag::TIRH));
+        assert!(!is_joining_script(tag::NEWA));
+    }
+
+    #[test]
+    fn test_broken_clusters() {
+        assert_eq!(syllables("\u{114B1}"), vec![(1, Syllable::Broken)]);
+        assert_eq!(
+            syllables("\u{1148F}\u{114B0}\u{114B3}"),
+            vec![(2, Syllable::Standard), (1, Syllable::Broken)]
+        );
+    }
+
+    #[test]
+    fn test_ignorables() {
+        // ZWJ and ZWNJ don't break clusters
+        assert_eq!(
+            syllables("\u{1148F}\u{114C2}\u{200D}\u{11490}"),
+            vec![(4, Syllable::Standard)]
+        );
+        assert_eq!(
+            syllables("\u{1148F}\u{200C}\u{114B0}"),
+            vec![(3, Syllable::Standard)]
+        );
+        assert_eq!(
+            syllables("\u{200D}\u{1148F} "),
+            vec![(2, Syllable::Standard), (1, Syllable::Symbol)]
+        );
+        assert_eq!(syllables("\u{200D}"), vec![(1, Syllable::NonCluster)]);
+    }
+}
@@ -0,0 +1,618 @@
+// DO NOT EDIT THIS FILE. IT WAS AUTOMATICALLY GENERATED BY:
+//
+//   scripts/gen_unicode_data.py use ../ucd-generate/ucd-15.0.0
+//
+// Unicode version: 15.0.0.
+//
+// Universal Shaping Engine categories derived from the Indic_Syllabic_Category,
+// Indic_Positional_Category, Joining_Type, and General_Category properties of the
+// characters of the scripts shaped by USE.
+
+use crate::scripts::universal::UseCategory::{self, *};
+
+/// Ranges of characters with the same USE category, sorted by their first character.
+///
+/// Characters that aren't in a range have the category `Other`.
+pub(crate) const USE_CATEGORY: &[(char, char, UseCategory)] = &[
+    ('\u{2D}', '\u{2D}', BaseOther),
+    ('\u{30}', '\u{39}', Base),
+    ('\u{A0}', '\u{A0}', BaseOther),
+    ('\u{B2}', '\u{B3}', FinalModifierPost),
+    ('\u{D7}', '\u{D7}', BaseOther),
+    ('\u{34F}', '\u{34F}', Ignorable),
+    ('\u{640}', '\u{640}', Base),
+    ('\u{7CA}', '\u{7EA}', Base),
+    ('\u{7EB}', '\u{7F3}', VowelModifierAbove),
+    ('\u{7FA}', '\u{7FA}', Base),
+    ('\u{7FD}', '\u{7FD}', VowelModifierAbove),
+    ('\u{840}', '\u{858}', Base),
+    ('\u{859}', '\u{85B}', ConsonantModifierBelow),
+    ('\u{951}', '\u{951}', VowelModifierAbove),
+    ('\u{952}', '\u{952}', VowelModifierBelow),
+    ('\u{1700}', '\u{1711}', Base),
+    ('\u{1712}', '\u{1712}', VowelAbove),
+    ('\u{1713}', '\u{1714}', VowelBelow),
+    ('\u{1715}', '\u{1715}', VowelPost),
+    ('\u{171F}', '\u{1731}', Base),
+    ('\u{1732}', '\u{1732}', VowelAbove),
+    ('\u{1733}', '\u{1733}', VowelBelow),
+    ('\u{1734}', '\u{1734}', VowelPost),
+    ('\u{1740}', '\u{1751}', Base),
+    ('\u{1752}', '\u{1752}', VowelAbove),
+    ('\u{1753}', '\u{1753}', VowelBelow),
+    ('\u{1760}', '\u{176C}', Base),
+    ('\u{176E}', '\u{1770}', Base),
+    ('\u{1772}', '\u{1772}', VowelAbove),
+    ('\u{1773}', '\u{1773}', VowelBelow),
+    ('\u{1900}', '\u{1900}', BaseOther),
+    ('\u{1901}', '\u{191E}', Base),
+    ('\u{1920}', '\u{1921}', VowelAbove),
+    ('\u{1922}', '\u{1922}', VowelBelow),
+    ('\u{1923}', '\u{1924}', VowelPost),
+    ('\u{1925}', '\u{1928}', VowelAbove),
+    ('\u{1929}', '\u{192B}', ConsonantSubjoined),
+    ('\u{1930}', '\u{1931}', ConsonantFinalPost),
+    ('\u{1932}', '\u{1932}', VowelModifierBelow),
+    ('\u{1933}', '\u{1938}', ConsonantFinalPost),
+    ('\u{1939}', '\u{1939}', ConsonantFinalBelow),
+    ('\u{193A}', '\u{193A}', VowelModifierAbove),
+    ('\u{193B}', '\u{193B}', FinalModifierBelow),
+    ('\u{1946}', '\u{194F}', Base),
+    ('\u{1A00}', '\u{1A16}', Base),
+    ('\u{1A17}', '\u{1A18}', VowelAbove),
+    ('\u{1A19}', '\u{1A19}', VowelPre),
+    ('\u{1A1A}', '\u{1A1A}', VowelPost),
+    ('\u{1A1B}', '\u{1A1B}', VowelAbove),
+    ('\u{1A20}', '\u{1A54}', Base),
+    ('\u{1A55}', '\u{1A55}', ConsonantMedialPre),
+    ('\u{1A56}', '\u{1A56}', ConsonantMedialBelow),
+    ('\u{1A57}', '\u{1A57}', ConsonantSubjoined),
+    ('\u{1A58}', '\u{1A59}', ConsonantFinalAbove),
+    ('\u{1A5A}', '\u{1A5A}', ConsonantMedialAbove),
+    ('\u{1A5B}', '\u{1A5E}', ConsonantSubjoined),
+    ('\u{1A60}', '\u{1A60}', Sakot),
+    ('\u{1A61}', '\u{1A61}', VowelPost),
+    ('\u{1A62}', '\u{1A62}', VowelAbove),
+    ('\u{1A63}', '\u{1A64}', VowelPost),
+    ('\u{1A65}', '\u{1A68}', VowelAbove),
+    ('\u{1A69}', '\u{1A6A}', VowelBelow),
+    ('\u{1A6B}', '\u{1A6B}', VowelAbove),
+    ('\u{1A6C}', '\u{1A6C}', VowelBelow),
+    ('\u{1A6D}', '\u{1A6D}', VowelPost),
+    ('\u{1A6E}', '\u{1A72}', VowelPre),
+    ('\u{1A73}', '\u{1A73}', VowelAbove),
+    ('\u{1A74}', '\u{1A79}', VowelModifierAbove),
+    ('\u{1A7A}', '\u{1A7A}', VowelAbove),
+    ('\u{1A7B}', '\u{1A7C}', VowelModifierAbove),
+    ('\u{1A7F}', '\u{1A7F}', VowelModifierBelow),
+    ('\u{1A80}', '\u{1A89}', Base),
+    ('\u{1A90}', '\u{1A99}', Base),
+    ('\u{1B00}', '\u{1B02}', VowelModifierAbove),
+    ('\u{1B03}', '\u{1B03}', ConsonantFinalAbove),
+    ('\u{1B04}', '\u{1B04}', VowelModifierPost),
+    ('\u{1B05}', '\u{1B33}', Base),
+    ('\u{1B34}', '\u{1B34}', ConsonantModifierAbove),
+    ('\u{1B35}', '\u{1B35}', VowelPost),
+    ('\u{1B36}', '\u{1B37}', VowelAbove),
+    ('\u{1B38}', '\u{1B3B}', VowelBelow),
+    ('\u{1B3C}', '\u{1B3D}', VowelAbove),
+    ('\u{1B3E}', '\u{1B41}', VowelPre),
+    ('\u{1B42}', '\u{1B43}', VowelAbove),
+    ('\u{1B44}', '\u{1B44}', Halant),
+    ('\u{1B45}', '\u{1B4C}', Base),
+    ('\u{1B50}', '\u{1B59}', Base),
+    ('\u{1B6B}', '\u{1B6B}', SymbolModifierAbove),
+    ('\u{1B6C}', '\u{1B6C}', SymbolModifierBelow),
+    ('\u{1B6D}', '\u{1B73}', SymbolModifierAbove),
+    ('\u{1B80}', '\u{1B80}', VowelModifierAbove),
+    ('\u{1B81}', '\u{1B81}', ConsonantFinalAbove),
+    ('\u{1B82}', '\u{1B82}', VowelModifierPost),
+    ('\u{1B83}', '\u{1BA0}', Base),
+    ('\u{1BA1}', '\u{1BA3}', ConsonantSubjoined),
+    ('\u{1BA4}', '\u{1BA4}', VowelAbove),
+    ('\u{1BA5}', '\u{1BA5}', VowelBelow),
+    ('\u{1BA6}', '\u{1BA6}', VowelPre),
+    ('\u{1BA7}', '\u{1BA7}', VowelPost),
+    ('\u{1BA8}', '\u{1BA9}', VowelAbove),
+    ('\u{1BAA}', '\u{1BAA}', VowelPost),
+    ('\u{1BAB}', '\u{1BAB}', InvisibleStacker),
+    ('\u{1BAC}', '\u{1BAD}', ConsonantSubjoined),
+    ('\u{1BAE}', '\u{1BE5}', Base),
+    ('\u{1BE6}', '\u{1BE6}', ConsonantModifierAbove),
+    ('\u{1BE7}', '\u{1BE7}', VowelPost),
+    ('\u{1BE8}', '\u{1BE9}', VowelAbove),
+    ('\u{1BEA}', '\u{1BEC}', VowelPost),
+    ('\u{1BED}', '\u{1BED}', VowelAbove),
+    ('\u{1BEE}', '\u{1BEE}', VowelPost),
+    ('\u{1BEF}', '\u{1BEF}', VowelAbove),
+    ('\u{1BF0}', '\u{1BF1}', ConsonantFinalAbove),
+    ('\u{1BF2}', '\u{1BF3}', VowelPost),
+    ('\u{1C00}', '\u{1C23}', Base),
+    ('\u{1C24}', '\u{1C25}', ConsonantSubjoined),
+    ('\u{1C26}', '\u{1C26}', VowelPost),
+    ('\u{1C27}', '\u{1C29}', VowelPre),
+    ('\u{1C2A}', '\u{1C2B}', VowelPost),
+    ('\u{1C2C}', '\u{1C2C}', VowelBelow),
+    ('\u{1C2D}', '\u{1C33}', ConsonantFinalAbove),
+    ('\u{1C34}', '\u{1C35}', VowelModifierPre),
+    ('\u{1C36}', '\u{1C36}', FinalModifierAbove),
+    ('\u{1C37}', '\u{1C37}', ConsonantModifierBelow),
+    ('\u{1C40}', '\u{1C49}', Base),
+    ('\u{1C4D}', '\u{1C4F}', Base),
+    ('\u{1CD0}', '\u{1CD2}', VowelModifierAbove),
+    ('\u{1CD4}', '\u{1CD9}', VowelModifierBelow),
+    ('\u{1CDA}', '\u{1CDB}', VowelModifierAbove),
+    ('\u{1CDC}', '\u{1CDF}', VowelModifierBelow),
+    ('\u{1CE0}', '\u{1CE0}', VowelModifierAbove),
+    ('\u{1CE1}', '\u{1CE1}', VowelModifierPost),
+    ('\u{1CF4}', '\u{1CF4}', VowelModifierAbove),
+    ('\u{1CF5}', '\u{1CF6}', ConsonantWithStacker),
+    ('\u{1CF7}', '\u{1CF7}', VowelModifierPost),
+    ('\u{1CF8}', '\u{1CF9}', VowelModifierAbove),
+    ('\u{1CFA}', '\u{1CFA}', BaseOther),
+    ('\u{1DFB}', '\u{1DFB}', FinalModifierAbove),
+    ('\u{200C}', '\u{200D}', Ignorable),
+    ('\u{2010}', '\u{2015}', BaseOther),
+    ('\u{2022}', '\u{2022}', BaseOther),
+    ('\u{2074}', '\u{2074}', FinalModifierPost),
+    ('\u{2082}', '\u{2084}', FinalModifierPost),
+    ('\u{20F0}', '\u{20F0}', VowelModifierAbove),
+    ('\u{25CC}', '\u{25CC}', BaseOther),
+    ('\u{25FB}', '\u{25FE}', BaseOther),
+    ('\u{A800}', '\u{A801}', Base),
+    ('\u{A802}', '\u{A802}', VowelAbove),
+    ('\u{A803}', '\u{A805}', Base),
+    ('\u{A806}', '\u{A806}', Halant),
+    ('\u{A807}', '\u{A80A}', Base),
+    ('\u{A80B}', '\u{A80B}', VowelModifierAbove),
+    ('\u{A80C}', '\u{A822}', Base),
+    ('\u{A823}', '\u{A824}', VowelPost),
+    ('\u{A825}', '\u{A825}', VowelBelow),
+    ('\u{A826}', '\u{A826}', VowelAbove),
+    ('\u{A827}', '\u{A827}', VowelPost),
+    ('\u{A82C}', '\u{A82C}', VowelBelow),
+    ('\u{A840}', '\u{A873}', Base),
+    ('\u{A880}', '\u{A881}', VowelModifierPost),
+    ('\u{A882}', '\u{A8B3}', Base),
+    ('\u{A8B4}', '\u{A8B4}', ConsonantMedialPost),
+    ('\u{A8B5}', '\u{A8C3}', VowelPost),
+    ('\u{A8C4}', '\u{A8C4}', Halant),
+    ('\u{A8C5}', '\u{A8C5}', VowelModifierAbove),
+    ('\u{A8D0}', '\u{A8D9}', Base),
+    ('\u{A900}', '\u{A925}', Base),
+    ('\u{A926}', '\u{A92A}', VowelAbove),
+    ('\u{A92B}', '\u{A92D}', VowelModifierBelow),
+    ('\u{A930}', '\u{A946}', Base),
+    ('\u{A947}', '\u{A949}', VowelBelow),
+    ('\u{A94A}', '\u{A94A}', VowelAbove),
+    ('\u{A94B}', '\u{A94E}', VowelBelow),
+    ('\u{A94F}', '\u{A951}', ConsonantFinalAbove),
+    ('\u{A952}', '\u{A952}', ConsonantFinalPost),
+    ('\u{A953}', '\u{A953}', VowelPost),
+    ('\u{A980}', '\u{A981}', VowelModifierAbove),
+    ('\u{A982}', '\u{A982}', ConsonantFinalAbove),
+    ('\u{A983}', '\u{A983}', VowelModifierPost),
+    ('\u{A984}', '\u{A9B2}', Base),
+    ('\u{A9B3}', '\u{A9B3}', ConsonantModifierAbove),
+    ('\u{A9B4}', '\u{A9B5}', VowelPost),
+    ('\u{A9B6}', '\u{A9B7}', VowelAbove),
+    ('\u{A9B8}', '\u{A9B9}', VowelBelow),
+    ('\u{A9BA}', '\u{A9BB}', VowelPre),
+    ('\u{A9BC}', '\u{A9BC}', VowelAbove),
+    ('\u{A9BD}', '\u{A9BD}', ConsonantMedialBelow),
+    ('\u{A9BE}', '\u{A9BE}', ConsonantMedialPost),
+    ('\u{A9BF}', '\u{A9BF}', ConsonantMedialBelow),
+    ('\u{A9C0}', '\u{A9C0}', Halant),
+    ('\u{A9D0}', '\u{A9D9}', Base),
+    ('\u{AA00}', '\u{AA28}', Base),
+    ('\u{AA29}', '\u{AA29}', VowelModifierAbove),
+    ('\u{AA2A}', '\u{AA2C}', VowelAbove),
+    ('\u{AA2D}', '\u{AA2D}', VowelBelow),
+    ('\u{AA2E}', '\u{AA2E}', VowelAbove),
+    ('\u{AA2F}', '\u{AA30}', VowelPre),
+    ('\u{AA31}', '\u{AA31}', VowelAbove),
+    ('\u{AA32}', '\u{AA32}', VowelBelow),
+    ('\u{AA33}', '\u{AA33}', ConsonantMedialPost),
+    ('\u{AA34}', '\u{AA34}', ConsonantMedialPre),
+    ('\u{AA35}', '\u{AA35}', ConsonantMedialAbove),
+    ('\u{AA36}', '\u{AA36}', ConsonantMedialBelow),
+    ('\u{AA40}', '\u{AA42}', Base),
+    ('\u{AA43}', '\u{AA43}', ConsonantFinalAbove),
+    ('\u{AA44}', '\u{AA4B}', Base),
+    ('\u{AA4C}', '\u{AA4C}', ConsonantFinalAbove),
+    ('\u{AA4D}', '\u{AA4D}', ConsonantFinalPost),
+    ('\u{AA50}', '\u{AA59}', Base),
+    ('\u{AA80}', '\u{AAAF}', Base),
+    ('\u{AAB0}', '\u{AAB0}', VowelAbove),
+    ('\u{AAB1}', '\u{AAB1}', Base),
+    ('\u{AAB2}', '\u{AAB3}', VowelAbove),
+    ('\u{AAB4}', '\u{AAB4}', VowelBelow),
+    ('\u{AAB5}', '\u{AAB6}', Base),
+    ('\u{AAB7}', '\u{AAB8}', VowelAbove),
+    ('\u{AAB9}', '\u{AABD}', Base),
+    ('\u{AABE}', '\u{AABE}', VowelAbove),
+    ('\u{AABF}', '\u{AABF}', VowelModifierAbove),
+    ('\u{AAC0}', '\u{AAC0}', Base),
+    ('\u{AAC1}', '\u{AAC1}', VowelModifierAbove),
+    ('\u{AAC2}', '\u{AAC2}', Base),
+    ('\u{AAE0}', '\u{AAEA}', Base),
+    ('\u{AAEB}', '\u{AAEB}', VowelPre),
+    ('\u{AAEC}', '\u{AAEC}', VowelBelow),
+    ('\u{AAED}', '\u{AAED}', VowelAbove),
+    ('\u{AAEE}', '\u{AAEE}', VowelPre),
+    ('\u{AAEF}', '\u{AAEF}', VowelPost),
+    ('\u{AAF5}', '\u{AAF5}', VowelModifierPost),
+    ('\u{AAF6}', '\u{AAF6}', InvisibleStacker),
+    ('\u{ABC0}', '\u{ABE2}', Base),
+    ('\u{ABE3}', '\u{ABE4}', VowelPost),
+    ('\u{ABE5}', '\u{ABE5}', VowelAbove),
+    ('\u{ABE6}', '\u{ABE7}', VowelPost),
+    ('\u{ABE8}', '\u{ABE8}', VowelBelow),
+    ('\u{ABE9}', '\u{ABEA}', VowelPost),
+    ('\u{ABEC}', '\u{ABEC}', VowelModifierPost),
+    ('\u{ABED}', '\u{ABED}', VowelBelow),
+    ('\u{ABF0}', '\u{ABF9}', Base),
+    ('\u{FE00}', '\u{FE0F}', Ignorable),
+    ('\u{10A00}', '\u{10A00}', Base),
+    ('\u{10A01}', '\u{10A03}', VowelBelow),
+    ('\u{10A05}', '\u{10A05}', VowelAbove),
+    ('\u{10A06}', '\u{10A06}', VowelBelow),
+    ('\u{10A0C}', '\u{10A0C}', VowelPost),
+    ('\u{10A0D}', '\u{10A0E}', VowelModifierBelow),
+    ('\u{10A0F}', '\u{10A0F}', VowelModifierAbove),
+    ('\u{10A10}', '\u{10A13}', Base),
+    ('\u{10A15}', '\u{10A17}', Base),
+    ('\u{10A19}', '\u{10A35}', Base),
+    ('\u{10A38}', '\u{10A38}', ConsonantModifierAbove),
+    ('\u{10A39}', '\u{10A3A}', ConsonantModifierBelow),
+    ('\u{10A3F}', '\u{10A3F}', InvisibleStacker),
+    ('\u{10A40}', '\u{10A48}', Base),
+    ('\u{10AC0}', '\u{10AC5}', Base),
+    ('\u{10AC7}', '\u{10AC7}', Base),
+    ('\u{10AC9}', '\u{10ACA}', Base),
+    ('\u{10ACD}', '\u{10AE1}', Base),
+    ('\u{10AE4}', '\u{10AE4}', Base),
+    ('\u{10AE5}', '\u{10AE6}', ConsonantModifierBelow),
+    ('\u{10AEB}', '\u{10AEF}', Base),
+    ('\u{10B80}', '\u{10B91}', Base),
+    ('\u{10BA9}', '\u{10BAE}', Base),
+    ('\u{10D00}', '\u{10D23}', Base),
+    ('\u{10D24}', '\u{10D26}', VowelModifierAbove),
+    ('\u{10D27}', '\u{10D27}', ConsonantModifierAbove),
+    ('\u{10D30}', '\u{10D39}', Base),
+    ('\u{10EAB}', '\u{10EAC}', VowelAbove),
+    ('\u{10F30}', '\u{10F44}', Base),
+    ('\u{10F46}', '\u{10F50}', VowelModifierBelow),
+    ('\u{10F51}', '\u{10F54}', Base),
+    ('\u{10F70}', '\u{10F81}', Base),
+    ('\u{10FB0}', '\u{10FB0}', Base),
+    ('\u{10FB2}', '\u{10FB6}', Base),
+    ('\u{10FB8}', '\u{10FBF}', Base),
+    ('\u{10FC1}', '\u{10FC4}', Base),
+    ('\u{10FC9}', '\u{10FCB}', Base),
+    ('\u{11000}', '\u{11000}', VowelModifierPost),
+    ('\u{11001}', '\u{11001}', VowelModifierAbove),
+    ('\u{11002}', '\u{11002}', VowelModifierPost),
+    ('\u{11003}', '\u{11004}', ConsonantWithStacker),
+    ('\u{11005}', '\u{11037}', Base),
+    ('\u{11038}', '\u{1103B}', VowelAbove),
+    ('\u{1103C}', '\u{11041}', VowelBelow),
+    ('\u{11042}', '\u{11045}', VowelAbove),
+    ('\u{11046}', '\u{11046}', HalantOrVowelModifier),
+    ('\u{11052}', '\u{11065}', BaseNumber),
+    ('\u{11066}', '\u{1106F}', Base),
+    ('\u{11070}', '\u{11070}', VowelAbove),
+    ('\u{11071}', '\u{11072}', Base),
+    ('\u{11073}', '\u{11074}', VowelAbove),
+    ('\u{11075}', '\u{11075}', Base),
+    ('\u{1107F}', '\u{1107F}', HalantNumber),
+    ('\u{11080}', '\u{11081}', VowelModifierAbove),
+    ('\u{11082}', '\u{11082}', VowelModifierPost),
+    ('\u{11083}', '\u{110AF}', Base),
+    ('\u{110B0}', '\u{110B0}', VowelPost),
+    ('\u{110B1}', '\u{110B1}', VowelPre),
+    ('\u{110B2}', '\u{110B2}', VowelPost),
+    ('\u{110B3}', '\u{110B4}', VowelBelow),
+    ('\u{110B5}', '\u{110B6}', VowelAbove),
+    ('\u{110B7}', '\u{110B8}', VowelPost),
+    ('\u{110B9}', '\u{110B9}', Halant),
+    ('\u{110BA}', '\u{110BA}', ConsonantModifierBelow),
+    ('\u{110C2}', '\u{110C2}', VowelBelow),
+    ('\u{11100}', '\u{11102}', VowelModifierAbove),
+    ('\u{11103}', '\u{11126}', Base),
+    ('\u{11127}', '\u{1112B}', VowelBelow),
+    ('\u{1112C}', '\u{1112C}', VowelPre),
+    ('\u{1112D}', '\u{1112D}', VowelBelow),
+    ('\u{1112E}', '\u{1112F}', VowelAbove),
+    ('\u{11130}', '\u{11132}', VowelBelow),
+    ('\u{11133}', '\u{11133}', InvisibleStacker),
+    ('\u{11134}', '\u{11134}', ConsonantModifierAbove),
+    ('\u{11136}', '\u{1113F}', Base),
+    ('\u{11144}', '\u{11144}', Base),
+    ('\u{11145}', '\u{11146}', VowelPost),
+    ('\u{11147}', '\u{11147}', Base),
+    ('\u{11150}', '\u{11172}', Base),
+    ('\u{11173}', '\u{11173}', ConsonantModifierBelow),
+    ('\u{11180}', '\u{11181}', VowelModifierAbove),
+    ('\u{11182}', '\u{11182}', VowelModifierPost),
+    ('\u{11183}', '\u{111B2}', Base),
+    ('\u{111B3}', '\u{111B3}', VowelPost),
+    ('\u{111B4}', '\u{111B4}', VowelPre),
+    ('\u{111B5}', '\u{111B5}', VowelPost),
+    ('\u{111B6}', '\u{111BB}', VowelBelow),
+    ('\u{111BC}', '\u{111BF}', VowelAbove),
+    ('\u{111C0}', '\u{111C0}', Halant),
+    ('\u{111C1}', '\u{111C1}', Base),
+    ('\u{111C2}', '\u{111C3}', Repha),
+    ('\u{111C9}', '\u{111C9}', FinalModifierBelow),
+    ('\u{111CA}', '\u{111CA}', ConsonantModifierBelow),
+    ('\u{111CB}', '\u{111CB}', VowelAbove),
+    ('\u{111CC}', '\u{111CC}', VowelBelow),
+    ('\u{111CE}', '\u{111CE}', VowelPre),
+    ('\u{111CF}', '\u{111CF}', VowelModifierAbove),
+    ('\u{111D0}', '\u{111DA}', Base),
+    ('\u{11200}', '\u{11211}', Base),
+    ('\u{11213}', '\u{1122B}', Base),
+    ('\u{1122C}', '\u{1122E}', VowelPost),
+    ('\u{1122F}', '\u{1122F}', VowelBelow),
+    ('\u{11230}', '\u{11233}', VowelAbove),
+    ('\u{11234}', '\u{11234}', VowelModifierAbove),
+    ('\u{11235}', '\u{11235}', Halant),
+    ('\u{11236}', '\u{11237}', ConsonantModifierAbove),
+    ('\u{1123E}', '\u{1123E}', VowelModifierAbove),
+    ('\u{1123F}', '\u{11240}', Base),
+    ('\u{11241}', '\u{11241}', VowelBelow),
+    ('\u{11280}', '\u{11286}', Base),
+    ('\u{11288}', '\u{11288}', Base),
+    ('\u{1128A}', '\u{1128D}', Base),
+    ('\u{1128F}', '\u{1129D}', Base),
+    ('\u{1129F}', '\u{112A8}', Base),
+    ('\u{112B0}', '\u{112DE}', Base),
+    ('\u{112DF}', '\u{112DF}', VowelModifierAbove),
+    ('\u{112E0}', '\u{112E0}', VowelPost),
+    ('\u{112E1}', '\u{112E1}', VowelPre),
+    ('\u{112E2}', '\u{112E2}', VowelPost),
+    ('\u{112E3}', '\u{112E4}', VowelBelow),
+    ('\u{112E5}', '\u{112E8}', VowelAbove),
+    ('\u{112E9}', '\u{112E9}', ConsonantModifierBelow),
+    ('\u{112EA}', '\u{112EA}', VowelBelow),
+    ('\u{112F0}', '\u{112F9}', Base),
+    ('\u{11300}', '\u{11301}', VowelModifierAbove),
+    ('\u{11302}', '\u{11303}', VowelModifierPost),
+    ('\u{11305}', '\u{1130C}', Base),
+    ('\u{1130F}', '\u{11310}', Base),
+    ('\u{11313}', '\u{11328}', Base),
+    ('\u{1132A}', '\u{11330}', Base),
+    ('\u{11332}', '\u{11333}', Base),
+    ('\u{11335}', '\u{11339}', Base),
+    ('\u{1133B}', '\u{1133C}', ConsonantModifierBelow),
+    ('\u{1133D}', '\u{1133D}', Base),
+    ('\u{1133E}', '\u{1133F}', VowelPost),
+    ('\u{11340}', '\u{11340}', VowelAbove),
+    ('\u{11341}', '\u{11344}', VowelPost),
+    ('\u{11347}', '\u{11348}', VowelPre),
+    ('\u{1134B}', '\u{1134C}', VowelPre),
+    ('\u{1134D}', '\u{1134D}', HalantOrVowelModifier),
+    ('\u{11357}', '\u{11357}', VowelPost),
+    ('\u{1135E}', '\u{11361}', Base),
+    ('\u{11362}', '\u{11363}', VowelPost),
+    ('\u{11366}', '\u{1136C}', VowelModifierAbove),
+    ('\u{11370}', '\u{11374}', VowelModifierAbove),
+    ('\u{11400}', '\u{11434}', Base),
+    ('\u{11435}', '\u{11435}', VowelPost),
+    ('\u{11436}', '\u{11436}', VowelPre),
+    ('\u{11437}', '\u{11437}', VowelPost),
+    ('\u{11438}', '\u{1143D}', VowelBelow),
+    ('\u{1143E}', '\u{1143F}', VowelAbove),
+    ('\u{11440}', '\u{11441}', VowelPost),
+    ('\u{11442}', '\u{11442}', Halant),
+    ('\u{11443}', '\u{11444}', VowelModifierAbove),
+    ('\u{11445}', '\u{11445}', VowelModifierPost),
+    ('\u{11446}', '\u{11446}', ConsonantModifierBelow),
+    ('\u{11447}', '\u{11447}', Base),
+    ('\u{11450}', '\u{11459}', Base),
+    ('\u{1145E}', '\u{1145E}', FinalModifierAbove),
+    ('\u{1145F}', '\u{1145F}', Base),
+    ('\u{11460}', '\u{11461}', ConsonantWithStacker),
+    ('\u{11481}', '\u{114AF}', Base),
+    ('\u{114B0}', '\u{114B0}', VowelPost),
+    ('\u{114B1}', '\u{114B1}', VowelPre),
+    ('\u{114B2}', '\u{114B2}', VowelPost),
+    ('\u{114B3}', '\u{114B8}', VowelBelow),
+    ('\u{114B9}', '\u{114B9}', VowelPre),
+    ('\u{114BA}', '\u{114BA}', VowelAbove),
+    ('\u{114BB}', '\u{114BC}', VowelPre),
+    ('\u{114BD}', '\u{114BD}', VowelPost),
+    ('\u{114BE}', '\u{114BE}', VowelPre),
+    ('\u{114BF}', '\u{114C0}', VowelModifierAbove),
+    ('\u{114C1}', '\u{114C1}', VowelModifierPost),
+    ('\u{114C2}', '\u{114C2}', Halant),
+    ('\u{114C3}', '\u{114C3}', ConsonantModifierBelow),
+    ('\u{114C4}', '\u{114C4}', Base),
+    ('\u{114D0}', '\u{114D9}', Base),
+    ('\u{11580}', '\u{115AE}', Base),
+    ('\u{115AF}', '\u{115AF}', VowelPost),
+    ('\u{115B0}', '\u{115B0}', VowelPre),
+    ('\u{115B1}', '\u{115B1}', VowelPost),
+    ('\u{115B2}', '\u{115B5}', VowelBelow),
+    ('\u{115B8}', '\u{115BB}', VowelPre),
+    ('\u{115BC}', '\u{115BD}', VowelModifierAbove),
+    ('\u{115BE}', '\u{115BE}', VowelModifierPost),
+    ('\u{115BF}', '\u{115BF}', Halant),
+    ('\u{115C0}', '\u{115C0}', ConsonantModifierBelow),
+    ('\u{115D8}', '\u{115DB}', Base),
+    ('\u{115DC}', '\u{115DD}', VowelBelow),
+    ('\u{11600}', '\u{1162F}', Base),
+    ('\u{11630}', '\u{11632}', VowelPost),
+    ('\u{11633}', '\u{11638}', VowelBelow),
+    ('\u{11639}', '\u{1163A}', VowelAbove),
+    ('\u{1163B}', '\u{1163C}', VowelPost),
+    ('\u{1163D}', '\u{1163D}', VowelModifierAbove),
+    ('\u{1163E}', '\u{1163E}', VowelModifierPost),
+    ('\u{1163F}', '\u{1163F}', Halant),
+    ('\u{11640}', '\u{11640}', VowelAbove),
+    ('\u{11650}', '\u{11659}', Base),
+    ('\u{11680}', '\u{116AA}', Base),
+    ('\u{116AB}', '\u{116AB}', VowelModifierAbove),
+    ('\u{116AC}', '\u{116AC}', VowelModifierPost),
+    ('\u{116AD}', '\u{116AD}', VowelAbove),
+    ('\u{116AE}', '\u{116AE}', VowelPre),
+    ('\u{116AF}', '\u{116AF}', VowelPost),
+    ('\u{116B0}', '\u{116B1}', VowelBelow),
+    ('\u{116B2}', '\u{116B5}', VowelAbove),
+    ('\u{116B6}', '\u{116B6}', Halant),
+    ('\u{116B7}', '\u{116B7}', ConsonantModifierBelow),
+    ('\u{116B8}', '\u{116B8}', Base),
+    ('\u{116C0}', '\u{116C9}', Base),
+    ('\u{11700}', '\u{1171A}', Base),
+    ('\u{1171D}', '\u{1171D}', ConsonantMedialBelow),
+    ('\u{1171E}', '\u{1171E}', ConsonantMedialPre),
+    ('\u{1171F}', '\u{1171F}', ConsonantMedialAbove),
+    ('\u{11720}', '\u{11721}', VowelPost),
+    ('\u{11722}', '\u{11723}', VowelAbove),
+    ('\u{11724}', '\u{11725}', VowelBelow),
+    ('\u{11726}', '\u{11726}', VowelPre),
+    ('\u{11727}', '\u{11727}', VowelAbove),
+    ('\u{11728}', '\u{11728}', VowelBelow),
+    ('\u{11729}', '\u{1172B}', VowelAbove),
+    ('\u{11730}', '\u{1173B}', Base),
+    ('\u{11740}', '\u{11746}', Base),
+    ('\u{11800}', '\u{1182B}', Base),
+    ('\u{1182C}', '\u{1182C}', VowelPost),
+    ('\u{1182D}', '\u{1182D}', VowelPre),
+    ('\u{1182E}', '\u{1182E}', VowelPost),
+    ('\u{1182F}', '\u{11832}', VowelBelow),
+    ('\u{11833}', '\u{11836}', VowelAbove),
+    ('\u{11837}', '\u{11837}', VowelModifierAbove),
+    ('\u{11838}', '\u{11838}', VowelModifierPost),
+    ('\u{11839}', '\u{11839}', Halant),
+    ('\u{1183A}', '\u{1183A}', ConsonantModifierBelow),
+    ('\u{11900}', '\u{11906}', Base),
+    ('\u{11909}', '\u{11909}', Base),
+    ('\u{1190C}', '\u{11913}', Base),
+    ('\u{11915}', '\u{11916}', Base),
+    ('\u{11918}', '\u{1192F}', Base),
+    ('\u{11930}', '\u{11934}', VowelPost),
+    ('\u{11935}', '\u{11935}', VowelPre),
+    ('\u{11937}', '\u{11938}', VowelPre),
+    ('\u{1193B}', '\u{1193C}', VowelModifierAbove),
+    ('\u{1193D}', '\u{1193D}', VowelPost),
+    ('\u{1193E}', '\u{1193E}', InvisibleStacker),
+    ('\u{1193F}', '\u{1193F}', Repha),
+    ('\u{11940}', '\u{11940}', ConsonantMedialPost),
+    ('\u{11941}', '\u{11941}', Repha),
+    ('\u{11942}', '\u{11942}', ConsonantMedialPost),
+    ('\u{11943}', '\u{11943}', ConsonantModifierBelow),
+    ('\u{11950}', '\u{11959}', Base),
+    ('\u{119A0}', '\u{119A7}', Base),
+    ('\u{119AA}', '\u{119D0}', Base),
+    ('\u{119D1}', '\u{119D1}', VowelPost),
+    ('\u{119D2}', '\u{119D2}', VowelPre),
+    ('\u{119D3}', '\u{119D3}', VowelPost),
+    ('\u{119D4}', '\u{119D7}', VowelBelow),
+    ('\u{119DA}', '\u{119DB}', VowelAbove),
+    ('\u{119DC}', '\u{119DD}', VowelPost),
+    ('\u{119DE}', '\u{119DF}', VowelModifierPost),
+    ('\u{119E0}', '\u{119E0}', Halant),
+    ('\u{119E1}', '\u{119E1}', Base),
+    ('\u{119E4}', '\u{119E4}', VowelPre),
+    ('\u{11A00}', '\u{11A00}', Base),
+    ('\u{11A01}', '\u{11A01}', VowelAbove),
+    ('\u{11A02}', '\u{11A03}', VowelBelow),
+    ('\u{11A04}', '\u{11A09}', VowelAbove),
+    ('\u{11A0A}', '\u{11A0A}', VowelBelow),
+    ('\u{11A0B}', '\u{11A32}', Base),
+    ('\u{11A33}', '\u{11A33}', FinalModifierBelow),
+    ('\u{11A34}', '\u{11A34}', VowelBelow),
+    ('\u{11A35}', '\u{11A38}', VowelModifierAbove),
+    ('\u{11A39}', '\u{11A39}', VowelModifierPost),
+    ('\u{11A3A}', '\u{11A3A}', Repha),
+    ('\u{11A3B}', '\u{11A3E}', ConsonantMedialBelow),
+    ('\u{11A3F}', '\u{11A3F}', BaseOther),
+    ('\u{11A45}', '\u{11A45}', BaseOther),
+    ('\u{11A47}', '\u{11A47}', InvisibleStacker),
+    ('\u{11A50}', '\u{11A50}', Base),
+    ('\u{11A51}', '\u{11A51}', VowelAbove),
+    ('\u{11A52}', '\u{11A53}', VowelBelow),
+    ('\u{11A54}', '\u{11A56}', VowelAbove),
+    ('\u{11A57}', '\u{11A58}', VowelPost),
+    ('\u{11A59}', '\u{11A5B}', VowelBelow),
+    ('\u{11A5C}', '\u{11A83}', Base),
+    ('\u{11A84}', '\u{11A89}', Repha),
+    ('\u{11A8A}', '\u{11A95}', ConsonantFinalBelow),
+    ('\u{11A96}', '\u{11A96}', VowelModifierAbove),
+    ('\u{11A97}', '\u{11A97}', VowelModifierPost),
+    ('\u{11A98}', '\u{11A98}', ConsonantModifierAbove),
+    ('\u{11A99}', '\u{11A99}', InvisibleStacker),
+    ('\u{11A9D}', '\u{11A9D}', Base),
+    ('\u{11C00}', '\u{11C08}', Base),
+    ('\u{11C0A}', '\u{11C2E}', Base),
+    ('\u{11C2F}', '\u{11C2F}', VowelPost),
+    ('\u{11C30}', '\u{11C31}', VowelAbove),
+    ('\u{11C32}', '\u{11C36}', VowelBelow),
+    ('\u{11C38}', '\u{11C3B}', VowelAbove),
+    ('\u{11C3C}', '\u{11C3D}', VowelModifierAbove),
+    ('\u{11C3E}', '\u{11C3E}', VowelModifierPost),
+    ('\u{11C3F}', '\u{11C3F}', Halant),
+    ('\u{11C40}', '\u{11C40}', Base),
+    ('\u{11C50}', '\u{11C6C}', Base),
+    ('\u{11C72}', '\u{11C8F}', Base),
+    ('\u{11C92}', '\u{11CA7}', ConsonantSubjoined),
+    ('\u{11CA9}', '\u{11CAF}', ConsonantSubjoined),
+    ('\u{11CB0}', '\u{11CB0}', VowelBelow),
+    ('\u{11CB1}', '\u{11CB1}', VowelPre),
+    ('\u{11CB2}', '\u{11CB2}', VowelBelow),
+    ('\u{11CB3}', '\u{11CB3}', VowelAbove),
+    ('\u{11CB4}', '\u{11CB4}', VowelPost),
+    ('\u{11CB5}', '\u{11CB6}', VowelModifierAbove),
+    ('\u{11D00}', '\u{11D06}', Base),
+    ('\u{11D08}', '\u{11D09}', Base),
+    ('\u{11D0B}', '\u{11D30}', Base),
+    ('\u{11D31}', '\u{11D35}', VowelAbove),
+    ('\u{11D36}', '\u{11D36}', VowelBelow),
+    ('\u{11D3A}', '\u{11D3A}', VowelAbove),
+    ('\u{11D3C}', '\u{11D3D}', VowelAbove),
+    ('\u{11D3F}', '\u{11D3F}', VowelAbove),
+    ('\u{11D40}', '\u{11D41}', VowelModifierAbove),
+    ('\u{11D42}', '\u{11D42}', ConsonantModifierBelow),
+    ('\u{11D43}', '\u{11D43}', VowelAbove),
+    ('\u{11D44}', '\u{11D44}', VowelBelow),
+    ('\u{11D45}', '\u{11D45}', InvisibleStacker),
+    ('\u{11D46}', '\u{11D46}', Repha),
+    ('\u{11D47}', '\u{11D47}', ConsonantMedialBelow),
+    ('\u{11D50}', '\u{11D59}', Base),
+    ('\u{11D60}', '\u{11D65}', Base),
+    ('\u{11D67}', '\u{11D68}', Base),
+    ('\u{11D6A}', '\u{11D89}', Base),
+    ('\u{11D8A}', '\u{11D8E}', VowelPost),
+    ('\u{11D90}', '\u{11D91}', VowelAbove),
+    ('\u{11D93}', '\u{11D94}', VowelPost),
+    ('\u{11D95}', '\u{11D95}', VowelModifierAbove),
+    ('\u{11D96}', '\u{11D96}', VowelModifierPost),
+    ('\u{11D97}', '\u{11D97}', InvisibleStacker),
+    ('\u{11DA0}', '\u{11DA9}', Base),
+    ('\u{11EE0}', '\u{11EF1}', Base),
+    ('\u{11EF2}', '\u{11EF2}', BaseOther),
+    ('\u{11EF3}', '\u{11EF3}', VowelAbove),
+    ('\u{11EF4}', '\u{11EF4}', VowelBelow),
+    ('\u{11EF5}', '\u{11EF5}', VowelPre),
+    ('\u{11EF6}', '\u{11EF6}', VowelPost),
+    ('\u{11F00}', '\u{11F01}', VowelModifierAbove),
+    ('\u{11F02}', '\u{11F02}', Repha),
+    ('\u{11F03}', '\u{11F03}', VowelModifierPost),
+    ('\u{11F04}', '\u{11F10}', Base),
+    ('\u{11F12}', '\u{11F33}', Base),
+    ('\u{11F34}', '\u{11F35}', VowelPost),
+    ('\u{11F36}', '\u{11F37}', VowelAbove),
+    ('\u{11F38}', '\u{11F3A}', VowelBelow),
+    ('\u{11F3E}', '\u{11F3F}', VowelPre),
+    ('\u{11F40}', '\u{11F40}', VowelAbove),
+    ('\u{11F41}', '\u{11F41}', VowelPost),
+    ('\u{11F42}', '\u{11F42}', InvisibleStacker),
+    ('\u{11F50}', '\u{11F59}', Base),
+    ('\u{1E900}', '\u{1E943}', Base),
+    ('\u{1E944}', '\u{1E94A}', ConsonantModifierAbove),
+    ('\u{1E94B}', '\u{1E94B}', Base),
+    ('\u{1E950}', '\u{1E959}', Base),
+    ('\u{E0100}', '\u{E01EF}', Ignorable),
+];
@@ -8,19 +8,31 @@ use std::fmt;
 use std::num::Wrapping;
 
 use itertools::Itertools;
+use log::warn;
+use rustc_hash::FxHashSet;
 
 use crate::binary::read::{ReadArrayCow, ReadScope};
 use crate::binary::write::{Placeholder, WriteBinary};
 use crate::binary::write::{WriteBinaryDep, WriteBuffer, WriteContext};
 use crate::binary::{long_align, U16Be, U32Be};
 use crate::cff::cff2::{OutputFormat, CFF2};
-use crate::cff::{CFFError, SubsetCFF, CFF};
+use crate::cff::{CFFError, CFF};
 use crate::error::{ParseError, ReadWriteError, WriteError};
+use crate::font::read_cmap_subtable;
+use crate::gsub::{FeatureMask, Features};
+use crate::layout::subset::{gsub_closure, subset_layout_tables};
 use crate::post::PostTable;
 use crate::tables::cmap::subset::{CmapStrategy, CmapTarget, MappingsToKeep, NewIds, OldIds};
-use crate::tables::cmap::{owned, EncodingId, PlatformId};
+use crate::tables::cmap::{owned, Cmap, EncodingId, PlatformId};
 use crate::tables::glyf::GlyfTable;
+use crate::tables::hdmx::HdmxTable;
+use crate::tables::kern::KernTable;
 use crate::tables::loca::{self, LocaTable};
+use crate::tables::os2::Os2;
+use crate::tables::variable_fonts::gvar::GvarTable;
+use crate::tables::variable_fonts::hvar::HvarTable;
+use crate::tables::variable_fonts::vvar::VvarTable;
+use crate::tables::vorg::{VertOriginYMetrics, VorgTable};
 use crate::tables::{
     self, cmap, FontTableProvider, HeadTable, HheaTable, HmtxTable, IndexToLocFormat, MaxpTable,
     TableRecord,
@@ -86,13 +98,66 @@ struct OrderedTables {
 ///
 /// If either of these requirements are not upheld this function will return
 /// `ParseError::BadValue`.
+///
+/// The OpenType layout tables are kept so that the subset font can still be shaped. The glyphs
+/// that the `GSUB` table can substitute for the supplied glyphs are retained as well, after the
+/// supplied glyphs, so the glyph with index `i` in `glyph_ids` has id `i` in the subset font.
+/// Only the substitutions of features with a `FeatureMask` flag are followed, so alternates from
+/// features such as `aalt`, `salt`, and `ssNN` aren't retained unless they are supplied.
+/// The `GDEF`, `GSUB`, and `GPOS` tables are subset to the retained glyphs.
+///
+/// Variable TrueType and CFF2 fonts remain variable: the `fvar`, `avar`, `STAT`, and `MVAR`
+/// tables are kept, and the `gvar`, `HVAR`, and `VVAR` tables are subset to the retained glyphs.
 pub fn subset(
     provider: &impl FontTableProvider,
     glyph_ids: &[u16],
+) -> Result<Vec<u8>, SubsetError> {
+    let glyph_ids = gsub_closure(provider, glyph_ids, &Features::Mask(FeatureMask::all()))?;
+    subset_closure(provider, &glyph_ids)
+}
+
+/// Subset this font so that it only contains the glyphs needed to display `chars` with
+/// `features`.
+///
+/// The characters are mapped to glyphs with the `cmap` table, and characters that the font
+/// doesn't map are ignored. The glyphs that the `GSUB` lookups of the enabled features can
+/// substitute for them, the components of composite glyphs, and the base and accent glyphs of
+/// CFF accented characters are retained as well. As with [subset], the `GDEF`, `GSUB`, and
+/// `GPOS` tables are subset to the retained glyphs.
+pub fn subset_chars(
+    provider: &impl FontTableProvider,
+    chars: &[char],
+    features: &Features,
+) -> Result<Vec<u8>, SubsetError> {
+    let cmap_data = provider.read_table_data(tag::CMAP)?;
+    let cmap = ReadScope::new(&cmap_data).read::<Cmap<'_>>()?;
+    let (_encoding, cmap_subtable) =
+        read_cmap_subtable(&cmap)?.ok_or(ParseError::UnsuitableCmap)?;
+
+    let mut glyph_ids = vec![0];
+    let mut seen = FxHashSet::default();
+    for &ch in chars {
+        if let Some(glyph_id) = cmap_subtable.map_glyph(u32::from(ch))? {
+            if glyph_id != 0 && seen.insert(glyph_id) {
+                glyph_ids.push(glyph_id);
+            }
+        }
+    }
+    let glyph_ids = gsub_closure(provider, &glyph_ids, features)?;
+    subset_closure(provider, &glyph_ids)
+}
+
+/// Subset the font and its layout tables to `glyph_ids`, which must already include their `GSUB`
+/// closure.
+fn subset_closure(
+    provider: &impl FontTableProvider,
+    glyph_ids: &[u16],
 ) -> Result<Vec<u8>, SubsetError> {
     let mappings_to_keep = MappingsToKeep::new(provider, glyph_ids, CmapTarget::Unrestricted)?;
     if provider.has_table(tag::CFF) {
         subset_cff(provider, glyph_ids, mappings_to_keep, true)
+    } else if provider.has_table(tag::CFF2) && provider.has_table(tag::FVAR) {
+        subset_variable_cff2(provider, glyph_ids, mappings_to_keep)
     } else if provider.has_table(tag::CFF2) {
         subset_cff2(
             provider,
@@ -106,6 +171,8 @@ pub fn subset(
             provider,
             glyph_ids,
             CmapStrategy::Generate(mappings_to_keep),
+            true,
+            true,
         )
         .map_err(SubsetError::from)
     }
@@ -115,10 +182,17 @@ pub fn subset(
 ///
 /// If `mappings_to_keep` is `None` a `cmap` table in the subset font will be omitted.
 /// Otherwise it will be used to build a new `cmap` table.
+///
+/// If `include_optional_tables` is `true` the auxiliary and variation tables described in
+/// [subset_auxiliary_tables] and [subset_variation_tables] are subset and included in the subset
+/// font. If `include_layout_tables` is also `true` the `GDEF`, `GSUB`, and `GPOS` tables are
+/// subset and included too.
 fn subset_ttf(
     provider: &impl FontTableProvider,
     glyph_ids: &[u16],
     cmap_strategy: CmapStrategy,
+    include_optional_tables: bool,
+    include_layout_tables: bool,
 ) -> Result<Vec<u8>, ReadWriteError> {
     let head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
     let mut maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
@@ -145,18 +219,21 @@ fn subset_ttf(
     let subset_glyphs = glyf.subset(glyph_ids)?;
 
     // Build a new cmap table
-    let cmap = match cmap_strategy {
+    let (cmap, chars) = match cmap_strategy {
         CmapStrategy::Generate(mappings_to_keep) => {
             let mappings_to_keep = mappings_to_keep.update_to_new_ids(&subset_glyphs);
-            Some(create_cmap_table(&mappings_to_keep)?)
+            let chars = mappings_to_keep.char_codes().collect::<Vec<_>>();
+            (Some(create_cmap_table(&mappings_to_keep)?), Some(chars))
         }
-        CmapStrategy::MacRomanSupplied(cmap) => {
-            Some(create_cmap_table_from_cmap_array(glyph_ids, cmap)?)
-        }
-        CmapStrategy::Omit => None,
+        CmapStrategy::MacRomanSupplied(cmap) => (
+            Some(create_cmap_table_from_cmap_array(glyph_ids, cmap)?),
+            None,
+        ),
+        CmapStrategy::Omit => (None, None),
     };
 
     // Build new maxp table
+    let old_num_glyphs = usize::from(maxp.num_glyphs);
     let num_glyphs = u16::try_from(subset_glyphs.len()).map_err(ParseError::from)?;
     maxp.num_glyphs = num_glyphs;
 
@@ -167,6 +244,25 @@ fn subset_ttf(
     // Build new hmtx table
     let hmtx = create_hmtx_table(&hmtx, num_h_metrics, &subset_glyphs)?;
 
+    // Subset the layout and auxiliary tables
+    let optional_tables = if include_optional_tables {
+        let mut tables = if include_layout_tables {
+            subset_layout_tables(provider, &subset_glyphs)?
+        } else {
+            Vec::new()
+        };
+        tables.extend(subset_auxiliary_tables(
+            provider,
+            old_num_glyphs,
+            &subset_glyphs,
+            chars.as_deref(),
+        )?);
+        tables.extend(subset_variation_tables(provider, &subset_glyphs)?);
+        tables
+    } else {
+        Vec::new()
+    };
+
     // Extract the new glyf table now that we're done with subset_glyphs
     let glyf = GlyfTable::from(subset_glyphs);
 
@@ -197,6 +293,9 @@ fn subset_ttf(
     if let Some(prep) = prep {
         builder.add_table::<_, ReadScope<'_>>(tag::PREP, ReadScope::new(&prep), ())?;
     }
+    for (table_tag, table) in &optional_tables {
+        builder.add_table::<_, ReadScope<'_>>(*table_tag, ReadScope::new(table.bytes()), ())?;
+    }
     let mut builder = builder.add_head_table(&head)?;
     builder.add_glyf_table(glyf)?;
     builder.data()
@@ -215,25 +314,15 @@ fn subset_cff(
         return Err(SubsetError::InvalidFontCount);
     }
 
-    let head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
-    let maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
-    let hhea = ReadScope::new(&provider.read_table_data(tag::HHEA)?).read::<HheaTable>()?;
-    let hmtx_data = provider.read_table_data(tag::HMTX)?;
-    let hmtx = ReadScope::new(&hmtx_data).read_dep::<HmtxTable<'_>>((
-        usize::from(maxp.num_glyphs),
-        usize::from(hhea.num_h_metrics),
-    ))?;
-
     // Build the new CFF table
     let cff_subset = cff.subset(glyph_ids, convert_cff_to_cid_if_more_than_255_glyphs)?;
+    let mut cff_table = WriteBuffer::new();
+    CFF::write(&mut cff_table, cff_subset.table())?;
     build_otf(
-        cff_subset,
+        &cff_subset,
+        (tag::CFF, cff_table),
         mappings_to_keep,
         provider,
-        &head,
-        maxp,
-        hhea,
-        &hmtx,
     )
 }
 
@@ -247,42 +336,60 @@ fn subset_cff2(
     let cff2_data = provider.read_table_data(tag::CFF2)?;
     let scope = ReadScope::new(&cff2_data);
     let cff2: CFF2<'_> = scope.read::<CFF2<'_>>()?;
-    let head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
-    let maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
-    let hhea = ReadScope::new(&provider.read_table_data(tag::HHEA)?).read::<HheaTable>()?;
-    let hmtx_data = provider.read_table_data(tag::HMTX)?;
-    let hmtx = ReadScope::new(&hmtx_data).read_dep::<HmtxTable<'_>>((
-        usize::from(maxp.num_glyphs),
-        usize::from(hhea.num_h_metrics),
-    ))?;
 
     // Build the new CFF table
-    let cff_subset = cff2
-        .subset_to_cff(glyph_ids, provider, include_fstype, output_format)?
-        .into();
+    let cff_subset = cff2.subset_to_cff(glyph_ids, provider, include_fstype, output_format)?;
+    let mut cff_table = WriteBuffer::new();
+    CFF::write(&mut cff_table, cff_subset.table())?;
 
     // Wrap the rest of the OpenType tables around it
     build_otf(
-        cff_subset,
+        &cff_subset,
+        (tag::CFF, cff_table),
+        mappings_to_keep,
+        provider,
+    )
+}
+
+/// Subset a variable CFF2 font, keeping its `CFF2` table and variations.
+fn subset_variable_cff2(
+    provider: &impl FontTableProvider,
+    glyph_ids: &[u16],
+    mappings_to_keep: MappingsToKeep<OldIds>,
+) -> Result<Vec<u8>, SubsetError> {
+    let cff2_data = provider.read_table_data(tag::CFF2)?;
+    let cff2 = ReadScope::new(&cff2_data).read::<CFF2<'_>>()?;
+
+    // Build the new CFF2 table
+    let cff2_subset = cff2.subset(glyph_ids)?;
+    let mut cff2_table = WriteBuffer::new();
+    CFF2::write(&mut cff2_table, cff2_subset.table().clone())?;
+
+    build_otf(
+        &cff2_subset,
+        (tag::CFF2, cff2_table),
         mappings_to_keep,
         provider,
-        &head,
-        maxp,
-        hhea,
-        &hmtx,
     )
 }
 
+/// Build an OpenType font with CFF outlines around the subset `CFF` or `CFF2` table in
+/// `cff_table`. The `GDEF`, `GSUB`, and `GPOS` tables are subset and included in the font.
 fn build_otf(
-    cff_subset: SubsetCFF<'_>,
+    cff_subset: &impl SubsetGlyphs,
+    cff_table: (u32, WriteBuffer),
     mappings_to_keep: MappingsToKeep<OldIds>,
     provider: &impl FontTableProvider,
-    head: &HeadTable,
-    mut maxp: MaxpTable,
-    mut hhea: HheaTable,
-    hmtx: &HmtxTable<'_>,
 ) -> Result<Vec<u8>, SubsetError> {
-    let mappings_to_keep = mappings_to_keep.update_to_new_ids(&cff_subset);
+    let head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
+    let mut maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
+    let mut hhea = ReadScope::new(&provider.read_table_data(tag::HHEA)?).read::<HheaTable>()?;
+    let hmtx_data = provider.read_table_data(tag::HMTX)?;
+    let hmtx = ReadScope::new(&hmtx_data).read_dep::<HmtxTable<'_>>((
+        usize::from(maxp.num_glyphs),
+        usize::from(hhea.num_h_metrics),
+    ))?;
+    let mappings_to_keep = mappings_to_keep.update_to_new_ids(cff_subset);
 
     // Build a new post table with version set to 3, which does not contain any additional
     // PostScript data
@@ -303,14 +410,26 @@ fn build_otf(
     hhea.num_h_metrics = num_glyphs;
 
     // Build new hmtx table
-    let hmtx = create_hmtx_table(hmtx, num_h_metrics, &cff_subset)?;
+    let hmtx = create_hmtx_table(&hmtx, num_h_metrics, cff_subset)?;
+
+    // Build a new OS/2 table with the character ranges of the retained mappings
+    let chars = mappings_to_keep.char_codes().collect::<Vec<_>>();
+    let os_2 = subset_os2(
+        ReadScope::new(&provider.read_table_data(tag::OS_2)?),
+        Some(&chars),
+    )?;
+
+    // Subset the layout and variation tables
+    let mut optional_tables = subset_layout_tables(provider, cff_subset)?;
+    optional_tables.push((tag::OS_2, os_2));
+    optional_tables.extend(subset_variation_tables(provider, cff_subset)?);
+    optional_tables.push(cff_table);
 
     // Get the remaining tables
     let cvt = provider.table_data(tag::CVT)?;
     let fpgm = provider.table_data(tag::FPGM)?;
     let name = provider.table_data(tag::NAME)?;
     let prep = provider.table_data(tag::PREP)?;
-    let os_2 = provider.read_table_data(tag::OS_2)?;
 
     // Build the new font
     let mut builder = FontBuilder::new(tag::OTTO);
@@ -327,19 +446,184 @@ fn build_otf(
     if let Some(name) = name {
         builder.add_table::<_, ReadScope<'_>>(tag::NAME, ReadScope::new(&name), ())?;
     }
-    builder.add_table::<_, ReadScope<'_>>(tag::OS_2, ReadScope::new(&os_2), ())?;
     builder.add_table::<_, PostTable<'_>>(tag::POST, &post, ())?;
     if let Some(prep) = prep {
         builder.add_table::<_, ReadScope<'_>>(tag::PREP, ReadScope::new(&prep), ())?;
     }
-
-    // Extract the new CFF table now that we're done with cff_subset
-    let cff = CFF::from(cff_subset);
-    builder.add_table::<_, CFF<'_>>(tag::CFF, &cff, ())?;
+    for (table_tag, table) in &optional_tables {
+        builder.add_table::<_, ReadScope<'_>>(*table_tag, ReadScope::new(table.bytes()), ())?;
+    }
     let builder = builder.add_head_table(&head)?;
     builder.data().map_err(SubsetError::from)
 }
 
+/// Subset the tables that describe the metrics and coverage of the font, rather than its glyphs:
+/// `OS/2`, `vhea`, `vmtx`, `VORG`, `gasp`, `hdmx`, and `kern`.
+///
+/// `num_glyphs` is the number of glyphs in the font. If `chars` is supplied the character
+/// indices and Unicode ranges of `OS/2` are updated to describe these characters. Only the format
+/// 0 subtables of `kern` are retained. Returns the tag and data of each table, and a table that
+/// can't be subset is left out and a warning is logged, rather than failing.
+fn subset_auxiliary_tables(
+    provider: &impl FontTableProvider,
+    num_glyphs: usize,
+    glyphs: &impl SubsetGlyphs,
+    chars: Option<&[u32]>,
+) -> Result<Vec<(u32, WriteBuffer)>, ReadWriteError> {
+    let mut tables = Vec::new();
+    for table_tag in [
+        tag::OS_2,
+        tag::VHEA,
+        tag::VORG,
+        tag::GASP,
+        tag::HDMX,
+        tag::KERN,
+    ] {
+        let data = match provider.table_data(table_tag)? {
+            Some(data) => data,
+            None => continue,
+        };
+        let scope = ReadScope::new(&data);
+        let result = match table_tag {
+            tag::OS_2 => subset_os2(scope, chars).map(|os2| vec![(tag::OS_2, os2)]),
+            tag::VHEA => subset_vertical_metrics(provider, scope, num_glyphs, glyphs),
+            tag::VORG => subset_vorg(scope, glyphs).map(|vorg| vec![(tag::VORG, vorg)]),
+            tag::HDMX => subset_hdmx(scope, num_glyphs, glyphs).map(|hdmx| vec![(tag::HDMX, hdmx)]),
+            tag::KERN => scope
+                .read::<KernTable<'_>>()
+                .map_err(ReadWriteError::from)
+                .and_then(|kern| Ok(kern.subset(glyphs)?))
+                .map(|kern| kern.map(|kern| (tag::KERN, kern)).into_iter().collect()),
+            _ => {
+                let mut table = WriteBuffer::new();
+                table.write_bytes(&data)?;
+                Ok(vec![(table_tag, table)])
+            }
+        };
+        match result {
+            Ok(subset_tables) => tables.extend(subset_tables),
+            Err(err) => warn!(
+                "unable to subset {} table: {:?}",
+                tag::DisplayTag(table_tag),
+                err
+            ),
+        }
+    }
+    Ok(tables)
+}
+
+/// Subset the tables that make the font variable: `fvar`, `avar`, `STAT`, `MVAR`, `cvar`, `gvar`,
+/// `HVAR`, and `VVAR`.
+///
+/// The glyph variations in `gvar` and the metrics variations in `HVAR` and `VVAR` are subset to
+/// the glyphs in `glyphs`, and the other tables are copied as is, since they don't refer to
+/// glyphs. Unlike [subset_auxiliary_tables] an error is returned if a table can't be subset,
+/// since leaving it out would change the outlines or metrics of the font.
+fn subset_variation_tables(
+    provider: &impl FontTableProvider,
+    glyphs: &impl SubsetGlyphs,
+) -> Result<Vec<(u32, WriteBuffer)>, ReadWriteError> {
+    let mut tables = Vec::new();
+    for table_tag in [
+        tag::FVAR,
+        tag::AVAR,
+        tag::STAT,
+        tag::MVAR,
+        tag::CVAR,
+        tag::GVAR,
+        tag::HVAR,
+        tag::VVAR,
+    ] {
+        let data = match provider.table_data(table_tag)? {
+            Some(data) => data,
+            None => continue,
+        };
+        let scope = ReadScope::new(&data);
+        let table = match table_tag {
+            tag::GVAR => scope.read::<GvarTable<'_>>()?.subset(glyphs)?,
+            tag::HVAR => scope.read::<HvarTable<'_>>()?.subset(glyphs)?,
+            tag::VVAR => scope.read::<VvarTable<'_>>()?.subset(glyphs)?,
+            _ => {
+                let mut table = WriteBuffer::new();
+                table.write_bytes(&data)?;
+                table
+            }
+        };
+        tables.push((table_tag, table));
+    }
+    Ok(tables)
+}
+
+fn subset_os2(scope: ReadScope<'_>, chars: Option<&[u32]>) -> Result<WriteBuffer, ReadWriteError> {
+    let mut os2 = scope.read_dep::<Os2>(scope.data().len())?;
+    if let Some(chars) = chars {
+        os2.set_char_ranges(chars.iter().copied());
+    }
+    let mut table = WriteBuffer::new();
+    Os2::write(&mut table, &os2)?;
+    Ok(table)
+}
+
+/// Subset `vhea` and `vmtx`, with the same glyph remapping as `hmtx`.
+fn subset_vertical_metrics(
+    provider: &impl FontTableProvider,
+    vhea: ReadScope<'_>,
+    num_glyphs: usize,
+    glyphs: &impl SubsetGlyphs,
+) -> Result<Vec<(u32, WriteBuffer)>, ReadWriteError> {
+    let mut vhea = vhea.read::<HheaTable>()?;
+    let vmtx_data = provider.read_table_data(tag::VMTX)?;
+    let vmtx = ReadScope::new(&vmtx_data)
+        .read_dep::<HmtxTable<'_>>((num_glyphs, usize::from(vhea.num_h_metrics)))?;
+    let vmtx = create_hmtx_table(&vmtx, usize::from(vhea.num_h_metrics), glyphs)?;
+    vhea.num_h_metrics = u16::try_from(glyphs.len())?;
+
+    let mut vhea_table = WriteBuffer::new();
+    HheaTable::write(&mut vhea_table, &vhea)?;
+    let mut vmtx_table = WriteBuffer::new();
+    HmtxTable::write(&mut vmtx_table, &vmtx)?;
+    Ok(vec![(tag::VHEA, vhea_table), (tag::VMTX, vmtx_table)])
+}
+
+fn subset_vorg(
+    scope: ReadScope<'_>,
+    glyphs: &impl SubsetGlyphs,
+) -> Result<WriteBuffer, ReadWriteError> {
+    let vorg = scope.read::<VorgTable>()?;
+    let mut vert_origin_y_metrics = (0..glyphs.len())
+        .filter_map(|new_id| {
+            let new_id = u16::try_from(new_id).ok()?;
+            let old_id = glyphs.old_id(new_id);
+            vorg.vert_origin_y_metrics
+                .binary_search_by_key(&old_id, |metrics| metrics.glyph_index)
+                .ok()
+                .map(|index| VertOriginYMetrics {
+                    glyph_index: new_id,
+                    vert_origin_y: vorg.vert_origin_y_metrics[index].vert_origin_y,
+                })
+        })
+        .collect::<Vec<_>>();
+    vert_origin_y_metrics.sort_unstable_by_key(|metrics| metrics.glyph_index);
+    let vorg = VorgTable {
+        default_vert_origin_y: vorg.default_vert_origin_y,
+        vert_origin_y_metrics,
+    };
+    let mut table = WriteBuffer::new();
+    VorgTable::write(&mut table, &vorg)?;
+    Ok(table)
+}
+
+fn subset_hdmx(
+    scope: ReadScope<'_>,
+    num_glyphs: usize,
+    glyphs: &impl SubsetGlyphs,
+) -> Result<WriteBuffer, ReadWriteError> {
+    let hdmx = scope.read_dep::<HdmxTable<'_>>(num_glyphs)?.subset(glyphs);
+    let mut table = WriteBuffer::new();
+    HdmxTable::write(&mut table, &hdmx)?;
+    Ok(table)
+}
+
 fn create_cmap_table(
     mappings_to_keep: &MappingsToKeep<NewIds>,
 ) -> Result<owned::Cmap, ReadWriteError> {
@@ -677,7 +961,8 @@ pub mod prince {
                 PrinceCmapTarget::Omit => CmapStrategy::Omit,
                 PrinceCmapTarget::MacRomanCmap(cmap) => CmapStrategy::MacRomanSupplied(cmap),
             };
-            super::subset_ttf(provider, glyph_ids, cmap_strategy).map_err(SubsetError::from)
+            super::subset_ttf(provider, glyph_ids, cmap_strategy, false, false)
+                .map_err(SubsetError::from)
         }
     }
 
@@ -1302,6 +1587,8 @@ mod tests {
             &opentype_file.table_provider(0).unwrap(),
             &mut glyph_ids,
             CmapStrategy::Omit,
+            false,
+            false,
         )
         .unwrap();
 
@@ -1,11 +1,18 @@
 //! OpenType font table parsing and writing.
 
+pub mod aat;
 pub mod cmap;
 pub mod glyf;
+pub mod hdmx;
+pub mod kern;
+pub mod kerx;
 pub mod loca;
+pub mod morx;
 pub mod os2;
 pub mod svg;
+pub mod trak;
 pub mod variable_fonts;
+pub mod vorg;
 
 use std::borrow::Cow;
 use std::convert::TryFrom;
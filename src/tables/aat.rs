@@ -0,0 +1,303 @@
+//! Structures shared by the Apple Advanced Typography (AAT) tables.
+//!
+//! The `morx` and `kerx` tables map glyphs to values with lookup tables, and drive contextual
+//! processing with extended state tables.
+//!
+//! <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6Tables.html>
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use crate::binary::read::{ReadArray, ReadBinary, ReadBinaryDep, ReadCtxt, ReadScope};
+use crate::binary::U16Be;
+use crate::error::ParseError;
+
+/// The glyph index of a glyph that has been deleted by a `morx` subtable.
+pub const DELETED_GLYPH: u16 = 0xFFFF;
+
+/// A lookup table, mapping glyphs to 16-bit values.
+pub enum LookupTable<'a> {
+    /// Simple array indexed by glyph.
+    Format0(ReadScope<'a>),
+    /// Segments that map a range of glyphs to a single value.
+    Format2(ReadArray<'a, (U16Be, U16Be, U16Be)>),
+    /// Segments that map a range of glyphs to an array of values.
+    Format4 {
+        scope: ReadScope<'a>,
+        segments: ReadArray<'a, (U16Be, U16Be, U16Be)>,
+    },
+    /// Sorted list of glyph-value pairs.
+    Format6(ReadArray<'a, (U16Be, U16Be)>),
+    /// Array of values for a range of glyphs.
+    Format8 {
+        first_glyph: u16,
+        values: ReadArray<'a, U16Be>,
+    },
+    /// Array of values of variable size for a range of glyphs.
+    Format10 {
+        unit_size: u16,
+        first_glyph: u16,
+        glyph_count: u16,
+        values: ReadScope<'a>,
+    },
+}
+
+/// An extended state table, used by the `morx` and `kerx` tables.
+pub struct ExtendedStateTable<'a> {
+    class_count: u32,
+    class_table: LookupTable<'a>,
+    state_array: ReadScope<'a>,
+    entry_table: ReadScope<'a>,
+    entry_data_len: usize,
+}
+
+/// An entry of an extended state table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StateEntry {
+    /// The state to move to.
+    pub new_state: u16,
+    /// The flags of the entry. Their meaning depends on the table.
+    pub flags: u16,
+    /// Additional per-entry data, such as indices of actions.
+    pub data: [u16; 2],
+}
+
+impl ReadBinary for LookupTable<'_> {
+    type HostType<'a> = LookupTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let scope = ctxt.scope();
+        let format = ctxt.read_u16be()?;
+        match format {
+            0 => Ok(LookupTable::Format0(scope.offset(2))),
+            2 | 4 | 6 => {
+                let unit_size = usize::from(ctxt.read_u16be()?);
+                let unit_count = usize::from(ctxt.read_u16be()?);
+                let _search_range = ctxt.read_u16be()?;
+                let _entry_selector = ctxt.read_u16be()?;
+                let _range_shift = ctxt.read_u16be()?;
+                match format {
+                    2 => Ok(LookupTable::Format2(
+                        ctxt.read_array_stride(unit_count, unit_size)?,
+                    )),
+                    4 => Ok(LookupTable::Format4 {
+                        scope,
+                        segments: ctxt.read_array_stride(unit_count, unit_size)?,
+                    }),
+                    _ => Ok(LookupTable::Format6(
+                        ctxt.read_array_stride(unit_count, unit_size)?,
+                    )),
+                }
+            }
+            8 => {
+                let first_glyph = ctxt.read_u16be()?;
+                let glyph_count = usize::from(ctxt.read_u16be()?);
+                let values = ctxt.read_array(glyph_count)?;
+                Ok(LookupTable::Format8 {
+                    first_glyph,
+                    values,
+                })
+            }
+            10 => {
+                let unit_size = ctxt.read_u16be()?;
+                let first_glyph = ctxt.read_u16be()?;
+                let glyph_count = ctxt.read_u16be()?;
+                ctxt.check(matches!(unit_size, 1 | 2 | 4))?;
+                let values = ctxt.read_scope(usize::from(unit_size) * usize::from(glyph_count))?;
+                Ok(LookupTable::Format10 {
+                    unit_size,
+                    first_glyph,
+                    glyph_count,
+                    values,
+                })
+            }
+            _ => Err(ParseError::BadVersion),
+        }
+    }
+}
+
+impl LookupTable<'_> {
+    /// Returns the value of `glyph`, or `None` if the table has no value for it.
+    ///
+    /// Values wider than 16 bits in format 10 tables are truncated.
+    pub fn value(&self, glyph: u16) -> Option<u16> {
+        match self {
+            LookupTable::Format0(values) => values
+                .offset(usize::from(glyph) * 2)
+                .ctxt()
+                .read_u16be()
+                .ok(),
+            LookupTable::Format2(segments) => {
+                let index = find_segment(segments, glyph)?;
+                let (_, _, value) = segments.get_item(index);
+                Some(value)
+            }
+            LookupTable::Format4 { scope, segments } => {
+                let index = find_segment(segments, glyph)?;
+                let (_, first, offset) = segments.get_item(index);
+                scope
+                    .offset(usize::from(offset) + usize::from(glyph - first) * 2)
+                    .ctxt()
+                    .read_u16be()
+                    .ok()
+            }
+            LookupTable::Format6(entries) => {
+                let index = entries
+                    .binary_search_by(|(entry_glyph, _)| entry_glyph.cmp(&glyph))
+                    .ok()?;
+                let (_, value) = entries.get_item(index);
+                Some(value)
+            }
+            LookupTable::Format8 {
+                first_glyph,
+                values,
+            } => {
+                let index = usize::from(glyph.checked_sub(*first_glyph)?);
+                (index < values.len()).then(|| values.get_item(index))
+            }
+            LookupTable::Format10 {
+                unit_size,
+                first_glyph,
+                glyph_count,
+                values,
+            } => {
+                let index = glyph.checked_sub(*first_glyph)?;
+                if index >= *glyph_count {
+                    return None;
+                }
+                let mut ctxt = values
+                    .offset(usize::from(index) * usize::from(*unit_size))
+                    .ctxt();
+                let value = match unit_size {
+                    1 => ctxt.read_u8().map(u16::from),
+                    2 => ctxt.read_u16be(),
+                    _ => ctxt.read_u32be().map(|value| value as u16),
+                };
+                value.ok()
+            }
+        }
+    }
+}
+
+// Finds the segment containing `glyph`, ignoring the 0xFFFF sentinel segment
+fn find_segment(segments: &ReadArray<'_, (U16Be, U16Be, U16Be)>, glyph: u16) -> Option<usize> {
+    if glyph == DELETED_GLYPH {
+        return None;
+    }
+    segments
+        .binary_search_by(|(last, first, _)| {
+            if last < glyph {
+                Ordering::Less
+            } else if first > glyph {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()
+}
+
+impl ReadBinaryDep for ExtendedStateTable<'_> {
+    type Args<'a> = usize;
+    type HostType<'a> = ExtendedStateTable<'a>;
+
+    /// Read an extended state table whose entries have `entry_data_len` 16-bit values after the
+    /// new state and flags.
+    fn read_dep<'a>(
+        ctxt: &mut ReadCtxt<'a>,
+        entry_data_len: usize,
+    ) -> Result<Self::HostType<'a>, ParseError> {
+        let scope = ctxt.scope();
+        let class_count = ctxt.read_u32be()?;
+        let class_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+        let state_array_offset = usize::try_from(ctxt.read_u32be()?)?;
+        let entry_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+        ctxt.check(entry_data_len <= 2)?;
+        let class_table = scope.offset(class_table_offset).read::<LookupTable<'_>>()?;
+        Ok(ExtendedStateTable {
+            class_count,
+            class_table,
+            state_array: scope.offset(state_array_offset),
+            entry_table: scope.offset(entry_table_offset),
+            entry_data_len,
+        })
+    }
+}
+
+impl ExtendedStateTable<'_> {
+    /// The class of the end of the text.
+    pub const END_OF_TEXT: u16 = 0;
+    /// The class of glyphs that aren't in the class table.
+    pub const OUT_OF_BOUNDS: u16 = 1;
+    /// The class of deleted glyphs.
+    pub const DELETED_GLYPH: u16 = 2;
+    /// The initial state at the start of the text.
+    pub const START_OF_TEXT: u16 = 0;
+
+    /// Returns the class of `glyph`.
+    pub fn glyph_class(&self, glyph: u16) -> u16 {
+        if glyph == DELETED_GLYPH {
+            return Self::DELETED_GLYPH;
+        }
+        match self.class_table.value(glyph) {
+            Some(class) if u32::from(class) < self.class_count => class,
+            _ => Self::OUT_OF_BOUNDS,
+        }
+    }
+
+    /// Returns the entry for `class` in `state`.
+    pub fn entry(&self, state: u16, class: u16) -> Result<StateEntry, ParseError> {
+        let class_count = usize::try_from(self.class_count)?;
+        let index = usize::from(state) * class_count + usize::from(class);
+        let entry_index = self.state_array.offset(index * 2).ctxt().read_u16be()?;
+        let entry_size = 4 + 2 * self.entry_data_len;
+        let mut ctxt = self
+            .entry_table
+            .offset(usize::from(entry_index) * entry_size)
+            .ctxt();
+        let new_state = ctxt.read_u16be()?;
+        let flags = ctxt.read_u16be()?;
+        let mut data = [0; 2];
+        for value in data.iter_mut().take(self.entry_data_len) {
+            *value = ctxt.read_u16be()?;
+        }
+        Ok(StateEntry {
+            new_state,
+            flags,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_table_formats() {
+        let format2 = [
+            0, 2, 0, 6, 0, 2, 0, 12, 0, 1, 0, 0, // header
+            0, 20, 0, 10, 0, 3, // 10..=20 → 3
+            0xFF, 0xFF, 0xFF, 0xFF, 0, 0, // sentinel
+        ];
+        let table = ReadScope::new(&format2).read::<LookupTable<'_>>().unwrap();
+        assert_eq!(table.value(15), Some(3));
+        assert_eq!(table.value(9), None);
+        assert_eq!(table.value(DELETED_GLYPH), None);
+
+        let format6 = [
+            0, 6, 0, 4, 0, 2, 0, 8, 0, 1, 0, 0, // header
+            0, 3, 0, 30, // 3 → 30
+            0, 7, 0, 70, // 7 → 70
+        ];
+        let table = ReadScope::new(&format6).read::<LookupTable<'_>>().unwrap();
+        assert_eq!(table.value(7), Some(70));
+        assert_eq!(table.value(5), None);
+
+        let format8 = [0, 8, 0, 5, 0, 2, 0, 10, 0, 11];
+        let table = ReadScope::new(&format8).read::<LookupTable<'_>>().unwrap();
+        assert_eq!(table.value(6), Some(11));
+        assert_eq!(table.value(4), None);
+        assert_eq!(table.value(7), None);
+    }
+}
@@ -343,6 +343,11 @@ impl<T> MappingsToKeep<T> {
     fn plane(&self) -> CharExistence {
         self.plane
     }
+
+    /// Returns the character codes of the mappings.
+    pub(crate) fn char_codes(&self) -> impl Iterator<Item = u32> + '_ {
+        self.mappings.keys().map(|ch| ch.as_u32())
+    }
 }
 
 impl MappingsToKeep<OldIds> {
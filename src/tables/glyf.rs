@@ -30,6 +30,7 @@ use crate::tables::os2::Os2;
 use crate::tables::{F2Dot14, HheaTable, HmtxTable, IndexToLocFormat};
 
 pub use subset::SubsetGlyph;
+pub(crate) use variation::fill_region_deltas;
 
 /// Recursion limit for nested composite glyphs
 ///
@@ -293,44 +293,12 @@ fn glyph_deltas(
     for (scale, region) in applicable {
         let variation_data =
             region.variation_data(num_points, variations.shared_point_numbers())?;
-        // This is the output for this region, by the end every point needs to have a delta assigned.
-        // Either explicitly or inferred. This buffer is reused between regions so we re-fill it
-        // with zeros for each new region.
-        region_deltas.fill(Vector2F::zero());
-
         // This maps point numbers to deltas, in order. It allows direct lookup of deltas for a
         // point as well as navigating between explicit points.
         let explicit_deltas = variation_data.iter().collect::<BTreeMap<_, _>>();
-
-        // Fill in the explicit deltas
-        for (number, delta) in &explicit_deltas {
-            let region_delta = region_deltas
-                .get_mut(usize::safe_from(*number))
-                .ok_or(ParseError::BadIndex)?;
-            *region_delta = Vector2F::new(delta.0 as f32, delta.1 as f32);
-        }
-
-        // > Calculation of inferred deltas is done for a given glyph and a given region on a
-        // > contour-by-contour basis.
-        // >
-        // > For a given contour, if the point number list does not include any of the points in
-        // > that contour, then none of the points in the contour are affected and no inferred deltas
-        // > need to be computed.
-        // >
-        // > If the point number list includes some but not all of the points in a given contour,
-        // > then inferred deltas must be derived for the points that were not included in the point
-        // > number list, as follows.
-
-        // Only need to do this for simple glyphs
-        match glyph {
-            Glyph::Simple(simple_glyph) => {
-                // Deltas need to be inferred if not all points were assigned explicit deltas
-                if explicit_deltas.len() != usize::safe_from(num_points.get()) {
-                    infer_unreferenced_points(&mut region_deltas, &explicit_deltas, simple_glyph)?;
-                }
-            }
-            _ => {}
-        }
+        // This is the output for this region, by the end every point needs to have a delta
+        // assigned. Either explicitly or inferred. This buffer is reused between regions.
+        fill_region_deltas(glyph, &explicit_deltas, &mut region_deltas)?;
 
         // Scale and accumulate the deltas from this variation region onto the final deltas
         final_deltas
@@ -343,6 +311,49 @@ fn glyph_deltas(
     Ok(Some(final_deltas))
 }
 
+/// Fill `region_deltas`, which holds a delta for each point of `glyph`, from the deltas of the
+/// points in `explicit_deltas` for one variation region.
+///
+/// The deltas of points without an explicit delta are inferred.
+pub(crate) fn fill_region_deltas(
+    glyph: &Glyph<'_>,
+    explicit_deltas: &BTreeMap<u32, (i16, i16)>,
+    region_deltas: &mut [Vector2F],
+) -> Result<(), ParseError> {
+    region_deltas.fill(Vector2F::zero());
+
+    // Fill in the explicit deltas
+    for (number, delta) in explicit_deltas {
+        let region_delta = region_deltas
+            .get_mut(usize::safe_from(*number))
+            .ok_or(ParseError::BadIndex)?;
+        *region_delta = Vector2F::new(delta.0 as f32, delta.1 as f32);
+    }
+
+    // > Calculation of inferred deltas is done for a given glyph and a given region on a
+    // > contour-by-contour basis.
+    // >
+    // > For a given contour, if the point number list does not include any of the points in
+    // > that contour, then none of the points in the contour are affected and no inferred deltas
+    // > need to be computed.
+    // >
+    // > If the point number list includes some but not all of the points in a given contour,
+    // > then inferred deltas must be derived for the points that were not included in the point
+    // > number list, as follows.
+
+    // Only need to do this for simple glyphs
+    match glyph {
+        Glyph::Simple(simple_glyph) => {
+            // Deltas need to be inferred if not all points were assigned explicit deltas
+            if explicit_deltas.len() != region_deltas.len() {
+                infer_unreferenced_points(region_deltas, explicit_deltas, simple_glyph)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 fn infer_unreferenced_points(
     deltas: &mut [Vector2F],
     raw_deltas: &BTreeMap<u32, (i16, i16)>,
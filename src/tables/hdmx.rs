@@ -0,0 +1,134 @@
+//! Parsing and writing of the `hdmx` table.
+//!
+//! > The Horizontal Device Metrics table stores integer advance widths scaled to particular pixel
+//! > sizes.
+//!
+//! — <https://learn.microsoft.com/en-us/typography/opentype/spec/hdmx>
+
+use std::convert::TryFrom;
+
+use crate::binary::read::{ReadArrayCow, ReadBinaryDep, ReadCtxt};
+use crate::binary::write::{WriteBinary, WriteContext};
+use crate::binary::{long_align, I16Be, I32Be, U16Be, U8};
+use crate::error::{ParseError, WriteError};
+use crate::subset::SubsetGlyphs;
+
+/// `hdmx` table
+pub struct HdmxTable<'a> {
+    /// The device records of the table, one for each pixel size.
+    pub records: Vec<DeviceRecord<'a>>,
+}
+
+/// The advance widths of the glyphs at a pixel size.
+pub struct DeviceRecord<'a> {
+    /// The pixel size, in pixels per em.
+    pub pixel_size: u8,
+    /// The maximum of `widths`.
+    pub max_width: u8,
+    /// The advance width of each glyph, in pixels.
+    pub widths: ReadArrayCow<'a, U8>,
+}
+
+impl HdmxTable<'_> {
+    /// Returns a copy of this table that only contains the widths of the glyphs in `glyphs`.
+    pub(crate) fn subset(&self, glyphs: &impl SubsetGlyphs) -> HdmxTable<'static> {
+        let records = self
+            .records
+            .iter()
+            .map(|record| {
+                let widths = (0..glyphs.len())
+                    .map(|new_id| {
+                        let old_id = glyphs.old_id(new_id as u16);
+                        record.widths.get_item(usize::from(old_id))
+                    })
+                    .collect::<Vec<_>>();
+                DeviceRecord {
+                    pixel_size: record.pixel_size,
+                    max_width: widths.iter().copied().max().unwrap_or(0),
+                    widths: ReadArrayCow::Owned(widths),
+                }
+            })
+            .collect();
+        HdmxTable { records }
+    }
+}
+
+impl ReadBinaryDep for HdmxTable<'_> {
+    type Args<'a> = usize;
+    type HostType<'a> = HdmxTable<'a>;
+
+    /// Read the table, with `num_glyphs` from `maxp`.
+    fn read_dep<'a>(
+        ctxt: &mut ReadCtxt<'a>,
+        num_glyphs: usize,
+    ) -> Result<Self::HostType<'a>, ParseError> {
+        let version = ctxt.read_u16be()?;
+        ctxt.check_version(version == 0)?;
+        let num_records = usize::try_from(ctxt.read_i16be()?)?;
+        let size_device_record = usize::try_from(ctxt.read_i32be()?)?;
+        ctxt.check(size_device_record >= 2 + num_glyphs)?;
+
+        let mut records = Vec::with_capacity(num_records);
+        for _ in 0..num_records {
+            // Records are padded to a multiple of four bytes
+            let mut record = ctxt.read_scope(size_device_record)?.ctxt();
+            records.push(DeviceRecord {
+                pixel_size: record.read_u8()?,
+                max_width: record.read_u8()?,
+                widths: ReadArrayCow::Borrowed(record.read_array::<U8>(num_glyphs)?),
+            });
+        }
+        Ok(HdmxTable { records })
+    }
+}
+
+impl WriteBinary<&Self> for HdmxTable<'_> {
+    type Output = ();
+
+    fn write<C: WriteContext>(ctxt: &mut C, table: &Self) -> Result<(), WriteError> {
+        let num_glyphs = table
+            .records
+            .first()
+            .map_or(0, |record| record.widths.len());
+        let size_device_record = long_align(2 + num_glyphs);
+        U16Be::write(ctxt, 0u16)?; // version
+        I16Be::write(ctxt, i16::try_from(table.records.len())?)?;
+        I32Be::write(ctxt, i32::try_from(size_device_record)?)?;
+        for record in &table.records {
+            if record.widths.len() != num_glyphs {
+                return Err(WriteError::BadValue);
+            }
+            U8::write(ctxt, record.pixel_size)?;
+            U8::write(ctxt, record.max_width)?;
+            ReadArrayCow::write(ctxt, &record.widths)?;
+            ctxt.write_zeros(size_device_record - 2 - num_glyphs)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::read::ReadScope;
+    use crate::binary::write::WriteBuffer;
+
+    #[test]
+    fn test_read_write_hdmx() {
+        let data = [
+            0, 0, // version
+            0, 2, // two records
+            0, 0, 0, 8, // record size
+            12, 7, 6, 7, 5, 0, 0, 0, // 12 ppem
+            16, 9, 8, 9, 7, 0, 0, 0, // 16 ppem
+        ];
+        let hdmx = ReadScope::new(&data).read_dep::<HdmxTable<'_>>(3).unwrap();
+        assert_eq!(hdmx.records.len(), 2);
+        assert_eq!(hdmx.records[1].pixel_size, 16);
+        assert_eq!(hdmx.records[1].widths.get_item(2), 7);
+
+        let mut buffer = WriteBuffer::new();
+        HdmxTable::write(&mut buffer, &hdmx).unwrap();
+        assert_eq!(buffer.bytes(), &data);
+    }
+}
@@ -0,0 +1,556 @@
+//! Parsing of the `kern` table.
+//!
+//! > The kerning table contains the values that control the inter-character spacing for the
+//! > glyphs in a font.
+//!
+//! — <https://docs.microsoft.com/en-us/typography/opentype/spec/kern>
+//!
+//! Both the Microsoft (version 0) and Apple (version 1) forms of the table are supported. The
+//! Apple form additionally has contextual (format 1) and compact (format 3) subtables.
+//!
+//! <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6kern.html>
+
+use std::convert::TryFrom;
+
+use log::warn;
+
+use crate::binary::read::{ReadArray, ReadBinary, ReadCtxt, ReadFrom, ReadScope};
+use crate::binary::write::{WriteBinary, WriteBuffer};
+use crate::binary::{I16Be, U16Be};
+use crate::error::{ParseError, WriteError};
+use crate::subset::SubsetGlyphs;
+
+/// The maximum depth of the glyph stack of a contextual subtable.
+const MAX_STACK_DEPTH: usize = 8;
+
+/// The maximum number of times a contextual subtable may stay on the same glyph.
+const MAX_DONT_ADVANCE: usize = 16;
+
+/// `kern` table
+pub struct KernTable<'a> {
+    /// The supported subtables of the table.
+    pub subtables: Vec<KernSubtable<'a>>,
+}
+
+/// A `kern` subtable.
+pub struct KernSubtable<'a> {
+    /// The kerning values are for vertical text.
+    pub vertical: bool,
+    /// The kerning values are perpendicular to the flow of text.
+    pub cross_stream: bool,
+    /// The kerning values are minimum values rather than adjustments.
+    pub minimum: bool,
+    /// The kerning values replace the accumulated kerning rather than adding to it.
+    pub replace: bool,
+    /// The kerning values vary with the variation tuple of the font.
+    pub variation: bool,
+    /// The kerning data of the subtable.
+    pub data: KernData<'a>,
+}
+
+/// The kerning data of a `kern` subtable.
+pub enum KernData<'a> {
+    /// Ordered list of kerning pairs.
+    Format0(ReadArray<'a, KernPair>),
+    /// Contextual kerning driven by a state table.
+    Format1(StateTable<'a>),
+    /// Two-dimensional array of kerning values indexed by glyph class.
+    Format2(ClassTableKerning<'a>),
+    /// Compact two-dimensional array of kerning values.
+    Format3(IndexedKerning<'a>),
+}
+
+/// A kerning pair of a format 0 subtable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KernPair {
+    /// The left glyph of the pair.
+    pub left: u16,
+    /// The right glyph of the pair.
+    pub right: u16,
+    /// The kerning value of the pair.
+    pub value: i16,
+}
+
+/// A format 1 subtable.
+pub struct StateTable<'a> {
+    scope: ReadScope<'a>,
+    state_size: u16,
+    class_table: ReadScope<'a>,
+    state_array_offset: u16,
+    entry_table: ReadScope<'a>,
+}
+
+/// A format 2 subtable.
+pub struct ClassTableKerning<'a> {
+    scope: ReadScope<'a>,
+    left_class_table: ClassTable<'a>,
+    right_class_table: ClassTable<'a>,
+    array_offset: usize,
+}
+
+struct ClassTable<'a> {
+    first_glyph: u16,
+    offsets: ReadArray<'a, U16Be>,
+}
+
+/// A format 3 subtable.
+pub struct IndexedKerning<'a> {
+    kern_values: ReadArray<'a, I16Be>,
+    left_classes: &'a [u8],
+    right_classes: &'a [u8],
+    right_class_count: usize,
+    kern_indices: &'a [u8],
+}
+
+impl ReadBinary for KernTable<'_> {
+    type HostType<'a> = KernTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let table = ctxt.scope();
+        let version = ctxt.read_u16be()?;
+        let (apple, subtable_count, header_size) = match version {
+            0 => (false, usize::from(ctxt.read_u16be()?), 4),
+            1 => {
+                let minor_version = ctxt.read_u16be()?;
+                ctxt.check_version(minor_version == 0)?;
+                (true, usize::try_from(ctxt.read_u32be()?)?, 8)
+            }
+            _ => return Err(ParseError::BadVersion),
+        };
+
+        let mut subtables = Vec::new();
+        let mut offset = header_size;
+        for _ in 0..subtable_count {
+            let scope = table.offset(offset);
+            let mut ctxt = scope.ctxt();
+            let subtable = if apple {
+                let length = usize::try_from(ctxt.read_u32be()?)?;
+                let coverage = ctxt.read_u16be()?;
+                let _tuple_index = ctxt.read_u16be()?;
+                offset += length;
+                KernSubtableHeader {
+                    format: coverage & 0xFF,
+                    vertical: coverage & 0x8000 != 0,
+                    cross_stream: coverage & 0x4000 != 0,
+                    minimum: false,
+                    replace: false,
+                    variation: coverage & 0x2000 != 0,
+                }
+            } else {
+                let _version = ctxt.read_u16be()?;
+                // The length of large format 0 subtables overflows, so it's only used to find
+                // the next subtable
+                let length = usize::from(ctxt.read_u16be()?);
+                let coverage = ctxt.read_u16be()?;
+                offset += length;
+                KernSubtableHeader {
+                    format: coverage >> 8,
+                    vertical: coverage & 0x1 == 0,
+                    cross_stream: coverage & 0x4 != 0,
+                    minimum: coverage & 0x2 != 0,
+                    replace: coverage & 0x8 != 0,
+                    variation: false,
+                }
+            };
+            match subtable.read_data(scope, &mut ctxt) {
+                Ok(Some(data)) => subtables.push(subtable.into_subtable(data)),
+                Ok(None) => warn!("skipping kern subtable with format {}", subtable.format),
+                Err(err) => warn!("skipping invalid kern subtable: {}", err),
+            }
+        }
+
+        Ok(KernTable { subtables })
+    }
+}
+
+struct KernSubtableHeader {
+    format: u16,
+    vertical: bool,
+    cross_stream: bool,
+    minimum: bool,
+    replace: bool,
+    variation: bool,
+}
+
+impl KernSubtableHeader {
+    fn read_data<'a>(
+        &self,
+        scope: ReadScope<'a>,
+        ctxt: &mut ReadCtxt<'a>,
+    ) -> Result<Option<KernData<'a>>, ParseError> {
+        let data = match self.format {
+            0 => {
+                let pair_count = usize::from(ctxt.read_u16be()?);
+                let _search_range = ctxt.read_u16be()?;
+                let _entry_selector = ctxt.read_u16be()?;
+                let _range_shift = ctxt.read_u16be()?;
+                KernData::Format0(ctxt.read_array::<KernPair>(pair_count)?)
+            }
+            1 => {
+                // Offsets in the state table are from the end of the subtable header
+                let state_table = ctxt.scope();
+                let state_size = ctxt.read_u16be()?;
+                let class_table_offset = ctxt.read_u16be()?;
+                let state_array_offset = ctxt.read_u16be()?;
+                let entry_table_offset = ctxt.read_u16be()?;
+                ctxt.check(state_size > 0)?;
+                KernData::Format1(StateTable {
+                    scope: state_table,
+                    state_size,
+                    class_table: state_table.offset(usize::from(class_table_offset)),
+                    state_array_offset,
+                    entry_table: state_table.offset(usize::from(entry_table_offset)),
+                })
+            }
+            2 => {
+                // Offsets are from the start of the subtable
+                let _row_width = ctxt.read_u16be()?;
+                let left_class_table_offset = usize::from(ctxt.read_u16be()?);
+                let right_class_table_offset = usize::from(ctxt.read_u16be()?);
+                let array_offset = usize::from(ctxt.read_u16be()?);
+                KernData::Format2(ClassTableKerning {
+                    scope,
+                    left_class_table: scope
+                        .offset(left_class_table_offset)
+                        .read::<ClassTable<'_>>()?,
+                    right_class_table: scope
+                        .offset(right_class_table_offset)
+                        .read::<ClassTable<'_>>()?,
+                    array_offset,
+                })
+            }
+            3 => {
+                let glyph_count = usize::from(ctxt.read_u16be()?);
+                let kern_value_count = usize::from(ctxt.read_u8()?);
+                let left_class_count = usize::from(ctxt.read_u8()?);
+                let right_class_count = usize::from(ctxt.read_u8()?);
+                let _flags = ctxt.read_u8()?;
+                KernData::Format3(IndexedKerning {
+                    kern_values: ctxt.read_array::<I16Be>(kern_value_count)?,
+                    left_classes: ctxt.read_slice(glyph_count)?,
+                    right_classes: ctxt.read_slice(glyph_count)?,
+                    right_class_count,
+                    kern_indices: ctxt.read_slice(left_class_count * right_class_count)?,
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(data))
+    }
+
+    fn into_subtable(self, data: KernData<'_>) -> KernSubtable<'_> {
+        KernSubtable {
+            vertical: self.vertical,
+            cross_stream: self.cross_stream,
+            minimum: self.minimum,
+            replace: self.replace,
+            variation: self.variation,
+            data,
+        }
+    }
+}
+
+impl ReadFrom for KernPair {
+    type ReadType = (U16Be, U16Be, I16Be);
+
+    fn read_from((left, right, value): (u16, u16, i16)) -> Self {
+        KernPair { left, right, value }
+    }
+}
+
+impl ReadBinary for ClassTable<'_> {
+    type HostType<'a> = ClassTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let first_glyph = ctxt.read_u16be()?;
+        let glyph_count = usize::from(ctxt.read_u16be()?);
+        let offsets = ctxt.read_array::<U16Be>(glyph_count)?;
+        Ok(ClassTable {
+            first_glyph,
+            offsets,
+        })
+    }
+}
+
+impl ClassTable<'_> {
+    fn offset(&self, glyph: u16) -> usize {
+        match glyph.checked_sub(self.first_glyph).map(usize::from) {
+            Some(index) if index < self.offsets.len() => usize::from(self.offsets.get_item(index)),
+            _ => 0,
+        }
+    }
+}
+
+impl<'a> KernTable<'a> {
+    /// Calculate the horizontal kerning of a run of glyphs.
+    ///
+    /// Returns the adjustment to the advance of each glyph, in font units. Only subtables for
+    /// horizontal text that apply along the line are used. Marks should be removed from `glyphs`
+    /// beforehand.
+    pub fn horizontal_kerning(&self, glyphs: &[u16]) -> Result<Vec<i32>, ParseError> {
+        let mut adjustments = vec![0; glyphs.len()];
+        for subtable in &self.subtables {
+            if subtable.vertical || subtable.cross_stream || subtable.minimum || subtable.variation
+            {
+                continue;
+            }
+            match &subtable.data {
+                KernData::Format1(state_table) => state_table.apply(glyphs, &mut adjustments)?,
+                data => {
+                    for (i, pair) in glyphs.windows(2).enumerate() {
+                        if let Some(value) = data.pair_value(pair[0], pair[1]) {
+                            if subtable.replace {
+                                adjustments[i] = i32::from(value);
+                            } else {
+                                adjustments[i] += i32::from(value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(adjustments)
+    }
+}
+
+impl KernTable<'_> {
+    /// Returns a `kern` table that only contains the kerning pairs of the glyphs in `glyphs`, with
+    /// their new ids, or `None` if no pairs remain.
+    ///
+    /// The table is written in the Microsoft form. Only format 0 subtables are retained, since
+    /// the other formats can't be expressed in it or refer to the glyphs through classes and
+    /// state tables.
+    pub(crate) fn subset(
+        &self,
+        glyphs: &impl SubsetGlyphs,
+    ) -> Result<Option<WriteBuffer>, WriteError> {
+        let new_id = |glyph: u16| match glyphs.new_id(glyph) {
+            0 if glyph != 0 => None,
+            new_id => Some(new_id),
+        };
+
+        let mut subtables = Vec::new();
+        for subtable in &self.subtables {
+            let pairs = match &subtable.data {
+                KernData::Format0(pairs) if !subtable.variation => pairs,
+                _ => continue,
+            };
+            let mut new_pairs = pairs
+                .iter()
+                .filter_map(|pair| {
+                    Some(KernPair {
+                        left: new_id(pair.left)?,
+                        right: new_id(pair.right)?,
+                        value: pair.value,
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !new_pairs.is_empty() {
+                new_pairs.sort_unstable_by_key(|pair| (pair.left, pair.right));
+                subtables.push((subtable, new_pairs));
+            }
+        }
+        if subtables.is_empty() {
+            return Ok(None);
+        }
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, 0u16)?; // version
+        U16Be::write(&mut table, u16::try_from(subtables.len())?)?;
+        for (subtable, pairs) in subtables {
+            let coverage = u16::from(!subtable.vertical)
+                | u16::from(subtable.minimum) << 1
+                | u16::from(subtable.cross_stream) << 2
+                | u16::from(subtable.replace) << 3;
+            let pair_count = u16::try_from(pairs.len())?;
+            let entry_selector = 15u16.saturating_sub(pair_count.leading_zeros() as u16);
+            let search_range = 6 * (1usize << entry_selector);
+            let range_shift = 6 * pairs.len() - search_range;
+            // The length and search fields of large subtables overflow, as they do in fonts, so
+            // only their low 16 bits are written
+            U16Be::write(&mut table, 0u16)?; // version
+            U16Be::write(&mut table, (14 + 6 * pairs.len()) as u16)?;
+            U16Be::write(&mut table, coverage)?;
+            U16Be::write(&mut table, pair_count)?;
+            U16Be::write(&mut table, search_range as u16)?;
+            U16Be::write(&mut table, entry_selector)?;
+            U16Be::write(&mut table, range_shift as u16)?;
+            for pair in pairs {
+                U16Be::write(&mut table, pair.left)?;
+                U16Be::write(&mut table, pair.right)?;
+                I16Be::write(&mut table, pair.value)?;
+            }
+        }
+        Ok(Some(table))
+    }
+}
+
+impl KernData<'_> {
+    /// Returns the kerning value of a pair of glyphs.
+    ///
+    /// Returns `None` for format 1 subtables, which are contextual.
+    pub fn pair_value(&self, left: u16, right: u16) -> Option<i16> {
+        let value = match self {
+            KernData::Format0(pairs) => pairs
+                .binary_search_by(|pair| (pair.left, pair.right).cmp(&(left, right)))
+                .ok()
+                .map(|index| pairs.get_item(index).value),
+            KernData::Format1(_) => None,
+            KernData::Format2(table) => table.value(left, right),
+            KernData::Format3(table) => table.value(left, right),
+        };
+        value.filter(|&value| value != 0)
+    }
+}
+
+impl ClassTableKerning<'_> {
+    fn value(&self, left: u16, right: u16) -> Option<i16> {
+        // The left class is the offset of a row of the array and the right class the offset
+        // within the row, both from the start of the subtable.
+        let offset = self.left_class_table.offset(left) + self.right_class_table.offset(right);
+        if offset < self.array_offset {
+            return None;
+        }
+        self.scope.offset(offset).ctxt().read_i16be().ok()
+    }
+}
+
+impl IndexedKerning<'_> {
+    fn value(&self, left: u16, right: u16) -> Option<i16> {
+        let left_class = usize::from(*self.left_classes.get(usize::from(left))?);
+        let right_class = usize::from(*self.right_classes.get(usize::from(right))?);
+        let index = *self
+            .kern_indices
+            .get(left_class * self.right_class_count + right_class)?;
+        self.kern_values.read_item(usize::from(index)).ok()
+    }
+}
+
+impl StateTable<'_> {
+    const END_OF_TEXT: u16 = 0;
+    const OUT_OF_BOUNDS: u16 = 1;
+
+    fn glyph_class(&self, glyph: u16) -> Result<u16, ParseError> {
+        let mut ctxt = self.class_table.ctxt();
+        let first_glyph = ctxt.read_u16be()?;
+        let glyph_count = ctxt.read_u16be()?;
+        match glyph.checked_sub(first_glyph) {
+            Some(index) if index < glyph_count => {
+                let class = ctxt.read_slice(usize::from(glyph_count))?[usize::from(index)];
+                Ok(u16::from(class))
+            }
+            _ => Ok(Self::OUT_OF_BOUNDS),
+        }
+    }
+
+    // Runs the state machine over `glyphs`. Kerning values are applied to glyphs popped from the
+    // stack, moving them and the glyphs after them, so they're added to the advance of the glyph
+    // before.
+    fn apply(&self, glyphs: &[u16], adjustments: &mut [i32]) -> Result<(), ParseError> {
+        let mut state = 0;
+        let mut stack = Vec::with_capacity(MAX_STACK_DEPTH);
+        let mut dont_advance_count = 0;
+        let mut i = 0;
+        loop {
+            let class = match glyphs.get(i) {
+                Some(&glyph) => self.glyph_class(glyph)?,
+                None => Self::END_OF_TEXT,
+            };
+            let row = usize::from(self.state_array_offset) + state * usize::from(self.state_size);
+            let entry_index = self
+                .scope
+                .offset(row + usize::from(class))
+                .ctxt()
+                .read_u8()?;
+            let mut entry = self.entry_table.offset(usize::from(entry_index) * 4).ctxt();
+            let new_state = entry.read_u16be()?;
+            let flags = entry.read_u16be()?;
+
+            if flags & 0x8000 != 0 && i < glyphs.len() {
+                if stack.len() == MAX_STACK_DEPTH {
+                    stack.clear();
+                }
+                stack.push(i);
+            }
+            let value_offset = usize::from(flags & 0x3FFF);
+            if value_offset != 0 {
+                let mut values = self.scope.offset(value_offset).ctxt();
+                while let Some(index) = stack.pop() {
+                    let value = values.read_i16be()?;
+                    // 0x8001 resets cross-stream kerning, which doesn't apply here
+                    if value != 0x8001_u16 as i16 && index > 0 {
+                        adjustments[index - 1] += i32::from(value & !1);
+                    }
+                    if value & 1 != 0 {
+                        break;
+                    }
+                }
+            }
+
+            state = usize::from(new_state.saturating_sub(self.state_array_offset))
+                / usize::from(self.state_size);
+            if i >= glyphs.len() {
+                break;
+            }
+            if flags & 0x4000 == 0 || dont_advance_count == MAX_DONT_ADVANCE {
+                i += 1;
+                dont_advance_count = 0;
+            } else {
+                dont_advance_count += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::{FontTableProvider, OpenTypeFont};
+    use crate::tag;
+    use crate::tests::read_fixture;
+
+    fn kern_data(path: &str) -> Vec<u8> {
+        let buffer = read_fixture(path);
+        let otf = ReadScope::new(&buffer).read::<OpenTypeFont<'_>>().unwrap();
+        let provider = otf.table_provider(0).unwrap();
+        provider.read_table_data(tag::KERN).unwrap().into_owned()
+    }
+
+    #[test]
+    fn test_read_microsoft() {
+        let data = kern_data("tests/fonts/kern/KernMicrosoftTest.ttf");
+        let kern = ReadScope::new(&data).read::<KernTable<'_>>().unwrap();
+        assert_eq!(kern.subtables.len(), 2);
+        assert!(matches!(kern.subtables[0].data, KernData::Format0(_)));
+        assert!(matches!(kern.subtables[1].data, KernData::Format2(_)));
+        assert_eq!(kern.subtables[0].data.pair_value(2, 3), Some(-80));
+        assert_eq!(kern.subtables[0].data.pair_value(3, 2), None);
+        // W o is kerned by the class subtable, W A is in a row of zeros
+        assert_eq!(kern.subtables[1].data.pair_value(6, 5), Some(-50));
+        assert_eq!(kern.subtables[1].data.pair_value(6, 2), None);
+        assert_eq!(kern.subtables[1].data.pair_value(2, 5), None);
+    }
+
+    #[test]
+    fn test_apple_state_table() {
+        let data = kern_data("tests/fonts/kern/KernAppleTest.ttf");
+        let kern = ReadScope::new(&data).read::<KernTable<'_>>().unwrap();
+        assert_eq!(kern.subtables.len(), 2);
+        assert!(matches!(kern.subtables[0].data, KernData::Format1(_)));
+        assert!(matches!(kern.subtables[1].data, KernData::Format3(_)));
+        // T o T o A V
+        assert_eq!(
+            kern.horizontal_kerning(&[4, 5, 4, 5, 2, 3]).unwrap(),
+            vec![-60, 0, -60, 0, -70, 0]
+        );
+        assert_eq!(kern.horizontal_kerning(&[5, 4]).unwrap(), vec![0, 0]);
+        assert_eq!(kern.horizontal_kerning(&[]).unwrap(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_bad_version() {
+        let data = [0, 2, 0, 0];
+        assert!(ReadScope::new(&data).read::<KernTable<'_>>().is_err());
+    }
+}
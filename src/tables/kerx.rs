@@ -0,0 +1,333 @@
+//! Parsing of the `kerx` and `ankr` tables.
+//!
+//! > The extended kerning table (tag name: 'kerx') allows you to specify the intercharacter
+//! > spacing for the glyphs in a font.
+//!
+//! — <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6kerx.html>
+//!
+//! The `ankr` table holds the anchor points used by format 4 `kerx` subtables.
+//!
+//! <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6ankr.html>
+
+use std::convert::TryFrom;
+
+use log::warn;
+
+use crate::binary::read::{ReadArray, ReadBinary, ReadCtxt, ReadScope};
+use crate::error::ParseError;
+use crate::layout::Anchor;
+use crate::tables::aat::{ExtendedStateTable, LookupTable};
+use crate::tables::kern::KernPair;
+
+/// `kerx` table
+pub struct KerxTable<'a> {
+    /// The supported subtables of the table.
+    pub subtables: Vec<KerxSubtable<'a>>,
+}
+
+/// A `kerx` subtable.
+pub struct KerxSubtable<'a> {
+    /// The coverage flags of the subtable.
+    pub coverage: u32,
+    /// The number of variation tuples of the subtable values.
+    pub tuple_count: u32,
+    /// The kerning data of the subtable.
+    pub data: KerxData<'a>,
+}
+
+/// The kerning data of a `kerx` subtable.
+pub enum KerxData<'a> {
+    /// Ordered list of kerning pairs.
+    Format0(ReadArray<'a, KernPair>),
+    /// Contextual kerning driven by a state table.
+    Format1(ContextualKerning<'a>),
+    /// Two-dimensional array of kerning values indexed by glyph class.
+    Format2(ClassKerning<'a>),
+    /// Attachment of glyphs to a marked glyph, driven by a state table.
+    Format4(AttachmentKerning<'a>),
+    /// Two-dimensional array of kerning values indexed by row and column.
+    Format6(IndexKerning<'a>),
+}
+
+/// A format 1 subtable.
+///
+/// The entries of the state table have the index of the kerning values to apply.
+pub struct ContextualKerning<'a> {
+    pub state_table: ExtendedStateTable<'a>,
+    values: ReadScope<'a>,
+}
+
+/// A format 2 subtable.
+pub struct ClassKerning<'a> {
+    left_class_table: LookupTable<'a>,
+    right_class_table: LookupTable<'a>,
+    array: ReadScope<'a>,
+}
+
+/// A format 4 subtable.
+///
+/// The entries of the state table have the index of the attachment action to perform.
+pub struct AttachmentKerning<'a> {
+    pub state_table: ExtendedStateTable<'a>,
+    /// How the attachment points are specified.
+    pub action_type: AttachmentActionType,
+    actions: ReadScope<'a>,
+}
+
+/// The type of the actions of a format 4 `kerx` subtable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttachmentActionType {
+    /// Actions are pairs of glyph outline point indices.
+    ControlPoints,
+    /// Actions are pairs of `ankr` anchor point indices.
+    AnchorPoints,
+    /// Actions are pairs of coordinates.
+    Coordinates,
+}
+
+/// A format 6 subtable.
+pub struct IndexKerning<'a> {
+    row_index_table: LookupTable<'a>,
+    column_index_table: LookupTable<'a>,
+    array: ReadScope<'a>,
+}
+
+/// `ankr` table
+pub struct AnkrTable<'a> {
+    lookup_table: LookupTable<'a>,
+    glyph_data: ReadScope<'a>,
+}
+
+impl KerxSubtable<'_> {
+    const VERTICAL: u32 = 0x8000_0000;
+    const CROSS_STREAM: u32 = 0x4000_0000;
+    const VARIATION: u32 = 0x2000_0000;
+
+    /// Returns `true` if the subtable applies to horizontal text.
+    pub fn is_horizontal(&self) -> bool {
+        self.coverage & Self::VERTICAL == 0
+    }
+
+    /// Returns `true` if the kerning values are perpendicular to the flow of text.
+    pub fn is_cross_stream(&self) -> bool {
+        self.coverage & Self::CROSS_STREAM != 0
+    }
+
+    /// Returns `true` if the kerning values vary with the variation tuple of the font.
+    pub fn is_variation(&self) -> bool {
+        self.coverage & Self::VARIATION != 0
+    }
+}
+
+impl ReadBinary for KerxTable<'_> {
+    type HostType<'a> = KerxTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let version = ctxt.read_u16be()?;
+        ctxt.check_version((2..=4).contains(&version))?;
+        let _padding = ctxt.read_u16be()?;
+        let subtable_count = ctxt.read_u32be()?;
+        let mut subtables = Vec::new();
+        for _ in 0..subtable_count {
+            let scope = ctxt.scope();
+            let length = usize::try_from(ctxt.read_u32be()?)?;
+            let coverage = ctxt.read_u32be()?;
+            let tuple_count = ctxt.read_u32be()?;
+            // Offsets of some formats are from the start of the subtable, including its header
+            let subtable_scope = scope.offset_length(0, length)?;
+            match read_subtable_data(coverage & 0xFF, subtable_scope) {
+                Ok(Some(data)) => subtables.push(KerxSubtable {
+                    coverage,
+                    tuple_count,
+                    data,
+                }),
+                Ok(None) => warn!("skipping kerx subtable with format {}", coverage & 0xFF),
+                Err(err) => warn!("skipping invalid kerx subtable: {}", err),
+            }
+            *ctxt = scope.offset(length).ctxt();
+        }
+        Ok(KerxTable { subtables })
+    }
+}
+
+fn read_subtable_data(
+    format: u32,
+    scope: ReadScope<'_>,
+) -> Result<Option<KerxData<'_>>, ParseError> {
+    let body = scope.offset(12);
+    let mut ctxt = body.ctxt();
+    let data = match format {
+        0 => {
+            let pair_count = usize::try_from(ctxt.read_u32be()?)?;
+            let _search_range = ctxt.read_u32be()?;
+            let _entry_selector = ctxt.read_u32be()?;
+            let _range_shift = ctxt.read_u32be()?;
+            KerxData::Format0(ctxt.read_array::<KernPair>(pair_count)?)
+        }
+        1 => {
+            let state_table = ctxt.read_dep::<ExtendedStateTable<'_>>(1)?;
+            let values_offset = usize::try_from(ctxt.read_u32be()?)?;
+            KerxData::Format1(ContextualKerning {
+                state_table,
+                values: body.offset(values_offset),
+            })
+        }
+        2 => {
+            let _row_width = ctxt.read_u32be()?;
+            let left_class_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let right_class_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let array_offset = usize::try_from(ctxt.read_u32be()?)?;
+            KerxData::Format2(ClassKerning {
+                left_class_table: scope
+                    .offset(left_class_table_offset)
+                    .read::<LookupTable<'_>>()?,
+                right_class_table: scope
+                    .offset(right_class_table_offset)
+                    .read::<LookupTable<'_>>()?,
+                array: scope.offset(array_offset),
+            })
+        }
+        4 => {
+            let state_table = ctxt.read_dep::<ExtendedStateTable<'_>>(1)?;
+            let flags = ctxt.read_u32be()?;
+            let action_type = match flags >> 30 {
+                0 => AttachmentActionType::ControlPoints,
+                1 => AttachmentActionType::AnchorPoints,
+                2 => AttachmentActionType::Coordinates,
+                _ => return Err(ParseError::BadValue),
+            };
+            let actions_offset = usize::try_from(flags & 0x00FF_FFFF)?;
+            KerxData::Format4(AttachmentKerning {
+                state_table,
+                action_type,
+                actions: body.offset(actions_offset),
+            })
+        }
+        6 => {
+            let flags = ctxt.read_u32be()?;
+            // Long values aren't supported
+            if flags & 1 != 0 {
+                return Ok(None);
+            }
+            let _row_count = ctxt.read_u16be()?;
+            let _column_count = ctxt.read_u16be()?;
+            let row_index_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let column_index_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let array_offset = usize::try_from(ctxt.read_u32be()?)?;
+            KerxData::Format6(IndexKerning {
+                row_index_table: scope
+                    .offset(row_index_table_offset)
+                    .read::<LookupTable<'_>>()?,
+                column_index_table: scope
+                    .offset(column_index_table_offset)
+                    .read::<LookupTable<'_>>()?,
+                array: scope.offset(array_offset),
+            })
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(data))
+}
+
+impl KerxData<'_> {
+    /// Returns the kerning value of a pair of glyphs.
+    ///
+    /// Returns `None` for format 1 and 4 subtables, which are contextual.
+    pub fn pair_value(&self, left: u16, right: u16) -> Option<i16> {
+        let value = match self {
+            KerxData::Format0(pairs) => pairs
+                .binary_search_by(|pair| (pair.left, pair.right).cmp(&(left, right)))
+                .ok()
+                .map(|index| pairs.get_item(index).value),
+            KerxData::Format1(_) | KerxData::Format4(_) => None,
+            KerxData::Format2(table) => {
+                // Unlike the `kern` table the class values are indices rather than byte offsets
+                let row = table.left_class_table.value(left).unwrap_or(0);
+                let column = table.right_class_table.value(right).unwrap_or(0);
+                let index = usize::from(row) + usize::from(column);
+                table.array.offset(index * 2).ctxt().read_i16be().ok()
+            }
+            KerxData::Format6(table) => {
+                let row = table.row_index_table.value(left).unwrap_or(0);
+                let column = table.column_index_table.value(right).unwrap_or(0);
+                let index = usize::from(row) + usize::from(column);
+                table.array.offset(index * 2).ctxt().read_i16be().ok()
+            }
+        };
+        value.filter(|&value| value != 0)
+    }
+}
+
+impl ContextualKerning<'_> {
+    /// The entry pushes the current glyph onto the kerning stack.
+    pub const PUSH: u16 = 0x8000;
+    /// The entry doesn't advance to the next glyph.
+    pub const DONT_ADVANCE: u16 = 0x4000;
+    /// The entry clears the kerning stack.
+    pub const RESET: u16 = 0x2000;
+
+    /// Returns the kerning value at `index`.
+    pub fn value(&self, index: usize) -> Result<i16, ParseError> {
+        Ok(self.values.offset(index * 2).ctxt().read_i16be()?)
+    }
+}
+
+impl AttachmentKerning<'_> {
+    /// The entry marks the current glyph.
+    pub const MARK: u16 = 0x8000;
+    /// The entry doesn't advance to the next glyph.
+    pub const DONT_ADVANCE: u16 = 0x4000;
+
+    /// Returns the values of the action at `index`.
+    ///
+    /// These are two point indices for control point and anchor point actions, and the x and y
+    /// coordinates of the marked glyph and the current glyph for coordinate actions.
+    pub fn action(&self, index: u16) -> Result<Vec<i16>, ParseError> {
+        let count = match self.action_type {
+            AttachmentActionType::ControlPoints | AttachmentActionType::AnchorPoints => 2,
+            AttachmentActionType::Coordinates => 4,
+        };
+        let mut ctxt = self.actions.offset(usize::from(index) * count * 2).ctxt();
+        (0..count)
+            .map(|_| ctxt.read_i16be().map_err(ParseError::from))
+            .collect()
+    }
+}
+
+impl ReadBinary for AnkrTable<'_> {
+    type HostType<'a> = AnkrTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let scope = ctxt.scope();
+        let version = ctxt.read_u16be()?;
+        ctxt.check_version(version == 0)?;
+        let _flags = ctxt.read_u16be()?;
+        let lookup_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+        let glyph_data_offset = usize::try_from(ctxt.read_u32be()?)?;
+        Ok(AnkrTable {
+            lookup_table: scope
+                .offset(lookup_table_offset)
+                .read::<LookupTable<'_>>()?,
+            glyph_data: scope.offset(glyph_data_offset),
+        })
+    }
+}
+
+impl AnkrTable<'_> {
+    /// Returns anchor point `index` of `glyph`.
+    pub fn anchor(&self, glyph: u16, index: u16) -> Option<Anchor> {
+        let offset = self.lookup_table.value(glyph)?;
+        let mut ctxt = self.glyph_data.offset(usize::from(offset)).ctxt();
+        let count = ctxt.read_u32be().ok()?;
+        if u32::from(index) >= count {
+            return None;
+        }
+        let mut ctxt = self
+            .glyph_data
+            .offset(usize::from(offset) + 4 + usize::from(index) * 4)
+            .ctxt();
+        let x = ctxt.read_i16be().ok()?;
+        let y = ctxt.read_i16be().ok()?;
+        Some(Anchor { x, y })
+    }
+}
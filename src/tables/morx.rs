@@ -0,0 +1,283 @@
+//! Parsing of the `morx` table.
+//!
+//! > The extended glyph metamorphosis table (tag name: 'morx') specifies a set of
+//! > transformations that can apply to the glyphs of your font.
+//!
+//! — <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6morx.html>
+//!
+//! The transformations are applied by the [aat](crate::aat) module.
+
+use std::convert::TryFrom;
+
+use log::warn;
+
+use crate::binary::read::{ReadArray, ReadBinary, ReadCtxt, ReadFrom, ReadScope};
+use crate::binary::{U16Be, U32Be};
+use crate::error::ParseError;
+use crate::tables::aat::{ExtendedStateTable, LookupTable};
+
+/// `morx` table
+pub struct MorxTable<'a> {
+    /// The chains of subtables, which are applied in order.
+    pub chains: Vec<Chain<'a>>,
+}
+
+/// A chain of `morx` subtables.
+pub struct Chain<'a> {
+    /// The subtable feature flags that are enabled by default.
+    pub default_flags: u32,
+    /// The features that enable and disable the subtables of the chain.
+    pub features: ReadArray<'a, Feature>,
+    /// The supported subtables of the chain.
+    pub subtables: Vec<MorxSubtable<'a>>,
+}
+
+/// A feature of a `morx` chain.
+///
+/// When the feature setting is selected the flags of the chain are adjusted to
+/// `(flags & disable_flags) | enable_flags`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Feature {
+    /// The feature type, such as 1 for ligatures.
+    pub feature_type: u16,
+    /// The feature setting, such as 2 for common ligatures on.
+    pub feature_setting: u16,
+    /// The flags enabled by the setting.
+    pub enable_flags: u32,
+    /// The flags that are left enabled by the setting.
+    pub disable_flags: u32,
+}
+
+/// A `morx` subtable.
+pub struct MorxSubtable<'a> {
+    /// The coverage flags of the subtable.
+    pub coverage: u32,
+    /// The subtable is applied when one of these flags is enabled in the chain.
+    pub sub_feature_flags: u32,
+    /// The data of the subtable.
+    pub data: SubtableData<'a>,
+}
+
+/// The data of a `morx` subtable.
+pub enum SubtableData<'a> {
+    /// Rearranges a range of glyphs.
+    Rearrangement(ExtendedStateTable<'a>),
+    /// Substitutes glyphs depending on their context.
+    Contextual(ContextualSubtable<'a>),
+    /// Forms ligatures.
+    Ligature(LigatureSubtable<'a>),
+    /// Substitutes glyphs regardless of context.
+    Noncontextual(LookupTable<'a>),
+    /// Inserts glyphs.
+    Insertion(InsertionSubtable<'a>),
+}
+
+/// A contextual glyph substitution subtable.
+///
+/// The entries of the state table have a mark index and a current index, which are indices of
+/// substitution tables.
+pub struct ContextualSubtable<'a> {
+    pub state_table: ExtendedStateTable<'a>,
+    substitution_tables: ReadScope<'a>,
+}
+
+/// A ligature subtable.
+///
+/// The entries of the state table have the index of the first ligature action to perform.
+pub struct LigatureSubtable<'a> {
+    pub state_table: ExtendedStateTable<'a>,
+    lig_actions: ReadScope<'a>,
+    components: ReadScope<'a>,
+    ligatures: ReadScope<'a>,
+}
+
+/// A glyph insertion subtable.
+///
+/// The entries of the state table have the indices of the glyphs to insert at the current and
+/// marked glyphs.
+pub struct InsertionSubtable<'a> {
+    pub state_table: ExtendedStateTable<'a>,
+    insertion_actions: ReadScope<'a>,
+}
+
+impl MorxSubtable<'_> {
+    const VERTICAL: u32 = 0x8000_0000;
+    const DESCENDING: u32 = 0x4000_0000;
+    const ALL_DIRECTIONS: u32 = 0x2000_0000;
+
+    /// Returns `true` if the subtable applies to horizontal text.
+    pub fn is_horizontal(&self) -> bool {
+        self.coverage & (Self::VERTICAL | Self::ALL_DIRECTIONS) != Self::VERTICAL
+    }
+
+    /// Returns `true` if the subtable processes glyphs in descending order.
+    pub fn is_descending(&self) -> bool {
+        self.coverage & Self::DESCENDING != 0
+    }
+}
+
+impl ReadBinary for MorxTable<'_> {
+    type HostType<'a> = MorxTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let version = ctxt.read_u16be()?;
+        ctxt.check_version(version == 2 || version == 3)?;
+        let _unused = ctxt.read_u16be()?;
+        let chain_count = ctxt.read_u32be()?;
+        let mut chains = Vec::new();
+        for _ in 0..chain_count {
+            let scope = ctxt.scope();
+            let default_flags = ctxt.read_u32be()?;
+            let chain_length = usize::try_from(ctxt.read_u32be()?)?;
+            let feature_count = usize::try_from(ctxt.read_u32be()?)?;
+            let subtable_count = ctxt.read_u32be()?;
+            let features = ctxt.read_array::<Feature>(feature_count)?;
+            let mut subtables = Vec::new();
+            for _ in 0..subtable_count {
+                let length = usize::try_from(ctxt.read_u32be()?)?;
+                let coverage = ctxt.read_u32be()?;
+                let sub_feature_flags = ctxt.read_u32be()?;
+                let subtable_scope = ctxt.read_scope(length.saturating_sub(12))?;
+                match read_subtable_data(coverage & 0xFF, subtable_scope) {
+                    Ok(Some(data)) => subtables.push(MorxSubtable {
+                        coverage,
+                        sub_feature_flags,
+                        data,
+                    }),
+                    Ok(None) => warn!("skipping morx subtable with type {}", coverage & 0xFF),
+                    Err(err) => warn!("skipping invalid morx subtable: {}", err),
+                }
+            }
+            chains.push(Chain {
+                default_flags,
+                features,
+                subtables,
+            });
+            // Skip the subtable glyph coverage tables of version 3
+            *ctxt = scope.offset(chain_length).ctxt();
+        }
+        Ok(MorxTable { chains })
+    }
+}
+
+fn read_subtable_data(
+    subtable_type: u32,
+    scope: ReadScope<'_>,
+) -> Result<Option<SubtableData<'_>>, ParseError> {
+    let mut ctxt = scope.ctxt();
+    let data = match subtable_type {
+        0 => SubtableData::Rearrangement(ctxt.read_dep::<ExtendedStateTable<'_>>(0)?),
+        1 => {
+            let state_table = ctxt.read_dep::<ExtendedStateTable<'_>>(2)?;
+            let substitution_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+            SubtableData::Contextual(ContextualSubtable {
+                state_table,
+                substitution_tables: scope.offset(substitution_table_offset),
+            })
+        }
+        2 => {
+            let state_table = ctxt.read_dep::<ExtendedStateTable<'_>>(1)?;
+            let lig_action_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let component_offset = usize::try_from(ctxt.read_u32be()?)?;
+            let ligature_offset = usize::try_from(ctxt.read_u32be()?)?;
+            SubtableData::Ligature(LigatureSubtable {
+                state_table,
+                lig_actions: scope.offset(lig_action_offset),
+                components: scope.offset(component_offset),
+                ligatures: scope.offset(ligature_offset),
+            })
+        }
+        4 => SubtableData::Noncontextual(ctxt.read::<LookupTable<'_>>()?),
+        5 => {
+            let state_table = ctxt.read_dep::<ExtendedStateTable<'_>>(2)?;
+            let insertion_action_offset = usize::try_from(ctxt.read_u32be()?)?;
+            SubtableData::Insertion(InsertionSubtable {
+                state_table,
+                insertion_actions: scope.offset(insertion_action_offset),
+            })
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(data))
+}
+
+impl ReadFrom for Feature {
+    type ReadType = ((U16Be, U16Be), U32Be, U32Be);
+
+    fn read_from(
+        ((feature_type, feature_setting), enable_flags, disable_flags): ((u16, u16), u32, u32),
+    ) -> Self {
+        Feature {
+            feature_type,
+            feature_setting,
+            enable_flags,
+            disable_flags,
+        }
+    }
+}
+
+impl<'a> ContextualSubtable<'a> {
+    /// The entry sets the mark to the current glyph.
+    pub const SET_MARK: u16 = 0x8000;
+    /// The entry doesn't advance to the next glyph.
+    pub const DONT_ADVANCE: u16 = 0x4000;
+
+    /// Returns the substitution table at `index`.
+    pub fn substitution_table(&self, index: u16) -> Result<LookupTable<'a>, ParseError> {
+        let offset = self
+            .substitution_tables
+            .offset(usize::from(index) * 4)
+            .ctxt()
+            .read_u32be()?;
+        self.substitution_tables
+            .offset(usize::try_from(offset)?)
+            .read::<LookupTable<'_>>()
+    }
+}
+
+impl LigatureSubtable<'_> {
+    /// The entry pushes the current glyph onto the component stack.
+    pub const SET_COMPONENT: u16 = 0x8000;
+    /// The entry doesn't advance to the next glyph.
+    pub const DONT_ADVANCE: u16 = 0x4000;
+    /// The entry performs ligature actions.
+    pub const PERFORM_ACTION: u16 = 0x2000;
+
+    /// The action is the last of the list.
+    pub const ACTION_LAST: u32 = 0x8000_0000;
+    /// The action stores the ligature.
+    pub const ACTION_STORE: u32 = 0x4000_0000;
+
+    /// Returns the ligature action at `index`.
+    pub fn lig_action(&self, index: usize) -> Result<u32, ParseError> {
+        Ok(self.lig_actions.offset(index * 4).ctxt().read_u32be()?)
+    }
+
+    /// Returns the component value at `index`.
+    pub fn component(&self, index: usize) -> Result<u16, ParseError> {
+        Ok(self.components.offset(index * 2).ctxt().read_u16be()?)
+    }
+
+    /// Returns the ligature glyph at `index`.
+    pub fn ligature(&self, index: usize) -> Result<u16, ParseError> {
+        Ok(self.ligatures.offset(index * 2).ctxt().read_u16be()?)
+    }
+}
+
+impl InsertionSubtable<'_> {
+    /// The entry sets the mark to the current glyph.
+    pub const SET_MARK: u16 = 0x8000;
+    /// The entry doesn't advance to the next glyph.
+    pub const DONT_ADVANCE: u16 = 0x4000;
+    /// Glyphs are inserted before the current glyph rather than after it.
+    pub const CURRENT_INSERT_BEFORE: u16 = 0x0800;
+    /// Glyphs are inserted before the marked glyph rather than after it.
+    pub const MARKED_INSERT_BEFORE: u16 = 0x0400;
+
+    /// Returns `count` glyphs to insert, starting at `index`.
+    pub fn insertion_glyphs(&self, index: u16, count: usize) -> Result<Vec<u16>, ParseError> {
+        let mut ctxt = self.insertion_actions.offset(usize::from(index) * 2).ctxt();
+        let glyphs = ctxt.read_array::<U16Be>(count)?;
+        Ok(glyphs.to_vec())
+    }
+}
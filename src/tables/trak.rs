@@ -0,0 +1,157 @@
+//! Parsing of the `trak` table.
+//!
+//! > The tracking table (tag: 'trak') allows you to design glyphs for your font that have a
+//! > variety of tracking values, and to have those tracking values depend on the point size.
+//!
+//! — <https://developer.apple.com/fonts/TrueType-Reference-Manual/RM06/Chap6trak.html>
+
+use std::convert::TryFrom;
+
+use crate::binary::read::{ReadBinary, ReadCtxt, ReadScope};
+use crate::binary::I16Be;
+use crate::error::ParseError;
+use crate::tables::Fixed;
+
+/// `trak` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrakTable {
+    /// Tracking for horizontal text.
+    pub horizontal: Option<TrackData>,
+    /// Tracking for vertical text.
+    pub vertical: Option<TrackData>,
+}
+
+/// The tracking values for one text direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackData {
+    /// The point sizes that tracking values are given for, in increasing order.
+    pub sizes: Vec<Fixed>,
+    /// The tracks, such as normal, tight, or loose.
+    pub tracks: Vec<Track>,
+}
+
+/// A track of a `trak` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    /// The track value. 0 is normal tracking, -1 is tight, and 1 is loose.
+    pub track: Fixed,
+    /// The `name` table id of the name of the track.
+    pub name_index: u16,
+    /// The tracking value, in font units, for each size.
+    pub values: Vec<i16>,
+}
+
+impl ReadBinary for TrakTable {
+    type HostType<'a> = TrakTable;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let scope = ctxt.scope();
+        let version = ctxt.read::<Fixed>()?;
+        ctxt.check_version(version == Fixed::from_raw(0x0001_0000))?;
+        let format = ctxt.read_u16be()?;
+        ctxt.check_version(format == 0)?;
+        let horizontal_offset = ctxt.read_u16be()?;
+        let vertical_offset = ctxt.read_u16be()?;
+        let _reserved = ctxt.read_u16be()?;
+        Ok(TrakTable {
+            horizontal: read_track_data(scope, horizontal_offset)?,
+            vertical: read_track_data(scope, vertical_offset)?,
+        })
+    }
+}
+
+fn read_track_data(scope: ReadScope<'_>, offset: u16) -> Result<Option<TrackData>, ParseError> {
+    if offset == 0 {
+        return Ok(None);
+    }
+    let mut ctxt = scope.offset(usize::from(offset)).ctxt();
+    let track_count = ctxt.read_u16be()?;
+    let size_count = usize::from(ctxt.read_u16be()?);
+    let size_table_offset = usize::try_from(ctxt.read_u32be()?)?;
+    let sizes = scope
+        .offset(size_table_offset)
+        .ctxt()
+        .read_array::<Fixed>(size_count)?
+        .to_vec();
+    let mut tracks = Vec::with_capacity(usize::from(track_count));
+    for _ in 0..track_count {
+        let track = ctxt.read::<Fixed>()?;
+        let name_index = ctxt.read_u16be()?;
+        // Offsets of the values are from the start of the table
+        let values_offset = usize::from(ctxt.read_u16be()?);
+        let values = scope
+            .offset(values_offset)
+            .ctxt()
+            .read_array::<I16Be>(size_count)?
+            .to_vec();
+        tracks.push(Track {
+            track,
+            name_index,
+            values,
+        });
+    }
+    Ok(Some(TrackData { sizes, tracks }))
+}
+
+impl TrackData {
+    /// Returns the tracking value of `track` at `point_size`, in font units.
+    ///
+    /// Values between the sizes of the table are interpolated, and values outside them are
+    /// extrapolated from the nearest two sizes. Returns `None` if there is no such track.
+    pub fn tracking(&self, track: Fixed, point_size: f32) -> Option<i32> {
+        let track = self.tracks.iter().find(|entry| entry.track == track)?;
+        match (self.sizes.len(), track.values.as_slice()) {
+            (0, _) | (_, []) => None,
+            (1, [value, ..]) => Some(i32::from(*value)),
+            (size_count, values) => {
+                // Interpolate between the first size at or above `point_size` and the one before
+                let index = self
+                    .sizes
+                    .iter()
+                    .take(size_count - 1)
+                    .position(|&size| f32::from(size) >= point_size)
+                    .unwrap_or(size_count - 1)
+                    .max(1)
+                    - 1;
+                let size0 = f32::from(self.sizes[index]);
+                let size1 = f32::from(self.sizes[index + 1]);
+                let value0 = f32::from(*values.get(index)?);
+                let value1 = f32::from(*values.get(index + 1)?);
+                let t = if size0 == size1 {
+                    0.
+                } else {
+                    (point_size - size0) / (size1 - size0)
+                };
+                Some((value0 + t * (value1 - value0)).round() as i32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track_data() -> TrackData {
+        TrackData {
+            sizes: vec![Fixed::from(9.), Fixed::from(12.), Fixed::from(24.)],
+            tracks: vec![Track {
+                track: Fixed::from(0.),
+                name_index: 256,
+                values: vec![20, 0, -40],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_tracking() {
+        let data = track_data();
+        let normal = Fixed::from(0.);
+        assert_eq!(data.tracking(normal, 12.), Some(0));
+        assert_eq!(data.tracking(normal, 18.), Some(-20));
+        assert_eq!(data.tracking(normal, 9.), Some(20));
+        assert_eq!(data.tracking(normal, 6.), Some(40));
+        assert_eq!(data.tracking(normal, 36.), Some(-80));
+        assert_eq!(data.tracking(Fixed::from(1.), 12.), None);
+    }
+}
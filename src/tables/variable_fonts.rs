@@ -3,6 +3,7 @@
 //! Common tables pertaining to variable fonts.
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::fmt::Formatter;
@@ -14,9 +15,10 @@ use crate::binary::read::{
     ReadArray, ReadBinary, ReadBinaryDep, ReadCtxt, ReadFixedSizeDep, ReadFrom, ReadScope,
     ReadUnchecked,
 };
-use crate::binary::write::{WriteBinary, WriteContext};
+use crate::binary::write::{WriteBinary, WriteBuffer, WriteContext};
 use crate::binary::{I16Be, I32Be, U16Be, U32Be, I8, U8};
-use crate::error::{ParseError, WriteError};
+use crate::error::{ParseError, ReadWriteError, WriteError};
+use crate::subset::SubsetGlyphs;
 use crate::tables::variable_fonts::cvar::CvarTable;
 use crate::tables::variable_fonts::gvar::{GvarTable, NumPoints};
 use crate::tables::{F2Dot14, Fixed};
@@ -29,6 +31,7 @@ pub mod gvar;
 pub mod hvar;
 pub mod mvar;
 pub mod stat;
+pub mod vvar;
 
 pub use crate::tables::variable_fonts::fvar::{OwnedTuple, Tuple};
 
@@ -121,7 +124,7 @@ pub struct CvarVariationData<'a> {
     deltas: Vec<i16>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 enum PointNumbers {
     All(u32),
     Specific(Vec<u16>),
@@ -130,6 +133,68 @@ enum PointNumbers {
 /// A collection of point numbers that are shared between variations.
 pub struct SharedPointNumbers<'a>(&'a PointNumbers);
 
+/// A tuple variation from a `gvar` or `cvar` table that has been modified, ready to be written.
+struct OwnedTupleVariation {
+    /// The region of the variation space that the variation applies to.
+    region: Vec<RegionAxisCoordinates>,
+    /// The numbers of the points, or CVTs, that the deltas are for.
+    point_numbers: PointNumbers,
+    /// The deltas for each point. For `gvar` these are the x deltas followed by the y deltas.
+    deltas: Vec<i16>,
+}
+
+/// The limit placed on a variation axis by partial instancing, in normalized coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum NormalizedAxisLimit {
+    /// The axis is pinned to this value and removed from the font.
+    Pin(F2Dot14),
+    /// The axis is limited to the range from the first value to the second, which includes zero.
+    Range(F2Dot14, F2Dot14),
+}
+
+impl OwnedTupleVariation {
+    /// Returns the tuple variations that replace a tuple variation for `region` when the axes
+    /// are limited to `limits`.
+    ///
+    /// `deltas` are the deltas for the points in `point_numbers`. Variations that apply
+    /// regardless of the location in the new variation space are dropped, as are variations
+    /// where all the deltas round to zero.
+    fn limit_axes(
+        region: Vec<RegionAxisCoordinates>,
+        point_numbers: &PointNumbers,
+        deltas: &[f32],
+        limits: &[NormalizedAxisLimit],
+    ) -> Vec<OwnedTupleVariation> {
+        limit_region(region.into_iter(), limits)
+            .into_iter()
+            .filter(|(_, region)| {
+                region
+                    .iter()
+                    .any(|coords| coords.peak_coord != F2Dot14::from(0))
+            })
+            .filter_map(|(scalar, region)| {
+                // NOTE(cast): saturating
+                let deltas = deltas
+                    .iter()
+                    .map(|delta| (delta * scalar).round() as i16)
+                    .collect::<Vec<_>>();
+                deltas
+                    .iter()
+                    .any(|&delta| delta != 0)
+                    .then(|| OwnedTupleVariation {
+                        region,
+                        point_numbers: point_numbers.clone(),
+                        deltas,
+                    })
+            })
+            .collect()
+    }
+
+    fn peak(&self) -> Vec<F2Dot14> {
+        self.region.iter().map(|coords| coords.peak_coord).collect()
+    }
+}
+
 /// Item variation store.
 ///
 /// > Includes a variation region list, which defines the different regions of the font’s variation
@@ -178,7 +243,7 @@ pub struct VariationRegion<'a> {
     region_axes: ReadArray<'a, RegionAxisCoordinates>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct RegionAxisCoordinates {
     /// The region start coordinate value for the current axis.
     start_coord: F2Dot14,
@@ -202,7 +267,7 @@ struct DeltaSetIndexMap<'a> {
 /// [ItemVariationStore].
 ///
 /// <https://learn.microsoft.com/en-us/typography/opentype/spec/otvarcommonformats#associating-target-items-to-variation-data>
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct DeltaSetIndexMapEntry {
     /// Index into the outer table (row)
     pub outer_index: u16,
@@ -212,8 +277,12 @@ pub struct DeltaSetIndexMapEntry {
 
 /// Contains owned versions of some variable font tables.
 pub mod owned {
-    use super::{DeltaSetIndexMapEntry, DeltaSetT, Tuple};
-    use crate::error::ParseError;
+    use std::convert::TryFrom;
+
+    use super::{DeltaSetIndexMapEntry, DeltaSetT, RegionAxisCoordinates, Tuple};
+    use crate::binary::write::{WriteBinary, WriteBuffer, WriteContext};
+    use crate::binary::{I16Be, I32Be, U16Be, U32Be, I8, U8};
+    use crate::error::{ParseError, WriteError};
     use crate::tables::F2Dot14;
 
     /// Owned version of [super::ItemVariationStore].
@@ -226,12 +295,16 @@ pub mod owned {
 
     /// Owned version of [super::VariationRegionList].
     pub(super) struct VariationRegionList {
+        /// The number of variation axes for this font.
+        pub(super) axis_count: u16,
         /// Array of variation regions.
         pub(super) variation_regions: Vec<VariationRegion>,
     }
 
     /// Owned version of [super::ItemVariationData].
     pub(super) struct ItemVariationData {
+        /// The number of delta sets for distinct items.
+        pub(super) item_count: u16,
         /// A packed field: the high bit is a flag.
         pub(super) word_delta_count: u16,
         /// The number of variation regions referenced.
@@ -247,7 +320,13 @@ pub mod owned {
     pub(crate) struct VariationRegion {
         /// Array of region axis coordinates records, in the order of axes given in
         /// the `fvar` table.
-        pub(super) region_axes: Vec<super::RegionAxisCoordinates>,
+        pub(super) region_axes: Vec<RegionAxisCoordinates>,
+    }
+
+    /// Owned version of [super::DeltaSetIndexMap].
+    pub(crate) struct DeltaSetIndexMap {
+        /// The delta-set index of each item.
+        pub(crate) entries: Vec<DeltaSetIndexMapEntry>,
     }
 
     impl ItemVariationStore {
@@ -305,6 +384,74 @@ pub mod owned {
     }
 
     impl ItemVariationData {
+        /// Build item variation data from delta-set `rows`, each with a delta for each of the
+        /// regions in `region_indexes`.
+        ///
+        /// The smallest size that holds the deltas of each region is used. Regions that need
+        /// "word" deltas are moved to the start of each row, as required by the format.
+        pub(super) fn from_rows(
+            region_indexes: Vec<u16>,
+            rows: Vec<Vec<i32>>,
+        ) -> Result<Self, WriteError> {
+            let delta_size = |delta: i32| {
+                if i8::try_from(delta).is_ok() {
+                    1
+                } else if i16::try_from(delta).is_ok() {
+                    2
+                } else {
+                    4
+                }
+            };
+            let region_delta_sizes = (0..region_indexes.len())
+                .map(|column| {
+                    rows.iter()
+                        .map(|row| delta_size(row[column]))
+                        .max()
+                        .unwrap_or(1)
+                })
+                .collect::<Vec<_>>();
+            let long_words = region_delta_sizes.contains(&4);
+            let is_word = |column: usize| {
+                let size = region_delta_sizes[column];
+                if long_words {
+                    size == 4
+                } else {
+                    size == 2
+                }
+            };
+            let mut columns = (0..region_indexes.len()).collect::<Vec<_>>();
+            columns.sort_by_key(|&column| !is_word(column));
+            let word_count = columns.iter().filter(|&&column| is_word(column)).count();
+
+            let mut delta_sets = WriteBuffer::new();
+            for row in &rows {
+                for (i, &column) in columns.iter().enumerate() {
+                    let delta = row[column];
+                    match (long_words, i < word_count) {
+                        (true, true) => I32Be::write(&mut delta_sets, delta)?,
+                        (true, false) => I16Be::write(&mut delta_sets, i16::try_from(delta)?)?,
+                        (false, true) => I16Be::write(&mut delta_sets, i16::try_from(delta)?)?,
+                        (false, false) => I8::write(&mut delta_sets, i8::try_from(delta)?)?,
+                    }
+                }
+            }
+
+            let mut word_delta_count = u16::try_from(word_count)?;
+            if long_words {
+                word_delta_count |= Self::LONG_WORDS;
+            }
+            Ok(ItemVariationData {
+                item_count: u16::try_from(rows.len())?,
+                word_delta_count,
+                region_index_count: u16::try_from(region_indexes.len())?,
+                region_indexes: columns
+                    .iter()
+                    .map(|&column| region_indexes[column])
+                    .collect(),
+                delta_sets: delta_sets.into_inner().into_boxed_slice(),
+            })
+        }
+
         pub fn delta_set(&self, index: u16) -> Option<super::DeltaSet<'_>> {
             self.delta_set_impl(index)
         }
@@ -315,6 +462,81 @@ pub mod owned {
             super::scalar(self.region_axes.iter().copied(), tuple)
         }
     }
+
+    impl WriteBinary<&Self> for ItemVariationStore {
+        type Output = ();
+
+        fn write<C: WriteContext>(ctxt: &mut C, store: &Self) -> Result<(), WriteError> {
+            // Offsets are from the start of the store
+            let start = ctxt.bytes_written();
+            U16Be::write(ctxt, 1u16)?; // format
+            let variation_region_list_offset = ctxt.placeholder::<U32Be, _>()?;
+            U16Be::write(ctxt, u16::try_from(store.item_variation_data.len())?)?;
+            let item_variation_data_offsets =
+                ctxt.placeholder_array::<U32Be, _>(store.item_variation_data.len())?;
+
+            ctxt.write_placeholder(
+                variation_region_list_offset,
+                u32::try_from(ctxt.bytes_written() - start)?,
+            )?;
+            let region_list = &store.variation_region_list;
+            U16Be::write(ctxt, region_list.axis_count)?;
+            U16Be::write(ctxt, u16::try_from(region_list.variation_regions.len())?)?;
+            for region in &region_list.variation_regions {
+                ctxt.write_iter::<RegionAxisCoordinates, _>(region.region_axes.iter().copied())?;
+            }
+
+            for (offset, variation_data) in item_variation_data_offsets
+                .into_iter()
+                .zip(store.item_variation_data.iter())
+            {
+                ctxt.write_placeholder(offset, u32::try_from(ctxt.bytes_written() - start)?)?;
+                U16Be::write(ctxt, variation_data.item_count)?;
+                U16Be::write(ctxt, variation_data.word_delta_count)?;
+                U16Be::write(ctxt, variation_data.region_index_count)?;
+                ctxt.write_iter::<U16Be, _>(variation_data.region_indexes.iter().copied())?;
+                ctxt.write_bytes(&variation_data.delta_sets)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl WriteBinary<&Self> for DeltaSetIndexMap {
+        type Output = ();
+
+        /// Write the map in the smallest entry format that can hold its entries.
+        fn write<C: WriteContext>(ctxt: &mut C, map: &Self) -> Result<(), WriteError> {
+            let (max_outer, max_inner) =
+                map.entries.iter().fold((0, 0), |(outer, inner), entry| {
+                    (outer.max(entry.outer_index), inner.max(entry.inner_index))
+                });
+            let bit_count = |value: u16| (16 - value.leading_zeros()).max(1);
+            let inner_bit_count = bit_count(max_inner);
+            let entry_size = (inner_bit_count + bit_count(max_outer) + 7) / 8;
+            // The casts are safe as there are at most 4 bytes and 16 inner index bits
+            let entry_format = (((entry_size - 1) << 4) | (inner_bit_count - 1)) as u8;
+
+            let map_count = map.entries.len();
+            match u16::try_from(map_count) {
+                Ok(map_count) => {
+                    U8::write(ctxt, 0u8)?; // format
+                    U8::write(ctxt, entry_format)?;
+                    U16Be::write(ctxt, map_count)?;
+                }
+                Err(_) => {
+                    U8::write(ctxt, 1u8)?; // format
+                    U8::write(ctxt, entry_format)?;
+                    U32Be::write(ctxt, u32::try_from(map_count)?)?;
+                }
+            }
+            for entry in &map.entries {
+                let entry =
+                    u32::from(entry.outer_index) << inner_bit_count | u32::from(entry.inner_index);
+                ctxt.write_bytes(&entry.to_be_bytes()[4 - entry_size as usize..])?;
+            }
+            Ok(())
+        }
+    }
 }
 
 impl<'a> UserTuple<'a> {
@@ -578,6 +800,59 @@ fn read_packed_point_numbers(
     Ok(PointNumbers::Specific(point_numbers))
 }
 
+/// Write packed point numbers.
+///
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/otvarcommonformats#packed-point-numbers>
+fn write_packed_point_numbers<C: WriteContext>(
+    ctxt: &mut C,
+    point_numbers: &PointNumbers,
+) -> Result<(), WriteError> {
+    let numbers = match point_numbers {
+        PointNumbers::All(_) => return U8::write(ctxt, 0u8),
+        PointNumbers::Specific(numbers) => numbers,
+    };
+    match numbers.len() {
+        // A count of zero means all points, so an empty set can't be written
+        0 => return Err(WriteError::BadValue),
+        count @ 1..=127 => U8::write(ctxt, count as u8)?,
+        count @ 128..=0x7FFF => U16Be::write(ctxt, count as u16 | 0x8000)?,
+        _ => return Err(WriteError::BadValue),
+    }
+
+    // Point numbers are stored as differences from the previous one
+    let diffs = numbers
+        .iter()
+        .scan(0u16, |prev, &number| {
+            let diff = number.wrapping_sub(*prev);
+            *prev = number;
+            Some(diff)
+        })
+        .collect::<Vec<_>>();
+    let max_run_count = usize::from(PointNumbers::POINT_RUN_COUNT_MASK) + 1;
+    let mut diffs = diffs.as_slice();
+    while let Some(&first) = diffs.first() {
+        let words = first > 0xFF;
+        let run_count = diffs
+            .iter()
+            .take(max_run_count)
+            .take_while(|&&diff| (diff > 0xFF) == words)
+            .count();
+        let (run, rest) = diffs.split_at(run_count);
+        // Cast is safe as the run count is at most 128
+        let control_byte = (run_count - 1) as u8;
+        if words {
+            U8::write(ctxt, control_byte | PointNumbers::POINTS_ARE_WORDS)?;
+            ctxt.write_iter::<U16Be, _>(run.iter().copied())?;
+        } else {
+            U8::write(ctxt, control_byte)?;
+            // Cast is safe as the differences in the run fit in a byte
+            ctxt.write_iter::<U8, _>(run.iter().map(|&diff| diff as u8))?;
+        }
+        diffs = rest;
+    }
+    Ok(())
+}
+
 // The count may be stored in one or two bytes:
 //
 // * If the first byte is 0, then a second count byte is not used. This value
@@ -603,11 +878,13 @@ fn read_count(ctxt: &mut ReadCtxt<'_>) -> Result<u16, ParseError> {
 }
 
 mod packed_deltas {
+    use std::convert::TryFrom;
     use std::iter;
 
     use crate::binary::read::ReadCtxt;
-    use crate::binary::{I16Be, I8};
-    use crate::error::ParseError;
+    use crate::binary::write::{WriteBinary, WriteContext};
+    use crate::binary::{I16Be, I8, U8};
+    use crate::error::{ParseError, WriteError};
     use crate::SafeFrom;
 
     /// Flag indicating that this run contains no data (no explicit delta values
@@ -649,6 +926,53 @@ mod packed_deltas {
 
         Ok(deltas)
     }
+
+    /// Write packed `deltas`.
+    ///
+    /// Runs of zeros are written without data, and runs of deltas that fit in a byte are
+    /// written as bytes.
+    pub(super) fn write<C: WriteContext>(ctxt: &mut C, deltas: &[i16]) -> Result<(), WriteError> {
+        let max_run_count = usize::from(DELTA_RUN_COUNT_MASK) + 1;
+        let is_byte = |delta: i16| i8::try_from(delta).is_ok();
+        let mut deltas = deltas;
+        while let Some(&first) = deltas.first() {
+            let run_count = if first == 0 {
+                deltas.iter().take_while(|&&delta| delta == 0).count()
+            } else if is_byte(first) {
+                // A run of bytes continues through a single zero, as that's smaller than
+                // starting a new run
+                let mut count = 0;
+                while count < deltas.len()
+                    && is_byte(deltas[count])
+                    && !(deltas[count] == 0 && deltas.get(count + 1).copied().unwrap_or(0) == 0)
+                {
+                    count += 1;
+                }
+                count
+            } else {
+                deltas
+                    .iter()
+                    .take_while(|&&delta| delta != 0 && !is_byte(delta))
+                    .count()
+            }
+            .min(max_run_count);
+            let (run, rest) = deltas.split_at(run_count);
+            // Cast is safe as the run count is at most 64
+            let control_byte = (run_count - 1) as u8;
+            if first == 0 {
+                U8::write(ctxt, control_byte | DELTAS_ARE_ZERO)?;
+            } else if is_byte(first) {
+                U8::write(ctxt, control_byte)?;
+                // Cast is safe as the deltas in the run fit in a byte
+                ctxt.write_iter::<I8, _>(run.iter().map(|&delta| delta as i8))?;
+            } else {
+                U8::write(ctxt, control_byte | DELTAS_ARE_WORDS)?;
+                ctxt.write_iter::<I16Be, _>(run.iter().copied())?;
+            }
+            deltas = rest;
+        }
+        Ok(())
+    }
 }
 
 impl GvarVariationData<'_> {
@@ -854,6 +1178,127 @@ impl<'data, T> TupleVariationHeader<'data, T> {
         // NOTE(clone): Cheap as ReadTuple just contains ReadArray
         self.intermediate_region.clone()
     }
+
+    /// Returns the region of the variation space that this tuple variation applies to, given
+    /// its `peak` tuple.
+    ///
+    /// If there's no intermediate region the region is from zero to the peak on each axis.
+    pub(crate) fn region(&self, peak: &ReadTuple<'data>) -> Vec<RegionAxisCoordinates> {
+        match &self.intermediate_region {
+            Some((start, end)) => start
+                .0
+                .iter()
+                .zip(peak.0.iter())
+                .zip(end.0.iter())
+                .map(
+                    |((start_coord, peak_coord), end_coord)| RegionAxisCoordinates {
+                        start_coord,
+                        peak_coord,
+                        end_coord,
+                    },
+                )
+                .collect(),
+            None => peak
+                .0
+                .iter()
+                .map(|peak_coord| RegionAxisCoordinates {
+                    start_coord: peak_coord.min(F2Dot14::from(0)),
+                    peak_coord,
+                    end_coord: peak_coord.max(F2Dot14::from(0)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T> TupleVariationStore<'_, T> {
+    /// Write a tuple variation store containing `tuple_variations`.
+    ///
+    /// Peak tuples in `shared_tuples` are written as an index into the shared tuples of the
+    /// `gvar` table, and other peak tuples are embedded. The offset to the serialized data is
+    /// from `data_offset_base` bytes before the store. Point numbers are shared when all the
+    /// variations use the same points.
+    fn write_tuple_variations<C: WriteContext>(
+        ctxt: &mut C,
+        tuple_variations: &[OwnedTupleVariation],
+        shared_tuples: &BTreeMap<Vec<F2Dot14>, u16>,
+        data_offset_base: usize,
+    ) -> Result<(), WriteError> {
+        let count = u16::try_from(tuple_variations.len())?;
+        if count > Self::COUNT_MASK {
+            return Err(WriteError::BadValue);
+        }
+        let shared_point_numbers = match tuple_variations {
+            [first, rest @ ..]
+                if !rest.is_empty()
+                    && rest
+                        .iter()
+                        .all(|variation| variation.point_numbers == first.point_numbers) =>
+            {
+                Some(&first.point_numbers)
+            }
+            _ => None,
+        };
+
+        // The serialized data is written first as the headers hold its size
+        let mut headers = WriteBuffer::new();
+        let mut data = WriteBuffer::new();
+        if let Some(point_numbers) = shared_point_numbers {
+            write_packed_point_numbers(&mut data, point_numbers)?;
+        }
+        for variation in tuple_variations {
+            let start = data.bytes_written();
+            let mut flags_and_index = 0;
+            if shared_point_numbers.is_none() {
+                flags_and_index |= TupleVariationHeader::<'_, T>::PRIVATE_POINT_NUMBERS;
+                write_packed_point_numbers(&mut data, &variation.point_numbers)?;
+            }
+            packed_deltas::write(&mut data, &variation.deltas)?;
+
+            let peak = variation.peak();
+            let shared_index = shared_tuples.get(&peak).copied();
+            match shared_index {
+                Some(index) => flags_and_index |= index,
+                None => flags_and_index |= TupleVariationHeader::<'_, T>::EMBEDDED_PEAK_TUPLE,
+            }
+            // The intermediate region is only needed if it differs from the one implied by the
+            // peak
+            let zero = F2Dot14::from(0);
+            let intermediate_region = variation.region.iter().any(|coords| {
+                coords.peak_coord != zero
+                    && (coords.start_coord != coords.peak_coord.min(zero)
+                        || coords.end_coord != coords.peak_coord.max(zero))
+            });
+            if intermediate_region {
+                flags_and_index |= TupleVariationHeader::<'_, T>::INTERMEDIATE_REGION;
+            }
+
+            U16Be::write(&mut headers, u16::try_from(data.bytes_written() - start)?)?;
+            U16Be::write(&mut headers, flags_and_index)?;
+            if shared_index.is_none() {
+                headers.write_iter::<F2Dot14, _>(peak.into_iter())?;
+            }
+            if intermediate_region {
+                headers.write_iter::<F2Dot14, _>(
+                    variation.region.iter().map(|coords| coords.start_coord),
+                )?;
+                headers.write_iter::<F2Dot14, _>(
+                    variation.region.iter().map(|coords| coords.end_coord),
+                )?;
+            }
+        }
+
+        let mut flags_and_count = count;
+        if shared_point_numbers.is_some() {
+            flags_and_count |= Self::SHARED_POINT_NUMBERS;
+        }
+        U16Be::write(ctxt, flags_and_count)?;
+        // The data follows the count, the data offset, and the headers
+        U16Be::write(ctxt, u16::try_from(data_offset_base + 4 + headers.len())?)?;
+        ctxt.write_bytes(headers.bytes())?;
+        ctxt.write_bytes(data.bytes())?;
+        Ok(())
+    }
 }
 
 impl<T> ReadBinaryDep for TupleVariationHeader<'_, T> {
@@ -986,6 +1431,242 @@ impl<'a> ItemVariationStore<'a> {
             item_variation_data,
         })
     }
+
+    /// Returns a copy of this store that only contains the delta sets at `entries`, and the
+    /// regions that they use.
+    ///
+    /// The new entry of each delta set is returned along with the store. Entries with the outer
+    /// and inner index 0xFFFF, which have no variation data, are left as they are.
+    pub(crate) fn subset(
+        &self,
+        entries: impl IntoIterator<Item = DeltaSetIndexMapEntry>,
+    ) -> Result<
+        (
+            owned::ItemVariationStore,
+            BTreeMap<DeltaSetIndexMapEntry, DeltaSetIndexMapEntry>,
+        ),
+        ParseError,
+    > {
+        let mut used_entries = BTreeMap::new();
+        for entry in entries {
+            let new_entry = if entry == DeltaSetIndexMapEntry::NO_VARIATION_DATA {
+                entry
+            } else {
+                // The new indices are set below, once all the delta sets are known
+                DeltaSetIndexMapEntry {
+                    outer_index: 0,
+                    inner_index: 0,
+                }
+            };
+            used_entries.insert(entry, new_entry);
+        }
+
+        // Each item variation data that has a delta set in use is kept, with the regions it
+        // refers to
+        let mut used_regions = BTreeSet::new();
+        let mut used_data = BTreeSet::new();
+        for entry in used_entries.keys() {
+            if *entry == DeltaSetIndexMapEntry::NO_VARIATION_DATA {
+                continue;
+            }
+            let data = self
+                .item_variation_data
+                .get(usize::from(entry.outer_index))
+                .ok_or(ParseError::BadIndex)?;
+            if entry.inner_index >= data.item_count {
+                return Err(ParseError::BadIndex);
+            }
+            if used_data.insert(entry.outer_index) {
+                used_regions.extend(data.region_indexes.iter());
+            }
+        }
+        let variation_regions = used_regions
+            .iter()
+            .map(|&region_index| {
+                self.variation_region(region_index)
+                    .map(|region| region.to_owned())
+                    .ok_or(ParseError::BadIndex)
+            })
+            .collect::<Result<_, _>>()?;
+        let new_region_index = |region_index| {
+            // The cast is safe as there are fewer than 32768 regions
+            used_regions.range(..region_index).count() as u16
+        };
+
+        let mut item_variation_data = Vec::with_capacity(used_data.len());
+        for (new_outer_index, &outer_index) in used_data.iter().enumerate() {
+            let data = &self.item_variation_data[usize::from(outer_index)];
+            let row_length = data.row_length();
+            let mut delta_sets = Vec::new();
+            let mut item_count = 0;
+            for (entry, new_entry) in used_entries.range_mut(
+                DeltaSetIndexMapEntry {
+                    outer_index,
+                    inner_index: 0,
+                }..=DeltaSetIndexMapEntry {
+                    outer_index,
+                    inner_index: u16::MAX,
+                },
+            ) {
+                if *entry == DeltaSetIndexMapEntry::NO_VARIATION_DATA {
+                    continue;
+                }
+                let start = usize::from(entry.inner_index) * row_length;
+                let row = data
+                    .delta_sets
+                    .get(start..start + row_length)
+                    .ok_or(ParseError::BadIndex)?;
+                delta_sets.extend_from_slice(row);
+                // The casts are safe as there are at most 65535 data and items in each
+                *new_entry = DeltaSetIndexMapEntry {
+                    outer_index: new_outer_index as u16,
+                    inner_index: item_count,
+                };
+                item_count += 1;
+            }
+            item_variation_data.push(owned::ItemVariationData {
+                item_count,
+                word_delta_count: data.word_delta_count,
+                region_index_count: data.region_index_count,
+                region_indexes: data.region_indexes.iter().map(new_region_index).collect(),
+                delta_sets: delta_sets.into_boxed_slice(),
+            });
+        }
+
+        let store = owned::ItemVariationStore {
+            variation_region_list: owned::VariationRegionList {
+                axis_count: *self.variation_region_list.variation_regions.args(),
+                variation_regions,
+            },
+            item_variation_data,
+        };
+        Ok((store, used_entries))
+    }
+
+    /// Returns a copy of this store for a font with its axes limited to `limits`, one for each
+    /// axis of the font.
+    ///
+    /// Pinned axes are removed from the regions, and the regions of limited axes are
+    /// renormalized to the new range. The delta sets keep their indices, so delta-set index maps
+    /// for this store remain valid. Deltas that apply regardless of the location in the new
+    /// variation space are dropped: they are expected to have been applied to the default
+    /// values of the font.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[NormalizedAxisLimit],
+    ) -> Result<owned::ItemVariationStore, ReadWriteError> {
+        // The regions and scalars that each old region is made up of in the new variation space
+        let mut regions = Vec::<Vec<RegionAxisCoordinates>>::new();
+        let mut new_regions =
+            Vec::with_capacity(self.variation_region_list.variation_regions.len());
+        for region in self.variation_region_list.variation_regions.iter_res() {
+            let region = region?;
+            let mut parts = Vec::new();
+            for (scalar, new_region) in limit_region(region.region_axes.iter(), limits) {
+                if new_region
+                    .iter()
+                    .all(|coords| coords.peak_coord == F2Dot14::from(0))
+                {
+                    continue;
+                }
+                let index = match regions.iter().position(|region| *region == new_region) {
+                    Some(index) => index,
+                    None => {
+                        regions.push(new_region);
+                        regions.len() - 1
+                    }
+                };
+                parts.push((scalar, index));
+            }
+            new_regions.push(parts);
+        }
+
+        let mut used_regions = BTreeSet::new();
+        let mut item_variation_data = Vec::with_capacity(self.item_variation_data.len());
+        for data in &self.item_variation_data {
+            let region_indexes = data.region_indexes.iter().collect::<Vec<_>>();
+            let mut columns = BTreeSet::new();
+            for &region_index in &region_indexes {
+                let parts = new_regions
+                    .get(usize::from(region_index))
+                    .ok_or(ParseError::BadIndex)?;
+                columns.extend(parts.iter().map(|&(_, index)| index));
+            }
+            let columns = columns.into_iter().collect::<Vec<_>>();
+
+            let mut rows = Vec::with_capacity(usize::from(data.item_count));
+            for item in 0..data.item_count {
+                let delta_set = data.delta_set(item).ok_or(ParseError::BadIndex)?;
+                let mut row = vec![0.; columns.len()];
+                for (delta, &region_index) in delta_set.iter().zip(region_indexes.iter()) {
+                    for &(scalar, index) in &new_regions[usize::from(region_index)] {
+                        // NOTE(unwrap): Safe as the columns include all the new regions
+                        let column = columns.binary_search(&index).unwrap();
+                        row[column] += scalar * delta as f32;
+                    }
+                }
+                rows.push(row.into_iter().map(|delta| delta.round() as i32).collect());
+            }
+
+            // Regions with no deltas after rounding are removed
+            let (columns, rows) = drop_zero_columns(columns, rows);
+            used_regions.extend(columns.iter().copied());
+            item_variation_data.push((columns, rows));
+        }
+
+        let new_region_index = |index| {
+            // The cast is safe as there are fewer than 32768 regions in each data
+            used_regions.range(..index).count() as u16
+        };
+        let item_variation_data = item_variation_data
+            .into_iter()
+            .map(|(columns, rows)| {
+                owned::ItemVariationData::from_rows(
+                    columns.into_iter().map(new_region_index).collect(),
+                    rows,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        let axis_count = limits
+            .iter()
+            .filter(|limit| matches!(limit, NormalizedAxisLimit::Range(..)))
+            .count();
+        Ok(owned::ItemVariationStore {
+            variation_region_list: owned::VariationRegionList {
+                // The cast is safe as the store had at least this many axes
+                axis_count: axis_count as u16,
+                variation_regions: used_regions
+                    .iter()
+                    .map(|&index| owned::VariationRegion {
+                        region_axes: regions[index].clone(),
+                    })
+                    .collect(),
+            },
+            item_variation_data,
+        })
+    }
+}
+
+/// Remove the columns of `rows` where every delta is zero.
+fn drop_zero_columns(columns: Vec<usize>, rows: Vec<Vec<i32>>) -> (Vec<usize>, Vec<Vec<i32>>) {
+    let keep = (0..columns.len())
+        .map(|column| rows.iter().any(|row| row[column] != 0))
+        .collect::<Vec<_>>();
+    let columns = columns
+        .into_iter()
+        .zip(keep.iter())
+        .filter_map(|(column, &keep)| keep.then_some(column))
+        .collect();
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .zip(keep.iter())
+                .filter_map(|(delta, &keep)| keep.then_some(delta))
+                .collect()
+        })
+        .collect();
+    (columns, rows)
 }
 
 impl ReadBinary for ItemVariationStore<'_> {
@@ -1025,7 +1706,10 @@ impl VariationRegionList<'_> {
             .iter_res()
             .map(|region| region.map(|region| region.to_owned()))
             .collect::<Result<_, _>>()?;
-        Ok(owned::VariationRegionList { variation_regions })
+        Ok(owned::VariationRegionList {
+            axis_count: *self.variation_regions.args(),
+            variation_regions,
+        })
     }
 }
 
@@ -1033,8 +1717,10 @@ impl WriteBinary<&Self> for ItemVariationStore<'_> {
     type Output = ();
 
     fn write<C: WriteContext>(ctxt: &mut C, store: &Self) -> Result<Self::Output, WriteError> {
+        // Offsets are from the start of the store
+        let start = ctxt.bytes_written();
         U16Be::write(ctxt, 1u16)?; // format
-        let variation_region_list_offset_placeholder = ctxt.placeholder::<U16Be, _>()?;
+        let variation_region_list_offset_placeholder = ctxt.placeholder::<U32Be, _>()?;
         U16Be::write(ctxt, u16::try_from(store.item_variation_data.len())?)?;
         let item_variation_data_offsets_placeholders =
             ctxt.placeholder_array::<U32Be, _>(store.item_variation_data.len())?;
@@ -1042,7 +1728,7 @@ impl WriteBinary<&Self> for ItemVariationStore<'_> {
         // Write out the VariationRegionList
         ctxt.write_placeholder(
             variation_region_list_offset_placeholder,
-            u16::try_from(ctxt.bytes_written())?,
+            u32::try_from(ctxt.bytes_written() - start)?,
         )?;
         VariationRegionList::write(ctxt, &store.variation_region_list)?;
 
@@ -1051,7 +1737,10 @@ impl WriteBinary<&Self> for ItemVariationStore<'_> {
             .into_iter()
             .zip(store.item_variation_data.iter())
         {
-            ctxt.write_placeholder(offset_placeholder, u32::try_from(ctxt.bytes_written())?)?;
+            ctxt.write_placeholder(
+                offset_placeholder,
+                u32::try_from(ctxt.bytes_written() - start)?,
+            )?;
             ItemVariationData::write(ctxt, variation_data)?;
         }
 
@@ -1227,6 +1916,7 @@ impl ItemVariationData<'_> {
 
     fn to_owned(&self) -> owned::ItemVariationData {
         owned::ItemVariationData {
+            item_count: self.item_count,
             word_delta_count: self.word_delta_count,
             region_index_count: self.region_index_count(),
             region_indexes: self.region_indexes.to_vec(),
@@ -1327,6 +2017,119 @@ fn calculate_scalar(instance: F2Dot14, start: F2Dot14, peak: F2Dot14, end: F2Dot
     }
 }
 
+/// Returns the regions, and the scalar of each, that make up the region `region_axes` in the
+/// variation space limited to `limits`.
+///
+/// Pinned axes are removed from the region, with the scalar of the region at the pinned value
+/// folded into the returned scalars. The region of a limited axis may have to be split into two
+/// in the new variation space. Regions that don't apply to the new variation space are not
+/// returned.
+pub(crate) fn limit_region(
+    region_axes: impl Iterator<Item = RegionAxisCoordinates>,
+    limits: &[NormalizedAxisLimit],
+) -> Vec<(f32, Vec<RegionAxisCoordinates>)> {
+    let mut regions = vec![(1., Vec::new())];
+    for (coords, limit) in region_axes.zip(limits.iter()) {
+        match *limit {
+            NormalizedAxisLimit::Pin(value) => {
+                let axis_scalar = calculate_scalar(
+                    value,
+                    coords.start_coord,
+                    coords.peak_coord,
+                    coords.end_coord,
+                );
+                regions
+                    .iter_mut()
+                    .for_each(|(scalar, _)| *scalar *= axis_scalar);
+            }
+            NormalizedAxisLimit::Range(min, max) => {
+                let tents = limit_tent(coords, min, max);
+                regions = regions
+                    .into_iter()
+                    .flat_map(|(scalar, region)| {
+                        tents.iter().map(move |&(tent_scalar, tent)| {
+                            let mut region = region.clone();
+                            region.push(tent);
+                            (scalar * tent_scalar, region)
+                        })
+                    })
+                    .collect();
+            }
+        }
+        regions.retain(|&(scalar, _)| scalar != 0.);
+    }
+    regions
+}
+
+/// Returns the tents, and the scalar of each, that give the same scalars as `tent` when the axis
+/// is limited to the range `min` to `max` and then renormalized to the range -1 to 1.
+fn limit_tent(
+    tent: RegionAxisCoordinates,
+    min: F2Dot14,
+    max: F2Dot14,
+) -> Vec<(f32, RegionAxisCoordinates)> {
+    let zero = F2Dot14::from(0);
+    let RegionAxisCoordinates {
+        start_coord: start,
+        peak_coord: peak,
+        end_coord: end,
+    } = tent;
+    if peak == zero {
+        return vec![(1., tent)];
+    }
+    if start > peak || peak > end || (start < zero && end > zero) {
+        // The axis is ignored for regions like this
+        let zero_tent = RegionAxisCoordinates {
+            start_coord: zero,
+            peak_coord: zero,
+            end_coord: zero,
+        };
+        return vec![(1., zero_tent)];
+    }
+    if peak < zero {
+        // Handle negative tents by mirroring them, and the limits, to be positive
+        let mirror = |coords: RegionAxisCoordinates| RegionAxisCoordinates {
+            start_coord: -coords.end_coord,
+            peak_coord: -coords.peak_coord,
+            end_coord: -coords.start_coord,
+        };
+        return limit_tent(mirror(tent), -max, -min)
+            .into_iter()
+            .map(|(scalar, tent)| (scalar, mirror(tent)))
+            .collect();
+    }
+
+    let (start, peak, end, max) = (
+        f32::from(start),
+        f32::from(peak),
+        f32::from(end),
+        f32::from(max),
+    );
+    let tent = |start: f32, peak: f32, end: f32| RegionAxisCoordinates {
+        start_coord: F2Dot14::from(start),
+        peak_coord: F2Dot14::from(peak),
+        end_coord: F2Dot14::from(end),
+    };
+    if start >= max {
+        // The tent is outside the new range
+        Vec::new()
+    } else if peak >= max {
+        // The tent is cut off before, or at, its peak: it becomes a ramp up to the new maximum
+        let scalar = (max - start) / (peak - start);
+        vec![(scalar, tent(start / max, 1., 1.))]
+    } else if end > max {
+        // The tent is cut off after its peak: it becomes a tent that falls to zero at the new
+        // maximum, plus a ramp for the value of the original tent at the new maximum
+        let scalar = (end - max) / (end - peak);
+        vec![
+            (1., tent(start / max, peak / max, 1.)),
+            (scalar, tent(peak / max, 1., 1.)),
+        ]
+    } else {
+        vec![(1., tent(start / max, peak / max, end / max))]
+    }
+}
+
 impl ReadBinaryDep for VariationRegion<'_> {
     type Args<'a> = u16;
     type HostType<'a> = VariationRegion<'a>;
@@ -1377,6 +2180,107 @@ impl WriteBinary for RegionAxisCoordinates {
     }
 }
 
+/// Subset a metrics variations table, `HVAR` or `VVAR`, to the glyphs in `glyphs`.
+///
+/// `mappings` are the delta-set index maps of the table in the order they appear in its header,
+/// starting with the one for advances. Glyph ids are used as the inner index into the first item
+/// variation data when there's no map for advances, but the delta sets of the subset table are
+/// not in glyph order, so the map for advances is always written. The other maps are written if
+/// they're present.
+fn subset_metrics_variations(
+    minor_version: u16,
+    store: &ItemVariationStore<'_>,
+    mappings: &[Option<&DeltaSetIndexMap<'_>>],
+    glyphs: &impl SubsetGlyphs,
+) -> Result<WriteBuffer, ReadWriteError> {
+    // Cast is safe as glyph indexes are 16-bit values
+    let old_ids = (0..glyphs.len())
+        .map(|new_id| glyphs.old_id(new_id as u16))
+        .collect::<Vec<_>>();
+    let mut old_entries = Vec::with_capacity(mappings.len());
+    for (index, mapping) in mappings.iter().enumerate() {
+        let entries = match mapping {
+            Some(mapping) => Some(
+                old_ids
+                    .iter()
+                    .map(|&old_id| mapping.entry(u32::from(old_id)))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None if index == 0 => Some(
+                old_ids
+                    .iter()
+                    .map(|&old_id| DeltaSetIndexMapEntry {
+                        outer_index: 0,
+                        inner_index: old_id,
+                    })
+                    .collect(),
+            ),
+            None => None,
+        };
+        old_entries.push(entries);
+    }
+    let (store, new_entries) = store.subset(old_entries.iter().flatten().flatten().copied())?;
+    let mappings = old_entries
+        .into_iter()
+        .map(|entries| {
+            entries.map(|entries| owned::DeltaSetIndexMap {
+                entries: entries.iter().map(|entry| new_entries[entry]).collect(),
+            })
+        })
+        .collect::<Vec<_>>();
+    write_metrics_variations(minor_version, &store, &mappings)
+}
+
+/// Limit the axes of a metrics variations table, `HVAR` or `VVAR`, to `limits`.
+///
+/// `mappings` are the delta-set index maps of the table in the order they appear in its header.
+/// They're written unchanged as the delta sets keep their indices.
+fn limit_metrics_variations(
+    minor_version: u16,
+    store: &ItemVariationStore<'_>,
+    mappings: &[Option<&DeltaSetIndexMap<'_>>],
+    limits: &[NormalizedAxisLimit],
+) -> Result<WriteBuffer, ReadWriteError> {
+    let store = store.limit_axes(limits)?;
+    let mappings = mappings
+        .iter()
+        .map(|mapping| mapping.map(|mapping| mapping.try_to_owned()).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+    write_metrics_variations(minor_version, &store, &mappings)
+}
+
+fn write_metrics_variations(
+    minor_version: u16,
+    store: &owned::ItemVariationStore,
+    mappings: &[Option<owned::DeltaSetIndexMap>],
+) -> Result<WriteBuffer, ReadWriteError> {
+    let mut table = WriteBuffer::new();
+    U16Be::write(&mut table, 1u16)?; // major version
+    U16Be::write(&mut table, minor_version)?;
+    let store_offset = table.placeholder::<U32Be, _>()?;
+    let mapping_offsets = table.placeholder_array::<U32Be, _>(mappings.len())?;
+    table.write_placeholder(store_offset, u32::try_from(table.bytes_written())?)?;
+    owned::ItemVariationStore::write(&mut table, store)?;
+    for (offset, mapping) in mapping_offsets.into_iter().zip(mappings) {
+        match mapping {
+            Some(mapping) => {
+                table.write_placeholder(offset, u32::try_from(table.bytes_written())?)?;
+                owned::DeltaSetIndexMap::write(&mut table, mapping)?;
+            }
+            None => table.write_placeholder(offset, 0)?,
+        }
+    }
+    Ok(table)
+}
+
+impl DeltaSetIndexMapEntry {
+    /// The entry of items that have no variation data.
+    pub const NO_VARIATION_DATA: DeltaSetIndexMapEntry = DeltaSetIndexMapEntry {
+        outer_index: 0xFFFF,
+        inner_index: 0xFFFF,
+    };
+}
+
 impl DeltaSetIndexMap<'_> {
     /// Mask for the low 4 bits of the DeltaSetIndexMap entry format.
     ///
@@ -1422,6 +2326,14 @@ impl DeltaSetIndexMap<'_> {
         })
     }
 
+    /// Returns an owned copy of this map.
+    fn try_to_owned(&self) -> Result<owned::DeltaSetIndexMap, ParseError> {
+        let entries = (0..self.map_count)
+            .map(|i| self.entry(i))
+            .collect::<Result<_, _>>()?;
+        Ok(owned::DeltaSetIndexMap { entries })
+    }
+
     /// The size of an entry in bytes
     fn entry_size(&self) -> u8 {
         Self::entry_size_impl(self.entry_format)
@@ -1503,6 +2415,33 @@ mod tests {
     use super::*;
     use crate::binary::read::ReadScope;
 
+    #[test]
+    fn test_write_delta_set_index_map() {
+        let entries = vec![
+            DeltaSetIndexMapEntry {
+                outer_index: 0,
+                inner_index: 3,
+            },
+            DeltaSetIndexMapEntry {
+                outer_index: 1,
+                inner_index: 0,
+            },
+            DeltaSetIndexMapEntry::NO_VARIATION_DATA,
+        ];
+        let map = owned::DeltaSetIndexMap {
+            entries: entries.clone(),
+        };
+        let mut buffer = WriteBuffer::new();
+        owned::DeltaSetIndexMap::write(&mut buffer, &map).unwrap();
+        let data = buffer.into_inner();
+        let map = ReadScope::new(&data)
+            .read::<DeltaSetIndexMap<'_>>()
+            .unwrap();
+        for (i, expected) in entries.iter().enumerate() {
+            assert_eq!(map.entry(i as u32).unwrap(), *expected);
+        }
+    }
+
     #[test]
     fn test_read_count() {
         let mut ctxt = ReadScope::new(&[0]).ctxt();
@@ -1540,4 +2479,79 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_write_packed_point_numbers() {
+        let point_numbers = (0..200).chain([300, 301, 1000]).collect::<Vec<u16>>();
+        let mut buffer = WriteBuffer::new();
+        write_packed_point_numbers(&mut buffer, &PointNumbers::Specific(point_numbers.clone()))
+            .unwrap();
+        let data = buffer.into_inner();
+        let mut ctxt = ReadScope::new(&data).ctxt();
+        let read = read_packed_point_numbers(&mut ctxt, 1001).unwrap();
+        assert_eq!(
+            read.iter().collect::<Vec<_>>(),
+            point_numbers
+                .iter()
+                .map(|&n| u32::from(n))
+                .collect::<Vec<_>>()
+        );
+        assert!(!ctxt.bytes_available());
+    }
+
+    #[test]
+    fn test_write_packed_deltas() {
+        let deltas = [10, -105, 0, -58, 0, 0, 0, 0, 0, 0, 0, 0, 4130, -1228]
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(1).take(70))
+            .collect::<Vec<i16>>();
+        let mut buffer = WriteBuffer::new();
+        packed_deltas::write(&mut buffer, &deltas).unwrap();
+        let data = buffer.into_inner();
+        // The first runs are as in the example in the spec, except the single zero, which is
+        // kept in the run of bytes
+        assert_eq!(
+            &data[..11],
+            &[0x03, 0x0A, 0x97, 0x00, 0xC6, 0x87, 0x41, 0x10, 0x22, 0xFB, 0x34]
+        );
+        let mut ctxt = ReadScope::new(&data).ctxt();
+        assert_eq!(
+            packed_deltas::read(&mut ctxt, deltas.len() as u32).unwrap(),
+            deltas
+        );
+    }
+
+    #[test]
+    fn test_limit_tent() {
+        let tent = |start: f32, peak: f32, end: f32| RegionAxisCoordinates {
+            start_coord: F2Dot14::from(start),
+            peak_coord: F2Dot14::from(peak),
+            end_coord: F2Dot14::from(end),
+        };
+        let (min, max) = (F2Dot14::from(-1.), F2Dot14::from(0.5));
+
+        // Inside the new range: scaled to it
+        assert_eq!(
+            limit_tent(tent(0., 0.25, 0.5), min, max),
+            vec![(1., tent(0., 0.5, 1.))]
+        );
+        // Cut off after the peak: a tent and a ramp
+        assert_eq!(
+            limit_tent(tent(0., 0.25, 1.), min, max),
+            vec![(1., tent(0., 0.5, 1.)), (2. / 3., tent(0.5, 1., 1.))]
+        );
+        // Cut off before the peak: a ramp with the value of the tent at the new maximum
+        assert_eq!(
+            limit_tent(tent(0., 1., 1.), min, max),
+            vec![(0.5, tent(0., 1., 1.))]
+        );
+        // Outside the new range
+        assert_eq!(limit_tent(tent(0.5, 1., 1.), min, max), vec![]);
+        // Negative tents are limited by the minimum
+        assert_eq!(
+            limit_tent(tent(-1., -1., 0.), F2Dot14::from(-0.5), max),
+            vec![(0.5, tent(-1., -1., 0.))]
+        );
+    }
 }
@@ -10,8 +10,12 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/avar>
 
+use std::convert::TryFrom;
+
 use crate::binary::read::{ReadArray, ReadBinary, ReadCtxt, ReadFrom, ReadScope, ReadUnchecked};
-use crate::error::ParseError;
+use crate::binary::write::{WriteBinary, WriteBuffer};
+use crate::binary::U16Be;
+use crate::error::{ParseError, WriteError};
 use crate::tables::{F2Dot14, Fixed};
 
 /// `avar` Axis Variations Table.
@@ -52,6 +56,38 @@ impl AvarTable<'_> {
             ctxt.read::<SegmentMap<'_>>().ok()
         })
     }
+
+    /// Returns an `avar` table for a font with its axes limited to `limits`, one for each axis.
+    ///
+    /// Each limit is `None` if the axis is pinned and removed from the font, or the range of the
+    /// axis after default normalization. The segment maps of the remaining axes are rewritten so
+    /// that the limits map to -1 and 1, and the mappings outside of them are dropped.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[Option<(F2Dot14, F2Dot14)>],
+    ) -> Result<WriteBuffer, WriteError> {
+        let segment_maps = self
+            .segment_maps()
+            .zip(limits.iter())
+            .filter_map(|(segment_map, limit)| {
+                limit.map(|(min, max)| segment_map.limit(f32::from(min), f32::from(max)))
+            })
+            .collect::<Vec<_>>();
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, self.major_version)?;
+        U16Be::write(&mut table, self.minor_version)?;
+        U16Be::write(&mut table, 0u16)?; // reserved
+        U16Be::write(&mut table, u16::try_from(segment_maps.len())?)?;
+        for axis_value_maps in segment_maps {
+            U16Be::write(&mut table, u16::try_from(axis_value_maps.len())?)?;
+            for map in axis_value_maps {
+                F2Dot14::write(&mut table, map.from_coordinate)?;
+                F2Dot14::write(&mut table, map.to_coordinate)?;
+            }
+        }
+        Ok(table)
+    }
 }
 
 impl ReadBinary for AvarTable<'_> {
@@ -123,6 +159,39 @@ impl SegmentMap<'_> {
         }
         normalized_value
     }
+
+    /// Returns the axis value mappings of this segment map for the axis limited to the range
+    /// `min` to `max`, in default normalized coordinates.
+    fn limit(&self, min: f32, max: f32) -> Vec<AxisValueMap> {
+        let mapped_min = f32::from(self.normalize(Fixed::from(min)));
+        let mapped_max = f32::from(self.normalize(Fixed::from(max)));
+        let map = |from: f32, to: f32| AxisValueMap {
+            from_coordinate: F2Dot14::from(from),
+            to_coordinate: F2Dot14::from(to),
+        };
+
+        // The mappings for -1, 0, and 1 are required
+        let mut maps = vec![map(-1., -1.)];
+        if min < 0. && mapped_min < 0. {
+            maps.extend(
+                self.axis_value_mappings()
+                    .map(|map| (f32::from(map.from_coordinate), f32::from(map.to_coordinate)))
+                    .filter(|&(from, _)| min < from && from < 0.)
+                    .map(|(from, to)| map(-from / min, -to / mapped_min)),
+            );
+        }
+        maps.push(map(0., 0.));
+        if max > 0. && mapped_max > 0. {
+            maps.extend(
+                self.axis_value_mappings()
+                    .map(|map| (f32::from(map.from_coordinate), f32::from(map.to_coordinate)))
+                    .filter(|&(from, _)| 0. < from && from < max)
+                    .map(|(from, to)| map(from / max, to / mapped_max)),
+            );
+        }
+        maps.push(map(1., 1.));
+        maps
+    }
 }
 
 impl ReadBinary for SegmentMap<'_> {
@@ -300,4 +369,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_avar_limit() -> Result<(), ReadWriteError> {
+        let mut buf = WriteBuffer::new();
+        U16Be::write(&mut buf, 6u16)?; // position_map_count
+        [
+            (-1.0, -1.0),
+            (-0.75, -0.5),
+            (0., 0.),
+            (0.4, 0.4),
+            (0.6, 0.9),
+            (1.0, 1.0),
+        ]
+        .iter()
+        .copied()
+        .try_for_each(|(from_coord, to_coord)| {
+            F2Dot14::write(&mut buf, F2Dot14::from(from_coord))?;
+            F2Dot14::write(&mut buf, F2Dot14::from(to_coord))
+        })?;
+
+        let data = buf.into_inner();
+        let mut ctxt = ReadScope::new(&data).ctxt();
+        let segment_map = ctxt.read::<SegmentMap<'_>>()?;
+
+        // Limit the axis to -0.5..0.5, which avar maps to -0.3333..0.65
+        let limited = segment_map
+            .limit(-0.5, 0.5)
+            .into_iter()
+            .map(|map| (f32::from(map.from_coordinate), f32::from(map.to_coordinate)))
+            .collect::<Vec<_>>();
+        let expected = [(-1.0, -1.0), (0., 0.), (0.8, 0.6154), (1.0, 1.0)];
+        assert_eq!(limited.len(), expected.len());
+        for ((from, to), (expected_from, expected_to)) in limited.into_iter().zip(expected) {
+            assert_fixed_close(Fixed::from(from), expected_from);
+            assert_fixed_close(Fixed::from(to), expected_to);
+        }
+
+        Ok(())
+    }
 }
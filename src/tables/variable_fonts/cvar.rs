@@ -4,9 +4,15 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/cvar>
 
+use std::collections::BTreeMap;
+
 use crate::binary::read::{ReadArrayCow, ReadBinaryDep, ReadCtxt};
-use crate::error::ParseError;
-use crate::tables::variable_fonts::{OwnedTuple, TupleVariationStore};
+use crate::binary::write::{WriteBinary, WriteBuffer};
+use crate::binary::U16Be;
+use crate::error::{ParseError, ReadWriteError};
+use crate::tables::variable_fonts::{
+    NormalizedAxisLimit, OwnedTuple, OwnedTupleVariation, TupleVariationStore,
+};
 use crate::tables::CvtTable;
 use crate::SafeFrom;
 
@@ -47,6 +53,47 @@ impl CvarTable<'_> {
             values: ReadArrayCow::Owned(values.into_iter().map(|val| val.round() as i16).collect()),
         })
     }
+
+    /// Returns a `cvar` table for a font with its axes limited to `limits`.
+    ///
+    /// `num_cvts` is the number of values in the CVT table. The deltas that apply at the pinned
+    /// values of the axes are expected to have been applied to the CVT table.
+    pub(crate) fn limit_axes(
+        &self,
+        num_cvts: u32,
+        limits: &[NormalizedAxisLimit],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        let mut variations = Vec::new();
+        for header in self.store.headers() {
+            let peak = header.peak_tuple().ok_or(ParseError::MissingValue)?;
+            let region = header.region(&peak);
+            let data = header.variation_data(num_cvts, self.store.shared_point_numbers())?;
+            let deltas = data
+                .deltas
+                .iter()
+                .map(|&delta| f32::from(delta))
+                .collect::<Vec<_>>();
+            variations.extend(OwnedTupleVariation::limit_axes(
+                region,
+                &data.point_numbers,
+                &deltas,
+                limits,
+            ));
+        }
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, self.major_version)?;
+        U16Be::write(&mut table, self.minor_version)?;
+        // The offset to the data is from the start of the table, which is 4 bytes before the
+        // store. `cvar` tables don't have shared tuples.
+        TupleVariationStore::<'_, super::Cvar>::write_tuple_variations(
+            &mut table,
+            &variations,
+            &BTreeMap::new(),
+            4,
+        )?;
+        Ok(table)
+    }
 }
 
 impl ReadBinaryDep for CvarTable<'_> {
@@ -4,11 +4,14 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/fvar>
 
+use std::convert::TryFrom;
+
 use crate::binary::read::{
     ReadArray, ReadBinary, ReadBinaryDep, ReadCtxt, ReadFrom, ReadScope, ReadUnchecked,
 };
+use crate::binary::write::{WriteBinary, WriteBuffer};
 use crate::binary::{U16Be, U32Be};
-use crate::error::ParseError;
+use crate::error::{ParseError, ReadWriteError};
 use crate::tables::variable_fonts::avar::AvarTable;
 use crate::tables::variable_fonts::UserTuple;
 use crate::tables::{F2Dot14, Fixed};
@@ -170,6 +173,73 @@ impl FvarTable<'_> {
     pub fn owned_tuple(&self, values: &[F2Dot14]) -> Option<OwnedTuple> {
         (values.len() == usize::from(self.axis_count())).then(|| OwnedTuple(TinyVec::from(values)))
     }
+
+    /// Returns a `fvar` table for a font with its axes limited to `limits`, one for each axis.
+    ///
+    /// Each limit is the new minimum and maximum of the axis, in user coordinates. Axes where
+    /// these are equal are pinned to that value and removed. Instances are only kept if they're
+    /// at the pinned value of each pinned axis and within the range of the other axes.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[(Fixed, Fixed)],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        if limits.len() != usize::from(self.axis_count()) {
+            return Err(ParseError::BadValue.into());
+        }
+        let is_pinned = |&(min, max): &(Fixed, Fixed)| min == max;
+        let axis_count = limits.iter().filter(|limit| !is_pinned(limit)).count();
+        let mut instances = Vec::new();
+        for instance in self.instances() {
+            let instance = instance?;
+            let keep = instance
+                .coordinates
+                .iter()
+                .zip(limits.iter())
+                .all(|(value, &(min, max))| min <= value && value <= max);
+            if keep {
+                instances.push(instance);
+            }
+        }
+        let has_post_script_name_ids =
+            usize::from(self.instance_size) > usize::from(self.axis_count()) * Fixed::SIZE + 4;
+        let instance_size =
+            axis_count * Fixed::SIZE + 4 + if has_post_script_name_ids { 2 } else { 0 };
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, self.major_version)?;
+        U16Be::write(&mut table, self.minor_version)?;
+        U16Be::write(&mut table, 16u16)?; // axes array offset, the size of the header
+        U16Be::write(&mut table, 2u16)?; // reserved
+        U16Be::write(&mut table, u16::try_from(axis_count)?)?;
+        U16Be::write(&mut table, u16::try_from(VariationAxisRecord::SIZE)?)?;
+        U16Be::write(&mut table, u16::try_from(instances.len())?)?;
+        U16Be::write(&mut table, u16::try_from(instance_size)?)?;
+        for (axis, &(min, max)) in self.axes().zip(limits.iter()) {
+            if min == max {
+                continue;
+            }
+            U32Be::write(&mut table, axis.axis_tag)?;
+            Fixed::write(&mut table, min)?;
+            Fixed::write(&mut table, axis.default_value)?;
+            Fixed::write(&mut table, max)?;
+            U16Be::write(&mut table, axis.flags)?;
+            U16Be::write(&mut table, axis.axis_name_id)?;
+        }
+        for instance in instances {
+            U16Be::write(&mut table, instance.subfamily_name_id)?;
+            U16Be::write(&mut table, instance.flags)?;
+            for (value, limit) in instance.coordinates.iter().zip(limits.iter()) {
+                if !is_pinned(limit) {
+                    Fixed::write(&mut table, value)?;
+                }
+            }
+            if has_post_script_name_ids {
+                // 0xFFFF indicates that there's no PostScript name for the instance
+                U16Be::write(&mut table, instance.post_script_name_id.unwrap_or(0xFFFF))?;
+            }
+        }
+        Ok(table)
+    }
 }
 
 fn default_normalize(axis: &VariationAxisRecord, coord: Fixed) -> Fixed {
@@ -291,6 +361,21 @@ impl OwnedTuple {
     pub fn as_tuple(&self) -> Tuple<'_> {
         Tuple(&self.0)
     }
+
+    /// The tuple of the default instance of a font with `axis_count` axes.
+    pub(crate) fn default_instance(axis_count: u16) -> OwnedTuple {
+        OwnedTuple(
+            std::iter::repeat(F2Dot14::from(0))
+                .take(usize::from(axis_count))
+                .collect(),
+        )
+    }
+}
+
+impl From<Tuple<'_>> for OwnedTuple {
+    fn from(tuple: Tuple<'_>) -> Self {
+        OwnedTuple(TinyVec::from(tuple.0))
+    }
 }
 
 impl std::ops::Deref for OwnedTuple {
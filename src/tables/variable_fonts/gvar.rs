@@ -4,11 +4,23 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/gvar>
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use pathfinder_geometry::vector::Vector2F;
+
 use crate::binary::read::{ReadBinary, ReadCtxt, ReadScope, ReadUnchecked};
+use crate::binary::write::{WriteBinary, WriteBuffer, WriteContext};
 use crate::binary::{U16Be, U32Be};
-use crate::error::ParseError;
+use crate::error::{ParseError, ReadWriteError, WriteError};
+use crate::subset::SubsetGlyphs;
+use crate::tables::glyf::{fill_region_deltas, GlyfRecord, GlyfTable};
 use crate::tables::loca::LocaOffsets;
-use crate::tables::variable_fonts::{ReadTuple, TupleVariationStore};
+use crate::tables::variable_fonts::{
+    Gvar, NormalizedAxisLimit, OwnedTupleVariation, PointNumbers, ReadTuple, TupleVariationHeader,
+    TupleVariationStore,
+};
 use crate::tables::F2Dot14;
 use crate::SafeFrom;
 use std::fmt;
@@ -88,6 +100,21 @@ impl<'a> GvarTable<'a> {
         glyph_index: u16,
         num_points: NumPoints,
     ) -> Result<Option<TupleVariationStore<'a, super::Gvar>>, ParseError> {
+        let scope = self.glyph_variation_data_scope(glyph_index)?;
+        if !scope.data().is_empty() {
+            scope
+                .read_dep::<TupleVariationStore<'_, super::Gvar>>((
+                    self.axis_count,
+                    num_points.get(),
+                    scope,
+                ))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn glyph_variation_data_scope(&self, glyph_index: u16) -> Result<ReadScope<'a>, ParseError> {
         let glyph_index = usize::from(glyph_index);
         let start = self
             .glyph_variation_data_offsets
@@ -100,20 +127,148 @@ impl<'a> GvarTable<'a> {
             .map(usize::safe_from)
             .ok_or(ParseError::BadIndex)?;
         let length = end.checked_sub(start).ok_or(ParseError::BadOffset)?;
-        if length > 0 {
-            let scope = self
-                .glyph_variation_data_array_scope
-                .offset_length(start, length)?;
-            scope
-                .read_dep::<TupleVariationStore<'_, super::Gvar>>((
-                    self.axis_count,
-                    num_points.get(),
-                    scope,
-                ))
-                .map(Some)
-        } else {
-            Ok(None)
+        self.glyph_variation_data_array_scope
+            .offset_length(start, length)
+    }
+
+    /// Returns a `gvar` table that only contains the variations of the glyphs in `glyphs`.
+    ///
+    /// The variation data of each glyph is copied as is, since it refers to the points of the
+    /// glyph and the shared tuples, which are all retained.
+    pub(crate) fn subset(&self, glyphs: &impl SubsetGlyphs) -> Result<WriteBuffer, ReadWriteError> {
+        // Cast is safe as glyph indexes are 16-bit values
+        let glyph_variation_data = (0..glyphs.len())
+            .map(|new_id| self.glyph_variation_data_scope(glyphs.old_id(new_id as u16)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let glyph_variation_data = glyph_variation_data
+            .iter()
+            .map(|scope| scope.data())
+            .collect::<Vec<_>>();
+        let table = write_table(
+            self.major_version,
+            self.minor_version,
+            self.axis_count,
+            self.shared_tuple_count,
+            self.shared_tuples_scope.data(),
+            &glyph_variation_data,
+        )?;
+        Ok(table)
+    }
+
+    /// Returns a `gvar` table for a font with its axes limited to `limits`.
+    ///
+    /// `glyf` is the table of the glyphs that the variations apply to, which is needed to infer
+    /// the deltas of points that don't have an explicit delta. If `bake` is true the deltas that
+    /// apply at the pinned values of the axes are expected to have been applied to the glyphs. In
+    /// that case the remaining variations are given deltas for every point, as the inferred
+    /// deltas of the other points depend on the outline of the glyph before it was changed.
+    pub(crate) fn limit_axes(
+        &self,
+        glyf: &GlyfTable<'_>,
+        limits: &[NormalizedAxisLimit],
+        bake: bool,
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        let mut glyph_variations = Vec::with_capacity(glyf.records().len());
+        for (glyph_index, record) in glyf.records().iter().enumerate() {
+            // Cast is safe as glyph indexes are 16-bit values
+            let glyph_index = glyph_index as u16;
+            let mut record = record.clone();
+            record.parse()?;
+            let num_points = NumPoints::new(record.number_of_points()?);
+            let GlyfRecord::Parsed(glyph) = &record else {
+                unreachable!("glyph should be parsed")
+            };
+            let mut variations = Vec::new();
+            if let Some(store) = self.glyph_variation_data(glyph_index, num_points)? {
+                let mut region_deltas = vec![Vector2F::zero(); usize::safe_from(num_points.get())];
+                for header in store.headers() {
+                    let region = header.region(&header.peak_tuple(self)?);
+                    let data = header.variation_data(num_points, store.shared_point_numbers())?;
+                    let (point_numbers, deltas) = if bake {
+                        let explicit_deltas = data.iter().collect::<BTreeMap<_, _>>();
+                        fill_region_deltas(glyph, &explicit_deltas, &mut region_deltas)?;
+                        let deltas = region_deltas
+                            .iter()
+                            .map(|delta| delta.x())
+                            .chain(region_deltas.iter().map(|delta| delta.y()))
+                            .collect::<Vec<_>>();
+                        (Cow::Owned(PointNumbers::All(num_points.get())), deltas)
+                    } else {
+                        let deltas = data
+                            .x_coord_deltas
+                            .iter()
+                            .chain(data.y_coord_deltas.iter())
+                            .map(|&delta| f32::from(delta))
+                            .collect::<Vec<_>>();
+                        (data.point_numbers, deltas)
+                    };
+                    variations.extend(OwnedTupleVariation::limit_axes(
+                        region,
+                        &point_numbers,
+                        &deltas,
+                        limits,
+                    ));
+                }
+            }
+            glyph_variations.push(variations);
         }
+
+        // Peak tuples that are used more than once are shared, most used first
+        let mut peak_counts = BTreeMap::<_, usize>::new();
+        for variation in glyph_variations.iter().flatten() {
+            *peak_counts.entry(variation.peak()).or_default() += 1;
+        }
+        let mut shared_peaks = peak_counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .collect::<Vec<_>>();
+        shared_peaks.sort_by(|(_, a), (_, b)| b.cmp(a));
+        // Cast is safe as the index is limited to the maximum tuple index
+        let shared_tuples = shared_peaks
+            .into_iter()
+            .take(usize::from(TupleVariationHeader::<'_, Gvar>::TUPLE_INDEX_MASK) + 1)
+            .enumerate()
+            .map(|(index, (peak, _))| (peak, index as u16))
+            .collect::<BTreeMap<_, _>>();
+        let mut shared_tuples_data = WriteBuffer::new();
+        let mut sorted_shared_tuples = shared_tuples.iter().collect::<Vec<_>>();
+        sorted_shared_tuples.sort_by_key(|&(_, index)| index);
+        for (peak, _) in sorted_shared_tuples {
+            shared_tuples_data.write_iter::<F2Dot14, _>(peak.iter().copied())?;
+        }
+
+        let glyph_variation_data = glyph_variations
+            .iter()
+            .map(|variations| {
+                let mut data = WriteBuffer::new();
+                if !variations.is_empty() {
+                    TupleVariationStore::<'_, Gvar>::write_tuple_variations(
+                        &mut data,
+                        variations,
+                        &shared_tuples,
+                        0,
+                    )?;
+                }
+                Ok(data.into_inner())
+            })
+            .collect::<Result<Vec<_>, WriteError>>()?;
+        let axis_count = limits
+            .iter()
+            .filter(|limit| matches!(limit, NormalizedAxisLimit::Range(..)))
+            .count();
+        let table = write_table(
+            self.major_version,
+            self.minor_version,
+            u16::try_from(axis_count)?,
+            u16::try_from(shared_tuples.len())?,
+            shared_tuples_data.bytes(),
+            &glyph_variation_data
+                .iter()
+                .map(|data| data.as_slice())
+                .collect::<Vec<_>>(),
+        )?;
+        Ok(table)
     }
 
     /// Returns the shared peak tuple at the supplied index.
@@ -133,6 +288,72 @@ impl<'a> GvarTable<'a> {
     }
 }
 
+/// Write a `gvar` table with the supplied shared tuples and variation data for each glyph.
+fn write_table(
+    major_version: u16,
+    minor_version: u16,
+    axis_count: u16,
+    shared_tuple_count: u16,
+    shared_tuples: &[u8],
+    glyph_variation_data: &[&[u8]],
+) -> Result<WriteBuffer, WriteError> {
+    // Short offsets are used if the data fits, with each glyph's data padded to an even
+    // length so that the offsets can be divided by two
+    let short_length = glyph_variation_data
+        .iter()
+        .map(|data| data.len() + data.len() % 2)
+        .sum::<usize>();
+    let short_offsets = short_length / 2 <= usize::from(u16::MAX);
+    let mut offsets = Vec::with_capacity(glyph_variation_data.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for data in glyph_variation_data {
+        offset += data.len();
+        if short_offsets {
+            offset += offset % 2;
+        }
+        offsets.push(offset);
+    }
+
+    let glyph_count = u16::try_from(glyph_variation_data.len())?;
+    let offsets_size = if short_offsets {
+        offsets.len() * U16Be::SIZE
+    } else {
+        offsets.len() * U32Be::SIZE
+    };
+    // The header is 20 bytes, and followed by the offsets and shared tuples
+    let shared_tuples_offset = 20 + offsets_size;
+    let glyph_variation_data_array_offset = shared_tuples_offset + shared_tuples.len();
+
+    let mut table = WriteBuffer::new();
+    U16Be::write(&mut table, major_version)?;
+    U16Be::write(&mut table, minor_version)?;
+    U16Be::write(&mut table, axis_count)?;
+    U16Be::write(&mut table, shared_tuple_count)?;
+    U32Be::write(&mut table, u32::try_from(shared_tuples_offset)?)?;
+    U16Be::write(&mut table, glyph_count)?;
+    U16Be::write(&mut table, if short_offsets { 0u16 } else { 1 })?; // flags
+    U32Be::write(
+        &mut table,
+        u32::try_from(glyph_variation_data_array_offset)?,
+    )?;
+    for offset in offsets {
+        if short_offsets {
+            U16Be::write(&mut table, u16::try_from(offset / 2)?)?;
+        } else {
+            U32Be::write(&mut table, u32::try_from(offset)?)?;
+        }
+    }
+    table.write_bytes(shared_tuples)?;
+    for data in glyph_variation_data.iter().copied() {
+        table.write_bytes(data)?;
+        if short_offsets && data.len() % 2 == 1 {
+            table.write_zeros(1)?;
+        }
+    }
+    Ok(table)
+}
+
 impl ReadBinary for GvarTable<'_> {
     type HostType<'a> = GvarTable<'a>;
 
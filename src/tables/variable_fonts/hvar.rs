@@ -7,9 +7,12 @@
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/hvar>
 
 use crate::binary::read::{ReadBinary, ReadCtxt, ReadScope};
-use crate::error::ParseError;
+use crate::binary::write::WriteBuffer;
+use crate::error::{ParseError, ReadWriteError};
+use crate::subset::SubsetGlyphs;
 use crate::tables::variable_fonts::{
-    DeltaSetIndexMap, DeltaSetIndexMapEntry, ItemVariationStore, OwnedTuple,
+    limit_metrics_variations, subset_metrics_variations, DeltaSetIndexMap, DeltaSetIndexMapEntry,
+    ItemVariationStore, NormalizedAxisLimit, OwnedTuple,
 };
 use crate::SafeFrom;
 
@@ -87,6 +90,39 @@ impl<'a> HvarTable<'a> {
     }
 }
 
+impl HvarTable<'_> {
+    /// Returns a `HVAR` table that only contains the variations of the glyphs in `glyphs`.
+    pub(crate) fn subset(&self, glyphs: &impl SubsetGlyphs) -> Result<WriteBuffer, ReadWriteError> {
+        subset_metrics_variations(
+            self.minor_version,
+            &self.item_variation_store,
+            &[
+                self.advance_width_mapping.as_ref(),
+                self.lsb_mapping.as_ref(),
+                self.rsb_mapping.as_ref(),
+            ],
+            glyphs,
+        )
+    }
+
+    /// Returns a `HVAR` table for a font with its axes limited to `limits`.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[NormalizedAxisLimit],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        limit_metrics_variations(
+            self.minor_version,
+            &self.item_variation_store,
+            &[
+                self.advance_width_mapping.as_ref(),
+                self.lsb_mapping.as_ref(),
+                self.rsb_mapping.as_ref(),
+            ],
+            limits,
+        )
+    }
+}
+
 impl ReadBinary for HvarTable<'_> {
     type HostType<'a> = HvarTable<'a>;
 
@@ -118,7 +154,7 @@ impl ReadBinary for HvarTable<'_> {
     }
 }
 
-fn read_optional_index_map(
+pub(super) fn read_optional_index_map(
     scope: ReadScope<'_>,
     offset: u32,
 ) -> Result<Option<DeltaSetIndexMap<'_>>, ParseError> {
@@ -2,10 +2,15 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/mvar>
 
+use std::convert::TryFrom;
+
 use crate::binary::read::{ReadArray, ReadBinary, ReadCtxt, ReadFrom, ReadUnchecked};
+use crate::binary::write::{WriteBinary, WriteBuffer, WriteContext};
 use crate::binary::{U16Be, U32Be};
-use crate::error::ParseError;
-use crate::tables::variable_fonts::{DeltaSetIndexMapEntry, ItemVariationStore, OwnedTuple};
+use crate::error::{ParseError, ReadWriteError};
+use crate::tables::variable_fonts::{
+    owned, DeltaSetIndexMapEntry, ItemVariationStore, NormalizedAxisLimit, OwnedTuple,
+};
 
 /// `MVAR` Metrics Variations Table
 pub struct MvarTable<'a> {
@@ -77,6 +82,41 @@ impl<'a> MvarTable<'a> {
         // NOTE(cast): Safe as value_records was contructed from u16 value_record_count.
         self.value_records.len() as u16
     }
+
+    /// Returns a `MVAR` table for a font with its axes limited to `limits`.
+    ///
+    /// The value records are unchanged as the delta sets keep their indices.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[NormalizedAxisLimit],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        let store = self
+            .item_variation_store
+            .as_ref()
+            .map(|store| store.limit_axes(limits))
+            .transpose()?;
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, self.major_version)?;
+        U16Be::write(&mut table, self.minor_version)?;
+        U16Be::write(&mut table, 0u16)?; // reserved
+        U16Be::write(&mut table, u16::try_from(ValueRecord::SIZE)?)?;
+        U16Be::write(&mut table, self.value_records_len())?;
+        let store_offset = table.placeholder::<U16Be, _>()?;
+        for record in self.value_records() {
+            U32Be::write(&mut table, record.value_tag)?;
+            U16Be::write(&mut table, record.delta_set_outer_index)?;
+            U16Be::write(&mut table, record.delta_set_inner_index)?;
+        }
+        match store {
+            Some(store) => {
+                table.write_placeholder(store_offset, u16::try_from(table.bytes_written())?)?;
+                owned::ItemVariationStore::write(&mut table, &store)?;
+            }
+            None => table.write_placeholder(store_offset, 0)?,
+        }
+        Ok(table)
+    }
 }
 
 impl ReadBinary for MvarTable<'_> {
@@ -10,6 +10,7 @@
 //!
 //! <https://learn.microsoft.com/en-us/typography/opentype/spec/stat>
 
+use std::convert::TryFrom;
 use std::fmt;
 
 use bitflags::bitflags;
@@ -18,8 +19,9 @@ use crate::binary::read::{
     ReadArray, ReadBinary, ReadBinaryDep, ReadCtxt, ReadFixedSizeDep, ReadFrom, ReadScope,
     ReadUnchecked,
 };
+use crate::binary::write::{WriteBinary, WriteBuffer, WriteContext};
 use crate::binary::{U16Be, U32Be};
-use crate::error::ParseError;
+use crate::error::{ParseError, ReadWriteError};
 use crate::tables::Fixed;
 use crate::tag::DisplayTag;
 use crate::{size, SafeFrom};
@@ -239,6 +241,98 @@ impl<'a> StatTable<'a> {
         })
     }
 
+    /// Returns a `STAT` table for a font with its variation axes limited to `limits`, the tag of
+    /// each axis in the `fvar` table with its new minimum and maximum value.
+    ///
+    /// Axis value tables with a value outside of the new range of an axis are removed, as are
+    /// format 2 tables with a range that doesn't overlap it. The design axes are unchanged, since
+    /// they can include axes that aren't in the `fvar` table.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[(u32, Fixed, Fixed)],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        // The limits of each design axis, if it's an axis of the font
+        let axis_limits = (0..usize::from(self.design_axis_count))
+            .map(|index| {
+                let axis = self.design_axis(index)?;
+                Ok(limits
+                    .iter()
+                    .find(|&&(axis_tag, _, _)| axis_tag == axis.axis_tag)
+                    .map(|&(_, min, max)| (min, max)))
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        let overlaps = |axis_index: u16, start: Fixed, end: Fixed| match axis_limits
+            .get(usize::from(axis_index))
+        {
+            Some(&Some((min, max))) => start <= max && min <= end,
+            _ => true,
+        };
+
+        let mut axis_values = Vec::new();
+        for offset in self.axis_value_offsets.iter() {
+            let scope = self.axis_value_scope.offset(usize::from(offset));
+            let (keep, length) = match scope.read_dep::<AxisValueTable<'_>>(self.design_axis_count)
+            {
+                Ok(AxisValueTable::Format1(table)) => {
+                    (overlaps(table.axis_index, table.value, table.value), 12)
+                }
+                Ok(AxisValueTable::Format2(table)) => {
+                    let keep = overlaps(
+                        table.axis_index,
+                        table.range_min_value,
+                        table.range_max_value,
+                    );
+                    (keep, 20)
+                }
+                Ok(AxisValueTable::Format3(table)) => {
+                    (overlaps(table.axis_index, table.value, table.value), 16)
+                }
+                Ok(AxisValueTable::Format4(table)) => {
+                    let values = table
+                        .axis_values
+                        .iter_res()
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let keep = values
+                        .iter()
+                        .all(|value| overlaps(value.axis_index, value.value, value.value));
+                    (keep, 8 + table.axis_values.len() * AxisValue::size(0))
+                }
+                // Tables with an unknown format are ignored, and so are left out
+                Err(ParseError::BadVersion) => (false, 0),
+                Err(err) => return Err(err.into()),
+            };
+            if keep {
+                axis_values.push(scope.offset_length(0, length)?.data());
+            }
+        }
+
+        let mut table = WriteBuffer::new();
+        U16Be::write(&mut table, self.major_version)?;
+        U16Be::write(&mut table, self.minor_version)?;
+        U16Be::write(&mut table, self.design_axis_size)?;
+        U16Be::write(&mut table, self.design_axis_count)?;
+        let design_axes_offset = table.placeholder::<U32Be, _>()?;
+        U16Be::write(&mut table, u16::try_from(axis_values.len())?)?;
+        let axis_value_offsets_offset = table.placeholder::<U32Be, _>()?;
+        if let Some(elided_fallback_name_id) = self.elided_fallback_name_id {
+            U16Be::write(&mut table, elided_fallback_name_id)?;
+        }
+        table.write_placeholder(design_axes_offset, u32::try_from(table.bytes_written())?)?;
+        table.write_bytes(self.design_axes_array)?;
+        let axis_value_offsets_start = table.bytes_written();
+        table.write_placeholder(
+            axis_value_offsets_offset,
+            u32::try_from(axis_value_offsets_start)?,
+        )?;
+        let axis_value_offsets = table.placeholder_array::<U16Be, u16>(axis_values.len())?;
+        for (axis_value, offset) in axis_values.into_iter().zip(axis_value_offsets) {
+            let axis_value_offset = table.bytes_written() - axis_value_offsets_start;
+            table.write_placeholder(offset, u16::try_from(axis_value_offset)?)?;
+            table.write_bytes(axis_value)?;
+        }
+        Ok(table)
+    }
+
     /// Find a name that best describes `value` in the axis at index
     /// `axis_index`.
     ///
@@ -0,0 +1,172 @@
+//! `VVAR` — Vertical Metrics Variations Table
+//!
+//! Optional table in variable fonts to provide vertical metrics variations.
+//! If absent then `gvar` deltas must be used to determine adjustments to
+//! metrics.
+//!
+//! <https://learn.microsoft.com/en-us/typography/opentype/spec/vvar>
+
+use crate::binary::read::{ReadBinary, ReadCtxt};
+use crate::binary::write::WriteBuffer;
+use crate::error::{ParseError, ReadWriteError};
+use crate::subset::SubsetGlyphs;
+use crate::tables::variable_fonts::hvar::read_optional_index_map;
+use crate::tables::variable_fonts::{
+    limit_metrics_variations, subset_metrics_variations, DeltaSetIndexMap, DeltaSetIndexMapEntry,
+    ItemVariationStore, NormalizedAxisLimit, OwnedTuple,
+};
+use crate::SafeFrom;
+
+/// `VVAR` — Vertical Metrics Variations Table.
+pub struct VvarTable<'a> {
+    /// Major version number of the vertical metrics variations table.
+    pub major_version: u16,
+    /// Minor version number of the vertical metrics variations table.
+    pub minor_version: u16,
+    /// The item variation store table.
+    item_variation_store: ItemVariationStore<'a>,
+    /// The delta-set index mapping for advance heights.
+    advance_height_mapping: Option<DeltaSetIndexMap<'a>>,
+    /// The delta-set index mapping for top side bearings.
+    tsb_mapping: Option<DeltaSetIndexMap<'a>>,
+    /// The delta-set index mapping for bottom side bearings.
+    bsb_mapping: Option<DeltaSetIndexMap<'a>>,
+    /// The delta-set index mapping for the y coordinates of vertical origins.
+    v_org_mapping: Option<DeltaSetIndexMap<'a>>,
+}
+
+impl<'a> VvarTable<'a> {
+    /// Calculate the delta for the advance height of the supplied `glyph_id`.
+    pub fn advance_delta(&self, instance: &OwnedTuple, glyph_id: u16) -> Result<f32, ParseError> {
+        // As in `HVAR`, glyph indices are used as implicit delta-set indices when there is no
+        // mapping for advance heights.
+        let delta_set_entry =
+            Self::delta_set_entry_for_glyph(glyph_id, self.advance_height_mapping.as_ref())?
+                .unwrap_or(DeltaSetIndexMapEntry {
+                    outer_index: 0,
+                    inner_index: glyph_id,
+                });
+        self.item_variation_store
+            .adjustment(delta_set_entry, instance)
+    }
+
+    /// Calculate the delta for the top side bearing of the supplied `glyph_id`.
+    pub fn top_side_bearing_delta(
+        &self,
+        instance: &OwnedTuple,
+        glyph_id: u16,
+    ) -> Result<Option<f32>, ParseError> {
+        self.mapped_delta(instance, glyph_id, self.tsb_mapping.as_ref())
+    }
+
+    /// Calculate the delta for the bottom side bearing of the supplied `glyph_id`.
+    pub fn bottom_side_bearing_delta(
+        &self,
+        instance: &OwnedTuple,
+        glyph_id: u16,
+    ) -> Result<Option<f32>, ParseError> {
+        self.mapped_delta(instance, glyph_id, self.bsb_mapping.as_ref())
+    }
+
+    /// Calculate the delta for the y coordinate of the vertical origin of the supplied
+    /// `glyph_id`, which is given in the `VORG` table.
+    pub fn vert_origin_delta(
+        &self,
+        instance: &OwnedTuple,
+        glyph_id: u16,
+    ) -> Result<Option<f32>, ParseError> {
+        self.mapped_delta(instance, glyph_id, self.v_org_mapping.as_ref())
+    }
+
+    fn mapped_delta(
+        &self,
+        instance: &OwnedTuple,
+        glyph_id: u16,
+        delta_set_index_map: Option<&DeltaSetIndexMap<'_>>,
+    ) -> Result<Option<f32>, ParseError> {
+        Self::delta_set_entry_for_glyph(glyph_id, delta_set_index_map)?
+            .map(|delta_set_entry| {
+                self.item_variation_store
+                    .adjustment(delta_set_entry, instance)
+            })
+            .transpose()
+    }
+
+    fn delta_set_entry_for_glyph(
+        glyph_id: u16,
+        delta_set_index_map: Option<&DeltaSetIndexMap<'_>>,
+    ) -> Result<Option<DeltaSetIndexMapEntry>, ParseError> {
+        delta_set_index_map
+            .map(|mapping| mapping.entry(u32::from(glyph_id)))
+            .transpose()
+    }
+}
+
+impl VvarTable<'_> {
+    /// Returns a `VVAR` table that only contains the variations of the glyphs in `glyphs`.
+    pub(crate) fn subset(&self, glyphs: &impl SubsetGlyphs) -> Result<WriteBuffer, ReadWriteError> {
+        subset_metrics_variations(
+            self.minor_version,
+            &self.item_variation_store,
+            &[
+                self.advance_height_mapping.as_ref(),
+                self.tsb_mapping.as_ref(),
+                self.bsb_mapping.as_ref(),
+                self.v_org_mapping.as_ref(),
+            ],
+            glyphs,
+        )
+    }
+
+    /// Returns a `VVAR` table for a font with its axes limited to `limits`.
+    pub(crate) fn limit_axes(
+        &self,
+        limits: &[NormalizedAxisLimit],
+    ) -> Result<WriteBuffer, ReadWriteError> {
+        limit_metrics_variations(
+            self.minor_version,
+            &self.item_variation_store,
+            &[
+                self.advance_height_mapping.as_ref(),
+                self.tsb_mapping.as_ref(),
+                self.bsb_mapping.as_ref(),
+                self.v_org_mapping.as_ref(),
+            ],
+            limits,
+        )
+    }
+}
+
+impl ReadBinary for VvarTable<'_> {
+    type HostType<'a> = VvarTable<'a>;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let scope = ctxt.scope();
+        let major_version = ctxt.read_u16be()?;
+        ctxt.check_version(major_version == 1)?;
+        let minor_version = ctxt.read_u16be()?;
+        let item_variation_store_offset = ctxt.read_u32be()?;
+        let advance_height_mapping_offset = ctxt.read_u32be()?;
+        let tsb_mapping_offset = ctxt.read_u32be()?;
+        let bsb_mapping_offset = ctxt.read_u32be()?;
+        let v_org_mapping_offset = ctxt.read_u32be()?;
+
+        let item_variation_store = scope
+            .offset(usize::safe_from(item_variation_store_offset))
+            .read::<ItemVariationStore<'_>>()?;
+        let advance_height_mapping = read_optional_index_map(scope, advance_height_mapping_offset)?;
+        let tsb_mapping = read_optional_index_map(scope, tsb_mapping_offset)?;
+        let bsb_mapping = read_optional_index_map(scope, bsb_mapping_offset)?;
+        let v_org_mapping = read_optional_index_map(scope, v_org_mapping_offset)?;
+
+        Ok(VvarTable {
+            major_version,
+            minor_version,
+            item_variation_store,
+            advance_height_mapping,
+            tsb_mapping,
+            bsb_mapping,
+            v_org_mapping,
+        })
+    }
+}
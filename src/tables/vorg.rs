@@ -0,0 +1,116 @@
+//! Parsing and writing of the `VORG` table.
+//!
+//! > This optional table specifies the y coordinate of the vertical origin of every glyph in the
+//! > font.
+//!
+//! — <https://learn.microsoft.com/en-us/typography/opentype/spec/vorg>
+//!
+//! The table is used by fonts with CFF outlines. The vertical origin of glyphs with TrueType
+//! outlines is derived from their bounding box and the top side bearing in `vmtx` instead.
+
+use std::convert::TryFrom;
+
+use crate::binary::read::{ReadBinary, ReadCtxt, ReadFrom};
+use crate::binary::write::{WriteBinary, WriteContext};
+use crate::binary::{I16Be, U16Be};
+use crate::error::{ParseError, WriteError};
+
+/// `VORG` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VorgTable {
+    /// The y coordinate of the vertical origin of glyphs that aren't listed in
+    /// `vert_origin_y_metrics`.
+    pub default_vert_origin_y: i16,
+    /// The vertical origins of glyphs that differ from the default, sorted by glyph index.
+    pub vert_origin_y_metrics: Vec<VertOriginYMetrics>,
+}
+
+/// The y coordinate of the vertical origin of a glyph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VertOriginYMetrics {
+    pub glyph_index: u16,
+    pub vert_origin_y: i16,
+}
+
+impl VorgTable {
+    /// Returns the y coordinate of the vertical origin of `glyph_index`.
+    pub fn vert_origin_y(&self, glyph_index: u16) -> i16 {
+        self.vert_origin_y_metrics
+            .binary_search_by_key(&glyph_index, |metrics| metrics.glyph_index)
+            .map_or(self.default_vert_origin_y, |index| {
+                self.vert_origin_y_metrics[index].vert_origin_y
+            })
+    }
+}
+
+impl ReadBinary for VorgTable {
+    type HostType<'a> = VorgTable;
+
+    fn read<'a>(ctxt: &mut ReadCtxt<'a>) -> Result<Self::HostType<'a>, ParseError> {
+        let major_version = ctxt.read_u16be()?;
+        ctxt.check_version(major_version == 1)?;
+        let _minor_version = ctxt.read_u16be()?;
+        let default_vert_origin_y = ctxt.read_i16be()?;
+        let num_vert_origin_y_metrics = usize::from(ctxt.read_u16be()?);
+        let vert_origin_y_metrics = ctxt
+            .read_array::<VertOriginYMetrics>(num_vert_origin_y_metrics)?
+            .to_vec();
+        Ok(VorgTable {
+            default_vert_origin_y,
+            vert_origin_y_metrics,
+        })
+    }
+}
+
+impl ReadFrom for VertOriginYMetrics {
+    type ReadType = (U16Be, I16Be);
+
+    fn read_from((glyph_index, vert_origin_y): (u16, i16)) -> Self {
+        VertOriginYMetrics {
+            glyph_index,
+            vert_origin_y,
+        }
+    }
+}
+
+impl WriteBinary<&Self> for VorgTable {
+    type Output = ();
+
+    fn write<C: WriteContext>(ctxt: &mut C, table: &VorgTable) -> Result<(), WriteError> {
+        U16Be::write(ctxt, 1u16)?; // major_version
+        U16Be::write(ctxt, 0u16)?; // minor_version
+        I16Be::write(ctxt, table.default_vert_origin_y)?;
+        U16Be::write(ctxt, u16::try_from(table.vert_origin_y_metrics.len())?)?;
+        for metrics in &table.vert_origin_y_metrics {
+            U16Be::write(ctxt, metrics.glyph_index)?;
+            I16Be::write(ctxt, metrics.vert_origin_y)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::read::ReadScope;
+    use crate::binary::write::WriteBuffer;
+
+    #[test]
+    fn test_read_write_vorg() {
+        let data = [
+            0, 1, 0, 0, // version
+            0x03, 0x70, // default of 880
+            0, 2, // two metrics
+            0, 3, 0x03, 0x20, // glyph 3 → 800
+            0, 9, 0xFF, 0x9C, // glyph 9 → -100
+        ];
+        let vorg = ReadScope::new(&data).read::<VorgTable>().unwrap();
+        assert_eq!(vorg.vert_origin_y(3), 800);
+        assert_eq!(vorg.vert_origin_y(9), -100);
+        assert_eq!(vorg.vert_origin_y(4), 880);
+
+        let mut buffer = WriteBuffer::new();
+        VorgTable::write(&mut buffer, &vorg).unwrap();
+        assert_eq!(buffer.bytes(), &data);
+    }
+}
@@ -81,6 +81,8 @@ impl fmt::Debug for DisplayTag {
     }
 }
 
+/// `aalt`
+pub const AALT: u32 = tag!(b"aalt");
 /// `abvf`
 pub const ABVF: u32 = tag!(b"abvf");
 /// `abvm`
@@ -89,20 +91,32 @@ pub const ABVM: u32 = tag!(b"abvm");
 pub const ABVS: u32 = tag!(b"abvs");
 /// `acnt`
 pub const ACNT: u32 = tag!(b"acnt");
+/// `adlm`
+pub const ADLM: u32 = tag!(b"adlm");
 /// `afrc`
 pub const AFRC: u32 = tag!(b"afrc");
+/// `ahom`
+pub const AHOM: u32 = tag!(b"ahom");
 /// `akhn`
 pub const AKHN: u32 = tag!(b"akhn");
+/// `ankr`
+pub const ANKR: u32 = tag!(b"ankr");
 /// `arab`
 pub const ARAB: u32 = tag!(b"arab");
 /// `avar`
 pub const AVAR: u32 = tag!(b"avar");
+/// `bali`
+pub const BALI: u32 = tag!(b"bali");
+/// `batk`
+pub const BATK: u32 = tag!(b"batk");
 /// `BASE`
 pub const BASE: u32 = tag!(b"BASE");
 /// `bdat`
 pub const BDAT: u32 = tag!(b"bdat");
 /// `beng`
 pub const BENG: u32 = tag!(b"beng");
+/// `bhks`
+pub const BHKS: u32 = tag!(b"bhks");
 /// `bloc`
 pub const BLOC: u32 = tag!(b"bloc");
 /// `blwf`
@@ -113,10 +127,18 @@ pub const BLWM: u32 = tag!(b"blwm");
 pub const BLWS: u32 = tag!(b"blws");
 /// `bng2`
 pub const BNG2: u32 = tag!(b"bng2");
+/// `brah`
+pub const BRAH: u32 = tag!(b"brah");
 /// `bsln`
 pub const BSLN: u32 = tag!(b"bsln");
+/// `bugi`
+pub const BUGI: u32 = tag!(b"bugi");
+/// `buhd`
+pub const BUHD: u32 = tag!(b"buhd");
 /// `c2sc`
 pub const C2SC: u32 = tag!(b"c2sc");
+/// `cakm`
+pub const CAKM: u32 = tag!(b"cakm");
 /// `calt`
 pub const CALT: u32 = tag!(b"calt");
 /// `CBDT`
@@ -131,6 +153,10 @@ pub const CFAR: u32 = tag!(b"cfar");
 pub const CFF: u32 = tag!(b"CFF ");
 /// `CFF2`
 pub const CFF2: u32 = tag!(b"CFF2");
+/// `cham`
+pub const CHAM: u32 = tag!(b"cham");
+/// `chrs`
+pub const CHRS: u32 = tag!(b"chrs");
 /// `cjct`
 pub const CJCT: u32 = tag!(b"cjct");
 /// `clig`
@@ -155,10 +181,14 @@ pub const DEV2: u32 = tag!(b"dev2");
 pub const DEVA: u32 = tag!(b"deva");
 /// `DFLT`
 pub const DFLT: u32 = tag!(b"DFLT");
+/// `diak`
+pub const DIAK: u32 = tag!(b"diak");
 /// `dist`
 pub const DIST: u32 = tag!(b"dist");
 /// `dlig`
 pub const DLIG: u32 = tag!(b"dlig");
+/// `dogr`
+pub const DOGR: u32 = tag!(b"dogr");
 /// `dupe`
 pub const DUPE: u32 = tag!(b"dupe");
 /// `EBDT`
@@ -201,8 +231,14 @@ pub const GLAT: u32 = tag!(b"Glat");
 pub const GLOC: u32 = tag!(b"Gloc");
 /// `glyf`
 pub const GLYF: u32 = tag!(b"glyf");
+/// `gong`
+pub const GONG: u32 = tag!(b"gong");
+/// `gonm`
+pub const GONM: u32 = tag!(b"gonm");
 /// `GPOS`
 pub const GPOS: u32 = tag!(b"GPOS");
+/// `gran`
+pub const GRAN: u32 = tag!(b"gran");
 /// `grek`
 pub const GREK: u32 = tag!(b"grek");
 /// `GSUB`
@@ -219,6 +255,12 @@ pub const GVAR: u32 = tag!(b"gvar");
 pub const HALF: u32 = tag!(b"half");
 /// `haln`
 pub const HALN: u32 = tag!(b"haln");
+/// `hang`
+pub const HANG: u32 = tag!(b"hang");
+/// `hani`
+pub const HANI: u32 = tag!(b"hani");
+/// `hano`
+pub const HANO: u32 = tag!(b"hano");
 /// `hdmx`
 pub const HDMX: u32 = tag!(b"hdmx");
 /// `head`
@@ -237,28 +279,52 @@ pub const HVAR: u32 = tag!(b"HVAR");
 pub const INIT: u32 = tag!(b"init");
 /// `isol`
 pub const ISOL: u32 = tag!(b"isol");
+/// `java`
+pub const JAVA: u32 = tag!(b"java");
 /// `jpg `
 pub const JPG: u32 = tag!(b"jpg ");
 /// `JSTF`
 pub const JSTF: u32 = tag!(b"JSTF");
 /// `just`
 pub const JUST: u32 = tag!(b"just");
+/// `kali`
+pub const KALI: u32 = tag!(b"kali");
+/// `kana`
+pub const KANA: u32 = tag!(b"kana");
+/// `kawi`
+pub const KAWI: u32 = tag!(b"kawi");
 /// `kern`
 pub const KERN: u32 = tag!(b"kern");
+/// `kerx`
+pub const KERX: u32 = tag!(b"kerx");
+/// `khar`
+pub const KHAR: u32 = tag!(b"khar");
 /// `khmr`
 pub const KHMR: u32 = tag!(b"khmr");
+/// `khoj`
+pub const KHOJ: u32 = tag!(b"khoj");
 /// `knd2`
 pub const KND2: u32 = tag!(b"knd2");
 /// `knda`
 pub const KNDA: u32 = tag!(b"knda");
+/// `kthi`
+pub const KTHI: u32 = tag!(b"kthi");
+/// `lana`
+pub const LANA: u32 = tag!(b"lana");
 /// `lao `
 pub const LAO: u32 = tag!(b"lao ");
 /// `latn`
 pub const LATN: u32 = tag!(b"latn");
 /// `lcar`
 pub const LCAR: u32 = tag!(b"lcar");
+/// `lepc`
+pub const LEPC: u32 = tag!(b"lepc");
 /// `liga`
 pub const LIGA: u32 = tag!(b"liga");
+/// `limb`
+pub const LIMB: u32 = tag!(b"limb");
+/// `ljmo`
+pub const LJMO: u32 = tag!(b"ljmo");
 /// `lnum`
 pub const LNUM: u32 = tag!(b"lnum");
 /// `loca`
@@ -267,6 +333,16 @@ pub const LOCA: u32 = tag!(b"loca");
 pub const LOCL: u32 = tag!(b"locl");
 /// `LTSH`
 pub const LTSH: u32 = tag!(b"LTSH");
+/// `mahj`
+pub const MAHJ: u32 = tag!(b"mahj");
+/// `maka`
+pub const MAKA: u32 = tag!(b"maka");
+/// `mand`
+pub const MAND: u32 = tag!(b"mand");
+/// `mani`
+pub const MANI: u32 = tag!(b"mani");
+/// `marc`
+pub const MARC: u32 = tag!(b"marc");
 /// `mark`
 pub const MARK: u32 = tag!(b"mark");
 /// `MATH`
@@ -283,6 +359,18 @@ pub const MKMK: u32 = tag!(b"mkmk");
 pub const MLM2: u32 = tag!(b"mlm2");
 /// `mlym`
 pub const MLYM: u32 = tag!(b"mlym");
+/// `modi`
+pub const MODI: u32 = tag!(b"modi");
+/// `mong`
+pub const MONG: u32 = tag!(b"mong");
+/// `mtei`
+pub const MTEI: u32 = tag!(b"mtei");
+/// `mult`
+pub const MULT: u32 = tag!(b"mult");
+/// `mym2`
+pub const MYM2: u32 = tag!(b"mym2");
+/// `mymr`
+pub const MYMR: u32 = tag!(b"mymr");
 /// `mort`
 pub const MORT: u32 = tag!(b"mort");
 /// `morx`
@@ -293,6 +381,12 @@ pub const MVAR: u32 = tag!(b"MVAR");
 pub const MSET: u32 = tag!(b"mset");
 /// `name`
 pub const NAME: u32 = tag!(b"name");
+/// `nand`
+pub const NAND: u32 = tag!(b"nand");
+/// `newa`
+pub const NEWA: u32 = tag!(b"newa");
+/// `nko `
+pub const NKO: u32 = tag!(b"nko ");
 /// `nukt`
 pub const NUKT: u32 = tag!(b"nukt");
 /// `onum`
@@ -309,8 +403,14 @@ pub const ORYA: u32 = tag!(b"orya");
 pub const OS_2: u32 = tag!(b"OS/2");
 /// `OTTO`
 pub const OTTO: u32 = tag!(b"OTTO");
+/// `ougr`
+pub const OUGR: u32 = tag!(b"ougr");
 /// `PCLT`
 pub const PCLT: u32 = tag!(b"PCLT");
+/// `phag`
+pub const PHAG: u32 = tag!(b"phag");
+/// `phlp`
+pub const PHLP: u32 = tag!(b"phlp");
 /// `pnum`
 pub const PNUM: u32 = tag!(b"pnum");
 /// `png `
@@ -331,42 +431,84 @@ pub const PSTF: u32 = tag!(b"pstf");
 pub const PSTS: u32 = tag!(b"psts");
 /// `rclt`
 pub const RCLT: u32 = tag!(b"rclt");
+/// `rjng`
+pub const RJNG: u32 = tag!(b"rjng");
 /// `rkrf`
 pub const RKRF: u32 = tag!(b"rkrf");
 /// `rlig`
 pub const RLIG: u32 = tag!(b"rlig");
+/// `rohg`
+pub const ROHG: u32 = tag!(b"rohg");
 /// `rphf`
 pub const RPHF: u32 = tag!(b"rphf");
+/// `rtlm`
+pub const RTLM: u32 = tag!(b"rtlm");
 /// `rvrn`
 pub const RVRN: u32 = tag!(b"rvrn");
+/// `salt`
+pub const SALT: u32 = tag!(b"salt");
+/// `saur`
+pub const SAUR: u32 = tag!(b"saur");
 /// `sbix`
 pub const SBIX: u32 = tag!(b"sbix");
+/// `shrd`
+pub const SHRD: u32 = tag!(b"shrd");
+/// `sidd`
+pub const SIDD: u32 = tag!(b"sidd");
 /// `Silf`
 pub const SILF: u32 = tag!(b"Silf");
 /// `Sill`
 pub const SILL: u32 = tag!(b"Sill");
+/// `sind`
+pub const SIND: u32 = tag!(b"sind");
 /// `sinh`
 pub const SINH: u32 = tag!(b"sinh");
+/// `size`
+pub const SIZE: u32 = tag!(b"size");
 /// `smcp`
 pub const SMCP: u32 = tag!(b"smcp");
 /// `SND `
 pub const SND: u32 = tag!(b"SND ");
+/// `sogd`
+pub const SOGD: u32 = tag!(b"sogd");
+/// `soyo`
+pub const SOYO: u32 = tag!(b"soyo");
 /// `STAT`
 pub const STAT: u32 = tag!(b"STAT");
+/// `sund`
+pub const SUND: u32 = tag!(b"sund");
 /// `SVG `
 pub const SVG: u32 = tag!(b"SVG ");
+/// `swsh`
+pub const SWSH: u32 = tag!(b"swsh");
+/// `sylo`
+pub const SYLO: u32 = tag!(b"sylo");
 /// `syrc`
 pub const SYRC: u32 = tag!(b"syrc");
+/// `tagb`
+pub const TAGB: u32 = tag!(b"tagb");
+/// `takr`
+pub const TAKR: u32 = tag!(b"takr");
 /// `taml`
 pub const TAML: u32 = tag!(b"taml");
+/// `tavt`
+pub const TAVT: u32 = tag!(b"tavt");
 /// `tel2`
 pub const TEL2: u32 = tag!(b"tel2");
 /// `telu`
 pub const TELU: u32 = tag!(b"telu");
+/// `tglg`
+pub const TGLG: u32 = tag!(b"tglg");
 /// `thai`
 pub const THAI: u32 = tag!(b"thai");
+/// `tibt`
+pub const TIBT: u32 = tag!(b"tibt");
 /// `tiff`
 pub const TIFF: u32 = tag!(b"tiff");
+/// `tirh`
+pub const TIRH: u32 = tag!(b"tirh");
+/// `tjmo`
+pub const TJMO: u32 = tag!(b"tjmo");
 /// `tml2`
 pub const TML2: u32 = tag!(b"tml2");
 /// `tnum`
@@ -387,12 +529,24 @@ pub const VDMX: u32 = tag!(b"VDMX");
 pub const VERT: u32 = tag!(b"vert");
 /// `vhea`
 pub const VHEA: u32 = tag!(b"vhea");
+/// `vjmo`
+pub const VJMO: u32 = tag!(b"vjmo");
+/// `vkrn`
+pub const VKRN: u32 = tag!(b"vkrn");
 /// `vmtx`
 pub const VMTX: u32 = tag!(b"vmtx");
 /// `VORG`
 pub const VORG: u32 = tag!(b"VORG");
+/// `vpal`
+pub const VPAL: u32 = tag!(b"vpal");
 /// `vrt2`
 pub const VRT2: u32 = tag!(b"vrt2");
+/// `VVAR`
+pub const VVAR: u32 = tag!(b"VVAR");
+/// `yezi`
+pub const YEZI: u32 = tag!(b"yezi");
+/// `zanb`
+pub const ZANB: u32 = tag!(b"zanb");
 /// `Zapf`
 pub const ZAPF: u32 = tag!(b"Zapf");
 /// `zero`
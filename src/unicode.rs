@@ -1,10 +1,17 @@
 //! Unicode utilities and character properties.
 
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 
+mod bidi_data;
 pub mod codepoint;
 mod emoji_data;
 pub mod mcc;
+pub mod normalization;
+mod normalization_data;
+mod script_data;
+
+pub use self::script_data::Script;
 
 /// A Unicode variation selector.
 ///
@@ -38,6 +45,227 @@ impl TryFrom<char> for VariationSelector {
     }
 }
 
+/// A character that may carry additional data through text preprocessing.
+///
+/// Text preprocessing reorders, inserts, and replaces characters. Implementing this trait allows
+/// data associated with each character, such as the cluster it originated from, to follow the
+/// character through those changes.
+pub trait TextChar: Copy {
+    /// The character.
+    fn char(&self) -> char;
+
+    /// Returns a copy of `self` with the character replaced by `ch`.
+    fn with_char(&self, ch: char) -> Self;
+
+    /// Returns a copy of `self` that also covers the text of `other`.
+    ///
+    /// This is used when characters are combined by composition. The default implementation
+    /// returns `self` unchanged.
+    fn merge(&self, _other: &Self) -> Self {
+        *self
+    }
+
+    /// Returns `true` if the text of `self` lies between that of `a` and `b` inclusive.
+    ///
+    /// This is used to find the characters whose clusters are merged by composition. The default
+    /// implementation returns `false`.
+    fn cluster_between(&self, _a: &Self, _b: &Self) -> bool {
+        false
+    }
+}
+
+impl TextChar for char {
+    fn char(&self) -> char {
+        *self
+    }
+
+    fn with_char(&self, ch: char) -> Self {
+        ch
+    }
+}
+
+/// A character and the cluster it belongs to.
+///
+/// The cluster is the byte offset of the start of the cluster in the source text.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ClusterChar {
+    pub ch: char,
+    pub cluster: usize,
+}
+
+impl TextChar for ClusterChar {
+    fn char(&self) -> char {
+        self.ch
+    }
+
+    fn with_char(&self, ch: char) -> Self {
+        ClusterChar {
+            ch,
+            cluster: self.cluster,
+        }
+    }
+
+    fn merge(&self, other: &Self) -> Self {
+        ClusterChar {
+            ch: self.ch,
+            cluster: self.cluster.min(other.cluster),
+        }
+    }
+
+    fn cluster_between(&self, a: &Self, b: &Self) -> bool {
+        let (start, end) = (a.cluster.min(b.cluster), a.cluster.max(b.cluster));
+        (start..=end).contains(&self.cluster)
+    }
+}
+
+/// The Unicode [Bidi_Class](https://www.unicode.org/reports/tr9/#Bidirectional_Character_Types)
+/// property.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BidiClass {
+    /// Left-to-right
+    L,
+    /// Right-to-left
+    R,
+    /// Right-to-left Arabic
+    AL,
+    /// European number
+    EN,
+    /// European number separator
+    ES,
+    /// European number terminator
+    ET,
+    /// Arabic number
+    AN,
+    /// Common number separator
+    CS,
+    /// Nonspacing mark
+    NSM,
+    /// Boundary neutral
+    BN,
+    /// Paragraph separator
+    B,
+    /// Segment separator
+    S,
+    /// Whitespace
+    WS,
+    /// Other neutrals
+    ON,
+    /// Left-to-right embedding
+    LRE,
+    /// Left-to-right override
+    LRO,
+    /// Right-to-left embedding
+    RLE,
+    /// Right-to-left override
+    RLO,
+    /// Pop directional format
+    PDF,
+    /// Left-to-right isolate
+    LRI,
+    /// Right-to-left isolate
+    RLI,
+    /// First strong isolate
+    FSI,
+    /// Pop directional isolate
+    PDI,
+}
+
+/// The Unicode `Bidi_Paired_Bracket_Type` property of a bracket.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BracketType {
+    Open,
+    Close,
+}
+
+/// Returns the `Bidi_Class` Unicode property for a character.
+///
+/// ```
+/// use allsorts::unicode::{bidi_class, BidiClass};
+///
+/// assert_eq!(bidi_class('a'), BidiClass::L);
+/// assert_eq!(bidi_class('\u{05D0}'), BidiClass::R);
+/// assert_eq!(bidi_class('\u{0627}'), BidiClass::AL);
+/// ```
+pub fn bidi_class(ch: char) -> BidiClass {
+    lookup_range(bidi_data::BIDI_CLASS, ch).unwrap_or(BidiClass::L)
+}
+
+/// Returns the `Bidi_Mirroring_Glyph` Unicode property for a character.
+///
+/// This is the character whose glyph is the mirror image of the glyph of `ch`, if there is one.
+///
+/// ```
+/// use allsorts::unicode::bidi_mirroring_glyph;
+///
+/// assert_eq!(bidi_mirroring_glyph('('), Some(')'));
+/// assert_eq!(bidi_mirroring_glyph('a'), None);
+/// ```
+pub fn bidi_mirroring_glyph(ch: char) -> Option<char> {
+    let table = bidi_data::BIDI_MIRRORING_GLYPH;
+    table
+        .binary_search_by_key(&ch, |&(c, _)| c)
+        .ok()
+        .map(|index| table[index].1)
+}
+
+/// Returns the `Bidi_Paired_Bracket` and `Bidi_Paired_Bracket_Type` Unicode properties for a
+/// character, if it is a bracket.
+pub fn bidi_paired_bracket(ch: char) -> Option<(char, BracketType)> {
+    let table = bidi_data::BIDI_PAIRED_BRACKET;
+    table
+        .binary_search_by_key(&ch, |&(c, _, _)| c)
+        .ok()
+        .map(|index| (table[index].1, table[index].2))
+}
+
+/// Returns the `Script` Unicode property for a character.
+///
+/// ```
+/// use allsorts::unicode::{script, Script};
+///
+/// assert_eq!(script('a'), Script::Latin);
+/// assert_eq!(script('\u{0915}'), Script::Devanagari);
+/// assert_eq!(script(' '), Script::Common);
+/// ```
+pub fn script(ch: char) -> Script {
+    lookup_range(script_data::SCRIPT, ch).unwrap_or(Script::Unknown)
+}
+
+/// Returns the `Script_Extensions` Unicode property for a character.
+///
+/// This is the set of scripts the character is used with. For most characters it contains only
+/// the value of the `Script` property.
+///
+/// ```
+/// use allsorts::unicode::{script_extensions, Script};
+///
+/// assert_eq!(script_extensions('a'), &[Script::Latin]);
+/// assert!(script_extensions('\u{0964}').contains(&Script::Bengali));
+/// ```
+pub fn script_extensions(ch: char) -> &'static [Script] {
+    match lookup_range(script_data::SCRIPT_EXTENSIONS, ch) {
+        Some(scripts) => scripts,
+        None => std::slice::from_ref(&script_data::SCRIPTS[script(ch) as usize]),
+    }
+}
+
+/// Looks up `ch` in a table of sorted, non-overlapping, inclusive character ranges.
+pub(crate) fn lookup_range<T: Copy>(table: &[(char, char, T)], ch: char) -> Option<T> {
+    table
+        .binary_search_by(|&(start, end, _)| {
+            if ch < start {
+                Ordering::Greater
+            } else if ch > end {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()
+        .map(|index| table[index].2)
+}
+
 /// Returns the `Emoji_Presentation` Unicode property for a character.
 ///
 /// ```
@@ -1,5 +1,7 @@
 use unicode_canonical_combining_class::get_canonical_combining_class;
 
+use crate::unicode::TextChar;
+
 /// An enumeration of the Unicode
 /// [Canonical_Combining_Class values](http://www.unicode.org/reports/tr44/#Canonical_Combining_Class_Values),
 /// with the following modifications:
@@ -214,10 +216,13 @@ const MODIFIED_COMBINING_CLASS: &'static [ModifiedCombiningClass; 256] = &[
     X,      // CCC127
     X,      // CCC128
     // Tibetan
+    //
+    // Vowel sign U (CCC132) is ordered before the vowel signs above (CCC130) so that subjoined
+    // vowels are stacked nearest to the base, in the order used by fonts and HarfBuzz.
     CCC129,             // CCC129
-    CCC130,             // CCC130
+    CCC132,             // CCC130
     X,                  // CCC131
-    CCC132,             // CCC132
+    CCC130,             // CCC132
     X,                  // CCC133
     X,                  // CCC134
     X,                  // CCC135
@@ -363,10 +368,10 @@ pub fn modified_combining_class(c: char) -> ModifiedCombiningClass {
 
 /// Sorts sub-slices of non-starter `char`s (i.e. `char`s with non-zero combining class values) by
 /// their modified combining class values. This sort is stable.
-pub fn sort_by_modified_combining_class(cs: &mut [char]) {
+pub fn sort_by_modified_combining_class<T: TextChar>(cs: &mut [T]) {
     for css in
-        cs.split_mut(|&c| modified_combining_class(c) == ModifiedCombiningClass::NotReordered)
+        cs.split_mut(|c| modified_combining_class(c.char()) == ModifiedCombiningClass::NotReordered)
     {
-        css.sort_by_key(|&c| modified_combining_class(c));
+        css.sort_by_key(|c| modified_combining_class(c.char()));
     }
 }
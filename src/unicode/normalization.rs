@@ -0,0 +1,323 @@
+//! Unicode normalisation.
+//!
+//! In addition to the standard NFD and NFC forms this module provides a pair of passes that
+//! decompose and compose characters according to the glyphs available in a font. This allows
+//! text such as "e" followed by COMBINING ACUTE ACCENT to be rendered with the precomposed "é"
+//! glyph, and "é" to be rendered as "e" and a combining mark when the font lacks it.
+//!
+//! <https://www.unicode.org/reports/tr15/>
+
+use unicode_canonical_combining_class::get_canonical_combining_class;
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+use crate::unicode::mcc::{modified_combining_class, ModifiedCombiningClass};
+use crate::unicode::normalization_data::{CANONICAL_COMPOSITION, CANONICAL_DECOMPOSITION};
+use crate::unicode::TextChar;
+
+// Hangul syllable composition constants, shared with the Hangul shaper
+pub(crate) const S_BASE: u32 = 0xAC00;
+pub(crate) const L_BASE: u32 = 0x1100;
+pub(crate) const V_BASE: u32 = 0x1161;
+pub(crate) const T_BASE: u32 = 0x11A7;
+pub(crate) const L_COUNT: u32 = 19;
+pub(crate) const V_COUNT: u32 = 21;
+pub(crate) const T_COUNT: u32 = 28;
+pub(crate) const N_COUNT: u32 = V_COUNT * T_COUNT;
+pub(crate) const S_COUNT: u32 = L_COUNT * N_COUNT;
+
+/// Returns the single-level canonical decomposition of `c`, if it has one.
+///
+/// The second character is `None` for characters that decompose to a single character. Hangul
+/// syllables decompose into an <LV> syllable and a trailing jamo, or a leading and vowel jamo.
+pub fn decompose(c: char) -> Option<(char, Option<char>)> {
+    let u = c as u32;
+    if (S_BASE..S_BASE + S_COUNT).contains(&u) {
+        let s_index = u - S_BASE;
+        let t_index = s_index % T_COUNT;
+        return if t_index == 0 {
+            let l = L_BASE + s_index / N_COUNT;
+            let v = V_BASE + (s_index % N_COUNT) / T_COUNT;
+            Some((from_u32(l), Some(from_u32(v))))
+        } else {
+            Some((from_u32(u - t_index), Some(from_u32(T_BASE + t_index))))
+        };
+    }
+
+    CANONICAL_DECOMPOSITION
+        .binary_search_by_key(&c, |&(ch, _)| ch)
+        .ok()
+        .map(|index| CANONICAL_DECOMPOSITION[index].1)
+}
+
+/// Returns the primary composite of `a` and `b`, if there is one.
+pub fn compose(a: char, b: char) -> Option<char> {
+    let (a_u, b_u) = (a as u32, b as u32);
+    if (L_BASE..L_BASE + L_COUNT).contains(&a_u) && (V_BASE..V_BASE + V_COUNT).contains(&b_u) {
+        let lv_index = (a_u - L_BASE) * N_COUNT + (b_u - V_BASE) * T_COUNT;
+        return Some(from_u32(S_BASE + lv_index));
+    }
+    if (S_BASE..S_BASE + S_COUNT).contains(&a_u) && (T_BASE + 1..T_BASE + T_COUNT).contains(&b_u) {
+        let t_index = (a_u - S_BASE) % T_COUNT;
+        if t_index == 0 {
+            return Some(from_u32(a_u + (b_u - T_BASE)));
+        }
+    }
+
+    CANONICAL_COMPOSITION
+        .binary_search_by_key(&(a, b), |&(pair, _)| pair)
+        .ok()
+        .map(|index| CANONICAL_COMPOSITION[index].1)
+}
+
+/// Convert `cs` to Normalization Form D (canonical decomposition).
+pub fn nfd<T: TextChar>(cs: &mut Vec<T>) {
+    let mut decomposed = Vec::with_capacity(cs.len());
+    for &c in cs.iter() {
+        decompose_full(c, &mut decomposed);
+    }
+    canonical_ordering(&mut decomposed);
+    *cs = decomposed;
+}
+
+/// Convert `cs` to Normalization Form C (canonical decomposition, followed by canonical
+/// composition).
+pub fn nfc<T: TextChar>(cs: &mut Vec<T>) {
+    nfd(cs);
+    compose_where(cs, ccc, |_, _| true);
+}
+
+/// Decompose the characters of `cs` that the font can't map, or that are followed by combining
+/// marks, into characters that it can map.
+///
+/// `has_glyph` returns `true` if the font has a glyph for a character. A character is only
+/// decomposed if all the characters of its decomposition can be mapped. Characters that carry
+/// combining marks are decomposed even if the font has them, so that [compose_for_font] can
+/// choose the best composition of the base and its marks. The decomposed marks are not
+/// reordered; this is left to the text preprocessing that follows.
+pub fn decompose_for_font<T: TextChar>(cs: &mut Vec<T>, mut has_glyph: impl FnMut(char) -> bool) {
+    let mut i = 0;
+    let mut parts = Vec::new();
+    while i < cs.len() {
+        let c = cs[i].char();
+        let has_marks = is_mark(c) || cs.get(i + 1).map_or(false, |next| is_mark(next.char()));
+        parts.clear();
+        if (has_marks || !has_glyph(c)) && decompose_mappable(c, &mut has_glyph, &mut parts) {
+            let decomposed = parts
+                .iter()
+                .map(|&ch| cs[i].with_char(ch))
+                .collect::<Vec<_>>();
+            cs.splice(i..=i, decomposed);
+            i += parts.len();
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Compose combining marks with their base character where the font has a glyph for the
+/// composition.
+///
+/// `has_glyph` returns `true` if the font has a glyph for a character. Marks are expected to be
+/// sorted by their modified combining class. Only marks are composed, so adjacent base
+/// characters, such as conjoining jamo, are left alone.
+pub fn compose_for_font<T: TextChar>(cs: &mut Vec<T>, mut has_glyph: impl FnMut(char) -> bool) {
+    compose_where(
+        cs,
+        |c| modified_combining_class(c) as u8,
+        |composed, c| is_mark(c) && has_glyph(composed),
+    );
+}
+
+/// Apply the canonical composition algorithm to `cs`, which has been decomposed.
+///
+/// `combining_class` determines whether a character is blocked from its starter, and `accept`
+/// is called with each candidate composition and the character being composed with the starter.
+fn compose_where<T: TextChar>(
+    cs: &mut Vec<T>,
+    combining_class: impl Fn(char) -> u8,
+    mut accept: impl FnMut(char, char) -> bool,
+) {
+    let mut composed: Vec<T> = Vec::with_capacity(cs.len());
+    let mut starter: Option<usize> = None;
+    for i in 0..cs.len() {
+        let c = cs[i];
+        let ch = c.char();
+        let class = combining_class(ch);
+        if let Some(starter) = starter {
+            let blocked = match composed.last() {
+                Some(prev) if composed.len() - 1 != starter => {
+                    let prev_class = combining_class(prev.char());
+                    prev_class == 0 || prev_class >= class
+                }
+                _ => false,
+            };
+            if !blocked {
+                if let Some(comp) = compose(composed[starter].char(), ch) {
+                    if accept(comp, ch) {
+                        // The characters from the starter onwards now form a single cluster.
+                        // Like HarfBuzz's merge_clusters this takes in the adjacent characters
+                        // whose clusters lie between those of the starter and the mark.
+                        let first = composed[starter];
+                        let mut start = starter;
+                        while start > 0 && composed[start - 1].cluster_between(&first, &c) {
+                            start -= 1;
+                        }
+                        let mut end = i + 1;
+                        while end < cs.len() && cs[end].cluster_between(&first, &c) {
+                            end += 1;
+                        }
+                        let merged = composed[start..].iter().fold(c, |m, d| m.merge(d));
+                        for d in composed[start..].iter_mut().chain(&mut cs[i + 1..end]) {
+                            *d = d.merge(&merged);
+                        }
+                        composed[starter] = composed[starter].with_char(comp);
+                        continue;
+                    }
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(composed.len());
+        }
+        composed.push(c);
+    }
+    *cs = composed;
+}
+
+/// Append the full canonical decomposition of `c` to `out`.
+fn decompose_full<T: TextChar>(c: T, out: &mut Vec<T>) {
+    match decompose(c.char()) {
+        Some((a, b)) => {
+            decompose_full(c.with_char(a), out);
+            if let Some(b) = b {
+                decompose_full(c.with_char(b), out);
+            }
+        }
+        None => out.push(c),
+    }
+}
+
+/// Append a decomposition of `c` to `out` consisting only of characters that the font can map.
+///
+/// The decomposition is applied recursively to the first character while the font can map the
+/// result. Returns `false`, leaving `out` unchanged, if there's no such decomposition.
+fn decompose_mappable(
+    c: char,
+    has_glyph: &mut impl FnMut(char) -> bool,
+    out: &mut Vec<char>,
+) -> bool {
+    let (a, b) = match decompose(c) {
+        Some(decomposition) => decomposition,
+        None => return false,
+    };
+    if let Some(b) = b {
+        if !has_glyph(b) {
+            return false;
+        }
+    }
+    if !decompose_mappable(a, has_glyph, out) {
+        if !has_glyph(a) {
+            return false;
+        }
+        out.push(a);
+    }
+    out.extend(b);
+    true
+}
+
+/// Sort each run of non-starters by canonical combining class. This sort is stable.
+fn canonical_ordering<T: TextChar>(cs: &mut [T]) {
+    for run in cs.split_mut(|c| ccc(c.char()) == 0) {
+        run.sort_by_key(|c| ccc(c.char()));
+    }
+}
+
+fn ccc(c: char) -> u8 {
+    get_canonical_combining_class(c) as u8
+}
+
+fn is_mark(c: char) -> bool {
+    let is_variation_selector = matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}');
+    !is_variation_selector
+        && (modified_combining_class(c) != ModifiedCombiningClass::NotReordered
+            || matches!(
+                get_general_category(c),
+                GeneralCategory::NonspacingMark
+                    | GeneralCategory::SpacingMark
+                    | GeneralCategory::EnclosingMark
+            ))
+}
+
+pub(crate) fn from_u32(c: u32) -> char {
+    // The arithmetic on syllables and jamo always produces a valid character
+    char::from_u32(c).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(s: &str, f: fn(&mut Vec<char>)) -> String {
+        let mut cs = s.chars().collect::<Vec<_>>();
+        f(&mut cs);
+        cs.into_iter().collect()
+    }
+
+    // Decompose and compose `s` for a font that has glyphs for the characters in `font`
+    fn for_font(s: &str, font: &str) -> String {
+        let mut cs = s.chars().collect::<Vec<_>>();
+        decompose_for_font(&mut cs, |ch| font.contains(ch));
+        crate::unicode::mcc::sort_by_modified_combining_class(&mut cs);
+        compose_for_font(&mut cs, |ch| font.contains(ch));
+        cs.into_iter().collect()
+    }
+
+    #[test]
+    fn test_nfd() {
+        assert_eq!(normalize("\u{E9}", nfd), "e\u{301}");
+        assert_eq!(normalize("\u{1E69}", nfd), "s\u{323}\u{307}");
+        assert_eq!(normalize("\u{1E0B}\u{323}", nfd), "d\u{323}\u{307}");
+        assert_eq!(normalize("\u{212B}", nfd), "A\u{30A}");
+        assert_eq!(normalize("\u{AC01}", nfd), "\u{1100}\u{1161}\u{11A8}");
+    }
+
+    #[test]
+    fn test_nfc() {
+        assert_eq!(normalize("e\u{301}", nfc), "\u{E9}");
+        assert_eq!(normalize("s\u{307}\u{323}", nfc), "\u{1E69}");
+        assert_eq!(normalize("\u{1E0B}\u{323}", nfc), "\u{1E0D}\u{307}");
+        assert_eq!(normalize("\u{212B}", nfc), "\u{C5}");
+        assert_eq!(normalize("\u{1100}\u{1161}\u{11A8}", nfc), "\u{AC01}");
+        // Composition exclusions aren't composed
+        assert_eq!(normalize("\u{915}\u{93C}", nfc), "\u{915}\u{93C}");
+        // A mark with the same combining class blocks composition
+        assert_eq!(normalize("a\u{301}\u{301}", nfc), "\u{E1}\u{301}");
+        assert_eq!(normalize("a\u{302}\u{301}", nfc), "\u{1EA5}");
+    }
+
+    #[test]
+    fn test_for_font() {
+        // Composed when the font has the precomposed character
+        assert_eq!(for_font("e\u{301}", "e\u{E9}"), "\u{E9}");
+        assert_eq!(for_font("e\u{301}", "e\u{301}"), "e\u{301}");
+        // Decomposed when the font lacks the precomposed character
+        assert_eq!(for_font("\u{E9}", "e\u{301}"), "e\u{301}");
+        assert_eq!(for_font("\u{E9}", "e\u{301}\u{E9}"), "\u{E9}");
+        // Left alone if the font can't map the decomposition either
+        assert_eq!(for_font("\u{E9}", "e"), "\u{E9}");
+        // Partially composed
+        assert_eq!(
+            for_font("\u{1EC7}", "e\u{302}\u{323}\u{1EB9}"),
+            "\u{1EB9}\u{302}"
+        );
+        assert_eq!(
+            for_font("\u{E9}\u{323}", "e\u{301}\u{323}\u{E9}"),
+            "\u{E9}\u{323}"
+        );
+        // Base characters aren't composed with each other
+        assert_eq!(
+            for_font("\u{1100}\u{1161}", "\u{1100}\u{1161}\u{AC00}"),
+            "\u{1100}\u{1161}"
+        );
+    }
+}
@@ -0,0 +1,2134 @@
+// DO NOT EDIT THIS FILE. IT WAS AUTOMATICALLY GENERATED BY:
+//
+//   scripts/gen_unicode_data.py script ../ucd-generate/ucd-15.0.0
+//
+// Unicode version: 15.0.0.
+//
+// Script and Script_Extensions data derived from the Unicode Character Database.
+
+/// The Unicode [Script](https://www.unicode.org/reports/tr24/) property.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Script {
+    Adlam,
+    Ahom,
+    AnatolianHieroglyphs,
+    Arabic,
+    Armenian,
+    Avestan,
+    Balinese,
+    Bamum,
+    BassaVah,
+    Batak,
+    Bengali,
+    Bhaiksuki,
+    Bopomofo,
+    Brahmi,
+    Braille,
+    Buginese,
+    Buhid,
+    CanadianAboriginal,
+    Carian,
+    CaucasianAlbanian,
+    Chakma,
+    Cham,
+    Cherokee,
+    Chorasmian,
+    Common,
+    Coptic,
+    Cuneiform,
+    Cypriot,
+    CyproMinoan,
+    Cyrillic,
+    Deseret,
+    Devanagari,
+    DivesAkuru,
+    Dogra,
+    Duployan,
+    EgyptianHieroglyphs,
+    Elbasan,
+    Elymaic,
+    Ethiopic,
+    Georgian,
+    Glagolitic,
+    Gothic,
+    Grantha,
+    Greek,
+    Gujarati,
+    GunjalaGondi,
+    Gurmukhi,
+    Han,
+    Hangul,
+    HanifiRohingya,
+    Hanunoo,
+    Hatran,
+    Hebrew,
+    Hiragana,
+    ImperialAramaic,
+    Inherited,
+    InscriptionalPahlavi,
+    InscriptionalParthian,
+    Javanese,
+    Kaithi,
+    Kannada,
+    Katakana,
+    Kawi,
+    KayahLi,
+    Kharoshthi,
+    KhitanSmallScript,
+    Khmer,
+    Khojki,
+    Khudawadi,
+    Lao,
+    Latin,
+    Lepcha,
+    Limbu,
+    LinearA,
+    LinearB,
+    Lisu,
+    Lycian,
+    Lydian,
+    Mahajani,
+    Makasar,
+    Malayalam,
+    Mandaic,
+    Manichaean,
+    Marchen,
+    MasaramGondi,
+    Medefaidrin,
+    MeeteiMayek,
+    MendeKikakui,
+    MeroiticCursive,
+    MeroiticHieroglyphs,
+    Miao,
+    Modi,
+    Mongolian,
+    Mro,
+    Multani,
+    Myanmar,
+    Nabataean,
+    NagMundari,
+    Nandinagari,
+    NewTaiLue,
+    Newa,
+    Nko,
+    Nushu,
+    NyiakengPuachueHmong,
+    Ogham,
+    OlChiki,
+    OldHungarian,
+    OldItalic,
+    OldNorthArabian,
+    OldPermic,
+    OldPersian,
+    OldSogdian,
+    OldSouthArabian,
+    OldTurkic,
+    OldUyghur,
+    Oriya,
+    Osage,
+    Osmanya,
+    PahawhHmong,
+    Palmyrene,
+    PauCinHau,
+    PhagsPa,
+    Phoenician,
+    PsalterPahlavi,
+    Rejang,
+    Runic,
+    Samaritan,
+    Saurashtra,
+    Sharada,
+    Shavian,
+    Siddham,
+    SignWriting,
+    Sinhala,
+    Sogdian,
+    SoraSompeng,
+    Soyombo,
+    Sundanese,
+    SylotiNagri,
+    Syriac,
+    Tagalog,
+    Tagbanwa,
+    TaiLe,
+    TaiTham,
+    TaiViet,
+    Takri,
+    Tamil,
+    Tangsa,
+    Tangut,
+    Telugu,
+    Thaana,
+    Thai,
+    Tibetan,
+    Tifinagh,
+    Tirhuta,
+    Toto,
+    Ugaritic,
+    Unknown,
+    Vai,
+    Vithkuqi,
+    Wancho,
+    WarangCiti,
+    Yezidi,
+    Yi,
+    ZanabazarSquare,
+}
+
+/// Every `Script`, in the order of their declaration.
+pub(crate) const SCRIPTS: [Script; 164] = [
+    Script::Adlam,
+    Script::Ahom,
+    Script::AnatolianHieroglyphs,
+    Script::Arabic,
+    Script::Armenian,
+    Script::Avestan,
+    Script::Balinese,
+    Script::Bamum,
+    Script::BassaVah,
+    Script::Batak,
+    Script::Bengali,
+    Script::Bhaiksuki,
+    Script::Bopomofo,
+    Script::Brahmi,
+    Script::Braille,
+    Script::Buginese,
+    Script::Buhid,
+    Script::CanadianAboriginal,
+    Script::Carian,
+    Script::CaucasianAlbanian,
+    Script::Chakma,
+    Script::Cham,
+    Script::Cherokee,
+    Script::Chorasmian,
+    Script::Common,
+    Script::Coptic,
+    Script::Cuneiform,
+    Script::Cypriot,
+    Script::CyproMinoan,
+    Script::Cyrillic,
+    Script::Deseret,
+    Script::Devanagari,
+    Script::DivesAkuru,
+    Script::Dogra,
+    Script::Duployan,
+    Script::EgyptianHieroglyphs,
+    Script::Elbasan,
+    Script::Elymaic,
+    Script::Ethiopic,
+    Script::Georgian,
+    Script::Glagolitic,
+    Script::Gothic,
+    Script::Grantha,
+    Script::Greek,
+    Script::Gujarati,
+    Script::GunjalaGondi,
+    Script::Gurmukhi,
+    Script::Han,
+    Script::Hangul,
+    Script::HanifiRohingya,
+    Script::Hanunoo,
+    Script::Hatran,
+    Script::Hebrew,
+    Script::Hiragana,
+    Script::ImperialAramaic,
+    Script::Inherited,
+    Script::InscriptionalPahlavi,
+    Script::InscriptionalParthian,
+    Script::Javanese,
+    Script::Kaithi,
+    Script::Kannada,
+    Script::Katakana,
+    Script::Kawi,
+    Script::KayahLi,
+    Script::Kharoshthi,
+    Script::KhitanSmallScript,
+    Script::Khmer,
+    Script::Khojki,
+    Script::Khudawadi,
+    Script::Lao,
+    Script::Latin,
+    Script::Lepcha,
+    Script::Limbu,
+    Script::LinearA,
+    Script::LinearB,
+    Script::Lisu,
+    Script::Lycian,
+    Script::Lydian,
+    Script::Mahajani,
+    Script::Makasar,
+    Script::Malayalam,
+    Script::Mandaic,
+    Script::Manichaean,
+    Script::Marchen,
+    Script::MasaramGondi,
+    Script::Medefaidrin,
+    Script::MeeteiMayek,
+    Script::MendeKikakui,
+    Script::MeroiticCursive,
+    Script::MeroiticHieroglyphs,
+    Script::Miao,
+    Script::Modi,
+    Script::Mongolian,
+    Script::Mro,
+    Script::Multani,
+    Script::Myanmar,
+    Script::Nabataean,
+    Script::NagMundari,
+    Script::Nandinagari,
+    Script::NewTaiLue,
+    Script::Newa,
+    Script::Nko,
+    Script::Nushu,
+    Script::NyiakengPuachueHmong,
+    Script::Ogham,
+    Script::OlChiki,
+    Script::OldHungarian,
+    Script::OldItalic,
+    Script::OldNorthArabian,
+    Script::OldPermic,
+    Script::OldPersian,
+    Script::OldSogdian,
+    Script::OldSouthArabian,
+    Script::OldTurkic,
+    Script::OldUyghur,
+    Script::Oriya,
+    Script::Osage,
+    Script::Osmanya,
+    Script::PahawhHmong,
+    Script::Palmyrene,
+    Script::PauCinHau,
+    Script::PhagsPa,
+    Script::Phoenician,
+    Script::PsalterPahlavi,
+    Script::Rejang,
+    Script::Runic,
+    Script::Samaritan,
+    Script::Saurashtra,
+    Script::Sharada,
+    Script::Shavian,
+    Script::Siddham,
+    Script::SignWriting,
+    Script::Sinhala,
+    Script::Sogdian,
+    Script::SoraSompeng,
+    Script::Soyombo,
+    Script::Sundanese,
+    Script::SylotiNagri,
+    Script::Syriac,
+    Script::Tagalog,
+    Script::Tagbanwa,
+    Script::TaiLe,
+    Script::TaiTham,
+    Script::TaiViet,
+    Script::Takri,
+    Script::Tamil,
+    Script::Tangsa,
+    Script::Tangut,
+    Script::Telugu,
+    Script::Thaana,
+    Script::Thai,
+    Script::Tibetan,
+    Script::Tifinagh,
+    Script::Tirhuta,
+    Script::Toto,
+    Script::Ugaritic,
+    Script::Unknown,
+    Script::Vai,
+    Script::Vithkuqi,
+    Script::Wancho,
+    Script::WarangCiti,
+    Script::Yezidi,
+    Script::Yi,
+    Script::ZanabazarSquare,
+];
+
+impl Script {
+    /// The four letter [ISO 15924](https://www.unicode.org/iso15924/) code of the script.
+    pub fn iso15924_code(self) -> &'static str {
+        match self {
+            Script::Adlam => "Adlm",
+            Script::Ahom => "Ahom",
+            Script::AnatolianHieroglyphs => "Hluw",
+            Script::Arabic => "Arab",
+            Script::Armenian => "Armn",
+            Script::Avestan => "Avst",
+            Script::Balinese => "Bali",
+            Script::Bamum => "Bamu",
+            Script::BassaVah => "Bass",
+            Script::Batak => "Batk",
+            Script::Bengali => "Beng",
+            Script::Bhaiksuki => "Bhks",
+            Script::Bopomofo => "Bopo",
+            Script::Brahmi => "Brah",
+            Script::Braille => "Brai",
+            Script::Buginese => "Bugi",
+            Script::Buhid => "Buhd",
+            Script::CanadianAboriginal => "Cans",
+            Script::Carian => "Cari",
+            Script::CaucasianAlbanian => "Aghb",
+            Script::Chakma => "Cakm",
+            Script::Cham => "Cham",
+            Script::Cherokee => "Cher",
+            Script::Chorasmian => "Chrs",
+            Script::Common => "Zyyy",
+            Script::Coptic => "Copt",
+            Script::Cuneiform => "Xsux",
+            Script::Cypriot => "Cprt",
+            Script::CyproMinoan => "Cpmn",
+            Script::Cyrillic => "Cyrl",
+            Script::Deseret => "Dsrt",
+            Script::Devanagari => "Deva",
+            Script::DivesAkuru => "Diak",
+            Script::Dogra => "Dogr",
+            Script::Duployan => "Dupl",
+            Script::EgyptianHieroglyphs => "Egyp",
+            Script::Elbasan => "Elba",
+            Script::Elymaic => "Elym",
+            Script::Ethiopic => "Ethi",
+            Script::Georgian => "Geor",
+            Script::Glagolitic => "Glag",
+            Script::Gothic => "Goth",
+            Script::Grantha => "Gran",
+            Script::Greek => "Grek",
+            Script::Gujarati => "Gujr",
+            Script::GunjalaGondi => "Gong",
+            Script::Gurmukhi => "Guru",
+            Script::Han => "Hani",
+            Script::Hangul => "Hang",
+            Script::HanifiRohingya => "Rohg",
+            Script::Hanunoo => "Hano",
+            Script::Hatran => "Hatr",
+            Script::Hebrew => "Hebr",
+            Script::Hiragana => "Hira",
+            Script::ImperialAramaic => "Armi",
+            Script::Inherited => "Zinh",
+            Script::InscriptionalPahlavi => "Phli",
+            Script::InscriptionalParthian => "Prti",
+            Script::Javanese => "Java",
+            Script::Kaithi => "Kthi",
+            Script::Kannada => "Knda",
+            Script::Katakana => "Kana",
+            Script::Kawi => "Kawi",
+            Script::KayahLi => "Kali",
+            Script::Kharoshthi => "Khar",
+            Script::KhitanSmallScript => "Kits",
+            Script::Khmer => "Khmr",
+            Script::Khojki => "Khoj",
+            Script::Khudawadi => "Sind",
+            Script::Lao => "Laoo",
+            Script::Latin => "Latn",
+            Script::Lepcha => "Lepc",
+            Script::Limbu => "Limb",
+            Script::LinearA => "Lina",
+            Script::LinearB => "Linb",
+            Script::Lisu => "Lisu",
+            Script::Lycian => "Lyci",
+            Script::Lydian => "Lydi",
+            Script::Mahajani => "Mahj",
+            Script::Makasar => "Maka",
+            Script::Malayalam => "Mlym",
+            Script::Mandaic => "Mand",
+            Script::Manichaean => "Mani",
+            Script::Marchen => "Marc",
+            Script::MasaramGondi => "Gonm",
+            Script::Medefaidrin => "Medf",
+            Script::MeeteiMayek => "Mtei",
+            Script::MendeKikakui => "Mend",
+            Script::MeroiticCursive => "Merc",
+            Script::MeroiticHieroglyphs => "Mero",
+            Script::Miao => "Plrd",
+            Script::Modi => "Modi",
+            Script::Mongolian => "Mong",
+            Script::Mro => "Mroo",
+            Script::Multani => "Mult",
+            Script::Myanmar => "Mymr",
+            Script::Nabataean => "Nbat",
+            Script::NagMundari => "Nagm",
+            Script::Nandinagari => "Nand",
+            Script::NewTaiLue => "Talu",
+            Script::Newa => "Newa",
+            Script::Nko => "Nkoo",
+            Script::Nushu => "Nshu",
+            Script::NyiakengPuachueHmong => "Hmnp",
+            Script::Ogham => "Ogam",
+            Script::OlChiki => "Olck",
+            Script::OldHungarian => "Hung",
+            Script::OldItalic => "Ital",
+            Script::OldNorthArabian => "Narb",
+            Script::OldPermic => "Perm",
+            Script::OldPersian => "Xpeo",
+            Script::OldSogdian => "Sogo",
+            Script::OldSouthArabian => "Sarb",
+            Script::OldTurkic => "Orkh",
+            Script::OldUyghur => "Ougr",
+            Script::Oriya => "Orya",
+            Script::Osage => "Osge",
+            Script::Osmanya => "Osma",
+            Script::PahawhHmong => "Hmng",
+            Script::Palmyrene => "Palm",
+            Script::PauCinHau => "Pauc",
+            Script::PhagsPa => "Phag",
+            Script::Phoenician => "Phnx",
+            Script::PsalterPahlavi => "Phlp",
+            Script::Rejang => "Rjng",
+            Script::Runic => "Runr",
+            Script::Samaritan => "Samr",
+            Script::Saurashtra => "Saur",
+            Script::Sharada => "Shrd",
+            Script::Shavian => "Shaw",
+            Script::Siddham => "Sidd",
+            Script::SignWriting => "Sgnw",
+            Script::Sinhala => "Sinh",
+            Script::Sogdian => "Sogd",
+            Script::SoraSompeng => "Sora",
+            Script::Soyombo => "Soyo",
+            Script::Sundanese => "Sund",
+            Script::SylotiNagri => "Sylo",
+            Script::Syriac => "Syrc",
+            Script::Tagalog => "Tglg",
+            Script::Tagbanwa => "Tagb",
+            Script::TaiLe => "Tale",
+            Script::TaiTham => "Lana",
+            Script::TaiViet => "Tavt",
+            Script::Takri => "Takr",
+            Script::Tamil => "Taml",
+            Script::Tangsa => "Tnsa",
+            Script::Tangut => "Tang",
+            Script::Telugu => "Telu",
+            Script::Thaana => "Thaa",
+            Script::Thai => "Thai",
+            Script::Tibetan => "Tibt",
+            Script::Tifinagh => "Tfng",
+            Script::Tirhuta => "Tirh",
+            Script::Toto => "Toto",
+            Script::Ugaritic => "Ugar",
+            Script::Unknown => "Zzzz",
+            Script::Vai => "Vaii",
+            Script::Vithkuqi => "Vith",
+            Script::Wancho => "Wcho",
+            Script::WarangCiti => "Wara",
+            Script::Yezidi => "Yezi",
+            Script::Yi => "Yiii",
+            Script::ZanabazarSquare => "Zanb",
+        }
+    }
+}
+
+/// Ranges of characters with the same `Script`, sorted by their first character.
+///
+/// Characters that aren't in a range have the script `Unknown`.
+pub(crate) const SCRIPT: &[(char, char, Script)] = &[
+    ('\u{0}', '\u{40}', Script::Common),
+    ('\u{41}', '\u{5A}', Script::Latin),
+    ('\u{5B}', '\u{60}', Script::Common),
+    ('\u{61}', '\u{7A}', Script::Latin),
+    ('\u{7B}', '\u{A9}', Script::Common),
+    ('\u{AA}', '\u{AA}', Script::Latin),
+    ('\u{AB}', '\u{B9}', Script::Common),
+    ('\u{BA}', '\u{BA}', Script::Latin),
+    ('\u{BB}', '\u{BF}', Script::Common),
+    ('\u{C0}', '\u{D6}', Script::Latin),
+    ('\u{D7}', '\u{D7}', Script::Common),
+    ('\u{D8}', '\u{F6}', Script::Latin),
+    ('\u{F7}', '\u{F7}', Script::Common),
+    ('\u{F8}', '\u{2B8}', Script::Latin),
+    ('\u{2B9}', '\u{2DF}', Script::Common),
+    ('\u{2E0}', '\u{2E4}', Script::Latin),
+    ('\u{2E5}', '\u{2E9}', Script::Common),
+    ('\u{2EA}', '\u{2EB}', Script::Bopomofo),
+    ('\u{2EC}', '\u{2FF}', Script::Common),
+    ('\u{300}', '\u{36F}', Script::Inherited),
+    ('\u{370}', '\u{373}', Script::Greek),
+    ('\u{374}', '\u{374}', Script::Common),
+    ('\u{375}', '\u{377}', Script::Greek),
+    ('\u{37A}', '\u{37D}', Script::Greek),
+    ('\u{37E}', '\u{37E}', Script::Common),
+    ('\u{37F}', '\u{37F}', Script::Greek),
+    ('\u{384}', '\u{384}', Script::Greek),
+    ('\u{385}', '\u{385}', Script::Common),
+    ('\u{386}', '\u{386}', Script::Greek),
+    ('\u{387}', '\u{387}', Script::Common),
+    ('\u{388}', '\u{38A}', Script::Greek),
+    ('\u{38C}', '\u{38C}', Script::Greek),
+    ('\u{38E}', '\u{3A1}', Script::Greek),
+    ('\u{3A3}', '\u{3E1}', Script::Greek),
+    ('\u{3E2}', '\u{3EF}', Script::Coptic),
+    ('\u{3F0}', '\u{3FF}', Script::Greek),
+    ('\u{400}', '\u{484}', Script::Cyrillic),
+    ('\u{485}', '\u{486}', Script::Inherited),
+    ('\u{487}', '\u{52F}', Script::Cyrillic),
+    ('\u{531}', '\u{556}', Script::Armenian),
+    ('\u{559}', '\u{58A}', Script::Armenian),
+    ('\u{58D}', '\u{58F}', Script::Armenian),
+    ('\u{591}', '\u{5C7}', Script::Hebrew),
+    ('\u{5D0}', '\u{5EA}', Script::Hebrew),
+    ('\u{5EF}', '\u{5F4}', Script::Hebrew),
+    ('\u{600}', '\u{604}', Script::Arabic),
+    ('\u{605}', '\u{605}', Script::Common),
+    ('\u{606}', '\u{60B}', Script::Arabic),
+    ('\u{60C}', '\u{60C}', Script::Common),
+    ('\u{60D}', '\u{61A}', Script::Arabic),
+    ('\u{61B}', '\u{61B}', Script::Common),
+    ('\u{61C}', '\u{61E}', Script::Arabic),
+    ('\u{61F}', '\u{61F}', Script::Common),
+    ('\u{620}', '\u{63F}', Script::Arabic),
+    ('\u{640}', '\u{640}', Script::Common),
+    ('\u{641}', '\u{64A}', Script::Arabic),
+    ('\u{64B}', '\u{655}', Script::Inherited),
+    ('\u{656}', '\u{66F}', Script::Arabic),
+    ('\u{670}', '\u{670}', Script::Inherited),
+    ('\u{671}', '\u{6DC}', Script::Arabic),
+    ('\u{6DD}', '\u{6DD}', Script::Common),
+    ('\u{6DE}', '\u{6FF}', Script::Arabic),
+    ('\u{700}', '\u{70D}', Script::Syriac),
+    ('\u{70F}', '\u{74A}', Script::Syriac),
+    ('\u{74D}', '\u{74F}', Script::Syriac),
+    ('\u{750}', '\u{77F}', Script::Arabic),
+    ('\u{780}', '\u{7B1}', Script::Thaana),
+    ('\u{7C0}', '\u{7FA}', Script::Nko),
+    ('\u{7FD}', '\u{7FF}', Script::Nko),
+    ('\u{800}', '\u{82D}', Script::Samaritan),
+    ('\u{830}', '\u{83E}', Script::Samaritan),
+    ('\u{840}', '\u{85B}', Script::Mandaic),
+    ('\u{85E}', '\u{85E}', Script::Mandaic),
+    ('\u{860}', '\u{86A}', Script::Syriac),
+    ('\u{870}', '\u{88E}', Script::Arabic),
+    ('\u{890}', '\u{891}', Script::Arabic),
+    ('\u{898}', '\u{8E1}', Script::Arabic),
+    ('\u{8E2}', '\u{8E2}', Script::Common),
+    ('\u{8E3}', '\u{8FF}', Script::Arabic),
+    ('\u{900}', '\u{950}', Script::Devanagari),
+    ('\u{951}', '\u{954}', Script::Inherited),
+    ('\u{955}', '\u{963}', Script::Devanagari),
+    ('\u{964}', '\u{965}', Script::Common),
+    ('\u{966}', '\u{97F}', Script::Devanagari),
+    ('\u{980}', '\u{983}', Script::Bengali),
+    ('\u{985}', '\u{98C}', Script::Bengali),
+    ('\u{98F}', '\u{990}', Script::Bengali),
+    ('\u{993}', '\u{9A8}', Script::Bengali),
+    ('\u{9AA}', '\u{9B0}', Script::Bengali),
+    ('\u{9B2}', '\u{9B2}', Script::Bengali),
+    ('\u{9B6}', '\u{9B9}', Script::Bengali),
+    ('\u{9BC}', '\u{9C4}', Script::Bengali),
+    ('\u{9C7}', '\u{9C8}', Script::Bengali),
+    ('\u{9CB}', '\u{9CE}', Script::Bengali),
+    ('\u{9D7}', '\u{9D7}', Script::Bengali),
+    ('\u{9DC}', '\u{9DD}', Script::Bengali),
+    ('\u{9DF}', '\u{9E3}', Script::Bengali),
+    ('\u{9E6}', '\u{9FE}', Script::Bengali),
+    ('\u{A01}', '\u{A03}', Script::Gurmukhi),
+    ('\u{A05}', '\u{A0A}', Script::Gurmukhi),
+    ('\u{A0F}', '\u{A10}', Script::Gurmukhi),
+    ('\u{A13}', '\u{A28}', Script::Gurmukhi),
+    ('\u{A2A}', '\u{A30}', Script::Gurmukhi),
+    ('\u{A32}', '\u{A33}', Script::Gurmukhi),
+    ('\u{A35}', '\u{A36}', Script::Gurmukhi),
+    ('\u{A38}', '\u{A39}', Script::Gurmukhi),
+    ('\u{A3C}', '\u{A3C}', Script::Gurmukhi),
+    ('\u{A3E}', '\u{A42}', Script::Gurmukhi),
+    ('\u{A47}', '\u{A48}', Script::Gurmukhi),
+    ('\u{A4B}', '\u{A4D}', Script::Gurmukhi),
+    ('\u{A51}', '\u{A51}', Script::Gurmukhi),
+    ('\u{A59}', '\u{A5C}', Script::Gurmukhi),
+    ('\u{A5E}', '\u{A5E}', Script::Gurmukhi),
+    ('\u{A66}', '\u{A76}', Script::Gurmukhi),
+    ('\u{A81}', '\u{A83}', Script::Gujarati),
+    ('\u{A85}', '\u{A8D}', Script::Gujarati),
+    ('\u{A8F}', '\u{A91}', Script::Gujarati),
+    ('\u{A93}', '\u{AA8}', Script::Gujarati),
+    ('\u{AAA}', '\u{AB0}', Script::Gujarati),
+    ('\u{AB2}', '\u{AB3}', Script::Gujarati),
+    ('\u{AB5}', '\u{AB9}', Script::Gujarati),
+    ('\u{ABC}', '\u{AC5}', Script::Gujarati),
+    ('\u{AC7}', '\u{AC9}', Script::Gujarati),
+    ('\u{ACB}', '\u{ACD}', Script::Gujarati),
+    ('\u{AD0}', '\u{AD0}', Script::Gujarati),
+    ('\u{AE0}', '\u{AE3}', Script::Gujarati),
+    ('\u{AE6}', '\u{AF1}', Script::Gujarati),
+    ('\u{AF9}', '\u{AFF}', Script::Gujarati),
+    ('\u{B01}', '\u{B03}', Script::Oriya),
+    ('\u{B05}', '\u{B0C}', Script::Oriya),
+    ('\u{B0F}', '\u{B10}', Script::Oriya),
+    ('\u{B13}', '\u{B28}', Script::Oriya),
+    ('\u{B2A}', '\u{B30}', Script::Oriya),
+    ('\u{B32}', '\u{B33}', Script::Oriya),
+    ('\u{B35}', '\u{B39}', Script::Oriya),
+    ('\u{B3C}', '\u{B44}', Script::Oriya),
+    ('\u{B47}', '\u{B48}', Script::Oriya),
+    ('\u{B4B}', '\u{B4D}', Script::Oriya),
+    ('\u{B55}', '\u{B57}', Script::Oriya),
+    ('\u{B5C}', '\u{B5D}', Script::Oriya),
+    ('\u{B5F}', '\u{B63}', Script::Oriya),
+    ('\u{B66}', '\u{B77}', Script::Oriya),
+    ('\u{B82}', '\u{B83}', Script::Tamil),
+    ('\u{B85}', '\u{B8A}', Script::Tamil),
+    ('\u{B8E}', '\u{B90}', Script::Tamil),
+    ('\u{B92}', '\u{B95}', Script::Tamil),
+    ('\u{B99}', '\u{B9A}', Script::Tamil),
+    ('\u{B9C}', '\u{B9C}', Script::Tamil),
+    ('\u{B9E}', '\u{B9F}', Script::Tamil),
+    ('\u{BA3}', '\u{BA4}', Script::Tamil),
+    ('\u{BA8}', '\u{BAA}', Script::Tamil),
+    ('\u{BAE}', '\u{BB9}', Script::Tamil),
+    ('\u{BBE}', '\u{BC2}', Script::Tamil),
+    ('\u{BC6}', '\u{BC8}', Script::Tamil),
+    ('\u{BCA}', '\u{BCD}', Script::Tamil),
+    ('\u{BD0}', '\u{BD0}', Script::Tamil),
+    ('\u{BD7}', '\u{BD7}', Script::Tamil),
+    ('\u{BE6}', '\u{BFA}', Script::Tamil),
+    ('\u{C00}', '\u{C0C}', Script::Telugu),
+    ('\u{C0E}', '\u{C10}', Script::Telugu),
+    ('\u{C12}', '\u{C28}', Script::Telugu),
+    ('\u{C2A}', '\u{C39}', Script::Telugu),
+    ('\u{C3C}', '\u{C44}', Script::Telugu),
+    ('\u{C46}', '\u{C48}', Script::Telugu),
+    ('\u{C4A}', '\u{C4D}', Script::Telugu),
+    ('\u{C55}', '\u{C56}', Script::Telugu),
+    ('\u{C58}', '\u{C5A}', Script::Telugu),
+    ('\u{C5D}', '\u{C5D}', Script::Telugu),
+    ('\u{C60}', '\u{C63}', Script::Telugu),
+    ('\u{C66}', '\u{C6F}', Script::Telugu),
+    ('\u{C77}', '\u{C7F}', Script::Telugu),
+    ('\u{C80}', '\u{C8C}', Script::Kannada),
+    ('\u{C8E}', '\u{C90}', Script::Kannada),
+    ('\u{C92}', '\u{CA8}', Script::Kannada),
+    ('\u{CAA}', '\u{CB3}', Script::Kannada),
+    ('\u{CB5}', '\u{CB9}', Script::Kannada),
+    ('\u{CBC}', '\u{CC4}', Script::Kannada),
+    ('\u{CC6}', '\u{CC8}', Script::Kannada),
+    ('\u{CCA}', '\u{CCD}', Script::Kannada),
+    ('\u{CD5}', '\u{CD6}', Script::Kannada),
+    ('\u{CDD}', '\u{CDE}', Script::Kannada),
+    ('\u{CE0}', '\u{CE3}', Script::Kannada),
+    ('\u{CE6}', '\u{CEF}', Script::Kannada),
+    ('\u{CF1}', '\u{CF3}', Script::Kannada),
+    ('\u{D00}', '\u{D0C}', Script::Malayalam),
+    ('\u{D0E}', '\u{D10}', Script::Malayalam),
+    ('\u{D12}', '\u{D44}', Script::Malayalam),
+    ('\u{D46}', '\u{D48}', Script::Malayalam),
+    ('\u{D4A}', '\u{D4F}', Script::Malayalam),
+    ('\u{D54}', '\u{D63}', Script::Malayalam),
+    ('\u{D66}', '\u{D7F}', Script::Malayalam),
+    ('\u{D81}', '\u{D83}', Script::Sinhala),
+    ('\u{D85}', '\u{D96}', Script::Sinhala),
+    ('\u{D9A}', '\u{DB1}', Script::Sinhala),
+    ('\u{DB3}', '\u{DBB}', Script::Sinhala),
+    ('\u{DBD}', '\u{DBD}', Script::Sinhala),
+    ('\u{DC0}', '\u{DC6}', Script::Sinhala),
+    ('\u{DCA}', '\u{DCA}', Script::Sinhala),
+    ('\u{DCF}', '\u{DD4}', Script::Sinhala),
+    ('\u{DD6}', '\u{DD6}', Script::Sinhala),
+    ('\u{DD8}', '\u{DDF}', Script::Sinhala),
+    ('\u{DE6}', '\u{DEF}', Script::Sinhala),
+    ('\u{DF2}', '\u{DF4}', Script::Sinhala),
+    ('\u{E01}', '\u{E3A}', Script::Thai),
+    ('\u{E3F}', '\u{E3F}', Script::Common),
+    ('\u{E40}', '\u{E5B}', Script::Thai),
+    ('\u{E81}', '\u{E82}', Script::Lao),
+    ('\u{E84}', '\u{E84}', Script::Lao),
+    ('\u{E86}', '\u{E8A}', Script::Lao),
+    ('\u{E8C}', '\u{EA3}', Script::Lao),
+    ('\u{EA5}', '\u{EA5}', Script::Lao),
+    ('\u{EA7}', '\u{EBD}', Script::Lao),
+    ('\u{EC0}', '\u{EC4}', Script::Lao),
+    ('\u{EC6}', '\u{EC6}', Script::Lao),
+    ('\u{EC8}', '\u{ECE}', Script::Lao),
+    ('\u{ED0}', '\u{ED9}', Script::Lao),
+    ('\u{EDC}', '\u{EDF}', Script::Lao),
+    ('\u{F00}', '\u{F47}', Script::Tibetan),
+    ('\u{F49}', '\u{F6C}', Script::Tibetan),
+    ('\u{F71}', '\u{F97}', Script::Tibetan),
+    ('\u{F99}', '\u{FBC}', Script::Tibetan),
+    ('\u{FBE}', '\u{FCC}', Script::Tibetan),
+    ('\u{FCE}', '\u{FD4}', Script::Tibetan),
+    ('\u{FD5}', '\u{FD8}', Script::Common),
+    ('\u{FD9}', '\u{FDA}', Script::Tibetan),
+    ('\u{1000}', '\u{109F}', Script::Myanmar),
+    ('\u{10A0}', '\u{10C5}', Script::Georgian),
+    ('\u{10C7}', '\u{10C7}', Script::Georgian),
+    ('\u{10CD}', '\u{10CD}', Script::Georgian),
+    ('\u{10D0}', '\u{10FA}', Script::Georgian),
+    ('\u{10FB}', '\u{10FB}', Script::Common),
+    ('\u{10FC}', '\u{10FF}', Script::Georgian),
+    ('\u{1100}', '\u{11FF}', Script::Hangul),
+    ('\u{1200}', '\u{1248}', Script::Ethiopic),
+    ('\u{124A}', '\u{124D}', Script::Ethiopic),
+    ('\u{1250}', '\u{1256}', Script::Ethiopic),
+    ('\u{1258}', '\u{1258}', Script::Ethiopic),
+    ('\u{125A}', '\u{125D}', Script::Ethiopic),
+    ('\u{1260}', '\u{1288}', Script::Ethiopic),
+    ('\u{128A}', '\u{128D}', Script::Ethiopic),
+    ('\u{1290}', '\u{12B0}', Script::Ethiopic),
+    ('\u{12B2}', '\u{12B5}', Script::Ethiopic),
+    ('\u{12B8}', '\u{12BE}', Script::Ethiopic),
+    ('\u{12C0}', '\u{12C0}', Script::Ethiopic),
+    ('\u{12C2}', '\u{12C5}', Script::Ethiopic),
+    ('\u{12C8}', '\u{12D6}', Script::Ethiopic),
+    ('\u{12D8}', '\u{1310}', Script::Ethiopic),
+    ('\u{1312}', '\u{1315}', Script::Ethiopic),
+    ('\u{1318}', '\u{135A}', Script::Ethiopic),
+    ('\u{135D}', '\u{137C}', Script::Ethiopic),
+    ('\u{1380}', '\u{1399}', Script::Ethiopic),
+    ('\u{13A0}', '\u{13F5}', Script::Cherokee),
+    ('\u{13F8}', '\u{13FD}', Script::Cherokee),
+    ('\u{1400}', '\u{167F}', Script::CanadianAboriginal),
+    ('\u{1680}', '\u{169C}', Script::Ogham),
+    ('\u{16A0}', '\u{16EA}', Script::Runic),
+    ('\u{16EB}', '\u{16ED}', Script::Common),
+    ('\u{16EE}', '\u{16F8}', Script::Runic),
+    ('\u{1700}', '\u{1715}', Script::Tagalog),
+    ('\u{171F}', '\u{171F}', Script::Tagalog),
+    ('\u{1720}', '\u{1734}', Script::Hanunoo),
+    ('\u{1735}', '\u{1736}', Script::Common),
+    ('\u{1740}', '\u{1753}', Script::Buhid),
+    ('\u{1760}', '\u{176C}', Script::Tagbanwa),
+    ('\u{176E}', '\u{1770}', Script::Tagbanwa),
+    ('\u{1772}', '\u{1773}', Script::Tagbanwa),
+    ('\u{1780}', '\u{17DD}', Script::Khmer),
+    ('\u{17E0}', '\u{17E9}', Script::Khmer),
+    ('\u{17F0}', '\u{17F9}', Script::Khmer),
+    ('\u{1800}', '\u{1801}', Script::Mongolian),
+    ('\u{1802}', '\u{1803}', Script::Common),
+    ('\u{1804}', '\u{1804}', Script::Mongolian),
+    ('\u{1805}', '\u{1805}', Script::Common),
+    ('\u{1806}', '\u{1819}', Script::Mongolian),
+    ('\u{1820}', '\u{1878}', Script::Mongolian),
+    ('\u{1880}', '\u{18AA}', Script::Mongolian),
+    ('\u{18B0}', '\u{18F5}', Script::CanadianAboriginal),
+    ('\u{1900}', '\u{191E}', Script::Limbu),
+    ('\u{1920}', '\u{192B}', Script::Limbu),
+    ('\u{1930}', '\u{193B}', Script::Limbu),
+    ('\u{1940}', '\u{1940}', Script::Limbu),
+    ('\u{1944}', '\u{194F}', Script::Limbu),
+    ('\u{1950}', '\u{196D}', Script::TaiLe),
+    ('\u{1970}', '\u{1974}', Script::TaiLe),
+    ('\u{1980}', '\u{19AB}', Script::NewTaiLue),
+    ('\u{19B0}', '\u{19C9}', Script::NewTaiLue),
+    ('\u{19D0}', '\u{19DA}', Script::NewTaiLue),
+    ('\u{19DE}', '\u{19DF}', Script::NewTaiLue),
+    ('\u{19E0}', '\u{19FF}', Script::Khmer),
+    ('\u{1A00}', '\u{1A1B}', Script::Buginese),
+    ('\u{1A1E}', '\u{1A1F}', Script::Buginese),
+    ('\u{1A20}', '\u{1A5E}', Script::TaiTham),
+    ('\u{1A60}', '\u{1A7C}', Script::TaiTham),
+    ('\u{1A7F}', '\u{1A89}', Script::TaiTham),
+    ('\u{1A90}', '\u{1A99}', Script::TaiTham),
+    ('\u{1AA0}', '\u{1AAD}', Script::TaiTham),
+    ('\u{1AB0}', '\u{1ACE}', Script::Inherited),
+    ('\u{1B00}', '\u{1B4C}', Script::Balinese),
+    ('\u{1B50}', '\u{1B7E}', Script::Balinese),
+    ('\u{1B80}', '\u{1BBF}', Script::Sundanese),
+    ('\u{1BC0}', '\u{1BF3}', Script::Batak),
+    ('\u{1BFC}', '\u{1BFF}', Script::Batak),
+    ('\u{1C00}', '\u{1C37}', Script::Lepcha),
+    ('\u{1C3B}', '\u{1C49}', Script::Lepcha),
+    ('\u{1C4D}', '\u{1C4F}', Script::Lepcha),
+    ('\u{1C50}', '\u{1C7F}', Script::OlChiki),
+    ('\u{1C80}', '\u{1C88}', Script::Cyrillic),
+    ('\u{1C90}', '\u{1CBA}', Script::Georgian),
+    ('\u{1CBD}', '\u{1CBF}', Script::Georgian),
+    ('\u{1CC0}', '\u{1CC7}', Script::Sundanese),
+    ('\u{1CD0}', '\u{1CD2}', Script::Inherited),
+    ('\u{1CD3}', '\u{1CD3}', Script::Common),
+    ('\u{1CD4}', '\u{1CE0}', Script::Inherited),
+    ('\u{1CE1}', '\u{1CE1}', Script::Common),
+    ('\u{1CE2}', '\u{1CE8}', Script::Inherited),
+    ('\u{1CE9}', '\u{1CEC}', Script::Common),
+    ('\u{1CED}', '\u{1CED}', Script::Inherited),
+    ('\u{1CEE}', '\u{1CF3}', Script::Common),
+    ('\u{1CF4}', '\u{1CF4}', Script::Inherited),
+    ('\u{1CF5}', '\u{1CF7}', Script::Common),
+    ('\u{1CF8}', '\u{1CF9}', Script::Inherited),
+    ('\u{1CFA}', '\u{1CFA}', Script::Common),
+    ('\u{1D00}', '\u{1D25}', Script::Latin),
+    ('\u{1D26}', '\u{1D2A}', Script::Greek),
+    ('\u{1D2B}', '\u{1D2B}', Script::Cyrillic),
+    ('\u{1D2C}', '\u{1D5C}', Script::Latin),
+    ('\u{1D5D}', '\u{1D61}', Script::Greek),
+    ('\u{1D62}', '\u{1D65}', Script::Latin),
+    ('\u{1D66}', '\u{1D6A}', Script::Greek),
+    ('\u{1D6B}', '\u{1D77}', Script::Latin),
+    ('\u{1D78}', '\u{1D78}', Script::Cyrillic),
+    ('\u{1D79}', '\u{1DBE}', Script::Latin),
+    ('\u{1DBF}', '\u{1DBF}', Script::Greek),
+    ('\u{1DC0}', '\u{1DFF}', Script::Inherited),
+    ('\u{1E00}', '\u{1EFF}', Script::Latin),
+    ('\u{1F00}', '\u{1F15}', Script::Greek),
+    ('\u{1F18}', '\u{1F1D}', Script::Greek),
+    ('\u{1F20}', '\u{1F45}', Script::Greek),
+    ('\u{1F48}', '\u{1F4D}', Script::Greek),
+    ('\u{1F50}', '\u{1F57}', Script::Greek),
+    ('\u{1F59}', '\u{1F59}', Script::Greek),
+    ('\u{1F5B}', '\u{1F5B}', Script::Greek),
+    ('\u{1F5D}', '\u{1F5D}', Script::Greek),
+    ('\u{1F5F}', '\u{1F7D}', Script::Greek),
+    ('\u{1F80}', '\u{1FB4}', Script::Greek),
+    ('\u{1FB6}', '\u{1FC4}', Script::Greek),
+    ('\u{1FC6}', '\u{1FD3}', Script::Greek),
+    ('\u{1FD6}', '\u{1FDB}', Script::Greek),
+    ('\u{1FDD}', '\u{1FEF}', Script::Greek),
+    ('\u{1FF2}', '\u{1FF4}', Script::Greek),
+    ('\u{1FF6}', '\u{1FFE}', Script::Greek),
+    ('\u{2000}', '\u{200B}', Script::Common),
+    ('\u{200C}', '\u{200D}', Script::Inherited),
+    ('\u{200E}', '\u{2064}', Script::Common),
+    ('\u{2066}', '\u{2070}', Script::Common),
+    ('\u{2071}', '\u{2071}', Script::Latin),
+    ('\u{2074}', '\u{207E}', Script::Common),
+    ('\u{207F}', '\u{207F}', Script::Latin),
+    ('\u{2080}', '\u{208E}', Script::Common),
+    ('\u{2090}', '\u{209C}', Script::Latin),
+    ('\u{20A0}', '\u{20C0}', Script::Common),
+    ('\u{20D0}', '\u{20F0}', Script::Inherited),
+    ('\u{2100}', '\u{2125}', Script::Common),
+    ('\u{2126}', '\u{2126}', Script::Greek),
+    ('\u{2127}', '\u{2129}', Script::Common),
+    ('\u{212A}', '\u{212B}', Script::Latin),
+    ('\u{212C}', '\u{2131}', Script::Common),
+    ('\u{2132}', '\u{2132}', Script::Latin),
+    ('\u{2133}', '\u{214D}', Script::Common),
+    ('\u{214E}', '\u{214E}', Script::Latin),
+    ('\u{214F}', '\u{215F}', Script::Common),
+    ('\u{2160}', '\u{2188}', Script::Latin),
+    ('\u{2189}', '\u{218B}', Script::Common),
+    ('\u{2190}', '\u{2426}', Script::Common),
+    ('\u{2440}', '\u{244A}', Script::Common),
+    ('\u{2460}', '\u{27FF}', Script::Common),
+    ('\u{2800}', '\u{28FF}', Script::Braille),
+    ('\u{2900}', '\u{2B73}', Script::Common),
+    ('\u{2B76}', '\u{2B95}', Script::Common),
+    ('\u{2B97}', '\u{2BFF}', Script::Common),
+    ('\u{2C00}', '\u{2C5F}', Script::Glagolitic),
+    ('\u{2C60}', '\u{2C7F}', Script::Latin),
+    ('\u{2C80}', '\u{2CF3}', Script::Coptic),
+    ('\u{2CF9}', '\u{2CFF}', Script::Coptic),
+    ('\u{2D00}', '\u{2D25}', Script::Georgian),
+    ('\u{2D27}', '\u{2D27}', Script::Georgian),
+    ('\u{2D2D}', '\u{2D2D}', Script::Georgian),
+    ('\u{2D30}', '\u{2D67}', Script::Tifinagh),
+    ('\u{2D6F}', '\u{2D70}', Script::Tifinagh),
+    ('\u{2D7F}', '\u{2D7F}', Script::Tifinagh),
+    ('\u{2D80}', '\u{2D96}', Script::Ethiopic),
+    ('\u{2DA0}', '\u{2DA6}', Script::Ethiopic),
+    ('\u{2DA8}', '\u{2DAE}', Script::Ethiopic),
+    ('\u{2DB0}', '\u{2DB6}', Script::Ethiopic),
+    ('\u{2DB8}', '\u{2DBE}', Script::Ethiopic),
+    ('\u{2DC0}', '\u{2DC6}', Script::Ethiopic),
+    ('\u{2DC8}', '\u{2DCE}', Script::Ethiopic),
+    ('\u{2DD0}', '\u{2DD6}', Script::Ethiopic),
+    ('\u{2DD8}', '\u{2DDE}', Script::Ethiopic),
+    ('\u{2DE0}', '\u{2DFF}', Script::Cyrillic),
+    ('\u{2E00}', '\u{2E5D}', Script::Common),
+    ('\u{2E80}', '\u{2E99}', Script::Han),
+    ('\u{2E9B}', '\u{2EF3}', Script::Han),
+    ('\u{2F00}', '\u{2FD5}', Script::Han),
+    ('\u{2FF0}', '\u{2FFB}', Script::Common),
+    ('\u{3000}', '\u{3004}', Script::Common),
+    ('\u{3005}', '\u{3005}', Script::Han),
+    ('\u{3006}', '\u{3006}', Script::Common),
+    ('\u{3007}', '\u{3007}', Script::Han),
+    ('\u{3008}', '\u{3020}', Script::Common),
+    ('\u{3021}', '\u{3029}', Script::Han),
+    ('\u{302A}', '\u{302D}', Script::Inherited),
+    ('\u{302E}', '\u{302F}', Script::Hangul),
+    ('\u{3030}', '\u{3037}', Script::Common),
+    ('\u{3038}', '\u{303B}', Script::Han),
+    ('\u{303C}', '\u{303F}', Script::Common),
+    ('\u{3041}', '\u{3096}', Script::Hiragana),
+    ('\u{3099}', '\u{309A}', Script::Inherited),
+    ('\u{309B}', '\u{309C}', Script::Common),
+    ('\u{309D}', '\u{309F}', Script::Hiragana),
+    ('\u{30A0}', '\u{30A0}', Script::Common),
+    ('\u{30A1}', '\u{30FA}', Script::Katakana),
+    ('\u{30FB}', '\u{30FC}', Script::Common),
+    ('\u{30FD}', '\u{30FF}', Script::Katakana),
+    ('\u{3105}', '\u{312F}', Script::Bopomofo),
+    ('\u{3131}', '\u{318E}', Script::Hangul),
+    ('\u{3190}', '\u{319F}', Script::Common),
+    ('\u{31A0}', '\u{31BF}', Script::Bopomofo),
+    ('\u{31C0}', '\u{31E3}', Script::Common),
+    ('\u{31F0}', '\u{31FF}', Script::Katakana),
+    ('\u{3200}', '\u{321E}', Script::Hangul),
+    ('\u{3220}', '\u{325F}', Script::Common),
+    ('\u{3260}', '\u{327E}', Script::Hangul),
+    ('\u{327F}', '\u{32CF}', Script::Common),
+    ('\u{32D0}', '\u{32FE}', Script::Katakana),
+    ('\u{32FF}', '\u{32FF}', Script::Common),
+    ('\u{3300}', '\u{3357}', Script::Katakana),
+    ('\u{3358}', '\u{33FF}', Script::Common),
+    ('\u{3400}', '\u{4DBF}', Script::Han),
+    ('\u{4DC0}', '\u{4DFF}', Script::Common),
+    ('\u{4E00}', '\u{9FFF}', Script::Han),
+    ('\u{A000}', '\u{A48C}', Script::Yi),
+    ('\u{A490}', '\u{A4C6}', Script::Yi),
+    ('\u{A4D0}', '\u{A4FF}', Script::Lisu),
+    ('\u{A500}', '\u{A62B}', Script::Vai),
+    ('\u{A640}', '\u{A69F}', Script::Cyrillic),
+    ('\u{A6A0}', '\u{A6F7}', Script::Bamum),
+    ('\u{A700}', '\u{A721}', Script::Common),
+    ('\u{A722}', '\u{A787}', Script::Latin),
+    ('\u{A788}', '\u{A78A}', Script::Common),
+    ('\u{A78B}', '\u{A7CA}', Script::Latin),
+    ('\u{A7D0}', '\u{A7D1}', Script::Latin),
+    ('\u{A7D3}', '\u{A7D3}', Script::Latin),
+    ('\u{A7D5}', '\u{A7D9}', Script::Latin),
+    ('\u{A7F2}', '\u{A7FF}', Script::Latin),
+    ('\u{A800}', '\u{A82C}', Script::SylotiNagri),
+    ('\u{A830}', '\u{A839}', Script::Common),
+    ('\u{A840}', '\u{A877}', Script::PhagsPa),
+    ('\u{A880}', '\u{A8C5}', Script::Saurashtra),
+    ('\u{A8CE}', '\u{A8D9}', Script::Saurashtra),
+    ('\u{A8E0}', '\u{A8FF}', Script::Devanagari),
+    ('\u{A900}', '\u{A92D}', Script::KayahLi),
+    ('\u{A92E}', '\u{A92E}', Script::Common),
+    ('\u{A92F}', '\u{A92F}', Script::KayahLi),
+    ('\u{A930}', '\u{A953}', Script::Rejang),
+    ('\u{A95F}', '\u{A95F}', Script::Rejang),
+    ('\u{A960}', '\u{A97C}', Script::Hangul),
+    ('\u{A980}', '\u{A9CD}', Script::Javanese),
+    ('\u{A9CF}', '\u{A9CF}', Script::Common),
+    ('\u{A9D0}', '\u{A9D9}', Script::Javanese),
+    ('\u{A9DE}', '\u{A9DF}', Script::Javanese),
+    ('\u{A9E0}', '\u{A9FE}', Script::Myanmar),
+    ('\u{AA00}', '\u{AA36}', Script::Cham),
+    ('\u{AA40}', '\u{AA4D}', Script::Cham),
+    ('\u{AA50}', '\u{AA59}', Script::Cham),
+    ('\u{AA5C}', '\u{AA5F}', Script::Cham),
+    ('\u{AA60}', '\u{AA7F}', Script::Myanmar),
+    ('\u{AA80}', '\u{AAC2}', Script::TaiViet),
+    ('\u{AADB}', '\u{AADF}', Script::TaiViet),
+    ('\u{AAE0}', '\u{AAF6}', Script::MeeteiMayek),
+    ('\u{AB01}', '\u{AB06}', Script::Ethiopic),
+    ('\u{AB09}', '\u{AB0E}', Script::Ethiopic),
+    ('\u{AB11}', '\u{AB16}', Script::Ethiopic),
+    ('\u{AB20}', '\u{AB26}', Script::Ethiopic),
+    ('\u{AB28}', '\u{AB2E}', Script::Ethiopic),
+    ('\u{AB30}', '\u{AB5A}', Script::Latin),
+    ('\u{AB5B}', '\u{AB5B}', Script::Common),
+    ('\u{AB5C}', '\u{AB64}', Script::Latin),
+    ('\u{AB65}', '\u{AB65}', Script::Greek),
+    ('\u{AB66}', '\u{AB69}', Script::Latin),
+    ('\u{AB6A}', '\u{AB6B}', Script::Common),
+    ('\u{AB70}', '\u{ABBF}', Script::Cherokee),
+    ('\u{ABC0}', '\u{ABED}', Script::MeeteiMayek),
+    ('\u{ABF0}', '\u{ABF9}', Script::MeeteiMayek),
+    ('\u{AC00}', '\u{D7A3}', Script::Hangul),
+    ('\u{D7B0}', '\u{D7C6}', Script::Hangul),
+    ('\u{D7CB}', '\u{D7FB}', Script::Hangul),
+    ('\u{F900}', '\u{FA6D}', Script::Han),
+    ('\u{FA70}', '\u{FAD9}', Script::Han),
+    ('\u{FB00}', '\u{FB06}', Script::Latin),
+    ('\u{FB13}', '\u{FB17}', Script::Armenian),
+    ('\u{FB1D}', '\u{FB36}', Script::Hebrew),
+    ('\u{FB38}', '\u{FB3C}', Script::Hebrew),
+    ('\u{FB3E}', '\u{FB3E}', Script::Hebrew),
+    ('\u{FB40}', '\u{FB41}', Script::Hebrew),
+    ('\u{FB43}', '\u{FB44}', Script::Hebrew),
+    ('\u{FB46}', '\u{FB4F}', Script::Hebrew),
+    ('\u{FB50}', '\u{FBC2}', Script::Arabic),
+    ('\u{FBD3}', '\u{FD3D}', Script::Arabic),
+    ('\u{FD3E}', '\u{FD3F}', Script::Common),
+    ('\u{FD40}', '\u{FD8F}', Script::Arabic),
+    ('\u{FD92}', '\u{FDC7}', Script::Arabic),
+    ('\u{FDCF}', '\u{FDCF}', Script::Arabic),
+    ('\u{FDF0}', '\u{FDFF}', Script::Arabic),
+    ('\u{FE00}', '\u{FE0F}', Script::Inherited),
+    ('\u{FE10}', '\u{FE19}', Script::Common),
+    ('\u{FE20}', '\u{FE2D}', Script::Inherited),
+    ('\u{FE2E}', '\u{FE2F}', Script::Cyrillic),
+    ('\u{FE30}', '\u{FE52}', Script::Common),
+    ('\u{FE54}', '\u{FE66}', Script::Common),
+    ('\u{FE68}', '\u{FE6B}', Script::Common),
+    ('\u{FE70}', '\u{FE74}', Script::Arabic),
+    ('\u{FE76}', '\u{FEFC}', Script::Arabic),
+    ('\u{FEFF}', '\u{FEFF}', Script::Common),
+    ('\u{FF01}', '\u{FF20}', Script::Common),
+    ('\u{FF21}', '\u{FF3A}', Script::Latin),
+    ('\u{FF3B}', '\u{FF40}', Script::Common),
+    ('\u{FF41}', '\u{FF5A}', Script::Latin),
+    ('\u{FF5B}', '\u{FF65}', Script::Common),
+    ('\u{FF66}', '\u{FF6F}', Script::Katakana),
+    ('\u{FF70}', '\u{FF70}', Script::Common),
+    ('\u{FF71}', '\u{FF9D}', Script::Katakana),
+    ('\u{FF9E}', '\u{FF9F}', Script::Common),
+    ('\u{FFA0}', '\u{FFBE}', Script::Hangul),
+    ('\u{FFC2}', '\u{FFC7}', Script::Hangul),
+    ('\u{FFCA}', '\u{FFCF}', Script::Hangul),
+    ('\u{FFD2}', '\u{FFD7}', Script::Hangul),
+    ('\u{FFDA}', '\u{FFDC}', Script::Hangul),
+    ('\u{FFE0}', '\u{FFE6}', Script::Common),
+    ('\u{FFE8}', '\u{FFEE}', Script::Common),
+    ('\u{FFF9}', '\u{FFFD}', Script::Common),
+    ('\u{10000}', '\u{1000B}', Script::LinearB),
+    ('\u{1000D}', '\u{10026}', Script::LinearB),
+    ('\u{10028}', '\u{1003A}', Script::LinearB),
+    ('\u{1003C}', '\u{1003D}', Script::LinearB),
+    ('\u{1003F}', '\u{1004D}', Script::LinearB),
+    ('\u{10050}', '\u{1005D}', Script::LinearB),
+    ('\u{10080}', '\u{100FA}', Script::LinearB),
+    ('\u{10100}', '\u{10102}', Script::Common),
+    ('\u{10107}', '\u{10133}', Script::Common),
+    ('\u{10137}', '\u{1013F}', Script::Common),
+    ('\u{10140}', '\u{1018E}', Script::Greek),
+    ('\u{10190}', '\u{1019C}', Script::Common),
+    ('\u{101A0}', '\u{101A0}', Script::Greek),
+    ('\u{101D0}', '\u{101FC}', Script::Common),
+    ('\u{101FD}', '\u{101FD}', Script::Inherited),
+    ('\u{10280}', '\u{1029C}', Script::Lycian),
+    ('\u{102A0}', '\u{102D0}', Script::Carian),
+    ('\u{102E0}', '\u{102E0}', Script::Inherited),
+    ('\u{102E1}', '\u{102FB}', Script::Common),
+    ('\u{10300}', '\u{10323}', Script::OldItalic),
+    ('\u{1032D}', '\u{1032F}', Script::OldItalic),
+    ('\u{10330}', '\u{1034A}', Script::Gothic),
+    ('\u{10350}', '\u{1037A}', Script::OldPermic),
+    ('\u{10380}', '\u{1039D}', Script::Ugaritic),
+    ('\u{1039F}', '\u{1039F}', Script::Ugaritic),
+    ('\u{103A0}', '\u{103C3}', Script::OldPersian),
+    ('\u{103C8}', '\u{103D5}', Script::OldPersian),
+    ('\u{10400}', '\u{1044F}', Script::Deseret),
+    ('\u{10450}', '\u{1047F}', Script::Shavian),
+    ('\u{10480}', '\u{1049D}', Script::Osmanya),
+    ('\u{104A0}', '\u{104A9}', Script::Osmanya),
+    ('\u{104B0}', '\u{104D3}', Script::Osage),
+    ('\u{104D8}', '\u{104FB}', Script::Osage),
+    ('\u{10500}', '\u{10527}', Script::Elbasan),
+    ('\u{10530}', '\u{10563}', Script::CaucasianAlbanian),
+    ('\u{1056F}', '\u{1056F}', Script::CaucasianAlbanian),
+    ('\u{10570}', '\u{1057A}', Script::Vithkuqi),
+    ('\u{1057C}', '\u{1058A}', Script::Vithkuqi),
+    ('\u{1058C}', '\u{10592}', Script::Vithkuqi),
+    ('\u{10594}', '\u{10595}', Script::Vithkuqi),
+    ('\u{10597}', '\u{105A1}', Script::Vithkuqi),
+    ('\u{105A3}', '\u{105B1}', Script::Vithkuqi),
+    ('\u{105B3}', '\u{105B9}', Script::Vithkuqi),
+    ('\u{105BB}', '\u{105BC}', Script::Vithkuqi),
+    ('\u{10600}', '\u{10736}', Script::LinearA),
+    ('\u{10740}', '\u{10755}', Script::LinearA),
+    ('\u{10760}', '\u{10767}', Script::LinearA),
+    ('\u{10780}', '\u{10785}', Script::Latin),
+    ('\u{10787}', '\u{107B0}', Script::Latin),
+    ('\u{107B2}', '\u{107BA}', Script::Latin),
+    ('\u{10800}', '\u{10805}', Script::Cypriot),
+    ('\u{10808}', '\u{10808}', Script::Cypriot),
+    ('\u{1080A}', '\u{10835}', Script::Cypriot),
+    ('\u{10837}', '\u{10838}', Script::Cypriot),
+    ('\u{1083C}', '\u{1083C}', Script::Cypriot),
+    ('\u{1083F}', '\u{1083F}', Script::Cypriot),
+    ('\u{10840}', '\u{10855}', Script::ImperialAramaic),
+    ('\u{10857}', '\u{1085F}', Script::ImperialAramaic),
+    ('\u{10860}', '\u{1087F}', Script::Palmyrene),
+    ('\u{10880}', '\u{1089E}', Script::Nabataean),
+    ('\u{108A7}', '\u{108AF}', Script::Nabataean),
+    ('\u{108E0}', '\u{108F2}', Script::Hatran),
+    ('\u{108F4}', '\u{108F5}', Script::Hatran),
+    ('\u{108FB}', '\u{108FF}', Script::Hatran),
+    ('\u{10900}', '\u{1091B}', Script::Phoenician),
+    ('\u{1091F}', '\u{1091F}', Script::Phoenician),
+    ('\u{10920}', '\u{10939}', Script::Lydian),
+    ('\u{1093F}', '\u{1093F}', Script::Lydian),
+    ('\u{10980}', '\u{1099F}', Script::MeroiticHieroglyphs),
+    ('\u{109A0}', '\u{109B7}', Script::MeroiticCursive),
+    ('\u{109BC}', '\u{109CF}', Script::MeroiticCursive),
+    ('\u{109D2}', '\u{109FF}', Script::MeroiticCursive),
+    ('\u{10A00}', '\u{10A03}', Script::Kharoshthi),
+    ('\u{10A05}', '\u{10A06}', Script::Kharoshthi),
+    ('\u{10A0C}', '\u{10A13}', Script::Kharoshthi),
+    ('\u{10A15}', '\u{10A17}', Script::Kharoshthi),
+    ('\u{10A19}', '\u{10A35}', Script::Kharoshthi),
+    ('\u{10A38}', '\u{10A3A}', Script::Kharoshthi),
+    ('\u{10A3F}', '\u{10A48}', Script::Kharoshthi),
+    ('\u{10A50}', '\u{10A58}', Script::Kharoshthi),
+    ('\u{10A60}', '\u{10A7F}', Script::OldSouthArabian),
+    ('\u{10A80}', '\u{10A9F}', Script::OldNorthArabian),
+    ('\u{10AC0}', '\u{10AE6}', Script::Manichaean),
+    ('\u{10AEB}', '\u{10AF6}', Script::Manichaean),
+    ('\u{10B00}', '\u{10B35}', Script::Avestan),
+    ('\u{10B39}', '\u{10B3F}', Script::Avestan),
+    ('\u{10B40}', '\u{10B55}', Script::InscriptionalParthian),
+    ('\u{10B58}', '\u{10B5F}', Script::InscriptionalParthian),
+    ('\u{10B60}', '\u{10B72}', Script::InscriptionalPahlavi),
+    ('\u{10B78}', '\u{10B7F}', Script::InscriptionalPahlavi),
+    ('\u{10B80}', '\u{10B91}', Script::PsalterPahlavi),
+    ('\u{10B99}', '\u{10B9C}', Script::PsalterPahlavi),
+    ('\u{10BA9}', '\u{10BAF}', Script::PsalterPahlavi),
+    ('\u{10C00}', '\u{10C48}', Script::OldTurkic),
+    ('\u{10C80}', '\u{10CB2}', Script::OldHungarian),
+    ('\u{10CC0}', '\u{10CF2}', Script::OldHungarian),
+    ('\u{10CFA}', '\u{10CFF}', Script::OldHungarian),
+    ('\u{10D00}', '\u{10D27}', Script::HanifiRohingya),
+    ('\u{10D30}', '\u{10D39}', Script::HanifiRohingya),
+    ('\u{10E60}', '\u{10E7E}', Script::Arabic),
+    ('\u{10E80}', '\u{10EA9}', Script::Yezidi),
+    ('\u{10EAB}', '\u{10EAD}', Script::Yezidi),
+    ('\u{10EB0}', '\u{10EB1}', Script::Yezidi),
+    ('\u{10EFD}', '\u{10EFF}', Script::Arabic),
+    ('\u{10F00}', '\u{10F27}', Script::OldSogdian),
+    ('\u{10F30}', '\u{10F59}', Script::Sogdian),
+    ('\u{10F70}', '\u{10F89}', Script::OldUyghur),
+    ('\u{10FB0}', '\u{10FCB}', Script::Chorasmian),
+    ('\u{10FE0}', '\u{10FF6}', Script::Elymaic),
+    ('\u{11000}', '\u{1104D}', Script::Brahmi),
+    ('\u{11052}', '\u{11075}', Script::Brahmi),
+    ('\u{1107F}', '\u{1107F}', Script::Brahmi),
+    ('\u{11080}', '\u{110C2}', Script::Kaithi),
+    ('\u{110CD}', '\u{110CD}', Script::Kaithi),
+    ('\u{110D0}', '\u{110E8}', Script::SoraSompeng),
+    ('\u{110F0}', '\u{110F9}', Script::SoraSompeng),
+    ('\u{11100}', '\u{11134}', Script::Chakma),
+    ('\u{11136}', '\u{11147}', Script::Chakma),
+    ('\u{11150}', '\u{11176}', Script::Mahajani),
+    ('\u{11180}', '\u{111DF}', Script::Sharada),
+    ('\u{111E1}', '\u{111F4}', Script::Sinhala),
+    ('\u{11200}', '\u{11211}', Script::Khojki),
+    ('\u{11213}', '\u{11241}', Script::Khojki),
+    ('\u{11280}', '\u{11286}', Script::Multani),
+    ('\u{11288}', '\u{11288}', Script::Multani),
+    ('\u{1128A}', '\u{1128D}', Script::Multani),
+    ('\u{1128F}', '\u{1129D}', Script::Multani),
+    ('\u{1129F}', '\u{112A9}', Script::Multani),
+    ('\u{112B0}', '\u{112EA}', Script::Khudawadi),
+    ('\u{112F0}', '\u{112F9}', Script::Khudawadi),
+    ('\u{11300}', '\u{11303}', Script::Grantha),
+    ('\u{11305}', '\u{1130C}', Script::Grantha),
+    ('\u{1130F}', '\u{11310}', Script::Grantha),
+    ('\u{11313}', '\u{11328}', Script::Grantha),
+    ('\u{1132A}', '\u{11330}', Script::Grantha),
+    ('\u{11332}', '\u{11333}', Script::Grantha),
+    ('\u{11335}', '\u{11339}', Script::Grantha),
+    ('\u{1133B}', '\u{1133B}', Script::Inherited),
+    ('\u{1133C}', '\u{11344}', Script::Grantha),
+    ('\u{11347}', '\u{11348}', Script::Grantha),
+    ('\u{1134B}', '\u{1134D}', Script::Grantha),
+    ('\u{11350}', '\u{11350}', Script::Grantha),
+    ('\u{11357}', '\u{11357}', Script::Grantha),
+    ('\u{1135D}', '\u{11363}', Script::Grantha),
+    ('\u{11366}', '\u{1136C}', Script::Grantha),
+    ('\u{11370}', '\u{11374}', Script::Grantha),
+    ('\u{11400}', '\u{1145B}', Script::Newa),
+    ('\u{1145D}', '\u{11461}', Script::Newa),
+    ('\u{11480}', '\u{114C7}', Script::Tirhuta),
+    ('\u{114D0}', '\u{114D9}', Script::Tirhuta),
+    ('\u{11580}', '\u{115B5}', Script::Siddham),
+    ('\u{115B8}', '\u{115DD}', Script::Siddham),
+    ('\u{11600}', '\u{11644}', Script::Modi),
+    ('\u{11650}', '\u{11659}', Script::Modi),
+    ('\u{11660}', '\u{1166C}', Script::Mongolian),
+    ('\u{11680}', '\u{116B9}', Script::Takri),
+    ('\u{116C0}', '\u{116C9}', Script::Takri),
+    ('\u{11700}', '\u{1171A}', Script::Ahom),
+    ('\u{1171D}', '\u{1172B}', Script::Ahom),
+    ('\u{11730}', '\u{11746}', Script::Ahom),
+    ('\u{11800}', '\u{1183B}', Script::Dogra),
+    ('\u{118A0}', '\u{118F2}', Script::WarangCiti),
+    ('\u{118FF}', '\u{118FF}', Script::WarangCiti),
+    ('\u{11900}', '\u{11906}', Script::DivesAkuru),
+    ('\u{11909}', '\u{11909}', Script::DivesAkuru),
+    ('\u{1190C}', '\u{11913}', Script::DivesAkuru),
+    ('\u{11915}', '\u{11916}', Script::DivesAkuru),
+    ('\u{11918}', '\u{11935}', Script::DivesAkuru),
+    ('\u{11937}', '\u{11938}', Script::DivesAkuru),
+    ('\u{1193B}', '\u{11946}', Script::DivesAkuru),
+    ('\u{11950}', '\u{11959}', Script::DivesAkuru),
+    ('\u{119A0}', '\u{119A7}', Script::Nandinagari),
+    ('\u{119AA}', '\u{119D7}', Script::Nandinagari),
+    ('\u{119DA}', '\u{119E4}', Script::Nandinagari),
+    ('\u{11A00}', '\u{11A47}', Script::ZanabazarSquare),
+    ('\u{11A50}', '\u{11AA2}', Script::Soyombo),
+    ('\u{11AB0}', '\u{11ABF}', Script::CanadianAboriginal),
+    ('\u{11AC0}', '\u{11AF8}', Script::PauCinHau),
+    ('\u{11B00}', '\u{11B09}', Script::Devanagari),
+    ('\u{11C00}', '\u{11C08}', Script::Bhaiksuki),
+    ('\u{11C0A}', '\u{11C36}', Script::Bhaiksuki),
+    ('\u{11C38}', '\u{11C45}', Script::Bhaiksuki),
+    ('\u{11C50}', '\u{11C6C}', Script::Bhaiksuki),
+    ('\u{11C70}', '\u{11C8F}', Script::Marchen),
+    ('\u{11C92}', '\u{11CA7}', Script::Marchen),
+    ('\u{11CA9}', '\u{11CB6}', Script::Marchen),
+    ('\u{11D00}', '\u{11D06}', Script::MasaramGondi),
+    ('\u{11D08}', '\u{11D09}', Script::MasaramGondi),
+    ('\u{11D0B}', '\u{11D36}', Script::MasaramGondi),
+    ('\u{11D3A}', '\u{11D3A}', Script::MasaramGondi),
+    ('\u{11D3C}', '\u{11D3D}', Script::MasaramGondi),
+    ('\u{11D3F}', '\u{11D47}', Script::MasaramGondi),
+    ('\u{11D50}', '\u{11D59}', Script::MasaramGondi),
+    ('\u{11D60}', '\u{11D65}', Script::GunjalaGondi),
+    ('\u{11D67}', '\u{11D68}', Script::GunjalaGondi),
+    ('\u{11D6A}', '\u{11D8E}', Script::GunjalaGondi),
+    ('\u{11D90}', '\u{11D91}', Script::GunjalaGondi),
+    ('\u{11D93}', '\u{11D98}', Script::GunjalaGondi),
+    ('\u{11DA0}', '\u{11DA9}', Script::GunjalaGondi),
+    ('\u{11EE0}', '\u{11EF8}', Script::Makasar),
+    ('\u{11F00}', '\u{11F10}', Script::Kawi),
+    ('\u{11F12}', '\u{11F3A}', Script::Kawi),
+    ('\u{11F3E}', '\u{11F59}', Script::Kawi),
+    ('\u{11FB0}', '\u{11FB0}', Script::Lisu),
+    ('\u{11FC0}', '\u{11FF1}', Script::Tamil),
+    ('\u{11FFF}', '\u{11FFF}', Script::Tamil),
+    ('\u{12000}', '\u{12399}', Script::Cuneiform),
+    ('\u{12400}', '\u{1246E}', Script::Cuneiform),
+    ('\u{12470}', '\u{12474}', Script::Cuneiform),
+    ('\u{12480}', '\u{12543}', Script::Cuneiform),
+    ('\u{12F90}', '\u{12FF2}', Script::CyproMinoan),
+    ('\u{13000}', '\u{13455}', Script::EgyptianHieroglyphs),
+    ('\u{14400}', '\u{14646}', Script::AnatolianHieroglyphs),
+    ('\u{16800}', '\u{16A38}', Script::Bamum),
+    ('\u{16A40}', '\u{16A5E}', Script::Mro),
+    ('\u{16A60}', '\u{16A69}', Script::Mro),
+    ('\u{16A6E}', '\u{16A6F}', Script::Mro),
+    ('\u{16A70}', '\u{16ABE}', Script::Tangsa),
+    ('\u{16AC0}', '\u{16AC9}', Script::Tangsa),
+    ('\u{16AD0}', '\u{16AED}', Script::BassaVah),
+    ('\u{16AF0}', '\u{16AF5}', Script::BassaVah),
+    ('\u{16B00}', '\u{16B45}', Script::PahawhHmong),
+    ('\u{16B50}', '\u{16B59}', Script::PahawhHmong),
+    ('\u{16B5B}', '\u{16B61}', Script::PahawhHmong),
+    ('\u{16B63}', '\u{16B77}', Script::PahawhHmong),
+    ('\u{16B7D}', '\u{16B8F}', Script::PahawhHmong),
+    ('\u{16E40}', '\u{16E9A}', Script::Medefaidrin),
+    ('\u{16F00}', '\u{16F4A}', Script::Miao),
+    ('\u{16F4F}', '\u{16F87}', Script::Miao),
+    ('\u{16F8F}', '\u{16F9F}', Script::Miao),
+    ('\u{16FE0}', '\u{16FE0}', Script::Tangut),
+    ('\u{16FE1}', '\u{16FE1}', Script::Nushu),
+    ('\u{16FE2}', '\u{16FE3}', Script::Han),
+    ('\u{16FE4}', '\u{16FE4}', Script::KhitanSmallScript),
+    ('\u{16FF0}', '\u{16FF1}', Script::Han),
+    ('\u{17000}', '\u{187F7}', Script::Tangut),
+    ('\u{18800}', '\u{18AFF}', Script::Tangut),
+    ('\u{18B00}', '\u{18CD5}', Script::KhitanSmallScript),
+    ('\u{18D00}', '\u{18D08}', Script::Tangut),
+    ('\u{1AFF0}', '\u{1AFF3}', Script::Katakana),
+    ('\u{1AFF5}', '\u{1AFFB}', Script::Katakana),
+    ('\u{1AFFD}', '\u{1AFFE}', Script::Katakana),
+    ('\u{1B000}', '\u{1B000}', Script::Katakana),
+    ('\u{1B001}', '\u{1B11F}', Script::Hiragana),
+    ('\u{1B120}', '\u{1B122}', Script::Katakana),
+    ('\u{1B132}', '\u{1B132}', Script::Hiragana),
+    ('\u{1B150}', '\u{1B152}', Script::Hiragana),
+    ('\u{1B155}', '\u{1B155}', Script::Katakana),
+    ('\u{1B164}', '\u{1B167}', Script::Katakana),
+    ('\u{1B170}', '\u{1B2FB}', Script::Nushu),
+    ('\u{1BC00}', '\u{1BC6A}', Script::Duployan),
+    ('\u{1BC70}', '\u{1BC7C}', Script::Duployan),
+    ('\u{1BC80}', '\u{1BC88}', Script::Duployan),
+    ('\u{1BC90}', '\u{1BC99}', Script::Duployan),
+    ('\u{1BC9C}', '\u{1BC9F}', Script::Duployan),
+    ('\u{1BCA0}', '\u{1BCA3}', Script::Common),
+    ('\u{1CF00}', '\u{1CF2D}', Script::Inherited),
+    ('\u{1CF30}', '\u{1CF46}', Script::Inherited),
+    ('\u{1CF50}', '\u{1CFC3}', Script::Common),
+    ('\u{1D000}', '\u{1D0F5}', Script::Common),
+    ('\u{1D100}', '\u{1D126}', Script::Common),
+    ('\u{1D129}', '\u{1D166}', Script::Common),
+    ('\u{1D167}', '\u{1D169}', Script::Inherited),
+    ('\u{1D16A}', '\u{1D17A}', Script::Common),
+    ('\u{1D17B}', '\u{1D182}', Script::Inherited),
+    ('\u{1D183}', '\u{1D184}', Script::Common),
+    ('\u{1D185}', '\u{1D18B}', Script::Inherited),
+    ('\u{1D18C}', '\u{1D1A9}', Script::Common),
+    ('\u{1D1AA}', '\u{1D1AD}', Script::Inherited),
+    ('\u{1D1AE}', '\u{1D1EA}', Script::Common),
+    ('\u{1D200}', '\u{1D245}', Script::Greek),
+    ('\u{1D2C0}', '\u{1D2D3}', Script::Common),
+    ('\u{1D2E0}', '\u{1D2F3}', Script::Common),
+    ('\u{1D300}', '\u{1D356}', Script::Common),
+    ('\u{1D360}', '\u{1D378}', Script::Common),
+    ('\u{1D400}', '\u{1D454}', Script::Common),
+    ('\u{1D456}', '\u{1D49C}', Script::Common),
+    ('\u{1D49E}', '\u{1D49F}', Script::Common),
+    ('\u{1D4A2}', '\u{1D4A2}', Script::Common),
+    ('\u{1D4A5}', '\u{1D4A6}', Script::Common),
+    ('\u{1D4A9}', '\u{1D4AC}', Script::Common),
+    ('\u{1D4AE}', '\u{1D4B9}', Script::Common),
+    ('\u{1D4BB}', '\u{1D4BB}', Script::Common),
+    ('\u{1D4BD}', '\u{1D4C3}', Script::Common),
+    ('\u{1D4C5}', '\u{1D505}', Script::Common),
+    ('\u{1D507}', '\u{1D50A}', Script::Common),
+    ('\u{1D50D}', '\u{1D514}', Script::Common),
+    ('\u{1D516}', '\u{1D51C}', Script::Common),
+    ('\u{1D51E}', '\u{1D539}', Script::Common),
+    ('\u{1D53B}', '\u{1D53E}', Script::Common),
+    ('\u{1D540}', '\u{1D544}', Script::Common),
+    ('\u{1D546}', '\u{1D546}', Script::Common),
+    ('\u{1D54A}', '\u{1D550}', Script::Common),
+    ('\u{1D552}', '\u{1D6A5}', Script::Common),
+    ('\u{1D6A8}', '\u{1D7CB}', Script::Common),
+    ('\u{1D7CE}', '\u{1D7FF}', Script::Common),
+    ('\u{1D800}', '\u{1DA8B}', Script::SignWriting),
+    ('\u{1DA9B}', '\u{1DA9F}', Script::SignWriting),
+    ('\u{1DAA1}', '\u{1DAAF}', Script::SignWriting),
+    ('\u{1DF00}', '\u{1DF1E}', Script::Latin),
+    ('\u{1DF25}', '\u{1DF2A}', Script::Latin),
+    ('\u{1E000}', '\u{1E006}', Script::Glagolitic),
+    ('\u{1E008}', '\u{1E018}', Script::Glagolitic),
+    ('\u{1E01B}', '\u{1E021}', Script::Glagolitic),
+    ('\u{1E023}', '\u{1E024}', Script::Glagolitic),
+    ('\u{1E026}', '\u{1E02A}', Script::Glagolitic),
+    ('\u{1E030}', '\u{1E06D}', Script::Cyrillic),
+    ('\u{1E08F}', '\u{1E08F}', Script::Cyrillic),
+    ('\u{1E100}', '\u{1E12C}', Script::NyiakengPuachueHmong),
+    ('\u{1E130}', '\u{1E13D}', Script::NyiakengPuachueHmong),
+    ('\u{1E140}', '\u{1E149}', Script::NyiakengPuachueHmong),
+    ('\u{1E14E}', '\u{1E14F}', Script::NyiakengPuachueHmong),
+    ('\u{1E290}', '\u{1E2AE}', Script::Toto),
+    ('\u{1E2C0}', '\u{1E2F9}', Script::Wancho),
+    ('\u{1E2FF}', '\u{1E2FF}', Script::Wancho),
+    ('\u{1E4D0}', '\u{1E4F9}', Script::NagMundari),
+    ('\u{1E7E0}', '\u{1E7E6}', Script::Ethiopic),
+    ('\u{1E7E8}', '\u{1E7EB}', Script::Ethiopic),
+    ('\u{1E7ED}', '\u{1E7EE}', Script::Ethiopic),
+    ('\u{1E7F0}', '\u{1E7FE}', Script::Ethiopic),
+    ('\u{1E800}', '\u{1E8C4}', Script::MendeKikakui),
+    ('\u{1E8C7}', '\u{1E8D6}', Script::MendeKikakui),
+    ('\u{1E900}', '\u{1E94B}', Script::Adlam),
+    ('\u{1E950}', '\u{1E959}', Script::Adlam),
+    ('\u{1E95E}', '\u{1E95F}', Script::Adlam),
+    ('\u{1EC71}', '\u{1ECB4}', Script::Common),
+    ('\u{1ED01}', '\u{1ED3D}', Script::Common),
+    ('\u{1EE00}', '\u{1EE03}', Script::Arabic),
+    ('\u{1EE05}', '\u{1EE1F}', Script::Arabic),
+    ('\u{1EE21}', '\u{1EE22}', Script::Arabic),
+    ('\u{1EE24}', '\u{1EE24}', Script::Arabic),
+    ('\u{1EE27}', '\u{1EE27}', Script::Arabic),
+    ('\u{1EE29}', '\u{1EE32}', Script::Arabic),
+    ('\u{1EE34}', '\u{1EE37}', Script::Arabic),
+    ('\u{1EE39}', '\u{1EE39}', Script::Arabic),
+    ('\u{1EE3B}', '\u{1EE3B}', Script::Arabic),
+    ('\u{1EE42}', '\u{1EE42}', Script::Arabic),
+    ('\u{1EE47}', '\u{1EE47}', Script::Arabic),
+    ('\u{1EE49}', '\u{1EE49}', Script::Arabic),
+    ('\u{1EE4B}', '\u{1EE4B}', Script::Arabic),
+    ('\u{1EE4D}', '\u{1EE4F}', Script::Arabic),
+    ('\u{1EE51}', '\u{1EE52}', Script::Arabic),
+    ('\u{1EE54}', '\u{1EE54}', Script::Arabic),
+    ('\u{1EE57}', '\u{1EE57}', Script::Arabic),
+    ('\u{1EE59}', '\u{1EE59}', Script::Arabic),
+    ('\u{1EE5B}', '\u{1EE5B}', Script::Arabic),
+    ('\u{1EE5D}', '\u{1EE5D}', Script::Arabic),
+    ('\u{1EE5F}', '\u{1EE5F}', Script::Arabic),
+    ('\u{1EE61}', '\u{1EE62}', Script::Arabic),
+    ('\u{1EE64}', '\u{1EE64}', Script::Arabic),
+    ('\u{1EE67}', '\u{1EE6A}', Script::Arabic),
+    ('\u{1EE6C}', '\u{1EE72}', Script::Arabic),
+    ('\u{1EE74}', '\u{1EE77}', Script::Arabic),
+    ('\u{1EE79}', '\u{1EE7C}', Script::Arabic),
+    ('\u{1EE7E}', '\u{1EE7E}', Script::Arabic),
+    ('\u{1EE80}', '\u{1EE89}', Script::Arabic),
+    ('\u{1EE8B}', '\u{1EE9B}', Script::Arabic),
+    ('\u{1EEA1}', '\u{1EEA3}', Script::Arabic),
+    ('\u{1EEA5}', '\u{1EEA9}', Script::Arabic),
+    ('\u{1EEAB}', '\u{1EEBB}', Script::Arabic),
+    ('\u{1EEF0}', '\u{1EEF1}', Script::Arabic),
+    ('\u{1F000}', '\u{1F02B}', Script::Common),
+    ('\u{1F030}', '\u{1F093}', Script::Common),
+    ('\u{1F0A0}', '\u{1F0AE}', Script::Common),
+    ('\u{1F0B1}', '\u{1F0BF}', Script::Common),
+    ('\u{1F0C1}', '\u{1F0CF}', Script::Common),
+    ('\u{1F0D1}', '\u{1F0F5}', Script::Common),
+    ('\u{1F100}', '\u{1F1AD}', Script::Common),
+    ('\u{1F1E6}', '\u{1F1FF}', Script::Common),
+    ('\u{1F200}', '\u{1F200}', Script::Hiragana),
+    ('\u{1F201}', '\u{1F202}', Script::Common),
+    ('\u{1F210}', '\u{1F23B}', Script::Common),
+    ('\u{1F240}', '\u{1F248}', Script::Common),
+    ('\u{1F250}', '\u{1F251}', Script::Common),
+    ('\u{1F260}', '\u{1F265}', Script::Common),
+    ('\u{1F300}', '\u{1F6D7}', Script::Common),
+    ('\u{1F6DC}', '\u{1F6EC}', Script::Common),
+    ('\u{1F6F0}', '\u{1F6FC}', Script::Common),
+    ('\u{1F700}', '\u{1F776}', Script::Common),
+    ('\u{1F77B}', '\u{1F7D9}', Script::Common),
+    ('\u{1F7E0}', '\u{1F7EB}', Script::Common),
+    ('\u{1F7F0}', '\u{1F7F0}', Script::Common),
+    ('\u{1F800}', '\u{1F80B}', Script::Common),
+    ('\u{1F810}', '\u{1F847}', Script::Common),
+    ('\u{1F850}', '\u{1F859}', Script::Common),
+    ('\u{1F860}', '\u{1F887}', Script::Common),
+    ('\u{1F890}', '\u{1F8AD}', Script::Common),
+    ('\u{1F8B0}', '\u{1F8B1}', Script::Common),
+    ('\u{1F900}', '\u{1FA53}', Script::Common),
+    ('\u{1FA60}', '\u{1FA6D}', Script::Common),
+    ('\u{1FA70}', '\u{1FA7C}', Script::Common),
+    ('\u{1FA80}', '\u{1FA88}', Script::Common),
+    ('\u{1FA90}', '\u{1FABD}', Script::Common),
+    ('\u{1FABF}', '\u{1FAC5}', Script::Common),
+    ('\u{1FACE}', '\u{1FADB}', Script::Common),
+    ('\u{1FAE0}', '\u{1FAE8}', Script::Common),
+    ('\u{1FAF0}', '\u{1FAF8}', Script::Common),
+    ('\u{1FB00}', '\u{1FB92}', Script::Common),
+    ('\u{1FB94}', '\u{1FBCA}', Script::Common),
+    ('\u{1FBF0}', '\u{1FBF9}', Script::Common),
+    ('\u{20000}', '\u{2A6DF}', Script::Han),
+    ('\u{2A700}', '\u{2B739}', Script::Han),
+    ('\u{2B740}', '\u{2B81D}', Script::Han),
+    ('\u{2B820}', '\u{2CEA1}', Script::Han),
+    ('\u{2CEB0}', '\u{2EBE0}', Script::Han),
+    ('\u{2F800}', '\u{2FA1D}', Script::Han),
+    ('\u{30000}', '\u{3134A}', Script::Han),
+    ('\u{31350}', '\u{323AF}', Script::Han),
+    ('\u{E0001}', '\u{E0001}', Script::Common),
+    ('\u{E0020}', '\u{E007F}', Script::Common),
+    ('\u{E0100}', '\u{E01EF}', Script::Inherited),
+];
+
+/// Ranges of characters whose `Script_Extensions` differ from their `Script`, sorted by their
+/// first character.
+pub(crate) const SCRIPT_EXTENSIONS: &[(char, char, &[Script])] = &[
+    ('\u{342}', '\u{342}', &[Script::Greek]),
+    ('\u{345}', '\u{345}', &[Script::Greek]),
+    ('\u{363}', '\u{36F}', &[Script::Latin]),
+    ('\u{483}', '\u{483}', &[Script::Cyrillic, Script::OldPermic]),
+    (
+        '\u{484}',
+        '\u{484}',
+        &[Script::Cyrillic, Script::Glagolitic],
+    ),
+    ('\u{485}', '\u{486}', &[Script::Cyrillic, Script::Latin]),
+    (
+        '\u{487}',
+        '\u{487}',
+        &[Script::Cyrillic, Script::Glagolitic],
+    ),
+    (
+        '\u{60C}',
+        '\u{60C}',
+        &[
+            Script::Arabic,
+            Script::Nko,
+            Script::HanifiRohingya,
+            Script::Syriac,
+            Script::Thaana,
+            Script::Yezidi,
+        ],
+    ),
+    (
+        '\u{61B}',
+        '\u{61B}',
+        &[
+            Script::Arabic,
+            Script::Nko,
+            Script::HanifiRohingya,
+            Script::Syriac,
+            Script::Thaana,
+            Script::Yezidi,
+        ],
+    ),
+    (
+        '\u{61C}',
+        '\u{61C}',
+        &[Script::Arabic, Script::Syriac, Script::Thaana],
+    ),
+    (
+        '\u{61F}',
+        '\u{61F}',
+        &[
+            Script::Adlam,
+            Script::Arabic,
+            Script::Nko,
+            Script::HanifiRohingya,
+            Script::Syriac,
+            Script::Thaana,
+            Script::Yezidi,
+        ],
+    ),
+    (
+        '\u{640}',
+        '\u{640}',
+        &[
+            Script::Adlam,
+            Script::Arabic,
+            Script::Mandaic,
+            Script::Manichaean,
+            Script::OldUyghur,
+            Script::PsalterPahlavi,
+            Script::HanifiRohingya,
+            Script::Sogdian,
+            Script::Syriac,
+        ],
+    ),
+    ('\u{64B}', '\u{655}', &[Script::Arabic, Script::Syriac]),
+    (
+        '\u{660}',
+        '\u{669}',
+        &[Script::Arabic, Script::Thaana, Script::Yezidi],
+    ),
+    ('\u{670}', '\u{670}', &[Script::Arabic, Script::Syriac]),
+    (
+        '\u{6D4}',
+        '\u{6D4}',
+        &[Script::Arabic, Script::HanifiRohingya],
+    ),
+    (
+        '\u{951}',
+        '\u{951}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Grantha,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Kannada,
+            Script::Latin,
+            Script::Malayalam,
+            Script::Oriya,
+            Script::Sharada,
+            Script::Tamil,
+            Script::Telugu,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{952}',
+        '\u{952}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Grantha,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Kannada,
+            Script::Latin,
+            Script::Malayalam,
+            Script::Oriya,
+            Script::Tamil,
+            Script::Telugu,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{964}',
+        '\u{964}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Dogra,
+            Script::GunjalaGondi,
+            Script::MasaramGondi,
+            Script::Grantha,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Kannada,
+            Script::Mahajani,
+            Script::Malayalam,
+            Script::Nandinagari,
+            Script::Oriya,
+            Script::Khudawadi,
+            Script::Sinhala,
+            Script::SylotiNagri,
+            Script::Takri,
+            Script::Tamil,
+            Script::Telugu,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{965}',
+        '\u{965}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Dogra,
+            Script::GunjalaGondi,
+            Script::MasaramGondi,
+            Script::Grantha,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Kannada,
+            Script::Limbu,
+            Script::Mahajani,
+            Script::Malayalam,
+            Script::Nandinagari,
+            Script::Oriya,
+            Script::Khudawadi,
+            Script::Sinhala,
+            Script::SylotiNagri,
+            Script::Takri,
+            Script::Tamil,
+            Script::Telugu,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{966}',
+        '\u{96F}',
+        &[
+            Script::Devanagari,
+            Script::Dogra,
+            Script::Kaithi,
+            Script::Mahajani,
+        ],
+    ),
+    (
+        '\u{9E6}',
+        '\u{9EF}',
+        &[Script::Bengali, Script::Chakma, Script::SylotiNagri],
+    ),
+    ('\u{A66}', '\u{A6F}', &[Script::Gurmukhi, Script::Multani]),
+    ('\u{AE6}', '\u{AEF}', &[Script::Gujarati, Script::Khojki]),
+    ('\u{BE6}', '\u{BF3}', &[Script::Grantha, Script::Tamil]),
+    (
+        '\u{CE6}',
+        '\u{CEF}',
+        &[Script::Kannada, Script::Nandinagari],
+    ),
+    (
+        '\u{1040}',
+        '\u{1049}',
+        &[Script::Chakma, Script::Myanmar, Script::TaiLe],
+    ),
+    ('\u{10FB}', '\u{10FB}', &[Script::Georgian, Script::Latin]),
+    (
+        '\u{1735}',
+        '\u{1736}',
+        &[
+            Script::Buhid,
+            Script::Hanunoo,
+            Script::Tagbanwa,
+            Script::Tagalog,
+        ],
+    ),
+    (
+        '\u{1802}',
+        '\u{1803}',
+        &[Script::Mongolian, Script::PhagsPa],
+    ),
+    (
+        '\u{1805}',
+        '\u{1805}',
+        &[Script::Mongolian, Script::PhagsPa],
+    ),
+    (
+        '\u{1CD0}',
+        '\u{1CD0}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Grantha,
+            Script::Kannada,
+        ],
+    ),
+    ('\u{1CD1}', '\u{1CD1}', &[Script::Devanagari]),
+    (
+        '\u{1CD2}',
+        '\u{1CD2}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Grantha,
+            Script::Kannada,
+        ],
+    ),
+    (
+        '\u{1CD3}',
+        '\u{1CD3}',
+        &[Script::Devanagari, Script::Grantha],
+    ),
+    ('\u{1CD4}', '\u{1CD4}', &[Script::Devanagari]),
+    (
+        '\u{1CD5}',
+        '\u{1CD6}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    (
+        '\u{1CD7}',
+        '\u{1CD7}',
+        &[Script::Devanagari, Script::Sharada],
+    ),
+    (
+        '\u{1CD8}',
+        '\u{1CD8}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    (
+        '\u{1CD9}',
+        '\u{1CD9}',
+        &[Script::Devanagari, Script::Sharada],
+    ),
+    (
+        '\u{1CDA}',
+        '\u{1CDA}',
+        &[
+            Script::Devanagari,
+            Script::Kannada,
+            Script::Malayalam,
+            Script::Oriya,
+            Script::Tamil,
+            Script::Telugu,
+        ],
+    ),
+    ('\u{1CDB}', '\u{1CDB}', &[Script::Devanagari]),
+    (
+        '\u{1CDC}',
+        '\u{1CDD}',
+        &[Script::Devanagari, Script::Sharada],
+    ),
+    ('\u{1CDE}', '\u{1CDF}', &[Script::Devanagari]),
+    (
+        '\u{1CE0}',
+        '\u{1CE0}',
+        &[Script::Devanagari, Script::Sharada],
+    ),
+    (
+        '\u{1CE1}',
+        '\u{1CE1}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    ('\u{1CE2}', '\u{1CE8}', &[Script::Devanagari]),
+    (
+        '\u{1CE9}',
+        '\u{1CE9}',
+        &[Script::Devanagari, Script::Nandinagari],
+    ),
+    (
+        '\u{1CEA}',
+        '\u{1CEA}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    ('\u{1CEB}', '\u{1CEC}', &[Script::Devanagari]),
+    (
+        '\u{1CED}',
+        '\u{1CED}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    ('\u{1CEE}', '\u{1CF1}', &[Script::Devanagari]),
+    (
+        '\u{1CF2}',
+        '\u{1CF2}',
+        &[
+            Script::Bengali,
+            Script::Devanagari,
+            Script::Grantha,
+            Script::Kannada,
+            Script::Nandinagari,
+            Script::Oriya,
+            Script::Telugu,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{1CF3}',
+        '\u{1CF3}',
+        &[Script::Devanagari, Script::Grantha],
+    ),
+    (
+        '\u{1CF4}',
+        '\u{1CF4}',
+        &[Script::Devanagari, Script::Grantha, Script::Kannada],
+    ),
+    (
+        '\u{1CF5}',
+        '\u{1CF6}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    ('\u{1CF7}', '\u{1CF7}', &[Script::Bengali]),
+    (
+        '\u{1CF8}',
+        '\u{1CF9}',
+        &[Script::Devanagari, Script::Grantha],
+    ),
+    ('\u{1CFA}', '\u{1CFA}', &[Script::Nandinagari]),
+    ('\u{1DC0}', '\u{1DC1}', &[Script::Greek]),
+    ('\u{1DF8}', '\u{1DF8}', &[Script::Cyrillic, Script::Syriac]),
+    ('\u{1DFA}', '\u{1DFA}', &[Script::Syriac]),
+    ('\u{202F}', '\u{202F}', &[Script::Latin, Script::Mongolian]),
+    (
+        '\u{20F0}',
+        '\u{20F0}',
+        &[Script::Devanagari, Script::Grantha, Script::Latin],
+    ),
+    (
+        '\u{2E43}',
+        '\u{2E43}',
+        &[Script::Cyrillic, Script::Glagolitic],
+    ),
+    (
+        '\u{3001}',
+        '\u{3002}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Yi,
+        ],
+    ),
+    (
+        '\u{3003}',
+        '\u{3003}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    ('\u{3006}', '\u{3006}', &[Script::Han]),
+    (
+        '\u{3008}',
+        '\u{3011}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Yi,
+        ],
+    ),
+    (
+        '\u{3013}',
+        '\u{3013}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    (
+        '\u{3014}',
+        '\u{301B}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Yi,
+        ],
+    ),
+    (
+        '\u{301C}',
+        '\u{301F}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    ('\u{302A}', '\u{302D}', &[Script::Bopomofo, Script::Han]),
+    (
+        '\u{3030}',
+        '\u{3030}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    (
+        '\u{3031}',
+        '\u{3035}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    (
+        '\u{3037}',
+        '\u{3037}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    (
+        '\u{303C}',
+        '\u{303D}',
+        &[Script::Han, Script::Hiragana, Script::Katakana],
+    ),
+    ('\u{303E}', '\u{303F}', &[Script::Han]),
+    (
+        '\u{3099}',
+        '\u{309C}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    (
+        '\u{30A0}',
+        '\u{30A0}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    (
+        '\u{30FB}',
+        '\u{30FB}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Yi,
+        ],
+    ),
+    (
+        '\u{30FC}',
+        '\u{30FC}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    ('\u{3190}', '\u{319F}', &[Script::Han]),
+    ('\u{31C0}', '\u{31E3}', &[Script::Han]),
+    ('\u{3220}', '\u{3247}', &[Script::Han]),
+    ('\u{3280}', '\u{32B0}', &[Script::Han]),
+    ('\u{32C0}', '\u{32CB}', &[Script::Han]),
+    ('\u{32FF}', '\u{32FF}', &[Script::Han]),
+    ('\u{3358}', '\u{3370}', &[Script::Han]),
+    ('\u{337B}', '\u{337F}', &[Script::Han]),
+    ('\u{33E0}', '\u{33FE}', &[Script::Han]),
+    (
+        '\u{A66F}',
+        '\u{A66F}',
+        &[Script::Cyrillic, Script::Glagolitic],
+    ),
+    ('\u{A700}', '\u{A707}', &[Script::Han, Script::Latin]),
+    (
+        '\u{A830}',
+        '\u{A832}',
+        &[
+            Script::Devanagari,
+            Script::Dogra,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Khojki,
+            Script::Kannada,
+            Script::Kaithi,
+            Script::Mahajani,
+            Script::Malayalam,
+            Script::Modi,
+            Script::Nandinagari,
+            Script::Khudawadi,
+            Script::Takri,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{A833}',
+        '\u{A835}',
+        &[
+            Script::Devanagari,
+            Script::Dogra,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Khojki,
+            Script::Kannada,
+            Script::Kaithi,
+            Script::Mahajani,
+            Script::Modi,
+            Script::Nandinagari,
+            Script::Khudawadi,
+            Script::Takri,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{A836}',
+        '\u{A839}',
+        &[
+            Script::Devanagari,
+            Script::Dogra,
+            Script::Gujarati,
+            Script::Gurmukhi,
+            Script::Khojki,
+            Script::Kaithi,
+            Script::Mahajani,
+            Script::Modi,
+            Script::Khudawadi,
+            Script::Takri,
+            Script::Tirhuta,
+        ],
+    ),
+    (
+        '\u{A8F1}',
+        '\u{A8F1}',
+        &[Script::Bengali, Script::Devanagari],
+    ),
+    ('\u{A8F3}', '\u{A8F3}', &[Script::Devanagari, Script::Tamil]),
+    (
+        '\u{A92E}',
+        '\u{A92E}',
+        &[Script::KayahLi, Script::Latin, Script::Myanmar],
+    ),
+    (
+        '\u{A9CF}',
+        '\u{A9CF}',
+        &[Script::Buginese, Script::Javanese],
+    ),
+    ('\u{FD3E}', '\u{FD3F}', &[Script::Arabic, Script::Nko]),
+    ('\u{FDF2}', '\u{FDF2}', &[Script::Arabic, Script::Thaana]),
+    ('\u{FDFD}', '\u{FDFD}', &[Script::Arabic, Script::Thaana]),
+    (
+        '\u{FE45}',
+        '\u{FE46}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+    ),
+    (
+        '\u{FF61}',
+        '\u{FF65}',
+        &[
+            Script::Bopomofo,
+            Script::Hangul,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Yi,
+        ],
+    ),
+    (
+        '\u{FF70}',
+        '\u{FF70}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    (
+        '\u{FF9E}',
+        '\u{FF9F}',
+        &[Script::Hiragana, Script::Katakana],
+    ),
+    (
+        '\u{10100}',
+        '\u{10101}',
+        &[Script::CyproMinoan, Script::Cypriot, Script::LinearB],
+    ),
+    (
+        '\u{10102}',
+        '\u{10102}',
+        &[Script::Cypriot, Script::LinearB],
+    ),
+    (
+        '\u{10107}',
+        '\u{10133}',
+        &[Script::Cypriot, Script::LinearA, Script::LinearB],
+    ),
+    (
+        '\u{10137}',
+        '\u{1013F}',
+        &[Script::Cypriot, Script::LinearB],
+    ),
+    ('\u{102E0}', '\u{102FB}', &[Script::Arabic, Script::Coptic]),
+    (
+        '\u{10AF2}',
+        '\u{10AF2}',
+        &[Script::Manichaean, Script::OldUyghur],
+    ),
+    ('\u{11301}', '\u{11301}', &[Script::Grantha, Script::Tamil]),
+    ('\u{11303}', '\u{11303}', &[Script::Grantha, Script::Tamil]),
+    ('\u{1133B}', '\u{1133C}', &[Script::Grantha, Script::Tamil]),
+    ('\u{11FD0}', '\u{11FD1}', &[Script::Grantha, Script::Tamil]),
+    ('\u{11FD3}', '\u{11FD3}', &[Script::Grantha, Script::Tamil]),
+    ('\u{1BCA0}', '\u{1BCA3}', &[Script::Duployan]),
+    ('\u{1D360}', '\u{1D371}', &[Script::Han]),
+    ('\u{1F250}', '\u{1F251}', &[Script::Han]),
+];
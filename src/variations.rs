@@ -16,6 +16,8 @@ use crate::binary::read::{ReadArrayCow, ReadScope};
 use crate::cff::cff2::CFF2;
 use crate::cff::CFFError;
 use crate::error::{ParseError, ReadWriteError, WriteError};
+use crate::layout::subset::limit_layout_tables;
+use crate::layout::GDEFTable;
 use crate::post::PostTable;
 use crate::subset::FontBuilder;
 use crate::tables::glyf::{BoundingBox, GlyfRecord, GlyfTable, Glyph};
@@ -28,10 +30,11 @@ use crate::tables::variable_fonts::gvar::GvarTable;
 use crate::tables::variable_fonts::hvar::HvarTable;
 use crate::tables::variable_fonts::mvar::MvarTable;
 use crate::tables::variable_fonts::stat::{ElidableName, StatTable};
-use crate::tables::variable_fonts::OwnedTuple;
+use crate::tables::variable_fonts::vvar::VvarTable;
+use crate::tables::variable_fonts::{NormalizedAxisLimit, OwnedTuple};
 use crate::tables::{
-    owned, CvtTable, Fixed, FontTableProvider, HeadTable, HheaTable, HmtxTable, IndexToLocFormat,
-    LongHorMetric, MacStyle, MaxpTable, NameTable, CFF_MAGIC, TRUE_MAGIC,
+    owned, CvtTable, F2Dot14, Fixed, FontTableProvider, HeadTable, HheaTable, HmtxTable,
+    IndexToLocFormat, LongHorMetric, MacStyle, MaxpTable, NameTable, CFF_MAGIC, TRUE_MAGIC,
 };
 use crate::tag;
 use crate::tag::DisplayTag;
@@ -57,6 +60,23 @@ pub enum VariationError {
     NameError,
     /// The list of table tags was unable to be retrieved from the font.
     TagError,
+    /// An axis limit for partial instancing does not include the default value
+    /// of the axis.
+    AxisLimit,
+}
+
+/// The limit placed on a variation axis when creating a partial instance of a
+/// variable font with [partial_instance].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AxisLimit {
+    /// Keep the full range of the axis.
+    Full,
+    /// Pin the axis to a value, removing it from the font.
+    Pin(Fixed),
+    /// Limit the axis to the range from the first value to the second.
+    ///
+    /// The range must include the default value of the axis.
+    Range(Fixed, Fixed),
 }
 
 enum GlyphData<'a> {
@@ -254,19 +274,7 @@ pub fn instance(
             )?;
 
             // Update head
-            let mut bbox = RectI::default();
-            glyf.records().iter().for_each(|glyph| match glyph {
-                GlyfRecord::Present { .. } => {}
-                GlyfRecord::Parsed(glyph) => {
-                    if let Some(bounding_box) = glyph.bounding_box() {
-                        bbox = union_rect(bbox, bounding_box.into())
-                    }
-                }
-            });
-            head.x_min = bbox.min_x().try_into().ok().unwrap_or(i16::MIN);
-            head.y_min = bbox.min_y().try_into().ok().unwrap_or(i16::MIN);
-            head.x_max = bbox.max_x().try_into().ok().unwrap_or(i16::MAX);
-            head.y_max = bbox.max_y().try_into().ok().unwrap_or(i16::MAX);
+            update_head_bounding_box(&mut head, &glyf);
 
             // Build new hmtx table
             let hmtx = create_hmtx_table(&hmtx, hvar.as_ref(), &glyf, &instance, maxp.num_glyphs)?;
@@ -294,11 +302,6 @@ pub fn instance(
         _ => return Err(VariationError::NotImplemented),
     };
 
-    // Update italic flags
-    head.mac_style
-        .set(MacStyle::ITALIC, is_italic(user_instance, &fvar));
-    os2.fs_selection.set(FsSelection::ITALIC, head.is_italic());
-
     // Update hhea
     hhea.num_h_metrics = maxp.num_glyphs; // there's now metrics for each glyph
     hhea.advance_width_max = hmtx
@@ -313,36 +316,7 @@ pub fn instance(
         process_mvar(mvar, &instance, &mut os2, &mut hhea, &mut None, &mut post);
     }
 
-    // If one of the axes is wght or wdth then when need to update the corresponding
-    // fields in OS/2
-    for (axis, value) in fvar.axes().zip(user_instance.iter().copied()) {
-        if value == axis.default_value {
-            continue;
-        }
-
-        match axis.axis_tag {
-            tag::WGHT => {
-                // Map the value to one of the weight classes. Weight can be 1 to 1000 but
-                // weight classes are only defined for 100, 200, 300... 900.
-                os2.us_weight_class = ((f32::from(value).clamp(1., 1000.) / 100.0).round() as u16
-                    * 100)
-                    .clamp(100, 900);
-                head.mac_style
-                    .set(MacStyle::BOLD, os2.us_weight_class >= 600);
-                os2.fs_selection.set(FsSelection::BOLD, head.is_bold());
-            }
-            tag::WDTH => {
-                os2.us_width_class = Os2::value_to_width_class(value);
-                head.mac_style
-                    .set(MacStyle::CONDENSED, os2.us_width_class < 4);
-                head.mac_style
-                    .set(MacStyle::EXTENDED, os2.us_width_class > 6);
-            }
-            _ => {}
-        }
-    }
-    os2.fs_selection
-        .set(FsSelection::REGULAR, !(head.is_bold() || head.is_italic()));
+    update_style(user_instance, &fvar, &mut head, &mut os2);
 
     if let (Some(cvt), Some(cvar)) = (cvt.as_mut(), cvar) {
         *cvt = cvar.apply(&instance, cvt)?;
@@ -439,6 +413,327 @@ pub fn instance(
         .map_err(VariationError::from)
 }
 
+/// Create a partial instance of a variable font, limiting its variation axes
+/// to `limits`, one for each axis in the `fvar` table.
+///
+/// Pinned axes are removed from the font, and the deltas that apply at the
+/// pinned values are applied to the outlines and metrics. The remaining axes
+/// are limited to their new ranges, and the variations of the font are
+/// renormalized to them, so that the font is still variable along these axes.
+/// Named instances outside of the new variation space are removed. If every
+/// axis is pinned the result is the same as [instance].
+///
+/// The variation data in the `GDEF`, `GSUB`, and `GPOS` tables is renormalized
+/// too, and axis values of the `STAT` table outside of the new ranges are
+/// removed.
+///
+/// Only TrueType fonts with a `gvar` table are supported. If the font is a
+/// CFF2 font, or lacks a `gvar` table, and isn't pinned on every axis then
+/// [VariationError::NotImplemented] is returned. It's also returned when the
+/// `GDEF` table has an item variation store and an axis is pinned to a value
+/// other than its default, since the deltas at the new default would need to be
+/// applied to the `GPOS` table.
+pub fn partial_instance(
+    provider: &impl FontTableProvider,
+    limits: &[AxisLimit],
+) -> Result<Vec<u8>, VariationError> {
+    is_supported_variable_font(provider)?;
+
+    let fvar_data = provider.read_table_data(tag::FVAR)?;
+    let fvar = ReadScope::new(&fvar_data).read::<FvarTable<'_>>()?;
+    if limits.len() != usize::from(fvar.axis_count()) {
+        return Err(ParseError::BadValue.into());
+    }
+
+    // Resolve the limits to the new minimum and maximum of each axis. Pinned axes have the same
+    // minimum and maximum.
+    let user_limits = fvar
+        .axes()
+        .zip(limits.iter())
+        .map(|(axis, limit)| match *limit {
+            AxisLimit::Full => Ok((axis.min_value, axis.max_value)),
+            AxisLimit::Pin(value) => {
+                let value = value.clamp(axis.min_value, axis.max_value);
+                Ok((value, value))
+            }
+            AxisLimit::Range(min, max) => {
+                let min = min.max(axis.min_value);
+                let max = max.min(axis.max_value);
+                if min <= axis.default_value && axis.default_value <= max {
+                    Ok((min, max))
+                } else {
+                    Err(VariationError::AxisLimit)
+                }
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if user_limits.iter().all(|(min, max)| min == max) {
+        let user_instance = user_limits
+            .iter()
+            .map(|&(value, _)| value)
+            .collect::<Vec<_>>();
+        return instance(provider, &user_instance).map(|(data, _instance)| data);
+    }
+
+    let mut head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
+    let maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
+    let (Some(loca_data), Some(glyf_data), Some(gvar_data)) = (
+        provider.table_data(tag::LOCA)?,
+        provider.table_data(tag::GLYF)?,
+        provider.table_data(tag::GVAR)?,
+    ) else {
+        return Err(VariationError::NotImplemented);
+    };
+    let loca = ReadScope::new(&loca_data)
+        .read_dep::<LocaTable<'_>>((usize::from(maxp.num_glyphs), head.index_to_loc_format))?;
+    let glyf = ReadScope::new(&glyf_data).read_dep::<GlyfTable<'_>>(&loca)?;
+    let gvar = ReadScope::new(&gvar_data).read::<GvarTable<'_>>()?;
+    let mut hhea = ReadScope::new(&provider.read_table_data(tag::HHEA)?).read::<HheaTable>()?;
+    let hmtx_data = provider.read_table_data(tag::HMTX)?;
+    let hmtx = ReadScope::new(&hmtx_data).read_dep::<HmtxTable<'_>>((
+        usize::from(maxp.num_glyphs),
+        usize::from(hhea.num_h_metrics),
+    ))?;
+    let vhea_data = provider.table_data(tag::VHEA)?;
+    let mut vhea = vhea_data
+        .as_ref()
+        .map(|vhea_data| ReadScope::new(vhea_data).read::<HheaTable>())
+        .transpose()?;
+    let vmtx_data = provider.table_data(tag::VMTX)?;
+    let vmtx = vhea
+        .as_ref()
+        .and_then(|vhea| {
+            vmtx_data.as_ref().map(|vmtx_data| {
+                ReadScope::new(vmtx_data).read_dep::<HmtxTable<'_>>((
+                    usize::from(maxp.num_glyphs),
+                    usize::from(vhea.num_h_metrics),
+                ))
+            })
+        })
+        .transpose()?;
+    let os2_data = provider.read_table_data(tag::OS_2)?;
+    let mut os2 = ReadScope::new(&os2_data).read_dep::<Os2>(os2_data.len())?;
+    let post_data = provider.read_table_data(tag::POST)?;
+    let mut post = ReadScope::new(&post_data).read::<PostTable<'_>>()?;
+    let avar_data = provider.table_data(tag::AVAR)?;
+    let avar = avar_data
+        .as_ref()
+        .map(|avar_data| ReadScope::new(avar_data).read::<AvarTable<'_>>())
+        .transpose()?;
+    let cvt_data = provider.table_data(tag::CVT)?;
+    let mut cvt = cvt_data
+        .as_ref()
+        .map(|cvt_data| ReadScope::new(cvt_data).read_dep::<CvtTable<'_>>(cvt_data.len() as u32))
+        .transpose()?;
+    let cvar_data = provider.table_data(tag::CVAR)?;
+    let cvar = cvt
+        .as_ref()
+        .and_then(|cvt| {
+            cvar_data.as_ref().map(|cvar_data| {
+                ReadScope::new(cvar_data)
+                    .read_dep::<CvarTable<'_>>((fvar.axis_count(), cvt.values.len() as u32))
+            })
+        })
+        .transpose()?;
+    let hvar_data = provider.table_data(tag::HVAR)?;
+    let hvar = hvar_data
+        .as_ref()
+        .map(|hvar_data| ReadScope::new(hvar_data).read::<HvarTable<'_>>())
+        .transpose()?;
+    let vvar_data = provider.table_data(tag::VVAR)?;
+    let vvar = vvar_data
+        .as_ref()
+        .map(|vvar_data| ReadScope::new(vvar_data).read::<VvarTable<'_>>())
+        .transpose()?;
+    let mvar_data = provider.table_data(tag::MVAR)?;
+    let mvar = mvar_data
+        .as_ref()
+        .map(|mvar_data| ReadScope::new(mvar_data).read::<MvarTable<'_>>())
+        .transpose()?;
+    let gdef_data = provider.table_data(tag::GDEF)?;
+    let gdef = gdef_data
+        .as_ref()
+        .map(|gdef_data| ReadScope::new(gdef_data).read::<GDEFTable>())
+        .transpose()?;
+    let stat_data = provider.table_data(tag::STAT)?;
+    let stat = stat_data
+        .as_ref()
+        .map(|stat_data| ReadScope::new(stat_data).read::<StatTable<'_>>())
+        .transpose()?;
+
+    // The location of the new default in the variation space of the font: the pinned value on
+    // pinned axes and the default on the others.
+    let user_instance = fvar
+        .axes()
+        .zip(user_limits.iter())
+        .map(|(axis, &(min, max))| if min == max { min } else { axis.default_value })
+        .collect::<Vec<_>>();
+    let instance = fvar.normalize(user_instance.iter().copied(), avar.as_ref())?;
+    let mins = fvar.normalize(user_limits.iter().map(|&(min, _)| min), avar.as_ref())?;
+    let maxs = fvar.normalize(user_limits.iter().map(|&(_, max)| max), avar.as_ref())?;
+    let normalized_limits = user_limits
+        .iter()
+        .enumerate()
+        .map(|(i, &(min, max))| {
+            if min == max {
+                NormalizedAxisLimit::Pin(instance[i])
+            } else {
+                NormalizedAxisLimit::Range(mins[i], maxs[i])
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Build the new variation tables. The gvar table needs the outlines from before the deltas
+    // at the new default are applied.
+    let bake = instance.iter().any(|&value| value != F2Dot14::from(0));
+    let has_gdef_variations = gdef
+        .as_ref()
+        .and_then(|gdef| gdef.opt_item_variation_store.as_ref())
+        .is_some();
+    if bake && has_gdef_variations {
+        return Err(VariationError::NotImplemented);
+    }
+    let new_gvar = gvar.limit_axes(&glyf, &normalized_limits, bake)?;
+    let new_fvar = fvar.limit_axes(&user_limits)?;
+    let new_avar = avar
+        .as_ref()
+        .map(|avar| {
+            let default_mins = fvar.normalize(user_limits.iter().map(|&(min, _)| min), None)?;
+            let default_maxs = fvar.normalize(user_limits.iter().map(|&(_, max)| max), None)?;
+            let limits = user_limits
+                .iter()
+                .enumerate()
+                .map(|(i, &(min, max))| (min != max).then(|| (default_mins[i], default_maxs[i])))
+                .collect::<Vec<_>>();
+            avar.limit_axes(&limits).map_err(ReadWriteError::from)
+        })
+        .transpose()?;
+    let new_cvar = cvt
+        .as_ref()
+        .zip(cvar.as_ref())
+        .map(|(cvt, cvar)| cvar.limit_axes(cvt.values.len() as u32, &normalized_limits))
+        .transpose()?;
+    let new_hvar = hvar
+        .as_ref()
+        .map(|hvar| hvar.limit_axes(&normalized_limits))
+        .transpose()?;
+    let new_vvar = vvar
+        .as_ref()
+        .map(|vvar| vvar.limit_axes(&normalized_limits))
+        .transpose()?;
+    let new_mvar = mvar
+        .as_ref()
+        .map(|mvar| mvar.limit_axes(&normalized_limits))
+        .transpose()?;
+    let new_layout_tables = limit_layout_tables(provider, maxp.num_glyphs, &normalized_limits)?;
+    let new_stat = stat
+        .as_ref()
+        .map(|stat| {
+            let limits = fvar
+                .axes()
+                .zip(user_limits.iter())
+                .map(|(axis, &(min, max))| (axis.axis_tag, min, max))
+                .collect::<Vec<_>>();
+            stat.limit_axes(&limits)
+        })
+        .transpose()?;
+
+    // Apply the deltas at the new default to the glyphs and metrics
+    let glyf = apply_gvar(
+        glyf,
+        &gvar,
+        &hmtx,
+        vmtx.as_ref(),
+        Some(&os2),
+        &hhea,
+        &instance,
+    )?;
+    update_head_bounding_box(&mut head, &glyf);
+    let hmtx = create_hmtx_table(&hmtx, hvar.as_ref(), &glyf, &instance, maxp.num_glyphs)?;
+    hhea.num_h_metrics = maxp.num_glyphs; // there's now metrics for each glyph
+    hhea.advance_width_max = hmtx
+        .h_metrics
+        .iter()
+        .map(|m| m.advance_width)
+        .max()
+        .unwrap_or(0);
+    let vmtx = match (vmtx, &vvar) {
+        (Some(vmtx), Some(vvar)) => {
+            let vmtx = apply_vvar(&vmtx, vvar, &instance, maxp.num_glyphs)?;
+            if let Some(vhea) = vhea.as_mut() {
+                vhea.num_h_metrics = maxp.num_glyphs;
+                vhea.advance_width_max = vmtx
+                    .h_metrics
+                    .iter()
+                    .map(|m| m.advance_width)
+                    .max()
+                    .unwrap_or(0);
+            }
+            Some(vmtx)
+        }
+        (vmtx, _) => vmtx,
+    };
+    if let Some(mvar) = &mvar {
+        process_mvar(mvar, &instance, &mut os2, &mut hhea, &mut vhea, &mut post);
+    }
+    update_style(&user_instance, &fvar, &mut head, &mut os2);
+    if let (Some(cvt), Some(cvar)) = (cvt.as_mut(), cvar) {
+        *cvt = cvar.apply(&instance, cvt)?;
+    }
+
+    // Build the new font
+    let mut builder = FontBuilder::new(TRUE_MAGIC);
+    if let Some(cvt) = cvt {
+        builder.add_table::<_, CvtTable<'_>>(tag::CVT, &cvt, ())?;
+    }
+    builder.add_table::<_, HheaTable>(tag::HHEA, &hhea, ())?;
+    builder.add_table::<_, HmtxTable<'_>>(tag::HMTX, &hmtx, ())?;
+    builder.add_table::<_, MaxpTable>(tag::MAXP, &maxp, ())?;
+    builder.add_table::<_, Os2>(tag::OS_2, &os2, ())?;
+    builder.add_table::<_, PostTable<'_>>(tag::POST, &post, ())?;
+    if let (Some(vhea), Some(vmtx)) = (&vhea, &vmtx) {
+        builder.add_table::<_, HheaTable>(tag::VHEA, vhea, ())?;
+        builder.add_table::<_, HmtxTable<'_>>(tag::VMTX, vmtx, ())?;
+    }
+    for (table_tag, table) in [
+        (tag::FVAR, Some(new_fvar)),
+        (tag::AVAR, new_avar),
+        (tag::GVAR, Some(new_gvar)),
+        (tag::CVAR, new_cvar),
+        (tag::HVAR, new_hvar),
+        (tag::VVAR, new_vvar),
+        (tag::MVAR, new_mvar),
+        (tag::STAT, new_stat),
+    ] {
+        if let Some(table) = table {
+            builder.add_table::<_, ReadScope<'_>>(table_tag, ReadScope::new(table.bytes()), ())?;
+        }
+    }
+    for (table_tag, table) in new_layout_tables {
+        builder.add_table::<_, ReadScope<'_>>(table_tag, ReadScope::new(table.bytes()), ())?;
+    }
+
+    // Add the remaining tables from the source font that have not already been added. The
+    // variation tables that weren't limited above are left out as they would no longer match
+    // the axes of the font. Layout tables without variation data are copied unchanged.
+    let builder_tables = builder.table_tags().collect::<FxHashSet<_>>();
+    let tags = provider.table_tags().ok_or(VariationError::TagError)?;
+    for tag in tags.into_iter().filter(|tag| {
+        // head, glyf, loca will be added later so don't add them now
+        ![tag::HEAD, tag::GLYF, tag::LOCA].contains(tag)
+            && !is_var_table(*tag)
+            && !builder_tables.contains(tag)
+    }) {
+        let data = provider.read_table_data(tag)?;
+        builder.add_table::<_, ReadScope<'_>>(tag, ReadScope::new(&data), ())?;
+    }
+
+    head.index_to_loc_format = IndexToLocFormat::Long;
+    let mut builder = builder.add_head_table(&head)?;
+    builder.add_glyf_table(glyf)?;
+    builder.data().map_err(VariationError::from)
+}
+
 fn typographic_subfamily_name<'a>(
     user_instance: &[Fixed],
     fvar: &FvarTable<'a>,
@@ -850,6 +1145,33 @@ fn apply_hvar<'a>(
     })
 }
 
+fn apply_vvar<'a>(
+    vmtx: &HmtxTable<'_>,
+    vvar: &VvarTable<'_>,
+    instance: &OwnedTuple,
+    num_glyphs: u16,
+) -> Result<HmtxTable<'a>, ReadWriteError> {
+    let mut v_metrics = Vec::with_capacity(usize::from(num_glyphs));
+    for glyph_id in 0..num_glyphs {
+        let mut metric = vmtx.metric(glyph_id)?;
+        let delta = vvar.advance_delta(instance, glyph_id)?;
+        let new = (metric.advance_width as f32 + delta).round();
+        metric.advance_width = new.clamp(0., u16::MAX as f32) as u16;
+
+        if let Some(delta) = vvar.top_side_bearing_delta(instance, glyph_id)? {
+            metric.lsb = (metric.lsb as f32 + delta)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+        v_metrics.push(metric)
+    }
+
+    Ok(HmtxTable {
+        h_metrics: ReadArrayCow::Owned(v_metrics),
+        left_side_bearings: ReadArrayCow::Owned(vec![]),
+    })
+}
+
 fn htmx_from_phantom_points<'a>(
     glyf: &GlyfTable<'_>,
     num_glyphs: u16,
@@ -962,6 +1284,67 @@ fn apply_gvar<'a>(
     Ok(glyf)
 }
 
+/// Set the bounding box in `head` to the union of the bounding boxes of the glyphs in `glyf`.
+fn update_head_bounding_box(head: &mut HeadTable, glyf: &GlyfTable<'_>) {
+    let mut bbox = RectI::default();
+    glyf.records().iter().for_each(|glyph| match glyph {
+        GlyfRecord::Present { .. } => {}
+        GlyfRecord::Parsed(glyph) => {
+            if let Some(bounding_box) = glyph.bounding_box() {
+                bbox = union_rect(bbox, bounding_box.into())
+            }
+        }
+    });
+    head.x_min = bbox.min_x().try_into().ok().unwrap_or(i16::MIN);
+    head.y_min = bbox.min_y().try_into().ok().unwrap_or(i16::MIN);
+    head.x_max = bbox.max_x().try_into().ok().unwrap_or(i16::MAX);
+    head.y_max = bbox.max_y().try_into().ok().unwrap_or(i16::MAX);
+}
+
+/// Update the style flags and classes in `head` and `OS/2` for the instance `user_instance`.
+fn update_style(
+    user_instance: &[Fixed],
+    fvar: &FvarTable<'_>,
+    head: &mut HeadTable,
+    os2: &mut Os2,
+) {
+    // Update italic flags
+    head.mac_style
+        .set(MacStyle::ITALIC, is_italic(user_instance, fvar));
+    os2.fs_selection.set(FsSelection::ITALIC, head.is_italic());
+
+    // If one of the axes is wght or wdth then when need to update the corresponding
+    // fields in OS/2
+    for (axis, value) in fvar.axes().zip(user_instance.iter().copied()) {
+        if value == axis.default_value {
+            continue;
+        }
+
+        match axis.axis_tag {
+            tag::WGHT => {
+                // Map the value to one of the weight classes. Weight can be 1 to 1000 but
+                // weight classes are only defined for 100, 200, 300... 900.
+                os2.us_weight_class = ((f32::from(value).clamp(1., 1000.) / 100.0).round() as u16
+                    * 100)
+                    .clamp(100, 900);
+                head.mac_style
+                    .set(MacStyle::BOLD, os2.us_weight_class >= 600);
+                os2.fs_selection.set(FsSelection::BOLD, head.is_bold());
+            }
+            tag::WDTH => {
+                os2.us_width_class = Os2::value_to_width_class(value);
+                head.mac_style
+                    .set(MacStyle::CONDENSED, os2.us_width_class < 4);
+                head.mac_style
+                    .set(MacStyle::EXTENDED, os2.us_width_class > 6);
+            }
+            _ => {}
+        }
+    }
+    os2.fs_selection
+        .set(FsSelection::REGULAR, !(head.is_bold() || head.is_italic()));
+}
+
 fn union_rect(rect: RectI, other: RectI) -> RectI {
     RectI::from_points(
         rect.origin().min(other.origin()),
@@ -1030,6 +1413,7 @@ impl fmt::Display for VariationError {
             }
             VariationError::NameError => write!(f, "font did not contain a `name` table entry for the family name in a usable encoding"),
             VariationError::TagError => write!(f, "the list of table tags was unable to be retrieved from the font"),
+            VariationError::AxisLimit => write!(f, "variation: axis limit does not include the default value of the axis"),
         }
     }
 }
@@ -1061,6 +1445,9 @@ mod tests {
     use crate::cff::charstring::{ArgumentsStack, CharStringVisitorContext};
     use crate::cff::{cff2, CFFFont};
     use crate::font_data::FontData;
+    use crate::layout::{LayoutTable, GSUB};
+    use crate::tables::variable_fonts::stat::AxisValueTable;
+    use crate::tables::variable_fonts::DeltaSetIndexMapEntry;
     use crate::tables::{OpenTypeData, OpenTypeFont};
     use crate::tests::read_fixture;
 
@@ -1395,4 +1782,365 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn partial_instance_matches_instance() -> Result<(), VariationError> {
+        let buffer = read_fixture("tests/fonts/opentype/NotoSans-VF.abc.ttf");
+        let scope = ReadScope::new(&buffer);
+        let font_file = scope.read::<FontData<'_>>()?;
+        let table_provider = font_file.table_provider(0)?;
+
+        // - wght = min: 100, max: 900, default: 400
+        // - wdth = min: 62.5, max: 100, default: 100
+        // - CTGR = min: 0, max: 100, default: 0
+        let limits = [
+            AxisLimit::Range(Fixed::from(400), Fixed::from(700)),
+            AxisLimit::Pin(Fixed::from(87.5)),
+            AxisLimit::Full,
+        ];
+        let partial = partial_instance(&table_provider, &limits)?;
+        let partial_file = ReadScope::new(&partial).read::<FontData<'_>>()?;
+        let partial_provider = partial_file.table_provider(0)?;
+
+        let fvar_data = partial_provider.read_table_data(tag::FVAR)?;
+        let fvar = ReadScope::new(&fvar_data).read::<FvarTable<'_>>()?;
+        let axes = fvar
+            .axes()
+            .map(|axis| {
+                (
+                    axis.axis_tag,
+                    axis.min_value,
+                    axis.default_value,
+                    axis.max_value,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            axes,
+            vec![
+                (
+                    tag::WGHT,
+                    Fixed::from(400),
+                    Fixed::from(400),
+                    Fixed::from(700)
+                ),
+                (
+                    tag!(b"CTGR"),
+                    Fixed::from(0),
+                    Fixed::from(0),
+                    Fixed::from(100)
+                ),
+            ]
+        );
+        // Only the SemiCondensed instances from Regular to Bold remain
+        for instance in fvar.instances() {
+            let wght = instance?.coordinates.iter().next().unwrap();
+            assert!(Fixed::from(400) <= wght && wght <= Fixed::from(700));
+        }
+
+        // Instancing the partial instance gives the same outlines and metrics as instancing the
+        // original font at the same location
+        for (wght, ctgr) in [
+            (400., 0.),
+            (550., 0.),
+            (700., 0.),
+            (475., 100.),
+            (700., 50.),
+        ] {
+            let (expected, _) = instance(
+                &table_provider,
+                &[Fixed::from(wght), Fixed::from(87.5), Fixed::from(ctgr)],
+            )?;
+            let (actual, _) = instance(&partial_provider, &[Fixed::from(wght), Fixed::from(ctgr)])?;
+            let expected = glyph_metrics(&expected)?;
+            let actual = glyph_metrics(&actual)?;
+            assert_eq!(expected.len(), actual.len());
+            for (expected, actual) in expected.iter().zip(actual.iter()) {
+                for (expected, actual) in expected.iter().zip(actual.iter()) {
+                    // Deltas are rounded to integers in the partial instance
+                    assert!(
+                        (expected - actual).abs() <= 2,
+                        "{} {}: {:?} {:?}",
+                        wght,
+                        ctgr,
+                        expected,
+                        actual
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn partial_instance_range_excludes_default() -> Result<(), VariationError> {
+        let buffer = read_fixture("tests/fonts/opentype/NotoSans-VF.abc.ttf");
+        let scope = ReadScope::new(&buffer);
+        let font_file = scope.read::<FontData<'_>>()?;
+        let table_provider = font_file.table_provider(0)?;
+
+        let limits = [
+            AxisLimit::Range(Fixed::from(500), Fixed::from(700)),
+            AxisLimit::Full,
+            AxisLimit::Full,
+        ];
+        assert!(matches!(
+            partial_instance(&table_provider, &limits),
+            Err(VariationError::AxisLimit)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn partial_instance_limits_stat() -> Result<(), VariationError> {
+        let buffer = read_fixture("tests/fonts/opentype/NotoSans-VF.abc.ttf");
+        let font_file = ReadScope::new(&buffer).read::<FontData<'_>>()?;
+        let table_provider = font_file.table_provider(0)?;
+        let limits = [
+            AxisLimit::Range(Fixed::from(400), Fixed::from(700)),
+            AxisLimit::Pin(Fixed::from(87.5)),
+            AxisLimit::Full,
+        ];
+        let partial = partial_instance(&table_provider, &limits)?;
+        let partial_file = ReadScope::new(&partial).read::<FontData<'_>>()?;
+        let partial_provider = partial_file.table_provider(0)?;
+
+        let stat_data = table_provider.read_table_data(tag::STAT)?;
+        let stat = ReadScope::new(&stat_data).read::<StatTable<'_>>()?;
+        let partial_stat_data = partial_provider.read_table_data(tag::STAT)?;
+        let partial_stat = ReadScope::new(&partial_stat_data).read::<StatTable<'_>>()?;
+        assert_eq!(
+            partial_stat.design_axes().collect::<Result<Vec<_>, _>>()?,
+            stat.design_axes().collect::<Result<Vec<_>, _>>()?
+        );
+
+        // Only the values within the new limits remain
+        fn values<'a>(stat: &'a StatTable<'a>) -> Result<Vec<(u32, Fixed)>, ParseError> {
+            let mut values = Vec::new();
+            for table in stat.axis_value_tables() {
+                if let AxisValueTable::Format1(table) = table? {
+                    let axis = stat.design_axis(usize::from(table.axis_index))?;
+                    values.push((axis.axis_tag, table.value));
+                }
+            }
+            Ok(values)
+        }
+        let expected = values(&stat)?
+            .into_iter()
+            .filter(|&(axis_tag, value)| match axis_tag {
+                tag::WGHT => Fixed::from(400) <= value && value <= Fixed::from(700),
+                tag::WDTH => value == Fixed::from(87.5),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+        assert!(expected.len() < values(&stat)?.len());
+        assert_eq!(values(&partial_stat)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn partial_instance_limits_layout_tables() -> Result<(), VariationError> {
+        let buffer = read_fixture("tests/fonts/opentype/NotoSans-VF.abc.ttf");
+        let font_file = ReadScope::new(&buffer).read::<FontData<'_>>()?;
+        let table_provider = LayoutProvider(font_file.table_provider(0)?);
+        // wdth is pinned to its default, so that there are no deltas to apply to GPOS
+        let limits = [
+            AxisLimit::Range(Fixed::from(400), Fixed::from(700)),
+            AxisLimit::Pin(Fixed::from(100)),
+            AxisLimit::Full,
+        ];
+        let partial = partial_instance(&table_provider, &limits)?;
+        let partial_file = ReadScope::new(&partial).read::<FontData<'_>>()?;
+        let partial_provider = partial_file.table_provider(0)?;
+
+        let normalize = |provider: &dyn FontTableProvider, user_tuple: &[f32]| {
+            let fvar_data = provider.read_table_data(tag::FVAR)?;
+            let fvar = ReadScope::new(&fvar_data).read::<FvarTable<'_>>()?;
+            let avar_data = provider.table_data(tag::AVAR)?;
+            let avar = avar_data
+                .as_ref()
+                .map(|avar_data| ReadScope::new(avar_data).read::<AvarTable<'_>>())
+                .transpose()?;
+            fvar.normalize(user_tuple.iter().copied().map(Fixed::from), avar.as_ref())
+        };
+
+        // The feature variation on the pinned wdth axis is removed, and the condition on CTGR,
+        // which is now the second axis, still matches above 50
+        let gsub_data = partial_provider.read_table_data(tag::GSUB)?;
+        let gsub = ReadScope::new(&gsub_data).read::<LayoutTable<GSUB>>()?;
+        for (ctgr, matches) in [(0., false), (25., false), (75., true), (100., true)] {
+            let tuple = normalize(&partial_provider, &[400., ctgr])?;
+            let substitution = gsub.feature_variations(Some(tuple.as_tuple()))?;
+            assert_eq!(substitution.is_some(), matches, "CTGR {}", ctgr);
+        }
+
+        // The deltas of the GDEF item variation store are the same at the same location
+        let read_store = |provider: &dyn FontTableProvider| {
+            let gdef_data = provider.read_table_data(tag::GDEF)?;
+            let gdef = ReadScope::new(&gdef_data).read::<GDEFTable>()?;
+            gdef.opt_item_variation_store
+                .ok_or(ParseError::MissingValue)
+        };
+        let store = read_store(&table_provider)?;
+        let partial_store = read_store(&partial_provider)?;
+        let entry = DeltaSetIndexMapEntry {
+            outer_index: 0,
+            inner_index: 0,
+        };
+        for wght in [400., 550., 700.] {
+            let tuple = normalize(&table_provider, &[wght, 100., 0.])?;
+            let partial_tuple = normalize(&partial_provider, &[wght, 0.])?;
+            let expected = store.adjustment(entry, tuple.as_tuple())?;
+            let actual = partial_store.adjustment(entry, partial_tuple.as_tuple())?;
+            assert!(
+                (expected - actual).abs() <= 1.,
+                "{} {} {}",
+                wght,
+                expected,
+                actual
+            );
+        }
+
+        // Pinning wdth elsewhere would move the default, which isn't supported with GDEF deltas
+        let limits = [
+            AxisLimit::Full,
+            AxisLimit::Pin(Fixed::from(87.5)),
+            AxisLimit::Full,
+        ];
+        assert!(matches!(
+            partial_instance(&table_provider, &limits),
+            Err(VariationError::NotImplemented)
+        ));
+        Ok(())
+    }
+
+    // Replaces the GSUB table of a font with one that has feature variations, and adds a GDEF
+    // table with an item variation store, for a font with the axes wght, wdth, and CTGR.
+    struct LayoutProvider<T>(T);
+
+    #[rustfmt::skip]
+    const GSUB_FEATURE_VARIATIONS: &[u8] = &[
+        // Header
+        0x00, 0x01, 0x00, 0x01, // version 1.1
+        0x00, 0x0E, // scriptListOffset
+        0x00, 0x22, // featureListOffset
+        0x00, 0x30, // lookupListOffset
+        0x00, 0x00, 0x00, 0x48, // featureVariationsOffset
+        // Script list
+        0x00, 0x01, // scriptCount
+        b'D', b'F', b'L', b'T', 0x00, 0x08, // scriptRecords[0]
+        0x00, 0x04, // defaultLangSysOffset
+        0x00, 0x00, // langSysCount
+        0x00, 0x00, 0xFF, 0xFF, // lookupOrderOffset, requiredFeatureIndex
+        0x00, 0x01, 0x00, 0x00, // featureIndexCount, featureIndices[0]
+        // Feature list
+        0x00, 0x01, // featureCount
+        b'r', b'v', b'r', b'n', 0x00, 0x08, // featureRecords[0]
+        0x00, 0x00, 0x00, 0x00, // featureParamsOffset, lookupIndexCount
+        0x00, 0x00, // padding
+        // Lookup list
+        0x00, 0x01, 0x00, 0x04, // lookupCount, lookupOffsets[0]
+        0x00, 0x01, 0x00, 0x00, // lookupType, lookupFlag
+        0x00, 0x01, 0x00, 0x08, // subTableCount, subtableOffsets[0]
+        0x00, 0x01, 0x00, 0x06, 0x00, 0x01, // single substitution format 1, deltaGlyphID 1
+        0x00, 0x01, 0x00, 0x01, 0x00, 0x01, // coverage of glyph 1
+        // Feature variations
+        0x00, 0x01, 0x00, 0x00, // version 1.0
+        0x00, 0x00, 0x00, 0x02, // featureVariationRecordCount
+        0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x40, // records[0]
+        0x00, 0x00, 0x00, 0x26, 0x00, 0x00, 0x00, 0x40, // records[1]
+        // Condition set of records[0]: wdth from -1 to -0.5
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x06,
+        0x00, 0x01, 0x00, 0x01, 0xC0, 0x00, 0xE0, 0x00,
+        // Condition set of records[1]: wdth from -0.5 to 0, and CTGR from 0.5 to 1
+        0x00, 0x02, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x12,
+        0x00, 0x01, 0x00, 0x01, 0xE0, 0x00, 0x00, 0x00,
+        0x00, 0x01, 0x00, 0x02, 0x20, 0x00, 0x40, 0x00,
+        // Feature table substitution: rvrn applies the lookup
+        0x00, 0x01, 0x00, 0x00, // version 1.0
+        0x00, 0x01, // substitutionCount
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, // substitutions[0]
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, // featureParamsOffset, lookupIndexCount, lookupIndex
+    ];
+
+    #[rustfmt::skip]
+    const GDEF_ITEM_VARIATION_STORE: &[u8] = &[
+        // Header
+        0x00, 0x01, 0x00, 0x03, // version 1.3
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // null offsets
+        0x00, 0x00, 0x00, 0x12, // itemVarStoreOffset
+        // Item variation store
+        0x00, 0x01, // format
+        0x00, 0x00, 0x00, 0x0C, // variationRegionListOffset
+        0x00, 0x01, // itemVariationDataCount
+        0x00, 0x00, 0x00, 0x22, // itemVariationDataOffsets[0]
+        // Variation region list: a region peaking at 1 on wght
+        0x00, 0x03, // axisCount
+        0x00, 0x01, // regionCount
+        0x00, 0x00, 0x40, 0x00, 0x40, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // Item variation data
+        0x00, 0x01, // itemCount
+        0x00, 0x00, // wordDeltaCount
+        0x00, 0x01, // regionIndexCount
+        0x00, 0x00, // regionIndexes[0]
+        0x64, // deltaSets[0]
+    ];
+
+    impl<T: FontTableProvider> FontTableProvider for LayoutProvider<T> {
+        fn table_data(&self, tag: u32) -> Result<Option<Cow<'_, [u8]>>, ParseError> {
+            match tag {
+                tag::GSUB => Ok(Some(Cow::Borrowed(GSUB_FEATURE_VARIATIONS))),
+                tag::GDEF => Ok(Some(Cow::Borrowed(GDEF_ITEM_VARIATION_STORE))),
+                _ => self.0.table_data(tag),
+            }
+        }
+
+        fn has_table(&self, tag: u32) -> bool {
+            [tag::GSUB, tag::GDEF].contains(&tag) || self.0.has_table(tag)
+        }
+
+        fn table_tags(&self) -> Option<Vec<u32>> {
+            let mut tags = self.0.table_tags()?;
+            tags.retain(|&tag| ![tag::GSUB, tag::GDEF].contains(&tag));
+            tags.extend([tag::GSUB, tag::GDEF]);
+            Some(tags)
+        }
+    }
+
+    /// The advance and bounding box of each glyph in a static TrueType font.
+    fn glyph_metrics(data: &[u8]) -> Result<Vec<[i32; 5]>, VariationError> {
+        let font_file = ReadScope::new(data).read::<FontData<'_>>()?;
+        let provider = font_file.table_provider(0)?;
+        let head = ReadScope::new(&provider.read_table_data(tag::HEAD)?).read::<HeadTable>()?;
+        let maxp = ReadScope::new(&provider.read_table_data(tag::MAXP)?).read::<MaxpTable>()?;
+        let hhea = ReadScope::new(&provider.read_table_data(tag::HHEA)?).read::<HheaTable>()?;
+        let hmtx_data = provider.read_table_data(tag::HMTX)?;
+        let hmtx = ReadScope::new(&hmtx_data).read_dep::<HmtxTable<'_>>((
+            usize::from(maxp.num_glyphs),
+            usize::from(hhea.num_h_metrics),
+        ))?;
+        let loca_data = provider.read_table_data(tag::LOCA)?;
+        let loca = ReadScope::new(&loca_data)
+            .read_dep::<LocaTable<'_>>((usize::from(maxp.num_glyphs), head.index_to_loc_format))?;
+        let glyf_data = provider.read_table_data(tag::GLYF)?;
+        let mut glyf = ReadScope::new(&glyf_data).read_dep::<GlyfTable<'_>>(&loca)?;
+        let mut metrics = Vec::new();
+        for (glyph_id, record) in glyf.records_mut().iter_mut().enumerate() {
+            record.parse()?;
+            let GlyfRecord::Parsed(glyph) = record else {
+                unreachable!("glyph should be parsed")
+            };
+            let bbox = glyph.bounding_box().unwrap_or_else(BoundingBox::empty);
+            metrics.push([
+                i32::from(hmtx.horizontal_advance(glyph_id as u16)?),
+                i32::from(bbox.x_min),
+                i32::from(bbox.y_min),
+                i32::from(bbox.x_max),
+                i32::from(bbox.y_max),
+            ]);
+        }
+        Ok(metrics)
+    }
 }
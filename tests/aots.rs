@@ -330,9 +330,11 @@ fn make_direct_glyph(glyph_index: u16) -> RawGlyph<()> {
         glyph_index,
         liga_component_pos: 0,
         glyph_origin: GlyphOrigin::Direct,
+        cluster: 0,
         flags: RawGlyphFlags::empty(),
         extra_data: (),
         variation: None,
+        feature_mask: None,
     }
 }
 
@@ -7,7 +7,9 @@ mod arabic_tests {
     use crate::shape;
 
     use allsorts::binary::read::ReadScope;
-    use allsorts::gsub::RawGlyph;
+    use allsorts::font::MatchingPresentation;
+    use allsorts::gpos::Info;
+    use allsorts::gsub::{FeatureMask, Features, RawGlyph};
     use allsorts::scripts::arabic::gsub_apply_arabic;
     use allsorts::scripts::preprocess_text;
     use allsorts::tables::cmap::CmapSubtable;
@@ -461,6 +463,61 @@ mod arabic_tests {
         )
     }
 
+    // `ArabicPresentationFormsTest.ttf` has no `GSUB` table. It maps BEH, LAM, ALEF, MEEM, and
+    // FATHA, and the presentation forms of the letters, except for the medial form of MEEM.
+    const PRESENTATION_FORMS_FONT: &str = "tests/fonts/arabic/ArabicPresentationFormsTest.ttf";
+
+    #[test]
+    fn presentation_forms_fallback() {
+        let cases: &[(&str, &[u16])] = &[
+            ("\u{628}", &[3]),
+            ("\u{628}\u{628}", &[5, 4]),
+            ("\u{628}\u{628}\u{628}", &[5, 6, 4]),
+            // MEEM has no medial form, so the base glyph is used
+            ("\u{628}\u{645}\u{628}", &[5, 15, 4]),
+            ("\u{644}\u{627}", &[19]),
+            ("\u{628}\u{644}\u{627}", &[5, 20]),
+            ("\u{644}\u{64e}\u{627}", &[19, 21]),
+        ];
+        for &(text, expected) in cases {
+            let infos = shape_without_gsub(text);
+            let glyph_indices = infos
+                .iter()
+                .map(|info| info.glyph.glyph_index)
+                .collect::<Vec<_>>();
+            assert_eq!(glyph_indices, expected, "{:?}", text);
+        }
+
+        let infos = shape_without_gsub("\u{644}\u{64e}\u{627}");
+        assert_eq!(infos[0].glyph.unicodes.as_slice(), &['\u{644}', '\u{627}']);
+        let clusters = infos
+            .iter()
+            .map(|info| info.glyph.cluster)
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![0, 0]);
+    }
+
+    fn shape_without_gsub(text: &str) -> Vec<Info> {
+        let font_contents = common::read_fixture(PRESENTATION_FORMS_FONT);
+        let opentype_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont>()
+            .unwrap();
+        let font_table_provider = opentype_file
+            .table_provider(0)
+            .expect("Error getting font file");
+        let mut font = Font::new(font_table_provider).expect("Error getting font data");
+        let glyphs = font.map_glyphs(text, tag::ARAB, MatchingPresentation::NotRequired);
+        font.shape(
+            glyphs,
+            tag::ARAB,
+            None,
+            &Features::Mask(FeatureMask::default()),
+            None,
+            true,
+        )
+        .expect("error shaping text")
+    }
+
     fn test(lang_tag: Option<u32>, test_cases: Vec<(&str, &str, Vec<u16>)>) {
         let script_tag = tag::ARAB;
 
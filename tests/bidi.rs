@@ -0,0 +1,233 @@
+mod common;
+
+// Tests of the Unicode Bidirectional Algorithm in the formats of its conformance test data,
+// `BidiTest.txt` and `BidiCharacterTest.txt` from <https://www.unicode.org/Public/15.0.0/ucd/>.
+//
+// These are not the conformance tests. `GeneratedBidiTest.txt` and
+// `GeneratedBidiCharacterTest.txt` in `tests/bidi` are generated by `scripts/gen_bidi_tests.py`
+// with the results of GNU FriBidi, and the cases in `GeneratedBidiCharacterTest.txt` are only kept
+// where ICU gives the same results. Cases where those implementations deviate from UAX #9, or
+// disagree with each other, aren't covered. The conformance files can be checked with
+// `run_bidi_test` and `run_bidi_character_test`, which print the cases that fail.
+#[cfg(test)]
+mod generated {
+    use crate::common;
+
+    use allsorts::bidi::Paragraph;
+    use allsorts::glyph_position::TextDirection;
+    use allsorts::unicode::bidi_class;
+    use allsorts::unicode::BidiClass::{self, *};
+
+    // The maximum number of failures that are printed
+    const MAX_REPORTED: usize = 20;
+
+    #[test]
+    fn generated_bidi_test() {
+        let data = common::read_fixture("tests/bidi/GeneratedBidiTest.txt");
+        let failures = run_bidi_test(&String::from_utf8(data).unwrap());
+        assert_eq!(failures, 0);
+    }
+
+    #[test]
+    fn generated_bidi_character_test() {
+        let data = common::read_fixture("tests/bidi/GeneratedBidiCharacterTest.txt");
+        let failures = run_bidi_character_test(&String::from_utf8(data).unwrap());
+        assert_eq!(failures, 0);
+    }
+
+    #[test]
+    fn bidi_test_format() {
+        // A few cases in the format of `BidiTest.txt`
+        let data = "\
+@Levels: 1 2
+@Reorder: 1 0
+R L; 5
+@Levels: x 1 x
+@Reorder: 1
+RLE R PDF; 2
+@Levels: x 3 x
+RLE R PDF; 5
+";
+        assert_eq!(run_bidi_test(data), 0);
+    }
+
+    #[test]
+    fn bidi_character_test_format() {
+        // A few cases in the format of `BidiCharacterTest.txt`
+        let data = "\
+# A right-to-left paragraph with a left-to-right word
+05D0 0020 0061;2;1;1 1 2;2 1 0
+0061 0020 05D0;0;0;0 0 1;0 1 2
+# Brackets take the embedding direction when they contain a matching strong type
+0061 0028 05D0 0029;1;1;2 1 1 1;3 2 1 0
+# Explicit embeddings are removed by X9
+202B 0061 202C;0;0;x 2 x;1
+# Numbers
+05D0 0031 0032;2;1;1 2 2;1 2 0
+0627 0031;2;1;1 2;1 0
+# Trailing whitespace takes the paragraph level
+05D0 0020;0;0;1 0;0 1
+";
+        assert_eq!(run_bidi_character_test(data), 0);
+    }
+
+    /// Run the tests in the format of `BidiTest.txt`, returning the number that failed.
+    fn run_bidi_test(data: &str) -> usize {
+        let mut levels = Vec::new();
+        let mut reorder = Vec::new();
+        let mut failures = 0;
+        for line in data.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if let Some(value) = line.strip_prefix("@Levels:") {
+                levels = value.split_whitespace().map(parse_level).collect();
+            } else if let Some(value) = line.strip_prefix("@Reorder:") {
+                reorder = value
+                    .split_whitespace()
+                    .map(|index| index.parse().unwrap())
+                    .collect();
+            } else if let Some((classes, bitset)) = line.split_once(';') {
+                let text = classes
+                    .split_whitespace()
+                    .map(class_char)
+                    .collect::<String>();
+                let bitset = bitset.trim().parse::<u8>().unwrap();
+                let directions = [
+                    (1, None),
+                    (2, Some(TextDirection::LeftToRight)),
+                    (4, Some(TextDirection::RightToLeft)),
+                ];
+                for (_, direction) in directions.iter().filter(|(bit, _)| bitset & bit != 0) {
+                    let (_, actual_levels, actual_reorder) = resolve(&text, *direction);
+                    if actual_levels != levels || actual_reorder != reorder {
+                        report(&mut failures, line, &actual_levels, &actual_reorder);
+                    }
+                }
+            }
+        }
+        failures
+    }
+
+    /// Run the tests in the format of `BidiCharacterTest.txt`, returning the number that failed.
+    fn run_bidi_character_test(data: &str) -> usize {
+        let mut failures = 0;
+        for line in data.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields = line.split(';').collect::<Vec<_>>();
+            let text = fields[0]
+                .split_whitespace()
+                .map(|code| char::from_u32(u32::from_str_radix(code, 16).unwrap()).unwrap())
+                .collect::<String>();
+            let direction = match fields[1] {
+                "0" => Some(TextDirection::LeftToRight),
+                "1" => Some(TextDirection::RightToLeft),
+                _ => None,
+            };
+            let base_level = fields[2].parse::<u8>().unwrap();
+            let levels = fields[3]
+                .split_whitespace()
+                .map(parse_level)
+                .collect::<Vec<_>>();
+            let reorder = fields[4]
+                .split_whitespace()
+                .map(|index| index.parse().unwrap())
+                .collect::<Vec<usize>>();
+
+            let (actual_base_level, actual_levels, actual_reorder) = resolve(&text, direction);
+            if actual_base_level != base_level
+                || actual_levels != levels
+                || actual_reorder != reorder
+            {
+                report(&mut failures, line, &actual_levels, &actual_reorder);
+            }
+        }
+        failures
+    }
+
+    /// Returns the paragraph level, the level of each character, and the indices of the
+    /// characters in visual order. Characters removed by rule X9 have no level and are omitted
+    /// from the visual order.
+    fn resolve(text: &str, direction: Option<TextDirection>) -> (u8, Vec<Option<u8>>, Vec<usize>) {
+        let paragraph = Paragraph::new(text, direction);
+        let offsets = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+        let removed = text
+            .chars()
+            .map(|ch| is_removed(bidi_class(ch)))
+            .collect::<Vec<_>>();
+        let run_chars = |range: &std::ops::Range<usize>| {
+            offsets.partition_point(|&i| i < range.start)
+                ..offsets.partition_point(|&i| i < range.end)
+        };
+
+        let mut levels = vec![None; offsets.len()];
+        for run in paragraph.runs() {
+            for i in run_chars(&run.range) {
+                levels[i] = Some(run.level).filter(|_| !removed[i]);
+            }
+        }
+        let mut reorder = Vec::new();
+        for run in paragraph.visual_runs(0..text.len()) {
+            let mut indices = run_chars(&run.range)
+                .filter(|&i| !removed[i])
+                .collect::<Vec<_>>();
+            if run.direction() == TextDirection::RightToLeft {
+                indices.reverse();
+            }
+            reorder.extend(indices);
+        }
+        (paragraph.base_level(), levels, reorder)
+    }
+
+    fn report(failures: &mut usize, line: &str, levels: &[Option<u8>], reorder: &[usize]) {
+        if *failures < MAX_REPORTED {
+            eprintln!(
+                "{}\n    levels: {:?}\n    reorder: {:?}",
+                line, levels, reorder
+            );
+        }
+        *failures += 1;
+    }
+
+    fn parse_level(level: &str) -> Option<u8> {
+        match level {
+            "x" => None,
+            _ => Some(level.parse().unwrap()),
+        }
+    }
+
+    fn is_removed(class: BidiClass) -> bool {
+        matches!(class, RLE | LRE | RLO | LRO | PDF | BN)
+    }
+
+    // A character of each bidi class
+    fn class_char(class: &str) -> char {
+        match class {
+            "L" => 'a',
+            "R" => '\u{05D0}',
+            "AL" => '\u{0627}',
+            "EN" => '0',
+            "ES" => '+',
+            "ET" => '$',
+            "AN" => '\u{0660}',
+            "CS" => ',',
+            "NSM" => '\u{0300}',
+            "BN" => '\u{00AD}',
+            "B" => '\u{2029}',
+            "S" => '\t',
+            "WS" => ' ',
+            "ON" => '!',
+            "LRE" => '\u{202A}',
+            "LRO" => '\u{202D}',
+            "RLE" => '\u{202B}',
+            "RLO" => '\u{202E}',
+            "PDF" => '\u{202C}',
+            "LRI" => '\u{2066}',
+            "RLI" => '\u{2067}',
+            "FSI" => '\u{2068}',
+            "PDI" => '\u{2069}',
+            _ => panic!("unknown bidi class {}", class),
+        }
+    }
+}
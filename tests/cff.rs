@@ -17,6 +17,7 @@ use allsorts::cff::{
 use allsorts::subset::subset;
 use allsorts::tables::{OpenTypeData, OpenTypeFont};
 use allsorts::tag;
+use allsorts::Font;
 
 use crate::common::read_fixture;
 
@@ -246,10 +247,29 @@ fn test_subset_cff_cid() {
         subset(&opentype_file.table_provider(0).unwrap(), &mut glyph_ids,)
             .unwrap()
             .len(),
-        7900
+        15888
     );
 }
 
+#[test]
+fn test_subset_cff_cid_closure() {
+    // Only the vertical forms from `vert` of the punctuation in `glyph_ids` are added by the
+    // `GSUB` closure
+    let buffer = read_fixture("tests/fonts/noto/NotoSansJP-Regular.otf");
+    let opentype_file = ReadScope::new(&buffer).read::<OpenTypeFont<'_>>().unwrap();
+    let glyph_ids = [
+        0, 1, 2, 3, 4, 5, 6, 7, 14, 19, 20, 38, 39, 41, 42, 49, 50, 52, 66, 68, 69, 70, 72, 74, 77,
+        78, 79, 80, 81, 83, 84, 85, 86, 88, 202, 281, 338, 345, 350, 370, 393, 396, 399, 405, 410,
+        2522, 5221,
+    ];
+    let subset_buffer = subset(&opentype_file.table_provider(0).unwrap(), &glyph_ids).unwrap();
+    let subset_file = ReadScope::new(&subset_buffer)
+        .read::<OpenTypeFont<'_>>()
+        .unwrap();
+    let font = Font::new(subset_file.table_provider(0).unwrap()).unwrap();
+    assert_eq!(usize::from(font.num_glyphs()), glyph_ids.len() + 11);
+}
+
 #[test]
 fn test_subset_cff_type1() {
     let buffer = read_fixture("tests/fonts/opentype/Klei.otf");
@@ -259,7 +279,7 @@ fn test_subset_cff_type1() {
         subset(&opentype_file.table_provider(0).unwrap(), &mut glyph_ids,)
             .unwrap()
             .len(),
-        26576
+        26880
     );
 }
 
@@ -0,0 +1,72 @@
+mod shape;
+
+#[cfg(test)]
+mod hangul_tests {
+    use crate::shape;
+
+    use allsorts::tag;
+
+    // `HangulTest.ttf` maps the leading jamo KIYEOK and NIEUN to glyphs 2 and 3, the vowel jamo
+    // A and ARAEA to glyphs 4 and 5, the trailing jamo KIYEOK to glyph 6, and the syllable GA to
+    // glyph 7. It doesn't have the syllables GAG or NA. The `ljmo`, `vjmo`, and `tjmo` features
+    // substitute the jamo with glyphs 8–12.
+    const FONT: &str = "tests/fonts/hangul/HangulTest.ttf";
+
+    #[test]
+    fn composition() {
+        test(vec![
+            ("\u{1100}\u{1161}", vec![7]),
+            ("\u{AC00}", vec![7]),
+            ("\u{1100}\u{1161}\u{1100}\u{1161}", vec![7, 7]),
+        ])
+    }
+
+    #[test]
+    fn jamo_features() {
+        test(vec![
+            // GAG and NA aren't in the font so they are shaped as jamo
+            ("\u{1100}\u{1161}\u{11A8}", vec![8, 10, 12]),
+            ("\u{AC01}", vec![8, 10, 12]),
+            ("\u{AC00}\u{11A8}", vec![8, 10, 12]),
+            ("\u{B098}", vec![9, 10]),
+            // Old Hangul
+            ("\u{1100}\u{119E}", vec![8, 11]),
+            ("\u{1100}\u{119E}\u{11A8}", vec![8, 11, 12]),
+        ])
+    }
+
+    #[test]
+    fn incomplete_syllables() {
+        // Jamo that aren't part of a syllable don't get the jamo features
+        test(vec![
+            ("\u{1161}", vec![4]),
+            ("\u{11A8}", vec![6]),
+            ("\u{1100}\u{1100}\u{1161}", vec![2, 7]),
+            ("\u{1100}\u{1102}\u{119E}", vec![2, 9, 11]),
+        ])
+    }
+
+    #[test]
+    fn clusters() {
+        let infos = shape::shape(FONT, tag::HANG, "\u{1100}\u{1161}\u{11A8} \u{1100}\u{1161}");
+        let clusters = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![(8, 0), (10, 0), (12, 0), (1, 9), (7, 10)]);
+    }
+
+    #[test]
+    fn other_scripts() {
+        // Jamo are only composed and shaped in Hangul text
+        let infos = shape::shape(FONT, tag::LATN, "\u{1100}\u{1161}");
+        assert_eq!(shape::glyph_indices(&infos), vec![2, 4]);
+    }
+
+    fn test(test_cases: Vec<(&str, Vec<u16>)>) {
+        for (text, expected) in test_cases {
+            let infos = shape::shape(FONT, tag::HANG, text);
+            assert_eq!(shape::glyph_indices(&infos), expected, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+mod shape;
+
+#[cfg(test)]
+mod itemize_tests {
+    use crate::shape;
+
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::itemize::{font_script_tag, shape_text, ShapedScriptRun};
+    use allsorts::tag;
+    use allsorts::unicode::Script;
+
+    // `ItemizeTest.ttf` maps "a", "b", and KA to glyphs 2–4. It has `dev2` and `latn` scripts,
+    // and the `pres` feature of `dev2` substitutes KA with glyph 5.
+    const FONT: &str = "tests/fonts/itemize/ItemizeTest.ttf";
+    // `BidiTest.ttf` only has a `DFLT` script.
+    const DFLT_FONT: &str = "tests/fonts/bidi/BidiTest.ttf";
+
+    #[test]
+    fn mixed_scripts() {
+        let runs = shape_runs(FONT, "ab \u{0915} b");
+        assert_eq!(
+            summary(&runs),
+            vec![
+                (Script::Latin, tag::LATN, vec![(2, 0), (3, 1), (1, 2)]),
+                (Script::Devanagari, tag::DEV2, vec![(5, 3), (1, 6)]),
+                (Script::Latin, tag::LATN, vec![(3, 7)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn common_only() {
+        let runs = shape_runs(FONT, " ");
+        assert_eq!(
+            summary(&runs),
+            vec![(Script::Common, tag::DFLT, vec![(1, 0)])]
+        );
+        assert!(shape_runs(FONT, "").is_empty());
+    }
+
+    #[test]
+    fn script_tag_selection() {
+        // The Indic2 tag is only used if the font has it
+        shape::with_font(FONT, |font| {
+            assert_eq!(font_script_tag(font, Script::Devanagari), Ok(tag::DEV2));
+            assert_eq!(font_script_tag(font, Script::Bengali), Ok(tag::BENG));
+        });
+        shape::with_font(DFLT_FONT, |font| {
+            assert_eq!(font_script_tag(font, Script::Devanagari), Ok(tag::DEVA));
+            assert_eq!(font_script_tag(font, Script::Latin), Ok(tag::LATN));
+        });
+    }
+
+    fn shape_runs(path: &str, text: &str) -> Vec<ShapedScriptRun> {
+        shape::with_font(path, |font| {
+            let features = Features::Mask(FeatureMask::default());
+            shape_text(font, text, None, &features, None, true).expect("error shaping text")
+        })
+    }
+
+    // The script and script tag of a run, and the glyph index and cluster of each glyph
+    type RunSummary = (Script, u32, Vec<(u16, usize)>);
+
+    fn summary(runs: &[ShapedScriptRun]) -> Vec<RunSummary> {
+        runs.iter()
+            .map(|run| {
+                let glyphs = run
+                    .infos
+                    .iter()
+                    .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+                    .collect();
+                (run.script, run.script_tag, glyphs)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,81 @@
+mod shape;
+
+#[cfg(test)]
+mod mongolian_tests {
+    use crate::shape;
+
+    use allsorts::tag;
+
+    // `MongolianTest.ttf` contains the letters A, E, I, NA, and BA. Each letter has nominal
+    // (isolated), initial, medial, and final forms, in that order, starting at glyph 2, 6, 10,
+    // 14, and 18 respectively.
+    const FONT: &str = "tests/fonts/mongolian/MongolianTest.ttf";
+
+    #[test]
+    fn isol_init_medi_fina() {
+        test(vec![
+            ("\u{1820}", vec![2]),
+            ("\u{182A}", vec![18]),
+            ("\u{182A}\u{1820}", vec![19, 5]),
+            ("\u{182A}\u{1822}\u{1828}", vec![19, 12, 17]),
+            ("\u{1828}\u{1821}\u{182A}\u{1820}", vec![15, 8, 20, 5]),
+            ("\u{182A}\u{1820} \u{1828}", vec![19, 5, 1, 14]),
+        ])
+    }
+
+    #[test]
+    fn free_variation_selectors() {
+        // Each test contains a free variation selector that selects a variant form of the
+        // preceding letter through a ligature in the lookups of that letter's form
+        test(vec![
+            // A + FVS1 → isolated variant
+            ("\u{1820}\u{180B}", vec![32]),
+            // NA + FVS1 → initial variant
+            ("\u{1828}\u{180B}\u{1820}", vec![29, 5]),
+            // NA + FVS1 → medial variant
+            ("\u{182A}\u{1828}\u{180B}\u{1820}", vec![19, 30, 5]),
+            // A + FVS1 → final variant
+            ("\u{182A}\u{1820}\u{180B}", vec![19, 31]),
+        ])
+    }
+
+    #[test]
+    fn unused_free_variation_selectors() {
+        // Free variation selectors do not affect joining and are removed when the font has no
+        // variant for them
+        test(vec![
+            ("\u{182A}\u{180C}\u{1820}", vec![19, 5]),
+            ("\u{182A}\u{180D}\u{1820}", vec![19, 5]),
+            ("\u{182A}\u{180F}\u{1820}", vec![19, 5]),
+            ("\u{1828}\u{180D}", vec![14]),
+        ])
+    }
+
+    #[test]
+    fn vowel_separator() {
+        // The letter before the MVS ends the word and the vowel after it takes its final form
+        test(vec![
+            ("\u{1828}\u{180E}\u{1820}", vec![14, 25, 5]),
+            ("\u{182A}\u{1828}\u{180E}\u{1820}", vec![19, 17, 25, 5]),
+            ("\u{182A}\u{180E}\u{1820}\u{180B}", vec![18, 25, 31]),
+        ])
+    }
+
+    #[test]
+    fn joining_controls() {
+        test(vec![
+            // NARROW NO-BREAK SPACE separates suffixes without joining
+            ("\u{182A}\u{202F}\u{1820}", vec![18, 28, 2]),
+            // MONGOLIAN NIRUGU is join causing
+            ("\u{180A}\u{1820}", vec![27, 5]),
+            ("\u{182A}\u{180A}\u{182A}", vec![19, 27, 21]),
+        ])
+    }
+
+    fn test(test_cases: Vec<(&str, Vec<u16>)>) {
+        for (text, expected) in test_cases {
+            let infos = shape::shape(FONT, tag::MONG, text);
+            assert_eq!(shape::glyph_indices(&infos), expected, "{:?}", text);
+        }
+    }
+}
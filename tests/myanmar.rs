@@ -0,0 +1,161 @@
+mod shape;
+
+#[cfg(test)]
+mod myanmar_tests {
+    use crate::shape;
+
+    use allsorts::gpos::Placement;
+    use allsorts::layout::Anchor;
+    use allsorts::tag;
+
+    // `MyanmarTest.ttf` maps KA, KHA, GA, and NGA to glyphs 3–6, the vowel signs E, AA, and U to
+    // glyphs 7–9, anusvara, dot below, virama, and asat to glyphs 10–13, and medial YA, RA, and
+    // WA to glyphs 14–16. Its `mym2` script forms kinzi (17) with `rphf`, subjoined KHA (18) with
+    // `blwf`, a wide medial RA (19) with `pref`, and a post-base medial YA (20) with `pstf`. Its
+    // `mymr` script substitutes KA with an alternate (21).
+    //
+    // `MyanmarLegacyTest.ttf` has the same glyphs, but its features are only in the `mymr`
+    // script.
+    const FONT: &str = "tests/fonts/myanmar/MyanmarTest.ttf";
+    const LEGACY_FONT: &str = "tests/fonts/myanmar/MyanmarLegacyTest.ttf";
+
+    #[test]
+    fn pre_base_reordering() {
+        test(vec![
+            // Medial RA moves before the base
+            ("\u{1000}\u{103C}", vec![19, 3]),
+            // Vowel sign E moves before the base and medial RA
+            ("\u{1000}\u{1031}", vec![7, 3]),
+            ("\u{1000}\u{103C}\u{1031}\u{102C}", vec![7, 19, 3, 8]),
+            // Vowel sign E moves before the whole stack
+            ("\u{1000}\u{1039}\u{1001}\u{1031}", vec![7, 3, 18]),
+        ])
+    }
+
+    #[test]
+    fn kinzi() {
+        test(vec![
+            ("\u{1004}\u{103A}\u{1039}\u{1000}", vec![3, 17]),
+            ("\u{1004}\u{103A}\u{1039}\u{1000}\u{1031}", vec![7, 3, 17]),
+            ("\u{1004}\u{103A}\u{1039}\u{1000}\u{102F}", vec![3, 17, 9]),
+        ])
+    }
+
+    #[test]
+    fn post_base_forms() {
+        test(vec![
+            ("\u{1000}\u{1039}\u{1001}", vec![3, 18]),
+            ("\u{1000}\u{103B}", vec![3, 20]),
+            ("\u{1000}\u{103B}\u{103D}", vec![3, 20, 16]),
+        ])
+    }
+
+    #[test]
+    fn below_base_marks() {
+        test(vec![
+            // Anusvara moves before a vowel below, but dot below stays after it
+            ("\u{1000}\u{102F}\u{1036}", vec![3, 10, 9]),
+            ("\u{1000}\u{102F}\u{1037}", vec![3, 9, 11]),
+        ])
+    }
+
+    #[test]
+    fn broken_syllables() {
+        // A dotted circle is inserted as the base of syllables that have none
+        test(vec![
+            ("\u{1031}", vec![7, 2]),
+            ("\u{1031}\u{1000}", vec![7, 2, 3]),
+            ("\u{1000}\u{102C}\u{102F}", vec![3, 8, 2, 9]),
+            ("\u{25CC}\u{1031}", vec![7, 2]),
+        ])
+    }
+
+    #[test]
+    fn clusters() {
+        let infos = shape::shape(FONT, tag::MYM2, "\u{1000}\u{1031} \u{1001}");
+        let clusters = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![(7, 0), (3, 0), (1, 6), (4, 7)]);
+    }
+
+    #[test]
+    fn script_tags() {
+        // The mym2 script is used even when text is shaped with the mymr tag
+        for script_tag in [tag::MYM2, tag::MYMR] {
+            assert_eq!(
+                glyph_indices(FONT, script_tag, "\u{1000}\u{103C}"),
+                vec![19, 3]
+            );
+        }
+        // Fonts that only have the mymr script are supported
+        for script_tag in [tag::MYM2, tag::MYMR] {
+            assert_eq!(
+                glyph_indices(LEGACY_FONT, script_tag, "\u{1000}\u{103C}"),
+                vec![19, 3]
+            );
+            assert_eq!(
+                glyph_indices(LEGACY_FONT, script_tag, "\u{1004}\u{103A}\u{1039}\u{1000}"),
+                vec![3, 17]
+            );
+        }
+    }
+
+    #[test]
+    fn mark_positioning() {
+        for (font, script_tag) in [(FONT, tag::MYMR), (LEGACY_FONT, tag::MYM2)] {
+            let infos = shape::shape(font, script_tag, "\u{1000}\u{102F}");
+            let placements = infos
+                .iter()
+                .map(|info| (info.glyph.glyph_index, info.placement))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                placements,
+                vec![
+                    (3, Placement::None),
+                    (
+                        9,
+                        Placement::MarkAnchor(
+                            0,
+                            Anchor { x: 300, y: -50 },
+                            Anchor { x: -150, y: 650 }
+                        )
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn mym2_mark_positioning() {
+        // The dot below is attached by `blwm`, and the asat, which has no anchor, is left where
+        // it is rather than positioned from its outline
+        let infos = shape::shape(FONT, tag::MYM2, "\u{1000}\u{1037}\u{103A}");
+        let placements = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.placement))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placements,
+            vec![
+                (3, Placement::None),
+                (
+                    11,
+                    Placement::MarkAnchor(0, Anchor { x: 300, y: -50 }, Anchor { x: -150, y: 650 })
+                ),
+                (13, Placement::None),
+            ]
+        );
+    }
+
+    fn glyph_indices(font: &str, script_tag: u32, text: &str) -> Vec<u16> {
+        shape::glyph_indices(&shape::shape(font, script_tag, text))
+    }
+
+    fn test(test_cases: Vec<(&str, Vec<u16>)>) {
+        for (text, expected) in test_cases {
+            assert_eq!(glyph_indices(FONT, tag::MYM2, text), expected, "{:?}", text);
+        }
+    }
+}
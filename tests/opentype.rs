@@ -7,6 +7,7 @@ use std::rc::Rc;
 
 use allsorts::binary::read::ReadScope;
 use allsorts::error::ShapingError;
+use allsorts::font::MatchingPresentation;
 use allsorts::gsub::{self, FeatureMask, Features};
 use allsorts::tables::cmap::{Cmap, CmapSubtable, EncodingId, PlatformId};
 use allsorts::tables::glyf::{
@@ -433,3 +434,1088 @@ fn test_reverse_chaining_contextual_single_substitution() {
         );
     }
 }
+
+fn shaped_clusters(path: &str, script_tag: u32, text: &str) -> Vec<usize> {
+    let font_buffer = read_fixture(path);
+    let opentype_file = ReadScope::new(&font_buffer)
+        .read::<OpenTypeFont<'_>>()
+        .unwrap();
+    let font_table_provider = opentype_file
+        .table_provider(0)
+        .expect("error reading font file");
+    let mut font = Font::new(Box::new(font_table_provider)).expect("error reading font data");
+
+    let glyphs = font.map_glyphs(text, script_tag, MatchingPresentation::NotRequired);
+    let infos = font
+        .shape(
+            glyphs,
+            script_tag,
+            None,
+            &Features::Mask(FeatureMask::default()),
+            None,
+            true,
+        )
+        .expect("error shaping text");
+    infos.iter().map(|info| info.glyph.cluster).collect()
+}
+
+#[test]
+fn test_clusters_ligature() {
+    // Klei ligates ff, which should take the cluster of the first f
+    let clusters = shaped_clusters("tests/fonts/opentype/Klei.otf", tag::LATN, "a jiffy");
+    assert_eq!(clusters, &[0, 1, 2, 3, 4, 6]);
+}
+
+#[test]
+fn test_clusters_indic_reordering() {
+    // The pre-base matra is moved before KA so both glyphs share a cluster
+    let clusters = shaped_clusters(
+        "tests/fonts/noto/NotoSansDevanagari-Regular.ttf",
+        tag::DEVA,
+        "\u{0915}\u{093F}\u{0916}",
+    );
+    assert_eq!(clusters, &[0, 0, 6]);
+}
+
+#[test]
+fn test_clusters_arabic_mark_reordering() {
+    // HAMZA ABOVE is reordered before DAMMA, merging their clusters
+    let font_buffer = read_fixture("tests/fonts/noto/NotoNaskhArabic-Regular.ttf");
+    let opentype_file = ReadScope::new(&font_buffer)
+        .read::<OpenTypeFont<'_>>()
+        .unwrap();
+    let font_table_provider = opentype_file
+        .table_provider(0)
+        .expect("error reading font file");
+    let mut font = Font::new(Box::new(font_table_provider)).expect("error reading font data");
+
+    let glyphs = font.map_glyphs(
+        "\u{0627}\u{064F}\u{0654}\u{0628}",
+        tag::ARAB,
+        MatchingPresentation::NotRequired,
+    );
+    let chars_clusters = glyphs
+        .iter()
+        .map(|glyph| (glyph.unicodes[0], glyph.cluster))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        chars_clusters,
+        &[
+            ('\u{0627}', 0),
+            ('\u{0654}', 2),
+            ('\u{064F}', 2),
+            ('\u{0628}', 6)
+        ]
+    );
+}
+
+#[test]
+fn test_clusters_arabic_mark_composition() {
+    // ALEF and HAMZA ABOVE are composed after reordering as the font has ALEF WITH HAMZA ABOVE.
+    // DAMMA lies between them in the text, so it joins the cluster of the composition.
+    let chars_clusters = normalized_clusters(
+        "tests/fonts/noto/NotoNaskhArabic-Regular.ttf",
+        tag::ARAB,
+        "\u{0627}\u{064F}\u{0654}\u{0628}",
+    );
+    assert_eq!(
+        chars_clusters,
+        &[('\u{0623}', 0), ('\u{064F}', 0), ('\u{0628}', 6)]
+    );
+}
+
+fn normalized_clusters(path: &str, script_tag: u32, text: &str) -> Vec<(char, usize)> {
+    let font_buffer = read_fixture(path);
+    let opentype_file = ReadScope::new(&font_buffer)
+        .read::<OpenTypeFont<'_>>()
+        .unwrap();
+    let font_table_provider = opentype_file
+        .table_provider(0)
+        .expect("error reading font file");
+    let mut font = Font::new(Box::new(font_table_provider)).expect("error reading font data");
+
+    font.map_glyphs_normalized(text, script_tag, MatchingPresentation::NotRequired)
+        .iter()
+        .map(|glyph| (glyph.unicodes[0], glyph.cluster))
+        .collect()
+}
+
+mod gdef {
+    use allsorts::binary::read::ReadScope;
+    use allsorts::font::MatchingPresentation;
+    use allsorts::gpos::Placement;
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::layout::{Anchor, CaretValue, Device, GDEFTable};
+    use allsorts::tables::variable_fonts::fvar::FvarTable;
+    use allsorts::tables::{F2Dot14, FontTableProvider, OpenTypeFont};
+    use allsorts::tag;
+    use allsorts::Font;
+
+    use crate::common;
+    use crate::shape;
+
+    // `MarkFilteringTest.ttf` maps "a", COMBINING ACUTE ACCENT, and COMBINING DOT BELOW to glyphs
+    // 2–4. Mark glyph set 0 contains the acute and set 1 the dot below. A `ccmp` lookup filtered
+    // by set 0 substitutes "a" followed by the acute with glyph 5, and a `mark` lookup filtered
+    // by set 1 attaches marks to "a".
+    const FONT: &str = "tests/fonts/gdef/MarkFilteringTest.ttf";
+    // `LigatureCaretsTest.ttf` maps "f", "i", and "l" to glyphs 2–4 and has the ligatures "fi",
+    // "fl", "ffi", and "ffl" as glyphs 5–8. The carets of "fi" use a device table, "fl" a contour
+    // point, and "ffl" a variation index. "ffi" has no carets. It has a single `wght` axis.
+    const CARETS_FONT: &str = "tests/fonts/gdef/LigatureCaretsTest.ttf";
+
+    #[test]
+    fn read_mark_glyph_sets() {
+        let gdef = read_gdef(FONT);
+        let mark_glyph_sets = gdef.opt_mark_glyph_sets.unwrap();
+        assert_eq!(mark_glyph_sets.len(), 2);
+        assert_eq!(mark_glyph_sets[0].glyph_coverage_value(3), Some(0));
+        assert_eq!(mark_glyph_sets[0].glyph_coverage_value(4), None);
+        assert_eq!(mark_glyph_sets[1].glyph_coverage_value(4), Some(0));
+    }
+
+    #[test]
+    fn read_attach_and_lig_caret_lists() {
+        let gdef = read_gdef(CARETS_FONT);
+        let attach_list = gdef.opt_attach_list.unwrap();
+        assert_eq!(attach_list.attach_points(2), Some(&[0, 2][..]));
+        assert_eq!(attach_list.attach_points(3), Some(&[3][..]));
+        assert_eq!(attach_list.attach_points(4), None);
+
+        let lig_caret_list = gdef.opt_lig_caret_list.unwrap();
+        let device = Device::Hinting {
+            start_size: 10,
+            end_size: 12,
+            delta_values: vec![1, -1, 2],
+        };
+        assert_eq!(device.delta(11), -1);
+        assert_eq!(device.delta(9), 0);
+        assert_eq!(
+            lig_caret_list.caret_values(5),
+            Some(&[CaretValue::DeviceCoordinate(300, Some(device))][..])
+        );
+        assert_eq!(
+            lig_caret_list.caret_values(6),
+            Some(&[CaretValue::ContourPoint(2)][..])
+        );
+        let carets = lig_caret_list.caret_values(8).unwrap();
+        assert_eq!(carets[0], CaretValue::Coordinate(300));
+        assert!(matches!(
+            carets[1],
+            CaretValue::DeviceCoordinate(600, Some(Device::Variation(_)))
+        ));
+        assert_eq!(lig_caret_list.caret_values(7), None);
+    }
+
+    #[test]
+    fn ligature_carets() {
+        let font_contents = common::read_fixture(CARETS_FONT);
+        let opentype_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont>()
+            .unwrap();
+        let provider = opentype_file.table_provider(0).unwrap();
+        let fvar_data = provider.read_table_data(tag::FVAR).unwrap();
+        let fvar = ReadScope::new(&fvar_data).read::<FvarTable>().unwrap();
+        let max_weight = fvar.owned_tuple(&[F2Dot14::from(1.0)]).unwrap();
+        let mut font = Font::new(provider).unwrap();
+
+        let mut carets = |text, tuple| {
+            let glyphs = font.map_glyphs(text, tag::LATN, MatchingPresentation::NotRequired);
+            let infos = font
+                .shape(
+                    glyphs,
+                    tag::LATN,
+                    None,
+                    &Features::Mask(FeatureMask::default()),
+                    tuple,
+                    true,
+                )
+                .unwrap();
+            assert_eq!(infos.len(), 1, "{:?}", text);
+            font.ligature_carets(&infos[0], tuple).unwrap()
+        };
+        assert_eq!(carets("fi", None), vec![300]);
+        assert_eq!(carets("fl", None), vec![320]);
+        // Without carets in GDEF the advance is split evenly
+        assert_eq!(carets("ffi", None), vec![300, 600]);
+        assert_eq!(carets("ffl", None), vec![300, 600]);
+        assert_eq!(carets("ffl", Some(max_weight.as_tuple())), vec![300, 700]);
+        assert_eq!(carets("f", None), Vec::<i32>::new());
+    }
+
+    fn read_gdef(path: &str) -> GDEFTable {
+        let font_contents = common::read_fixture(path);
+        let opentype_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont>()
+            .unwrap();
+        let provider = opentype_file.table_provider(0).unwrap();
+        let gdef_data = provider.read_table_data(tag::GDEF).unwrap();
+        ReadScope::new(&gdef_data).read::<GDEFTable>().unwrap()
+    }
+
+    #[test]
+    fn gsub_mark_filtering_set() {
+        // The dot below isn't in the filtering set, so it's skipped when matching the acute
+        let glyphs = |text| shape::glyph_indices(&shape::shape(FONT, tag::LATN, text));
+        assert_eq!(glyphs("a\u{301}"), vec![5, 3]);
+        assert_eq!(glyphs("a\u{323}\u{301}"), vec![5, 4, 3]);
+        assert_eq!(glyphs("a\u{323}"), vec![2, 4]);
+    }
+
+    #[test]
+    fn gpos_mark_filtering_set() {
+        // Only the dot below is in the filtering set, so the acute isn't attached
+        let placements = shape::shape(FONT, tag::LATN, "a\u{323}")
+            .iter()
+            .map(|info| info.placement)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placements,
+            vec![
+                Placement::None,
+                Placement::MarkAnchor(0, Anchor { x: 250, y: 0 }, Anchor { x: -150, y: 0 })
+            ]
+        );
+        let placements = shape::shape(FONT, tag::LATN, "a\u{301}")
+            .iter()
+            .map(|info| info.placement)
+            .collect::<Vec<_>>();
+        assert_eq!(placements, vec![Placement::None, Placement::None]);
+    }
+}
+
+mod kern {
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // The fonts map "A", "V", "T", "o", "W", and U+0301 COMBINING ACUTE ACCENT to glyphs 2–7.
+    //
+    // `KernMicrosoftTest.ttf` has a version 0 `kern` table. A format 0 subtable kerns AV by -80
+    // and To by -60, and a format 2 subtable kerns Wo by -50.
+    const MICROSOFT_FONT: &str = "tests/fonts/kern/KernMicrosoftTest.ttf";
+    // `KernAppleTest.ttf` has a version 1 `kern` table. A format 1 subtable kerns "o" after "T"
+    // by -60, and a format 3 subtable kerns AV by -70.
+    const APPLE_FONT: &str = "tests/fonts/kern/KernAppleTest.ttf";
+    // `KernGposTest.ttf` has a `GPOS` `kern` feature for `latn` that kerns AV by -10, and none
+    // for `DFLT`. Its `kern` table kerns AV by -80.
+    const GPOS_FONT: &str = "tests/fonts/kern/KernGposTest.ttf";
+
+    #[test]
+    fn microsoft() {
+        assert_eq!(
+            kerning(MICROSOFT_FONT, "AVToWoWA", tag::LATN, true),
+            vec![-80, 0, -60, 0, -50, 0, 0, 0]
+        );
+        assert_eq!(
+            kerning(MICROSOFT_FONT, "AVTo", tag::LATN, false),
+            vec![0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn apple() {
+        assert_eq!(
+            kerning(APPLE_FONT, "ToAVoT", tag::LATN, true),
+            vec![-60, 0, -70, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn marks_are_skipped() {
+        assert_eq!(
+            kerning(MICROSOFT_FONT, "A\u{0301}V", tag::LATN, true),
+            vec![-80, 0, 0]
+        );
+    }
+
+    #[test]
+    fn gpos_kerning_takes_precedence() {
+        // The `kern` table is only used when `GPOS` has no `kern` feature for the script
+        assert_eq!(kerning(GPOS_FONT, "AV", tag::LATN, true), vec![-10, 0]);
+        assert_eq!(kerning(GPOS_FONT, "AV", tag::CYRL, true), vec![-80, 0]);
+        assert_eq!(kerning(GPOS_FONT, "AV", tag::CYRL, false), vec![0, 0]);
+    }
+
+    fn kerning(path: &str, text: &str, script_tag: u32, kerning: bool) -> Vec<i16> {
+        let features = Features::Mask(FeatureMask::default());
+        shape::shape_with_features(path, script_tag, text, &features, kerning)
+            .iter()
+            .map(|info| info.kerning)
+            .collect()
+    }
+}
+
+mod aat {
+    use allsorts::aat;
+    use allsorts::gpos::{Info, Placement};
+    use allsorts::gsub::{FeatureInfo, FeatureMask, Features};
+    use allsorts::layout::Anchor;
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // `AatMorxTest.ttf` has a `morx` table with a chain of five subtables:
+    //
+    // * noncontextual: "a" → "a.sc", enabled by `smcp`
+    // * ligature: "f" "i" → "f_i", enabled by default and disabled when `liga` isn't
+    // * contextual: "T" "o" → "T.alt" "o.alt"
+    // * rearrangement: "R" … "E" → … "E" "R"
+    // * insertion: "b" → "b" "x"
+    const MORX_FONT: &str = "tests/fonts/aat/AatMorxTest.ttf";
+    // `AatKerxTest.ttf` has a `kerx` table that kerns AV by -80 (format 0), To by -60
+    // (format 2), and "A" after "V" by -30 (format 1), and attaches U+0301 COMBINING ACUTE ACCENT
+    // to "o" (format 4). Its `trak` table has a normal track of 20, 0, and -40 at 9, 12, and 24
+    // points.
+    const KERX_FONT: &str = "tests/fonts/aat/AatKerxTest.ttf";
+
+    const F_I: u16 = 4;
+    const A_SC: u16 = 6;
+    const T_ALT: u16 = 8;
+    const O_ALT: u16 = 10;
+    const R: u16 = 11;
+    const E: u16 = 12;
+    const B: u16 = 13;
+    const X: u16 = 14;
+
+    #[test]
+    fn noncontextual_and_ligature() {
+        let default = shape::shape(MORX_FONT, tag::LATN, "fia");
+        assert_eq!(shape::glyph_indices(&default), vec![F_I, 5]);
+        assert_eq!(default[0].glyph.unicodes.as_slice(), &['f', 'i']);
+
+        let small_caps = Features::Custom(vec![FeatureInfo {
+            feature_tag: tag::SMCP,
+            alternate: None,
+        }]);
+        let infos = shape_features(MORX_FONT, "fia", &small_caps, true);
+        assert_eq!(shape::glyph_indices(&infos), vec![2, 3, A_SC]);
+    }
+
+    #[test]
+    fn contextual() {
+        let infos = shape::shape(MORX_FONT, tag::LATN, "ToTo");
+        assert_eq!(
+            shape::glyph_indices(&infos),
+            vec![T_ALT, O_ALT, T_ALT, O_ALT]
+        );
+        let infos = shape::shape(MORX_FONT, tag::LATN, "oT");
+        assert_eq!(shape::glyph_indices(&infos), vec![9, 7]);
+    }
+
+    #[test]
+    fn rearrangement() {
+        let infos = shape::shape(MORX_FONT, tag::LATN, "RaE");
+        assert_eq!(shape::glyph_indices(&infos), vec![5, E, R]);
+    }
+
+    #[test]
+    fn insertion() {
+        let infos = shape::shape(MORX_FONT, tag::LATN, "bab");
+        assert_eq!(shape::glyph_indices(&infos), vec![B, X, 5, B, X]);
+        let clusters = infos
+            .iter()
+            .map(|info| info.glyph.cluster)
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![0, 0, 1, 2, 2]);
+    }
+
+    #[test]
+    fn kerx() {
+        let infos = shape::shape(KERX_FONT, tag::LATN, "AVToVA");
+        assert_eq!(kerning(&infos), vec![-80, 0, -60, 0, -30, 0]);
+        let infos = shape_features(
+            KERX_FONT,
+            "AVToVA",
+            &Features::Mask(FeatureMask::default()),
+            false,
+        );
+        assert_eq!(kerning(&infos), vec![0; 6]);
+    }
+
+    #[test]
+    fn kerx_attachment() {
+        let infos = shape_features(
+            KERX_FONT,
+            "o\u{0301}",
+            &Features::Mask(FeatureMask::default()),
+            false,
+        );
+        assert_eq!(
+            infos[1].placement,
+            Placement::MarkAnchor(0, Anchor { x: 300, y: 700 }, Anchor { x: 0, y: 0 })
+        );
+    }
+
+    #[test]
+    fn trak() {
+        let mut infos = shape_features(
+            KERX_FONT,
+            "AT\u{0301}",
+            &Features::Mask(FeatureMask::default()),
+            false,
+        );
+        shape::with_font(KERX_FONT, |font| {
+            let trak_table = font.trak_table().unwrap().unwrap();
+            aat::apply_trak(&trak_table, 18., &mut infos);
+        });
+        assert_eq!(kerning(&infos), vec![-20, -20, 0]);
+    }
+
+    fn shape_features(path: &str, text: &str, features: &Features, kerning: bool) -> Vec<Info> {
+        shape::shape_with_features(path, tag::LATN, text, features, kerning)
+    }
+
+    fn kerning(infos: &[Info]) -> Vec<i16> {
+        infos.iter().map(|info| info.kerning).collect()
+    }
+}
+
+mod fallback {
+    use allsorts::font::MatchingPresentation;
+    use allsorts::gpos::{Info, Placement};
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::layout::Anchor;
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // `FallbackMarkTest.ttf` has no `GPOS` table. "a" spans (50, 0) to (450, 500), and the marks
+    // all span (0, 0) to (100, 100). The units per em is 1000, so the gap between stacked marks
+    // is 62.
+    const FONT: &str = "tests/fonts/fallback/FallbackMarkTest.ttf";
+
+    #[test]
+    fn above_and_below() {
+        // U+0301 COMBINING ACUTE ACCENT and U+0323 COMBINING DOT BELOW
+        let infos = shape_with_fallback(FONT, tag::LATN, "a\u{0323}\u{0301}");
+        assert_eq!(infos[1].placement, mark_anchor(200, -162));
+        assert_eq!(infos[2].placement, mark_anchor(200, 562));
+    }
+
+    #[test]
+    fn stacked() {
+        let infos = shape_with_fallback(FONT, tag::LATN, "a\u{0323}\u{0323}\u{0301}\u{0301}");
+        assert_eq!(infos[1].placement, mark_anchor(200, -162));
+        assert_eq!(infos[2].placement, mark_anchor(200, -324));
+        assert_eq!(infos[3].placement, mark_anchor(200, 562));
+        assert_eq!(infos[4].placement, mark_anchor(200, 724));
+    }
+
+    #[test]
+    fn attached_and_overlay() {
+        // U+0338 COMBINING LONG SOLIDUS OVERLAY is an overlay, U+0328 COMBINING OGONEK is
+        // attached below, and U+031B COMBINING HORN is attached above right
+        let infos = shape_with_fallback(FONT, tag::LATN, "a\u{0338}\u{0328}\u{031B}");
+        assert_eq!(infos[1].placement, mark_anchor(200, 0));
+        assert_eq!(infos[2].placement, mark_anchor(200, -100));
+        assert_eq!(infos[3].placement, mark_anchor(350, 500));
+    }
+
+    #[test]
+    fn mark_advance_is_cancelled() {
+        // The ogonek has an advance of 300
+        let infos = shape_with_fallback(FONT, tag::LATN, "a\u{0328}\u{0301}");
+        let kerning = infos.iter().map(|info| info.kerning).collect::<Vec<_>>();
+        assert_eq!(kerning, vec![0, -300, 0]);
+    }
+
+    #[test]
+    fn not_applied_with_gpos() {
+        // The `mym2` script of `MyanmarTest.ttf` attaches the dot below with `blwm` rather than
+        // `mark`, and has no anchor for the asat, so the asat is left where the font put it
+        let font = "tests/fonts/myanmar/MyanmarTest.ttf";
+        let infos = shape_with_fallback(font, tag::MYM2, "\u{1000}\u{1037}\u{103A}");
+        let placements = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.placement, info.kerning))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placements,
+            vec![
+                (3, Placement::None, 0),
+                (
+                    11,
+                    Placement::MarkAnchor(0, Anchor { x: 300, y: -50 }, Anchor { x: -150, y: 650 }),
+                    0
+                ),
+                (13, Placement::None, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let infos = shape::shape(FONT, tag::LATN, "a\u{0301}");
+        assert_eq!(infos[1].placement, Placement::MarkOverprint(0));
+    }
+
+    fn shape_with_fallback(path: &str, script_tag: u32, text: &str) -> Vec<Info> {
+        shape::with_font(path, |font| {
+            font.set_fallback_mark_positioning(true);
+            let glyphs = font.map_glyphs(text, script_tag, MatchingPresentation::NotRequired);
+            font.shape(
+                glyphs,
+                script_tag,
+                None,
+                &Features::Mask(FeatureMask::default()),
+                None,
+                true,
+            )
+            .expect("error shaping text")
+        })
+    }
+
+    fn mark_anchor(x: i16, y: i16) -> Placement {
+        Placement::MarkAnchor(0, Anchor { x, y }, Anchor { x: 0, y: 0 })
+    }
+}
+
+mod vertical {
+    use std::borrow::Cow;
+
+    use allsorts::binary::read::ReadScope;
+    use allsorts::error::ParseError;
+    use allsorts::glyph_position::{GlyphLayout, GlyphPosition, TextDirection};
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::tables::variable_fonts::fvar::FvarTable;
+    use allsorts::tables::{F2Dot14, FontTableProvider, OpenTypeFont};
+    use allsorts::tag;
+    use allsorts::Font;
+
+    use crate::common;
+    use crate::shape;
+
+    // `VerticalTest.ttf` has `vhea` and `vmtx` tables. The ideographs U+4E00 and U+4E8C are
+    // glyphs 3 and 4, and have their vertical origins at y = 880.
+    const FONT: &str = "tests/fonts/vertical/VerticalTest.ttf";
+    // `VerticalVorgTest.ttf` has the same glyphs and a `VORG` table, which puts the vertical
+    // origin of U+4E00 at y = 850 and defaults to y = 880.
+    const VORG_FONT: &str = "tests/fonts/vertical/VerticalVorgTest.ttf";
+
+    #[test]
+    fn upright_glyphs_have_vertical_origins() {
+        for (path, expected_origins) in [
+            (FONT, [(500, 880), (500, 880)]),
+            (VORG_FONT, [(500, 850), (250, 880)]),
+        ] {
+            let origins = layout(path, "\u{4E00}\u{4E8C}")
+                .iter()
+                .map(|position| (position.vert_origin_x, position.vert_origin_y))
+                .collect::<Vec<_>>();
+            assert_eq!(origins, expected_origins, "{}", path);
+        }
+    }
+
+    #[test]
+    fn vertical_origins() {
+        for path in [FONT, VORG_FONT] {
+            shape::with_font(path, |font| {
+                let glyphs = [3, 4, 3];
+                let expected = glyphs
+                    .iter()
+                    .map(|&glyph| font.vertical_origin(glyph))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    font.vertical_origins(&glyphs, None).unwrap(),
+                    expected,
+                    "{}",
+                    path
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn vertical_origins_vvar() {
+        // A variable font is only used for its two axes
+        let fvar_font_data = common::read_fixture("tests/fonts/variable/Inter[slnt,wght].abc.ttf");
+        let fvar_font_file = ReadScope::new(&fvar_font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let fvar_provider = fvar_font_file.table_provider(0).unwrap();
+        let fvar_data = fvar_provider.read_table_data(tag::FVAR).unwrap();
+        let fvar = ReadScope::new(&fvar_data).read::<FvarTable<'_>>().unwrap();
+        let tuple = fvar
+            .owned_tuple(&[F2Dot14::from_raw(0x2000), F2Dot14::from_raw(0)])
+            .unwrap();
+
+        let data = common::read_fixture(VORG_FONT);
+        let font_file = ReadScope::new(&data).read::<OpenTypeFont<'_>>().unwrap();
+        let provider = VvarProvider(font_file.table_provider(0).unwrap());
+        let mut font = Font::new(provider).unwrap();
+        assert_eq!(
+            font.vertical_origins(&[3, 4], None).unwrap(),
+            vec![850, 880]
+        );
+        // Half of the delta of 100 at the peak of the first axis
+        assert_eq!(
+            font.vertical_origins(&[3, 4], Some(tuple.as_tuple()))
+                .unwrap(),
+            vec![900, 930]
+        );
+    }
+
+    fn layout(path: &str, text: &str) -> Vec<GlyphPosition> {
+        let features = Features::Mask(FeatureMask::default() | FeatureMask::VRT2_OR_VERT);
+        let infos = shape::shape_with_features(path, tag::HANI, text, &features, false);
+        shape::with_font(path, |font| {
+            GlyphLayout::new(font, &infos, TextDirection::LeftToRight, true)
+                .glyph_positions()
+                .unwrap()
+        })
+    }
+
+    // Adds a `VVAR` table to a font. Its item variation store has a single delta of 100 for a
+    // region peaking at 1 on the first of two axes, which every vertical origin is mapped to.
+    struct VvarProvider<T>(T);
+
+    #[rustfmt::skip]
+    const VVAR: &[u8] = &[
+        // Header
+        0x00, 0x01, 0x00, 0x00, // version 1.0
+        0x00, 0x00, 0x00, 0x18, // itemVariationStoreOffset
+        0x00, 0x00, 0x00, 0x00, // advanceHeightMappingOffset
+        0x00, 0x00, 0x00, 0x00, // tsbMappingOffset
+        0x00, 0x00, 0x00, 0x00, // bsbMappingOffset
+        0x00, 0x00, 0x00, 0x3D, // vOrgMappingOffset
+        // Item variation store
+        0x00, 0x01, // format
+        0x00, 0x00, 0x00, 0x0C, // variationRegionListOffset
+        0x00, 0x01, // itemVariationDataCount
+        0x00, 0x00, 0x00, 0x1C, // itemVariationDataOffsets[0]
+        // Variation region list
+        0x00, 0x02, // axisCount
+        0x00, 0x01, // regionCount
+        0x00, 0x00, 0x40, 0x00, 0x40, 0x00, // start, peak, and end of the first axis
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // start, peak, and end of the second axis
+        // Item variation data
+        0x00, 0x01, // itemCount
+        0x00, 0x00, // wordDeltaCount
+        0x00, 0x01, // regionIndexCount
+        0x00, 0x00, // regionIndexes[0]
+        0x64, // deltaSets[0]
+        // Vertical origin mapping
+        0x00, // format
+        0x00, // entryFormat
+        0x00, 0x01, // mapCount
+        0x00, // mapData[0]
+    ];
+
+    impl<T: FontTableProvider> FontTableProvider for VvarProvider<T> {
+        fn table_data(&self, tag: u32) -> Result<Option<Cow<'_, [u8]>>, ParseError> {
+            match tag {
+                tag::VVAR => Ok(Some(Cow::Borrowed(VVAR))),
+                _ => self.0.table_data(tag),
+            }
+        }
+
+        fn has_table(&self, tag: u32) -> bool {
+            tag == tag::VVAR || self.0.has_table(tag)
+        }
+
+        fn table_tags(&self) -> Option<Vec<u32>> {
+            let mut tags = self.0.table_tags()?;
+            tags.push(tag::VVAR);
+            Some(tags)
+        }
+    }
+}
+
+mod feature_range {
+    use std::ops::Range;
+
+    use allsorts::error::ShapingError;
+    use allsorts::font::MatchingPresentation;
+    use allsorts::gpos::Info;
+    use allsorts::gsub::{FeatureInfo, FeatureMask, Features};
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // `FeatureRangeTest.ttf` has an "fi" ligature in `liga`, small capitals for "a" and "b" in
+    // `smcp`, and a `vkrn` pair adjustment of -100 between "a" and "b".
+    const FONT: &str = "tests/fonts/feature_range/FeatureRangeTest.ttf";
+
+    const A: u16 = 2;
+    const B: u16 = 3;
+    const F: u16 = 4;
+    const I: u16 = 5;
+    const F_I: u16 = 6;
+    const A_SC: u16 = 7;
+    const B_SC: u16 = 8;
+    const SPACE: u16 = 1;
+
+    const ARABIC_FONT: &str = "tests/fonts/arabic/ae_Arab.ttf";
+
+    const LAM_INIT: u16 = 904;
+    const ALEF_FINA: u16 = 823;
+
+    #[test]
+    fn smcp_for_range() {
+        let smcp = FeatureMask::default() | FeatureMask::SMCP;
+        let infos = shape_masks("ab ab", FeatureMask::default(), &[(3..5, smcp)]);
+        assert_eq!(shape::glyph_indices(&infos), vec![A, B, SPACE, A_SC, B_SC]);
+    }
+
+    #[test]
+    fn smcp_disabled_for_range() {
+        let smcp = FeatureMask::default() | FeatureMask::SMCP;
+        let infos = shape_masks("ab ab", smcp, &[(0..2, FeatureMask::default())]);
+        assert_eq!(shape::glyph_indices(&infos), vec![A, B, SPACE, A_SC, B_SC]);
+    }
+
+    #[test]
+    fn liga_disabled_for_range() {
+        let no_liga = FeatureMask::default() - FeatureMask::LIGA;
+        let infos = shape_masks("fi fi", FeatureMask::default(), &[(0..2, no_liga)]);
+        assert_eq!(shape::glyph_indices(&infos), vec![F, I, SPACE, F_I]);
+    }
+
+    #[test]
+    fn no_ligature_across_ranges() {
+        // The ligature is enabled on both sides, but the masks differ
+        let smcp = FeatureMask::default() | FeatureMask::SMCP;
+        let infos = shape_masks("fi", FeatureMask::default(), &[(1..2, smcp)]);
+        assert_eq!(shape::glyph_indices(&infos), vec![F, I]);
+    }
+
+    #[test]
+    fn gpos_feature_for_range() {
+        let vkrn = FeatureMask::default() | FeatureMask::VKRN;
+        let infos = shape_masks("ab ab", FeatureMask::default(), &[(3..5, vkrn)]);
+        let kerning = infos.iter().map(|info| info.kerning).collect::<Vec<_>>();
+        assert_eq!(kerning, vec![0, 0, 0, -100, 0]);
+    }
+
+    #[test]
+    fn gpos_pair_needs_both_glyphs() {
+        let vkrn = FeatureMask::default() | FeatureMask::VKRN;
+        let infos = shape_masks("ab", FeatureMask::default(), &[(1..2, vkrn)]);
+        let kerning = infos.iter().map(|info| info.kerning).collect::<Vec<_>>();
+        assert_eq!(kerning, vec![0, 0]);
+    }
+
+    #[test]
+    fn liga_disabled_for_range_in_complex_script() {
+        // `ae_Arab.ttf` has a lam-alef ligature in `liga`, which the Arabic shaper applies
+        let no_liga = FeatureMask::default() - FeatureMask::LIGA;
+        let features = Features::Mask(FeatureMask::default());
+        let infos = shape_ranges(
+            ARABIC_FONT,
+            tag::ARAB,
+            "\u{644}\u{627}",
+            &features,
+            &[(0..4, no_liga)],
+        )
+        .expect("error shaping text");
+        assert_eq!(shape::glyph_indices(&infos), vec![LAM_INIT, ALEF_FINA]);
+    }
+
+    #[test]
+    fn glyph_masks_with_custom_features() {
+        let features = Features::Custom(vec![FeatureInfo {
+            feature_tag: tag!(b"ss01"),
+            alternate: None,
+        }]);
+        let res = shape_ranges(
+            FONT,
+            tag::LATN,
+            "ab",
+            &features,
+            &[(1..2, FeatureMask::SMCP)],
+        );
+        assert!(matches!(res, Err(ShapingError::GlyphFeatureMask)));
+    }
+
+    // Shapes `text` with `features`, overridden by the masks of the byte ranges in `ranges`
+    fn shape_masks(
+        text: &str,
+        features: FeatureMask,
+        ranges: &[(Range<usize>, FeatureMask)],
+    ) -> Vec<Info> {
+        shape_ranges(FONT, tag::LATN, text, &Features::Mask(features), ranges)
+            .expect("error shaping text")
+    }
+
+    // Shapes `text` with the font at `path` and `features`, overridden by the masks of the byte
+    // ranges in `ranges`
+    fn shape_ranges(
+        path: &str,
+        script_tag: u32,
+        text: &str,
+        features: &Features,
+        ranges: &[(Range<usize>, FeatureMask)],
+    ) -> Result<Vec<Info>, ShapingError> {
+        shape::with_font(path, |font| {
+            let mut glyphs = font.map_glyphs(text, script_tag, MatchingPresentation::NotRequired);
+            for glyph in glyphs.iter_mut() {
+                let cluster = glyph.cluster;
+                glyph.feature_mask = ranges
+                    .iter()
+                    .find(|(range, _)| range.contains(&cluster))
+                    .map(|(_, mask)| *mask);
+            }
+            font.shape(glyphs, script_tag, None, features, None, true)
+                .map_err(|(err, _infos)| err)
+        })
+    }
+}
+
+mod alternates {
+    use allsorts::gsub::GlyphAlternates;
+    use allsorts::layout::{CharacterVariantParams, FeatureParams, StylisticSetParams};
+    use allsorts::tag;
+
+    use crate::shape;
+
+    const FONT: &str = "tests/fonts/alternates/AlternatesTest.ttf";
+
+    const A: u16 = 2;
+    const G: u16 = 3;
+    const A_ALT1: u16 = 4;
+    const A_ALT2: u16 = 5;
+    const A_SWSH: u16 = 6;
+    const G_SS01: u16 = 7;
+    const G_CV01: u16 = 8;
+
+    #[test]
+    fn alternates_of_a() {
+        let alternates = shape::with_font(FONT, |font| {
+            font.glyph_alternates(A, tag::LATN, None).unwrap()
+        });
+        // `liga` also substitutes "a" but it isn't an alternates feature
+        assert_eq!(
+            alternates,
+            vec![
+                alternates_for(tag::AALT, vec![A_ALT1, A_ALT2, A_SWSH], None),
+                alternates_for(tag::SALT, vec![A_ALT1, A_ALT2], None),
+                alternates_for(tag::SWSH, vec![A_SWSH], None),
+            ]
+        );
+    }
+
+    #[test]
+    fn alternates_of_g() {
+        let alternates = shape::with_font(FONT, |font| {
+            font.glyph_alternates(G, tag::LATN, None).unwrap()
+        });
+        let ss01 = FeatureParams::StylisticSet(StylisticSetParams { ui_name_id: 256 });
+        let cv01 = FeatureParams::CharacterVariant(CharacterVariantParams {
+            label_name_id: 257,
+            tooltip_name_id: 258,
+            sample_text_name_id: 259,
+            num_named_parameters: 1,
+            first_param_ui_label_name_id: 260,
+            characters: vec![u32::from('g')],
+        });
+        assert_eq!(
+            alternates,
+            vec![
+                alternates_for(tag::AALT, vec![G_SS01], None),
+                alternates_for(tag!(b"cv01"), vec![G_CV01], Some(cv01)),
+                alternates_for(tag!(b"ss01"), vec![G_SS01], Some(ss01)),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_alternates() {
+        let alternates = shape::with_font(FONT, |font| {
+            font.glyph_alternates(G_CV01, tag::LATN, None).unwrap()
+        });
+        assert!(alternates.is_empty());
+    }
+
+    #[test]
+    fn feature_names() {
+        let names = shape::with_font(FONT, |font| {
+            let alternates = font.glyph_alternates(G, tag::LATN, None).unwrap();
+            alternates
+                .iter()
+                .filter_map(|alternates| alternates.feature_params.as_ref())
+                .map(|params| font.name(params.ui_name_id().unwrap()).unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            names,
+            vec![
+                Some(String::from("Alternate g")),
+                Some(String::from("Single-storey g"))
+            ]
+        );
+    }
+
+    fn alternates_for(
+        feature_tag: u32,
+        alternates: Vec<u16>,
+        feature_params: Option<FeatureParams>,
+    ) -> GlyphAlternates {
+        GlyphAlternates {
+            feature_tag,
+            alternates,
+            feature_params,
+        }
+    }
+}
+
+mod normalization {
+    use allsorts::font::MatchingPresentation;
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // `NormalizationComposedTest.ttf` maps "e", "é", and COMBINING DOT BELOW to glyphs 2–4.
+    // `NormalizationDecomposedTest.ttf` maps "e", COMBINING ACUTE ACCENT, and COMBINING DOT
+    // BELOW to glyphs 2–4.
+    const COMPOSED_FONT: &str = "tests/fonts/normalization/NormalizationComposedTest.ttf";
+    const DECOMPOSED_FONT: &str = "tests/fonts/normalization/NormalizationDecomposedTest.ttf";
+
+    #[test]
+    fn composition() {
+        test(
+            COMPOSED_FONT,
+            vec![
+                ("e\u{301}", vec![(3, 0)]),
+                ("\u{E9}", vec![(3, 0)]),
+                ("e\u{301}e", vec![(3, 0), (2, 3)]),
+                // The dot below is reordered before the acute, which still composes with "e"
+                ("e\u{301}\u{323}", vec![(3, 0), (4, 0)]),
+                ("\u{E9}\u{323}", vec![(3, 0), (4, 2)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn decomposition() {
+        test(
+            DECOMPOSED_FONT,
+            vec![
+                ("\u{E9}", vec![(2, 0), (3, 0)]),
+                ("e\u{301}", vec![(2, 0), (3, 1)]),
+                ("\u{E9}\u{323}", vec![(2, 0), (4, 0), (3, 0)]),
+                // "è" can't be decomposed into characters the font has
+                ("\u{E8}", vec![(0, 0)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn complex_scripts_unchanged() {
+        // Normalisation isn't applied to scripts with a dedicated shaper
+        let glyphs = map_glyphs(COMPOSED_FONT, tag::DEVA, "e\u{301}");
+        assert_eq!(glyphs, vec![(2, 0), (0, 1)]);
+    }
+
+    fn map_glyphs(path: &str, script_tag: u32, text: &str) -> Vec<(u16, usize)> {
+        shape::with_font(path, |font| {
+            font.map_glyphs_normalized(text, script_tag, MatchingPresentation::NotRequired)
+                .iter()
+                .map(|glyph| (glyph.glyph_index, glyph.cluster))
+                .collect()
+        })
+    }
+
+    fn test(font: &str, test_cases: Vec<(&str, Vec<(u16, usize)>)>) {
+        for (text, expected) in test_cases {
+            assert_eq!(map_glyphs(font, tag::LATN, text), expected, "{:?}", text);
+        }
+    }
+}
+
+mod bidi {
+    use allsorts::bidi::{shape_paragraph, Paragraph, ShapedRun};
+    use allsorts::glyph_position::TextDirection;
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::tag;
+
+    use crate::shape;
+
+    // `BidiTest.ttf` maps "a", "b", "(", ")", ALEF, BET, and COMPLEMENT to glyphs 2–8. The `rtlm`
+    // feature substitutes COMPLEMENT, which has no mirroring character, with glyph 9.
+    const FONT: &str = "tests/fonts/bidi/BidiTest.ttf";
+
+    #[test]
+    fn mixed_directions() {
+        let runs = shape_runs("ab \u{05D0}\u{05D1}", None);
+        assert_eq!(
+            summary(&runs),
+            vec![(0, vec![(2, 0), (3, 1), (1, 2)]), (1, vec![(6, 3), (7, 5)])]
+        );
+        assert_eq!(runs[1].direction(), TextDirection::RightToLeft);
+    }
+
+    #[test]
+    fn visual_order() {
+        // The runs of a right-to-left paragraph are returned from left to right
+        let runs = shape_runs("\u{05D0} ab", None);
+        assert_eq!(
+            summary(&runs),
+            vec![(2, vec![(2, 3), (3, 4)]), (1, vec![(6, 0), (1, 2)])]
+        );
+    }
+
+    #[test]
+    fn mirroring() {
+        // Parentheses in right-to-left runs are mirrored
+        let runs = shape_runs("\u{05D0}(\u{05D1})", None);
+        assert_eq!(
+            summary(&runs),
+            vec![(1, vec![(6, 0), (5, 2), (7, 3), (4, 5)])]
+        );
+        let runs = shape_runs("a(b)", None);
+        assert_eq!(
+            summary(&runs),
+            vec![(0, vec![(2, 0), (4, 1), (3, 2), (5, 3)])]
+        );
+    }
+
+    #[test]
+    fn rtlm() {
+        // Characters without a mirroring character are mirrored by the rtlm feature
+        let runs = shape_runs("\u{05D0}\u{2201}", None);
+        assert_eq!(summary(&runs), vec![(1, vec![(6, 0), (9, 2)])]);
+        let runs = shape_runs("a\u{2201}", None);
+        assert_eq!(summary(&runs), vec![(0, vec![(2, 0), (8, 1)])]);
+        let runs = shape_runs("\u{2201}", Some(TextDirection::RightToLeft));
+        assert_eq!(summary(&runs), vec![(1, vec![(9, 0)])]);
+    }
+
+    #[test]
+    fn script_runs() {
+        // Runs in the same direction are split by script and shaped with the tag of each script
+        let runs = shape_runs("ab\u{0391} \u{05D0}", None);
+        let scripts = runs
+            .iter()
+            .map(|run| (run.level, run.script_tag))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scripts,
+            vec![(0, tag::LATN), (0, tag::GREK), (1, tag!(b"hebr"))]
+        );
+    }
+
+    fn shape_runs(text: &str, direction: Option<TextDirection>) -> Vec<ShapedRun> {
+        shape::with_font(FONT, |font| {
+            let paragraph = Paragraph::new(text, direction);
+            let features = Features::Mask(FeatureMask::default());
+            shape_paragraph(font, &paragraph, None, &features, None, true)
+                .expect("error shaping text")
+        })
+    }
+
+    // The level of each run, and the glyph index and cluster of each glyph
+    fn summary(runs: &[ShapedRun]) -> Vec<(u8, Vec<(u16, usize)>)> {
+        runs.iter()
+            .map(|run| {
+                let glyphs = run
+                    .infos
+                    .iter()
+                    .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+                    .collect();
+                (run.level, glyphs)
+            })
+            .collect()
+    }
+}
@@ -1,10 +1,18 @@
+use std::path::Path;
+
+use allsorts::binary::read::ReadScope;
 use allsorts::error::ParseError;
-use allsorts::gsub::{GlyphOrigin, RawGlyph, RawGlyphFlags};
+use allsorts::font::MatchingPresentation;
+use allsorts::gpos::Info;
+use allsorts::gsub::{FeatureMask, Features, GlyphOrigin, RawGlyph, RawGlyphFlags};
 use allsorts::tables::cmap::CmapSubtable;
+use allsorts::tables::{OffsetTableFontProvider, OpenTypeFont};
+use allsorts::Font;
 
 use tinyvec::tiny_vec;
 
 // Variant of `bin/shape::map_glyph`
+#[allow(dead_code)]
 pub fn map_glyph(
     cmap_subtable: &CmapSubtable,
     ch: char,
@@ -27,14 +35,60 @@ pub fn map_glyph(
 }
 
 // Copy of `bin/shape::make_glyph`
+#[allow(dead_code)]
 pub fn make_glyph(ch: char, glyph_index: u16) -> RawGlyph<()> {
     RawGlyph {
         unicodes: tiny_vec![[char; 1] => ch],
         glyph_index,
         liga_component_pos: 0,
         glyph_origin: GlyphOrigin::Char(ch),
+        cluster: 0,
         flags: RawGlyphFlags::empty(),
         extra_data: (),
         variation: None,
+        feature_mask: None,
     }
 }
+
+/// Call `f` with the font read from a path relative to CARGO_MANIFEST_DIR
+#[allow(dead_code)]
+pub fn with_font<T>(path: &str, f: impl FnOnce(&mut Font<OffsetTableFontProvider<'_>>) -> T) -> T {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    let font_contents = std::fs::read(path).expect("error reading file contents");
+    let opentype_file = ReadScope::new(&font_contents)
+        .read::<OpenTypeFont>()
+        .unwrap();
+    let font_table_provider = opentype_file
+        .table_provider(0)
+        .expect("Error getting font file");
+    let mut font = Font::new(font_table_provider).expect("Error getting font data");
+    f(&mut font)
+}
+
+/// Shape `text` with the font at `path` and the default features
+#[allow(dead_code)]
+pub fn shape(path: &str, script_tag: u32, text: &str) -> Vec<Info> {
+    let features = Features::Mask(FeatureMask::default());
+    shape_with_features(path, script_tag, text, &features, true)
+}
+
+/// Shape `text` with the font at `path` and `features`
+#[allow(dead_code)]
+pub fn shape_with_features(
+    path: &str,
+    script_tag: u32,
+    text: &str,
+    features: &Features,
+    kerning: bool,
+) -> Vec<Info> {
+    with_font(path, |font| {
+        let glyphs = font.map_glyphs(text, script_tag, MatchingPresentation::NotRequired);
+        font.shape(glyphs, script_tag, None, features, None, kerning)
+            .expect("error shaping text")
+    })
+}
+
+#[allow(dead_code)]
+pub fn glyph_indices(infos: &[Info]) -> Vec<u16> {
+    infos.iter().map(|info| info.glyph.glyph_index).collect()
+}
@@ -0,0 +1,399 @@
+mod common;
+
+#[cfg(test)]
+mod subset_tests {
+    use crate::common;
+
+    use allsorts::binary::read::ReadScope;
+    use allsorts::cff::cff2::CFF2;
+    use allsorts::font::MatchingPresentation;
+    use allsorts::gpos::{Info, Placement};
+    use allsorts::gsub::{FeatureMask, Features};
+    use allsorts::layout::Anchor;
+    use allsorts::subset::{subset, subset_chars};
+    use allsorts::tables::hdmx::HdmxTable;
+    use allsorts::tables::kern::{KernData, KernTable};
+    use allsorts::tables::os2::Os2;
+    use allsorts::tables::variable_fonts::fvar::FvarTable;
+    use allsorts::tables::{Fixed, FontTableProvider, OpenTypeFont};
+    use allsorts::tag;
+    use allsorts::{variations, Font};
+
+    // `LayoutSubsetTest.ttf` has an "fi" ligature in `liga`, small capitals for "a" and "x" in
+    // `smcp`, an alternate "c" before "b" or "x" in `calt`, kerning for "ab", "xb", and "yy",
+    // and an acute accent that attaches to "a", "a.sc", and "x".
+    const FONT: &str = "tests/fonts/subset/LayoutSubsetTest.ttf";
+
+    const A: u16 = 4;
+    const B: u16 = 5;
+    const F: u16 = 6;
+    const I: u16 = 7;
+    const ACUTECOMB: u16 = 10;
+    const C: u16 = 11;
+
+    // The ids of the glyphs in the subset of `A`, `B`, `F`, `I`, `ACUTECOMB`, and `C`, with the
+    // glyphs that `GSUB` can substitute for them after them
+    const NEW_A: u16 = 1;
+    const NEW_B: u16 = 2;
+    const NEW_F: u16 = 3;
+    const NEW_I: u16 = 4;
+    const NEW_ACUTECOMB: u16 = 5;
+    const NEW_C: u16 = 6;
+    const NEW_F_I: u16 = 7;
+    const NEW_A_SC: u16 = 8;
+    const NEW_C_ALT: u16 = 9;
+
+    #[test]
+    fn subset_includes_gsub_closure() {
+        let font_data = subset_font(&[0, A, B, F, I, ACUTECOMB, C]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        let font = Font::new(provider).unwrap();
+        assert_eq!(font.num_glyphs(), 10);
+    }
+
+    #[test]
+    fn subset_keeps_layout_tables() {
+        let font_data = subset_font(&[0, A, B, F, I, ACUTECOMB, C]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(provider.has_table(tag::GDEF));
+        assert!(provider.has_table(tag::GSUB));
+        assert!(provider.has_table(tag::GPOS));
+        // The tables are subset, not copied
+        let original_data = common::read_fixture(FONT);
+        let original_file = ReadScope::new(&original_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let original = original_file.table_provider(0).unwrap();
+        for &table in &[tag::GDEF, tag::GSUB, tag::GPOS] {
+            let subset_table = provider.read_table_data(table).unwrap();
+            let original_table = original.read_table_data(table).unwrap();
+            assert_ne!(subset_table, original_table);
+        }
+    }
+
+    #[test]
+    fn subset_substitutions() {
+        let font_data = subset_font(&[0, A, B, F, I, ACUTECOMB, C]);
+        let infos = shape(&font_data, "fi cb c", FeatureMask::default());
+        assert_eq!(
+            glyph_ids(&infos),
+            vec![NEW_F_I, 0, NEW_C_ALT, NEW_B, 0, NEW_C]
+        );
+
+        let smcp = FeatureMask::default() | FeatureMask::SMCP;
+        let infos = shape(&font_data, "ab", smcp);
+        assert_eq!(glyph_ids(&infos), vec![NEW_A_SC, NEW_B]);
+
+        let no_liga = FeatureMask::default() - FeatureMask::LIGA;
+        let infos = shape(&font_data, "fi", no_liga);
+        assert_eq!(glyph_ids(&infos), vec![NEW_F, NEW_I]);
+    }
+
+    #[test]
+    fn subset_positioning() {
+        let font_data = subset_font(&[0, A, B, F, I, ACUTECOMB, C]);
+        let infos = shape(&font_data, "ab", FeatureMask::default());
+        assert_eq!(glyph_ids(&infos), vec![NEW_A, NEW_B]);
+        assert_eq!(infos[0].kerning, -50);
+
+        let infos = shape(&font_data, "a\u{301}", FeatureMask::default());
+        assert_eq!(glyph_ids(&infos), vec![NEW_A, NEW_ACUTECOMB]);
+        assert_eq!(
+            infos[1].placement,
+            Placement::MarkAnchor(0, Anchor { x: 250, y: 500 }, Anchor { x: -150, y: 500 })
+        );
+    }
+
+    #[test]
+    fn subset_excludes_features_without_flags() {
+        // The alternates of "g" in `aalt`, `ss01`, and `cv01` aren't retained, since shaping with
+        // a `FeatureMask` can't apply those features
+        let font_data = subset_fixture("tests/fonts/alternates/AlternatesTest.ttf", &[0, 3]);
+        assert_eq!(num_glyphs(&font_data), 2);
+    }
+
+    #[test]
+    fn subset_removes_unused_tables() {
+        // The only substitution of "y" needs "x", so the `GSUB` table is left out, but the
+        // kerning of "yy" is kept
+        let font_data = subset_font(&[0, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(provider.has_table(tag::GDEF));
+        assert!(!provider.has_table(tag::GSUB));
+        assert!(provider.has_table(tag::GPOS));
+    }
+
+    #[test]
+    fn subset_chars_includes_enabled_features() {
+        // "fi" is ligated by default, but "a" is only substituted by `smcp`
+        let font_data = subset_chars_font(&['a', 'b', 'f', 'i'], FeatureMask::default());
+        assert_eq!(num_glyphs(&font_data), 6);
+        let infos = shape(&font_data, "fiab", FeatureMask::default());
+        assert_eq!(glyph_ids(&infos), vec![5, 1, 2]);
+
+        let smcp = FeatureMask::default() | FeatureMask::SMCP;
+        let font_data = subset_chars_font(&['a', 'b', 'f', 'i'], smcp);
+        assert_eq!(num_glyphs(&font_data), 7);
+        let infos = shape(&font_data, "ab", smcp);
+        assert_eq!(glyph_ids(&infos), vec![6, 2]);
+    }
+
+    #[test]
+    fn subset_chars_excludes_disabled_features() {
+        let no_liga = FeatureMask::default() - FeatureMask::LIGA;
+        let font_data = subset_chars_font(&['f', 'i', 'z'], no_liga);
+        assert_eq!(num_glyphs(&font_data), 3);
+        let infos = shape(&font_data, "fi", no_liga);
+        assert_eq!(glyph_ids(&infos), vec![1, 2]);
+    }
+
+    #[test]
+    fn subset_keeps_vertical_metrics() {
+        let font_data = subset_fixture("tests/fonts/vertical/VerticalTest.ttf", &[0, 4]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(provider.has_table(tag::VHEA));
+        assert!(provider.has_table(tag::VMTX));
+        let mut font = Font::new(provider).unwrap();
+        assert_eq!(font.vertical_advance(1), Some(1000));
+        assert_eq!(font.vertical_origin(1), 880);
+    }
+
+    #[test]
+    fn subset_keeps_vorg() {
+        let font_data = subset_fixture("tests/fonts/vertical/VerticalVorgTest.ttf", &[0, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(provider.has_table(tag::VORG));
+        let mut font = Font::new(provider).unwrap();
+        assert_eq!(font.vertical_origin(1), 850);
+    }
+
+    #[test]
+    fn subset_updates_os2_char_ranges() {
+        // Glyphs 3 and 4 are U+4E00 and U+4E8C
+        let font_data = subset_fixture("tests/fonts/vertical/VerticalTest.ttf", &[0, 3, 4]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        let os2_data = provider.read_table_data(tag::OS_2).unwrap();
+        let os2 = ReadScope::new(&os2_data)
+            .read_dep::<Os2>(os2_data.len())
+            .unwrap();
+        assert_eq!(os2.us_first_char_index, 0x4E00);
+        assert_eq!(os2.us_last_char_index, 0x4E8C);
+        // Only the CJK Unified Ideographs bit
+        assert_eq!(os2.ul_unicode_range1, 0);
+        assert_eq!(os2.ul_unicode_range2, 1 << (59 - 32));
+        assert_eq!(os2.ul_unicode_range3, 0);
+        assert_eq!(os2.ul_unicode_range4, 0);
+    }
+
+    #[test]
+    fn subset_keeps_format_0_kerning() {
+        // "A", "V", "o", and "W"
+        let font_data = subset_fixture("tests/fonts/kern/KernMicrosoftTest.ttf", &[0, 2, 3, 5, 6]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        let kern_data = provider.read_table_data(tag::KERN).unwrap();
+        let kern = ReadScope::new(&kern_data).read::<KernTable<'_>>().unwrap();
+        // The format 2 subtable is dropped
+        assert_eq!(kern.subtables.len(), 1);
+        match &kern.subtables[0].data {
+            KernData::Format0(pairs) => {
+                let pairs = pairs
+                    .iter()
+                    .map(|pair| (pair.left, pair.right, pair.value))
+                    .collect::<Vec<_>>();
+                assert_eq!(pairs, vec![(1, 2, -80)]);
+            }
+            _ => panic!("expected a format 0 subtable"),
+        }
+    }
+
+    #[test]
+    fn subset_removes_unsupported_kerning() {
+        // The `kern` table only has format 1 and 3 subtables
+        let font_data = subset_fixture("tests/fonts/kern/KernAppleTest.ttf", &[0, 2, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(!provider.has_table(tag::KERN));
+    }
+
+    #[test]
+    fn subset_keeps_gasp_and_hdmx() {
+        let font_data = subset_fixture("tests/fonts/gurmukhi/Saab.ttf", &[0, 1, 2, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        assert!(provider.has_table(tag::GASP));
+        let hdmx_data = provider.read_table_data(tag::HDMX).unwrap();
+        let hdmx = ReadScope::new(&hdmx_data)
+            .read_dep::<HdmxTable<'_>>(4)
+            .unwrap();
+        assert!(!hdmx.records.is_empty());
+        assert!(hdmx.records.iter().all(|record| record.widths.len() == 4));
+    }
+
+    #[test]
+    fn subset_keeps_truetype_variations() {
+        let path = "tests/fonts/opentype/NotoSans-VF.abc.ttf";
+        let font_data = subset_fixture(path, &[0, 2, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        for table in [
+            tag::FVAR,
+            tag::GVAR,
+            tag::HVAR,
+            tag::AVAR,
+            tag::STAT,
+            tag::MVAR,
+        ] {
+            assert!(provider.has_table(table), "{}", tag::DisplayTag(table));
+        }
+        assert_variable_advances_match(path, &provider, &[(0, 0), (2, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn subset_keeps_cff2_variations() {
+        let path = "tests/fonts/opentype/cff2/SourceSansVariable-Roman.abc.otf";
+        let font_data = subset_fixture(path, &[0, 2, 3]);
+        let font_file = ReadScope::new(&font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        for table in [
+            tag::CFF2,
+            tag::FVAR,
+            tag::HVAR,
+            tag::AVAR,
+            tag::STAT,
+            tag::MVAR,
+        ] {
+            assert!(provider.has_table(table), "{}", tag::DisplayTag(table));
+        }
+        let cff2_data = provider.read_table_data(tag::CFF2).unwrap();
+        let cff2 = ReadScope::new(&cff2_data).read::<CFF2<'_>>().unwrap();
+        assert_eq!(cff2.char_strings_index.len(), 3);
+        assert!(cff2.vstore.is_some());
+        assert_variable_advances_match(path, &provider, &[(0, 0), (2, 1), (3, 2)]);
+    }
+
+    // Instance the original font and the subset at the maximum of each axis and check that the
+    // advances of the glyphs in the subset match their originals.
+    fn assert_variable_advances_match(
+        path: &str,
+        subset_provider: &impl FontTableProvider,
+        glyph_ids: &[(u16, u16)],
+    ) {
+        let font_contents = common::read_fixture(path);
+        let font_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        let fvar_data = provider.read_table_data(tag::FVAR).unwrap();
+        let fvar = ReadScope::new(&fvar_data).read::<FvarTable<'_>>().unwrap();
+        let user_tuple = fvar
+            .axes()
+            .map(|axis| axis.max_value)
+            .collect::<Vec<Fixed>>();
+
+        let (instance_data, _) = variations::instance(&provider, &user_tuple).unwrap();
+        let instance_file = ReadScope::new(&instance_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let mut instance = Font::new(instance_file.table_provider(0).unwrap()).unwrap();
+
+        let (subset_instance_data, _) = variations::instance(subset_provider, &user_tuple).unwrap();
+        let subset_instance_file = ReadScope::new(&subset_instance_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let mut subset_instance =
+            Font::new(subset_instance_file.table_provider(0).unwrap()).unwrap();
+
+        for &(old_id, new_id) in glyph_ids {
+            assert_eq!(
+                subset_instance.horizontal_advance(new_id),
+                instance.horizontal_advance(old_id)
+            );
+        }
+    }
+
+    fn subset_fixture(path: &str, glyph_ids: &[u16]) -> Vec<u8> {
+        let font_contents = common::read_fixture(path);
+        let opentype_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        subset(&opentype_file.table_provider(0).unwrap(), glyph_ids).expect("error subsetting")
+    }
+
+    fn subset_chars_font(chars: &[char], features: FeatureMask) -> Vec<u8> {
+        let font_contents = common::read_fixture(FONT);
+        let opentype_file = ReadScope::new(&font_contents)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        subset_chars(
+            &opentype_file.table_provider(0).unwrap(),
+            chars,
+            &Features::Mask(features),
+        )
+        .expect("error subsetting")
+    }
+
+    fn num_glyphs(font_data: &[u8]) -> u16 {
+        let font_file = ReadScope::new(font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let provider = font_file.table_provider(0).unwrap();
+        Font::new(provider).unwrap().num_glyphs()
+    }
+
+    fn subset_font(glyph_ids: &[u16]) -> Vec<u8> {
+        subset_fixture(FONT, glyph_ids)
+    }
+
+    fn glyph_ids(infos: &[Info]) -> Vec<u16> {
+        infos.iter().map(|info| info.glyph.glyph_index).collect()
+    }
+
+    fn shape(font_data: &[u8], text: &str, features: FeatureMask) -> Vec<Info> {
+        let opentype_file = ReadScope::new(font_data)
+            .read::<OpenTypeFont<'_>>()
+            .unwrap();
+        let font_table_provider = opentype_file
+            .table_provider(0)
+            .expect("Error getting font file");
+        let mut font = Font::new(font_table_provider).expect("Error getting font data");
+        let glyphs = font.map_glyphs(text, tag::LATN, MatchingPresentation::NotRequired);
+        font.shape(
+            glyphs,
+            tag::LATN,
+            None,
+            &Features::Mask(features),
+            None,
+            true,
+        )
+        .expect("error shaping text")
+    }
+}
@@ -206,4 +206,57 @@ mod tests {
             );
         }
     }
+    mod pua_fallback {
+        use super::*;
+
+        use allsorts::font::MatchingPresentation;
+
+        // `ThaiPuaFallbackTest.ttf` has no `GPOS` table. It maps KO KAI, YO YING, DO CHADA,
+        // PO PLA, SARA I, SARA U, and MAI EK, and the PUA variants: MAI EK shifted down
+        // (Windows, glyph 9) and down-left (Mac, glyph 10), SARA I shifted left (glyph 11), YO
+        // YING without its descender (glyph 12), and SARA U shifted down (glyph 13).
+        const FONT: &str = "thai/ThaiPuaFallbackTest.ttf";
+
+        #[test]
+        fn test_shift_down() {
+            assert_eq!(shape("\u{0E01}\u{0E48}"), vec![2, 9]);
+            assert_eq!(shape("\u{0E01}\u{0E34}\u{0E48}"), vec![2, 6, 8]);
+            assert_eq!(shape("\u{0E0E}\u{0E38}"), vec![4, 13]);
+        }
+
+        #[test]
+        fn test_shift_left() {
+            assert_eq!(shape("\u{0E1B}\u{0E34}"), vec![5, 11]);
+            // Only the Mac PUA character is mapped
+            assert_eq!(shape("\u{0E1B}\u{0E48}"), vec![5, 10]);
+        }
+
+        #[test]
+        fn test_remove_descender() {
+            assert_eq!(shape("\u{0E0D}\u{0E38}"), vec![12, 7]);
+        }
+
+        fn shape(text: &str) -> Vec<u16> {
+            let font_buffer = common::read_fixture(Path::new("tests/fonts").join(FONT));
+            let opentype_file = ReadScope::new(&font_buffer)
+                .read::<OpenTypeFont<'_>>()
+                .unwrap();
+            let font_table_provider = opentype_file
+                .table_provider(0)
+                .expect("error reading font file");
+            let mut font = Font::new(font_table_provider).expect("error reading font data");
+            let glyphs = font.map_glyphs(text, tag::THAI, MatchingPresentation::NotRequired);
+            let infos = font
+                .shape(
+                    glyphs,
+                    tag::THAI,
+                    None,
+                    &Features::Mask(FeatureMask::default()),
+                    None,
+                    true,
+                )
+                .expect("error shaping text");
+            infos.iter().map(|info| info.glyph.glyph_index).collect()
+        }
+    }
 }
@@ -0,0 +1,108 @@
+mod shape;
+
+#[cfg(test)]
+mod tibetan_tests {
+    use crate::shape;
+
+    use allsorts::gpos::Placement;
+    use allsorts::layout::Anchor;
+    use allsorts::tag;
+
+    // `TibetanTest.ttf` maps KA, GA, RA, and SA to glyphs 3–6, subjoined KA, GA, YA, and RA to
+    // glyphs 7–10, and the vowel signs AA, I, U, E, and O to glyphs 11–15. It has ligatures for
+    // KA + subjoined YA (23), RA head (22) + subjoined GA (25), and I + anusvara (24).
+    const FONT: &str = "tests/fonts/tibetan/TibetanTest.ttf";
+
+    #[test]
+    fn stacks() {
+        test(vec![
+            ("\u{0F40}", vec![3]),
+            ("\u{0F40}\u{0FB1}", vec![23]),
+            // RA takes its head form above a subjoined consonant
+            ("\u{0F62}\u{0F90}", vec![22, 7]),
+            ("\u{0F62}\u{0F92}", vec![25]),
+            ("\u{0F62}\u{0F72}", vec![5, 12]),
+            ("\u{0F66}\u{0F90}\u{0FB1}", vec![6, 7, 9]),
+        ])
+    }
+
+    #[test]
+    fn vowels() {
+        test(vec![
+            ("\u{0F40}\u{0F72}\u{0F7E}", vec![3, 24]),
+            // Discouraged precomposed vowel signs are decomposed
+            ("\u{0F40}\u{0F73}", vec![3, 11, 12]),
+            ("\u{0F40}\u{0F75}", vec![3, 11, 13]),
+            // Vowels are ordered AA, U, then the vowels above
+            ("\u{0F40}\u{0F72}\u{0F74}", vec![3, 13, 12]),
+            ("\u{0F40}\u{0F74}\u{0F71}", vec![3, 11, 13]),
+            ("\u{0F40}\u{0F7C}\u{0F71}", vec![3, 11, 15]),
+        ])
+    }
+
+    #[test]
+    fn syllables() {
+        test(vec![
+            ("\u{0F40}\u{0F0B}\u{0F42}", vec![3, 17, 4]),
+            ("\u{0F40}\u{0F39}\u{0F84}", vec![3, 19, 18]),
+            ("\u{0F21}\u{0F18}", vec![20, 21]),
+            ("\u{0F40} \u{0F42}\u{0F7A}", vec![3, 1, 4, 14]),
+        ])
+    }
+
+    #[test]
+    fn broken_syllables() {
+        // A dotted circle is inserted before marks that have no base
+        test(vec![
+            ("\u{0F72}", vec![2, 12]),
+            ("\u{0F90}", vec![2, 7]),
+            ("\u{0F18}", vec![2, 21]),
+            ("\u{0F40}\u{0F0B}\u{0F74}", vec![3, 17, 2, 13]),
+            ("\u{25CC}\u{0F72}", vec![2, 12]),
+        ])
+    }
+
+    #[test]
+    fn clusters() {
+        let infos = shape::shape(FONT, tag::TIBT, "\u{0F72}\u{0F40}\u{0FB1}\u{0F0B}");
+        let clusters = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![(2, 0), (12, 0), (23, 3), (17, 9)]);
+    }
+
+    #[test]
+    fn mark_positioning() {
+        let infos = shape::shape(FONT, tag::TIBT, "\u{0F40}\u{0F90}\u{0F7A}");
+        let placements = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.placement))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placements,
+            vec![
+                (3, Placement::None),
+                (
+                    7,
+                    Placement::MarkAnchor(0, Anchor { x: 300, y: -50 }, Anchor { x: -150, y: 650 })
+                ),
+                (
+                    14,
+                    Placement::MarkAnchor(0, Anchor { x: 300, y: 600 }, Anchor { x: -150, y: 550 })
+                ),
+            ]
+        );
+    }
+
+    fn test(test_cases: Vec<(&str, Vec<u16>)>) {
+        for (text, expected) in test_cases {
+            assert_eq!(
+                shape::glyph_indices(&shape::shape(FONT, tag::TIBT, text)),
+                expected,
+                "{:?}",
+                text
+            );
+        }
+    }
+}
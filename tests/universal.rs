@@ -0,0 +1,106 @@
+mod shape;
+
+#[cfg(test)]
+mod universal_tests {
+    use crate::shape;
+
+    use allsorts::tag;
+
+    // `UniversalTest.ttf` maps the Tirhuta letters KA, KHA, YA, and RA to glyphs 3–6, VIRAMA to
+    // glyph 7, and the vowel signs AA, I, and U to glyphs 8–10. It has `rphf`, `pref`, and `half`
+    // ligatures for Tirhuta, producing glyphs 15, 17, and 16 respectively. The Tai Tham letter
+    // HIGH KA, SAKOT, and the vowel sign E are glyphs 12–14, with a `blwf` ligature of SAKOT and
+    // HIGH KA producing glyph 18.
+    const FONT: &str = "tests/fonts/universal/UniversalTest.ttf";
+
+    #[test]
+    fn pre_base_vowels() {
+        test(
+            tag::TIRH,
+            vec![
+                ("\u{1148F}", vec![3]),
+                ("\u{1148F}\u{114B1}", vec![9, 3]),
+                ("\u{1148F}\u{114B0}", vec![3, 8]),
+                ("\u{1148F}\u{114C2}\u{11490}\u{114B1}", vec![9, 16, 4]),
+            ],
+        )
+    }
+
+    #[test]
+    fn repha() {
+        test(
+            tag::TIRH,
+            vec![
+                ("\u{114A9}\u{114C2}\u{1148F}", vec![3, 15]),
+                ("\u{114A9}\u{114C2}\u{1148F}\u{114B0}", vec![3, 15, 8]),
+                ("\u{114A9}\u{114C2}\u{1148F}\u{114B1}", vec![9, 3, 15]),
+                (
+                    "\u{114A9}\u{114C2}\u{1148F}\u{114C2}\u{11490}",
+                    vec![16, 4, 15],
+                ),
+                // RA and VIRAMA at the end of a word start a new cluster
+                (
+                    "\u{1148F}\u{114C2}\u{11490}\u{114A9}\u{114C2}",
+                    vec![16, 4, 15],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn pre_base_forms() {
+        // A glyph substituted by `pref` is moved before the base
+        test(
+            tag::TIRH,
+            vec![
+                ("\u{1148F}\u{114C2}\u{114A8}", vec![17, 3]),
+                ("\u{1148F}\u{114C2}\u{114A8}\u{114B0}", vec![17, 3, 8]),
+            ],
+        )
+    }
+
+    #[test]
+    fn sakot() {
+        test(
+            tag::LANA,
+            vec![
+                ("\u{1A20}\u{1A60}\u{1A20}", vec![12, 18]),
+                ("\u{1A20}\u{1A60}\u{1A20}\u{1A6E}", vec![14, 12, 18]),
+            ],
+        )
+    }
+
+    #[test]
+    fn broken_clusters() {
+        // A dotted circle is inserted before marks that have no base
+        test(
+            tag::TIRH,
+            vec![
+                ("\u{114B3}", vec![2, 10]),
+                ("\u{114B1}", vec![9, 2]),
+                ("\u{1148F}\u{114B0}\u{114B3}", vec![3, 8, 2, 10]),
+            ],
+        )
+    }
+
+    #[test]
+    fn clusters() {
+        let infos = shape::shape(
+            FONT,
+            tag::TIRH,
+            "\u{114A9}\u{114C2}\u{1148F}\u{114B1} \u{1148F}",
+        );
+        let clusters = infos
+            .iter()
+            .map(|info| (info.glyph.glyph_index, info.glyph.cluster))
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, vec![(9, 0), (3, 0), (15, 0), (1, 16), (3, 17)]);
+    }
+
+    fn test(script_tag: u32, test_cases: Vec<(&str, Vec<u16>)>) {
+        for (text, expected) in test_cases {
+            let infos = shape::shape(FONT, script_tag, text);
+            assert_eq!(shape::glyph_indices(&infos), expected, "{:?}", text);
+        }
+    }
+}